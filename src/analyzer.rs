@@ -2,19 +2,25 @@ use anyhow::Result;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use rayon::prelude::*;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use walkdir::WalkDir;
 
+use crate::config::ExcludeRules;
 use crate::contribution_cache::{
     ContributionCache, ContributionStrategy, MultiSessionContribution, PathHash,
     RemovedContribution, SingleMessageContribution, SingleSessionContribution,
 };
 use crate::types::{
-    AgenticCodingToolStats, AnalyzerStatsView, ConversationMessage, SharedAnalyzerView,
+    AgenticCodingToolStats, AnalyzerStatsView, AnalyzerViewBuilder, CompactDate,
+    ConversationMessage, SharedAnalyzerView,
 };
 
+/// Sources parsed per batch at startup for analyzers that support it; only
+/// one batch of messages per analyzer is resident at a time.
+const PARSE_BATCH_SOURCES: usize = 256;
+
 /// VSCode GUI forks that might have extensions installed
 const VSCODE_GUI_FORKS: &[&str] = &[
     "Code",
@@ -97,6 +103,41 @@ pub fn get_vscode_extension_tasks_dirs(extension_id: &str) -> Vec<PathBuf> {
     dirs
 }
 
+/// Glob patterns for `target_filename` in every task directory of a VSCode
+/// extension, covering the same locations as [`get_vscode_extension_tasks_dirs`].
+pub fn vscode_extension_glob_patterns(extension_id: &str, target_filename: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+
+    if let Some(home_dir) = dirs::home_dir() {
+        let home_str = home_dir.to_string_lossy();
+        for fork in VSCODE_GUI_FORKS {
+            patterns.push(format!(
+                "{home_str}/.config/{fork}/User/globalStorage/{extension_id}/tasks/*/{target_filename}"
+            ));
+        }
+        for fork in VSCODE_CLI_FORKS {
+            patterns.push(format!(
+                "{home_str}/.{fork}/data/User/globalStorage/{extension_id}/tasks/*/{target_filename}"
+            ));
+        }
+        for fork in VSCODE_GUI_FORKS {
+            patterns.push(format!(
+                "{home_str}/Library/Application Support/{fork}/User/globalStorage/{extension_id}/tasks/*/{target_filename}"
+            ));
+        }
+    }
+
+    if let Ok(appdata) = std::env::var("APPDATA") {
+        for fork in VSCODE_GUI_FORKS {
+            patterns.push(format!(
+                "{appdata}\\{fork}\\User\\globalStorage\\{extension_id}\\tasks\\*\\{target_filename}"
+            ));
+        }
+    }
+
+    patterns
+}
+
 fn walk_vscode_extension_tasks(extension_id: &str) -> impl Iterator<Item = WalkDir> {
     get_vscode_extension_tasks_dirs(extension_id)
         .into_iter()
@@ -174,7 +215,8 @@ pub trait Analyzer: Send + Sync {
 
     /// Parse multiple data sources in parallel, returning messages grouped by source path.
     ///
-    /// Default: parses all sources in parallel using rayon.
+    /// Default: parses all sources through [`crate::parse_io::parse_each`],
+    /// in parallel unless `[parsing]` asks for fewer concurrent reads.
     /// Override for shared context loading (e.g., OpenCode loads session data once).
     /// Must be called within a rayon threadpool context for parallelism.
     ///
@@ -183,21 +225,25 @@ pub trait Analyzer: Send + Sync {
         &self,
         sources: &[DataSource],
     ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
-        sources
-            .par_iter()
-            .filter_map(|source| match self.parse_source(source) {
-                Ok(msgs) => Some((source.path.clone(), msgs)),
-                Err(e) => {
-                    eprintln!(
+        crate::parse_io::parse_each(sources, |source| match self.parse_source(source) {
+            Ok(msgs) => Some((source.path.clone(), msgs)),
+            Err(e) => {
+                crate::diagnostics::parse_warning(
+                    self.display_name(),
+                    &source.path,
+                    format!(
                         "Failed to parse {} source {:?}: {}",
                         self.display_name(),
                         source.path,
                         e
-                    );
-                    None
-                }
-            })
-            .collect()
+                    ),
+                );
+                None
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect()
     }
 
     /// Parse multiple data sources in parallel and deduplicate.
@@ -235,7 +281,7 @@ pub trait Analyzer: Send + Sync {
 
     /// Returns the contribution caching strategy for this analyzer.
     /// - `SingleMessage`: 1 file = 1 message (~40 bytes/file) - e.g., OpenCode
-    /// - `SingleSession`: 1 file = 1 session (~72 bytes/file) - e.g., Claude Code, Cline
+    /// - `SingleSession`: 1 file = 1 session (~200 bytes/file) - e.g., Claude Code, Cline
     /// - `MultiSession`: 1 file = many sessions (~100+ bytes/file) - e.g., Piebald
     fn contribution_strategy(&self) -> ContributionStrategy;
 
@@ -250,6 +296,18 @@ pub trait Analyzer: Send + Sync {
         false
     }
 
+    /// Whether sources can be parsed in separate batches with the same result,
+    /// letting startup fold each batch into aggregates and drop its messages
+    /// before parsing the next. Needs every file to hold whole sessions and no
+    /// cross-source parsing logic. Claude Code's resumed-session merging and
+    /// history store need every transcript at once, so it isn't batched and
+    /// its peak memory still grows with its logs; batching it after the merge
+    /// is not done yet.
+    fn supports_batched_parsing(&self) -> bool {
+        self.contribution_strategy() == ContributionStrategy::SingleSession
+            && !self.requires_full_reload_for_source_change()
+    }
+
     /// Get stats with pre-discovered sources (avoids double discovery).
     /// Default implementation parses sources in parallel via `parse_sources_parallel()`.
     /// Override for analyzers with complex cross-file logic (e.g., claude_code).
@@ -279,15 +337,189 @@ pub trait Analyzer: Send + Sync {
     }
 }
 
+/// Applies the `[exclude] paths` rules to an analyzer: excluded sources are
+/// never discovered, so they aren't parsed, cached, shown or uploaded, and
+/// the watcher ignores changes to them.
+///
+/// Also adds the messages restored by `splitrail import --raw` as a last
+/// source, so they're only counted where the tool's own logs are gone.
+struct Excluding<A> {
+    inner: A,
+}
+
+impl<A: Analyzer> Excluding<A> {
+    /// The tool's own sources, and the restored messages' path, when
+    /// `sources` includes them.
+    fn split_restored(&self, sources: &[DataSource]) -> Option<(Vec<DataSource>, PathBuf)> {
+        let restored = crate::backup::restored_source(self.display_name())?;
+        if !sources.iter().any(|source| source.path == restored.path) {
+            return None;
+        }
+        let own = sources
+            .iter()
+            .filter(|source| source.path != restored.path)
+            .cloned()
+            .collect();
+        Some((own, restored.path))
+    }
+
+    fn read_restored(&self, path: &Path) -> Vec<ConversationMessage> {
+        crate::backup::read_messages(path).unwrap_or_else(|e| {
+            crate::diagnostics::parse_warning(
+                self.display_name(),
+                path,
+                format!("Failed to read restored messages {}: {e:#}", path.display()),
+            );
+            Vec::new()
+        })
+    }
+
+    /// Append the restored messages the tool's own logs no longer have.
+    fn add_restored(&self, messages: &mut Vec<ConversationMessage>, path: &Path) -> bool {
+        let seen: HashSet<String> = messages
+            .iter()
+            .map(|message| message.global_hash.clone())
+            .collect();
+        let before = messages.len();
+        messages.extend(
+            self.read_restored(path)
+                .into_iter()
+                .filter(|message| !seen.contains(&message.global_hash)),
+        );
+        messages.len() > before
+    }
+}
+
+#[async_trait]
+impl<A: Analyzer> Analyzer for Excluding<A> {
+    fn display_name(&self) -> &'static str {
+        self.inner.display_name()
+    }
+
+    fn get_data_glob_patterns(&self) -> Vec<String> {
+        self.inner.get_data_glob_patterns()
+    }
+
+    fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+        let mut sources = self.inner.discover_data_sources()?;
+        let rules = ExcludeRules::current();
+        if !rules.is_empty() {
+            sources.retain(|source| !rules.excludes(&source.path));
+        }
+        // Last, so that batched loads see the tool's own copies first.
+        sources.extend(crate::backup::restored_source(self.display_name()));
+        Ok(sources)
+    }
+
+    fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+        if crate::backup::restored_source(self.display_name())
+            .is_some_and(|restored| restored.path == source.path)
+        {
+            return crate::backup::read_messages(&source.path);
+        }
+        self.inner.parse_source(source)
+    }
+
+    fn parse_sources_parallel_with_paths(
+        &self,
+        sources: &[DataSource],
+    ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
+        let Some((own, restored)) = self.split_restored(sources) else {
+            return self.inner.parse_sources_parallel_with_paths(sources);
+        };
+        let mut grouped = self.inner.parse_sources_parallel_with_paths(&own);
+        let messages = self.read_restored(&restored);
+        grouped.push((restored, messages));
+        grouped
+    }
+
+    fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
+        let Some((own, restored)) = self.split_restored(sources) else {
+            return self.inner.parse_sources_parallel(sources);
+        };
+        let mut messages = self.inner.parse_sources_parallel(&own);
+        self.add_restored(&mut messages, &restored);
+        messages
+    }
+
+    fn get_watch_directories(&self) -> Vec<PathBuf> {
+        self.inner.get_watch_directories()
+    }
+
+    fn is_valid_data_path(&self, path: &Path) -> bool {
+        self.inner.is_valid_data_path(path) && !ExcludeRules::current().excludes(path)
+    }
+
+    fn is_available(&self) -> bool {
+        if crate::backup::restored_source(self.display_name()).is_some() {
+            return true;
+        }
+        if ExcludeRules::current().is_empty() {
+            // Keep the analyzers' early-exit checks when nothing is excluded.
+            return self.inner.is_available();
+        }
+        self.discover_data_sources()
+            .is_ok_and(|sources| !sources.is_empty())
+    }
+
+    fn contribution_strategy(&self) -> ContributionStrategy {
+        self.inner.contribution_strategy()
+    }
+
+    fn remove_source_state(&self, path: &Path) -> Result<()> {
+        self.inner.remove_source_state(path)
+    }
+
+    // Restored messages are only counted where the tool's own logs lack
+    // them, which per-file contributions can't see: deleting a log would
+    // drop its messages without bringing their restored copies back.
+    fn requires_full_reload_for_source_change(&self) -> bool {
+        self.inner.requires_full_reload_for_source_change()
+            || crate::backup::restored_source(self.display_name()).is_some()
+    }
+
+    fn supports_batched_parsing(&self) -> bool {
+        self.inner.supports_batched_parsing()
+    }
+
+    fn get_stats_with_sources(&self, sources: Vec<DataSource>) -> Result<AgenticCodingToolStats> {
+        let mut stats = match self.split_restored(&sources) {
+            None => self.inner.get_stats_with_sources(sources)?,
+            Some((own, restored)) => {
+                let mut stats = self.inner.get_stats_with_sources(own)?;
+                if self.add_restored(&mut stats.messages, &restored) {
+                    stats.daily_stats = crate::utils::aggregate_by_date(&stats.messages);
+                    stats.daily_stats.retain(|date, _| date != "unknown");
+                    stats.num_conversations = stats
+                        .daily_stats
+                        .values()
+                        .map(|stats| stats.conversations as u64)
+                        .sum();
+                }
+                stats
+            }
+        };
+        crate::utils::assign_stable_session_ids(self.display_name(), &mut stats.messages);
+        Ok(stats)
+    }
+}
+
 /// Registry for managing multiple analyzers
 pub struct AnalyzerRegistry {
     analyzers: Vec<Box<dyn Analyzer>>,
     /// Unified contribution cache for incremental updates.
     /// Strategy-specific storage: SingleMessage (~40B), SingleSession (~72B), MultiSession (~100+B).
     contribution_cache: ContributionCache,
+    /// Which analyzer each cached contribution belongs to, so one analyzer's
+    /// can be dropped without touching the others'.
+    cached_paths: DashMap<String, HashSet<PathHash>>,
     /// Cached analyzer views for incremental updates.
     /// Key: analyzer display name, Value: shared view with RwLock for in-place mutation.
     analyzer_views_cache: DashMap<String, SharedAnalyzerView>,
+    /// Version of each cached view, taken from `next_view_version` whenever
+    /// the view is replaced or mutated, so listeners can tell which changed.
+    view_versions: DashMap<String, u64>,
+    next_view_version: std::sync::atomic::AtomicU64,
     /// Tracks the order in which analyzers were registered to maintain stable tab ordering.
     /// Contains display names in registration order.
     analyzer_order: parking_lot::RwLock<Vec<String>>,
@@ -309,7 +541,10 @@ impl AnalyzerRegistry {
         Self {
             analyzers: Vec::new(),
             contribution_cache: ContributionCache::new(),
+            cached_paths: DashMap::new(),
             analyzer_views_cache: DashMap::new(),
+            view_versions: DashMap::new(),
+            next_view_version: std::sync::atomic::AtomicU64::new(1),
             analyzer_order: parking_lot::RwLock::new(Vec::new()),
             dirty_files_for_upload: Arc::new(DashMap::new()),
         }
@@ -318,15 +553,31 @@ impl AnalyzerRegistry {
     /// Register an analyzer
     pub fn register<A: Analyzer + 'static>(&mut self, analyzer: A) {
         let name = analyzer.display_name().to_string();
-        self.analyzers.push(Box::new(analyzer));
+        self.analyzers.push(Box::new(Excluding { inner: analyzer }));
         // Track registration order for stable tab ordering in TUI
         self.analyzer_order.write().push(name);
     }
 
+    /// Drop analyzers before anything is loaded or watched.
+    pub fn retain(&mut self, mut keep: impl FnMut(&dyn Analyzer) -> bool) {
+        self.analyzers.retain(|analyzer| keep(analyzer.as_ref()));
+        let names = self.display_names();
+        self.analyzer_order
+            .write()
+            .retain(|name| names.contains(&name.as_str()));
+    }
+
     /// Invalidate all caches (file contributions and analyzer views)
     pub fn invalidate_all_caches(&self) {
         self.contribution_cache.clear();
+        self.cached_paths.clear();
         self.analyzer_views_cache.clear();
+        self.view_versions.clear();
+    }
+
+    /// Display names of every registered analyzer, available or not.
+    pub fn display_names(&self) -> Vec<&'static str> {
+        self.analyzers.iter().map(|a| a.display_name()).collect()
     }
 
     /// Get available analyzers (fast check, no source discovery).
@@ -355,6 +606,75 @@ impl AnalyzerRegistry {
             .collect()
     }
 
+    /// Parse one session's messages on demand, oldest first.
+    ///
+    /// Views only keep per-session aggregates, so the TUI's session detail pane
+    /// re-reads the session's sources when it needs individual messages. The
+    /// contribution cache says which files hold the session; every source is
+    /// read only when nothing about it is cached yet.
+    pub fn load_session_messages(
+        &self,
+        analyzer_name: &str,
+        session_id: &str,
+    ) -> Result<Vec<ConversationMessage>> {
+        let analyzer = self
+            .get_analyzer_by_display_name(analyzer_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown analyzer: {analyzer_name}"))?;
+        let owning: Vec<DataSource> = analyzer
+            .discover_data_sources()?
+            .into_iter()
+            .filter(|source| {
+                self.contribution_cache
+                    .covers_session(&PathHash::new(&source.path), session_id)
+            })
+            .collect();
+        let messages = if owning.is_empty() {
+            self.load_analyzer_messages(analyzer_name)?
+        } else {
+            analyzer.parse_sources_parallel(&owning)
+        };
+        let mut messages: Vec<ConversationMessage> = messages
+            .into_iter()
+            .filter(|msg| msg.conversation_hash == session_id)
+            .collect();
+        messages.sort_by_key(|msg| msg.date);
+        Ok(messages)
+    }
+
+    /// Parse every message of one analyzer on demand, in no particular order.
+    /// Nothing is cached, so each call reads every source again.
+    pub fn load_analyzer_messages(&self, analyzer_name: &str) -> Result<Vec<ConversationMessage>> {
+        let analyzer = self
+            .get_analyzer_by_display_name(analyzer_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown analyzer: {analyzer_name}"))?;
+        let sources = analyzer.discover_data_sources()?;
+        let pool = rayon::ThreadPoolBuilder::new().build()?;
+        Ok(pool.install(|| analyzer.parse_sources_parallel(&sources)))
+    }
+
+    /// Like [`Self::load_analyzer_messages`], but only reads files written
+    /// since `since`.
+    pub fn load_recent_messages(
+        &self,
+        analyzer_name: &str,
+        since: std::time::SystemTime,
+    ) -> Result<Vec<ConversationMessage>> {
+        let analyzer = self
+            .get_analyzer_by_display_name(analyzer_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown analyzer: {analyzer_name}"))?;
+        let sources: Vec<DataSource> = analyzer
+            .discover_data_sources()?
+            .into_iter()
+            .filter(|source| {
+                std::fs::metadata(&source.path)
+                    .and_then(|meta| meta.modified())
+                    .is_ok_and(|modified| modified >= since)
+            })
+            .collect();
+        let pool = rayon::ThreadPoolBuilder::new().build()?;
+        Ok(pool.install(|| analyzer.parse_sources_parallel(&sources)))
+    }
+
     /// Get analyzer by display name
     pub fn get_analyzer_by_display_name(&self, display_name: &str) -> Option<&dyn Analyzer> {
         self.analyzers
@@ -403,11 +723,101 @@ impl AnalyzerRegistry {
         })
     }
 
+    /// Rebuild every view and the contribution cache from scratch, e.g. after
+    /// the file watcher was restarted and may have missed changes. The pool
+    /// is built before the cache is cleared, so failing to start it keeps
+    /// the old views.
+    pub fn reload_all_stats_views_scoped(&self) -> Result<crate::types::MultiAnalyzerStatsView> {
+        let pool = rayon::ThreadPoolBuilder::new().build()?;
+        self.contribution_cache.clear();
+        self.cached_paths.clear();
+        pool.install(|| self.load_all_stats_views_parallel())
+    }
+
+    /// Drop one analyzer's cached contributions and view, then discover and
+    /// parse its sources again, for when the watcher missed changes or the
+    /// cache is suspected stale. Returns the number of sources found. Other
+    /// analyzers' views and cache entries are left alone.
+    pub fn rescan_analyzer(&self, analyzer_name: &str) -> Result<usize> {
+        let analyzer = self
+            .get_analyzer_by_display_name(analyzer_name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown analyzer: {analyzer_name}"))?;
+        if let Some((_, paths)) = self.cached_paths.remove(analyzer_name) {
+            for path_hash in paths {
+                self.contribution_cache.remove_any(&path_hash);
+            }
+        }
+
+        let sources = analyzer.discover_data_sources()?;
+        if sources.is_empty() {
+            self.analyzer_views_cache.remove(analyzer_name);
+            self.mark_view_changed(analyzer_name);
+            return Ok(0);
+        }
+        let pool = rayon::ThreadPoolBuilder::new().build()?;
+        pool.install(|| self.load_analyzer_view(analyzer, &sources, None));
+        self.contribution_cache.shrink_to_fit();
+        Ok(sources.len())
+    }
+
     /// Load view-only stats using rayon for parallel file reads.
     /// Called once at startup. Uses rayon threadpool for parallel I/O operations.
     /// Populates file contribution cache for true incremental updates.
     /// Must be called within a rayon threadpool context for parallelism.
     pub fn load_all_stats_views_parallel(&self) -> Result<crate::types::MultiAnalyzerStatsView> {
+        // Get available analyzers with their sources (single discovery)
+        let analyzer_data = self.available_analyzers_with_sources();
+
+        // Parse all analyzers in parallel using rayon
+        let all_views: Vec<_> = analyzer_data
+            .into_par_iter()
+            .map(|(analyzer, sources)| self.load_analyzer_view(analyzer, &sources, None))
+            .collect();
+
+        // Shrink caches after bulk insertion
+        self.contribution_cache.shrink_to_fit();
+
+        let versions = all_views
+            .iter()
+            .map(|view| self.view_version(&view.read().analyzer_name))
+            .collect();
+        Ok(crate::types::MultiAnalyzerStatsView {
+            analyzer_stats: all_views,
+            loading: Vec::new(),
+            versions,
+        })
+    }
+
+    /// Like [`Self::load_all_stats_views_parallel`], but discovers and loads
+    /// every analyzer independently, calling `on_loaded` with each one's name
+    /// as soon as its view is cached (or it turns out to have no data), so
+    /// fast analyzers can be shown before slow ones finish. With a `store`,
+    /// each parse batch is also mirrored into it on its way into the view.
+    /// Must be called within a rayon threadpool context for parallelism.
+    pub fn load_all_stats_views_streaming(
+        &self,
+        store: Option<&crate::store::StoreWriter>,
+        on_loaded: impl Fn(&str) + Sync,
+    ) {
+        self.analyzers.par_iter().for_each(|analyzer| {
+            if let Ok(sources) = analyzer.discover_data_sources()
+                && !sources.is_empty()
+            {
+                self.load_analyzer_view(analyzer.as_ref(), &sources, store);
+            }
+            on_loaded(analyzer.display_name());
+        });
+        self.contribution_cache.shrink_to_fit();
+    }
+
+    /// Parse one analyzer's sources into its view, caching the view and the
+    /// per-file contributions for incremental updates.
+    fn load_analyzer_view(
+        &self,
+        analyzer: &dyn Analyzer,
+        sources: &[DataSource],
+        store: Option<&crate::store::StoreWriter>,
+    ) -> SharedAnalyzerView {
         // Contribution cache variants based on analyzer strategy
         enum CachedContributions {
             SingleMessage(Vec<(PathHash, SingleMessageContribution)>),
@@ -415,165 +825,161 @@ impl AnalyzerRegistry {
             MultiSession(Vec<(PathHash, MultiSessionContribution)>),
         }
 
-        // Get available analyzers with their sources (single discovery)
-        let analyzer_data: Vec<_> = self
-            .available_analyzers_with_sources()
-            .into_iter()
-            .map(|(a, sources)| {
-                let strategy = a.contribution_strategy();
-                (a, a.display_name().to_string(), sources, strategy)
-            })
-            .collect();
-
-        // Parse all analyzers in parallel using rayon
-        let all_results: Vec<_> = analyzer_data
-            .into_par_iter()
-            .map(|(analyzer, name, sources, strategy)| {
-                let analyzer_name_arc: Arc<str> = Arc::from(name.as_str());
+        let name = analyzer.display_name().to_string();
+        let analyzer_name_arc: Arc<str> = Arc::from(name.as_str());
+        let mut builder = AnalyzerViewBuilder::new(Arc::clone(&analyzer_name_arc));
+        let mut contributions = match analyzer.contribution_strategy() {
+            ContributionStrategy::SingleMessage => CachedContributions::SingleMessage(Vec::new()),
+            ContributionStrategy::SingleSession => CachedContributions::SingleSession(Vec::new()),
+            ContributionStrategy::MultiSession => CachedContributions::MultiSession(Vec::new()),
+        };
+        // Messages are folded into the view and dropped batch by batch.
+        let batch_size = if analyzer.supports_batched_parsing() {
+            PARSE_BATCH_SOURCES
+        } else {
+            sources.len().max(1)
+        };
+        if let Some(store) = store {
+            store.clear(&name);
+        }
 
-                // Parse sources with path association preserved.
-                let grouped = analyzer.parse_sources_parallel_with_paths(&sources);
+        for batch in sources.chunks(batch_size) {
+            // Parse sources with path association preserved.
+            let grouped = analyzer.parse_sources_parallel_with_paths(batch);
+
+            // Compute contributions per source based on strategy
+            let messages: Vec<Vec<_>> = match &mut contributions {
+                CachedContributions::SingleMessage(contribs) => {
+                    let (batch_contribs, msgs): (Vec<_>, Vec<_>) = grouped
+                        .into_par_iter()
+                        .map(|(path, msgs)| {
+                            let path_hash = PathHash::new(&path);
+                            let contribution = msgs
+                                .first()
+                                .map(SingleMessageContribution::from_message)
+                                .unwrap_or_default();
+                            ((path_hash, contribution), msgs)
+                        })
+                        .unzip();
+                    contribs.extend(batch_contribs);
+                    msgs
+                }
+                CachedContributions::SingleSession(contribs) => {
+                    let (batch_contribs, msgs): (Vec<_>, Vec<_>) = grouped
+                        .into_par_iter()
+                        .map(|(path, msgs)| {
+                            let path_hash = PathHash::new(&path);
+                            let contribution = SingleSessionContribution::from_messages(&msgs);
+                            ((path_hash, contribution), msgs)
+                        })
+                        .unzip();
+                    contribs.extend(batch_contribs);
+                    msgs
+                }
+                CachedContributions::MultiSession(contribs) => {
+                    let (batch_contribs, msgs): (Vec<_>, Vec<_>) = grouped
+                        .into_par_iter()
+                        .map(|(path, msgs)| {
+                            let path_hash = PathHash::new(&path);
+                            let contribution = MultiSessionContribution::from_messages(
+                                &msgs,
+                                Arc::clone(&analyzer_name_arc),
+                            );
+                            ((path_hash, contribution), msgs)
+                        })
+                        .unzip();
+                    contribs.extend(batch_contribs);
+                    msgs
+                }
+            };
 
-                // Compute contributions per source based on strategy
-                let (contributions, all_messages): (CachedContributions, Vec<Vec<_>>) =
-                    match strategy {
-                        ContributionStrategy::SingleMessage => {
-                            let (contribs, msgs): (Vec<_>, Vec<_>) = grouped
-                                .into_par_iter()
-                                .map(|(path, msgs)| {
-                                    let path_hash = PathHash::new(&path);
-                                    let contribution = msgs
-                                        .first()
-                                        .map(SingleMessageContribution::from_message)
-                                        .unwrap_or_default();
-                                    ((path_hash, contribution), msgs)
-                                })
-                                .unzip();
-                            (CachedContributions::SingleMessage(contribs), msgs)
-                        }
-                        ContributionStrategy::SingleSession => {
-                            let (contribs, msgs): (Vec<_>, Vec<_>) = grouped
-                                .into_par_iter()
-                                .map(|(path, msgs)| {
-                                    let path_hash = PathHash::new(&path);
-                                    let contribution =
-                                        SingleSessionContribution::from_messages(&msgs);
-                                    ((path_hash, contribution), msgs)
-                                })
-                                .unzip();
-                            (CachedContributions::SingleSession(contribs), msgs)
-                        }
-                        ContributionStrategy::MultiSession => {
-                            let (contribs, msgs): (Vec<_>, Vec<_>) = grouped
-                                .into_par_iter()
-                                .map(|(path, msgs)| {
-                                    let path_hash = PathHash::new(&path);
-                                    let contribution = MultiSessionContribution::from_messages(
-                                        &msgs,
-                                        Arc::clone(&analyzer_name_arc),
-                                    );
-                                    ((path_hash, contribution), msgs)
-                                })
-                                .unzip();
-                            (CachedContributions::MultiSession(contribs), msgs)
-                        }
-                    };
-
-                let all_messages: Vec<_> = all_messages.into_iter().flatten().collect();
-
-                // Deduplicate messages across sources
-                let messages = crate::utils::deduplicate_by_global_hash(all_messages);
-
-                // Aggregate stats
-                let mut daily_stats = crate::utils::aggregate_by_date(&messages);
-                daily_stats.retain(|date, _| date != "unknown");
-                let num_conversations = daily_stats
-                    .values()
-                    .map(|stats| stats.conversations as u64)
-                    .sum();
-
-                let stats = AgenticCodingToolStats {
-                    daily_stats,
-                    num_conversations,
-                    messages,
-                    analyzer_name: name.clone(),
-                };
-
-                (
-                    name,
-                    contributions,
-                    Ok(stats) as Result<AgenticCodingToolStats>,
-                )
-            })
-            .collect();
+            // Deduplicates across batches and drops the messages
+            let messages: Vec<_> = messages.into_iter().flatten().collect();
+            if let Some(store) = store {
+                store.messages(&name, messages.clone());
+            }
+            builder.add_batch(messages);
+        }
 
-        // Build views from results and cache contributions
-        let mut all_views = Vec::new();
-        for (name, contributions, result) in all_results {
-            match result {
-                Ok(stats) => {
-                    // Cache file contributions based on type
-                    match contributions {
-                        CachedContributions::SingleMessage(contribs) => {
-                            for (path_hash, contribution) in contribs {
-                                self.contribution_cache
-                                    .insert_single_message(path_hash, contribution);
-                            }
-                        }
-                        CachedContributions::SingleSession(contribs) => {
-                            for (path_hash, contribution) in contribs {
-                                self.contribution_cache
-                                    .insert_single_session(path_hash, contribution);
-                            }
-                        }
-                        CachedContributions::MultiSession(contribs) => {
-                            for (path_hash, contribution) in contribs {
-                                self.contribution_cache
-                                    .insert_multi_session(path_hash, contribution);
-                            }
-                        }
-                    }
-                    // Convert to view (drops messages)
-                    let view = stats.into_view();
-                    // Cache the view for incremental updates
-                    self.analyzer_views_cache.insert(name, view.clone());
-                    all_views.push(view);
+        // Cache file contributions based on type
+        let mut paths = HashSet::with_capacity(sources.len());
+        match contributions {
+            CachedContributions::SingleMessage(contribs) => {
+                for (path_hash, contribution) in contribs {
+                    paths.insert(path_hash);
+                    self.contribution_cache
+                        .insert_single_message(path_hash, contribution);
                 }
-                Err(e) => {
-                    eprintln!("⚠️  Error analyzing {} data: {}", name, e);
+            }
+            CachedContributions::SingleSession(contribs) => {
+                for (path_hash, contribution) in contribs {
+                    paths.insert(path_hash);
+                    self.contribution_cache
+                        .insert_single_session(path_hash, contribution);
+                }
+            }
+            CachedContributions::MultiSession(contribs) => {
+                for (path_hash, contribution) in contribs {
+                    paths.insert(path_hash);
+                    self.contribution_cache
+                        .insert_multi_session(path_hash, contribution);
                 }
             }
         }
-
-        // Shrink caches after bulk insertion
-        self.contribution_cache.shrink_to_fit();
-
-        Ok(crate::types::MultiAnalyzerStatsView {
-            analyzer_stats: all_views,
-        })
+        self.cached_paths.insert(name.clone(), paths);
+        // Cache the view for incremental updates
+        let view = builder.finish();
+        if let Some(store) = store {
+            store.days(&name, view.read().daily_stats.clone());
+        }
+        self.analyzer_views_cache.insert(name.clone(), view.clone());
+        self.mark_view_changed(&name);
+        view
     }
 
     /// Reload stats for a single file change using true incremental update.
     /// O(1) update - only reparses the changed file, subtracts old contribution,
     /// adds new contribution. No cloning needed thanks to RwLock.
     /// Uses sequential parsing (no threadpool) since it's just one file.
+    ///
+    /// Returns the conversation the file's newest message belongs to, which is
+    /// the session being written to when the change came from a live agent.
     pub fn reload_file_incremental(
         &self,
         analyzer_name: &str,
         changed_path: &std::path::Path,
-    ) -> Result<()> {
+    ) -> Result<Option<String>> {
         let analyzer = self
             .get_analyzer_by_display_name(analyzer_name)
             .ok_or_else(|| anyhow::anyhow!("Analyzer not found: {}", analyzer_name))?;
 
         // Skip invalid paths (directories, wrong file types, etc.)
         if !analyzer.is_valid_data_path(changed_path) {
-            return Ok(());
+            return Ok(None);
         }
 
         // Mark file as dirty for incremental upload (only for valid data paths)
         self.mark_file_dirty(analyzer_name, changed_path);
 
+        let new_messages = self.replace_file_contribution(analyzer, changed_path)?;
+        let active_session = new_messages
+            .iter()
+            .max_by_key(|message| message.date)
+            .map(|message| message.conversation_hash.clone());
+
+        Ok(active_session)
+    }
+
+    /// Re-parse one file and swap its cached contribution, subtracting the
+    /// old one from the analyzer's view and adding the new. Returns the
+    /// file's messages.
+    fn replace_file_contribution(
+        &self,
+        analyzer: &dyn Analyzer,
+        changed_path: &std::path::Path,
+    ) -> Result<Vec<ConversationMessage>> {
+        let analyzer_name = analyzer.display_name();
+
         // Create Arc<str> once for this update
         let analyzer_name_arc: Arc<str> = Arc::from(analyzer_name);
 
@@ -582,6 +988,10 @@ impl AnalyzerRegistry {
 
         // Get contribution strategy for this analyzer
         let strategy = analyzer.contribution_strategy();
+        self.cached_paths
+            .entry(analyzer_name.to_string())
+            .or_default()
+            .insert(path_hash);
 
         // Parse just the changed file (sequential, no threadpool needed for single file)
         let source = DataSource {
@@ -655,8 +1065,130 @@ impl AnalyzerRegistry {
                 view.add_multi_session_contribution(&new_contribution);
             }
         }
+        self.mark_view_changed(analyzer_name);
 
-        Ok(())
+        Ok(new_messages)
+    }
+
+    /// Every model with usage in a cached view.
+    pub fn cached_models(&self) -> HashSet<String> {
+        let mut models = HashSet::new();
+        for view in self.analyzer_views_cache.iter() {
+            for day in view.read().daily_stats.values() {
+                models.extend(day.model_stats.keys().cloned());
+            }
+        }
+        models
+    }
+
+    /// Re-price usage dated `day` at current model rates; `before` holds the
+    /// rates the cached costs were computed with. One-session files are
+    /// re-priced from the per-model token counts already in memory. The
+    /// contributions of one-message and multi-session files keep no such
+    /// counts, so their files with usage on `day` are re-parsed instead.
+    /// Cached contributions move with the views, sessions and per-model
+    /// stats included, so later incremental updates subtract what was added.
+    /// Returns whether any view changed.
+    pub fn reprice_day(&self, day: CompactDate, before: &crate::models::PricingSnapshot) -> bool {
+        let mut changed = false;
+        for analyzer in &self.analyzers {
+            let name = analyzer.display_name();
+            let Some(shared_view) = self.get_cached_view(name) else {
+                continue;
+            };
+            let paths = self.cached_paths.get(name).map(|paths| paths.clone());
+            let view_changed = match paths {
+                // Full-reload analyzers rebuild the whole view on their next
+                // change, so only the view's days need the new prices.
+                Some(paths) if !analyzer.requires_full_reload_for_source_change() => {
+                    match analyzer.contribution_strategy() {
+                        ContributionStrategy::SingleSession => {
+                            self.reprice_single_sessions(&shared_view, &paths, day, before)
+                        }
+                        ContributionStrategy::SingleMessage => {
+                            let reparsed = self.reparse_day(analyzer.as_ref(), &paths, day);
+                            // One-message contributions carry no per-model
+                            // stats, so the view's are re-priced from its counts.
+                            if let Some(view_day) =
+                                shared_view.write().daily_stats.get_mut(&day.to_string())
+                            {
+                                for model in view_day.model_stats.values_mut() {
+                                    model.reprice(before);
+                                }
+                            }
+                            reparsed
+                        }
+                        ContributionStrategy::MultiSession => {
+                            self.reparse_day(analyzer.as_ref(), &paths, day)
+                        }
+                    }
+                }
+                _ => shared_view
+                    .write()
+                    .daily_stats
+                    .get_mut(&day.to_string())
+                    .is_some_and(|view_day| view_day.reprice(before) != 0.0),
+            };
+            if view_changed {
+                self.mark_view_changed(name);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Re-price the cached one-session contributions dated `day` in place.
+    fn reprice_single_sessions(
+        &self,
+        shared_view: &SharedAnalyzerView,
+        paths: &HashSet<PathHash>,
+        day: CompactDate,
+        before: &crate::models::PricingSnapshot,
+    ) -> bool {
+        let mut view = shared_view.write();
+        let mut changed = false;
+        for path_hash in paths {
+            if let Some(mut contribution) = self.contribution_cache.get_single_session(path_hash) {
+                let old = contribution.clone();
+                if contribution.date == day && contribution.reprice(before) != 0.0 {
+                    view.subtract_single_session_contribution(&old);
+                    view.add_single_session_contribution(&contribution);
+                    self.contribution_cache
+                        .insert_single_session(*path_hash, contribution);
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Re-parse the cached files of `analyzer` that have usage dated `day`,
+    /// which costs them at current rates.
+    fn reparse_day(
+        &self,
+        analyzer: &dyn Analyzer,
+        paths: &HashSet<PathHash>,
+        day: CompactDate,
+    ) -> bool {
+        let sources = match analyzer.discover_data_sources() {
+            Ok(sources) => sources,
+            Err(error) => {
+                eprintln!("Failed to re-price {}: {}", analyzer.display_name(), error);
+                return false;
+            }
+        };
+        let mut changed = false;
+        for source in sources {
+            let path_hash = PathHash::new(&source.path);
+            if !paths.contains(&path_hash) || !self.contribution_cache.covers_day(&path_hash, day) {
+                continue;
+            }
+            match self.replace_file_contribution(analyzer, &source.path) {
+                Ok(_) => changed = true,
+                Err(error) => eprintln!("Failed to re-price {:?}: {}", source.path, error),
+            }
+        }
+        changed
     }
 
     pub fn requires_full_reload_for_source_change(&self, analyzer_name: &str) -> bool {
@@ -687,6 +1219,9 @@ impl AnalyzerRegistry {
 
         // Try to remove from any cache and update view accordingly
         if let Some(removed) = self.contribution_cache.remove_any(&path_hash) {
+            if let Some(mut paths) = self.cached_paths.get_mut(analyzer_name) {
+                paths.remove(&path_hash);
+            }
             if let Some(shared_view) = self.analyzer_views_cache.get(analyzer_name) {
                 let mut view = shared_view.write();
                 match removed {
@@ -700,6 +1235,9 @@ impl AnalyzerRegistry {
                         view.subtract_multi_session_contribution(&old);
                     }
                 }
+                drop(view);
+                drop(shared_view);
+                self.mark_view_changed(analyzer_name);
             }
             true
         } else {
@@ -719,6 +1257,35 @@ impl AnalyzerRegistry {
             .map(|r| r.clone())
     }
 
+    /// All cached views with their versions, for broadcasting to listeners.
+    pub fn stats_view(&self, loading: Vec<String>) -> crate::types::MultiAnalyzerStatsView {
+        let analyzer_stats = self.get_all_cached_views();
+        let versions = analyzer_stats
+            .iter()
+            .map(|view| self.view_version(&view.read().analyzer_name))
+            .collect();
+        crate::types::MultiAnalyzerStatsView {
+            analyzer_stats,
+            loading,
+            versions,
+        }
+    }
+
+    fn view_version(&self, analyzer_name: &str) -> u64 {
+        self.view_versions
+            .get(analyzer_name)
+            .map_or(0, |version| *version)
+    }
+
+    /// Give the analyzer's view a new version after replacing or mutating it.
+    fn mark_view_changed(&self, analyzer_name: &str) {
+        let version = self
+            .next_view_version
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.view_versions
+            .insert(analyzer_name.to_string(), version);
+    }
+
     /// Get all cached views as a Vec, for building MultiAnalyzerStatsView.
     /// Returns SharedAnalyzerView clones (cheap Arc pointer copies).
     /// Views are returned in registration order for stable tab ordering in TUI.
@@ -735,6 +1302,7 @@ impl AnalyzerRegistry {
     pub fn update_cached_view(&self, analyzer_name: &str, view: SharedAnalyzerView) {
         self.analyzer_views_cache
             .insert(analyzer_name.to_string(), view);
+        self.mark_view_changed(analyzer_name);
     }
 
     /// Get a mapping of data directories to analyzer names for file watching.
@@ -753,6 +1321,24 @@ impl AnalyzerRegistry {
         dir_to_analyzer
     }
 
+    /// Sources an analyzer finds under `dir`, e.g. a project directory that
+    /// appeared while watching.
+    pub fn discover_sources_under(&self, analyzer_name: &str, dir: &Path) -> Vec<DataSource> {
+        let Some(analyzer) = self.get_analyzer_by_display_name(analyzer_name) else {
+            return Vec::new();
+        };
+        match analyzer.discover_data_sources() {
+            Ok(sources) => sources
+                .into_iter()
+                .filter(|source| source.path.starts_with(dir))
+                .collect(),
+            Err(e) => {
+                eprintln!("Error discovering {analyzer_name} sources: {e}");
+                Vec::new()
+            }
+        }
+    }
+
     /// Mark a file as dirty for the next upload (file has been modified).
     pub fn mark_file_dirty(&self, analyzer_name: &str, path: &Path) {
         self.dirty_files_for_upload
@@ -769,6 +1355,37 @@ impl AnalyzerRegistry {
         !self.dirty_files_for_upload.is_empty()
     }
 
+    /// Set `stable_session_id` on messages that are only part of their
+    /// sessions, from each whole session as [`Self::load_session_messages`]
+    /// reads it, so the ID matches a full parse.
+    fn assign_session_ids_from_sessions(
+        &self,
+        analyzer_name: &str,
+        messages: &mut [ConversationMessage],
+    ) {
+        let sessions: HashSet<String> = messages
+            .iter()
+            .map(|message| message.conversation_hash.clone())
+            .collect();
+        let mut ids = HashMap::new();
+        for session in sessions {
+            let Ok(session_messages) = self.load_session_messages(analyzer_name, &session) else {
+                continue;
+            };
+            if let Some(first) =
+                crate::utils::first_messages_by_session(&session_messages).remove(session.as_str())
+            {
+                ids.insert(
+                    session.clone(),
+                    crate::utils::stable_session_id(analyzer_name, first),
+                );
+            }
+        }
+        for message in messages {
+            message.stable_session_id = ids.get(&message.conversation_hash).cloned();
+        }
+    }
+
     /// Load messages from dirty files for incremental upload.
     /// Returns messages filtered to only those created since last_upload_timestamp.
     /// Returns empty vec if no dirty files are tracked.
@@ -800,7 +1417,14 @@ impl AnalyzerRegistry {
                     }
                 } else {
                     let source = DataSource { path: path.clone() };
-                    if let Ok(msgs) = analyzer.parse_source(&source) {
+                    if let Ok(mut msgs) = analyzer.parse_source(&source) {
+                        // A file holding a single message is only part of its
+                        // session, so the ID comes from the whole session.
+                        if analyzer.contribution_strategy() == ContributionStrategy::SingleMessage {
+                            self.assign_session_ids_from_sessions(analyzer_name, &mut msgs);
+                        } else {
+                            crate::utils::assign_stable_session_ids(analyzer_name, &mut msgs);
+                        }
                         all_messages.extend(msgs);
                     }
                 }
@@ -856,7 +1480,11 @@ mod tests {
         }
 
         fn parse_source(&self, _source: &DataSource) -> Result<Vec<ConversationMessage>> {
-            Ok(Vec::new())
+            Ok(self
+                .stats
+                .as_ref()
+                .map(|stats| stats.messages.clone())
+                .unwrap_or_default())
         }
 
         fn get_stats_with_sources(
@@ -914,6 +1542,7 @@ mod tests {
             role: MessageRole::Assistant,
             uuid: None,
             session_name: Some("session".into()),
+            stable_session_id: None,
         };
 
         AgenticCodingToolStats {
@@ -973,6 +1602,132 @@ mod tests {
         assert_eq!(stats.analyzer_stats[0].analyzer_name, "ok");
     }
 
+    #[test]
+    fn streaming_load_reports_every_analyzer_once_its_view_is_cached() {
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(TestAnalyzer {
+            name: "ok",
+            available: true,
+            stats: Some(sample_stats("ok")),
+            sources: vec![PathBuf::from("/fake/path.jsonl")],
+            fail_stats: false,
+        });
+        registry.register(TestAnalyzer {
+            name: "empty",
+            available: false,
+            stats: None,
+            sources: Vec::new(),
+            fail_stats: false,
+        });
+
+        let loaded = parking_lot::Mutex::new(Vec::new());
+        registry.load_all_stats_views_streaming(None, |name| {
+            // The view is cached before the analyzer is reported.
+            assert_eq!(registry.get_cached_view(name).is_some(), name == "ok");
+            loaded.lock().push(name.to_string());
+        });
+
+        let mut loaded = loaded.into_inner();
+        loaded.sort();
+        assert_eq!(loaded, vec!["empty", "ok"]);
+        let views = registry.get_all_cached_views();
+        assert_eq!(views.len(), 1);
+        assert_eq!(views[0].read().num_conversations, 1);
+        assert!(registry.has_cached_contributions("ok"));
+    }
+
+    #[test]
+    fn load_session_messages_filters_by_conversation() {
+        let mut registry = AnalyzerRegistry::new();
+        let mut stats = sample_stats("ok");
+        let mut other = stats.messages[0].clone();
+        other.conversation_hash = "other".into();
+        other.global_hash = "global-other".into();
+        stats.messages.push(other);
+        registry.register(TestAnalyzer {
+            name: "ok",
+            available: true,
+            stats: Some(stats),
+            sources: vec![PathBuf::from("/fake/path.jsonl")],
+            fail_stats: false,
+        });
+
+        let messages = registry
+            .load_session_messages("ok", "conv")
+            .expect("load session");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].global_hash, "global");
+        assert!(registry.load_session_messages("missing", "conv").is_err());
+    }
+
+    /// One session per file, named after it, remembering which files it read.
+    struct SessionPerFileAnalyzer {
+        sources: Vec<PathBuf>,
+        parsed: Arc<parking_lot::Mutex<Vec<PathBuf>>>,
+    }
+
+    #[async_trait]
+    impl Analyzer for SessionPerFileAnalyzer {
+        fn display_name(&self) -> &'static str {
+            "files"
+        }
+
+        fn get_data_glob_patterns(&self) -> Vec<String> {
+            vec!["*.json".to_string()]
+        }
+
+        fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+            Ok(self
+                .sources
+                .iter()
+                .cloned()
+                .map(|path| DataSource { path })
+                .collect())
+        }
+
+        fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+            self.parsed.lock().push(source.path.clone());
+            let stem = source.path.file_stem().unwrap().to_string_lossy();
+            let mut message = sample_stats("files").messages.remove(0);
+            message.conversation_hash = stem.to_string();
+            message.global_hash = stem.to_string();
+            Ok(vec![message])
+        }
+
+        fn get_stats(&self) -> Result<AgenticCodingToolStats> {
+            anyhow::bail!("not used")
+        }
+
+        fn get_watch_directories(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+
+        fn contribution_strategy(&self) -> ContributionStrategy {
+            ContributionStrategy::SingleSession
+        }
+    }
+
+    #[test]
+    fn load_session_messages_reads_only_the_sessions_file() {
+        let sources: Vec<PathBuf> = ["a", "b", "c"]
+            .iter()
+            .map(|name| PathBuf::from(format!("/fake/{name}.json")))
+            .collect();
+        let parsed = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(SessionPerFileAnalyzer {
+            sources: sources.clone(),
+            parsed: Arc::clone(&parsed),
+        });
+        let analyzer = registry.get_analyzer_by_display_name("files").unwrap();
+        registry.load_analyzer_view(analyzer, &analyzer.discover_data_sources().unwrap(), None);
+        parsed.lock().clear();
+
+        let messages = registry.load_session_messages("files", "b").unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(*parsed.lock(), vec![sources[1].clone()]);
+    }
+
     #[tokio::test]
     async fn registry_builds_directory_mapping() {
         use std::fs;
@@ -1092,6 +1847,14 @@ mod tests {
             !mapping.contains_key(&session2_dir),
             "Should NOT watch individual session directories when watch_dirs is set"
         );
+
+        // A session directory appearing later is picked up from discovery.
+        let found: Vec<PathBuf> = registry
+            .discover_sources_under("nested", &session1_dir)
+            .into_iter()
+            .map(|source| source.path)
+            .collect();
+        assert_eq!(found, vec![file1]);
     }
 
     // =========================================================================
@@ -1198,6 +1961,34 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn stats_view_versions_change_only_for_the_updated_analyzer() {
+        let mut registry = AnalyzerRegistry::new();
+        for name in ["analyzer-a", "analyzer-b"] {
+            registry.register(TestAnalyzer {
+                name,
+                available: true,
+                stats: Some(sample_stats(name)),
+                sources: vec![PathBuf::from(format!("/fake/{name}.jsonl"))],
+                fail_stats: false,
+            });
+        }
+        let initial = registry
+            .load_all_stats_views_parallel()
+            .expect("load_all_stats_views_parallel");
+        assert_eq!(initial.versions.len(), 2);
+        assert_eq!(registry.stats_view(Vec::new()).versions, initial.versions);
+
+        registry.update_cached_view("analyzer-b", sample_stats("analyzer-b").into_view());
+        let updated = registry.stats_view(Vec::new());
+        assert_eq!(updated.versions[0], initial.versions[0]);
+        assert!(updated.versions[1] > initial.versions[1]);
+        assert_eq!(
+            updated.version_of(&updated.analyzer_stats[1]),
+            Some(updated.versions[1])
+        );
+    }
+
     // =========================================================================
     // DIRTY FILE TRACKING TESTS
     // =========================================================================
@@ -1230,6 +2021,86 @@ mod tests {
         assert!(!registry.has_dirty_files());
     }
 
+    /// One message per file, like OpenCode's message JSON files.
+    struct MessagePerFile {
+        files: Vec<(PathBuf, ConversationMessage)>,
+    }
+
+    #[async_trait]
+    impl Analyzer for MessagePerFile {
+        fn display_name(&self) -> &'static str {
+            "per-file"
+        }
+
+        fn get_data_glob_patterns(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+            Ok(self
+                .files
+                .iter()
+                .map(|(path, _)| DataSource { path: path.clone() })
+                .collect())
+        }
+
+        fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+            Ok(self
+                .files
+                .iter()
+                .filter(|(path, _)| *path == source.path)
+                .map(|(_, message)| message.clone())
+                .collect())
+        }
+
+        fn get_watch_directories(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+
+        fn is_valid_data_path(&self, _path: &Path) -> bool {
+            true
+        }
+
+        fn contribution_strategy(&self) -> ContributionStrategy {
+            ContributionStrategy::SingleMessage
+        }
+    }
+
+    #[test]
+    fn incremental_uploads_give_single_message_files_their_sessions_id() {
+        let message = |minute: u32, uuid: &str| ConversationMessage {
+            date: Utc.with_ymd_and_hms(2025, 1, 1, 0, minute, 0).unwrap(),
+            global_hash: uuid.into(),
+            uuid: Some(uuid.into()),
+            ..sample_stats("per-file").messages.remove(0)
+        };
+        let files = vec![
+            (PathBuf::from("/s/first.json"), message(0, "first")),
+            (PathBuf::from("/s/second.json"), message(5, "second")),
+        ];
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(MessagePerFile { files });
+        registry.load_all_stats_views_parallel().expect("views");
+
+        let full = registry
+            .get_analyzer_by_display_name("per-file")
+            .unwrap()
+            .get_stats()
+            .expect("stats");
+        let full_id = full.messages[0].stable_session_id.clone();
+        assert!(full_id.is_some());
+        assert!(
+            full.messages
+                .iter()
+                .all(|message| message.stable_session_id == full_id)
+        );
+
+        registry.mark_file_dirty("per-file", Path::new("/s/second.json"));
+        let uploaded = registry.load_messages_for_upload(0, None).expect("load");
+        assert_eq!(uploaded.len(), 1);
+        assert_eq!(uploaded[0].stable_session_id, full_id);
+    }
+
     #[test]
     fn test_load_messages_for_upload_empty_dirty_set_no_analyzers() {
         let registry = AnalyzerRegistry::new();
@@ -1269,10 +2140,175 @@ mod tests {
         // Load initial stats to populate cache
         let _ = registry.load_all_stats_views_parallel();
 
-        // Reload should mark file dirty
+        // Reload should mark file dirty and report the session written to
         assert!(!registry.has_dirty_files());
-        let _ = registry.reload_file_incremental("test", &path);
+        let active_session = registry
+            .reload_file_incremental("test", &path)
+            .expect("reload");
         assert!(registry.has_dirty_files());
+        assert_eq!(active_session.as_deref(), Some("conv"));
+    }
+
+    #[tokio::test]
+    async fn rescan_rebuilds_one_analyzer_and_keeps_the_others() {
+        let temp_dir = tempfile::tempdir().expect("tempdir");
+        let a_path = temp_dir.path().join("a.json");
+        let b_path = temp_dir.path().join("b.json");
+        std::fs::write(&a_path, "{}").expect("write");
+        std::fs::write(&b_path, "{}").expect("write");
+
+        let mut registry = AnalyzerRegistry::new();
+        for (name, path) in [("a", &a_path), ("b", &b_path)] {
+            registry.register(TestAnalyzer {
+                name,
+                available: true,
+                stats: Some(sample_stats(name)),
+                sources: vec![path.clone()],
+                fail_stats: false,
+            });
+        }
+        let _ = registry.load_all_stats_views_parallel();
+        let totals = |name: &str| {
+            let view = registry.get_cached_view(name).expect("view");
+            let view = view.read();
+            (view.num_conversations, view.daily_stats.len())
+        };
+        let before = totals("a");
+
+        assert_eq!(registry.rescan_analyzer("a").expect("rescan"), 1);
+        assert_eq!(totals("a"), before);
+        // The rescanned file's contribution is cached again, and the other
+        // analyzer's was never dropped.
+        assert!(registry.remove_file_from_cache("a", &a_path));
+        assert!(registry.remove_file_from_cache("b", &b_path));
+        assert!(registry.rescan_analyzer("missing").is_err());
+    }
+
+    #[test]
+    fn reprice_day_moves_views_and_cached_contributions_together() {
+        let mut stats = sample_stats("priced");
+        stats.messages[0].date = Utc::now();
+        stats.messages[0].model = Some("claude-sonnet-4".into());
+        stats.messages[0].stats.input_tokens = 1_000_000;
+        let path = PathBuf::from("/fake/priced.json");
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(TestAnalyzer {
+            name: "priced",
+            available: true,
+            stats: Some(stats),
+            sources: vec![path.clone()],
+            fail_stats: false,
+        });
+        let _ = registry.load_all_stats_views_parallel();
+        let today = CompactDate::from_local(&Utc::now());
+        let day_cost = |registry: &AnalyzerRegistry| {
+            let view = registry.get_cached_view("priced").expect("view");
+            let view = view.read();
+            view.daily_stats
+                .get(&today.to_string())
+                .map_or(0, |day| day.stats.cost_cents)
+        };
+        assert_eq!(day_cost(&registry), 0);
+
+        // As if the model had no pricing when the costs were cached.
+        let before = crate::models::PricingSnapshot::unpriced(["claude-sonnet-4"]);
+        assert!(registry.reprice_day(today, &before));
+        assert!(day_cost(&registry) > 0);
+        {
+            let view = registry.get_cached_view("priced").expect("view");
+            let view = view.read();
+            let day = &view.daily_stats[&today.to_string()];
+            assert!(day.model_stats["claude-sonnet-4"].cost > 0.0);
+        }
+
+        // The cached contribution carries the new cost, so removing the file
+        // takes all of it back out.
+        assert!(registry.remove_file_from_cache("priced", &path));
+        assert_eq!(day_cost(&registry), 0);
+    }
+
+    /// One file holding one message dated now, costed at current rates once
+    /// `priced` is set.
+    struct RepricedAnalyzer {
+        strategy: ContributionStrategy,
+        priced: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Analyzer for RepricedAnalyzer {
+        fn display_name(&self) -> &'static str {
+            "repriced"
+        }
+
+        fn get_data_glob_patterns(&self) -> Vec<String> {
+            vec!["*.json".to_string()]
+        }
+
+        fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+            Ok(vec![DataSource {
+                path: PathBuf::from("/fake/repriced.json"),
+            }])
+        }
+
+        fn parse_source(&self, _source: &DataSource) -> Result<Vec<ConversationMessage>> {
+            let mut message = sample_stats("repriced").messages.remove(0);
+            message.date = Utc::now();
+            message.model = Some("claude-sonnet-4".into());
+            message.stats.input_tokens = 1_000_000;
+            if self.priced.load(std::sync::atomic::Ordering::Relaxed) {
+                message.stats.cost =
+                    crate::models::calculate_total_cost("claude-sonnet-4", 1_000_000, 0, 0, 0);
+            }
+            Ok(vec![message])
+        }
+
+        fn get_stats(&self) -> Result<AgenticCodingToolStats> {
+            anyhow::bail!("not used")
+        }
+
+        fn get_watch_directories(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+
+        fn contribution_strategy(&self) -> ContributionStrategy {
+            self.strategy
+        }
+    }
+
+    #[test]
+    fn reprice_day_reparses_one_message_and_multi_session_files() {
+        for strategy in [
+            ContributionStrategy::SingleMessage,
+            ContributionStrategy::MultiSession,
+        ] {
+            let priced = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let mut registry = AnalyzerRegistry::new();
+            registry.register(RepricedAnalyzer {
+                strategy,
+                priced: Arc::clone(&priced),
+            });
+            let _ = registry.load_all_stats_views_parallel();
+            let today = CompactDate::from_local(&Utc::now());
+
+            // As if the model had no pricing when the file was first parsed.
+            priced.store(true, std::sync::atomic::Ordering::Relaxed);
+            let before = crate::models::PricingSnapshot::unpriced(["claude-sonnet-4"]);
+            assert!(registry.reprice_day(today, &before), "{strategy:?}");
+
+            let view = registry.get_cached_view("repriced").expect("view");
+            let view = view.read();
+            let day = &view.daily_stats[&today.to_string()];
+            assert_eq!(day.stats.cost_cents, 300, "{strategy:?}");
+            assert!(
+                (day.model_stats["claude-sonnet-4"].cost - 3.0).abs() < 1e-9,
+                "{strategy:?}"
+            );
+            assert_eq!(view.session_aggregates.len(), 1, "{strategy:?}");
+            assert_eq!(
+                view.session_aggregates[0].stats.cost_cents, 300,
+                "{strategy:?}"
+            );
+        }
     }
 
     #[tokio::test]
@@ -1298,7 +2334,8 @@ mod tests {
         let _ = registry.load_all_stats_views_parallel();
 
         // Invalid path (directory) should not mark dirty
-        let _ = registry.reload_file_incremental("test", &invalid_path);
+        let active_session = registry.reload_file_incremental("test", &invalid_path);
         assert!(!registry.has_dirty_files());
+        assert!(matches!(active_session, Ok(None)));
     }
 }
@@ -20,11 +20,7 @@ impl AntigravityCliAnalyzer {
     }
 
     fn data_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| {
-            h.join(".gemini")
-                .join("antigravity-cli")
-                .join("conversations")
-        })
+        Application::AntigravityCli.default_data_dir()
     }
 }
 
@@ -479,7 +475,7 @@ fn extract_tool_stats_from_fields(fields: &[AgProtoField], stats: &mut Stats) {
 #[async_trait]
 impl Analyzer for AntigravityCliAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Antigravity CLI"
+        Application::AntigravityCli.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -651,6 +647,7 @@ impl Analyzer for AntigravityCliAnalyzer {
                 role,
                 uuid: None,
                 session_name: session_name.clone(),
+                stable_session_id: None,
             });
         }
 
@@ -669,7 +666,7 @@ impl Analyzer for AntigravityCliAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::AntigravityCli.info().strategy
     }
 
     fn is_available(&self) -> bool {
@@ -1,10 +1,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use simd_json::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -13,10 +12,13 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use crate::analyzer::{Analyzer, DataSource};
 use crate::contribution_cache::ContributionStrategy;
 use crate::models::calculate_total_cost_for_service_tier_at;
-use crate::types::{Application, ConversationMessage, MessageRole, Stats};
+use crate::types::{Application, ConversationMessage, FileCategory, MessageRole, Stats};
 use crate::utils::{fast_hash, hash_text};
 use walkdir::WalkDir;
 
+/// Claude Code's own override for `~/.claude`.
+const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
 // Type alias for parse_jsonl_file return type
 type ParseResult = (
     Vec<ConversationMessage>,
@@ -27,48 +29,106 @@ type ParseResult = (
 
 pub struct ClaudeCodeAnalyzer {
     discovery_was_complete: AtomicBool,
+    /// Conversation hashes of transcripts on disk skipped by `[exclude] paths`,
+    /// so frozen history doesn't bring them back.
+    excluded_sessions: parking_lot::Mutex<Vec<String>>,
+    /// Keep stats for transcripts after Claude Code's cleanup deletes them,
+    /// from `[history] freeze_deleted_sessions`.
+    freeze_deleted: bool,
+    /// Size cap for the history store in bytes, from `[cache] max_size_mb`.
+    history_limit: Option<u64>,
+    /// Projects directories from `[paths] claude_code`, replacing the default.
+    projects_dirs: Vec<PathBuf>,
 }
 
 impl ClaudeCodeAnalyzer {
-    pub const DISPLAY_NAME: &str = "Claude Code";
+    pub const DISPLAY_NAME: &str = Application::ClaudeCode.display_name();
 
     pub fn new() -> Self {
         Self {
             discovery_was_complete: AtomicBool::new(true),
+            excluded_sessions: parking_lot::Mutex::new(Vec::new()),
+            freeze_deleted: false,
+            history_limit: None,
+            projects_dirs: Vec::new(),
+        }
+    }
+
+    pub fn freeze_deleted_sessions(mut self, freeze_deleted: bool) -> Self {
+        self.freeze_deleted = freeze_deleted;
+        self
+    }
+
+    pub fn history_size_limit(mut self, max_size_mb: Option<u64>) -> Self {
+        self.history_limit = max_size_mb.map(|mb| mb * 1024 * 1024);
+        self
+    }
+
+    pub fn projects_dirs(mut self, projects_dirs: Vec<PathBuf>) -> Self {
+        self.projects_dirs = projects_dirs;
+        self
+    }
+
+    /// How many sessions are kept only in the history store because their
+    /// transcripts were deleted.
+    pub fn frozen_session_count(&self) -> Result<usize> {
+        let mut discovered: Vec<String> = self
+            .discover_data_sources()?
+            .iter()
+            .map(|source| hash_text(&source.path.to_string_lossy()))
+            .collect();
+        discovered.extend(self.excluded_sessions.lock().iter().cloned());
+        super::claude_code_history::frozen_session_count(&discovered)
+    }
+
+    /// Configured projects directories, else `projects` under each entry of
+    /// `CLAUDE_CONFIG_DIR` (comma-separated), else `~/.claude/projects`.
+    fn data_dirs(&self) -> Vec<PathBuf> {
+        if !self.projects_dirs.is_empty() {
+            return self.projects_dirs.clone();
+        }
+        if let Ok(config_dirs) = std::env::var(CLAUDE_CONFIG_DIR_ENV) {
+            let dirs: Vec<PathBuf> = config_dirs
+                .split(',')
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| PathBuf::from(crate::utils::expand_home(dir)).join("projects"))
+                .collect();
+            if !dirs.is_empty() {
+                return dirs;
+            }
         }
+        Application::ClaudeCode
+            .default_data_dir()
+            .into_iter()
+            .collect()
     }
 
-    fn data_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".claude").join("projects"))
+    fn existing_data_dirs(&self) -> Vec<PathBuf> {
+        self.data_dirs()
+            .into_iter()
+            .filter(|directory| directory.is_dir())
+            .collect()
     }
 
-    pub(crate) fn discover_sources_in(&self, projects_dir: &Path) -> Vec<DataSource> {
+    pub(crate) fn discover_sources_in(&self, projects_dirs: &[PathBuf]) -> Vec<DataSource> {
         let mut sources = Vec::new();
         let mut complete = true;
-        for entry in WalkDir::new(projects_dir)
-            .min_depth(2)
-            .into_iter()
-            .filter_entry(|entry| is_claude_transcript_tree_path(projects_dir, entry.path()))
-        {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(error) => {
-                    complete = false;
-                    crate::utils::warn_once(format!(
-                        "Skipping unreadable Claude Code transcript path: {error}"
-                    ));
-                    continue;
-                }
-            };
-            if entry.file_type().is_file() && is_claude_transcript_path(projects_dir, entry.path())
-            {
-                sources.push(DataSource {
-                    path: entry.into_path(),
-                });
-            }
+        for projects_dir in projects_dirs {
+            complete &= collect_transcripts(projects_dir, &mut sources);
         }
         self.discovery_was_complete
             .store(complete, Ordering::Release);
+        let rules = crate::config::ExcludeRules::current();
+        let mut excluded = Vec::new();
+        sources.retain(|source| {
+            let keep = !rules.excludes(&source.path);
+            if !keep {
+                excluded.push(hash_text(&source.path.to_string_lossy()));
+            }
+            keep
+        });
+        *self.excluded_sessions.lock() = excluded;
         sources.sort_by(|left, right| {
             left.path
                 .components()
@@ -110,22 +170,17 @@ impl Analyzer for ClaudeCodeAnalyzer {
     fn get_data_glob_patterns(&self) -> Vec<String> {
         let mut patterns = Vec::new();
 
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_str = home_dir.to_string_lossy();
-            patterns.push(format!("{home_str}/.claude/projects/*/*.jsonl"));
-            patterns.push(format!(
-                "{home_str}/.claude/projects/*/*/subagents/**/*.jsonl"
-            ));
+        for projects_dir in self.data_dirs() {
+            let projects_str = projects_dir.to_string_lossy();
+            patterns.push(format!("{projects_str}/*/*.jsonl"));
+            patterns.push(format!("{projects_str}/*/*/subagents/**/*.jsonl"));
         }
 
         patterns
     }
 
     fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
-        Ok(Self::data_dir()
-            .filter(|directory| directory.is_dir())
-            .map(|projects_dir| self.discover_sources_in(&projects_dir))
-            .unwrap_or_default())
+        Ok(self.discover_sources_in(&self.existing_data_dirs()))
     }
 
     fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
@@ -140,24 +195,29 @@ impl Analyzer for ClaudeCodeAnalyzer {
         &self,
         sources: &[DataSource],
     ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
-        let grouped = sources
-            .par_iter()
-            .map(|source| match Self::parse_live_source(source) {
+        let grouped =
+            crate::parse_io::parse_each(sources, |source| match Self::parse_live_source(source) {
                 Ok(messages) => (source.path.clone(), messages),
                 Err(error) => {
-                    eprintln!(
-                        "Failed to parse {} source {:?}: {}",
+                    crate::diagnostics::parse_warning(
                         self.display_name(),
-                        source.path,
-                        error
+                        &source.path,
+                        format!(
+                            "Failed to parse {} source {:?}: {}",
+                            self.display_name(),
+                            source.path,
+                            error
+                        ),
                     );
                     (source.path.clone(), Vec::new())
                 }
-            })
-            .collect();
+            });
         deduplicate_grouped_messages(super::claude_code_history::merge_grouped(
             grouped,
             self.discovery_was_complete.load(Ordering::Acquire),
+            self.freeze_deleted,
+            self.history_limit,
+            &self.excluded_sessions.lock(),
         ))
     }
 
@@ -190,44 +250,42 @@ impl Analyzer for ClaudeCodeAnalyzer {
     }
 
     fn remove_source_state(&self, path: &Path) -> Result<()> {
+        if self.freeze_deleted {
+            return Ok(());
+        }
         super::claude_code_history::remove_session(&crate::utils::hash_text(
             &path.to_string_lossy(),
         ))
     }
 
     fn get_watch_directories(&self) -> Vec<PathBuf> {
-        Self::data_dir()
-            .filter(|d| d.is_dir())
-            .into_iter()
-            .collect()
+        self.existing_data_dirs()
     }
 
     fn is_valid_data_path(&self, path: &Path) -> bool {
         path.is_file()
-            && Self::data_dir()
-                .is_some_and(|projects_dir| is_claude_transcript_path(&projects_dir, path))
+            && self
+                .data_dirs()
+                .iter()
+                .any(|projects_dir| is_claude_transcript_path(projects_dir, path))
     }
 
     fn is_available(&self) -> bool {
-        Self::data_dir()
-            .filter(|directory| directory.is_dir())
-            .is_some_and(|projects_dir| {
-                WalkDir::new(&projects_dir)
-                    .min_depth(2)
-                    .into_iter()
-                    .filter_entry(|entry| {
-                        is_claude_transcript_tree_path(&projects_dir, entry.path())
-                    })
-                    .filter_map(Result::ok)
-                    .any(|entry| {
-                        entry.file_type().is_file()
-                            && is_claude_transcript_path(&projects_dir, entry.path())
-                    })
-            })
+        self.existing_data_dirs().into_iter().any(|projects_dir| {
+            WalkDir::new(&projects_dir)
+                .min_depth(2)
+                .into_iter()
+                .filter_entry(|entry| is_claude_transcript_tree_path(&projects_dir, entry.path()))
+                .filter_map(Result::ok)
+                .any(|entry| {
+                    entry.file_type().is_file()
+                        && is_claude_transcript_path(&projects_dir, entry.path())
+                })
+        })
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::ClaudeCode.info().strategy
     }
 
     fn requires_full_reload_for_source_change(&self) -> bool {
@@ -323,6 +381,33 @@ pub(crate) fn deduplicate_messages(messages: Vec<ConversationMessage>) -> Vec<Co
     result.into_iter().map(|(_, message)| message).collect()
 }
 
+/// Walk one projects directory, returning whether every entry was readable.
+fn collect_transcripts(projects_dir: &Path, sources: &mut Vec<DataSource>) -> bool {
+    let mut complete = true;
+    for entry in WalkDir::new(projects_dir)
+        .min_depth(2)
+        .into_iter()
+        .filter_entry(|entry| is_claude_transcript_tree_path(projects_dir, entry.path()))
+    {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                complete = false;
+                crate::utils::warn_once(format!(
+                    "Skipping unreadable Claude Code transcript path: {error}"
+                ));
+                continue;
+            }
+        };
+        if entry.file_type().is_file() && is_claude_transcript_path(projects_dir, entry.path()) {
+            sources.push(DataSource {
+                path: entry.into_path(),
+            });
+        }
+    }
+    complete
+}
+
 fn is_claude_transcript_tree_path(projects_dir: &Path, path: &Path) -> bool {
     let Ok(relative) = path.strip_prefix(projects_dir) else {
         return false;
@@ -347,12 +432,22 @@ pub fn extract_and_hash_project_id(file_path: &Path) -> String {
     // Claude Code path formats:
     // ~/.claude/projects/{PROJECT_ID}/{conversation_uuid}.jsonl
     // ~/.claude/projects/{PROJECT_ID}/{conversation_uuid}/subagents/**/agent-{AGENT_ID}.jsonl
+    // Relocated projects directories are matched by their `projects` name alone.
     let components: Vec<_> = file_path.components().collect();
-    let project_id = components.windows(3).find_map(|components| {
-        (components[0].as_os_str() == ".claude" && components[1].as_os_str() == "projects")
-            .then(|| components[2].as_os_str().to_str())
-            .flatten()
-    });
+    let project_id = components
+        .windows(3)
+        .find_map(|components| {
+            (components[0].as_os_str() == ".claude" && components[1].as_os_str() == "projects")
+                .then(|| components[2].as_os_str().to_str())
+                .flatten()
+        })
+        .or_else(|| {
+            components.windows(2).rev().find_map(|components| {
+                (components[0].as_os_str() == "projects")
+                    .then(|| components[1].as_os_str().to_str())
+                    .flatten()
+            })
+        });
 
     project_id
         .map(hash_text)
@@ -538,6 +633,13 @@ pub mod tool_schema {
     }
 }
 
+/// The server of an MCP tool name like `mcp__github__create_issue`. Server
+/// names may themselves contain single underscores.
+pub fn mcp_server(tool_name: &str) -> Option<&str> {
+    let (server, tool) = tool_name.strip_prefix("mcp__")?.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some(server)
+}
+
 pub fn extract_tool_stats(
     message_content: &Content,
     tool_use_result: &Option<simd_json::OwnedValue>,
@@ -546,25 +648,49 @@ pub fn extract_tool_stats(
 
     if let Content::Blocks(blocks) = message_content {
         for block in blocks {
-            let tool_name = match block {
-                ContentBlock::ToolUse { name, .. } => name,
+            let (tool_name, input) = match block {
+                ContentBlock::ToolUse { name, input, .. } => (name, input),
                 _ => continue,
             };
 
+            if matches!(
+                tool_name.as_str(),
+                "Read" | "Edit" | "MultiEdit" | "Write" | "NotebookEdit"
+            ) && let Some(path) = input
+                .get("file_path")
+                .or_else(|| input.get("notebook_path"))
+                .and_then(|v| v.as_str())
+            {
+                stats.add_file_path(path);
+            }
+
             match tool_name.as_str() {
                 "Read" => stats.files_read += 1,
                 "Edit" | "MultiEdit" => stats.files_edited += 1,
                 "Write" => stats.files_added += 1,
-                "Bash" => stats.terminal_commands += 1,
+                "Bash" => {
+                    stats.terminal_commands += 1;
+                    if let Some(command) = input.get("command").and_then(|v| v.as_str()) {
+                        stats.add_command(command);
+                    }
+                }
                 "Glob" => stats.file_searches += 1,
                 "Grep" => stats.file_content_searches += 1,
                 "TodoWrite" => stats.todo_writes += 1,
                 "TodoRead" => stats.todo_reads += 1,
-                _ => {}
+                name => {
+                    if let Some(server) = mcp_server(name) {
+                        *stats.mcp_tool_calls.entry(server.to_string()).or_default() += 1;
+                    }
+                }
             }
         }
     }
 
+    if let Some(tool_result) = &tool_use_result {
+        extract_file_result_stats(tool_result, &mut stats);
+    }
+
     if let Some(tool_result) = &tool_use_result
         && let Ok(todo_write_result) =
             simd_json::serde::from_owned_value::<tool_schema::TodoWriteResult>(tool_result.clone())
@@ -609,6 +735,77 @@ pub fn extract_tool_stats(
     stats
 }
 
+/// Line and byte counts from the `toolUseResult` of a file tool:
+///
+/// - Read: `{"file": {"filePath", "content", "numLines"}}`
+/// - Write of a new file: `{"type": "create", "filePath", "content"}`
+/// - Edit, MultiEdit, and Write over an existing file: `{"filePath",
+///   "structuredPatch": [{"lines": [" context", "-old", "+new"]}]}`
+///
+/// Patch lines starting with `+` count as edited and `-` as deleted, each
+/// line's bytes including its newline. Lines read, added, and edited also go
+/// to the file's composition category.
+fn extract_file_result_stats(tool_result: &simd_json::OwnedValue, stats: &mut Stats) {
+    let category = |path: Option<&str>| {
+        let ext = path
+            .and_then(|path| Path::new(path).extension())
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("");
+        FileCategory::from_extension(ext)
+    };
+
+    if let Some(file) = tool_result.get("file")
+        && let Some(content) = file.get("content").and_then(|v| v.as_str())
+    {
+        let lines = file
+            .get("numLines")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(|| content.lines().count() as u64);
+        stats.lines_read += lines;
+        stats.bytes_read += content.len() as u64;
+        stats.add_composition_lines(
+            category(file.get("filePath").and_then(|v| v.as_str())),
+            lines,
+        );
+        return;
+    }
+
+    let file_category = category(tool_result.get("filePath").and_then(|v| v.as_str()));
+    if tool_result.get("type").and_then(|v| v.as_str()) == Some("create")
+        && let Some(content) = tool_result.get("content").and_then(|v| v.as_str())
+    {
+        let lines = content.lines().count() as u64;
+        stats.lines_added += lines;
+        stats.bytes_added += content.len() as u64;
+        stats.add_composition_lines(file_category, lines);
+        return;
+    }
+
+    let Some(hunks) = tool_result
+        .get("structuredPatch")
+        .and_then(|v| v.as_array())
+    else {
+        return;
+    };
+    let mut edited = 0;
+    for line in hunks
+        .iter()
+        .filter_map(|hunk| hunk.get("lines").and_then(|v| v.as_array()))
+        .flatten()
+        .filter_map(|line| line.as_str())
+    {
+        if let Some(added) = line.strip_prefix('+') {
+            edited += 1;
+            stats.bytes_edited += added.len() as u64 + 1;
+        } else if let Some(removed) = line.strip_prefix('-') {
+            stats.lines_deleted += 1;
+            stats.bytes_deleted += removed.len() as u64 + 1;
+        }
+    }
+    stats.lines_edited += edited;
+    stats.add_composition_lines(file_category, edited);
+}
+
 #[cfg(test)]
 pub fn calculate_cost_from_tokens(usage: &Usage, model_name: &str) -> f64 {
     calculate_total_cost_for_service_tier_at(
@@ -652,6 +849,11 @@ pub fn parse_jsonl_file<R: Read>(
     let mut fallback_session_name = None;
 
     let mut current_model = None;
+    // Subagent transcripts live under `subagents/`; older ones sit in the
+    // main transcript and are only marked `isSidechain`.
+    let subagent_file = path
+        .components()
+        .any(|component| component.as_os_str() == "subagents");
 
     // Read entire file at once to avoid per-line allocations
     let mut buffer = Vec::new();
@@ -673,6 +875,9 @@ pub fn parse_jsonl_file<R: Read>(
             Ok(ClaudeCodeEntry::Message(entry)) => {
                 // Track all UUIDs for summary linking, even if we skip the message
                 all_uuids.push(entry.uuid.clone());
+                if let Some(cwd) = &entry.cwd {
+                    crate::utils::remember_project(project_hash, cwd);
+                }
 
                 let model = entry.message.as_ref().and_then(|m| m.model.clone());
                 if let Some(m) = &model {
@@ -680,6 +885,7 @@ pub fn parse_jsonl_file<R: Read>(
                 }
 
                 let timestamp = entry.timestamp;
+                let sidechain = subagent_file || entry.is_sidechain == Some(true);
                 let tool_use_result = entry.tool_use_result;
                 let request_id = entry.request_id;
                 let uuid = Some(entry.uuid);
@@ -710,6 +916,7 @@ pub fn parse_jsonl_file<R: Read>(
                         },
                         uuid,
                         session_name: None, // Will be populated later
+                        stable_session_id: None,
                     };
 
                     // Always extract tool stats from content if present
@@ -739,6 +946,7 @@ pub fn parse_jsonl_file<R: Read>(
                             + usage_val.cache_read_input_tokens;
                         msg.stats.cost =
                             calculate_cost_from_tokens_at(&usage_val, &model_name, timestamp);
+                        msg.stats.subagent_messages = u64::from(sidechain);
 
                         if let Some(request_id) = request_id
                             && let Some(message_id) = message_id
@@ -789,12 +997,16 @@ pub fn parse_jsonl_file<R: Read>(
                 }
             }
             Err(e) => {
-                crate::utils::warn_once(format!(
-                    "Skipping invalid entry in {} line {}: {}",
-                    path.display(),
-                    i + 1,
-                    e
-                ));
+                crate::diagnostics::parse_warning(
+                    ClaudeCodeAnalyzer::DISPLAY_NAME,
+                    path,
+                    format!(
+                        "Skipping invalid entry in {} line {}: {}",
+                        path.display(),
+                        i + 1,
+                        e
+                    ),
+                );
                 continue;
             }
             _ => continue, // Skip other entry types like FileHistorySnapshot, QueueOperation, Progress
@@ -804,6 +1016,19 @@ pub fn parse_jsonl_file<R: Read>(
     Ok((messages, summaries, all_uuids, fallback_session_name))
 }
 
+fn sum_counts(dst: &mut BTreeMap<String, u64>, src: &BTreeMap<String, u64>) {
+    for (key, count) in src {
+        *dst.entry(key.clone()).or_default() += count;
+    }
+}
+
+fn max_counts(dst: &mut BTreeMap<String, u64>, src: &BTreeMap<String, u64>) {
+    for (key, count) in src {
+        let existing = dst.entry(key.clone()).or_default();
+        *existing = (*existing).max(*count);
+    }
+}
+
 // Type alias for token fingerprint
 pub type TokenFingerprint = (u64, u64, u64, u64, u64);
 
@@ -816,6 +1041,9 @@ pub fn merge_message_into(
     seen_fps: &mut HashSet<TokenFingerprint>,
     src_fp: TokenFingerprint,
 ) {
+    // `subagent_messages` stays `dst`'s: parts of one response share its
+    // thread, and a copy echoed into a subagent transcript isn't subagent work.
+
     // Preserve session name
     if dst.session_name.is_none() && src.session_name.is_some() {
         dst.session_name = src.session_name.clone();
@@ -838,6 +1066,12 @@ pub fn merge_message_into(
         dst.stats.todos_created = dst.stats.todos_created.max(src.stats.todos_created);
         dst.stats.todos_completed = dst.stats.todos_completed.max(src.stats.todos_completed);
         dst.stats.todos_in_progress = dst.stats.todos_in_progress.max(src.stats.todos_in_progress);
+        max_counts(&mut dst.stats.mcp_tool_calls, &src.stats.mcp_tool_calls);
+        max_counts(&mut dst.stats.file_extensions, &src.stats.file_extensions);
+        max_counts(
+            &mut dst.stats.command_categories,
+            &src.stats.command_categories,
+        );
     } else {
         // New fingerprint: aggregate all stats with sum()
         seen_fps.insert(src_fp);
@@ -860,6 +1094,12 @@ pub fn merge_message_into(
         dst.stats.todos_created += src.stats.todos_created;
         dst.stats.todos_completed += src.stats.todos_completed;
         dst.stats.todos_in_progress += src.stats.todos_in_progress;
+        sum_counts(&mut dst.stats.mcp_tool_calls, &src.stats.mcp_tool_calls);
+        sum_counts(&mut dst.stats.file_extensions, &src.stats.file_extensions);
+        sum_counts(
+            &mut dst.stats.command_categories,
+            &src.stats.command_categories,
+        );
 
         // Preserve each message's timestamp-aware price: `dst.stats.cost` was
         // already priced at `dst.date`, and `src.stats.cost` was already
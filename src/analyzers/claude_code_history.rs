@@ -5,7 +5,19 @@ use std::path::{Path, PathBuf};
 
 use crate::types::ConversationMessage;
 
-const HISTORY_FILE_NAME: &str = "claude-code-messages.sqlite3";
+pub(crate) const HISTORY_FILE_NAME: &str = "claude-code-messages.sqlite3";
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS messages (
+        global_hash TEXT PRIMARY KEY NOT NULL,
+        conversation_hash TEXT NOT NULL,
+        payload BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS messages_conversation_hash
+        ON messages(conversation_hash);";
+/// Upgrades applied when opening the store, tracked in `PRAGMA user_version`:
+/// `MIGRATIONS[n]` takes version `n` to `n + 1`. Stores from before
+/// versioning are version 0 and may already have the version 1 tables, so
+/// every step must be idempotent.
+const MIGRATIONS: &[&str] = &[SCHEMA];
 
 pub(crate) fn merge_session(
     live_messages: Vec<ConversationMessage>,
@@ -49,9 +61,17 @@ pub(crate) fn remove_session(conversation_hash: &str) -> Result<()> {
     Ok(())
 }
 
+/// Merge freshly parsed transcripts with their retained history. With
+/// `freeze_deleted`, sessions whose transcripts no longer exist are kept in the
+/// store and returned as extra groups instead of being pruned. `max_bytes`
+/// caps the store's size once discovery is known to be complete. Sessions in
+/// `excluded` still exist on disk but are never returned as frozen.
 pub(crate) fn merge_grouped(
     grouped: Vec<(PathBuf, Vec<ConversationMessage>)>,
     prune_missing: bool,
+    freeze_deleted: bool,
+    max_bytes: Option<u64>,
+    excluded: &[String],
 ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
     let path = match history_path() {
         Ok(path) => path,
@@ -66,17 +86,226 @@ pub(crate) fn merge_grouped(
         .collect();
 
     let mut grouped = grouped;
+    let discovery_complete = prune_missing;
+    let prune_missing = prune_missing && !freeze_deleted;
     if let Err(error) = merge_at(&path, &mut grouped, &conversation_hashes, prune_missing) {
         warn_history_error("update", Some(&path), &error);
     }
+    if let Some(max_bytes) = max_bytes.filter(|_| discovery_complete)
+        && let Err(error) = enforce_size_limit(&path, &conversation_hashes, max_bytes)
+    {
+        warn_history_error("trim", Some(&path), &error);
+    }
+    if freeze_deleted {
+        let mut on_disk = conversation_hashes;
+        on_disk.extend_from_slice(excluded);
+        match frozen_at(&path, &on_disk) {
+            Ok(frozen) => {
+                grouped.extend(frozen.into_iter().map(|(conversation_hash, messages)| {
+                    (frozen_source_path(&conversation_hash), messages)
+                }))
+            }
+            Err(error) => warn_history_error("read", Some(&path), &error),
+        }
+    }
     grouped
 }
 
-fn history_path() -> Result<PathBuf> {
-    let state_root = dirs::state_dir()
-        .or_else(dirs::data_local_dir)
-        .context("Could not find platform state directory")?;
-    Ok(state_root.join("splitrail").join(HISTORY_FILE_NAME))
+/// Number of sessions retained in the history store whose transcripts are no
+/// longer among `discovered` (conversation hashes of the files on disk).
+pub(crate) fn frozen_session_count(discovered: &[String]) -> Result<usize> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(0);
+    }
+    Ok(frozen_at(&path, discovered)?.len())
+}
+
+/// Placeholder source path for a frozen session. It never exists on disk, so
+/// the watcher never touches it; it only keys the session's cached contribution.
+fn frozen_source_path(conversation_hash: &str) -> PathBuf {
+    PathBuf::from(format!("frozen://claude_code/{conversation_hash}"))
+}
+
+/// Retained messages for sessions not in `discovered`, grouped by session in
+/// hash order.
+fn frozen_at(
+    path: &Path,
+    discovered: &[String],
+) -> Result<Vec<(String, Vec<ConversationMessage>)>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let connection = Connection::open(path).context("Failed to open Claude Code history store")?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .context("Failed to configure Claude Code history store")?;
+    let discovered: HashSet<&str> = discovered.iter().map(String::as_str).collect();
+    let mut frozen: std::collections::BTreeMap<String, Vec<ConversationMessage>> =
+        std::collections::BTreeMap::new();
+    let mut statement = connection
+        .prepare("SELECT conversation_hash, payload FROM messages")
+        .context("Failed to prepare Claude Code history query")?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })
+        .context("Failed to query Claude Code history")?;
+    for row in rows {
+        let (conversation_hash, mut payload) =
+            row.context("Failed to read Claude Code history entry")?;
+        if discovered.contains(conversation_hash.as_str()) {
+            continue;
+        }
+        match simd_json::from_slice::<ConversationMessage>(&mut payload) {
+            Ok(message) => frozen.entry(conversation_hash).or_default().push(message),
+            Err(error) => crate::utils::warn_once(format!(
+                "Skipping invalid Claude Code history entry: {error}"
+            )),
+        }
+    }
+    Ok(frozen.into_iter().collect())
+}
+
+/// Shrink the store to at most `max_bytes`. Entries that no longer decode are
+/// dropped first, then whole sessions whose transcripts are gone, least
+/// recently written first, and finally the file is vacuumed to hand the space
+/// back. Sessions still on disk are never evicted since the next parse would
+/// only write them again. Returns how many sessions were evicted.
+fn enforce_size_limit(path: &Path, discovered: &[String], max_bytes: u64) -> Result<usize> {
+    let file_size = |path: &Path| std::fs::metadata(path).map_or(0, |meta| meta.len());
+    if file_size(path) <= max_bytes {
+        return Ok(0);
+    }
+    let connection = Connection::open(path).context("Failed to open Claude Code history store")?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .context("Failed to configure Claude Code history store")?;
+
+    let mut dead = Vec::new();
+    {
+        let mut statement = connection
+            .prepare("SELECT rowid, payload FROM messages")
+            .context("Failed to prepare Claude Code history query")?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .context("Failed to query Claude Code history")?;
+        for row in rows {
+            let (rowid, mut payload) = row.context("Failed to read Claude Code history entry")?;
+            if simd_json::from_slice::<ConversationMessage>(&mut payload).is_err() {
+                dead.push(rowid);
+            }
+        }
+    }
+
+    // Payload bytes per session, oldest last write first. The file also holds
+    // the index and page slack, so scale the target by the current overhead.
+    let mut sessions: Vec<(String, u64)> = Vec::new();
+    {
+        let mut statement = connection
+            .prepare(
+                "SELECT conversation_hash, SUM(length(payload)) FROM messages
+                 GROUP BY conversation_hash ORDER BY MAX(rowid)",
+            )
+            .context("Failed to prepare Claude Code history query")?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .context("Failed to query Claude Code history")?;
+        for row in rows {
+            let (conversation_hash, bytes) =
+                row.context("Failed to read Claude Code history entry")?;
+            sessions.push((conversation_hash, bytes.max(0) as u64));
+        }
+    }
+    let payload_bytes: u64 = sessions.iter().map(|(_, bytes)| bytes).sum();
+    let overhead = file_size(path) as f64 / payload_bytes.max(1) as f64;
+    let target = (max_bytes as f64 / overhead.max(1.0)) as u64;
+
+    let discovered: HashSet<&str> = discovered.iter().map(String::as_str).collect();
+    let mut remaining = payload_bytes;
+    let mut evicted = Vec::new();
+    for (conversation_hash, bytes) in &sessions {
+        if remaining <= target {
+            break;
+        }
+        if !discovered.contains(conversation_hash.as_str()) {
+            remaining -= bytes;
+            evicted.push(conversation_hash.as_str());
+        }
+    }
+
+    if dead.is_empty() && evicted.is_empty() {
+        crate::utils::warn_once(format!(
+            "Claude Code history store is over its {} MB limit but holds only live sessions",
+            max_bytes / (1024 * 1024)
+        ));
+        return Ok(0);
+    }
+    connection
+        .execute_batch("BEGIN")
+        .context("Failed to begin Claude Code history transaction")?;
+    for rowid in &dead {
+        connection
+            .execute("DELETE FROM messages WHERE rowid = ?1", [rowid])
+            .context("Failed to drop invalid Claude Code history entry")?;
+    }
+    for conversation_hash in &evicted {
+        connection
+            .execute(
+                "DELETE FROM messages WHERE conversation_hash = ?1",
+                [conversation_hash],
+            )
+            .context("Failed to evict Claude Code history session")?;
+    }
+    connection
+        .execute_batch("COMMIT; VACUUM;")
+        .context("Failed to compact Claude Code history store")?;
+    Ok(evicted.len())
+}
+
+pub(crate) fn history_path() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join(HISTORY_FILE_NAME))
+}
+
+/// Write a consistent copy of the store at `path` to `dest`, even while
+/// another process is writing to it. Returns `false` when there is no store.
+pub(crate) fn export_store(path: &Path, dest: &Path) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let connection = Connection::open(path).context("Failed to open Claude Code history store")?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .context("Failed to configure Claude Code history store")?;
+    connection
+        .execute("VACUUM INTO ?1", [dest.to_string_lossy()])
+        .context("Failed to copy Claude Code history store")?;
+    Ok(true)
+}
+
+/// Add the entries of another store (typically another machine's) that the
+/// store at `path` doesn't have yet. Entries already present are left alone.
+/// Returns how many were added.
+pub(crate) fn import_store(path: &Path, source: &Path) -> Result<usize> {
+    let connection = open_store(path)?;
+    connection
+        .execute("ATTACH DATABASE ?1 AS imported", [source.to_string_lossy()])
+        .context("Failed to open imported Claude Code history")?;
+    let added = connection
+        .execute(
+            "INSERT OR IGNORE INTO messages (global_hash, conversation_hash, payload)
+             SELECT global_hash, conversation_hash, payload FROM imported.messages",
+            [],
+        )
+        .context("Failed to import Claude Code history")?;
+    connection
+        .execute("DETACH DATABASE imported", [])
+        .context("Failed to close imported Claude Code history")?;
+    Ok(added)
 }
 
 fn merge_at<T>(
@@ -88,29 +317,7 @@ fn merge_at<T>(
 where
     T: MessageGroups,
 {
-    let Some(parent) = path.parent() else {
-        anyhow::bail!("Claude Code history path has no parent directory");
-    };
-    create_private_directory(parent)?;
-
-    let mut connection =
-        Connection::open(path).context("Failed to open Claude Code history store")?;
-    set_private_file_permissions(path)?;
-    connection
-        .busy_timeout(std::time::Duration::from_secs(5))
-        .context("Failed to configure Claude Code history store")?;
-    connection
-        .execute_batch(
-            "CREATE TABLE IF NOT EXISTS messages (
-                global_hash TEXT PRIMARY KEY NOT NULL,
-                conversation_hash TEXT NOT NULL,
-                payload BLOB NOT NULL
-            );
-            CREATE INDEX IF NOT EXISTS messages_conversation_hash
-                ON messages(conversation_hash);",
-        )
-        .context("Failed to initialize Claude Code history store")?;
-
+    let mut connection = open_store(path)?;
     let transaction = connection
         .transaction()
         .context("Failed to begin Claude Code history transaction")?;
@@ -242,6 +449,33 @@ impl MessageGroups for Vec<ConversationMessage> {
     }
 }
 
+/// Open the store for writing, creating or upgrading it as needed. A file
+/// that isn't a readable database is quarantined and replaced by a new one.
+fn open_store(path: &Path) -> Result<Connection> {
+    let Some(parent) = path.parent() else {
+        anyhow::bail!("Claude Code history path has no parent directory");
+    };
+    create_private_directory(parent)?;
+
+    match open_store_once(path) {
+        Err(error) if crate::persist::is_corrupt_sqlite(&error) => {
+            crate::persist::quarantine(path, "Claude Code history store", &error);
+            open_store_once(path)
+        }
+        result => result,
+    }
+}
+
+fn open_store_once(path: &Path) -> Result<Connection> {
+    let connection = Connection::open(path).context("Failed to open Claude Code history store")?;
+    set_private_file_permissions(path)?;
+    connection
+        .busy_timeout(std::time::Duration::from_secs(5))
+        .context("Failed to configure Claude Code history store")?;
+    crate::persist::migrate_sqlite(&connection, MIGRATIONS, "Claude Code history store")?;
+    Ok(connection)
+}
+
 fn create_private_directory(path: &Path) -> Result<()> {
     std::fs::create_dir_all(path).context("Failed to create Claude Code history directory")?;
     #[cfg(unix)]
@@ -303,6 +537,7 @@ mod tests {
             role: MessageRole::Assistant,
             uuid: Some(hash.to_string()),
             session_name: Some("Session prompt".to_string()),
+            stable_session_id: None,
         }
     }
 
@@ -339,6 +574,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn corrupt_store_is_quarantined_and_new_stores_are_versioned() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join(HISTORY_FILE_NAME);
+        std::fs::write(&path, b"definitely not a sqlite database, just long enough").unwrap();
+
+        let conversation = "session".to_string();
+        let mut messages = vec![message("first", &conversation, "local-first", 10)];
+        merge_at(
+            &path,
+            &mut messages,
+            std::slice::from_ref(&conversation),
+            false,
+        )
+        .unwrap();
+
+        assert!(crate::persist::quarantine_path(&path).exists());
+        let connection = Connection::open(&path).unwrap();
+        let version: i64 = connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+
+        // A store from a newer splitrail is left untouched.
+        connection.pragma_update(None, "user_version", 99).unwrap();
+        drop(connection);
+        assert!(merge_at(&path, &mut messages, &[conversation], false).is_err());
+        assert!(path.exists());
+    }
+
     #[test]
     fn live_message_updates_matching_history_record() {
         let directory = tempdir().unwrap();
@@ -428,6 +693,134 @@ mod tests {
         assert_eq!(current[0].1[0].global_hash, "first");
     }
 
+    #[test]
+    fn frozen_sessions_outlive_their_transcripts() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join(HISTORY_FILE_NAME);
+        let kept = "kept-session".to_string();
+        let deleted = "deleted-session".to_string();
+        let mut initial = vec![
+            message("kept", &kept, "local-kept", 10),
+            message("deleted-a", &deleted, "local-a", 20),
+            message("deleted-b", &deleted, "local-b", 5),
+        ];
+        merge_at(&path, &mut initial, &[kept.clone(), deleted.clone()], false).unwrap();
+
+        // The deleted transcript is no longer discovered; without pruning its
+        // messages stay behind as a frozen session.
+        let mut current = vec![(PathBuf::from("kept.jsonl"), Vec::new())];
+        merge_at(&path, &mut current, std::slice::from_ref(&kept), false).unwrap();
+        let frozen = frozen_at(&path, std::slice::from_ref(&kept)).unwrap();
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].0, deleted);
+        assert_eq!(
+            frozen[0]
+                .1
+                .iter()
+                .map(|message| message.stats.input_tokens)
+                .sum::<u64>(),
+            25
+        );
+        assert!(frozen_at(&path, &[kept, deleted]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn size_limit_evicts_oldest_deleted_sessions_and_compacts() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join(HISTORY_FILE_NAME);
+        let live = "live-session".to_string();
+        let conversations = ["oldest", "older", "newest"].map(String::from);
+        for conversation in &conversations {
+            let mut messages: Vec<_> = (0..200)
+                .map(|i| message(&format!("{conversation}-{i}"), conversation, "local", i))
+                .collect();
+            merge_at(
+                &path,
+                &mut messages,
+                std::slice::from_ref(conversation),
+                false,
+            )
+            .unwrap();
+        }
+        let mut messages = vec![message("live-0", &live, "local", 1)];
+        merge_at(&path, &mut messages, std::slice::from_ref(&live), false).unwrap();
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .execute(
+                "INSERT INTO messages (global_hash, conversation_hash, payload)
+                 VALUES ('corrupt', ?1, X'FF')",
+                [&live],
+            )
+            .unwrap();
+        drop(connection);
+
+        let before = std::fs::metadata(&path).unwrap().len();
+        let evicted = enforce_size_limit(&path, std::slice::from_ref(&live), before / 2).unwrap();
+        assert_eq!(evicted, 2);
+        assert!(std::fs::metadata(&path).unwrap().len() < before);
+
+        let remaining: Vec<String> = frozen_at(&path, &[])
+            .unwrap()
+            .into_iter()
+            .map(|(conversation, _)| conversation)
+            .collect();
+        assert_eq!(remaining, vec![live.clone(), "newest".to_string()]);
+        let connection = Connection::open(&path).unwrap();
+        let corrupt: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM messages WHERE global_hash = 'corrupt'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(corrupt, 0);
+
+        // Under the limit nothing is touched.
+        assert_eq!(enforce_size_limit(&path, &[], u64::MAX).unwrap(), 0);
+    }
+
+    #[test]
+    fn importing_another_store_adds_only_missing_entries() {
+        let laptop = tempdir().unwrap();
+        let laptop_path = laptop.path().join(HISTORY_FILE_NAME);
+        let desktop = tempdir().unwrap();
+        let desktop_path = desktop.path().join(HISTORY_FILE_NAME);
+        let conversation = "session".to_string();
+        let mut laptop_messages = vec![
+            message("shared", &conversation, "local-shared", 99),
+            message("laptop-only", &conversation, "local-laptop", 20),
+        ];
+        merge_at(
+            &laptop_path,
+            &mut laptop_messages,
+            std::slice::from_ref(&conversation),
+            false,
+        )
+        .unwrap();
+        let mut desktop_messages = vec![message("shared", &conversation, "local-shared", 10)];
+        merge_at(
+            &desktop_path,
+            &mut desktop_messages,
+            std::slice::from_ref(&conversation),
+            false,
+        )
+        .unwrap();
+
+        let bundle = laptop.path().join("export.sqlite3");
+        assert!(export_store(&laptop_path, &bundle).unwrap());
+        assert!(!export_store(&laptop.path().join("missing"), &bundle).unwrap());
+        assert_eq!(import_store(&desktop_path, &bundle).unwrap(), 1);
+        assert_eq!(import_store(&desktop_path, &bundle).unwrap(), 0);
+
+        let (_, merged) = frozen_at(&desktop_path, &[]).unwrap().remove(0);
+        assert_eq!(merged.len(), 2);
+        let shared = merged
+            .iter()
+            .find(|message| message.global_hash == "shared")
+            .unwrap();
+        assert_eq!(shared.stats.input_tokens, 10);
+    }
+
     #[test]
     fn removing_session_prevents_history_resurrection() {
         let directory = tempdir().unwrap();
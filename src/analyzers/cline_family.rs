@@ -0,0 +1,597 @@
+//! Shared implementation for [Cline](https://cline.bot) and the extensions
+//! forked from it: Roo Code, Kilo Code and Zoo Code.
+//!
+//! All of them keep one directory per task under the extension's VSCode
+//! `globalStorage`, holding `ui_messages.json` (the chat as shown, with an
+//! `api_req_started` entry carrying each request's usage) and
+//! `api_conversation_history.json` (what was sent to the model). The forks
+//! differ in where they record the model, how messages are indexed, and how
+//! far the logged cost can be trusted; [`ClineForkConfig`] captures those.
+
+use crate::analyzer::{
+    Analyzer, DataSource, discover_vscode_extension_sources, get_vscode_extension_tasks_dirs,
+    vscode_extension_glob_patterns, vscode_extension_has_sources,
+};
+use crate::contribution_cache::ContributionStrategy;
+use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at, resolve_cost};
+use crate::types::{Application, ConversationMessage, MessageRole, Stats};
+use crate::utils::hash_text;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::path::{Path, PathBuf};
+
+const UI_MESSAGES_FILE: &str = "ui_messages.json";
+const API_HISTORY_FILE: &str = "api_conversation_history.json";
+const TASK_METADATA_FILE: &str = "task_metadata.json";
+
+// ---------------------------------------------------------------------------
+// Per-fork configuration
+// ---------------------------------------------------------------------------
+
+/// Where a fork records the model behind each request.
+pub(crate) enum ModelSource {
+    /// `task_metadata.json`'s `model_usage`, matched by timestamp. Tasks
+    /// without the file are still being written and are skipped.
+    TaskMetadata,
+    /// The last `<model>` tag in the environment details sent with user turns.
+    EnvironmentDetails,
+}
+
+/// How messages are numbered within a task for their hashes.
+pub(crate) enum MessageIndex {
+    /// The `conversationHistoryIndex` Cline stores on each UI message.
+    ConversationHistory,
+    /// Position among the messages counted so far.
+    Sequential,
+}
+
+/// How far the `cost` logged with a request can be trusted.
+pub(crate) enum LoggedCost {
+    /// The extension's own estimate. Requests logged without one are priced
+    /// from the model.
+    Estimate,
+    /// What the provider billed. Kilo Code's gateway passes OpenRouter's price
+    /// through, so a request logged without a cost was free.
+    Billed,
+}
+
+/// Configuration that differentiates Cline-format analyzers.
+pub(crate) struct ClineForkConfig {
+    pub application: Application,
+    /// VSCode extension ID, e.g. `saoudrizwan.claude-dev`.
+    pub extension_id: &'static str,
+    pub model_source: ModelSource,
+    pub message_index: MessageIndex,
+    /// `ask` kinds that mark the user's turn.
+    pub user_asks: &'static [&'static str],
+    /// Whether requests carrying a `cancelReason` are left out.
+    pub skip_cancelled: bool,
+    /// Whether prompts that look like shell commands are passed over when
+    /// naming the session.
+    pub skip_commands_in_session_name: bool,
+    pub logged_cost: LoggedCost,
+}
+
+// ---------------------------------------------------------------------------
+// On-disk format
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct TaskMetadata {
+    model_usage: Vec<ModelUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelUsage {
+    ts: i64, // Timestamp in milliseconds
+    model_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum UiMessage {
+    Say {
+        ts: i64,
+        say: String,
+        #[serde(default)]
+        text: String,
+        #[serde(rename = "conversationHistoryIndex")]
+        conversation_history_index: Option<i64>,
+    },
+    Ask {
+        ts: i64,
+        ask: String,
+        #[serde(default)]
+        text: String,
+        #[serde(rename = "conversationHistoryIndex")]
+        conversation_history_index: Option<i64>,
+    },
+}
+
+/// The JSON embedded in an `api_req_started` message's text. Requests still
+/// in flight carry no token counts yet and don't parse.
+#[derive(Debug, Deserialize)]
+struct ApiRequest {
+    #[serde(rename = "tokensIn")]
+    tokens_in: u64,
+    #[serde(rename = "tokensOut")]
+    tokens_out: u64,
+    #[serde(rename = "cacheWrites")]
+    cache_writes: u64,
+    #[serde(rename = "cacheReads")]
+    cache_reads: u64,
+    cost: Option<f64>,
+    #[serde(rename = "cancelReason")]
+    cancel_reason: Option<String>,
+}
+
+/// Usage of one request, from either source.
+struct RequestUsage {
+    tokens_in: u64,
+    tokens_out: u64,
+    cache_writes: u64,
+    cache_reads: u64,
+    cost: Option<f64>,
+}
+
+impl From<ApiRequest> for RequestUsage {
+    fn from(request: ApiRequest) -> Self {
+        Self {
+            tokens_in: request.tokens_in,
+            tokens_out: request.tokens_out,
+            cache_writes: request.cache_writes,
+            cache_reads: request.cache_reads,
+            cost: request.cost,
+        }
+    }
+}
+
+/// An assistant turn from a v2 `api_conversation_history.json`, which
+/// records each request's `metrics` and `modelInfo` on the turn itself.
+struct HistoryRequest {
+    index: i64,
+    ts: i64,
+    model: Option<String>,
+    usage: RequestUsage,
+}
+
+/// What `api_conversation_history.json` contributes to a task.
+#[derive(Default)]
+struct ApiHistory {
+    /// Model from the last `<model>` tag in user turns' environment details.
+    environment_model: Option<String>,
+    /// Per-request usage, present only in the v2 format.
+    requests: Vec<HistoryRequest>,
+}
+
+/// The turns of an `api_conversation_history.json`: a bare array, or v2's
+/// `{"version": 2, "messages": [...]}`.
+fn history_turns(history: &OwnedValue) -> &[OwnedValue] {
+    history
+        .as_array()
+        .or_else(|| history.get("messages").and_then(|m| m.as_array()))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn read_api_history(task_dir: &Path) -> ApiHistory {
+    let mut api_history = ApiHistory::default();
+    let Ok(mut content) = std::fs::read(task_dir.join(API_HISTORY_FILE)) else {
+        return api_history;
+    };
+    let Ok(history) = simd_json::from_slice::<OwnedValue>(&mut content) else {
+        return api_history;
+    };
+
+    for (index, turn) in history_turns(&history).iter().enumerate() {
+        match turn.get("role").and_then(|r| r.as_str()) {
+            Some("user") => {
+                let Some(content) = turn.get("content").and_then(|c| c.as_array()) else {
+                    continue;
+                };
+                for item in content {
+                    if let Some(text) = item.get("text").and_then(|t| t.as_str())
+                        && let Some(model) = extract_model_from_text(text)
+                    {
+                        api_history.environment_model = Some(model);
+                    }
+                }
+            }
+            Some("assistant") => {
+                let Some(metrics) = turn.get("metrics") else {
+                    continue;
+                };
+                let tokens = metrics.get("tokens");
+                let count = |key: &str| {
+                    tokens
+                        .and_then(|t| t.get(key))
+                        .and_then(|n| n.as_u64())
+                        .unwrap_or(0)
+                };
+                api_history.requests.push(HistoryRequest {
+                    index: index as i64,
+                    ts: turn.get("ts").and_then(|ts| ts.as_i64()).unwrap_or(0),
+                    model: turn
+                        .get("modelInfo")
+                        .and_then(|info| info.get("modelId"))
+                        .and_then(|id| id.as_str())
+                        .map(str::to_string),
+                    usage: RequestUsage {
+                        tokens_in: count("prompt"),
+                        tokens_out: count("completion"),
+                        cache_writes: count("cacheWrites"),
+                        cache_reads: count("cached"),
+                        cost: metrics.get("cost").and_then(|c| c.as_f64()),
+                    },
+                });
+            }
+            _ => {}
+        }
+    }
+
+    api_history
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+// Helper function to extract project ID from a task path and hash it
+fn extract_and_hash_project_id(file_path: &Path) -> String {
+    // Path format: ~/.config/Code/User/globalStorage/{EXTENSION_ID}/tasks/{TASK_ID}/
+    // We'll use the parent directory of tasks as the project identifier (global storage path)
+
+    let path_components: Vec<_> = file_path.components().collect();
+    for (i, component) in path_components.iter().enumerate() {
+        if let std::path::Component::Normal(name) = component
+            && name.to_str() == Some("tasks")
+            && i > 0
+            && let std::path::Component::Normal(project_id) = &path_components[i - 1]
+            && let Some(project_id_str) = project_id.to_str()
+        {
+            return hash_text(project_id_str);
+        }
+    }
+
+    // Fallback: hash the full file path
+    hash_text(&file_path.to_string_lossy())
+}
+
+fn is_probably_tool_json_text(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    (trimmed.starts_with('{') || trimmed.starts_with("[{")) && trimmed.contains("\"tool\"")
+}
+
+fn is_probably_shell_command(text: &str) -> bool {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return false;
+    }
+
+    // Common shell command prefixes
+    let lower = trimmed.to_lowercase();
+    for prefix in [
+        "git ", "cargo ", "just ", "npm ", "pnpm ", "yarn ", "python ", "pytest ", "npx ", "pip ",
+        "bundle ", "rails ", "docker ", "kubectl ", "helm ", "make ", "go ", "node ", "deno ",
+        "bash ", "sh ", "zsh ",
+    ] {
+        if lower.starts_with(prefix) {
+            return true;
+        }
+    }
+
+    // Pipelines or multiple commands chained
+    if lower.contains(" && ") || lower.contains(" || ") || lower.contains(" | ") {
+        return true;
+    }
+
+    false
+}
+
+// Helper function to extract model from environment details text
+fn extract_model_from_text(text: &str) -> Option<String> {
+    // Look for <model>...</model> tags in the text
+    if let Some(start) = text.find("<model>")
+        && let Some(end) = text[start..].find("</model>")
+    {
+        let model = &text[start + 7..start + end];
+        return Some(model.to_string());
+    }
+    None
+}
+
+impl ClineForkConfig {
+    fn stats(&self, usage: RequestUsage, model: Option<&str>, date: DateTime<Utc>) -> Stats {
+        let logged = match self.logged_cost {
+            LoggedCost::Estimate => usage.cost,
+            LoggedCost::Billed => Some(usage.cost.unwrap_or(0.0)),
+        };
+        let mut stats = Stats {
+            input_tokens: usage.tokens_in,
+            output_tokens: usage.tokens_out,
+            cache_creation_tokens: usage.cache_writes,
+            cache_read_tokens: usage.cache_reads,
+            cached_tokens: usage.cache_writes + usage.cache_reads,
+            tool_calls: if usage.tokens_out > 0 { 1 } else { 0 },
+            ..Default::default()
+        };
+        stats.set_cost(resolve_cost(logged, || {
+            model.map_or(0.0, |model| {
+                calculate_total_cost_for_service_tier_at(
+                    model,
+                    ServiceTier::Standard,
+                    usage.tokens_in,
+                    usage.tokens_out,
+                    usage.cache_writes,
+                    usage.cache_reads,
+                    Some(date),
+                )
+            })
+        }));
+        stats
+    }
+
+    fn session_name_from(&self, text: &str) -> Option<String> {
+        if text.is_empty()
+            || is_probably_tool_json_text(text)
+            || (self.skip_commands_in_session_name && is_probably_shell_command(text))
+        {
+            return None;
+        }
+        Some(if text.chars().count() > 50 {
+            let chars: String = text.chars().take(50).collect();
+            format!("{}...", chars)
+        } else {
+            text.to_string()
+        })
+    }
+
+    /// Parse a single task directory.
+    fn parse_task_directory(&self, task_dir: &Path) -> Result<Vec<ConversationMessage>> {
+        let project_hash = extract_and_hash_project_id(task_dir);
+
+        // Get the conversation hash from the task directory name (timestamp or UUID)
+        let conversation_hash = task_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(hash_text)
+            .unwrap_or_else(|| hash_text(&task_dir.to_string_lossy()));
+
+        let metadata = match self.model_source {
+            ModelSource::TaskMetadata => {
+                let metadata_path = task_dir.join(TASK_METADATA_FILE);
+                if !metadata_path.exists() {
+                    return Ok(Vec::new());
+                }
+                let metadata: TaskMetadata =
+                    simd_json::from_slice(&mut std::fs::read(&metadata_path)?)
+                        .context("Failed to parse task_metadata.json")?;
+                Some(metadata)
+            }
+            ModelSource::EnvironmentDetails => None,
+        };
+        let api_history = read_api_history(task_dir);
+        let model_at = |ts: i64| match &metadata {
+            Some(metadata) => metadata.model_usage[..]
+                .iter()
+                .rev()
+                .find(|m| m.ts <= ts)
+                .map(|m| m.model_id.clone()),
+            None => api_history.environment_model.clone(),
+        };
+
+        let ui_messages_path = task_dir.join(UI_MESSAGES_FILE);
+        if !ui_messages_path.exists() {
+            return Ok(Vec::new());
+        }
+        let ui_messages: Vec<UiMessage> =
+            simd_json::from_slice(&mut std::fs::read(&ui_messages_path)?)
+                .context("Failed to parse ui_messages.json")?;
+
+        // v2 histories log every request's usage themselves, so the copies in
+        // `api_req_started` messages are skipped.
+        let usage_in_history = !api_history.requests.is_empty();
+
+        let mut entries = Vec::new();
+        let mut fallback_session_name: Option<String> = None;
+        let mut sequential_index = 0;
+
+        for message in ui_messages {
+            match message {
+                UiMessage::Say {
+                    ts,
+                    say,
+                    text,
+                    conversation_history_index,
+                } => {
+                    if usage_in_history || say != "api_req_started" || text.is_empty() {
+                        continue;
+                    }
+                    // Parse the embedded JSON in the text field
+                    let Ok(api_req) = simd_json::from_slice::<ApiRequest>(&mut text.into_bytes())
+                    else {
+                        continue;
+                    };
+                    if self.skip_cancelled && api_req.cancel_reason.is_some() {
+                        continue;
+                    }
+
+                    let index = match self.message_index {
+                        MessageIndex::ConversationHistory => {
+                            conversation_history_index.unwrap_or(sequential_index)
+                        }
+                        MessageIndex::Sequential => sequential_index,
+                    };
+                    let date = DateTime::from_timestamp_millis(ts).unwrap_or_else(Utc::now);
+                    let model = model_at(ts);
+
+                    entries.push(ConversationMessage {
+                        application: self.application,
+                        date,
+                        project_hash: project_hash.clone(),
+                        conversation_hash: conversation_hash.clone(),
+                        local_hash: Some(format!("{}-{}", conversation_hash, index)),
+                        global_hash: hash_text(&format!(
+                            "{}:{}:{}:{}",
+                            project_hash, conversation_hash, index, ts
+                        )),
+                        stats: self.stats(api_req.into(), model.as_deref(), date),
+                        model,
+                        role: MessageRole::Assistant, // API requests are from the assistant
+                        uuid: None,
+                        session_name: fallback_session_name.clone(),
+                        stable_session_id: None,
+                    });
+                    sequential_index += 1;
+                }
+                UiMessage::Ask {
+                    ts,
+                    ask,
+                    text,
+                    conversation_history_index,
+                } => {
+                    // Track user interactions (followup questions, confirmations)
+                    if !self.user_asks.contains(&ask.as_str()) {
+                        continue;
+                    }
+
+                    let index = match self.message_index {
+                        MessageIndex::ConversationHistory => {
+                            conversation_history_index.unwrap_or(sequential_index)
+                        }
+                        MessageIndex::Sequential => sequential_index,
+                    };
+                    let date = DateTime::from_timestamp_millis(ts).unwrap_or_else(Utc::now);
+                    if fallback_session_name.is_none() {
+                        fallback_session_name = self.session_name_from(&text);
+                    }
+
+                    entries.push(ConversationMessage {
+                        application: self.application,
+                        date,
+                        project_hash: project_hash.clone(),
+                        conversation_hash: conversation_hash.clone(),
+                        local_hash: Some(format!("{}-user-{}", conversation_hash, index)),
+                        global_hash: hash_text(&format!(
+                            "{}:{}:user:{}:{}",
+                            project_hash, conversation_hash, index, ts
+                        )),
+                        model: None,
+                        stats: Stats::default(), // User messages don't have token costs
+                        role: MessageRole::User,
+                        uuid: None,
+                        session_name: fallback_session_name.clone(),
+                        stable_session_id: None,
+                    });
+                    sequential_index += 1;
+                }
+            }
+        }
+
+        if usage_in_history {
+            for request in api_history.requests {
+                let date = DateTime::from_timestamp_millis(request.ts).unwrap_or_else(Utc::now);
+                let model = request.model.or_else(|| model_at(request.ts));
+                entries.push(ConversationMessage {
+                    application: self.application,
+                    date,
+                    project_hash: project_hash.clone(),
+                    conversation_hash: conversation_hash.clone(),
+                    local_hash: Some(format!("{}-{}", conversation_hash, request.index)),
+                    global_hash: hash_text(&format!(
+                        "{}:{}:{}:{}",
+                        project_hash, conversation_hash, request.index, request.ts
+                    )),
+                    stats: self.stats(request.usage, model.as_deref(), date),
+                    model,
+                    role: MessageRole::Assistant,
+                    uuid: None,
+                    session_name: fallback_session_name.clone(),
+                    stable_session_id: None,
+                });
+            }
+            entries.sort_by_key(|entry| entry.date);
+        }
+
+        Ok(entries)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Analyzer struct
+// ---------------------------------------------------------------------------
+
+/// Generic analyzer for any extension that uses Cline's task directory format.
+pub(crate) struct ClineFormatAnalyzer {
+    config: ClineForkConfig,
+}
+
+impl ClineFormatAnalyzer {
+    pub fn new(config: ClineForkConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl Analyzer for ClineFormatAnalyzer {
+    fn display_name(&self) -> &'static str {
+        self.config.application.display_name()
+    }
+
+    fn get_data_glob_patterns(&self) -> Vec<String> {
+        vscode_extension_glob_patterns(self.config.extension_id, UI_MESSAGES_FILE)
+    }
+
+    fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+        discover_vscode_extension_sources(self.config.extension_id, UI_MESSAGES_FILE, true)
+    }
+
+    fn is_available(&self) -> bool {
+        vscode_extension_has_sources(self.config.extension_id, UI_MESSAGES_FILE)
+    }
+
+    fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+        self.config.parse_task_directory(&source.path)
+    }
+
+    fn get_watch_directories(&self) -> Vec<PathBuf> {
+        get_vscode_extension_tasks_dirs(self.config.extension_id)
+    }
+
+    fn is_valid_data_path(&self, path: &Path) -> bool {
+        path.is_file() && path.file_name().is_some_and(|n| n == UI_MESSAGES_FILE)
+    }
+
+    fn contribution_strategy(&self) -> ContributionStrategy {
+        self.config.application.info().strategy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_project_hash() {
+        let path = PathBuf::from(
+            "/home/user/.config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks/1762355683581/ui_messages.json",
+        );
+        let parent = path.parent().unwrap();
+        let hash = extract_and_hash_project_id(parent);
+        assert!(!hash.is_empty());
+        assert_eq!(hash.len(), 64); // SHA256 hex length
+    }
+
+    #[test]
+    fn test_extract_model_from_text() {
+        assert_eq!(
+            extract_model_from_text("<environment_details><model>gpt-5</model>").as_deref(),
+            Some("gpt-5")
+        );
+        assert_eq!(extract_model_from_text("no model here"), None);
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use simd_json::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
 use crate::analyzer::{Analyzer, DataSource};
@@ -20,6 +23,12 @@ use std::sync::OnceLock;
 
 const DEFAULT_FALLBACK_MODEL: &str = "gpt-5";
 
+/// Thread names Codex records next to `sessions/`, one JSON object per line.
+const SESSION_INDEX_FILE: &str = "session_index.jsonl";
+
+/// Keys holding a thread's name, in the index and in `session_meta`.
+const THREAD_NAME_KEYS: [&str; 3] = ["thread_name", "title", "name"];
+
 static FALLBACK_MODEL: OnceLock<String> = OnceLock::new();
 
 pub(crate) fn get_fallback_model_with_home(home_dir: Option<PathBuf>) -> String {
@@ -51,15 +60,29 @@ fn get_fallback_model() -> &'static str {
     })
 }
 
-pub struct CodexCliAnalyzer;
+pub struct CodexCliAnalyzer {
+    /// Session directories from `[paths] codex_cli`, replacing the defaults.
+    session_dirs: Vec<PathBuf>,
+}
 
 impl CodexCliAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            session_dirs: Vec::new(),
+        }
     }
 
-    fn data_dirs() -> Vec<PathBuf> {
-        data_dirs_with_home(dirs::home_dir())
+    pub fn session_dirs(mut self, session_dirs: Vec<PathBuf>) -> Self {
+        self.session_dirs = session_dirs;
+        self
+    }
+
+    fn data_dirs(&self) -> Vec<PathBuf> {
+        if self.session_dirs.is_empty() {
+            data_dirs_with_home(dirs::home_dir())
+        } else {
+            self.session_dirs.clone()
+        }
     }
 }
 
@@ -138,27 +161,22 @@ pub(crate) fn is_valid_data_path_in(path: &Path, data_dirs: &[PathBuf]) -> bool
 #[async_trait]
 impl Analyzer for CodexCliAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Codex CLI"
+        Application::CodexCli.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
-        let mut patterns = Vec::new();
-
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_str = home_dir.to_string_lossy();
-            patterns.push(format!("{home_str}/.codex/sessions/**/*.jsonl"));
-            patterns.push(format!("{home_str}/.codex/archived_sessions/**/*.jsonl"));
-        }
-
-        patterns
+        self.data_dirs()
+            .iter()
+            .map(|dir| format!("{}/**/*.jsonl", dir.to_string_lossy()))
+            .collect()
     }
 
     fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
-        Ok(discover_data_sources_in(Self::data_dirs()))
+        Ok(discover_data_sources_in(self.data_dirs()))
     }
 
     fn is_available(&self) -> bool {
-        Self::data_dirs()
+        self.data_dirs()
             .into_iter()
             .filter(|d| d.is_dir())
             .flat_map(|dir| WalkDir::new(dir).into_iter())
@@ -175,14 +193,16 @@ impl Analyzer for CodexCliAnalyzer {
 
     // Codex CLI doesn't need deduplication since each session is separate
     fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
-        sources
-            .par_iter()
-            .flat_map(|source| self.parse_source(source).unwrap_or_default())
-            .collect()
+        crate::parse_io::parse_each(sources, |source| {
+            self.parse_source(source).unwrap_or_default()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
     }
 
     fn get_watch_directories(&self) -> Vec<PathBuf> {
-        Self::data_dirs()
+        self.data_dirs()
             .into_iter()
             .filter(|d| d.is_dir())
             .collect()
@@ -190,25 +210,27 @@ impl Analyzer for CodexCliAnalyzer {
 
     fn is_valid_data_path(&self, path: &Path) -> bool {
         // Must be a .jsonl file under one of the watched Codex data directories.
-        is_valid_data_path_in(path, &Self::data_dirs())
+        is_valid_data_path_in(path, &self.data_dirs())
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::CodexCli.info().strategy
     }
 }
 
 // CODEX CLI JSONL FILES SCHEMA - NEW WRAPPER FORMAT
 
+/// Token counts from `token_count` events. Some Codex releases write the
+/// cache and reasoning counts under the Chat Completions style names.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CodexCliTokenUsage {
     #[serde(default)]
     input_tokens: u64,
     #[serde(default)]
     output_tokens: u64,
-    #[serde(default)]
+    #[serde(default, alias = "cached_tokens", alias = "cache_read_input_tokens")]
     cached_input_tokens: u64,
-    #[serde(default)]
+    #[serde(default, alias = "reasoning_tokens")]
     reasoning_output_tokens: u64,
     #[serde(default)]
     total_tokens: u64,
@@ -265,6 +287,153 @@ struct CodexCliEventMsg {
     info: Option<CodexCliTokenCountInfo>,
 }
 
+/// Layouts Codex has used for rollout files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RolloutFormat {
+    /// Early releases: a bare header line (`id`, `timestamp`, `instructions`)
+    /// followed by response items as top-level objects without timestamps of
+    /// their own, interleaved with `{"record_type": "state"}` markers.
+    Legacy,
+    /// Every line is `{"timestamp", "type", "payload"}`.
+    Wrapped,
+}
+
+/// Decide from the first non-blank line. Unparseable files are treated as
+/// wrapped, which skips whatever doesn't fit.
+pub(crate) fn detect_rollout_format(buffer: &[u8]) -> RolloutFormat {
+    let Some(line) = buffer
+        .split(|&b| b == b'\n')
+        .find(|line| !line.iter().all(|b| b.is_ascii_whitespace()))
+    else {
+        return RolloutFormat::Wrapped;
+    };
+    match simd_json::to_owned_value(&mut line.to_vec()) {
+        Ok(value) if value.get("payload").is_none() && value.get("type").is_none() => {
+            RolloutFormat::Legacy
+        }
+        Ok(value) if value.get("payload").is_none() && value.get("record_type").is_some() => {
+            RolloutFormat::Legacy
+        }
+        _ => RolloutFormat::Wrapped,
+    }
+}
+
+/// Every entry of a rollout file in the wrapped shape, converting legacy
+/// lines: the header becomes `session_meta` and items become
+/// `response_item`s dated by the header, since they carry no timestamp.
+fn rollout_entries(buffer: &[u8]) -> Vec<CodexCliWrapper> {
+    let lines = buffer
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(|b| b.is_ascii_whitespace()));
+    match detect_rollout_format(buffer) {
+        // simd_json needs a mutable slice - copy one line at a time.
+        RolloutFormat::Wrapped => lines
+            .filter_map(|line| simd_json::from_slice::<CodexCliWrapper>(&mut line.to_vec()).ok())
+            .collect(),
+        RolloutFormat::Legacy => {
+            let mut last_timestamp: Option<DateTime<Utc>> = None;
+            lines
+                .filter_map(|line| {
+                    let value = simd_json::to_owned_value(&mut line.to_vec()).ok()?;
+                    if value.get("record_type").is_some() {
+                        return None;
+                    }
+                    if let Some(timestamp) = value
+                        .get("timestamp")
+                        .and_then(|v| v.as_str())
+                        .and_then(|v| v.parse::<DateTime<Utc>>().ok())
+                    {
+                        last_timestamp = Some(timestamp);
+                    }
+                    let entry_type = if value.get("type").is_some() {
+                        "response_item"
+                    } else if value.get("id").is_some() {
+                        "session_meta"
+                    } else {
+                        return None;
+                    };
+                    Some(CodexCliWrapper {
+                        timestamp: last_timestamp?,
+                        entry_type: entry_type.to_string(),
+                        payload: value,
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// The session UUID at the end of `rollout-<date>T<time>-<uuid>.jsonl`.
+fn session_id_from_file_name(file_path: &Path) -> Option<String> {
+    let stem = file_path.file_stem()?.to_str()?;
+    let id = stem.get(stem.len().checked_sub(36)?..)?;
+    (id.len() == 36 && id.chars().filter(|&c| c == '-').count() == 4).then(|| id.to_string())
+}
+
+/// The `.codex` directory a rollout file belongs to.
+fn codex_home_of(file_path: &Path) -> Option<&Path> {
+    file_path
+        .ancestors()
+        .find(|path| {
+            matches!(
+                path.file_name().and_then(|name| name.to_str()),
+                Some("sessions" | "archived_sessions")
+            )
+        })?
+        .parent()
+}
+
+fn thread_name(value: &simd_json::OwnedValue) -> Option<String> {
+    THREAD_NAME_KEYS
+        .iter()
+        .find_map(|key| value.get(*key).and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// Session id to thread name from `session_index.jsonl`; later lines win.
+pub(crate) fn parse_session_index(content: &str) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for line in content.lines() {
+        let Ok(value) = simd_json::to_owned_value(&mut line.as_bytes().to_vec()) else {
+            continue;
+        };
+        let Some(id) = value.get("id").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        match thread_name(&value) {
+            Some(name) => names.insert(id.to_string(), name),
+            None => names.remove(id),
+        };
+    }
+    names
+}
+
+type SessionIndex = Arc<HashMap<String, String>>;
+
+/// Parsed indexes by path, reread when the file's mtime changes, since every
+/// rollout file of a home looks names up in the same index.
+static SESSION_INDEXES: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, SessionIndex)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The thread name the user gave session `id`, if any.
+fn indexed_session_name(file_path: &Path, id: &str) -> Option<String> {
+    let index_path = codex_home_of(file_path)?.join(SESSION_INDEX_FILE);
+    let modified = std::fs::metadata(&index_path).ok()?.modified().ok()?;
+    let mut indexes = SESSION_INDEXES.lock();
+    let index = match indexes.get(&index_path) {
+        Some((cached_at, index)) if *cached_at == modified => index.clone(),
+        _ => {
+            let content = std::fs::read_to_string(&index_path).ok()?;
+            let index = Arc::new(parse_session_index(&content));
+            indexes.insert(index_path, (modified, index.clone()));
+            index
+        }
+    };
+    index.get(id).cloned()
+}
+
 // Wrapper structure for all entries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CodexCliWrapper {
@@ -324,31 +493,25 @@ pub(crate) fn parse_codex_cli_jsonl_file(
     let mut saw_token_usage = false;
     let mut _turn_context: Option<CodexCliTurnContext> = None;
     let mut current_tool_call_ids: HashSet<String> = HashSet::with_capacity(20);
-    let mut session_name: Option<String> = None;
+    // A name the user gave the thread beats summaries and the first prompt.
+    let mut session_name: Option<String> =
+        session_id_from_file_name(file_path).and_then(|id| indexed_session_name(file_path, &id));
     let mut fallback_session_name: Option<String> = None;
 
-    for line in buffer.split(|&b| b == b'\n') {
-        // Skip empty lines
-        if line.is_empty() || line.iter().all(|&b| b.is_ascii_whitespace()) {
-            continue;
-        }
-
-        // simd_json needs mutable slice - copy this line only
-        let mut line_buf = line.to_vec();
-        let wrapper = match simd_json::from_slice::<CodexCliWrapper>(&mut line_buf) {
-            Ok(wrapper) => wrapper,
-            Err(_) => continue,
-        };
-
+    for wrapper in rollout_entries(&buffer) {
         match wrapper.entry_type.as_str() {
             "session_meta" => {
                 // Try to parse the payload as session metadata
                 let mut payload_bytes = simd_json::to_vec(&wrapper.payload)?;
-                if let Ok(_session_meta) =
+                if let Ok(session_meta) =
                     simd_json::from_slice::<CodexCliSessionMeta>(&mut payload_bytes)
                 {
                     session_model =
                         extract_model_from_value(&wrapper.payload).map(SessionModel::explicit);
+                    if session_name.is_none() {
+                        session_name = indexed_session_name(file_path, &session_meta.id)
+                            .or_else(|| thread_name(&wrapper.payload));
+                    }
                 }
             }
             "turn_context" => {
@@ -478,6 +641,7 @@ pub(crate) fn parse_codex_cli_jsonl_file(
                                 role: MessageRole::User,
                                 uuid: None,
                                 session_name: effective_name,
+                                stable_session_id: None,
                             });
                         }
                         // Token usage is now emitted immediately when processing token_count
@@ -516,6 +680,7 @@ pub(crate) fn parse_codex_cli_jsonl_file(
                                 session_name: session_name
                                     .clone()
                                     .or_else(|| fallback_session_name.clone()),
+                                stable_session_id: None,
                             });
                         }
                         _ => {}
@@ -584,6 +749,7 @@ pub(crate) fn parse_codex_cli_jsonl_file(
                                 session_name: session_name
                                     .clone()
                                     .or_else(|| fallback_session_name.clone()),
+                                stable_session_id: None,
                             });
 
                             saw_token_usage = true;
@@ -602,6 +768,55 @@ pub(crate) fn parse_codex_cli_jsonl_file(
     Ok((entries, detected_model))
 }
 
+/// The last usage-window report in a rollout. Codex logs a `rate_limits`
+/// object with its token counts: `primary` (the five-hour window) and
+/// `secondary` (the weekly one), each with `used_percent`, `window_minutes`
+/// and either `resets_at` (Unix seconds) or `resets_in_seconds`.
+pub(crate) fn latest_rate_limits(file_path: &Path) -> Result<Vec<crate::limits::UsageWindow>> {
+    let buffer = std::fs::read(file_path)?;
+    let Some((reported_at, limits)) = rollout_entries(&buffer)
+        .into_iter()
+        .filter(|wrapper| wrapper.entry_type == "event_msg")
+        .filter_map(|wrapper| {
+            let limits = wrapper.payload.get("rate_limits")?.clone();
+            Some((wrapper.timestamp, limits))
+        })
+        .next_back()
+    else {
+        return Ok(Vec::new());
+    };
+
+    let windows = ["primary", "secondary"]
+        .iter()
+        .filter_map(|key| {
+            let window = limits.get(*key)?;
+            let used_percent = window.get("used_percent")?.cast_f64()?;
+            let resets_at = match window.get("resets_at").and_then(|v| v.cast_f64()) {
+                Some(at) => DateTime::from_timestamp(at as i64, 0)?,
+                None => {
+                    let seconds = window.get("resets_in_seconds")?.cast_f64()?;
+                    reported_at + chrono::TimeDelta::seconds(seconds as i64)
+                }
+            };
+            let window_minutes = window.get("window_minutes").and_then(|v| v.cast_f64());
+            let kind = match window_minutes {
+                Some(minutes) if minutes >= 24.0 * 60.0 => crate::limits::WindowKind::Weekly,
+                Some(_) => crate::limits::WindowKind::FiveHour,
+                None if *key == "secondary" => crate::limits::WindowKind::Weekly,
+                None => crate::limits::WindowKind::FiveHour,
+            };
+            Some(crate::limits::UsageWindow {
+                tool: Application::CodexCli.display_name().to_string(),
+                kind,
+                resets_at,
+                tokens: None,
+                used: Some(used_percent / 100.0),
+            })
+        })
+        .collect();
+    Ok(windows)
+}
+
 fn calculate_cost_from_tokens(
     usage: &CodexCliTokenUsage,
     model_name: &str,
@@ -0,0 +1,244 @@
+use crate::analyzer::{Analyzer, DataSource};
+use crate::config::CustomAnalyzerConfig;
+use crate::contribution_cache::ContributionStrategy;
+use crate::models::{calculate_total_cost, resolve_cost};
+use crate::types::{Application, ConversationMessage, MessageRole, Stats};
+use crate::utils::{expand_home, hash_text};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use simd_json::prelude::*;
+use std::path::{Component, Path, PathBuf};
+
+/// Analyzer for arbitrary JSONL logs described by a `[[custom_analyzer]]` config section.
+pub struct ConfigurableAnalyzer {
+    /// Leaked once at startup so it satisfies `display_name`'s `&'static str`.
+    name: &'static str,
+    pattern: String,
+    config: CustomAnalyzerConfig,
+}
+
+impl ConfigurableAnalyzer {
+    pub fn new(config: CustomAnalyzerConfig) -> Self {
+        let name: &'static str = Box::leak(config.name.clone().into_boxed_str());
+        let pattern = expand_home(&config.glob);
+        Self {
+            name,
+            pattern,
+            config,
+        }
+    }
+
+    /// Directory portion of the glob before the first wildcard component.
+    fn base_dir(&self) -> PathBuf {
+        Path::new(&self.pattern)
+            .components()
+            .take_while(|c| match c {
+                Component::Normal(part) => !part.to_string_lossy().contains(['*', '?', '[', '{']),
+                _ => true,
+            })
+            .collect()
+    }
+
+    fn parse_line(
+        &self,
+        value: &simd_json::OwnedValue,
+        line: &[u8],
+        project_hash: &str,
+        file_conversation_hash: &str,
+    ) -> Option<ConversationMessage> {
+        let date = lookup(value, &self.config.timestamp).and_then(parse_timestamp)?;
+
+        let model = self
+            .config
+            .model
+            .as_deref()
+            .and_then(|path| lookup(value, path))
+            .and_then(|v| v.as_str())
+            .filter(|m| !m.is_empty())
+            .map(str::to_string);
+
+        let mut stats = Stats {
+            input_tokens: self
+                .config
+                .input_tokens
+                .as_deref()
+                .and_then(|path| lookup(value, path))
+                .and_then(as_u64_lenient)
+                .unwrap_or(0),
+            output_tokens: self
+                .config
+                .output_tokens
+                .as_deref()
+                .and_then(|path| lookup(value, path))
+                .and_then(as_u64_lenient)
+                .unwrap_or(0),
+            ..Stats::default()
+        };
+
+        let logged_cost = self
+            .config
+            .cost
+            .as_deref()
+            .and_then(|path| lookup(value, path))
+            .and_then(|v| v.cast_f64());
+        let cost = resolve_cost(logged_cost, || {
+            model.as_deref().map_or(0.0, |model| {
+                calculate_total_cost(model, stats.input_tokens, stats.output_tokens, 0, 0)
+            })
+        });
+        stats.set_cost(cost);
+
+        let conversation_hash = self
+            .config
+            .session
+            .as_deref()
+            .and_then(|path| lookup(value, path))
+            .and_then(scalar_to_string)
+            .map(|session| hash_text(&format!("{}_{session}", self.name)))
+            .unwrap_or_else(|| file_conversation_hash.to_string());
+
+        // Hash the raw line so copies of the same log in several files dedupe.
+        let global_hash = hash_text(&format!("{}_{}", self.name, String::from_utf8_lossy(line)));
+
+        Some(ConversationMessage {
+            application: Application::Custom,
+            date,
+            project_hash: project_hash.to_string(),
+            conversation_hash,
+            local_hash: None,
+            global_hash,
+            model,
+            stats,
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        })
+    }
+}
+
+/// Resolve a dotted path such as `usage.input_tokens` or `choices.0.model`.
+fn lookup<'a>(value: &'a simd_json::OwnedValue, path: &str) -> Option<&'a simd_json::OwnedValue> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| {
+            if let Some(array) = current.as_array() {
+                segment.parse::<usize>().ok().and_then(|i| array.get(i))
+            } else {
+                current.as_object()?.get(segment)
+            }
+        })
+}
+
+fn as_u64_lenient(value: &simd_json::OwnedValue) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_f64().filter(|f| *f >= 0.0).map(|f| f as u64))
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn scalar_to_string(value: &simd_json::OwnedValue) -> Option<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| value.as_i64().map(|n| n.to_string()))
+        .or_else(|| value.as_u64().map(|n| n.to_string()))
+}
+
+fn parse_timestamp(value: &simd_json::OwnedValue) -> Option<DateTime<Utc>> {
+    if let Some(s) = value.as_str() {
+        return DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+
+    let raw = value.cast_f64()?;
+    // Values past ~2001-09-09 in milliseconds exceed 1e12; seconds stay far below it.
+    let millis = if raw.abs() >= 1e12 { raw } else { raw * 1000.0 };
+    Utc.timestamp_millis_opt(millis as i64).single()
+}
+
+#[async_trait]
+impl Analyzer for ConfigurableAnalyzer {
+    fn display_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn get_data_glob_patterns(&self) -> Vec<String> {
+        vec![self.pattern.clone()]
+    }
+
+    fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+        let sources = glob::glob(&self.pattern)?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .map(|path| DataSource { path })
+            .collect();
+        Ok(sources)
+    }
+
+    fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+        let buffer = std::fs::read(&source.path)?;
+        let project_hash = source
+            .path
+            .parent()
+            .map(|parent| hash_text(&parent.to_string_lossy()))
+            .unwrap_or_default();
+        let conversation_hash = hash_text(&source.path.to_string_lossy());
+
+        let mut messages = Vec::new();
+        for (i, line) in buffer.split(|&b| b == b'\n').enumerate() {
+            if line.iter().all(|b| b.is_ascii_whitespace()) {
+                continue;
+            }
+
+            let mut line_buf = line.to_vec();
+            let value = match simd_json::to_owned_value(&mut line_buf) {
+                Ok(value) => value,
+                Err(e) => {
+                    crate::diagnostics::parse_warning(
+                        self.name,
+                        &source.path,
+                        format!(
+                            "Skipping invalid entry in {} line {}: {}",
+                            source.path.display(),
+                            i + 1,
+                            e
+                        ),
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(message) = self.parse_line(&value, line, &project_hash, &conversation_hash)
+            {
+                messages.push(message);
+            }
+        }
+
+        Ok(messages)
+    }
+
+    fn get_watch_directories(&self) -> Vec<PathBuf> {
+        let base = self.base_dir();
+        if base.is_dir() {
+            vec![base]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn is_valid_data_path(&self, path: &Path) -> bool {
+        path.is_file()
+            && glob::Pattern::new(&self.pattern).is_ok_and(|pattern| pattern.matches_path(path))
+    }
+
+    fn contribution_strategy(&self) -> ContributionStrategy {
+        if self.config.session.is_some() {
+            ContributionStrategy::MultiSession
+        } else {
+            ContributionStrategy::SingleSession
+        }
+    }
+}
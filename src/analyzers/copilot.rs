@@ -372,6 +372,7 @@ pub(crate) fn parse_copilot_session_file(session_file: &Path) -> Result<Vec<Conv
             role: MessageRole::User,
             uuid: None,
             session_name: fallback_session_name.clone(),
+            stable_session_id: None,
         });
 
         // Create assistant message
@@ -390,6 +391,9 @@ pub(crate) fn parse_copilot_session_file(session_file: &Path) -> Result<Vec<Conv
             tool_calls,
             input_tokens,
             output_tokens,
+            // Each chat request is one premium request, however many tool
+            // calls it makes.
+            premium_requests: crate::premium::multiplier(model.as_deref().unwrap_or_default()),
             ..Default::default()
         };
 
@@ -409,19 +413,20 @@ pub(crate) fn parse_copilot_session_file(session_file: &Path) -> Result<Vec<Conv
         // Cost: Copilot IDE sessions don't natively record a dollar cost. Honor an
         // explicit provider-reported cost when present; otherwise price the counted
         // tokens with the shared pricing table, consistent with the other analyzers.
-        if let Some(cost) = request.cost.filter(|c| *c > 0.0) {
-            stats.cost = cost;
-        } else if let Some(model_name) = &model {
-            stats.cost = crate::models::calculate_total_cost_for_service_tier_at(
-                model_name,
-                crate::models::ServiceTier::Standard,
-                stats.input_tokens,
-                stats.output_tokens,
-                stats.cache_creation_tokens,
-                stats.cache_read_tokens,
-                Some(assistant_date),
-            );
-        }
+        let cost = crate::models::resolve_cost(request.cost.filter(|c| *c > 0.0), || {
+            model.as_deref().map_or(0.0, |model_name| {
+                crate::models::calculate_total_cost_for_service_tier_at(
+                    model_name,
+                    crate::models::ServiceTier::Standard,
+                    stats.input_tokens,
+                    stats.output_tokens,
+                    stats.cache_creation_tokens,
+                    stats.cache_read_tokens,
+                    Some(assistant_date),
+                )
+            })
+        });
+        stats.set_cost(cost);
 
         entries.push(ConversationMessage {
             application: Application::Copilot,
@@ -435,6 +440,7 @@ pub(crate) fn parse_copilot_session_file(session_file: &Path) -> Result<Vec<Conv
             role: MessageRole::Assistant,
             uuid: None,
             session_name: fallback_session_name.clone(),
+            stable_session_id: None,
         });
     }
 
@@ -444,7 +450,7 @@ pub(crate) fn parse_copilot_session_file(session_file: &Path) -> Result<Vec<Conv
 #[async_trait]
 impl Analyzer for CopilotAnalyzer {
     fn display_name(&self) -> &'static str {
-        "GitHub Copilot"
+        Application::Copilot.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -517,7 +523,7 @@ impl Analyzer for CopilotAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::Copilot.info().strategy
     }
 }
 
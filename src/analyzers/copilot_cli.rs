@@ -283,7 +283,7 @@ fn extract_cli_tool_text(tool_name: &str, arguments: &simd_json::OwnedValue) ->
     }
 }
 
-fn apply_cli_tool_stats(stats: &mut Stats, tool_name: &str) {
+fn apply_cli_tool_stats(stats: &mut Stats, tool_name: &str, arguments: &simd_json::OwnedValue) {
     match tool_name {
         "read_file" => stats.files_read += 1,
         "replace_string_in_file" | "multi_replace_string_in_file" => stats.files_edited += 1,
@@ -291,7 +291,12 @@ fn apply_cli_tool_stats(stats: &mut Stats, tool_name: &str) {
         "delete_file" => stats.files_deleted += 1,
         "file_search" => stats.file_searches += 1,
         "grep_search" | "semantic_search" => stats.file_content_searches += 1,
-        "run_in_terminal" | "bash" | "shell" | "powershell" => stats.terminal_commands += 1,
+        "run_in_terminal" | "bash" | "shell" | "powershell" => {
+            stats.terminal_commands += 1;
+            if let Some(command) = arguments.get("command").and_then(|v| v.as_str()) {
+                stats.add_command(command);
+            }
+        }
         _ => {}
     }
 }
@@ -349,6 +354,7 @@ fn push_copilot_cli_user_message(
         role: MessageRole::User,
         uuid: None,
         session_name: session_name.cloned(),
+        stable_session_id: None,
     });
 
     *user_index += 1;
@@ -542,6 +548,23 @@ fn apply_copilot_cli_live_prompt_overhead(
     }
 }
 
+/// Bill one premium request per prompt, on the first assistant turn that
+/// answers it. Runs last so models filled in from shutdown metrics count.
+fn count_copilot_cli_premium_requests(entries: &mut [ConversationMessage]) {
+    let mut awaiting_answer = false;
+    for message in entries.iter_mut() {
+        match message.role {
+            MessageRole::User => awaiting_answer = true,
+            MessageRole::Assistant if awaiting_answer => {
+                message.stats.premium_requests =
+                    crate::premium::multiplier(message.model.as_deref().unwrap_or_default());
+                awaiting_answer = false;
+            }
+            _ => {}
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn flush_copilot_cli_turn(
     entries: &mut Vec<ConversationMessage>,
@@ -622,6 +645,7 @@ fn flush_copilot_cli_turn(
             role: MessageRole::Assistant,
             uuid: None,
             session_name: session_name.cloned(),
+            stable_session_id: None,
         });
 
         *assistant_index += 1;
@@ -686,6 +710,9 @@ pub(crate) fn parse_copilot_cli_session_file(
         .map(|id| hash_text(id))
         .unwrap_or_else(|| hash_text(&session_file.to_string_lossy()));
     let project_hash = extract_copilot_cli_project_hash(workspace_path.as_deref());
+    if let Some(workspace) = &workspace_path {
+        crate::utils::remember_project(&project_hash, workspace);
+    }
 
     let mut entries = Vec::new();
     let mut pending_user: Option<CopilotCliPendingUser> = None;
@@ -947,7 +974,7 @@ pub(crate) fn parse_copilot_cli_session_file(
                         .cloned()
                         .unwrap_or_else(simd_json::OwnedValue::null);
 
-                    apply_cli_tool_stats(&mut turn.stats, tool_name);
+                    apply_cli_tool_stats(&mut turn.stats, tool_name, &arguments);
                     turn.tool_request_parts
                         .push(extract_cli_tool_text(tool_name, &arguments));
 
@@ -1071,6 +1098,7 @@ pub(crate) fn parse_copilot_cli_session_file(
         &mut entries[shutdown_segment_start..],
         live_context.static_prompt_tokens,
     );
+    count_copilot_cli_premium_requests(&mut entries);
 
     Ok(entries)
 }
@@ -1078,7 +1106,7 @@ pub(crate) fn parse_copilot_cli_session_file(
 #[async_trait]
 impl Analyzer for CopilotCliAnalyzer {
     fn display_name(&self) -> &'static str {
-        "GitHub Copilot CLI"
+        Application::CopilotCli.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -1132,6 +1160,6 @@ impl Analyzer for CopilotCliAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::CopilotCli.info().strategy
     }
 }
@@ -1,154 +1,149 @@
 use crate::analyzer::{Analyzer, DataSource};
+use crate::analyzers::lenient::{Drift, LenientValue};
 use crate::contribution_cache::ContributionStrategy;
 use crate::models::{
     ServiceTier, calculate_cache_cost_for_service_tier_at,
     calculate_input_cost_for_service_tier_at, calculate_output_cost_for_service_tier_at,
 };
 use crate::types::{Application, ConversationMessage, FileCategory, MessageRole, Stats};
-use crate::utils::{deserialize_utc_timestamp, hash_text};
+use crate::utils::hash_text;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
 use simd_json::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-pub struct GeminiCliAnalyzer;
+pub struct GeminiCliAnalyzer {
+    /// `tmp` directories from `[paths] gemini_cli`, replacing `~/.gemini/tmp`.
+    tmp_dirs: Vec<PathBuf>,
+}
 
 impl GeminiCliAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            tmp_dirs: Vec::new(),
+        }
     }
 
-    fn data_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".gemini").join("tmp"))
+    pub fn tmp_dirs(mut self, tmp_dirs: Vec<PathBuf>) -> Self {
+        self.tmp_dirs = tmp_dirs;
+        self
     }
-}
 
-// Gemini CLI-specific data structures following the plan's simplified flat approach
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GeminiCliSession {
-    session_id: String,
-    project_hash: String,
-    start_time: String,
-    last_updated: String,
-    messages: Vec<GeminiCliMessage>,
+    fn data_dirs(&self) -> Vec<PathBuf> {
+        if self.tmp_dirs.is_empty() {
+            Application::GeminiCli
+                .default_data_dir()
+                .into_iter()
+                .collect()
+        } else {
+            self.tmp_dirs.clone()
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "camelCase")]
+/// A Gemini CLI session message, read leniently: the upstream schema has
+/// changed shape more than once (see issue #137), so fields that don't
+/// match what's expected are dropped with a warning instead of failing the
+/// session.
+#[derive(Debug, Clone)]
 enum GeminiCliMessage {
     User {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
         timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
+        text: String,
     },
     Gemini {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
         timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
         model: String,
-        #[serde(default)]
-        thoughts: Vec<simd_json::OwnedValue>,
         tokens: Option<GeminiCliTokens>,
-        #[serde(rename = "toolCalls", default)]
         tool_calls: Vec<simd_json::OwnedValue>,
     },
-    System {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
-        timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
-    },
-    Error {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
-        timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
-    },
-    Info {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
-        timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
-    },
-    Warning {
-        id: String,
-        #[serde(deserialize_with = "deserialize_utc_timestamp")]
-        timestamp: DateTime<Utc>,
-        #[serde(default)]
-        content: Option<GeminiCliContent>,
-    },
+    /// `system`, `error`, `info` and `warning` messages carry no usage.
+    Other,
 }
 
-/// A single `Part` from Gemini CLI's multi-modal content. Only `text` is
-/// consumed by splitrail; any other fields (e.g. `inlineData`, `fileData`,
-/// `functionCall`) are silently ignored by serde's default behaviour, which
-/// is what we want — the schema is still evolving upstream and we don't want
-/// unrecognised part kinds to abort parsing.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct GeminiCliPart {
-    #[serde(default)]
-    text: Option<String>,
-}
+/// Message types Gemini CLI writes that carry no usage.
+const NON_USAGE_MESSAGE_TYPES: [&str; 4] = ["system", "error", "info", "warning"];
 
-/// The `content` field on a Gemini CLI message. Mirrors `PartListUnion` from
-/// `@google/genai`: it can be a plain string (legacy format), a single `Part`
-/// object, or an array of `Part` objects (current format, since Gemini CLI
-/// changed its schema — see issue #137).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-enum GeminiCliContent {
-    Text(String),
-    Part(GeminiCliPart),
-    Parts(Vec<GeminiCliPart>),
-}
+/// File name prefix of saved chat checkpoints.
+const CHECKPOINT_PREFIX: &str = "checkpoint-";
 
-impl GeminiCliContent {
-    /// Extract a concatenated plain-text representation of the content,
-    /// ignoring non-text parts (images, function calls, etc.).
-    fn as_text(&self) -> String {
-        match self {
-            GeminiCliContent::Text(s) => s.clone(),
-            GeminiCliContent::Part(p) => p.text.clone().unwrap_or_default(),
-            GeminiCliContent::Parts(ps) => {
-                let mut out = String::new();
-                for p in ps {
-                    if let Some(t) = &p.text {
-                        out.push_str(t);
-                    }
+impl GeminiCliMessage {
+    /// The message's id and contents, or `None` when it can't be placed.
+    fn read(value: &LenientValue) -> Option<(String, Self)> {
+        let id = value.get("id").required(LenientValue::str)?.to_string();
+        let message_type = value.get("type").required(LenientValue::str)?;
+        let timestamp = value.get("timestamp").required(LenientValue::timestamp)?;
+        let message = match message_type {
+            "user" => GeminiCliMessage::User {
+                timestamp,
+                text: value.get("content").text(),
+            },
+            "gemini" => {
+                let tokens = value.get("tokens");
+                GeminiCliMessage::Gemini {
+                    timestamp,
+                    model: value
+                        .get("model")
+                        .required(LenientValue::str)
+                        .unwrap_or("unknown")
+                        .to_string(),
+                    tokens: tokens.is_present().then(|| GeminiCliTokens::read(&tokens)),
+                    tool_calls: value
+                        .get("toolCalls")
+                        .items()
+                        .iter()
+                        .filter_map(LenientValue::raw)
+                        .cloned()
+                        .collect(),
                 }
-                out
             }
-        }
+            other if NON_USAGE_MESSAGE_TYPES.contains(&other) => GeminiCliMessage::Other,
+            other => {
+                value
+                    .get("type")
+                    .note(format!("unknown message type {other:?}"));
+                GeminiCliMessage::Other
+            }
+        };
+        Some((id, message))
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default)]
 struct GeminiCliTokens {
-    #[serde(default)]
     input: u64,
-    #[serde(default)]
     output: u64,
-    #[serde(default)]
     cached: u64,
-    #[serde(default)]
     thoughts: u64,
-    #[serde(default)]
     tool: u64,
-    #[serde(default)]
-    total: u64,
+}
+
+impl GeminiCliTokens {
+    fn read(tokens: &LenientValue) -> Self {
+        let count = |key| tokens.get(key).u64().unwrap_or_default();
+        Self {
+            input: count("input"),
+            output: count("output"),
+            cached: count("cached"),
+            thoughts: count("thoughts"),
+            tool: count("tool"),
+        }
+    }
+
+    /// Whether `next` looks like a later streamed chunk of the same response.
+    /// Some Gemini CLI builds log cumulative usage on every chunk: the prompt
+    /// side stays fixed while the generated side only grows, so summing the
+    /// chunks would count the response several times over.
+    fn is_continued_by(&self, next: &GeminiCliTokens) -> bool {
+        next.input == self.input
+            && next.cached == self.cached
+            && next.output >= self.output
+            && next.thoughts >= self.thoughts
+            && next.tool >= self.tool
+    }
 }
 
 // Tool extraction and file operation mapping
@@ -181,6 +176,7 @@ fn extract_tool_stats(tool_calls: &[simd_json::OwnedValue]) -> Stats {
                     } else {
                         continue;
                     };
+                    stats.add_file_path(path_str);
                     let ext = std::path::Path::new(path_str)
                         .extension()
                         .and_then(|e| e.to_str())
@@ -203,12 +199,26 @@ fn extract_tool_stats(tool_calls: &[simd_json::OwnedValue]) -> Stats {
             }
             "replace" => {
                 stats.files_edited += 1;
+                if let Some(path) = tool_call
+                    .get("args")
+                    .and_then(|v| v.get("file_path"))
+                    .and_then(|v| v.as_str())
+                {
+                    stats.add_file_path(path);
+                }
                 // Simple counting without complex content analysis
                 stats.lines_edited += 10; // Conservative estimate
                 stats.bytes_edited += 800;
             }
             "run_shell_command" => {
                 stats.terminal_commands += 1;
+                if let Some(command) = tool_call
+                    .get("args")
+                    .and_then(|v| v.get("command"))
+                    .and_then(|v| v.as_str())
+                {
+                    stats.add_command(command);
+                }
             }
             "list_directory" => {
                 // Treat as a lightweight read operation
@@ -276,6 +286,19 @@ fn calculate_gemini_cost(
     input_cost + output_cost + cache_cost
 }
 
+/// Checkpoints (`/chat save`, `/restore`) are copies of a chat's history and
+/// would count its usage twice.
+fn is_checkpoint_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(CHECKPOINT_PREFIX))
+        || path.ancestors().skip(1).any(|ancestor| {
+            ancestor
+                .file_name()
+                .is_some_and(|name| name == "checkpoints")
+        })
+}
+
 fn is_gemini_cli_chat_path(path: &Path) -> bool {
     path.is_file()
         && path
@@ -286,56 +309,113 @@ fn is_gemini_cli_chat_path(path: &Path) -> bool {
             .ancestors()
             .skip(1)
             .any(|ancestor| ancestor.file_name().is_some_and(|name| name == "chats"))
+        && !is_checkpoint_path(path)
+}
+
+/// Drops `session.json` when `session.jsonl` sits next to it: newer Gemini
+/// CLI builds migrate a chat to JSONL and may leave the old file behind.
+fn drop_migrated_json(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let jsonl: HashSet<PathBuf> = paths
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .map(|path| path.with_extension(""))
+        .collect();
+    paths.retain(|path| {
+        path.extension().is_some_and(|ext| ext == "jsonl")
+            || !jsonl.contains(&path.with_extension(""))
+    });
+    paths
+}
+
+fn is_internal_session_context(text: &str) -> bool {
+    text.trim_start().starts_with("<session_context>")
+}
+
+/// The latest `gemini` entry of the current turn, kept so that streamed
+/// chunks of the same response can be folded into it.
+struct OpenResponse {
+    entry: usize,
+    model: String,
+    /// When the latest chunk was logged.
+    last_chunk: DateTime<Utc>,
+    tokens: GeminiCliTokens,
+    tool_calls: Vec<simd_json::OwnedValue>,
+}
+
+fn response_stats(
+    tokens: &GeminiCliTokens,
+    model: &str,
+    tool_calls: &[simd_json::OwnedValue],
+    timestamp: DateTime<Utc>,
+) -> Stats {
+    let mut stats = extract_tool_stats(tool_calls);
+
+    stats.input_tokens = tokens.input;
+    stats.output_tokens = tokens.output;
+    stats.reasoning_tokens = tokens.thoughts;
+    stats.cache_creation_tokens = 0;
+    stats.cache_read_tokens = 0;
+    stats.cached_tokens = tokens.cached;
+    stats.cost = calculate_gemini_cost(tokens, model, timestamp);
+    stats.tool_calls = tool_calls.len() as u32;
+    stats
+}
+
+/// Identifies a message across files, so a resumed session that copies
+/// earlier messages into a new chat file doesn't count them again.
+fn message_local_hash(session_id: Option<&str>, message_id: &str) -> String {
+    hash_text(&format!(
+        "{}_{}",
+        session_id.unwrap_or_default(),
+        message_id
+    ))
 }
 
-fn is_internal_session_context(content: Option<&GeminiCliContent>) -> bool {
-    content
-        .map(GeminiCliContent::as_text)
-        .is_some_and(|text| text.trim_start().starts_with("<session_context>"))
+/// The session id is only used to scope message ids, so a missing or
+/// malformed one isn't worth a warning.
+fn read_session_id(value: &LenientValue) -> Option<String> {
+    value
+        .get("sessionId")
+        .raw()
+        .and_then(|id| id.as_str())
+        .map(str::to_string)
 }
 
 fn messages_from_session(
     file_path: &Path,
-    messages: Vec<GeminiCliMessage>,
+    session_id: Option<&str>,
+    messages: Vec<(String, GeminiCliMessage)>,
 ) -> Vec<ConversationMessage> {
     let project_hash = extract_and_hash_project_id_gemini_cli(file_path);
     let file_path_str = file_path.to_string_lossy();
     let conversation_hash = hash_text(&file_path.to_string_lossy());
     let mut entries = Vec::new();
     let mut fallback_session_name: Option<String> = None;
+    let mut open_response: Option<OpenResponse> = None;
 
-    for message in messages {
+    for (id, message) in messages {
         match message {
-            GeminiCliMessage::User {
-                id: _,
-                timestamp,
-                content,
-            } => {
-                if is_internal_session_context(content.as_ref()) {
+            GeminiCliMessage::User { timestamp, text } => {
+                if is_internal_session_context(&text) {
                     continue;
                 }
+                open_response = None;
 
-                if fallback_session_name.is_none() {
-                    let text_str = content
-                        .as_ref()
-                        .map(GeminiCliContent::as_text)
-                        .unwrap_or_default();
-                    if !text_str.is_empty() {
-                        let truncated = if text_str.chars().count() > 50 {
-                            let chars: String = text_str.chars().take(50).collect();
-                            format!("{chars}...")
-                        } else {
-                            text_str
-                        };
-                        fallback_session_name = Some(truncated);
-                    }
+                if fallback_session_name.is_none() && !text.is_empty() {
+                    let truncated = if text.chars().count() > 50 {
+                        let chars: String = text.chars().take(50).collect();
+                        format!("{chars}...")
+                    } else {
+                        text
+                    };
+                    fallback_session_name = Some(truncated);
                 }
 
                 entries.push(ConversationMessage {
                     date: timestamp,
                     application: Application::GeminiCli,
                     project_hash: project_hash.clone(),
-                    local_hash: None,
+                    local_hash: Some(message_local_hash(session_id, &id)),
                     global_hash: hash_text(&format!(
                         "{}_{}",
                         file_path_str,
@@ -347,32 +427,39 @@ fn messages_from_session(
                     role: MessageRole::User,
                     uuid: None,
                     session_name: fallback_session_name.clone(),
+                    stable_session_id: None,
                 });
             }
             GeminiCliMessage::Gemini {
-                id: _,
                 timestamp,
-                content: _,
                 model,
-                thoughts: _,
                 tokens: Some(tokens),
                 tool_calls,
             } => {
-                let mut stats = extract_tool_stats(&tool_calls);
-
-                stats.input_tokens = tokens.input;
-                stats.output_tokens = tokens.output;
-                stats.reasoning_tokens = tokens.thoughts;
-                stats.cache_creation_tokens = 0;
-                stats.cache_read_tokens = 0;
-                stats.cached_tokens = tokens.cached;
-                stats.cost = calculate_gemini_cost(&tokens, &model, timestamp);
-                stats.tool_calls = tool_calls.len() as u32;
+                if let Some(open) = open_response.as_mut().filter(|open| {
+                    open.model == model
+                        && timestamp - open.last_chunk <= super::STREAM_CHUNK_WINDOW
+                        && open.tokens.is_continued_by(&tokens)
+                }) {
+                    // A later chunk of the response already recorded: its
+                    // totals supersede the earlier ones instead of adding up.
+                    for call in tool_calls {
+                        if !open.tool_calls.contains(&call) {
+                            open.tool_calls.push(call);
+                        }
+                    }
+                    open.tokens = tokens;
+                    open.last_chunk = timestamp;
+                    let entry = &mut entries[open.entry];
+                    entry.stats =
+                        response_stats(&open.tokens, &open.model, &open.tool_calls, entry.date);
+                    continue;
+                }
 
                 entries.push(ConversationMessage {
                     application: Application::GeminiCli,
-                    model: Some(model),
-                    local_hash: None,
+                    model: Some(model.clone()),
+                    local_hash: Some(message_local_hash(session_id, &id)),
                     global_hash: hash_text(&format!(
                         "{}_{}",
                         file_path_str,
@@ -381,10 +468,18 @@ fn messages_from_session(
                     date: timestamp,
                     project_hash: project_hash.clone(),
                     conversation_hash: conversation_hash.clone(),
-                    stats,
+                    stats: response_stats(&tokens, &model, &tool_calls, timestamp),
                     role: MessageRole::Assistant,
                     uuid: None,
                     session_name: fallback_session_name.clone(),
+                    stable_session_id: None,
+                });
+                open_response = Some(OpenResponse {
+                    entry: entries.len() - 1,
+                    model,
+                    last_chunk: timestamp,
+                    tokens,
+                    tool_calls,
                 });
             }
             _ => {}
@@ -394,27 +489,36 @@ fn messages_from_session(
     entries
 }
 
+fn report_drift(drift: Drift, file_path: &Path) {
+    drift.report(Application::GeminiCli.display_name(), file_path);
+}
+
 // JSON session parsing (not JSONL)
 fn parse_json_session_file(file_path: &Path) -> Result<Vec<ConversationMessage>> {
-    let session: GeminiCliSession =
+    let session: simd_json::OwnedValue =
         simd_json::from_slice(&mut std::fs::read_to_string(file_path)?.into_bytes())?;
-    Ok(messages_from_session(file_path, session.messages))
+    let drift = Drift::default();
+    let root = LenientValue::root(&session, &drift);
+    let session_id = read_session_id(&root);
+    let messages = root
+        .get("messages")
+        .items()
+        .iter()
+        .filter_map(GeminiCliMessage::read)
+        .collect();
+    report_drift(drift, file_path);
+    Ok(messages_from_session(
+        file_path,
+        session_id.as_deref(),
+        messages,
+    ))
 }
 
 fn upsert_jsonl_message(
     message_order: &mut Vec<String>,
     latest_messages: &mut HashMap<String, GeminiCliMessage>,
-    message: GeminiCliMessage,
+    (id, message): (String, GeminiCliMessage),
 ) {
-    let id = match &message {
-        GeminiCliMessage::User { id, .. }
-        | GeminiCliMessage::Gemini { id, .. }
-        | GeminiCliMessage::System { id, .. }
-        | GeminiCliMessage::Error { id, .. }
-        | GeminiCliMessage::Info { id, .. }
-        | GeminiCliMessage::Warning { id, .. } => id.clone(),
-    };
-
     if !latest_messages.contains_key(&id) {
         message_order.push(id.clone());
     }
@@ -425,78 +529,93 @@ fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMessage>
     let content = std::fs::read_to_string(file_path)?;
     let mut message_order = Vec::new();
     let mut latest_messages = HashMap::new();
+    let mut session_id = None;
+    let drift = Drift::default();
 
-    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+    for (line_idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
         let mut line_bytes = line.as_bytes().to_vec();
-        let value: simd_json::OwnedValue = simd_json::from_slice(&mut line_bytes)?;
+        let Ok(value) = simd_json::from_slice::<simd_json::OwnedValue>(&mut line_bytes) else {
+            drift.note(format!("line {} is not valid JSON", line_idx + 1));
+            continue;
+        };
+        let record = LenientValue::root(&value, &drift);
 
-        if let Some(messages) = value
-            .get("$set")
-            .and_then(|set| set.get("messages"))
-            .and_then(|messages| messages.as_array())
-        {
-            for message_value in messages {
-                let mut message_bytes = simd_json::to_vec(message_value)?;
-                let message: GeminiCliMessage = simd_json::from_slice(&mut message_bytes)?;
+        let snapshot = record.get("$set").get("messages");
+        if snapshot.is_present() {
+            for message in snapshot.items().iter().filter_map(GeminiCliMessage::read) {
                 upsert_jsonl_message(&mut message_order, &mut latest_messages, message);
             }
             continue;
         }
 
-        if value.get("type").is_none() || value.get("id").is_none() {
+        // Session metadata lines carry neither field.
+        if !record.get("type").is_present() || !record.get("id").is_present() {
+            if session_id.is_none() {
+                session_id = read_session_id(&record);
+            }
             continue;
         }
 
-        let mut message_bytes = line.as_bytes().to_vec();
-        let message: GeminiCliMessage = simd_json::from_slice(&mut message_bytes)?;
-        upsert_jsonl_message(&mut message_order, &mut latest_messages, message);
+        if let Some(message) = GeminiCliMessage::read(&record) {
+            upsert_jsonl_message(&mut message_order, &mut latest_messages, message);
+        }
     }
+    report_drift(drift, file_path);
 
     let messages = message_order
         .into_iter()
-        .filter_map(|id| latest_messages.remove(&id))
+        .filter_map(|id| latest_messages.remove(&id).map(|message| (id, message)))
         .collect();
 
-    Ok(messages_from_session(file_path, messages))
+    Ok(messages_from_session(
+        file_path,
+        session_id.as_deref(),
+        messages,
+    ))
 }
 
 #[async_trait]
 impl Analyzer for GeminiCliAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Gemini CLI"
+        Application::GeminiCli.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
         let mut patterns = Vec::new();
 
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_str = home_dir.to_string_lossy();
-            patterns.push(format!("{home_str}/.gemini/tmp/*/chats/*.json"));
-            patterns.push(format!("{home_str}/.gemini/tmp/*/chats/*.jsonl"));
+        for tmp_dir in self.data_dirs() {
+            let tmp_str = tmp_dir.to_string_lossy();
+            patterns.push(format!("{tmp_str}/**/chats/**/*.json"));
+            patterns.push(format!("{tmp_str}/**/chats/**/*.jsonl"));
         }
 
         patterns
     }
 
     fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
-        let sources = Self::data_dir()
-            .filter(|d| d.is_dir())
+        let paths = self
+            .data_dirs()
             .into_iter()
+            .filter(|d| d.is_dir())
             .flat_map(|tmp_dir| WalkDir::new(tmp_dir).into_iter())
             .filter_map(|e| e.ok())
             .filter(|e| is_gemini_cli_chat_path(e.path()))
-            .map(|e| DataSource {
-                path: e.into_path(),
-            })
+            .map(|e| e.into_path())
             .collect();
 
-        Ok(sources)
+        Ok(drop_migrated_json(paths)
+            .into_iter()
+            .map(|path| DataSource { path })
+            .collect())
     }
 
     fn is_available(&self) -> bool {
-        Self::data_dir()
-            .filter(|d| d.is_dir())
+        self.data_dirs()
             .into_iter()
+            .filter(|d| d.is_dir())
             .flat_map(|tmp_dir| WalkDir::new(tmp_dir).into_iter())
             .filter_map(|e| e.ok())
             .any(|e| is_gemini_cli_chat_path(e.path()))
@@ -509,18 +628,46 @@ impl Analyzer for GeminiCliAnalyzer {
         }
     }
 
-    fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
-        let all_messages: Vec<ConversationMessage> = sources
-            .par_iter()
-            .flat_map(|source| self.parse_source(source).unwrap_or_default())
+    // A resumed session's file repeats the messages of the chat it resumed,
+    // so duplicates are dropped across all sources, not just within one.
+    fn parse_sources_parallel_with_paths(
+        &self,
+        sources: &[DataSource],
+    ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
+        let grouped =
+            crate::parse_io::parse_each(sources, |source| match self.parse_source(source) {
+                Ok(messages) => Some((source.path.clone(), messages)),
+                Err(error) => {
+                    crate::diagnostics::parse_warning(
+                        self.display_name(),
+                        &source.path,
+                        format!(
+                            "Failed to parse {} source {:?}: {}",
+                            self.display_name(),
+                            source.path,
+                            error
+                        ),
+                    );
+                    None
+                }
+            })
+            .into_iter()
+            .flatten()
             .collect();
-        crate::utils::deduplicate_by_local_hash(all_messages)
+        crate::utils::deduplicate_grouped_by_local_hash(grouped)
+    }
+
+    fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
+        self.parse_sources_parallel_with_paths(sources)
+            .into_iter()
+            .flat_map(|(_, messages)| messages)
+            .collect()
     }
 
     fn get_watch_directories(&self) -> Vec<PathBuf> {
-        Self::data_dir()
-            .filter(|d| d.is_dir())
+        self.data_dirs()
             .into_iter()
+            .filter(|d| d.is_dir())
             .collect()
     }
 
@@ -529,6 +676,12 @@ impl Analyzer for GeminiCliAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::GeminiCli.info().strategy
+    }
+
+    // Which file keeps a resumed session's shared messages depends on the
+    // other files, so a change to one can move another's contribution.
+    fn requires_full_reload_for_source_change(&self) -> bool {
+        true
     }
 }
@@ -0,0 +1,218 @@
+use crate::analyzer::{Analyzer, DataSource};
+use crate::contribution_cache::ContributionStrategy;
+use crate::models::{calculate_total_cost, resolve_cost};
+use crate::types::{Application, ConversationMessage, MessageRole, Stats};
+use crate::utils::hash_text;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// One usage record brought in by `splitrail import`, stored one per line in
+/// `imports/*.jsonl` under the data directory. Also the generic import
+/// format, so field names are part of the CLI's interface.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportedRow {
+    /// `YYYY-MM-DD` (counted on that local day) or an RFC 3339 timestamp.
+    #[serde(alias = "timestamp")]
+    pub date: String,
+    /// Where the record came from, e.g. `ccusage`.
+    pub tool: Option<String>,
+    #[serde(alias = "modelName")]
+    pub model: Option<String>,
+    #[serde(alias = "inputTokens")]
+    pub input_tokens: u64,
+    #[serde(alias = "outputTokens")]
+    pub output_tokens: u64,
+    #[serde(alias = "cacheCreationTokens")]
+    pub cache_creation_tokens: u64,
+    #[serde(alias = "cacheReadTokens")]
+    pub cache_read_tokens: u64,
+    /// Dollars; computed from the tokens when absent.
+    #[serde(alias = "totalCost")]
+    pub cost: Option<f64>,
+    /// Rows sharing a session are shown as one; otherwise each tool's day is
+    /// a session.
+    #[serde(alias = "sessionId")]
+    pub session: Option<String>,
+}
+
+impl ImportedRow {
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        parse_date(&self.date)
+    }
+
+    pub fn to_message(&self) -> Option<ConversationMessage> {
+        let date = self.timestamp()?;
+        let tool = self.tool.as_deref().unwrap_or("import");
+        let session = self
+            .session
+            .clone()
+            .unwrap_or_else(|| date.with_timezone(&Local).format("%Y-%m-%d").to_string());
+        let model = self.model.clone().filter(|model| !model.is_empty());
+
+        let mut stats = Stats {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cache_creation_tokens: self.cache_creation_tokens,
+            cache_read_tokens: self.cache_read_tokens,
+            cached_tokens: self.cache_creation_tokens + self.cache_read_tokens,
+            ..Stats::default()
+        };
+        stats.set_cost(resolve_cost(self.cost, || {
+            model.as_deref().map_or(0.0, |model| {
+                calculate_total_cost(
+                    model,
+                    self.input_tokens,
+                    self.output_tokens,
+                    self.cache_creation_tokens,
+                    self.cache_read_tokens,
+                )
+            })
+        }));
+
+        Some(ConversationMessage {
+            application: Application::Imported,
+            date,
+            project_hash: hash_text(&format!("imported_{tool}")),
+            conversation_hash: hash_text(&format!("imported_{tool}_{session}")),
+            local_hash: None,
+            // Independent of the file, so importing the same records again
+            // from a different export doesn't count them twice.
+            global_hash: hash_text(&format!(
+                "imported_{tool}_{session}_{}_{}",
+                date.to_rfc3339(),
+                model.as_deref().unwrap_or_default()
+            )),
+            model,
+            stats,
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: Some(format!("{tool} {session}")),
+            stable_session_id: None,
+        })
+    }
+}
+
+/// `YYYY-MM-DD` is taken as midday local time, so the record lands on that
+/// day whatever the time zone.
+pub fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Local
+        .from_local_datetime(&day.and_hms_opt(12, 0, 0)?)
+        .earliest()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Where `splitrail import` writes, under the data directory.
+pub fn imports_dir() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join("imports"))
+}
+
+/// History brought in from other usage trackers by `splitrail import`.
+pub struct ImportedAnalyzer {
+    dir: Option<PathBuf>,
+}
+
+impl ImportedAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            dir: imports_dir().ok(),
+        }
+    }
+
+    /// Read imports from `dir` instead of the data directory.
+    #[cfg(test)]
+    pub fn dir(mut self, dir: PathBuf) -> Self {
+        self.dir = Some(dir);
+        self
+    }
+}
+
+impl Default for ImportedAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Analyzer for ImportedAnalyzer {
+    fn display_name(&self) -> &'static str {
+        Application::Imported.display_name()
+    }
+
+    fn get_data_glob_patterns(&self) -> Vec<String> {
+        self.dir
+            .iter()
+            .map(|dir| format!("{}/*.jsonl", dir.display()))
+            .collect()
+    }
+
+    fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(Vec::new());
+        };
+        let mut sources: Vec<DataSource> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| self.is_valid_data_path(path))
+            .map(|path| DataSource { path })
+            .collect();
+        sources.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(sources)
+    }
+
+    fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
+        let buffer = std::fs::read(&source.path)?;
+        let mut messages = Vec::new();
+        for (i, line) in buffer.split(|&b| b == b'\n').enumerate() {
+            if line.iter().all(|b| b.is_ascii_whitespace()) {
+                continue;
+            }
+            let mut line = line.to_vec();
+            match simd_json::serde::from_slice::<ImportedRow>(&mut line) {
+                Ok(row) => messages.extend(row.to_message()),
+                Err(e) => crate::diagnostics::parse_warning(
+                    self.display_name(),
+                    &source.path,
+                    format!(
+                        "Skipping invalid entry in {} line {}: {}",
+                        source.path.display(),
+                        i + 1,
+                        e
+                    ),
+                ),
+            }
+        }
+        Ok(messages)
+    }
+
+    fn get_watch_directories(&self) -> Vec<PathBuf> {
+        self.dir
+            .iter()
+            .filter(|dir| dir.is_dir())
+            .cloned()
+            .collect()
+    }
+
+    fn is_valid_data_path(&self, path: &Path) -> bool {
+        path.is_file() && path.extension().is_some_and(|ext| ext == "jsonl")
+    }
+
+    fn contribution_strategy(&self) -> ContributionStrategy {
+        ContributionStrategy::MultiSession
+    }
+
+    /// Records are deduplicated across import files.
+    fn requires_full_reload_for_source_change(&self) -> bool {
+        true
+    }
+}
@@ -33,9 +33,7 @@ impl KiloCliAnalyzer {
     pub fn new() -> Self {
         Self {
             json_delegate: OpenCodeFormatAnalyzer::new(OpenCodeFormatConfig {
-                display_name: "Kilo CLI",
                 application: Application::KiloCli,
-                hash_prefix: "kilo_cli",
                 storage_subdir: "kilo",
             }),
         }
@@ -48,7 +46,7 @@ impl KiloCliAnalyzer {
 
     /// `~/.local/share/kilo` — parent directory (for watching the DB).
     fn app_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".local/share/kilo"))
+        Application::KiloCli.default_data_dir()
     }
 
     /// Discover all Kilo CLI SQLite database files.
@@ -106,7 +104,7 @@ impl KiloCliAnalyzer {
 #[async_trait]
 impl Analyzer for KiloCliAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Kilo CLI"
+        Application::KiloCli.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -163,7 +161,11 @@ impl Analyzer for KiloCliAnalyzer {
     fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
         // SQLite database — return all messages at once.
         if source.path.extension().is_some_and(|ext| ext == "db") {
-            return parse_sqlite_messages(&source.path, Application::KiloCli, "kilo_cli");
+            return parse_sqlite_messages(
+                &source.path,
+                Application::KiloCli,
+                Application::KiloCli.slug(),
+            );
         }
 
         // Legacy JSON message file — delegate to the shared JSON parser.
@@ -188,15 +190,23 @@ impl Analyzer for KiloCliAnalyzer {
         // so they are added first. During deduplication (which keeps the first-
         // seen entry per global_hash), SQLite wins over legacy JSON.
         for source in db_sources {
-            match parse_sqlite_messages(&source.path, Application::KiloCli, "kilo_cli") {
+            match parse_sqlite_messages(
+                &source.path,
+                Application::KiloCli,
+                Application::KiloCli.slug(),
+            ) {
                 Ok(messages) if !messages.is_empty() => {
                     results.push((source.path.clone(), messages));
                 }
                 Ok(_) => {} // empty DB
                 Err(e) => {
-                    eprintln!(
-                        "Failed to parse Kilo CLI SQLite DB {:?}: {}",
-                        source.path, e
+                    crate::diagnostics::parse_warning(
+                        self.display_name(),
+                        &source.path,
+                        format!(
+                            "Failed to parse Kilo CLI SQLite DB {:?}: {}",
+                            source.path, e
+                        ),
                     );
                 }
             }
@@ -299,7 +309,7 @@ impl Analyzer for KiloCliAnalyzer {
         if Self::has_sqlite_db() {
             ContributionStrategy::MultiSession
         } else {
-            ContributionStrategy::SingleMessage
+            Application::KiloCli.info().strategy
         }
     }
 }
@@ -0,0 +1,295 @@
+//! Tolerant reading of tool data whose schema keeps moving upstream.
+//!
+//! Strict serde structs fail a whole file when one field changes type or a
+//! new message kind appears. Analyzers that read through [`LenientValue`]
+//! instead get `None` for anything they can't make sense of, and every such
+//! surprise is collected in a [`Drift`] that is reported as one parse
+//! warning per distinct problem once the file has been read. The rest of the
+//! file still counts.
+
+use chrono::{DateTime, Utc};
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Schema surprises met while reading one file.
+#[derive(Debug, Default)]
+pub(crate) struct Drift {
+    problems: RefCell<BTreeSet<String>>,
+}
+
+impl Drift {
+    pub fn note(&self, problem: impl Into<String>) {
+        self.problems.borrow_mut().insert(problem.into());
+    }
+
+    /// Hand every distinct problem to the diagnostics pane and stderr.
+    pub fn report(self, analyzer: &str, path: &Path) {
+        for problem in self.problems.into_inner() {
+            crate::diagnostics::parse_warning(
+                analyzer,
+                path,
+                format!("Unexpected data in {}: {problem}", path.display()),
+            );
+        }
+    }
+}
+
+/// A possibly missing JSON value with typed extractors that never fail.
+///
+/// Missing and `null` values are simply absent. A value of the wrong type is
+/// absent too, but is noted in the [`Drift`] along with where it was found.
+/// Array indices are left out of that location so a problem repeated on
+/// every message is reported once.
+#[derive(Clone)]
+pub(crate) struct LenientValue<'a> {
+    value: Option<&'a OwnedValue>,
+    field: String,
+    drift: &'a Drift,
+}
+
+impl<'a> LenientValue<'a> {
+    pub fn root(value: &'a OwnedValue, drift: &'a Drift) -> Self {
+        Self {
+            value: Some(value),
+            field: String::new(),
+            drift,
+        }
+    }
+
+    fn child(&self, value: Option<&'a OwnedValue>, field: String) -> Self {
+        Self {
+            value,
+            field,
+            drift: self.drift,
+        }
+    }
+
+    fn present(&self) -> Option<&'a OwnedValue> {
+        self.value.filter(|value| !value.is_null())
+    }
+
+    fn mismatch(&self, expected: &str) {
+        if let Some(value) = self.present() {
+            let field = if self.field.is_empty() {
+                "top level"
+            } else {
+                &self.field
+            };
+            self.drift.note(format!(
+                "`{field}`: expected {expected}, found {}",
+                kind(value)
+            ));
+        }
+    }
+
+    /// The field `key` of this object.
+    pub fn get(&self, key: &str) -> Self {
+        let field = if self.field.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{key}", self.field)
+        };
+        let value = match self.present() {
+            Some(value) if value.is_object() => value.get(key),
+            Some(_) => {
+                self.mismatch("an object");
+                None
+            }
+            None => None,
+        };
+        self.child(value, field)
+    }
+
+    pub fn is_present(&self) -> bool {
+        self.present().is_some()
+    }
+
+    /// The underlying value, for code that walks it itself.
+    pub fn raw(&self) -> Option<&'a OwnedValue> {
+        self.present()
+    }
+
+    pub fn str(&self) -> Option<&'a str> {
+        let value = self.present()?;
+        let text = value.as_str();
+        if text.is_none() {
+            self.mismatch("a string");
+        }
+        text
+    }
+
+    /// A token count or similar. Negative numbers are drift; fractions are
+    /// truncated and numeric strings are accepted.
+    pub fn u64(&self) -> Option<u64> {
+        let value = self.present()?;
+        let number = value
+            .as_u64()
+            .or_else(|| value.as_i64().and_then(|n| u64::try_from(n).ok()))
+            .or_else(|| {
+                value
+                    .as_f64()
+                    .filter(|n| n.is_finite() && *n >= 0.0)
+                    .map(|n| n as u64)
+            })
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()));
+        if number.is_none() {
+            self.mismatch("a non-negative number");
+        }
+        number
+    }
+
+    pub fn bool(&self) -> Option<bool> {
+        let value = self.present()?;
+        let flag = value.as_bool();
+        if flag.is_none() {
+            self.mismatch("a boolean");
+        }
+        flag
+    }
+
+    /// An RFC 3339 timestamp. An empty string counts as missing.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        let text = self.str().filter(|text| !text.is_empty())?;
+        let parsed = DateTime::parse_from_rfc3339(text).ok();
+        if parsed.is_none() {
+            self.note(format!("invalid timestamp {text:?}"));
+        }
+        parsed.map(Into::into)
+    }
+
+    /// The elements of an array; empty when missing or not an array.
+    pub fn items(&self) -> Vec<LenientValue<'a>> {
+        let Some(value) = self.present() else {
+            return Vec::new();
+        };
+        let Some(items) = value.as_array() else {
+            self.mismatch("an array");
+            return Vec::new();
+        };
+        let field = format!("{}[]", self.field);
+        items
+            .iter()
+            .map(|item| self.child(Some(item), field.clone()))
+            .collect()
+    }
+
+    /// The text of Gemini's multi-modal `PartListUnion`: a plain string, a
+    /// single `Part` object, or an array of strings and parts. Parts without
+    /// `text` (images, function calls, ...) contribute nothing.
+    pub fn text(&self) -> String {
+        let Some(value) = self.present() else {
+            return String::new();
+        };
+        if let Some(text) = value.as_str() {
+            text.to_string()
+        } else if value.is_array() {
+            self.items().iter().map(LenientValue::text).collect()
+        } else if value.is_object() {
+            self.get("text").str().unwrap_or_default().to_string()
+        } else {
+            self.mismatch("text or content parts");
+            String::new()
+        }
+    }
+
+    /// Note a problem with this field that the extractors can't see.
+    pub fn note(&self, problem: impl std::fmt::Display) {
+        self.drift.note(format!("`{}`: {problem}", self.field));
+    }
+
+    /// Like `extract`, but also notes when the field is missing altogether.
+    pub fn required<T>(&self, extract: impl FnOnce(&Self) -> Option<T>) -> Option<T> {
+        if !self.is_present() {
+            self.drift.note(format!("`{}` is missing", self.field));
+            return None;
+        }
+        extract(self)
+    }
+}
+
+fn kind(value: &OwnedValue) -> &'static str {
+    if value.is_str() {
+        "a string"
+    } else if value.is_bool() {
+        "a boolean"
+    } else if value.is_number() {
+        "a number"
+    } else if value.is_array() {
+        "an array"
+    } else if value.is_object() {
+        "an object"
+    } else {
+        "null"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> OwnedValue {
+        simd_json::to_owned_value(&mut json.as_bytes().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn wrong_types_read_as_absent_and_are_noted_once() {
+        let value = parse(
+            r#"{"messages": [
+                {"tokens": {"input": "12", "output": 3.0, "cached": -1}},
+                {"tokens": {"input": 4, "output": "lots", "cached": 0}}
+            ]}"#,
+        );
+        let drift = Drift::default();
+        let root = LenientValue::root(&value, &drift);
+        let tokens: Vec<_> = root
+            .get("messages")
+            .items()
+            .iter()
+            .map(|message| {
+                let tokens = message.get("tokens");
+                (
+                    tokens.get("input").u64(),
+                    tokens.get("output").u64(),
+                    tokens.get("cached").u64(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![(Some(12), Some(3), None), (Some(4), None, Some(0))]
+        );
+        assert!(root.get("missing").required(LenientValue::str).is_none());
+        assert!(root.get("absent").str().is_none());
+
+        let problems = drift.problems.into_inner();
+        assert_eq!(
+            problems.into_iter().collect::<Vec<_>>(),
+            vec![
+                "`messages[].tokens.cached`: expected a non-negative number, found a number",
+                "`messages[].tokens.output`: expected a non-negative number, found a string",
+                "`missing` is missing",
+            ]
+        );
+    }
+
+    #[test]
+    fn text_accepts_every_part_list_shape() {
+        let drift = Drift::default();
+        for (json, expected) in [
+            (r#""plain""#, "plain"),
+            (r#"{"text": "one part"}"#, "one part"),
+            (
+                r#"[{"text": "a"}, {"inlineData": {"mimeType": "image/png"}}, "b"]"#,
+                "ab",
+            ),
+            ("null", ""),
+        ] {
+            let value = parse(json);
+            assert_eq!(LenientValue::root(&value, &drift).text(), expected);
+        }
+        assert!(drift.problems.borrow().is_empty());
+    }
+}
@@ -1,13 +1,17 @@
 pub mod antigravity;
 pub mod claude_code;
-mod claude_code_history;
+pub(crate) mod claude_code_history;
 pub mod cline;
+pub(crate) mod cline_family;
 pub mod codex_cli;
+pub mod configurable;
 pub mod copilot;
 pub mod copilot_cli;
 pub mod gemini_cli;
+pub mod imported;
 pub mod kilo_cli;
 pub mod kilo_code;
+pub(crate) mod lenient;
 pub mod opencode;
 pub(crate) mod opencode_common;
 pub mod pi_agent;
@@ -20,9 +24,11 @@ pub use antigravity::AntigravityCliAnalyzer;
 pub use claude_code::ClaudeCodeAnalyzer;
 pub use cline::ClineAnalyzer;
 pub use codex_cli::CodexCliAnalyzer;
+pub use configurable::ConfigurableAnalyzer;
 pub use copilot::CopilotAnalyzer;
 pub use copilot_cli::CopilotCliAnalyzer;
 pub use gemini_cli::GeminiCliAnalyzer;
+pub use imported::ImportedAnalyzer;
 pub use kilo_cli::KiloCliAnalyzer;
 pub use kilo_code::KiloCodeAnalyzer;
 pub use opencode::OpenCodeAnalyzer;
@@ -32,5 +38,11 @@ pub use qwen_code::QwenCodeAnalyzer;
 pub use roo_code::RooCodeAnalyzer;
 pub use zoo_code::ZooCodeAnalyzer;
 
+/// Longest gap between two logged chunks of one streamed response in the
+/// Gemini-style logs that record cumulative usage per chunk. A retry repeats
+/// the same prompt too, but Gemini CLI and Qwen Code wait at least a few
+/// seconds before retrying, so it falls outside this window.
+pub(crate) const STREAM_CHUNK_WINDOW: chrono::TimeDelta = chrono::TimeDelta::seconds(3);
+
 #[cfg(test)]
 pub mod tests;
@@ -1,13 +1,12 @@
 use crate::analyzer::{Analyzer, DataSource};
 use crate::contribution_cache::ContributionStrategy;
-use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at};
+use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at, resolve_cost};
 use crate::types::{Application, ConversationMessage, MessageRole, Stats};
 use crate::utils::hash_text;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use glob::glob;
-use rayon::prelude::*;
 use rusqlite::{Connection, OpenFlags};
 use serde::Deserialize;
 use simd_json::OwnedValue;
@@ -36,7 +35,7 @@ impl OpenCodeAnalyzer {
 
     /// `~/.local/share/opencode` — parent directory (for watching the DB).
     fn app_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".local/share/opencode"))
+        Application::OpenCode.default_data_dir()
     }
 
     /// Discover all OpenCode SQLite database files.
@@ -304,9 +303,11 @@ pub(crate) fn compute_message_stats_at(
         s.cache_creation_tokens = tokens.cache.write;
         s.cache_read_tokens = tokens.cache.read;
         s.cached_tokens = tokens.cache.write + tokens.cache.read;
+    }
 
-        if let Some(model_name) = msg.model_name() {
-            s.cost = calculate_total_cost_for_service_tier_at(
+    let cost = resolve_cost(msg.cost.filter(|cost| *cost > 0.0), || {
+        match (&msg.tokens, msg.model_name()) {
+            (Some(_), Some(model_name)) => calculate_total_cost_for_service_tier_at(
                 &model_name,
                 ServiceTier::Standard,
                 s.input_tokens,
@@ -314,15 +315,11 @@ pub(crate) fn compute_message_stats_at(
                 s.cache_creation_tokens,
                 s.cache_read_tokens,
                 Some(effective_at),
-            );
+            ),
+            _ => 0.0,
         }
-    }
-
-    if let Some(cost) = msg.cost
-        && cost > 0.0
-    {
-        s.cost = cost;
-    }
+    });
+    s.set_cost(cost);
 
     // Ensure tool_calls is at least 1 when a model call happened
     if s.tool_calls == 0
@@ -353,7 +350,9 @@ pub(crate) fn build_conversation_message(
     hash_prefix: &str,
 ) -> ConversationMessage {
     let project_hash = if let Some(worktree) = project_worktree {
-        hash_text(worktree)
+        let project_hash = hash_text(worktree);
+        crate::utils::remember_project(&project_hash, worktree);
+        project_hash
     } else if let Some(fallback) = fallback_project_hash {
         hash_text(fallback)
     } else {
@@ -381,6 +380,7 @@ pub(crate) fn build_conversation_message(
         },
         uuid: None,
         session_name: session_title,
+        stable_session_id: None,
     }
 }
 
@@ -464,6 +464,16 @@ pub(crate) fn load_sessions(session_root: &Path) -> HashMap<String, OpenCodeSess
 pub(crate) fn accumulate_tool_stat(stats: &mut Stats, tool_name: &str, value: &OwnedValue) {
     stats.tool_calls += 1;
 
+    if matches!(tool_name, "read" | "edit" | "write" | "patch")
+        && let Some(path) = value
+            .get("state")
+            .and_then(|s| s.get("input"))
+            .and_then(|i| i.get("filePath"))
+            .and_then(|p| p.as_str())
+    {
+        stats.add_file_path(path);
+    }
+
     match tool_name {
         "read" => {
             stats.files_read += 1;
@@ -870,7 +880,7 @@ pub(crate) fn parse_sqlite_messages(
                 worktree,
                 fallback,
                 stats,
-                application.clone(),
+                application,
                 hash_prefix,
             ))
         })
@@ -886,7 +896,7 @@ pub(crate) fn parse_sqlite_messages(
 #[async_trait]
 impl Analyzer for OpenCodeAnalyzer {
     fn display_name(&self) -> &'static str {
-        "OpenCode"
+        Application::OpenCode.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -943,7 +953,11 @@ impl Analyzer for OpenCodeAnalyzer {
     fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
         // SQLite database — return all messages at once.
         if source.path.extension().is_some_and(|ext| ext == "db") {
-            return parse_sqlite_messages(&source.path, Application::OpenCode, "opencode");
+            return parse_sqlite_messages(
+                &source.path,
+                Application::OpenCode,
+                Application::OpenCode.slug(),
+            );
         }
 
         // Legacy JSON message file — load context and parse single file.
@@ -971,8 +985,9 @@ impl Analyzer for OpenCodeAnalyzer {
         sources: &[DataSource],
     ) -> Vec<(PathBuf, Vec<ConversationMessage>)> {
         // Partition sources into JSON files and DB files.
-        let (db_sources, json_sources): (Vec<_>, Vec<_>) = sources
+        let (db_sources, json_sources): (Vec<DataSource>, Vec<DataSource>) = sources
             .iter()
+            .cloned()
             .partition(|s| s.path.extension().is_some_and(|ext| ext == "db"));
 
         let mut results: Vec<(PathBuf, Vec<ConversationMessage>)> = Vec::new();
@@ -982,15 +997,23 @@ impl Analyzer for OpenCodeAnalyzer {
         // so they are added first. During deduplication (which keeps the first-
         // seen entry per global_hash), SQLite wins over legacy JSON.
         for source in db_sources {
-            match parse_sqlite_messages(&source.path, Application::OpenCode, "opencode") {
+            match parse_sqlite_messages(
+                &source.path,
+                Application::OpenCode,
+                Application::OpenCode.slug(),
+            ) {
                 Ok(messages) if !messages.is_empty() => {
                     results.push((source.path.clone(), messages));
                 }
                 Ok(_) => {} // empty DB
                 Err(e) => {
-                    eprintln!(
-                        "Failed to parse OpenCode SQLite DB {:?}: {}",
-                        source.path, e
+                    crate::diagnostics::parse_warning(
+                        self.display_name(),
+                        &source.path,
+                        format!(
+                            "Failed to parse OpenCode SQLite DB {:?}: {}",
+                            source.path, e
+                        ),
                     );
                 }
             }
@@ -1007,17 +1030,17 @@ impl Analyzer for OpenCodeAnalyzer {
             let projects = load_projects(&project_root);
             let sessions = load_sessions(&session_root);
 
-            let json_results: Vec<_> = json_sources
-                .par_iter()
-                .filter_map(|source| {
-                    let content = fs::read_to_string(&source.path).ok()?;
-                    let mut bytes = content.into_bytes();
-                    let msg = simd_json::from_slice::<OpenCodeMessage>(&mut bytes).ok()?;
-                    let conversation_msg =
-                        json_to_conversation_message(msg, &sessions, &projects, &part_root);
-                    Some((source.path.clone(), vec![conversation_msg]))
-                })
-                .collect();
+            let json_results: Vec<_> = crate::parse_io::parse_each(&json_sources, |source| {
+                let content = fs::read_to_string(&source.path).ok()?;
+                let mut bytes = content.into_bytes();
+                let msg = simd_json::from_slice::<OpenCodeMessage>(&mut bytes).ok()?;
+                let conversation_msg =
+                    json_to_conversation_message(msg, &sessions, &projects, &part_root);
+                Some((source.path.clone(), vec![conversation_msg]))
+            })
+            .into_iter()
+            .flatten()
+            .collect();
 
             results.extend(json_results);
         }
@@ -1110,7 +1133,7 @@ impl Analyzer for OpenCodeAnalyzer {
         if Self::has_sqlite_db() {
             ContributionStrategy::MultiSession
         } else {
-            ContributionStrategy::SingleMessage
+            Application::OpenCode.info().strategy
         }
     }
 }
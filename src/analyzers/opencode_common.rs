@@ -8,14 +8,13 @@
 /// individual analyzer modules are thin wrappers.
 use crate::analyzer::{Analyzer, DataSource};
 use crate::contribution_cache::ContributionStrategy;
-use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at};
+use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at, resolve_cost};
 use crate::types::{Application, ConversationMessage, MessageRole, Stats};
 use crate::utils::hash_text;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use glob::glob;
-use rayon::prelude::*;
 use serde::Deserialize;
 use simd_json::OwnedValue;
 use simd_json::prelude::*;
@@ -34,12 +33,10 @@ use walkdir::WalkDir;
 /// message, per-session directories, companion metadata files).  This struct
 /// captures the handful of values that vary between the two.
 pub(crate) struct OpenCodeFormatConfig {
-    /// Human-readable name shown in the TUI and stats output (e.g. `"OpenCode"`).
-    pub display_name: &'static str,
     /// [`Application`] enum variant used when building [`ConversationMessage`]s.
+    /// Its display name and slug (the `global_hash` prefix, e.g. `"opencode"` →
+    /// `"opencode_{session}_{id}"`) come from the application table.
     pub application: Application,
-    /// Prefix for the `global_hash` field (e.g. `"opencode"` → `"opencode_{session}_{id}"`).
-    pub hash_prefix: &'static str,
     /// Sub-directory under `~/.local/share/` that contains the `storage/` tree.
     /// For OpenCode: `"opencode"` → `~/.local/share/opencode/storage/`.
     /// For Kilo CLI: `"kilo"` → `~/.local/share/kilo/storage/`.
@@ -416,7 +413,9 @@ fn to_conversation_message(
     let project = session.and_then(|s| projects.get(&s.project_id));
 
     let project_hash = if let Some(project) = project {
-        hash_text(&project.worktree)
+        let project_hash = hash_text(&project.worktree);
+        crate::utils::remember_project(&project_hash, &project.worktree);
+        project_hash
     } else if let Some(session) = session {
         hash_text(&session.id)
     } else {
@@ -449,9 +448,12 @@ fn to_conversation_message(
             s.cache_creation_tokens = tokens.cache.write;
             s.cache_read_tokens = tokens.cache.read;
             s.cached_tokens = tokens.cache.write + tokens.cache.read;
+        }
 
-            if let Some(model_name) = msg.model_name() {
-                s.cost = calculate_total_cost_for_service_tier_at(
+        // Prefer explicit cost from the tool if present
+        let cost = resolve_cost(msg.cost.filter(|cost| *cost > 0.0), || {
+            match (&msg.tokens, msg.model_name()) {
+                (Some(_), Some(model_name)) => calculate_total_cost_for_service_tier_at(
                     &model_name,
                     ServiceTier::Standard,
                     s.input_tokens,
@@ -459,16 +461,11 @@ fn to_conversation_message(
                     s.cache_creation_tokens,
                     s.cache_read_tokens,
                     effective_at,
-                );
+                ),
+                _ => 0.0,
             }
-        }
-
-        if let Some(cost) = msg.cost {
-            // Prefer explicit cost from the tool if present
-            if cost > 0.0 {
-                s.cost = cost;
-            }
-        }
+        });
+        s.set_cost(cost);
 
         s
     } else {
@@ -502,6 +499,7 @@ fn to_conversation_message(
         },
         uuid: None,
         session_name,
+        stable_session_id: None,
     }
 }
 
@@ -512,7 +510,7 @@ fn to_conversation_message(
 #[async_trait]
 impl Analyzer for OpenCodeFormatAnalyzer {
     fn display_name(&self) -> &'static str {
-        self.config.display_name
+        self.config.application.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -589,8 +587,8 @@ impl Analyzer for OpenCodeFormatAnalyzer {
             &sessions,
             &projects,
             &part_root,
-            self.config.application.clone(),
-            self.config.hash_prefix,
+            self.config.application,
+            self.config.application.slug(),
         )])
     }
 
@@ -602,7 +600,7 @@ impl Analyzer for OpenCodeFormatAnalyzer {
         let Some(storage_root) = self.storage_root() else {
             eprintln!(
                 "Could not determine {} storage root",
-                self.config.display_name
+                self.config.application.display_name()
             );
             return Vec::new();
         };
@@ -614,26 +612,26 @@ impl Analyzer for OpenCodeFormatAnalyzer {
         let projects = load_projects(&project_root);
         let sessions = load_sessions(&session_root);
 
-        let application = self.config.application.clone();
-        let hash_prefix = self.config.hash_prefix;
-
-        sources
-            .par_iter()
-            .filter_map(|source| {
-                let content = fs::read_to_string(&source.path).ok()?;
-                let mut bytes = content.into_bytes();
-                let msg = simd_json::from_slice::<Message>(&mut bytes).ok()?;
-                let conversation_msg = to_conversation_message(
-                    msg,
-                    &sessions,
-                    &projects,
-                    &part_root,
-                    application.clone(),
-                    hash_prefix,
-                );
-                Some((source.path.clone(), vec![conversation_msg]))
-            })
-            .collect()
+        let application = self.config.application;
+        let hash_prefix = self.config.application.slug();
+
+        crate::parse_io::parse_each(sources, |source| {
+            let content = fs::read_to_string(&source.path).ok()?;
+            let mut bytes = content.into_bytes();
+            let msg = simd_json::from_slice::<Message>(&mut bytes).ok()?;
+            let conversation_msg = to_conversation_message(
+                msg,
+                &sessions,
+                &projects,
+                &part_root,
+                application,
+                hash_prefix,
+            );
+            Some((source.path.clone(), vec![conversation_msg]))
+        })
+        .into_iter()
+        .flatten()
+        .collect()
     }
 
     // Each message file is unique — no deduplication needed.
@@ -659,25 +657,25 @@ impl Analyzer for OpenCodeFormatAnalyzer {
         let projects = load_projects(&project_root);
         let sessions = load_sessions(&session_root);
 
-        let application = self.config.application.clone();
-        let hash_prefix = self.config.hash_prefix;
-
-        let messages: Vec<ConversationMessage> = sources
-            .par_iter()
-            .filter_map(|source| {
-                let content = fs::read_to_string(&source.path).ok()?;
-                let mut bytes = content.into_bytes();
-                let msg = simd_json::from_slice::<Message>(&mut bytes).ok()?;
-                Some(to_conversation_message(
-                    msg,
-                    &sessions,
-                    &projects,
-                    &part_root,
-                    application.clone(),
-                    hash_prefix,
-                ))
-            })
-            .collect();
+        let application = self.config.application;
+        let hash_prefix = self.config.application.slug();
+
+        let messages: Vec<ConversationMessage> = crate::parse_io::parse_each(&sources, |source| {
+            let content = fs::read_to_string(&source.path).ok()?;
+            let mut bytes = content.into_bytes();
+            let msg = simd_json::from_slice::<Message>(&mut bytes).ok()?;
+            Some(to_conversation_message(
+                msg,
+                &sessions,
+                &projects,
+                &part_root,
+                application,
+                hash_prefix,
+            ))
+        })
+        .into_iter()
+        .flatten()
+        .collect();
 
         let mut daily_stats = crate::utils::aggregate_by_date(&messages);
         daily_stats.retain(|date, _| date != "unknown");
@@ -714,7 +712,7 @@ impl Analyzer for OpenCodeFormatAnalyzer {
 
     // Each message file contains exactly one message.
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleMessage
+        self.config.application.info().strategy
     }
 }
 
@@ -1,12 +1,13 @@
 use crate::analyzer::{Analyzer, DataSource};
 use crate::contribution_cache::ContributionStrategy;
+use crate::models::{ServiceTier, calculate_total_cost_for_service_tier_at, resolve_cost};
 use crate::types::{Application, ConversationMessage, MessageRole, Stats};
 use crate::utils::hash_text;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
 use serde::Deserialize;
+use simd_json::prelude::*;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -20,7 +21,7 @@ impl PiAgentAnalyzer {
     }
 
     fn data_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".pi").join("agent").join("sessions"))
+        Application::PiAgent.default_data_dir()
     }
 }
 
@@ -64,6 +65,8 @@ struct PiUsage {
 struct PiToolCall {
     #[serde(default)]
     name: String,
+    #[serde(default)]
+    arguments: Option<simd_json::OwnedValue>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -170,7 +173,17 @@ fn extract_tool_stats(content: &PiContent) -> Stats {
                     "read" | "Read" => stats.files_read += 1,
                     "edit" | "Edit" | "multiEdit" | "MultiEdit" => stats.files_edited += 1,
                     "write" | "Write" => stats.files_added += 1,
-                    "bash" | "Bash" => stats.terminal_commands += 1,
+                    "bash" | "Bash" => {
+                        stats.terminal_commands += 1;
+                        if let Some(command) = tool
+                            .arguments
+                            .as_ref()
+                            .and_then(|args| args.get("command"))
+                            .and_then(|v| v.as_str())
+                        {
+                            stats.add_command(command);
+                        }
+                    }
                     "glob" | "Glob" => stats.file_searches += 1,
                     "grep" | "Grep" => stats.file_content_searches += 1,
                     _ => {}
@@ -313,10 +326,21 @@ fn parse_jsonl_file<R: Read>(
                         stats.cache_creation_tokens = usage.cache_write;
                         stats.cached_tokens = usage.cache_read + usage.cache_write;
 
-                        // Cost comes directly from Pi's calculation
-                        if let Some(cost) = &usage.cost {
-                            stats.cost = cost.total;
-                        }
+                        // Cost comes from Pi's calculation when it made one
+                        let cost = resolve_cost(usage.cost.as_ref().map(|c| c.total), || {
+                            model_str.as_deref().map_or(0.0, |model| {
+                                calculate_total_cost_for_service_tier_at(
+                                    model,
+                                    ServiceTier::Standard,
+                                    usage.input,
+                                    usage.output,
+                                    usage.cache_write,
+                                    usage.cache_read,
+                                    Some(timestamp),
+                                )
+                            })
+                        });
+                        stats.set_cost(cost);
                     }
 
                     // Generate unique hash for this message
@@ -341,6 +365,7 @@ fn parse_jsonl_file<R: Read>(
                         role: MessageRole::Assistant,
                         uuid: None,
                         session_name: None,
+                        stable_session_id: None,
                     });
                 } else if msg.role == "user" {
                     // Capture fallback session name from first user message
@@ -368,17 +393,22 @@ fn parse_jsonl_file<R: Read>(
                         role: MessageRole::User,
                         uuid: None,
                         session_name: None,
+                        stable_session_id: None,
                     });
                 }
                 // Skip other roles (e.g., toolResult)
             }
             Err(e) => {
-                crate::utils::warn_once(format!(
-                    "Skipping invalid entry in {} line {}: {}",
-                    path.display(),
-                    i + 1,
-                    e
-                ));
+                crate::diagnostics::parse_warning(
+                    Application::PiAgent.display_name(),
+                    path,
+                    format!(
+                        "Skipping invalid entry in {} line {}: {}",
+                        path.display(),
+                        i + 1,
+                        e
+                    ),
+                );
                 continue;
             }
             _ => continue, // Skip other entry types
@@ -398,7 +428,7 @@ fn parse_jsonl_file<R: Read>(
 #[async_trait]
 impl Analyzer for PiAgentAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Pi Agent"
+        Application::PiAgent.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -457,9 +487,12 @@ impl Analyzer for PiAgentAnalyzer {
     }
 
     fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
-        let all_messages: Vec<ConversationMessage> = sources
-            .par_iter()
-            .flat_map(|source| self.parse_source(source).unwrap_or_default())
+        let all_messages: Vec<ConversationMessage> =
+            crate::parse_io::parse_each(sources, |source| {
+                self.parse_source(source).unwrap_or_default()
+            })
+            .into_iter()
+            .flatten()
             .collect();
         crate::utils::deduplicate_by_local_hash(all_messages)
     }
@@ -485,6 +518,6 @@ impl Analyzer for PiAgentAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::PiAgent.info().strategy
     }
 }
@@ -12,7 +12,6 @@ use crate::utils::hash_text;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
 use rusqlite::{Connection, OpenFlags};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -230,6 +229,9 @@ fn convert_messages(
 
             // Use project path from Piebald's projects table, falling back to "ungrouped" if not set.
             let project_hash = hash_text(chat.project_directory.as_deref().unwrap_or("ungrouped"));
+            if let Some(directory) = &chat.project_directory {
+                crate::utils::remember_project(&project_hash, directory);
+            }
 
             // Generate globally unique hash using created_at timestamp + message ID.
             // Use created_at (not updated_at) so the hash stays stable across token updates.
@@ -316,6 +318,7 @@ fn convert_messages(
                 role,
                 uuid: Some(msg.id.to_string()),
                 session_name: chat.title.clone(),
+                stable_session_id: None,
             })
         })
         .collect()
@@ -324,7 +327,7 @@ fn convert_messages(
 #[async_trait]
 impl Analyzer for PiebaldAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Piebald"
+        Application::Piebald.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -355,9 +358,12 @@ impl Analyzer for PiebaldAnalyzer {
     }
 
     fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
-        let all_messages: Vec<ConversationMessage> = sources
-            .par_iter()
-            .flat_map(|source| self.parse_source(source).unwrap_or_default())
+        let all_messages: Vec<ConversationMessage> =
+            crate::parse_io::parse_each(sources, |source| {
+                self.parse_source(source).unwrap_or_default()
+            })
+            .into_iter()
+            .flatten()
             .collect();
         crate::utils::deduplicate_by_local_hash(all_messages)
     }
@@ -377,7 +383,7 @@ impl Analyzer for PiebaldAnalyzer {
 
     // Piebald uses SQLite database containing all sessions
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::MultiSession
+        Application::Piebald.info().strategy
     }
 }
 
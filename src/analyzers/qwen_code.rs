@@ -1,4 +1,5 @@
 use crate::analyzer::{Analyzer, DataSource};
+use crate::analyzers::lenient::{Drift, LenientValue};
 use crate::contribution_cache::ContributionStrategy;
 use crate::models::{
     ServiceTier, calculate_cache_cost_for_service_tier_at,
@@ -9,8 +10,6 @@ use crate::utils::hash_text;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
 use simd_json::prelude::*;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
@@ -23,7 +22,7 @@ impl QwenCodeAnalyzer {
     }
 
     fn data_dir() -> Option<PathBuf> {
-        dirs::home_dir().map(|h| h.join(".qwen").join("projects"))
+        Application::QwenCode.default_data_dir()
     }
 }
 
@@ -32,47 +31,60 @@ impl QwenCodeAnalyzer {
 // every line is a record tagged with a `type` field. This mirrors the
 // Claude-Code-style transcript format that Qwen Code (and recent Gemini CLI
 // builds) adopted, replacing the old `~/.qwen/tmp/*/chats/*.json` single-blob
-// session format. See issue #190.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+// session format. See issue #190. Records are read through `LenientValue`
+// so a field that changes shape upstream costs that field, not the record.
 struct QwenCodeRecord {
-    #[serde(default)]
     uuid: Option<String>,
-    #[serde(rename = "type", default)]
     record_type: String,
-    #[serde(default, deserialize_with = "deserialize_optional_utc_timestamp")]
     timestamp: Option<DateTime<Utc>>,
-    #[serde(default)]
     model: Option<String>,
-    #[serde(default)]
-    message: Option<QwenCodeMessageBody>,
-    #[serde(rename = "usageMetadata", default)]
+    /// Concatenated plain text of the message, ignoring "thought" parts
+    /// (model reasoning) and non-text parts (tool calls/results).
+    text: String,
+    function_calls: Vec<QwenCodeFunctionCall>,
     usage_metadata: Option<QwenCodeUsageMetadata>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct QwenCodeMessageBody {
-    #[serde(default)]
-    parts: Vec<QwenCodePart>,
-}
+impl QwenCodeRecord {
+    /// A message's `parts` may carry plain `text` (optionally flagged as a
+    /// `thought`), a `functionCall` (a tool invocation), or a
+    /// `functionResponse` (a tool result). Unknown part kinds are ignored.
+    fn read(record: &LenientValue) -> Self {
+        let mut text = String::new();
+        let mut function_calls = Vec::new();
+        for part in record.get("message").get("parts").items() {
+            if !part.get("thought").bool().unwrap_or(false)
+                && let Some(part_text) = part.get("text").str()
+            {
+                text.push_str(part_text);
+            }
+            let call = part.get("functionCall");
+            if call.is_present() {
+                function_calls.push(QwenCodeFunctionCall {
+                    name: call.get("name").str().unwrap_or_default().to_string(),
+                    args: call.get("args").raw().cloned(),
+                });
+            }
+        }
 
-/// A single `Part` of a Qwen Code message. A part may carry plain `text`
-/// (optionally flagged as a `thought`), a `functionCall` (a tool invocation),
-/// or a `functionResponse` (a tool result). Unknown part kinds are ignored.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct QwenCodePart {
-    #[serde(default)]
-    text: Option<String>,
-    #[serde(default)]
-    thought: Option<bool>,
-    #[serde(rename = "functionCall", default)]
-    function_call: Option<QwenCodeFunctionCall>,
+        let usage = record.get("usageMetadata");
+        Self {
+            uuid: record.get("uuid").str().map(str::to_string),
+            record_type: record.get("type").str().unwrap_or_default().to_string(),
+            timestamp: record.get("timestamp").timestamp(),
+            model: record.get("model").str().map(str::to_string),
+            text,
+            function_calls,
+            usage_metadata: usage
+                .is_present()
+                .then(|| QwenCodeUsageMetadata::read(&usage)),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Default)]
 struct QwenCodeFunctionCall {
-    #[serde(default)]
     name: String,
-    #[serde(default)]
     args: Option<simd_json::OwnedValue>,
 }
 
@@ -80,64 +92,46 @@ struct QwenCodeFunctionCall {
 /// Gemini `usageMetadata` schema that Qwen Code emits. Note that
 /// `promptTokenCount` is the *full* input token count and already includes
 /// `cachedContentTokenCount`.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Default)]
 struct QwenCodeUsageMetadata {
-    #[serde(rename = "promptTokenCount", default)]
     prompt: u64,
-    #[serde(rename = "candidatesTokenCount", default)]
     candidates: u64,
-    #[serde(rename = "thoughtsTokenCount", default)]
     thoughts: u64,
-    #[serde(rename = "cachedContentTokenCount", default)]
     cached: u64,
-    #[serde(rename = "totalTokenCount", default)]
-    #[allow(dead_code)]
-    total: u64,
-}
-
-fn deserialize_optional_utc_timestamp<'de, D>(
-    deserializer: D,
-) -> std::result::Result<Option<DateTime<Utc>>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    // Tolerate a missing/null/empty timestamp instead of aborting the parse.
-    let opt: Option<String> = Option::deserialize(deserializer)?;
-    match opt {
-        Some(s) if !s.is_empty() => DateTime::parse_from_rfc3339(&s)
-            .map(|dt| Some(dt.into()))
-            .map_err(serde::de::Error::custom),
-        _ => Ok(None),
-    }
 }
 
-impl QwenCodePart {
-    /// Plain text of this part, ignoring "thought" parts (model reasoning) and
-    /// non-text parts (tool calls/results).
-    fn visible_text(&self) -> Option<&str> {
-        if self.thought.unwrap_or(false) {
-            return None;
+impl QwenCodeUsageMetadata {
+    fn read(usage: &LenientValue) -> Self {
+        let count = |key| usage.get(key).u64().unwrap_or_default();
+        Self {
+            prompt: count("promptTokenCount"),
+            candidates: count("candidatesTokenCount"),
+            thoughts: count("thoughtsTokenCount"),
+            cached: count("cachedContentTokenCount"),
         }
-        self.text.as_deref().filter(|t| !t.is_empty())
     }
-}
 
-impl QwenCodeMessageBody {
-    fn concatenated_text(&self) -> String {
-        let mut out = String::new();
-        for part in &self.parts {
-            if let Some(text) = part.visible_text() {
-                out.push_str(text);
-            }
-        }
-        out
+    /// Whether `next` looks like a later streamed chunk of the same response.
+    /// Usage can be logged cumulatively on every chunk: the prompt side stays
+    /// fixed while the generated side only grows, so the last chunk carries
+    /// the response's real totals.
+    fn is_continued_by(&self, next: &QwenCodeUsageMetadata) -> bool {
+        next.prompt == self.prompt
+            && next.cached == self.cached
+            && next.candidates >= self.candidates
+            && next.thoughts >= self.thoughts
     }
+}
 
-    fn function_calls(&self) -> impl Iterator<Item = &QwenCodeFunctionCall> {
-        self.parts
-            .iter()
-            .filter_map(|part| part.function_call.as_ref())
-    }
+/// The latest `assistant` entry of the current turn, kept so that streamed
+/// chunks of the same response can be folded into it.
+struct OpenResponse {
+    entry: usize,
+    model: String,
+    /// When the latest chunk was logged.
+    last_chunk: DateTime<Utc>,
+    usage: QwenCodeUsageMetadata,
+    function_calls: Vec<QwenCodeFunctionCall>,
 }
 
 // Tool extraction and file operation mapping. Tool invocations now appear as
@@ -148,6 +142,16 @@ fn extract_tool_stats<'a>(function_calls: impl Iterator<Item = &'a QwenCodeFunct
 
     for call in function_calls {
         stats.tool_calls += 1;
+        if matches!(
+            call.name.as_str(),
+            "read_file" | "replace" | "edit" | "write_file"
+        ) && let Some(path) = call.args.as_ref().and_then(|args| {
+            args.get("absolute_path")
+                .or_else(|| args.get("file_path"))
+                .and_then(|v| v.as_str())
+        }) {
+            stats.add_file_path(path);
+        }
         match call.name.as_str() {
             "read_many_files" => {
                 let paths = call
@@ -164,6 +168,7 @@ fn extract_tool_stats<'a>(function_calls: impl Iterator<Item = &'a QwenCodeFunct
                     let Some(path_str) = path.as_str() else {
                         continue;
                     };
+                    stats.add_file_path(path_str);
                     let ext = std::path::Path::new(path_str)
                         .extension()
                         .and_then(|e| e.to_str())
@@ -200,6 +205,14 @@ fn extract_tool_stats<'a>(function_calls: impl Iterator<Item = &'a QwenCodeFunct
             }
             "run_shell_command" => {
                 stats.terminal_commands += 1;
+                if let Some(command) = call
+                    .args
+                    .as_ref()
+                    .and_then(|v| v.get("command"))
+                    .and_then(|v| v.as_str())
+                {
+                    stats.add_command(command);
+                }
             }
             "list_directory" => {
                 // Treat as a lightweight read operation.
@@ -288,6 +301,27 @@ fn is_internal_session_context(text: &str) -> bool {
     text.trim_start().starts_with("<session_context>")
 }
 
+fn response_stats(
+    usage: &QwenCodeUsageMetadata,
+    model: &str,
+    function_calls: &[QwenCodeFunctionCall],
+    timestamp: DateTime<Utc>,
+) -> Stats {
+    let mut stats = extract_tool_stats(function_calls.iter());
+
+    // `promptTokenCount` already includes the cached tokens, so record only
+    // the non-cached portion as input to avoid double-counting in the
+    // input/cached columns.
+    stats.input_tokens = usage.prompt.saturating_sub(usage.cached);
+    stats.output_tokens = usage.candidates;
+    stats.reasoning_tokens = usage.thoughts;
+    stats.cache_creation_tokens = 0;
+    stats.cache_read_tokens = 0;
+    stats.cached_tokens = usage.cached;
+    stats.cost = calculate_qwen_cost(usage, model, timestamp);
+    stats
+}
+
 // JSONL session parsing.
 pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMessage>> {
     let project_hash = extract_and_hash_project_id_qwen_code(file_path);
@@ -297,6 +331,8 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
 
     let mut entries = Vec::new();
     let mut fallback_session_name: Option<String> = None;
+    let mut open_response: Option<OpenResponse> = None;
+    let drift = Drift::default();
 
     for (line_idx, line) in content
         .lines()
@@ -304,11 +340,12 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
         .enumerate()
     {
         let mut line_bytes = line.as_bytes().to_vec();
-        let record: QwenCodeRecord = match simd_json::from_slice(&mut line_bytes) {
-            Ok(record) => record,
+        let Ok(value) = simd_json::from_slice::<simd_json::OwnedValue>(&mut line_bytes) else {
             // Skip malformed lines rather than aborting the whole session.
-            Err(_) => continue,
+            drift.note(format!("line {} is not valid JSON", line_idx + 1));
+            continue;
         };
+        let record = QwenCodeRecord::read(&LenientValue::root(&value, &drift));
 
         let Some(timestamp) = record.timestamp else {
             continue;
@@ -328,16 +365,13 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
 
         match record.record_type.as_str() {
             "user" => {
-                let text = record
-                    .message
-                    .as_ref()
-                    .map(QwenCodeMessageBody::concatenated_text)
-                    .unwrap_or_default();
+                let text = record.text;
 
                 // Skip system-injected context that is not a real user turn.
                 if is_internal_session_context(&text) {
                     continue;
                 }
+                open_response = None;
 
                 if fallback_session_name.is_none() && !text.is_empty() {
                     let truncated = if text.chars().count() > 50 {
@@ -361,6 +395,7 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
                     role: MessageRole::User,
                     uuid: record.uuid.clone(),
                     session_name: fallback_session_name.clone(),
+                    stable_session_id: None,
                 });
             }
             "assistant" => {
@@ -370,36 +405,49 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
                     continue;
                 };
 
-                let mut stats = record
-                    .message
-                    .as_ref()
-                    .map(|body| extract_tool_stats(body.function_calls()))
-                    .unwrap_or_default();
-
-                // `promptTokenCount` already includes the cached tokens, so
-                // record only the non-cached portion as input to avoid
-                // double-counting in the input/cached columns.
-                stats.input_tokens = usage.prompt.saturating_sub(usage.cached);
-                stats.output_tokens = usage.candidates;
-                stats.reasoning_tokens = usage.thoughts;
-                stats.cache_creation_tokens = 0;
-                stats.cache_read_tokens = 0;
-                stats.cached_tokens = usage.cached;
                 let model = record.model.unwrap_or_default();
-                stats.cost = calculate_qwen_cost(&usage, &model, timestamp);
+                let function_calls = record.function_calls;
+
+                if let Some(open) = open_response.as_mut().filter(|open| {
+                    open.model == model
+                        && timestamp - open.last_chunk <= super::STREAM_CHUNK_WINDOW
+                        && open.usage.is_continued_by(&usage)
+                }) {
+                    // A later chunk of the response already recorded: its
+                    // totals supersede the earlier ones instead of adding up.
+                    for call in function_calls {
+                        if !open.function_calls.contains(&call) {
+                            open.function_calls.push(call);
+                        }
+                    }
+                    open.usage = usage;
+                    open.last_chunk = timestamp;
+                    let entry = &mut entries[open.entry];
+                    entry.stats =
+                        response_stats(&open.usage, &open.model, &open.function_calls, entry.date);
+                    continue;
+                }
 
                 entries.push(ConversationMessage {
                     application: Application::QwenCode,
-                    model: Some(model),
+                    model: Some(model.clone()),
                     local_hash: None,
                     global_hash,
                     date: timestamp,
                     project_hash: project_hash.clone(),
                     conversation_hash: conversation_hash.clone(),
-                    stats,
+                    stats: response_stats(&usage, &model, &function_calls, timestamp),
                     role: MessageRole::Assistant,
                     uuid: record.uuid.clone(),
                     session_name: fallback_session_name.clone(),
+                    stable_session_id: None,
+                });
+                open_response = Some(OpenResponse {
+                    entry: entries.len() - 1,
+                    model,
+                    last_chunk: timestamp,
+                    usage,
+                    function_calls,
                 });
             }
             // `tool_result`, `system` (telemetry, snapshots, slash commands),
@@ -407,6 +455,7 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
             _ => {}
         }
     }
+    drift.report(Application::QwenCode.display_name(), file_path);
 
     Ok(entries)
 }
@@ -414,7 +463,7 @@ pub fn parse_jsonl_session_file(file_path: &Path) -> Result<Vec<ConversationMess
 #[async_trait]
 impl Analyzer for QwenCodeAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Qwen Code"
+        Application::QwenCode.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
@@ -458,9 +507,10 @@ impl Analyzer for QwenCodeAnalyzer {
     }
 
     fn parse_sources_parallel(&self, sources: &[DataSource]) -> Vec<ConversationMessage> {
-        let all_messages: Vec<ConversationMessage> = sources
-            .par_iter()
-            .flat_map(|source| self.parse_source(source).unwrap_or_default())
+        let all_messages: Vec<ConversationMessage> = self
+            .parse_sources_parallel_with_paths(sources)
+            .into_iter()
+            .flat_map(|(_, messages)| messages)
             .collect();
         crate::utils::deduplicate_by_global_hash(all_messages)
     }
@@ -477,6 +527,6 @@ impl Analyzer for QwenCodeAnalyzer {
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        Application::QwenCode.info().strategy
     }
 }
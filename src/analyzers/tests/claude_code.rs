@@ -1,7 +1,7 @@
 use crate::analyzer::{Analyzer, DataSource};
 use crate::analyzers::claude_code::{
     ClaudeCodeAnalyzer, TokenFingerprint, calculate_cost_from_tokens, deduplicate_grouped_messages,
-    deduplicate_messages, extract_and_hash_project_id, is_claude_transcript_path,
+    deduplicate_messages, extract_and_hash_project_id, is_claude_transcript_path, mcp_server,
     merge_message_into, parse_jsonl_file,
 };
 use crate::types::{Application, ConversationMessage, MessageRole, Stats};
@@ -96,6 +96,7 @@ fn test_deduplicate_messages_merges_same_local_hash_across_uuids() {
         role: MessageRole::Assistant,
         uuid: Some("uuid-a".to_string()),
         session_name: None,
+        stable_session_id: None,
     };
     let mut second = first.clone();
     second.global_hash = "uuid-b".to_string();
@@ -174,6 +175,14 @@ fn test_parse_jsonl_file_tool_operations() {
     assert_eq!(multi_tool_msg.stats.tool_calls, 2);
     assert_eq!(multi_tool_msg.stats.files_edited, 1);
     assert_eq!(multi_tool_msg.stats.terminal_commands, 1);
+    assert_eq!(
+        multi_tool_msg.stats.file_extensions,
+        [("rs".to_string(), 1)].into()
+    );
+    assert_eq!(
+        multi_tool_msg.stats.command_categories,
+        [("files".to_string(), 1)].into()
+    );
 
     // Check todo result message
     let todo_msg = &messages[1];
@@ -182,6 +191,56 @@ fn test_parse_jsonl_file_tool_operations() {
     assert_eq!(todo_msg.stats.todos_in_progress, 1);
 }
 
+#[test]
+fn test_parse_jsonl_file_counts_calls_per_mcp_server() {
+    assert_eq!(mcp_server("mcp__github__create_issue"), Some("github"));
+    assert_eq!(
+        mcp_server("mcp__claude_ai_Linear__list_issues"),
+        Some("claude_ai_Linear")
+    );
+    assert_eq!(mcp_server("mcp__broken"), None);
+    assert_eq!(mcp_server("Bash"), None);
+
+    let line = r#"{"parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/repo","sessionId":"mcp-session","version":"1.0.51","message":{"id":"msg_mcp","type":"message","role":"assistant","model":"claude-sonnet-4-20250514","content":[{"type":"tool_use","id":"toolu_1","name":"mcp__github__create_issue","input":{}},{"type":"tool_use","id":"toolu_2","name":"mcp__github__list_prs","input":{}},{"type":"tool_use","id":"toolu_3","name":"mcp__linear__get_issue","input":{}},{"type":"tool_use","id":"toolu_4","name":"Bash","input":{"command":"ls"}}],"stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":20,"service_tier":"standard"}},"requestId":"req_mcp","type":"assistant","uuid":"mcp-uuid","timestamp":"2025-08-02T15:00:00.000Z"}"#;
+    let mut buf_reader = BufReader::new(Cursor::new(line));
+    let (messages, _, _, _) =
+        parse_jsonl_file(Path::new("mcp.jsonl"), &mut buf_reader, "proj", "conv").unwrap();
+
+    let stats = &messages[0].stats;
+    assert_eq!(stats.tool_calls, 4);
+    assert_eq!(stats.terminal_commands, 1);
+    assert_eq!(stats.mcp_tool_calls.len(), 2);
+    assert_eq!(stats.mcp_tool_calls["github"], 2);
+    assert_eq!(stats.mcp_tool_calls["linear"], 1);
+}
+
+#[test]
+fn test_parse_jsonl_file_counts_lines_and_bytes_from_file_results() {
+    let data = [
+        r#"{"parentUuid":null,"isSidechain":false,"cwd":"/repo","sessionId":"s","type":"user","message":{"role":"user","content":[{"tool_use_id":"t1","type":"tool_result","content":"ok"}]},"uuid":"read-uuid","timestamp":"2025-08-02T15:00:00.000Z","toolUseResult":{"type":"text","file":{"filePath":"/repo/src/main.rs","content":"fn main() {\n}\n","numLines":2,"startLine":1,"totalLines":2}}}"#,
+        r#"{"parentUuid":null,"isSidechain":false,"cwd":"/repo","sessionId":"s","type":"user","message":{"role":"user","content":[{"tool_use_id":"t2","type":"tool_result","content":"ok"}]},"uuid":"write-uuid","timestamp":"2025-08-02T15:01:00.000Z","toolUseResult":{"type":"create","filePath":"/repo/README.md","content":"Title\n\nBody\n","structuredPatch":[]}}"#,
+        r#"{"parentUuid":null,"isSidechain":false,"cwd":"/repo","sessionId":"s","type":"user","message":{"role":"user","content":[{"tool_use_id":"t3","type":"tool_result","content":"ok"}]},"uuid":"edit-uuid","timestamp":"2025-08-02T15:02:00.000Z","toolUseResult":{"filePath":"/repo/Cargo.toml","oldString":"a = 1","newString":"a = 2\nb = 3","originalFile":"a = 1\n","structuredPatch":[{"oldStart":1,"oldLines":1,"newStart":1,"newLines":2,"lines":["-a = 1","+a = 2","+b = 3"]}],"userModified":false,"replaceAll":false}}"#,
+    ]
+    .join("\n");
+    let mut buf_reader = BufReader::new(Cursor::new(data));
+    let (messages, _, _, _) =
+        parse_jsonl_file(Path::new("files.jsonl"), &mut buf_reader, "proj", "conv").unwrap();
+    assert_eq!(messages.len(), 3);
+
+    let read = &messages[0].stats;
+    assert_eq!((read.lines_read, read.bytes_read), (2, 14));
+    assert_eq!(read.code_lines, 2);
+
+    let write = &messages[1].stats;
+    assert_eq!((write.lines_added, write.bytes_added), (3, 12));
+    assert_eq!(write.docs_lines, 3);
+
+    let edit = &messages[2].stats;
+    assert_eq!((edit.lines_edited, edit.bytes_edited), (2, 12));
+    assert_eq!((edit.lines_deleted, edit.bytes_deleted), (1, 6));
+    assert_eq!(edit.data_lines, 2);
+}
+
 #[test]
 fn test_extract_and_hash_project_id() {
     let path1 = Path::new("/home/user/.claude/projects/proj123/conversation.jsonl");
@@ -261,7 +320,7 @@ fn test_claude_discovery_includes_subagent_transcripts() {
     }
 
     let paths: Vec<_> = ClaudeCodeAnalyzer::new()
-        .discover_sources_in(&projects)
+        .discover_sources_in(std::slice::from_ref(&projects))
         .into_iter()
         .map(|source| source.path)
         .collect();
@@ -270,6 +329,46 @@ fn test_claude_discovery_includes_subagent_transcripts() {
     assert_eq!(paths, vec![main, subagent]);
 }
 
+#[test]
+fn test_claude_projects_dirs_override_reads_every_root() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let first = temp_dir.path().join("d/claude/projects");
+    let second = temp_dir.path().join("e/claude/projects");
+    for root in [&first, &second] {
+        fs::create_dir_all(root.join("project")).unwrap();
+        fs::write(root.join("project/session.jsonl"), "").unwrap();
+    }
+    let missing = temp_dir.path().join("missing/projects");
+
+    let analyzer =
+        ClaudeCodeAnalyzer::new().projects_dirs(vec![first.clone(), second.clone(), missing]);
+    let paths: Vec<_> = analyzer
+        .discover_data_sources()
+        .unwrap()
+        .into_iter()
+        .map(|source| source.path)
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            first.join("project/session.jsonl"),
+            second.join("project/session.jsonl")
+        ]
+    );
+    assert_eq!(
+        analyzer.get_watch_directories(),
+        vec![first, second.clone()]
+    );
+    assert!(analyzer.is_valid_data_path(&second.join("project/session.jsonl")));
+    assert_eq!(analyzer.get_data_glob_patterns().len(), 6);
+    // Sessions in a relocated projects directory still group by project.
+    assert_eq!(
+        extract_and_hash_project_id(&paths[0]),
+        extract_and_hash_project_id(&paths[0].with_file_name("other.jsonl"))
+    );
+}
+
 #[test]
 fn test_claude_glob_patterns_include_subagent_transcripts() {
     let analyzer = ClaudeCodeAnalyzer::new();
@@ -338,6 +437,29 @@ fn test_main_and_subagent_transcripts_add_unique_usage_without_duplicate_inflati
             .iter()
             .all(|message| { message.project_hash == extract_and_hash_project_id(&main) })
     );
+    // Only the subagent's own response counts as subagent usage.
+    assert_eq!(grouped[0].1[0].stats.subagent_messages, 0);
+    assert_eq!(grouped[1].1[0].stats.subagent_messages, 1);
+}
+
+#[test]
+fn test_parse_jsonl_file_marks_inline_sidechain_usage() {
+    let data = r#"{"parentUuid":null,"isSidechain":false,"sessionId":"s","message":{"id":"msg-main","role":"assistant","model":"claude-sonnet-4-20250514","content":[],"usage":{"input_tokens":10,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":20}},"requestId":"req-main","type":"assistant","uuid":"main","timestamp":"2025-08-02T15:00:00.000Z"}
+{"parentUuid":"main","isSidechain":true,"sessionId":"s","message":{"id":"msg-task","role":"assistant","model":"claude-sonnet-4-20250514","content":[],"usage":{"input_tokens":300000,"cache_creation_input_tokens":0,"cache_read_input_tokens":0,"output_tokens":40}},"requestId":"req-task","type":"assistant","uuid":"task","timestamp":"2025-08-02T15:00:05.000Z"}"#;
+    let (messages, _, _, _) = parse_jsonl_file(
+        Path::new("session.jsonl"),
+        Cursor::new(data.as_bytes()),
+        "proj_hash",
+        "conv_hash",
+    )
+    .unwrap();
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].stats.subagent_messages, 0);
+    assert_eq!(messages[1].stats.subagent_messages, 1);
+    let tui = crate::types::TuiStats::from(&messages[1].stats);
+    assert!(tui.subagent_cost_cents > 0);
+    assert_eq!(tui.subagent_cost_cents, tui.cost_cents);
 }
 
 #[test]
@@ -385,6 +507,7 @@ fn test_deduplicate_messages_by_local_hash() {
         role: MessageRole::Assistant,
         uuid: Some("uuid1".to_string()),
         session_name: Some("Session 1".to_string()),
+        stable_session_id: None,
     };
 
     let duplicate_msg = ConversationMessage {
@@ -768,3 +891,12 @@ fn test_parse_agent_session_fallback_name_from_assistant() {
         name
     );
 }
+
+#[test]
+fn claude_code_is_parsed_in_one_pass() {
+    // Resumed-session merging and the history store need every transcript at
+    // once, so startup can't fold Claude Code's logs in batches.
+    let analyzer = ClaudeCodeAnalyzer::new();
+    assert!(analyzer.requires_full_reload_for_source_change());
+    assert!(!analyzer.supports_batched_parsing());
+}
@@ -1,5 +1,9 @@
-use crate::analyzer::Analyzer;
+use crate::analyzer::{Analyzer, DataSource};
 use crate::analyzers::cline::ClineAnalyzer;
+use crate::models::calculate_total_cost;
+use crate::types::MessageRole;
+use std::fs;
+use tempfile::tempdir;
 
 #[test]
 fn test_cline_analyzer_creation() {
@@ -30,3 +34,123 @@ async fn test_cline_get_stats_empty_sources() {
     assert!(result.is_ok());
     assert!(result.unwrap().messages.is_empty());
 }
+
+fn write_task(files: &[(&str, &str)]) -> (tempfile::TempDir, std::path::PathBuf) {
+    let temp_dir = tempdir().unwrap();
+    let task_dir = temp_dir
+        .path()
+        .join("saoudrizwan.claude-dev/tasks/1762355683581");
+    fs::create_dir_all(&task_dir).unwrap();
+    for (name, content) in files {
+        fs::write(task_dir.join(name), content).unwrap();
+    }
+    (temp_dir, task_dir)
+}
+
+#[test]
+fn test_cline_takes_models_from_metadata_and_skips_cancelled_requests() {
+    let (_temp_dir, task_dir) = write_task(&[
+        (
+            "task_metadata.json",
+            r#"{"files_in_context":[],"model_usage":[
+                {"ts":1710000000000,"model_id":"claude-sonnet-4","model_provider_id":"anthropic","mode":"act"},
+                {"ts":1710000005000,"model_id":"gpt-5","model_provider_id":"openai","mode":"act"}
+            ]}"#,
+        ),
+        (
+            "ui_messages.json",
+            r#"[
+                {"type":"ask","ts":1710000000500,"ask":"followup","text":"Fix the build","conversationHistoryIndex":0},
+                {"type":"say","ts":1710000001000,"say":"api_req_started","text":"{\"request\":\"r\",\"tokensIn\":100,\"tokensOut\":10,\"cacheWrites\":0,\"cacheReads\":0,\"cost\":0.5}","conversationHistoryIndex":1},
+                {"type":"say","ts":1710000002000,"say":"api_req_started","text":"{\"request\":\"r\",\"tokensIn\":100,\"tokensOut\":10,\"cacheWrites\":0,\"cacheReads\":0,\"cost\":0.5,\"cancelReason\":\"user_cancelled\"}","conversationHistoryIndex":2},
+                {"type":"say","ts":1710000006000,"say":"api_req_started","text":"{\"request\":\"r\",\"tokensIn\":1000,\"tokensOut\":100,\"cacheWrites\":0,\"cacheReads\":0}","conversationHistoryIndex":3}
+            ]"#,
+        ),
+    ]);
+
+    let messages = ClineAnalyzer::new()
+        .parse_source(&DataSource { path: task_dir })
+        .unwrap();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].role, MessageRole::User);
+    assert_eq!(messages[1].model.as_deref(), Some("claude-sonnet-4"));
+    assert_eq!(messages[1].stats.cost, 0.5);
+    assert_eq!(messages[1].stats.logged_cost_messages, 1);
+    assert_eq!(messages[1].session_name.as_deref(), Some("Fix the build"));
+    // Logged without a cost, so priced from the model.
+    assert_eq!(messages[2].model.as_deref(), Some("gpt-5"));
+    assert_eq!(
+        messages[2].stats.cost,
+        calculate_total_cost("gpt-5", 1000, 100, 0, 0)
+    );
+    assert_eq!(messages[2].stats.logged_cost_messages, 0);
+}
+
+#[test]
+fn test_cline_without_metadata_is_skipped() {
+    let (_temp_dir, task_dir) = write_task(&[(
+        "ui_messages.json",
+        r#"[{"type":"ask","ts":1710000000500,"ask":"followup","text":"hi","conversationHistoryIndex":0}]"#,
+    )]);
+
+    let messages = ClineAnalyzer::new()
+        .parse_source(&DataSource { path: task_dir })
+        .unwrap();
+    assert!(messages.is_empty());
+}
+
+/// Newer tasks log each request's usage on its turn in
+/// `api_conversation_history.json`; the `api_req_started` copies must not
+/// count a second time.
+#[test]
+fn test_cline_v2_history_metrics_replace_ui_usage() {
+    let (_temp_dir, task_dir) = write_task(&[
+        (
+            "task_metadata.json",
+            r#"{"model_usage":[{"ts":1710000000000,"model_id":"claude-sonnet-4"}]}"#,
+        ),
+        (
+            "api_conversation_history.json",
+            r#"[
+                {"role":"user","content":[{"type":"text","text":"Fix the build"}]},
+                {"role":"assistant","content":[{"type":"text","text":"On it"}],"ts":1710000001000,
+                 "modelInfo":{"modelId":"gpt-5","providerId":"openai","mode":"act"},
+                 "metrics":{"tokens":{"prompt":300,"completion":40,"cached":200},"cost":0.25}},
+                {"role":"user","content":[{"type":"text","text":"thanks"}]},
+                {"role":"assistant","content":[{"type":"text","text":"Done"}],"ts":1710000003000,
+                 "metrics":{"tokens":{"prompt":50,"completion":5}}}
+            ]"#,
+        ),
+        (
+            "ui_messages.json",
+            r#"[
+                {"type":"ask","ts":1710000000500,"ask":"followup","text":"Fix the build","conversationHistoryIndex":0},
+                {"type":"say","ts":1710000001000,"say":"api_req_started","text":"{\"request\":\"r\",\"tokensIn\":300,\"tokensOut\":40,\"cacheWrites\":0,\"cacheReads\":200,\"cost\":0.25}","conversationHistoryIndex":1}
+            ]"#,
+        ),
+    ]);
+
+    let messages = ClineAnalyzer::new()
+        .parse_source(&DataSource { path: task_dir })
+        .unwrap();
+
+    assert_eq!(messages.len(), 3);
+    let assistant: Vec<_> = messages
+        .iter()
+        .filter(|m| m.role == MessageRole::Assistant)
+        .collect();
+    assert_eq!(assistant.len(), 2);
+    assert_eq!(assistant[0].model.as_deref(), Some("gpt-5"));
+    assert_eq!(assistant[0].stats.input_tokens, 300);
+    assert_eq!(assistant[0].stats.output_tokens, 40);
+    assert_eq!(assistant[0].stats.cache_read_tokens, 200);
+    assert_eq!(assistant[0].stats.cost, 0.25);
+    assert_eq!(assistant[0].session_name.as_deref(), Some("Fix the build"));
+    // No `modelInfo`: the model comes from task metadata, the price from it.
+    assert_eq!(assistant[1].model.as_deref(), Some("claude-sonnet-4"));
+    assert_eq!(
+        assistant[1].stats.cost,
+        calculate_total_cost("claude-sonnet-4", 50, 5, 0, 0)
+    );
+}
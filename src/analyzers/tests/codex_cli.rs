@@ -493,3 +493,139 @@ fn test_get_fallback_model_with_home() {
     let model = get_fallback_model_with_home(Some(dir.path().to_path_buf()));
     assert_eq!(model, "gpt-5");
 }
+
+#[test]
+fn test_parse_codex_cli_legacy_rollout_format() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"id":"0196a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b","timestamp":"2025-05-02T09:15:00.000Z","instructions":null}}"#
+    )
+    .unwrap();
+    writeln!(temp_file, r#"{{"record_type":"state"}}"#).unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"type":"message","role":"user","content":[{{"type":"input_text","text":"Port the parser"}}]}}"#
+    )
+    .unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"type":"function_call","name":"shell","arguments":"{{}}","call_id":"call_1"}}"#
+    )
+    .unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"type":"message","role":"assistant","content":[{{"type":"output_text","text":"Done"}}]}}"#
+    )
+    .unwrap();
+    writeln!(temp_file, r#"{{"record_type":"state"}}"#).unwrap();
+
+    let contents = std::fs::read(temp_file.path()).unwrap();
+    assert_eq!(detect_rollout_format(&contents), RolloutFormat::Legacy);
+
+    let (messages, _model) = parse_codex_cli_jsonl_file(temp_file.path()).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].role, crate::types::MessageRole::User);
+    assert_eq!(messages[0].session_name.as_deref(), Some("Port the parser"));
+    assert_eq!(messages[1].role, crate::types::MessageRole::Assistant);
+    assert_eq!(messages[1].date.to_rfc3339(), "2025-05-02T09:15:00+00:00");
+}
+
+#[test]
+fn test_parse_codex_cli_accepts_alternate_usage_field_names() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"timestamp":"2026-01-10T10:00:00.000Z","type":"turn_context","payload":{{"model":"gpt-5-codex"}}}}"#
+    )
+    .unwrap();
+    writeln!(
+        temp_file,
+        r#"{{"timestamp":"2026-01-10T10:00:02.000Z","type":"event_msg","payload":{{"type":"token_count","info":{{"last_token_usage":{{"input_tokens":500,"cached_tokens":400,"output_tokens":40,"reasoning_tokens":25,"total_tokens":540}}}}}}}}"#
+    )
+    .unwrap();
+
+    let contents = std::fs::read(temp_file.path()).unwrap();
+    assert_eq!(detect_rollout_format(&contents), RolloutFormat::Wrapped);
+
+    let (messages, _model) = parse_codex_cli_jsonl_file(temp_file.path()).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].stats.input_tokens, 100);
+    assert_eq!(messages[0].stats.cached_tokens, 400);
+    assert_eq!(messages[0].stats.reasoning_tokens, 25);
+}
+
+#[test]
+fn test_codex_session_names_come_from_the_session_index() {
+    let index = parse_session_index(concat!(
+        r#"{"id":"a","thread_name":"First name","updated_at":"2026-01-01T00:00:00Z"}"#,
+        "\n",
+        r#"{"id":"a","thread_name":"Renamed"}"#,
+        "\n",
+        r#"{"id":"b","thread_name":"Gone"}"#,
+        "\n",
+        r#"{"id":"b","thread_name":""}"#,
+        "\n",
+        "not json\n",
+    ));
+    assert_eq!(index.get("a").map(String::as_str), Some("Renamed"));
+    assert!(!index.contains_key("b"));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let codex_dir = temp_dir.path().join(".codex");
+    let day_dir = codex_dir.join("sessions/2026/07/22");
+    std::fs::create_dir_all(&day_dir).unwrap();
+    let session_path = day_dir.join(SESSION_FILE_NAME);
+    write_test_session(&session_path);
+
+    let (messages, _) = parse_codex_cli_jsonl_file(&session_path).unwrap();
+    assert_eq!(
+        messages[0].session_name.as_deref(),
+        Some("Test archived sessions")
+    );
+
+    std::fs::write(
+        codex_dir.join("session_index.jsonl"),
+        r#"{"id":"243232f1-a7ab-44e6-b2c3-045b673746ea","thread_name":"Archive identity"}"#,
+    )
+    .unwrap();
+    let (messages, _) = parse_codex_cli_jsonl_file(&session_path).unwrap();
+    assert!(
+        messages
+            .iter()
+            .all(|m| m.session_name.as_deref() == Some("Archive identity"))
+    );
+}
+
+#[test]
+fn test_codex_cli_latest_rate_limits_reads_the_last_report() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file
+        .write_all(
+            concat!(
+                r#"{"timestamp":"2025-09-18T00:16:00.000Z","type":"session_meta","payload":{"id":"rate-limits","timestamp":"2025-09-18T00:16:00.000Z"}}"#,
+                "\n",
+                r#"{"timestamp":"2025-09-18T00:16:38.000Z","type":"event_msg","payload":{"type":"token_count","info":null,"rate_limits":{"primary":{"used_percent":10.0,"window_minutes":300,"resets_in_seconds":3600}}}}"#,
+                "\n",
+                r#"{"timestamp":"2025-09-18T01:00:00.000Z","type":"event_msg","payload":{"type":"token_count","info":null,"rate_limits":{"primary":{"used_percent":42.0,"window_minutes":300,"resets_in_seconds":1800},"secondary":{"used_percent":7.5,"window_minutes":10080,"resets_at":1758758400}}}}"#,
+                "\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    let windows = latest_rate_limits(temp_file.path()).unwrap();
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[0].kind, crate::limits::WindowKind::FiveHour);
+    assert_eq!(windows[0].used, Some(0.42));
+    assert_eq!(
+        windows[0].resets_at,
+        "2025-09-18T01:30:00Z"
+            .parse::<chrono::DateTime<chrono::Utc>>()
+            .unwrap()
+    );
+    assert_eq!(windows[1].kind, crate::limits::WindowKind::Weekly);
+    assert_eq!(windows[1].used, Some(0.075));
+    assert_eq!(windows[1].resets_at.timestamp(), 1_758_758_400);
+    assert_eq!(windows[1].tokens, None);
+}
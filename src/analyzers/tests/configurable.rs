@@ -0,0 +1,140 @@
+use crate::analyzer::{Analyzer, DataSource};
+use crate::analyzers::configurable::ConfigurableAnalyzer;
+use crate::config::CustomAnalyzerConfig;
+use crate::contribution_cache::ContributionStrategy;
+use crate::types::{Application, MessageRole};
+use std::fs;
+use tempfile::TempDir;
+
+fn config_for(dir: &TempDir) -> CustomAnalyzerConfig {
+    CustomAnalyzerConfig {
+        name: "Acme Agent".to_string(),
+        glob: format!("{}/*.jsonl", dir.path().display()),
+        timestamp: "ts".to_string(),
+        model: Some("request.model".to_string()),
+        input_tokens: Some("usage.input".to_string()),
+        output_tokens: Some("usage.output".to_string()),
+        cost: Some("usage.cost".to_string()),
+        session: None,
+    }
+}
+
+#[test]
+fn test_configurable_analyzer_display_name() {
+    let dir = TempDir::new().unwrap();
+    let analyzer = ConfigurableAnalyzer::new(config_for(&dir));
+    assert_eq!(analyzer.display_name(), "Acme Agent");
+    assert_eq!(
+        analyzer.contribution_strategy(),
+        ContributionStrategy::SingleSession
+    );
+}
+
+#[test]
+fn test_configurable_analyzer_parses_mapped_fields() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.jsonl");
+    fs::write(
+        &path,
+        concat!(
+            r#"{"ts":"2025-06-01T10:00:00Z","request":{"model":"acme-1"},"usage":{"input":100,"output":20,"cost":0.5}}"#,
+            "\n",
+            "not json\n",
+            r#"{"request":{"model":"acme-1"},"usage":{"input":1}}"#,
+            "\n",
+            r#"{"ts":1748772000000,"usage":{"input":"7","output":3}}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let analyzer = ConfigurableAnalyzer::new(config_for(&dir));
+    let sources = analyzer.discover_data_sources().unwrap();
+    assert_eq!(sources.len(), 1);
+
+    let messages = analyzer.parse_source(&sources[0]).unwrap();
+    // The invalid line and the line without a timestamp are skipped.
+    assert_eq!(messages.len(), 2);
+
+    let first = &messages[0];
+    assert_eq!(first.application, Application::Custom);
+    assert_eq!(first.role, MessageRole::Assistant);
+    assert_eq!(first.model.as_deref(), Some("acme-1"));
+    assert_eq!(first.stats.input_tokens, 100);
+    assert_eq!(first.stats.output_tokens, 20);
+    assert_eq!(first.stats.cost, 0.5);
+
+    let second = &messages[1];
+    assert_eq!(second.date.timestamp_millis(), 1748772000000);
+    assert_eq!(second.stats.input_tokens, 7);
+    assert_eq!(second.stats.output_tokens, 3);
+    assert!(second.model.is_none());
+    assert_eq!(second.stats.cost, 0.0);
+    assert_eq!(first.conversation_hash, second.conversation_hash);
+}
+
+#[test]
+fn test_configurable_analyzer_computes_cost_from_model_pricing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.jsonl");
+    fs::write(
+        &path,
+        r#"{"ts":1748772000,"request":{"model":"gpt-4.5"},"usage":{"input":1000000,"output":0}}"#,
+    )
+    .unwrap();
+
+    let analyzer = ConfigurableAnalyzer::new(config_for(&dir));
+    let messages = analyzer.parse_source(&DataSource { path }).unwrap();
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].date.timestamp(), 1748772000);
+    assert!((messages[0].stats.cost - 75.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_configurable_analyzer_session_path_and_array_indices() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.jsonl");
+    fs::write(
+        &path,
+        concat!(
+            r#"{"ts":"2025-06-01T10:00:00Z","sid":"a","choices":[{"model":"m"}]}"#,
+            "\n",
+            r#"{"ts":"2025-06-01T11:00:00Z","sid":"b","choices":[{"model":"m"}]}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let mut config = config_for(&dir);
+    config.model = Some("choices.0.model".to_string());
+    config.session = Some("sid".to_string());
+    let analyzer = ConfigurableAnalyzer::new(config);
+    assert_eq!(
+        analyzer.contribution_strategy(),
+        ContributionStrategy::MultiSession
+    );
+
+    let messages = analyzer.parse_source(&DataSource { path }).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].model.as_deref(), Some("m"));
+    assert_ne!(messages[0].conversation_hash, messages[1].conversation_hash);
+}
+
+#[test]
+fn test_configurable_analyzer_paths() {
+    let dir = TempDir::new().unwrap();
+    let log = dir.path().join("log.jsonl");
+    let other = dir.path().join("log.txt");
+    fs::write(&log, "").unwrap();
+    fs::write(&other, "").unwrap();
+
+    let analyzer = ConfigurableAnalyzer::new(config_for(&dir));
+    assert!(analyzer.is_valid_data_path(&log));
+    assert!(!analyzer.is_valid_data_path(&other));
+    assert_eq!(
+        analyzer.get_watch_directories(),
+        vec![dir.path().to_path_buf()]
+    );
+    assert!(analyzer.is_available());
+}
@@ -221,3 +221,54 @@ fn test_parse_empty_copilot_session_without_optional_metadata_fields() {
         parse_copilot_session_file(&session_path).expect("empty sessions should parse cleanly");
     assert!(messages.is_empty());
 }
+
+#[test]
+fn test_copilot_requests_count_premium_requests_by_model_multiplier() {
+    let dir = tempfile::tempdir().unwrap();
+    let session_path = dir.path().join("premium-session.json");
+    std::fs::write(
+        &session_path,
+        r#"{
+            "version": 3,
+            "sessionId": "premium-session",
+            "requests": [
+                {
+                    "requestId": "request-1",
+                    "message": {"text": "Refactor the parser"},
+                    "response": [{"value": "Done."}],
+                    "timestamp": 1768224201981,
+                    "modelId": "copilot/claude-opus-4.1"
+                },
+                {
+                    "requestId": "request-2",
+                    "message": {"text": "Now add a test"},
+                    "response": [{"value": "Added."}],
+                    "timestamp": 1768224261981,
+                    "modelId": "copilot/gpt-4.1"
+                },
+                {
+                    "requestId": "request-3",
+                    "message": {"text": "Explain the change"},
+                    "response": [{"value": "Sure."}],
+                    "timestamp": 1768224321981,
+                    "modelId": "copilot/o4-mini"
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let messages = parse_copilot_session_file(&session_path).unwrap();
+    let premium: Vec<f64> = messages
+        .iter()
+        .filter(|message| message.role == MessageRole::Assistant)
+        .map(|message| message.stats.premium_requests)
+        .collect();
+    assert_eq!(premium, vec![10.0, 0.0, 0.33]);
+    assert!(
+        messages
+            .iter()
+            .filter(|message| message.role == MessageRole::User)
+            .all(|message| message.stats.premium_requests == 0.0)
+    );
+}
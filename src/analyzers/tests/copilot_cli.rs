@@ -208,6 +208,10 @@ fn test_copilot_cli_uses_shutdown_metrics_for_multi_turn_sessions() {
     let expected_claude_cost =
         calculate_total_cost("claude-sonnet-4.5", 100_000, 8_700, 0, 2_200_000);
     assert!((messages[2].stats.cost - expected_claude_cost).abs() < f64::EPSILON);
+
+    // One prompt is one premium request, however many turns answer it.
+    assert_eq!(messages[1].stats.premium_requests, 1.0);
+    assert_eq!(messages[2].stats.premium_requests, 0.0);
 }
 
 #[test]
@@ -523,3 +523,225 @@ async fn test_gemini_cli_jsonl_latest_message_version_wins() {
     assert_eq!(assistant.stats.terminal_commands, 1);
     assert!(analyzer.is_valid_data_path(&session_path));
 }
+
+/// Some Gemini CLI builds log cumulative usage on every streamed chunk. The
+/// chunks of one response must count once, with the final chunk's totals.
+#[tokio::test]
+async fn test_gemini_cli_streamed_chunks_use_final_totals() {
+    let sample_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("analyzers")
+        .join("tests")
+        .join("source_data")
+        .join("gemini_cli_streamed.json");
+
+    let analyzer = GeminiCliAnalyzer::new();
+    let source = crate::analyzer::DataSource { path: sample_path };
+    let messages = analyzer.parse_sources_parallel(&[source]);
+
+    // 1 user turn, 3 chunks of one response, 1 follow-up response.
+    assert_eq!(messages.len(), 3);
+
+    let streamed = &messages[1];
+    assert_eq!(streamed.role, crate::types::MessageRole::Assistant);
+    assert_eq!(streamed.date.to_rfc3339(), "2025-12-02T08:00:02+00:00");
+    assert_eq!(streamed.stats.input_tokens, 1200);
+    assert_eq!(streamed.stats.output_tokens, 42);
+    assert_eq!(streamed.stats.reasoning_tokens, 90);
+    assert_eq!(streamed.stats.cached_tokens, 300);
+    assert_eq!(
+        streamed.stats.tool_calls, 1,
+        "repeated tool call counts once"
+    );
+    assert_eq!(streamed.stats.files_read, 1);
+
+    let follow_up = &messages[2];
+    assert_eq!(follow_up.stats.input_tokens, 2400);
+    assert_eq!(follow_up.stats.output_tokens, 25);
+
+    let total_output: u64 = messages.iter().map(|m| m.stats.output_tokens).sum();
+    assert_eq!(total_output, 67);
+}
+
+/// A retry repeats the prompt, so its usage looks like a later chunk of the
+/// failed response; it is only folded in when logged within the streaming
+/// window.
+#[tokio::test]
+async fn test_gemini_cli_retries_are_not_folded_into_the_previous_response() {
+    let dir = tempdir().unwrap();
+    let project_dir = dir.path().join("tmp").join("project-retry").join("chats");
+    let json_content = r#"{
+        "sessionId": "sess-retry",
+        "messages": [
+            {"type": "user", "id": "u-1", "timestamp": "2025-12-02T08:00:00Z", "content": "Hi"},
+            {"type": "gemini", "id": "g-1", "timestamp": "2025-12-02T08:00:02Z", "content": "",
+             "model": "gemini-2.5-pro",
+             "tokens": {"input": 500, "output": 10, "cached": 100, "thoughts": 0, "tool": 0}},
+            {"type": "gemini", "id": "g-2", "timestamp": "2025-12-02T08:00:09Z", "content": "Hello",
+             "model": "gemini-2.5-pro",
+             "tokens": {"input": 500, "output": 20, "cached": 100, "thoughts": 0, "tool": 0}}
+        ]
+    }"#;
+    let session_path = write_session(&project_dir, json_content);
+
+    let analyzer = GeminiCliAnalyzer::new();
+    let source = crate::analyzer::DataSource { path: session_path };
+    let messages = analyzer.parse_sources_parallel(&[source]);
+
+    assert_eq!(messages.len(), 3);
+    let total_input: u64 = messages.iter().map(|m| m.stats.input_tokens).sum();
+    assert_eq!(total_input, 1000);
+    let total_output: u64 = messages.iter().map(|m| m.stats.output_tokens).sum();
+    assert_eq!(total_output, 30);
+}
+
+/// Schema drift in one message must not cost the rest of the session: bad
+/// fields read as absent, messages that can't be placed are dropped, and
+/// each problem is reported once as a parse warning.
+#[tokio::test]
+async fn test_gemini_cli_schema_drift_degrades_to_warnings() {
+    let dir = tempdir().unwrap();
+    let project_dir = dir.path().join("tmp").join("project-drift").join("chats");
+    let json_content = r#"{
+        "sessionId": 42,
+        "messages": [
+            {
+                "type": "user",
+                "id": "msg-1",
+                "timestamp": "2025-11-20T10:00:00Z",
+                "content": [{"text": "Describe "}, {"inlineData": {"mimeType": "image/png"}}, "this image"]
+            },
+            {
+                "type": "gemini",
+                "id": "msg-2",
+                "timestamp": "2025-11-20T10:00:05Z",
+                "model": "gemini-2.5-pro",
+                "tokens": {"input": "40", "output": "a few", "thoughts": 2.0, "cached": 0}
+            },
+            {
+                "type": "gemini",
+                "id": "msg-3",
+                "model": "gemini-2.5-pro",
+                "tokens": {"input": 1, "output": 1}
+            },
+            {
+                "type": "citation",
+                "id": "msg-4",
+                "timestamp": "2025-11-20T10:00:06Z"
+            }
+        ]
+    }"#;
+    let session_path = write_session(&project_dir, json_content);
+
+    let analyzer = GeminiCliAnalyzer::new();
+    let source = crate::analyzer::DataSource {
+        path: session_path.clone(),
+    };
+    let messages = analyzer.parse_sources_parallel(&[source]);
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].session_name.as_deref(),
+        Some("Describe this image")
+    );
+    assert_eq!(messages[1].stats.input_tokens, 40);
+    assert_eq!(messages[1].stats.output_tokens, 0);
+    assert_eq!(messages[1].stats.reasoning_tokens, 2);
+
+    let issue = crate::diagnostics::parse_issues()
+        .into_iter()
+        .find(|issue| issue.path == session_path)
+        .expect("drift reported");
+    assert_eq!(issue.analyzer, "Gemini CLI");
+    assert_eq!(issue.count, 3, "{issue:?}");
+}
+
+fn write_chat(chats_dir: &std::path::Path, name: &str, body: &str) -> std::path::PathBuf {
+    std::fs::create_dir_all(chats_dir).unwrap();
+    let path = chats_dir.join(name);
+    std::fs::write(&path, body).unwrap();
+    path
+}
+
+const RESUMABLE_CHAT: &str = r#"{
+    "sessionId": "sess-resumed",
+    "messages": [
+        {"type": "user", "id": "u-1", "timestamp": "2026-07-01T09:00:00Z", "content": "start"},
+        {"type": "gemini", "id": "g-1", "timestamp": "2026-07-01T09:00:05Z", "content": "ok",
+         "model": "gemini-2.5-pro",
+         "tokens": {"input": 100, "output": 10, "cached": 0, "thoughts": 0, "tool": 0, "total": 110}}
+    ]
+}"#;
+
+#[test]
+fn test_gemini_cli_discovers_nested_chats_and_skips_checkpoints() {
+    let dir = tempdir().unwrap();
+    let tmp = dir.path().join("tmp");
+    let chats = tmp.join("project-a").join("chats");
+    let nested = write_chat(&chats.join("2026-07"), "session-1.jsonl", "{}\n");
+    let migrated = write_chat(&chats, "session-2.jsonl", "{}\n");
+    write_chat(&chats, "session-2.json", "{}");
+    write_chat(&chats, "checkpoint-before-refactor.json", "{}");
+    write_chat(&chats.join("checkpoints"), "auto.json", "{}");
+    let plain = write_chat(&tmp.join("project-b").join("chats"), "session-3.json", "{}");
+
+    let analyzer = GeminiCliAnalyzer::new().tmp_dirs(vec![tmp]);
+    let mut paths: Vec<_> = analyzer
+        .discover_data_sources()
+        .unwrap()
+        .into_iter()
+        .map(|source| source.path)
+        .collect();
+    paths.sort();
+    let mut expected = vec![nested, migrated, plain];
+    expected.sort();
+
+    assert_eq!(paths, expected);
+    assert!(
+        analyzer
+            .get_data_glob_patterns()
+            .iter()
+            .all(|pattern| pattern.contains("/**/chats/**/"))
+    );
+}
+
+#[test]
+fn test_gemini_cli_resumed_session_counts_once() {
+    let dir = tempdir().unwrap();
+    let chats = dir.path().join("tmp").join("project-a").join("chats");
+    let original = write_chat(&chats, "session-1.json", RESUMABLE_CHAT);
+    let resumed_body = RESUMABLE_CHAT.replace(
+        "\"total\": 110}}",
+        "\"total\": 110}},
+        {\"type\": \"gemini\", \"id\": \"g-2\", \"timestamp\": \"2026-07-02T10:00:00Z\", \"content\": \"again\",
+         \"model\": \"gemini-2.5-pro\",
+         \"tokens\": {\"input\": 200, \"output\": 20, \"cached\": 0, \"thoughts\": 0, \"tool\": 0, \"total\": 220}}",
+    );
+    let resumed = write_chat(&chats.join("resumed"), "session-2.json", &resumed_body);
+
+    let analyzer = GeminiCliAnalyzer::new();
+    // The TUI, live updates and uploads all parse through the grouped path.
+    let grouped = analyzer.parse_sources_parallel_with_paths(&[
+        crate::analyzer::DataSource {
+            path: original.clone(),
+        },
+        crate::analyzer::DataSource {
+            path: resumed.clone(),
+        },
+    ]);
+
+    assert_eq!(grouped.len(), 2);
+    assert_eq!(grouped[0].0, original);
+    assert_eq!(grouped[0].1.len(), 2);
+    assert_eq!(grouped[1].0, resumed);
+    assert_eq!(grouped[1].1.len(), 1);
+    let input: u64 = grouped
+        .iter()
+        .flat_map(|(_, messages)| messages)
+        .map(|m| m.stats.input_tokens)
+        .sum();
+    assert_eq!(input, 300);
+    // Batching or per-file reloads would miss the other file's copies.
+    assert!(analyzer.requires_full_reload_for_source_change());
+    assert!(!analyzer.supports_batched_parsing());
+}
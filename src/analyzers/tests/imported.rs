@@ -0,0 +1,41 @@
+use crate::analyzer::Analyzer;
+use crate::analyzers::imported::ImportedAnalyzer;
+use crate::types::Application;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_imported_analyzer_reads_rows_and_dedupes_across_files() {
+    let dir = TempDir::new().unwrap();
+    let rows = concat!(
+        r#"{"date":"2025-06-01","tool":"ccusage","model":"claude-sonnet-4-20250514","input_tokens":10,"output_tokens":100,"cache_read_tokens":50,"cost":0.5}"#,
+        "\n",
+        r#"{"date":"2025-06-02","tool":"ccusage","model":"claude-sonnet-4-20250514","output_tokens":1,"cost":0.25}"#,
+        "\n",
+        "not json\n",
+    );
+    fs::write(dir.path().join("ccusage-old.jsonl"), rows).unwrap();
+    // A newer export overlapping the first day.
+    fs::write(
+        dir.path().join("ccusage-new.jsonl"),
+        &rows[..rows.find('\n').unwrap() + 1],
+    )
+    .unwrap();
+    fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+    let analyzer = ImportedAnalyzer::new().dir(dir.path().to_path_buf());
+    assert_eq!(analyzer.display_name(), "Imported");
+    let sources = analyzer.discover_data_sources().unwrap();
+    assert_eq!(sources.len(), 2);
+
+    let messages = analyzer.parse_sources_parallel(&sources);
+    assert_eq!(messages.len(), 2);
+    let first = messages
+        .iter()
+        .find(|message| message.stats.cost == 0.5)
+        .unwrap();
+    assert_eq!(first.application, Application::Imported);
+    assert_eq!(first.stats.cached_tokens, 50);
+    assert_eq!(first.session_name.as_deref(), Some("ccusage 2025-06-01"));
+    assert_ne!(messages[0].conversation_hash, messages[1].conversation_hash);
+}
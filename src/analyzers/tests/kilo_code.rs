@@ -1,5 +1,7 @@
-use crate::analyzer::Analyzer;
+use crate::analyzer::{Analyzer, DataSource};
 use crate::analyzers::kilo_code::KiloCodeAnalyzer;
+use std::fs;
+use tempfile::tempdir;
 
 #[test]
 fn test_kilo_code_analyzer_creation() {
@@ -30,3 +32,37 @@ async fn test_kilo_code_get_stats_empty_sources() {
     assert!(result.is_ok());
     assert!(result.unwrap().messages.is_empty());
 }
+
+/// Kilo Code logs what its gateway billed, so a request without a cost was
+/// free rather than unpriced.
+#[test]
+fn test_kilo_code_trusts_billed_cost() {
+    let temp_dir = tempdir().unwrap();
+    let task_dir = temp_dir.path().join("kilocode.kilo-code/tasks/task-1");
+    fs::create_dir_all(&task_dir).unwrap();
+    fs::write(
+        task_dir.join("api_conversation_history.json"),
+        r#"[{"role":"user","content":[{"type":"text","text":"<model>anthropic/claude-sonnet-4</model>"}]}]"#,
+    )
+    .unwrap();
+    fs::write(
+        task_dir.join("ui_messages.json"),
+        r#"[
+            {"type":"say","ts":1710000001000,"say":"api_req_started","text":"{\"apiProtocol\":\"openai\",\"tokensIn\":100,\"tokensOut\":10,\"cacheWrites\":0,\"cacheReads\":0,\"cost\":0.0042}"},
+            {"type":"say","ts":1710000002000,"say":"api_req_started","text":"{\"apiProtocol\":\"openai\",\"tokensIn\":100,\"tokensOut\":10,\"cacheWrites\":0,\"cacheReads\":0}"}
+        ]"#,
+    )
+    .unwrap();
+
+    let messages = KiloCodeAnalyzer::new()
+        .parse_source(&DataSource { path: task_dir })
+        .unwrap();
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[0].model.as_deref(),
+        Some("anthropic/claude-sonnet-4")
+    );
+    assert_eq!(messages[0].stats.cost, 0.0042);
+    assert_eq!(messages[1].stats.cost, 0.0);
+}
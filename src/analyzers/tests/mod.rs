@@ -2,9 +2,11 @@ mod antigravity;
 mod claude_code;
 mod cline;
 mod codex_cli;
+mod configurable;
 mod copilot;
 mod copilot_cli;
 mod gemini_cli;
+mod imported;
 mod kilo_cli;
 mod kilo_code;
 mod opencode;
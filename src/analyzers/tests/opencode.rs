@@ -1,5 +1,7 @@
 use crate::analyzer::Analyzer;
 use crate::analyzers::opencode::OpenCodeAnalyzer;
+use crate::contribution_cache::ContributionStrategy;
+use crate::types::Application;
 
 #[test]
 fn test_opencode_analyzer_creation() {
@@ -30,3 +32,22 @@ async fn test_opencode_get_stats_empty_sources() {
     assert!(result.is_ok());
     assert!(result.unwrap().messages.is_empty());
 }
+
+#[test]
+fn test_opencode_watches_json_messages_and_databases() {
+    let patterns = OpenCodeAnalyzer::new().get_data_glob_patterns();
+    if dirs::home_dir().is_some() {
+        assert!(
+            patterns
+                .iter()
+                .any(|p| p.ends_with("storage/message/*/*.json"))
+        );
+        assert!(patterns.iter().any(|p| p.ends_with("opencode.db")));
+        assert!(patterns.iter().any(|p| p.ends_with("opencode-*.db")));
+    }
+    // Legacy JSON storage keeps one message per file.
+    assert_eq!(
+        Application::OpenCode.info().strategy,
+        ContributionStrategy::SingleMessage
+    );
+}
@@ -115,3 +115,96 @@ fn test_parse_sample_qwen_code_session() {
     let total_output: u64 = messages.iter().map(|m| m.stats.output_tokens).sum();
     assert!(total_input > 0 && total_output > 0);
 }
+
+#[test]
+fn test_parse_streamed_qwen_code_session() {
+    let sample_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("analyzers")
+        .join("tests")
+        .join("source_data")
+        .join("qwen_code_streamed.jsonl");
+
+    let messages =
+        parse_jsonl_session_file(&sample_path).expect("streamed Qwen Code session should parse");
+
+    // 1 user turn, then two responses streamed as 3 and 2 cumulative chunks.
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].role, MessageRole::User);
+
+    // First response: final chunk totals, keyed by the first chunk's record.
+    assert_eq!(messages[1].role, MessageRole::Assistant);
+    assert_eq!(
+        messages[1].uuid.as_deref(),
+        Some("5b0e7c1a-8f7e-4a43-9d0e-2f3c2b7f0a02")
+    );
+    assert_eq!(messages[1].stats.input_tokens, 600);
+    assert_eq!(messages[1].stats.cached_tokens, 200);
+    assert_eq!(messages[1].stats.output_tokens, 55);
+    assert_eq!(messages[1].stats.reasoning_tokens, 4);
+    assert_eq!(
+        messages[1].stats.tool_calls, 1,
+        "a function call repeated across chunks counts once"
+    );
+
+    // Second response starts a new prompt after the tool result.
+    assert_eq!(messages[2].stats.input_tokens, 100);
+    assert_eq!(messages[2].stats.cached_tokens, 800);
+    assert_eq!(messages[2].stats.output_tokens, 12);
+    assert_eq!(messages[2].stats.tool_calls, 0);
+}
+
+#[test]
+fn test_qwen_code_retries_are_not_folded_into_the_previous_response() {
+    let dir = tempfile::tempdir().unwrap();
+    let session_path = dir.path().join("session.jsonl");
+    std::fs::write(
+        &session_path,
+        [
+            r#"{"uuid":"u1","type":"user","timestamp":"2026-06-20T09:15:00Z","message":{"parts":[{"text":"Hi"}]}}"#,
+            r#"{"uuid":"a1","type":"assistant","timestamp":"2026-06-20T09:15:02Z","model":"coder-model","message":{"parts":[{"text":"He"}]},"usageMetadata":{"promptTokenCount":500,"candidatesTokenCount":10,"cachedContentTokenCount":100}}"#,
+            r#"{"uuid":"a2","type":"assistant","timestamp":"2026-06-20T09:15:09Z","model":"coder-model","message":{"parts":[{"text":"Hello"}]},"usageMetadata":{"promptTokenCount":500,"candidatesTokenCount":20,"cachedContentTokenCount":100}}"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    // The second response came after the retry delay, so both count.
+    let messages = parse_jsonl_session_file(&session_path).expect("session should parse");
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1].stats.output_tokens, 10);
+    assert_eq!(messages[2].stats.output_tokens, 20);
+    assert_eq!(messages[2].uuid.as_deref(), Some("a2"));
+}
+
+#[test]
+fn test_qwen_code_schema_drift_keeps_the_rest_of_the_record() {
+    let dir = tempfile::tempdir().unwrap();
+    let chats = dir.path().join("projects").join("drift").join("chats");
+    std::fs::create_dir_all(&chats).unwrap();
+    let session_path = chats.join("session.jsonl");
+    std::fs::write(
+        &session_path,
+        [
+            r#"{"uuid":"u1","type":"user","timestamp":"2025-11-20T10:00:00Z","message":{"parts":[{"text":"Hi","thought":"no"}]}}"#,
+            r#"{"uuid":"a1","type":"assistant","timestamp":"2025-11-20T10:00:05Z","model":"qwen3-coder-plus","message":{"parts":{"text":"Hello"}},"usageMetadata":{"promptTokenCount":120,"candidatesTokenCount":"7","cachedContentTokenCount":null}}"#,
+            r#"{"uuid":"a2","type":"assistant","timestamp":"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    let messages = parse_jsonl_session_file(&session_path).expect("session should parse");
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].session_name.as_deref(), Some("Hi"));
+    assert_eq!(messages[1].stats.input_tokens, 120);
+    assert_eq!(messages[1].stats.output_tokens, 7);
+
+    let issue = crate::diagnostics::parse_issues()
+        .into_iter()
+        .find(|issue| issue.path == session_path)
+        .expect("drift reported");
+    // `thought` isn't a boolean, `parts` isn't an array, and the last line
+    // was cut off mid-write.
+    assert_eq!(issue.count, 3, "{issue:?}");
+}
@@ -1,5 +1,7 @@
-use crate::analyzer::Analyzer;
+use crate::analyzer::{Analyzer, DataSource};
 use crate::analyzers::roo_code::RooCodeAnalyzer;
+use std::fs;
+use tempfile::tempdir;
 
 #[test]
 fn test_roo_code_analyzer_creation() {
@@ -30,3 +32,32 @@ async fn test_roo_code_get_stats_empty_sources() {
     assert!(result.is_ok());
     assert!(result.unwrap().messages.is_empty());
 }
+
+#[test]
+fn test_roo_code_session_name_skips_shell_commands() {
+    let temp_dir = tempdir().unwrap();
+    let task_dir = temp_dir
+        .path()
+        .join("rooveterinaryinc.roo-cline/tasks/9f365349-84f2-4a9a-b470-f94910583293");
+    fs::create_dir_all(&task_dir).unwrap();
+    fs::write(
+        task_dir.join("ui_messages.json"),
+        r#"[
+            {"type":"ask","ts":1710000000000,"ask":"command","text":"cargo test --workspace"},
+            {"type":"ask","ts":1710000001000,"ask":"followup","text":"Why do the tests fail?"}
+        ]"#,
+    )
+    .unwrap();
+
+    let messages = RooCodeAnalyzer::new()
+        .parse_source(&DataSource { path: task_dir })
+        .unwrap();
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].session_name, None);
+    assert_eq!(
+        messages[1].session_name.as_deref(),
+        Some("Why do the tests fail?")
+    );
+    assert_ne!(messages[0].local_hash, messages[1].local_hash);
+}
@@ -1,95 +1,67 @@
-use crate::analyzer::{
-    Analyzer, DataSource, discover_vscode_extension_sources, get_vscode_extension_tasks_dirs,
-    vscode_extension_has_sources,
+use super::cline_family::{
+    ClineForkConfig, ClineFormatAnalyzer, LoggedCost, MessageIndex, ModelSource,
 };
-use crate::analyzers::roo_code::parse_roo_format_task_directory;
+use crate::analyzer::{Analyzer, DataSource};
 use crate::contribution_cache::ContributionStrategy;
 use crate::types::{Application, ConversationMessage};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 
-const ZOO_CODE_EXTENSION_ID: &str = "zoocodeorganization.zoo-code";
-
-pub struct ZooCodeAnalyzer;
+/// Analyzer for the Zoo Code VSCode extension, which keeps Roo Code's task
+/// format under its own extension ID.
+pub struct ZooCodeAnalyzer {
+    delegate: ClineFormatAnalyzer,
+}
 
 impl ZooCodeAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self {
+            delegate: ClineFormatAnalyzer::new(ClineForkConfig {
+                application: Application::ZooCode,
+                extension_id: "zoocodeorganization.zoo-code",
+                model_source: ModelSource::EnvironmentDetails,
+                message_index: MessageIndex::Sequential,
+                user_asks: &["followup", "command", "tool", "completion_result"],
+                skip_cancelled: false,
+                skip_commands_in_session_name: true,
+                logged_cost: LoggedCost::Estimate,
+            }),
+        }
     }
 }
 
 #[async_trait]
 impl Analyzer for ZooCodeAnalyzer {
     fn display_name(&self) -> &'static str {
-        "Zoo Code"
+        self.delegate.display_name()
     }
 
     fn get_data_glob_patterns(&self) -> Vec<String> {
-        let mut patterns = Vec::new();
-        let vscode_gui_forks = [
-            "Antigravity",
-            "Code",
-            "Code - Insiders",
-            "Cursor",
-            "Positron",
-            "VSCodium",
-            "Windsurf",
-        ];
-        let vscode_cli_forks = ["vscode-server-insiders", "vscode-server"];
-
-        if let Some(home_dir) = dirs::home_dir() {
-            let home_str = home_dir.to_string_lossy();
-
-            for fork in &vscode_gui_forks {
-                patterns.push(format!(
-                    "{home_str}/.config/{fork}/User/globalStorage/{ZOO_CODE_EXTENSION_ID}/tasks/*/ui_messages.json"
-                ));
-            }
-            for fork in &vscode_cli_forks {
-                patterns.push(format!(
-                    "{home_str}/.{fork}/data/User/globalStorage/{ZOO_CODE_EXTENSION_ID}/tasks/*/ui_messages.json"
-                ));
-            }
-            for fork in &vscode_gui_forks {
-                patterns.push(format!(
-                    "{home_str}/Library/Application Support/{fork}/User/globalStorage/{ZOO_CODE_EXTENSION_ID}/tasks/*/ui_messages.json"
-                ));
-            }
-        }
-
-        if let Ok(appdata) = std::env::var("APPDATA") {
-            for fork in &vscode_gui_forks {
-                patterns.push(format!(
-                    "{appdata}\\{fork}\\User\\globalStorage\\{ZOO_CODE_EXTENSION_ID}\\tasks\\*\\ui_messages.json"
-                ));
-            }
-        }
-
-        patterns
+        self.delegate.get_data_glob_patterns()
     }
 
     fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
-        discover_vscode_extension_sources(ZOO_CODE_EXTENSION_ID, "ui_messages.json", true)
+        self.delegate.discover_data_sources()
     }
 
     fn is_available(&self) -> bool {
-        vscode_extension_has_sources(ZOO_CODE_EXTENSION_ID, "ui_messages.json")
+        self.delegate.is_available()
     }
 
     fn parse_source(&self, source: &DataSource) -> Result<Vec<ConversationMessage>> {
-        parse_roo_format_task_directory(&source.path, Application::ZooCode)
+        self.delegate.parse_source(source)
     }
 
     fn get_watch_directories(&self) -> Vec<PathBuf> {
-        get_vscode_extension_tasks_dirs(ZOO_CODE_EXTENSION_ID)
+        self.delegate.get_watch_directories()
     }
 
     fn is_valid_data_path(&self, path: &Path) -> bool {
-        path.is_file() && path.file_name().is_some_and(|n| n == "ui_messages.json")
+        self.delegate.is_valid_data_path(path)
     }
 
     fn contribution_strategy(&self) -> ContributionStrategy {
-        ContributionStrategy::SingleSession
+        self.delegate.contribution_strategy()
     }
 }
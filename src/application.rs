@@ -0,0 +1,242 @@
+//! Static metadata for every [`Application`].
+//!
+//! Names, slugs, default contribution strategies, colors, and data directories
+//! used to be spelled out separately by each analyzer, the cache, and the TUI.
+//! They now live in [`APPS`], so adding a tool means adding one row here.
+
+use crate::contribution_cache::ContributionStrategy;
+use crate::types::Application;
+use ratatui::style::Color;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct AppInfo {
+    pub application: Application,
+    /// Name shown in tabs, stats output, and upload payloads.
+    pub name: &'static str,
+    /// Stable snake_case identifier. Also the `global_hash` prefix for the
+    /// OpenCode-format analyzers, so it must never change once shipped.
+    pub slug: &'static str,
+    /// How the analyzer's files map onto cached contributions. Analyzers that
+    /// switch storage formats at runtime (OpenCode, Kilo CLI) may override it.
+    pub strategy: ContributionStrategy,
+    /// Brand color for the TUI; `None` falls back to a palette entry.
+    pub color: Option<Color>,
+    /// Primary data directory relative to the home directory, for tools whose
+    /// location doesn't depend on the platform or editor fork.
+    pub home_dir: Option<&'static str>,
+}
+
+/// One row per [`Application`] variant, in declaration order.
+pub static APPS: [AppInfo; 17] = [
+    AppInfo {
+        application: Application::ClaudeCode,
+        name: "Claude Code",
+        slug: "claude_code",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(217, 119, 87)),
+        home_dir: Some(".claude/projects"),
+    },
+    AppInfo {
+        application: Application::GeminiCli,
+        name: "Gemini CLI",
+        slug: "gemini_cli",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(66, 133, 244)),
+        home_dir: Some(".gemini/tmp"),
+    },
+    AppInfo {
+        application: Application::QwenCode,
+        name: "Qwen Code",
+        slug: "qwen_code",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(97, 92, 237)),
+        home_dir: Some(".qwen/projects"),
+    },
+    AppInfo {
+        application: Application::CodexCli,
+        name: "Codex CLI",
+        slug: "codex_cli",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(16, 163, 127)),
+        home_dir: Some(".codex/sessions"),
+    },
+    AppInfo {
+        application: Application::Cline,
+        name: "Cline",
+        slug: "cline",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Gray),
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::RooCode,
+        name: "Roo Code",
+        slug: "roo_code",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(232, 170, 80)),
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::ZooCode,
+        name: "Zoo Code",
+        slug: "zoo_code",
+        strategy: ContributionStrategy::SingleSession,
+        color: None,
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::KiloCode,
+        name: "Kilo Code",
+        slug: "kilo_code",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(242, 214, 0)),
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::KiloCli,
+        name: "Kilo CLI",
+        slug: "kilo_cli",
+        strategy: ContributionStrategy::SingleMessage,
+        color: Some(Color::Rgb(242, 214, 0)),
+        home_dir: Some(".local/share/kilo"),
+    },
+    AppInfo {
+        application: Application::Copilot,
+        name: "GitHub Copilot",
+        slug: "copilot",
+        strategy: ContributionStrategy::SingleSession,
+        color: Some(Color::Rgb(137, 87, 229)),
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::CopilotCli,
+        name: "GitHub Copilot CLI",
+        slug: "copilot_cli",
+        strategy: ContributionStrategy::SingleSession,
+        // Set apart from VS Code chat so the two read differently in All Tools.
+        color: Some(Color::Rgb(219, 97, 162)),
+        home_dir: Some(".copilot"),
+    },
+    AppInfo {
+        application: Application::OpenCode,
+        name: "OpenCode",
+        slug: "opencode",
+        strategy: ContributionStrategy::SingleMessage,
+        color: Some(Color::LightCyan),
+        home_dir: Some(".local/share/opencode"),
+    },
+    AppInfo {
+        application: Application::PiAgent,
+        name: "Pi Agent",
+        slug: "pi_agent",
+        strategy: ContributionStrategy::SingleSession,
+        color: None,
+        home_dir: Some(".pi/agent/sessions"),
+    },
+    AppInfo {
+        application: Application::Piebald,
+        name: "Piebald",
+        slug: "piebald",
+        strategy: ContributionStrategy::MultiSession,
+        color: None,
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::AntigravityCli,
+        name: "Antigravity CLI",
+        slug: "antigravity_cli",
+        strategy: ContributionStrategy::SingleSession,
+        color: None,
+        home_dir: Some(".gemini/antigravity-cli/conversations"),
+    },
+    AppInfo {
+        application: Application::Custom,
+        name: "Custom",
+        slug: "custom",
+        strategy: ContributionStrategy::SingleSession,
+        color: None,
+        home_dir: None,
+    },
+    AppInfo {
+        application: Application::Imported,
+        name: "Imported",
+        slug: "imported",
+        strategy: ContributionStrategy::MultiSession,
+        color: None,
+        home_dir: None,
+    },
+];
+
+impl Application {
+    pub const fn info(self) -> &'static AppInfo {
+        &APPS[self as usize]
+    }
+
+    pub const fn display_name(self) -> &'static str {
+        self.info().name
+    }
+
+    pub const fn slug(self) -> &'static str {
+        self.info().slug
+    }
+
+    /// Absolute default data directory, when the tool has a fixed one.
+    pub fn default_data_dir(self) -> Option<PathBuf> {
+        let dir = self.info().home_dir?;
+        dirs::home_dir().map(|home| home.join(dir))
+    }
+
+    /// Look up a built-in tool by display name or slug, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        APPS.iter()
+            .find(|info| {
+                info.name.eq_ignore_ascii_case(name) || info.slug.eq_ignore_ascii_case(name)
+            })
+            .map(|info| info.application)
+            .filter(|app| *app != Application::Custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_indexed_by_variant() {
+        for (index, info) in APPS.iter().enumerate() {
+            assert_eq!(
+                info.application as usize, index,
+                "{} is out of order",
+                info.name
+            );
+        }
+    }
+
+    #[test]
+    fn copilot_chat_and_cli_are_told_apart() {
+        let chat = Application::Copilot.info();
+        let cli = Application::CopilotCli.info();
+        assert_ne!(chat.name, cli.name);
+        assert_ne!(chat.slug, cli.slug);
+        assert_ne!(chat.color, cli.color);
+    }
+
+    #[test]
+    fn looks_up_by_name_or_slug() {
+        assert_eq!(
+            Application::from_name("claude code"),
+            Some(Application::ClaudeCode)
+        );
+        assert_eq!(
+            Application::from_name("kilo_cli"),
+            Some(Application::KiloCli)
+        );
+        assert_eq!(Application::from_name("Custom"), None);
+        assert_eq!(Application::from_name("nope"), None);
+        // Persisted hash prefixes of the OpenCode-format analyzers.
+        assert_eq!(Application::OpenCode.slug(), "opencode");
+        assert_eq!(Application::KiloCli.slug(), "kilo_cli");
+    }
+}
@@ -0,0 +1,324 @@
+//! Copies of the tools' own logs, kept after the tools delete them.
+//!
+//! Claude Code removes transcripts after `cleanupPeriodDays` and Gemini CLI
+//! prunes old chats, and splitrail is usually the only thing that still wants
+//! them. With `[archive] enabled = true` the TUI copies each of their source
+//! files, gzipped, into `archive/<tool>/` in the data directory at startup and
+//! hourly after that, re-copying files that have grown. `splitrail archive`
+//! does the same once. The copies mirror the tool's own layout, so gunzipping
+//! one back into place restores it.
+//!
+//! When the archive would grow past `max_size_mb`, the copies of the least
+//! recently written files are dropped first.
+
+use crate::analyzer::AnalyzerRegistry;
+use crate::types::Application;
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Tools whose logs are archived: those that delete their own history.
+pub const ARCHIVED_TOOLS: &[Application] = &[Application::ClaudeCode, Application::GeminiCli];
+
+/// Cap when `[archive] max_size_mb` isn't set.
+pub const DEFAULT_MAX_SIZE_MB: u64 = 1024;
+
+/// How often the TUI archives again after startup.
+pub const INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// What one archiving pass did.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveSummary {
+    pub dir: PathBuf,
+    /// Files copied, new or grown since the last pass.
+    pub copied: usize,
+    /// Older copies dropped to stay under the cap.
+    pub evicted: usize,
+    /// Files that didn't fit under the cap.
+    pub skipped: usize,
+    /// Size of the archive afterwards.
+    pub size_bytes: u64,
+}
+
+/// A tool's source files and the directories they're relative to.
+struct ToolSources {
+    slug: &'static str,
+    roots: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+/// A file in the archive, or one about to be copied there.
+struct Entry {
+    target: PathBuf,
+    source: Option<PathBuf>,
+    size: u64,
+    modified: SystemTime,
+}
+
+pub fn archive_dir() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join("archive"))
+}
+
+/// Archive every [`ARCHIVED_TOOLS`] source `registry` can find.
+pub fn run(registry: &AnalyzerRegistry, max_size_mb: Option<u64>) -> Result<ArchiveSummary> {
+    let tools: Vec<ToolSources> = ARCHIVED_TOOLS
+        .iter()
+        .filter_map(|app| {
+            let analyzer = registry.get_analyzer_by_display_name(app.display_name())?;
+            let files = analyzer
+                .discover_data_sources()
+                .ok()?
+                .into_iter()
+                .map(|source| source.path)
+                .filter(|path| analyzer.is_valid_data_path(path))
+                .collect();
+            Some(ToolSources {
+                slug: app.slug(),
+                roots: analyzer.get_watch_directories(),
+                files,
+            })
+        })
+        .collect();
+    let max_bytes = max_size_mb.unwrap_or(DEFAULT_MAX_SIZE_MB) * 1024 * 1024;
+    archive_into(&archive_dir()?, &tools, max_bytes)
+}
+
+fn archive_into(dir: &Path, tools: &[ToolSources], max_bytes: u64) -> Result<ArchiveSummary> {
+    let mut entries: Vec<Entry> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "gz"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(Entry {
+                target: entry.into_path(),
+                source: None,
+                size: metadata.len(),
+                modified: metadata.modified().ok()?,
+            })
+        })
+        .collect();
+
+    for tool in tools {
+        for file in &tool.files {
+            let Ok(metadata) = std::fs::metadata(file) else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            let target = dir.join(tool.slug).join(target_name(file, &tool.roots));
+            match entries.iter_mut().find(|entry| entry.target == target) {
+                // Copies carry their source's modification time.
+                Some(entry) if entry.modified >= modified => {}
+                Some(entry) => {
+                    entry.source = Some(file.clone());
+                    entry.size = metadata.len();
+                    entry.modified = modified;
+                }
+                None => entries.push(Entry {
+                    target,
+                    source: Some(file.clone()),
+                    size: metadata.len(),
+                    modified,
+                }),
+            }
+        }
+    }
+
+    // Keep the most recently written files that fit; a pending copy counts
+    // at its uncompressed size until it's written.
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.modified));
+    let mut summary = ArchiveSummary {
+        dir: dir.to_path_buf(),
+        ..ArchiveSummary::default()
+    };
+    let mut full = false;
+    for entry in entries {
+        full |= summary.size_bytes + entry.size > max_bytes;
+        match (&entry.source, full) {
+            (None, false) => summary.size_bytes += entry.size,
+            (Some(source), false) => match copy(source, &entry.target, entry.modified) {
+                Ok(size) => {
+                    summary.size_bytes += size;
+                    summary.copied += 1;
+                }
+                // E.g. a session deleted since discovery; the rest still archive.
+                Err(e) => crate::utils::warn_once(format!("WARNING: couldn't archive: {e:#}")),
+            },
+            (None, true) => {
+                std::fs::remove_file(&entry.target)
+                    .with_context(|| format!("Failed to remove {}", entry.target.display()))?;
+                summary.evicted += 1;
+            }
+            (Some(_), true) => {
+                // A stale copy of a grown file stays out too.
+                if entry.target.exists() {
+                    std::fs::remove_file(&entry.target)
+                        .with_context(|| format!("Failed to remove {}", entry.target.display()))?;
+                }
+                summary.skipped += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Where `file` goes under its tool's archive directory: its path below the
+/// tool's data directory, or its name behind a hash of its path elsewhere.
+fn target_name(file: &Path, roots: &[PathBuf]) -> PathBuf {
+    let relative = roots
+        .iter()
+        .filter_map(|root| file.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| {
+            let hash = crate::utils::hash_text(&file.to_string_lossy());
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            PathBuf::from(format!("{}-{name}", &hash[..16]))
+        });
+    let mut name = relative.into_os_string();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Gzip `source` to `target`, returning the compressed size.
+fn copy(source: &Path, target: &Path, modified: SystemTime) -> Result<u64> {
+    let contents =
+        std::fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&contents)?;
+    let compressed = encoder.finish()?;
+    crate::persist::write_atomic(target, &compressed, "archived log")?;
+    std::fs::File::options()
+        .write(true)
+        .open(target)
+        .and_then(|file| file.set_modified(modified))
+        .with_context(|| format!("Failed to timestamp {}", target.display()))?;
+    Ok(compressed.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::time::Duration;
+
+    fn write(path: &Path, contents: &str, age_days: u64) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_days * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    fn gunzip(path: &Path) -> String {
+        let mut contents = String::new();
+        GzDecoder::new(std::fs::File::open(path).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn copies_new_and_grown_files_and_keeps_deleted_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let projects = dir.path().join("projects");
+        let archive = dir.path().join("archive");
+        let session = projects.join("my-project").join("session.jsonl");
+        write(&session, "{\"a\":1}\n", 2);
+        let tools = |files: Vec<PathBuf>| {
+            vec![ToolSources {
+                slug: "claude_code",
+                roots: vec![projects.clone()],
+                files,
+            }]
+        };
+
+        let summary = archive_into(&archive, &tools(vec![session.clone()]), u64::MAX).unwrap();
+        assert_eq!(summary.copied, 1);
+        let copy = archive.join("claude_code/my-project/session.jsonl.gz");
+        assert_eq!(gunzip(&copy), "{\"a\":1}\n");
+
+        let summary = archive_into(&archive, &tools(vec![session.clone()]), u64::MAX).unwrap();
+        assert_eq!(summary.copied, 0);
+
+        write(&session, "{\"a\":1}\n{\"b\":2}\n", 1);
+        let summary = archive_into(&archive, &tools(vec![session.clone()]), u64::MAX).unwrap();
+        assert_eq!(summary.copied, 1);
+        assert_eq!(gunzip(&copy), "{\"a\":1}\n{\"b\":2}\n");
+
+        // The tool deleting its log leaves the copy alone.
+        std::fs::remove_file(&session).unwrap();
+        let summary = archive_into(&archive, &tools(Vec::new()), u64::MAX).unwrap();
+        assert_eq!((summary.copied, summary.evicted), (0, 0));
+        assert!(copy.exists());
+    }
+
+    #[test]
+    fn unreadable_sources_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tmp");
+        let archive = dir.path().join("archive");
+        let good = root.join("good.json");
+        // Stats fine but can't be read, like a file removed mid-pass.
+        let unreadable = root.join("gone.json");
+        write(&good, "{}", 1);
+        std::fs::create_dir_all(&unreadable).unwrap();
+        let tools = vec![ToolSources {
+            slug: "gemini_cli",
+            roots: vec![root.clone()],
+            files: vec![unreadable, good],
+        }];
+
+        let summary = archive_into(&archive, &tools, u64::MAX).unwrap();
+        assert_eq!(summary.copied, 1);
+        assert!(archive.join("gemini_cli/good.json.gz").exists());
+    }
+
+    #[test]
+    fn cap_keeps_the_most_recently_written_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tmp");
+        let archive = dir.path().join("archive");
+        let old = root.join("old.json");
+        let new = root.join("new.json");
+        write(&old, &"x".repeat(400), 10);
+        write(&new, &"y".repeat(400), 1);
+        let tools = vec![ToolSources {
+            slug: "gemini_cli",
+            roots: vec![root.clone()],
+            files: vec![old, new],
+        }];
+
+        // The newer copy compresses well, but the older file's 400 bytes
+        // still don't fit next to it.
+        let summary = archive_into(&archive, &tools, 420).unwrap();
+        assert_eq!((summary.copied, summary.skipped), (1, 1));
+        assert!(archive.join("gemini_cli/new.json.gz").exists());
+        assert!(!archive.join("gemini_cli/old.json.gz").exists());
+    }
+
+    #[test]
+    fn files_outside_the_data_directory_get_distinct_names() {
+        let roots = [PathBuf::from("/data")];
+        let a = target_name(Path::new("/elsewhere/a/chat.json"), &roots);
+        let b = target_name(Path::new("/elsewhere/b/chat.json"), &roots);
+        assert_ne!(a, b);
+        assert_eq!(
+            target_name(Path::new("/data/x/chat.json"), &roots),
+            PathBuf::from("x/chat.json.gz")
+        );
+    }
+}
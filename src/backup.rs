@@ -0,0 +1,239 @@
+//! `splitrail export --raw` and `splitrail import --raw`: a backup of every
+//! parsed message, for when the tools themselves delete old logs (Claude Code
+//! does after `cleanupPeriodDays`).
+//!
+//! The export is gzipped NDJSON, one [`ConversationMessage`] per line with an
+//! extra `analyzer` field naming the tool it came from. Importing merges each
+//! tool's messages into `restored/<tool>.ndjson.gz` in the data directory,
+//! which that tool's analyzer reads after its own logs; messages whose logs
+//! still exist are only counted once, by `global_hash`.
+
+use crate::analyzer::DataSource;
+use crate::types::{Application, ConversationMessage, MultiAnalyzerStats};
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// One line of a raw export.
+#[derive(Serialize, Deserialize)]
+struct RawRecord {
+    analyzer: String,
+    #[serde(flatten)]
+    message: ConversationMessage,
+}
+
+/// What a raw export or import covered.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub path: PathBuf,
+    /// Messages per tool in the file.
+    pub messages: BTreeMap<String, usize>,
+    /// Messages per tool that weren't restored before; empty for exports.
+    pub added: BTreeMap<String, usize>,
+}
+
+/// Where restored messages are kept, under the data directory.
+pub fn restored_dir() -> Result<PathBuf> {
+    Ok(crate::paths::data_dir()?.join("restored"))
+}
+
+/// File name for a tool's restored messages: its slug for built-in tools,
+/// otherwise the display name reduced to one safe path segment.
+fn file_name(analyzer_name: &str) -> String {
+    let stem = match Application::from_name(analyzer_name) {
+        Some(app) => app.slug().to_string(),
+        None => analyzer_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect(),
+    };
+    format!("{stem}.ndjson.gz")
+}
+
+/// The analyzer's restored messages as an extra source, if any were imported.
+pub fn restored_source(analyzer_name: &str) -> Option<DataSource> {
+    let path = restored_dir().ok()?.join(file_name(analyzer_name));
+    path.is_file().then_some(DataSource { path })
+}
+
+/// Write every loaded message to `path`.
+pub fn export(stats: &MultiAnalyzerStats, path: &Path) -> Result<BackupSummary> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut summary = BackupSummary {
+        path: path.to_path_buf(),
+        ..BackupSummary::default()
+    };
+    for tool in &stats.analyzer_stats {
+        for message in &tool.messages {
+            let record = RawRecord {
+                analyzer: tool.analyzer_name.clone(),
+                message: message.clone(),
+            };
+            simd_json::to_writer(&mut encoder, &record)?;
+            encoder.write_all(b"\n")?;
+        }
+        if !tool.messages.is_empty() {
+            summary
+                .messages
+                .insert(tool.analyzer_name.clone(), tool.messages.len());
+        }
+    }
+    crate::persist::write_atomic(path, encoder.finish()?, "raw export")?;
+    Ok(summary)
+}
+
+/// Merge the messages in a raw export into each tool's restored messages.
+pub fn import(path: &Path) -> Result<BackupSummary> {
+    import_into(path, &restored_dir()?)
+}
+
+fn import_into(path: &Path, dir: &Path) -> Result<BackupSummary> {
+    let mut by_tool: BTreeMap<String, Vec<ConversationMessage>> = BTreeMap::new();
+    for record in read_lines::<RawRecord>(path)? {
+        by_tool
+            .entry(record.analyzer)
+            .or_default()
+            .push(record.message);
+    }
+
+    let mut summary = BackupSummary {
+        path: path.to_path_buf(),
+        ..BackupSummary::default()
+    };
+    for (tool, incoming) in by_tool {
+        let target = dir.join(file_name(&tool));
+        let mut messages = if target.is_file() {
+            read_messages(&target)?
+        } else {
+            Vec::new()
+        };
+        let mut seen: HashSet<String> = messages
+            .iter()
+            .map(|message| message.global_hash.clone())
+            .collect();
+        let before = messages.len();
+        summary.messages.insert(tool.clone(), incoming.len());
+        messages.extend(
+            incoming
+                .into_iter()
+                .filter(|message| seen.insert(message.global_hash.clone())),
+        );
+        summary.added.insert(tool, messages.len() - before);
+
+        messages.sort_by_key(|message| message.date);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        for message in &messages {
+            simd_json::to_writer(&mut encoder, message)?;
+            encoder.write_all(b"\n")?;
+        }
+        crate::persist::write_atomic(&target, encoder.finish()?, "restored messages")?;
+    }
+    Ok(summary)
+}
+
+/// Read a tool's restored messages.
+pub fn read_messages(path: &Path) -> Result<Vec<ConversationMessage>> {
+    read_lines(path)
+}
+
+fn read_lines<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut items = Vec::new();
+    for (i, line) in BufReader::new(GzDecoder::new(file)).lines().enumerate() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut bytes = line.into_bytes();
+        items.push(
+            simd_json::serde::from_slice(&mut bytes)
+                .with_context(|| format!("Invalid entry in {} line {}", path.display(), i + 1))?,
+        );
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgenticCodingToolStats, MessageRole, Stats};
+    use chrono::{TimeZone, Utc};
+
+    fn message(hash: &str, day: u32) -> ConversationMessage {
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap(),
+            project_hash: "project".to_string(),
+            conversation_hash: "conversation".to_string(),
+            local_hash: None,
+            global_hash: hash.to_string(),
+            model: Some("claude-sonnet-4-5".to_string()),
+            stats: Stats {
+                input_tokens: 100,
+                output_tokens: 20,
+                cost: 0.5,
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: Some("abc".to_string()),
+        }
+    }
+
+    fn export_of(messages: Vec<ConversationMessage>, path: &Path) -> BackupSummary {
+        let stats = MultiAnalyzerStats {
+            analyzer_stats: vec![AgenticCodingToolStats {
+                daily_stats: BTreeMap::new(),
+                num_conversations: 1,
+                messages,
+                analyzer_name: "Claude Code".to_string(),
+            }],
+        };
+        export(&stats, path).unwrap()
+    }
+
+    #[test]
+    fn restore_merges_exports_without_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let restored = dir.path().join("restored");
+        let first = dir.path().join("first.ndjson.gz");
+        let second = dir.path().join("second.ndjson.gz");
+
+        let summary = export_of(vec![message("a", 1), message("b", 2)], &first);
+        assert_eq!(summary.messages["Claude Code"], 2);
+        export_of(vec![message("b", 2), message("c", 3)], &second);
+
+        let summary = import_into(&first, &restored).unwrap();
+        assert_eq!(summary.added["Claude Code"], 2);
+        let summary = import_into(&second, &restored).unwrap();
+        assert_eq!(summary.messages["Claude Code"], 2);
+        assert_eq!(summary.added["Claude Code"], 1);
+
+        let messages = read_messages(&restored.join("claude_code.ndjson.gz")).unwrap();
+        let hashes: Vec<_> = messages.iter().map(|m| m.global_hash.as_str()).collect();
+        assert_eq!(hashes, ["a", "b", "c"]);
+        assert_eq!(messages[0].stats.input_tokens, 100);
+        assert_eq!(messages[0].stats.cost, 0.5);
+        assert_eq!(messages[0].stable_session_id.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn custom_tool_names_become_safe_file_names() {
+        assert_eq!(file_name("Claude Code"), "claude_code.ndjson.gz");
+        assert_eq!(file_name("My Tool/2"), "my-tool-2.ndjson.gz");
+    }
+}
@@ -0,0 +1,286 @@
+//! `splitrail bench`: times each stage of a full load per analyzer on the
+//! local data, so "splitrail is slow" reports come with numbers attached.
+//!
+//! Analyzers are timed one after another so they don't compete for the
+//! thread pool. Persistence writes to a throwaway SQLite store in the temp
+//! directory, leaving the real one untouched.
+
+use crate::analyzer::AnalyzerRegistry;
+use crate::store::Store;
+use crate::types::{AgenticCodingToolStats, MultiAnalyzerStats};
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzerTiming {
+    pub analyzer: String,
+    pub sources: usize,
+    pub bytes: u64,
+    /// Messages as parsed, before deduplication.
+    pub messages: usize,
+    pub unique_messages: usize,
+    pub discovery_ms: f64,
+    pub parse_ms: f64,
+    pub dedup_ms: f64,
+    pub aggregation_ms: f64,
+    pub persistence_ms: f64,
+}
+
+impl AnalyzerTiming {
+    pub fn total_ms(&self) -> f64 {
+        self.discovery_ms
+            + self.parse_ms
+            + self.dedup_ms
+            + self.aggregation_ms
+            + self.persistence_ms
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.sources += other.sources;
+        self.bytes += other.bytes;
+        self.messages += other.messages;
+        self.unique_messages += other.unique_messages;
+        self.discovery_ms += other.discovery_ms;
+        self.parse_ms += other.parse_ms;
+        self.dedup_ms += other.dedup_ms;
+        self.aggregation_ms += other.aggregation_ms;
+        self.persistence_ms += other.persistence_ms;
+    }
+}
+
+fn millis(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+/// Time every analyzer that has data. Must be called within a rayon
+/// threadpool context, like a normal load.
+pub fn run(registry: &AnalyzerRegistry) -> Result<Vec<AnalyzerTiming>> {
+    let store_path =
+        std::env::temp_dir().join(format!("splitrail-bench-{}.db", std::process::id()));
+    let result = Store::open(&store_path).map(|mut store| {
+        registry
+            .available_analyzers()
+            .into_iter()
+            .filter_map(|analyzer| time_analyzer(analyzer, &mut store))
+            .collect()
+    });
+    remove_store(&store_path);
+    result
+}
+
+fn time_analyzer(
+    analyzer: &dyn crate::analyzer::Analyzer,
+    store: &mut Store,
+) -> Option<AnalyzerTiming> {
+    let name = analyzer.display_name();
+    let mut timing = AnalyzerTiming {
+        analyzer: name.to_string(),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let sources = analyzer.discover_data_sources().ok()?;
+    timing.discovery_ms = millis(start.elapsed());
+    if sources.is_empty() {
+        return None;
+    }
+    timing.sources = sources.len();
+    timing.bytes = sources
+        .iter()
+        .filter_map(|source| std::fs::metadata(&source.path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+
+    let start = Instant::now();
+    let messages: Vec<_> = analyzer
+        .parse_sources_parallel_with_paths(&sources)
+        .into_iter()
+        .flat_map(|(_, messages)| messages)
+        .collect();
+    timing.parse_ms = millis(start.elapsed());
+    timing.messages = messages.len();
+
+    let start = Instant::now();
+    let messages = crate::utils::deduplicate_by_global_hash(messages);
+    timing.dedup_ms = millis(start.elapsed());
+    timing.unique_messages = messages.len();
+
+    let start = Instant::now();
+    let mut daily_stats = crate::utils::aggregate_by_date(&messages);
+    let sessions = crate::tui::logic::aggregate_sessions_from_messages(&messages, Arc::from(name));
+    timing.aggregation_ms = millis(start.elapsed());
+    drop(sessions);
+
+    daily_stats.retain(|date, _| date != "unknown");
+    let stats = MultiAnalyzerStats {
+        analyzer_stats: vec![AgenticCodingToolStats {
+            num_conversations: daily_stats
+                .values()
+                .map(|day| day.conversations as u64)
+                .sum(),
+            daily_stats,
+            messages,
+            analyzer_name: name.to_string(),
+        }],
+    };
+    let start = Instant::now();
+    if let Err(e) = store.replace(&stats) {
+        crate::utils::warn_once(format!(
+            "WARNING: couldn't time persistence for {name}: {e:#}"
+        ));
+    }
+    timing.persistence_ms = millis(start.elapsed());
+
+    Some(timing)
+}
+
+fn remove_store(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.as_os_str().to_owned();
+        file.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(file));
+    }
+}
+
+fn format_ms(ms: f64) -> String {
+    if ms >= 1000.0 {
+        format!("{:.2}s", ms / 1000.0)
+    } else {
+        format!("{ms:.1}ms")
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// The breakdown table, slowest analyzer first, with a total row.
+pub fn render(timings: &[AnalyzerTiming]) -> Vec<String> {
+    const HEADER: [&str; 10] = [
+        "Analyzer",
+        "Files",
+        "Size",
+        "Messages",
+        "Discover",
+        "Parse",
+        "Dedup",
+        "Aggregate",
+        "Persist",
+        "Total",
+    ];
+
+    let mut sorted: Vec<&AnalyzerTiming> = timings.iter().collect();
+    sorted.sort_by(|a, b| b.total_ms().total_cmp(&a.total_ms()));
+    let mut total = AnalyzerTiming {
+        analyzer: "Total".to_string(),
+        ..Default::default()
+    };
+    for timing in &sorted {
+        total.add(timing);
+    }
+
+    let row = |timing: &AnalyzerTiming| {
+        [
+            timing.analyzer.clone(),
+            timing.sources.to_string(),
+            format_bytes(timing.bytes),
+            timing.unique_messages.to_string(),
+            format_ms(timing.discovery_ms),
+            format_ms(timing.parse_ms),
+            format_ms(timing.dedup_ms),
+            format_ms(timing.aggregation_ms),
+            format_ms(timing.persistence_ms),
+            format_ms(timing.total_ms()),
+        ]
+    };
+    let mut rows: Vec<[String; 10]> = vec![HEADER.map(str::to_string)];
+    rows.extend(sorted.into_iter().map(row));
+    rows.push(row(&total));
+
+    let mut widths = [0; 10];
+    for cells in &rows {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|cells| {
+            cells
+                .iter()
+                .zip(widths)
+                .enumerate()
+                .map(|(i, (cell, width))| {
+                    if i == 0 {
+                        format!("{cell:<width$}")
+                    } else {
+                        format!("{cell:>width$}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(analyzer: &str, parse_ms: f64) -> AnalyzerTiming {
+        AnalyzerTiming {
+            analyzer: analyzer.to_string(),
+            sources: 3,
+            bytes: 2048,
+            messages: 12,
+            unique_messages: 10,
+            discovery_ms: 1.0,
+            parse_ms,
+            dedup_ms: 0.5,
+            aggregation_ms: 0.25,
+            persistence_ms: 2.0,
+        }
+    }
+
+    #[test]
+    fn render_sorts_slowest_first_and_totals_every_stage() {
+        let lines = render(&[timing("Codex CLI", 10.0), timing("Claude Code", 1000.0)]);
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Analyzer"));
+        assert!(lines[1].starts_with("Claude Code"));
+        assert!(lines[1].ends_with("1.00s"));
+        assert!(lines[2].starts_with("Codex CLI"));
+        assert!(lines[2].contains("2.0 KB"));
+        assert!(lines[3].starts_with("Total"));
+        assert!(lines[3].contains("  6  "));
+        assert!(lines[3].contains("4.0 KB"));
+        assert!(lines[3].ends_with("1.02s"));
+        // Columns line up.
+        let width = lines[0].len();
+        assert!(lines.iter().all(|line| line.len() == width));
+    }
+
+    #[test]
+    fn format_bytes_picks_the_largest_whole_unit() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+}
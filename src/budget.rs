@@ -0,0 +1,243 @@
+//! Cost budgets from the `[budget]` config section.
+//!
+//! Budgets cover the current calendar month and the current ISO week
+//! (Monday to Sunday), either across all tools or for a single tool.
+
+use crate::config::BudgetConfig;
+use crate::types::{CompactDate, DateRange, SharedAnalyzerView};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+
+/// Scope name used for limits that apply across every tool.
+pub const ALL_TOOLS: &str = "All Tools";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Weekly,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Weekly => "week",
+            Self::Monthly => "month",
+        }
+    }
+
+    /// Days from the start of the period containing `today` up to `today`.
+    fn range(self, today: NaiveDate) -> DateRange {
+        let start = match self {
+            Self::Weekly => today
+                .checked_sub_days(Days::new(today.weekday().num_days_from_monday() as u64))
+                .unwrap_or(today),
+            Self::Monthly => today.with_day(1).unwrap_or(today),
+        };
+        DateRange {
+            since: Some(to_compact(start)),
+            until: Some(to_compact(today)),
+        }
+    }
+}
+
+/// Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetLevel {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    /// Tool name, or [`ALL_TOOLS`] for a global limit.
+    pub scope: String,
+    pub period: BudgetPeriod,
+    pub limit: f64,
+    pub spent: f64,
+    pub level: BudgetLevel,
+}
+
+impl BudgetStatus {
+    /// Share of the limit spent so far; may exceed 1.0.
+    pub fn fraction(&self) -> f64 {
+        if self.limit > 0.0 {
+            self.spent / self.limit
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn to_compact(date: NaiveDate) -> CompactDate {
+    CompactDate::from_parts(date.year() as u16, date.month() as u8, date.day() as u8)
+}
+
+/// Dollars spent by one tool, or by every tool when `scope` is `None`, on
+/// days inside `range`.
+pub fn spent_in(views: &[SharedAnalyzerView], scope: Option<&str>, range: DateRange) -> f64 {
+    let cents: u64 = views
+        .iter()
+        .filter_map(|view| {
+            let view = view.read();
+            if scope.is_some_and(|name| !view.analyzer_name.eq_ignore_ascii_case(name)) {
+                return None;
+            }
+            Some(
+                view.daily_stats
+                    .values()
+                    .filter(|day| range.contains(day.date))
+                    .map(|day| day.stats.cost_cents as u64)
+                    .sum::<u64>(),
+            )
+        })
+        .sum();
+    cents as f64 / 100.0
+}
+
+/// Check every configured limit against the per-tool views, global limits first
+/// and then per-tool limits in name order.
+pub fn evaluate(
+    config: &BudgetConfig,
+    views: &[SharedAnalyzerView],
+    today: NaiveDate,
+) -> Vec<BudgetStatus> {
+    let mut scopes: Vec<(Option<&str>, Option<f64>, Option<f64>)> =
+        vec![(None, config.monthly_usd, config.weekly_usd)];
+    let mut per_tool: Vec<_> = config.analyzers.iter().collect();
+    per_tool.sort_by(|a, b| a.0.cmp(b.0));
+    scopes.extend(
+        per_tool
+            .into_iter()
+            .map(|(name, limits)| (Some(name.as_str()), limits.monthly_usd, limits.weekly_usd)),
+    );
+
+    let mut statuses = Vec::new();
+    for (scope, monthly, weekly) in scopes {
+        for (period, limit) in [
+            (BudgetPeriod::Monthly, monthly),
+            (BudgetPeriod::Weekly, weekly),
+        ] {
+            let Some(limit) = limit.filter(|limit| *limit > 0.0) else {
+                continue;
+            };
+            let spent = spent_in(views, scope, period.range(today));
+            let level = if spent >= limit {
+                BudgetLevel::Exceeded
+            } else if spent >= limit * config.warn_at {
+                BudgetLevel::Warning
+            } else {
+                BudgetLevel::Ok
+            };
+            statuses.push(BudgetStatus {
+                scope: scope.unwrap_or(ALL_TOOLS).to_string(),
+                period,
+                limit,
+                spent,
+                level,
+            });
+        }
+    }
+    statuses
+}
+
+/// Text progress bar such as `[██████░░░░]` (`[######----]` with plain output),
+/// capped at full when over budget.
+pub fn progress_bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    let (full, empty) = if crate::utils::plain_output() {
+        ("#", "-")
+    } else {
+        ("█", "░")
+    };
+    format!("[{}{}]", full.repeat(filled), empty.repeat(width - filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BudgetLimits;
+    use crate::types::{AnalyzerStatsView, DailyStats, TuiStats};
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::Arc;
+
+    fn view(name: &str, days: &[(&str, u32)]) -> SharedAnalyzerView {
+        let daily_stats = days
+            .iter()
+            .map(|(date, cost_cents)| {
+                (
+                    date.to_string(),
+                    DailyStats {
+                        date: CompactDate::from_str(date).unwrap(),
+                        stats: TuiStats {
+                            cost_cents: *cost_cents,
+                            ..TuiStats::default()
+                        },
+                        ..DailyStats::default()
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        Arc::new(parking_lot::RwLock::new(AnalyzerStatsView {
+            daily_stats,
+            session_aggregates: Vec::new(),
+            num_conversations: 0,
+            analyzer_name: Arc::from(name),
+        }))
+    }
+
+    #[test]
+    fn evaluates_global_and_per_tool_limits() {
+        // Wednesday 2025-06-11: the week started Monday 2025-06-09.
+        let today = NaiveDate::from_ymd_opt(2025, 6, 11).unwrap();
+        let views = vec![
+            view(
+                "Claude Code",
+                &[
+                    ("2025-05-31", 5000),
+                    ("2025-06-02", 6000),
+                    ("2025-06-10", 3000),
+                ],
+            ),
+            view("Codex CLI", &[("2025-06-11", 1000)]),
+        ];
+        let config = BudgetConfig {
+            monthly_usd: Some(120.0),
+            weekly_usd: Some(50.0),
+            analyzers: HashMap::from([(
+                "Claude Code".to_string(),
+                BudgetLimits {
+                    monthly_usd: Some(80.0),
+                    weekly_usd: None,
+                },
+            )]),
+            ..BudgetConfig::default()
+        };
+
+        let statuses = evaluate(&config, &views, today);
+
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses[0].scope, ALL_TOOLS);
+        assert_eq!(statuses[0].period, BudgetPeriod::Monthly);
+        assert_eq!(statuses[0].spent, 100.0);
+        assert_eq!(statuses[0].level, BudgetLevel::Warning);
+
+        assert_eq!(statuses[1].period, BudgetPeriod::Weekly);
+        assert_eq!(statuses[1].spent, 40.0);
+        assert_eq!(statuses[1].level, BudgetLevel::Warning);
+
+        assert_eq!(statuses[2].scope, "Claude Code");
+        assert_eq!(statuses[2].spent, 90.0);
+        assert_eq!(statuses[2].level, BudgetLevel::Exceeded);
+    }
+
+    #[test]
+    fn progress_bar_caps_at_full() {
+        assert_eq!(progress_bar(0.0, 4), "[░░░░]");
+        assert_eq!(progress_bar(0.5, 4), "[██░░]");
+        assert_eq!(progress_bar(3.0, 4), "[████]");
+    }
+}
@@ -1,11 +1,15 @@
-use crate::models::ModelInfo;
-use crate::utils::LogLevel;
+use crate::models::{CostSourcePreference, ModelInfo, PriceOverride};
+use crate::premium::CopilotPlan;
+use crate::subscription::CostMode;
+use crate::utils::{LogLevel, NumberFormatOptions};
 use anyhow::{Context, Result};
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -20,12 +24,436 @@ pub struct Config {
     pub models: HashMap<String, ModelInfo>,
     #[serde(default)]
     pub aliases: HashMap<String, String>,
+    /// Per-model rate overrides keyed by model name or alias; also prices
+    /// models splitrail doesn't know yet. Applied live when the file changes.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pricing: HashMap<String, PriceOverride>,
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub pricing_updates: PricingUpdatesConfig,
+    #[serde(default)]
+    pub watcher: WatcherConfig,
+    #[serde(default)]
+    pub parsing: ParsingConfig,
+    #[serde(default)]
+    pub forecast: ForecastConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub costs: CostsConfig,
+    #[serde(default)]
+    pub copilot: CopilotConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub activity: ActivityConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub exclude: ExcludeConfig,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// Flat-fee plans keyed by tool name, used by the "actual" cost mode.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub subscriptions: HashMap<String, SubscriptionConfig>,
+    /// User-defined JSONL analyzers, declared as `[[custom_analyzer]]` tables.
+    #[serde(
+        default,
+        rename = "custom_analyzer",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub custom_analyzers: Vec<CustomAnalyzerConfig>,
+    /// Analyzers switched on or off by key, e.g. `copilot = false`. Analyzers
+    /// not listed here stay on.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub analyzers: HashMap<String, bool>,
+    /// Data directories per analyzer key, replacing the built-in locations,
+    /// e.g. `claude_code = ["D:/claude/projects"]`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub paths: HashMap<String, Vec<String>>,
+    /// TUI key bindings by action, e.g. `sessions = "v"` or `quit = "q, ctrl+c"`.
+    /// A listed action loses its default keys.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub keys: HashMap<String, String>,
+}
+
+/// A JSONL log format described entirely in config.
+///
+/// Each field mapping is a dotted path into a JSON line, e.g. `usage.input_tokens`
+/// or `choices.0.model`. Lines missing the timestamp are skipped.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomAnalyzerConfig {
+    /// Tab name shown in the TUI, e.g. "Acme Agent".
+    pub name: String,
+    /// Glob matching the JSONL files to read. A leading `~` expands to the home directory.
+    pub glob: String,
+    /// Path to an RFC 3339 timestamp or a Unix timestamp in seconds/milliseconds.
+    pub timestamp: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub input_tokens: Option<String>,
+    #[serde(default)]
+    pub output_tokens: Option<String>,
+    /// Path to a logged cost in dollars. When absent, cost is computed from the model's pricing.
+    #[serde(default)]
+    pub cost: Option<String>,
+    /// Path to a session identifier. Defaults to one session per file.
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// Cost limits, checked against the current calendar month and ISO week.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BudgetConfig {
+    /// Limit across all tools for the current month, in the cost currency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_usd: Option<f64>,
+    /// Limit across all tools for the current week (Monday to Sunday).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_usd: Option<f64>,
+    /// Fraction of a limit at which the budget turns into a warning. Default 0.8.
+    #[serde(default = "default_budget_warn_at")]
+    pub warn_at: f64,
+    /// Per-tool limits keyed by tool name, e.g. `[budget.analyzers."Claude Code"]`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub analyzers: HashMap<String, BudgetLimits>,
+}
+
+/// Desktop notifications raised by the TUI while it watches for new usage.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct NotificationsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Notify when today's spend across all tools crosses each of these amounts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub daily_thresholds_usd: Vec<f64>,
+}
+
+/// What happens to stats when a tool deletes its own transcripts.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct HistoryConfig {
+    /// Keep counting Claude Code sessions after its cleanup removes their
+    /// transcripts, instead of pruning them from the history store.
+    #[serde(default)]
+    pub freeze_deleted_sessions: bool,
+}
+
+/// Where splitrail keeps its own data on disk, and how much.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CacheConfig {
+    /// Directory for the upload state, status snapshot, and history store.
+    /// Point two machines at a synced folder, or use `splitrail sync`, to
+    /// carry them between machines. `SPLITRAIL_DATA_DIR` takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+    /// Cap for the Claude Code history store. When it grows past this, the
+    /// least recently written sessions whose transcripts are gone are evicted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+    /// Mirror every full parse into `splitrail.db` in the state directory for SQL queries.
+    #[serde(default)]
+    pub sqlite_store: bool,
+}
+
+/// Copies of the tools' logs kept after they delete them; see [`crate::archive`].
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ArchiveConfig {
+    /// Archive Claude Code and Gemini CLI logs while the TUI runs.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Cap for the archive. Default 1024.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+/// How the TUI notices new usage.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WatcherConfig {
+    /// Re-check data sources every this many seconds instead of relying on
+    /// file system events, which network mounts, WSL and some containers
+    /// never deliver.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// How source files are read when loading everything at startup.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ParsingConfig {
+    /// `auto` reads one file at a time from network mounts and spinning
+    /// disks and in parallel elsewhere; `parallel` or `sequential` force one.
+    #[serde(default)]
+    pub io: crate::parse_io::IoStrategy,
+    /// Most files read at once when parsing in parallel. Default: one per core.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+}
+
+impl ParsingConfig {
+    pub fn parse_io(&self) -> crate::parse_io::ParseIo {
+        crate::parse_io::ParseIo {
+            strategy: self.io,
+            threads: self.threads,
+        }
+    }
+}
+
+/// End-of-month spend projections.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ForecastConfig {
+    /// Trailing days averaged for the projection. Default 7.
+    #[serde(default = "default_forecast_window_days")]
+    pub window_days: u32,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            window_days: default_forecast_window_days(),
+        }
+    }
+}
+
+/// Relating agent sessions to the commits made in their repositories.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GitConfig {
+    /// Allow `splitrail report git` to run `git` in the directories agent
+    /// sessions worked in. Off by default.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// How message costs are determined.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CostsConfig {
+    /// Whether costs tools log themselves (Cline, OpenCode, Pi Agent, ...)
+    /// win over pricing tokens at model rates.
+    #[serde(default)]
+    pub source: CostSourcePreference,
+}
+
+/// GitHub Copilot billing.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CopilotConfig {
+    /// Plan whose monthly premium request allowance overage is estimated
+    /// against: "free" | "pro" | "pro-plus" | "business" | "enterprise".
+    #[serde(default)]
+    pub plan: CopilotPlan,
+}
+
+/// Token limits of Claude Code's usage windows, which Claude Code doesn't log.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct LimitsConfig {
+    /// Input and output tokens allowed per five-hour window. Unset, the
+    /// current window is compared with the busiest recent one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub five_hour_tokens: Option<u64>,
+    /// Input and output tokens allowed per rolling week.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_tokens: Option<u64>,
+}
+
+/// Active time estimation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ActivityConfig {
+    /// Minutes between messages after which a work block ends. Default 15.
+    #[serde(default = "default_idle_gap_minutes")]
+    pub idle_gap_minutes: u32,
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            idle_gap_minutes: default_idle_gap_minutes(),
+        }
+    }
+}
+
+/// Scrubbing applied to messages before upload. Preview the result with
+/// `splitrail upload --show-payload`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PrivacyConfig {
+    /// Re-hash project hashes with a random per-user salt, so they can't be
+    /// matched against hashes of known project paths.
+    #[serde(default)]
+    pub salt_project_hashes: bool,
+    /// Leave session names (usually the first prompt or a summary) out.
+    #[serde(default)]
+    pub strip_session_names: bool,
+    /// Send model families ("claude-sonnet") instead of exact versions.
+    #[serde(default)]
+    pub generalize_models: bool,
+}
+
+/// Sources never read, e.g. confidential clients' repositories.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ExcludeConfig {
+    /// Globs (`**/client-*/**`) or plain substrings, matched against source
+    /// file paths and the project directory a transcript records.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+/// TUI colors: a built-in preset, with any role overridden by a color name
+/// or `#rrggbb`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThemeConfig {
+    /// "dark" | "light" | "solarized" | "no-color".
+    #[serde(default = "default_theme_preset")]
+    pub preset: String,
+    /// Title, tab bar and selected row. Defaults to `tui.accent_color` with
+    /// the dark preset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// The highest value in each table column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best: Option<String>,
+    /// Cost cells and totals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<String>,
+    /// Tool-call counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<String>,
+    /// Secondary text such as cached tokens and model lists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dim: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Values in the summary panel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl ThemeConfig {
+    /// Every role override, keyed by role name.
+    pub fn roles(&self) -> [(&'static str, Option<&str>); 9] {
+        [
+            ("accent", self.accent.as_deref()),
+            ("best", self.best.as_deref()),
+            ("cost", self.cost.as_deref()),
+            ("count", self.count.as_deref()),
+            ("dim", self.dim.as_deref()),
+            ("success", self.success.as_deref()),
+            ("warning", self.warning.as_deref()),
+            ("error", self.error.as_deref()),
+            ("summary", self.summary.as_deref()),
+        ]
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preset: default_theme_preset(),
+            accent: None,
+            best: None,
+            cost: None,
+            count: None,
+            dim: None,
+            success: None,
+            warning: None,
+            error: None,
+            summary: None,
+        }
+    }
+}
+
+/// A flat-fee plan such as Claude Max or ChatGPT Plus.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SubscriptionConfig {
+    /// Monthly fee in USD; 0 for usage that is included at no extra cost.
+    pub monthly_usd: f64,
+}
+
+/// Refreshing model rates from a remote pricing manifest at startup.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PricingUpdatesConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// LiteLLM-format JSON: model name to per-token (or `*_per_1m`) rates.
+    #[serde(default = "default_pricing_manifest_url")]
+    pub url: String,
+    /// How old the cached manifest may get before it is fetched again.
+    #[serde(default = "default_pricing_refresh_hours")]
+    pub refresh_hours: u32,
+}
+
+impl Default for PricingUpdatesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: default_pricing_manifest_url(),
+            refresh_hours: default_pricing_refresh_hours(),
+        }
+    }
+}
+
+fn default_pricing_manifest_url() -> String {
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json"
+        .to_string()
+}
+
+fn default_pricing_refresh_hours() -> u32 {
+    24
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BudgetLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_usd: Option<f64>,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            monthly_usd: None,
+            weekly_usd: None,
+            warn_at: default_budget_warn_at(),
+            analyzers: HashMap::new(),
+        }
+    }
+}
+
+impl BudgetConfig {
+    pub fn is_empty(&self) -> bool {
+        self.monthly_usd.is_none()
+            && self.weekly_usd.is_none()
+            && self
+                .analyzers
+                .values()
+                .all(|limits| limits.monthly_usd.is_none() && limits.weekly_usd.is_none())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ServerConfig {
     pub url: String,
     pub api_token: String,
+    /// Path on `url` that uploads are POSTed to.
+    #[serde(default = "default_upload_path")]
+    pub upload_path: String,
+    /// Upload payload format: 0 sends a bare array of messages, as
+    /// splitrail.dev expects; 1 wraps them in `{"schema_version": 1, "messages": [...]}`.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The API token lives in the OS keychain rather than in `api_token`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keychain: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,6 +461,19 @@ pub struct UploadConfig {
     pub auto_upload: bool,
     pub upload_today_only: bool,
     pub retry_attempts: u32,
+    /// Messages per upload request. Progress is checkpointed after each one.
+    #[serde(default = "default_upload_chunk_size")]
+    pub chunk_size: usize,
+    /// Gzip request bodies. The server must accept `Content-Encoding: gzip`.
+    #[serde(default)]
+    pub compress: bool,
+    /// Proxy for all requests, e.g. `http://proxy.corp:3128`. Without it,
+    /// the `HTTP_PROXY`/`HTTPS_PROXY` environment variables apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// PEM file of extra root certificates, e.g. a corporate CA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle_path: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -62,6 +503,82 @@ pub struct UploadState {
     /// Whether Claude transcripts were uploaded after subagent discovery was introduced.
     #[serde(default)]
     pub claude_subagent_backfill_completed: bool,
+    /// Salt for `[privacy] salt_project_hashes`, created on first use.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub project_salt: String,
+    /// Set when a corrupt state file lost `last_date_uploaded`; uploads then
+    /// need `--full` rather than silently re-sending everything from zero.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub watermark_lost: bool,
+}
+
+/// Keys accepted by `splitrail config set`, used for validation hints and shell completion.
+pub const CONFIG_KEYS: &[&str] = &[
+    "api-token",
+    "auto-upload",
+    "upload-today-only",
+    "server-upload-path",
+    "server-schema-version",
+    "upload-chunk-size",
+    "upload-compress",
+    "upload-proxy-url",
+    "upload-ca-bundle-path",
+    "privacy-salt-project-hashes",
+    "exclude-paths",
+    "privacy-strip-session-names",
+    "privacy-generalize-models",
+    "number-comma",
+    "number-human",
+    "locale",
+    "decimal-places",
+    "currency-symbol",
+    "cost-decimal-places",
+    "reverse-sort-default",
+    "hide-empty-periods",
+    "default-view",
+    "default-tab",
+    "confirm-quit",
+    "hidden-columns",
+    "columns",
+    "accent-color",
+    "color-costs",
+    "show-header",
+    "show-trend",
+    "trend-days",
+    "cost-mode",
+    "log-level",
+    "budget-monthly-usd",
+    "budget-weekly-usd",
+    "budget-warn-at",
+    "notifications-enabled",
+    "notify-daily-thresholds",
+    "freeze-deleted-sessions",
+    "cache-max-size-mb",
+    "data-dir",
+    "sqlite-store",
+    "archive-logs",
+    "archive-max-size-mb",
+    "pricing-updates",
+    "pricing-updates-url",
+    "watcher-poll-interval",
+    "parse-io",
+    "parse-threads",
+    "forecast-window-days",
+    "git-integration",
+    "theme",
+    "cost-source",
+    "copilot-plan",
+    "limit-five-hour-tokens",
+    "limit-weekly-tokens",
+    "idle-gap-minutes",
+];
+
+fn default_upload_path() -> String {
+    "/api/upload-stats".to_string()
+}
+
+fn default_upload_chunk_size() -> usize {
+    3000
 }
 
 fn default_currency_symbol() -> String {
@@ -80,10 +597,59 @@ fn default_accent_color() -> String {
     "cyan".to_string()
 }
 
+fn default_trend_days() -> usize {
+    30
+}
+
 fn default_true() -> bool {
     true
 }
 
+fn default_theme_preset() -> String {
+    "dark".to_string()
+}
+
+fn default_forecast_window_days() -> u32 {
+    crate::forecast::DEFAULT_WINDOW_DAYS
+}
+
+fn default_idle_gap_minutes() -> u32 {
+    crate::stats::active::DEFAULT_IDLE_GAP_MINUTES
+}
+
+fn default_budget_warn_at() -> f64 {
+    0.8
+}
+
+/// Parse a budget amount; "none" or an empty value removes the limit.
+fn parse_budget_amount(value: &str) -> Result<Option<f64>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    let amount = value
+        .trim_start_matches('$')
+        .parse::<f64>()
+        .context("Invalid amount. Use a number like 200 or 'none'")?;
+    anyhow::ensure!(amount > 0.0, "Budget must be greater than zero");
+    Ok(Some(amount))
+}
+
+/// A token limit, or `None` for "none"/"auto"/empty.
+fn parse_token_limit(value: &str) -> Result<Option<u64>> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") || value.eq_ignore_ascii_case("auto")
+    {
+        return Ok(None);
+    }
+    let tokens = value
+        .replace(['_', ','], "")
+        .parse::<u64>()
+        .context("Invalid token limit. Use a whole number like 2000000 or 'auto'")?;
+    anyhow::ensure!(tokens > 0, "Token limit must be greater than zero");
+    Ok(Some(tokens))
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FormattingConfig {
     pub number_comma: bool,
@@ -98,6 +664,32 @@ pub struct FormattingConfig {
     pub cost_decimal_places: usize,
 }
 
+impl FormattingConfig {
+    /// Number formatting for display, with command-line flags taking precedence.
+    pub fn number_format(&self, overrides: &FormatOverrides) -> NumberFormatOptions {
+        NumberFormatOptions {
+            use_comma: overrides.number_comma || self.number_comma,
+            use_human: overrides.number_human || self.number_human,
+            locale: overrides
+                .locale
+                .clone()
+                .unwrap_or_else(|| self.locale.clone()),
+            decimal_places: overrides.decimal_places.unwrap_or(self.decimal_places),
+            currency_symbol: self.currency_symbol.clone(),
+            cost_decimal_places: self.cost_decimal_places,
+        }
+    }
+}
+
+/// Formatting flags given on the command line. They outlive config reloads.
+#[derive(Debug, Clone, Default)]
+pub struct FormatOverrides {
+    pub number_comma: bool,
+    pub number_human: bool,
+    pub locale: Option<String>,
+    pub decimal_places: Option<usize>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TuiConfig {
     #[serde(default)]
@@ -115,10 +707,16 @@ pub struct TuiConfig {
     #[serde(default)]
     pub confirm_quit: bool,
     /// Columns to hide from the aggregate table, e.g. ["models", "cached",
-    /// "reason"]. Recognized: cached, input, output, reason, convs, tools,
-    /// apps, models.
+    /// "reason"]. Recognized: cached, hit, input, output, reason, convs,
+    /// tools, active, per-hour, subagents, premium, apps, models.
     #[serde(default)]
     pub hidden_columns: Vec<String>,
+    /// Columns to show, in order, after the always-shown period and cost,
+    /// e.g. ["models", "input", "output"]. Takes precedence over
+    /// `hidden_columns` when set; unlisted columns are hidden. Edit in the
+    /// TUI with 'C'.
+    #[serde(default)]
+    pub columns: Vec<String>,
     /// Accent color for the title, tab bar and selected row: "cyan" | "green"
     /// | "magenta" | "blue" | "red" | "yellow" | "white".
     #[serde(default = "default_accent_color")]
@@ -129,6 +727,25 @@ pub struct TuiConfig {
     /// Show the "AGENTIC DEVELOPMENT TOOL ACTIVITY ANALYSIS" header banner.
     #[serde(default = "default_true")]
     pub show_header: bool,
+    /// Show the cost and token trend charts above the aggregate table on
+    /// startup (toggle with 'c').
+    #[serde(default)]
+    pub show_trend: bool,
+    /// How many days, ending today, the trend charts cover.
+    #[serde(default = "default_trend_days")]
+    pub trend_days: usize,
+    /// Whether costs start out at API rates or at what `[subscriptions]`
+    /// plans actually cost (toggle with '$').
+    #[serde(default)]
+    pub cost_mode: CostMode,
+    /// Per-tool color overrides keyed by tool name, e.g. `"Codex CLI" = "green"`
+    /// or `"Claude Code" = "#d97757"`. Unlisted tools get a stable default.
+    #[serde(default)]
+    pub analyzer_colors: HashMap<String, String>,
+    /// Per-tool icons keyed by tool name, e.g. a Nerd Font glyph. Unlisted
+    /// tools show no icon.
+    #[serde(default)]
+    pub analyzer_icons: HashMap<String, String>,
 }
 
 impl Default for TuiConfig {
@@ -140,9 +757,15 @@ impl Default for TuiConfig {
             default_tab: String::new(),
             confirm_quit: false,
             hidden_columns: Vec::new(),
+            columns: Vec::new(),
             accent_color: default_accent_color(),
             color_costs: false,
             show_header: true,
+            show_trend: false,
+            trend_days: default_trend_days(),
+            cost_mode: CostMode::default(),
+            analyzer_colors: HashMap::new(),
+            analyzer_icons: HashMap::new(),
         }
     }
 }
@@ -153,11 +776,18 @@ impl Default for Config {
             server: ServerConfig {
                 url: "https://splitrail.dev".to_string(),
                 api_token: "".to_string(),
+                upload_path: default_upload_path(),
+                schema_version: 0,
+                keychain: false,
             },
             upload: UploadConfig {
                 auto_upload: false,
                 upload_today_only: false,
                 retry_attempts: 3,
+                chunk_size: default_upload_chunk_size(),
+                compress: false,
+                proxy_url: None,
+                ca_bundle_path: None,
             },
             formatting: FormattingConfig {
                 number_comma: false,
@@ -171,8 +801,190 @@ impl Default for Config {
             tui: TuiConfig::default(),
             models: HashMap::new(),
             aliases: HashMap::new(),
+            pricing: HashMap::new(),
+            budget: BudgetConfig::default(),
+            notifications: NotificationsConfig::default(),
+            history: HistoryConfig::default(),
+            cache: CacheConfig::default(),
+            archive: ArchiveConfig::default(),
+            pricing_updates: PricingUpdatesConfig::default(),
+            watcher: WatcherConfig::default(),
+            parsing: ParsingConfig::default(),
+            forecast: ForecastConfig::default(),
+            git: GitConfig::default(),
+            costs: CostsConfig::default(),
+            copilot: CopilotConfig::default(),
+            limits: LimitsConfig::default(),
+            activity: ActivityConfig::default(),
+            privacy: PrivacyConfig::default(),
+            exclude: ExcludeConfig::default(),
+            theme: ThemeConfig::default(),
+            subscriptions: HashMap::new(),
+            custom_analyzers: Vec::new(),
+            analyzers: HashMap::new(),
+            paths: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+/// Key naming an analyzer in `[analyzers]`, `--only` and `--exclude`: the
+/// slug of a built-in tool (display names work too), or a custom analyzer's
+/// name in snake case.
+pub fn analyzer_key(name: &str) -> String {
+    if let Some(app) = crate::types::Application::from_name(name) {
+        return app.slug().to_string();
+    }
+    let mut key = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            key.push(c.to_ascii_lowercase());
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
         }
     }
+    key.trim_end_matches('_').to_string()
+}
+
+impl Config {
+    /// `[paths]` entry for an analyzer, with `~` expanded. Empty means the
+    /// analyzer's own defaults.
+    pub fn analyzer_paths(&self, display_name: &str) -> Vec<PathBuf> {
+        let key = analyzer_key(display_name);
+        self.paths
+            .iter()
+            .filter(|(name, _)| analyzer_key(name) == key)
+            .flat_map(|(_, dirs)| dirs)
+            .map(|dir| dir.trim())
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(crate::utils::expand_home(dir)))
+            .collect()
+    }
+}
+
+/// Analyzers picked on the command line with `--only` and `--exclude`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnalyzerFilter {
+    pub only: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+static ANALYZER_FILTER: OnceLock<AnalyzerFilter> = OnceLock::new();
+
+/// Apply `--only`/`--exclude` to every registry built by this process.
+pub fn set_analyzer_filter(filter: AnalyzerFilter) {
+    let _ = ANALYZER_FILTER.set(filter);
+}
+
+/// One `[exclude] paths` entry: a glob when it has glob characters, else a
+/// substring.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExcludeRule {
+    Glob(glob::Pattern),
+    Substring(String),
+}
+
+impl ExcludeRule {
+    pub fn parse(rule: &str) -> Option<Self> {
+        let rule = crate::utils::expand_home(rule.trim());
+        if rule.contains(['*', '?', '[']) {
+            glob::Pattern::new(&rule).ok().map(ExcludeRule::Glob)
+        } else {
+            Some(ExcludeRule::Substring(rule))
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            ExcludeRule::Glob(pattern) => pattern.matches(path),
+            ExcludeRule::Substring(needle) => path.contains(needle.as_str()),
+        }
+    }
+}
+
+/// `[exclude] paths`, applied to every analyzer's sources.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExcludeRules {
+    rules: Vec<ExcludeRule>,
+}
+
+static EXCLUDE_RULES: RwLock<Option<Arc<ExcludeRules>>> = RwLock::new(None);
+
+/// Apply `[exclude] paths` to sources discovered from now on. Returns whether
+/// the rules changed, i.e. whether loaded stats need a reload.
+pub fn set_exclude_rules(rules: ExcludeRules) -> bool {
+    let mut current = EXCLUDE_RULES.write();
+    let changed = current.as_deref().unwrap_or(&ExcludeRules::default()) != &rules;
+    *current = Some(Arc::new(rules));
+    changed
+}
+
+impl ExcludeRules {
+    /// Rules from the config, skipping (and warning about) invalid globs.
+    pub fn from_config(exclude: &ExcludeConfig) -> Self {
+        let rules = exclude
+            .paths
+            .iter()
+            .filter(|rule| !rule.trim().is_empty())
+            .filter_map(|rule| {
+                let parsed = ExcludeRule::parse(rule);
+                if parsed.is_none() {
+                    crate::utils::warn_once(format!("Ignoring invalid [exclude] glob '{rule}'"));
+                }
+                parsed
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn current() -> Arc<ExcludeRules> {
+        EXCLUDE_RULES.read().clone().unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether a source is excluded, by its own path or by the working
+    /// directory its transcript records (Claude Code, Codex), since most
+    /// tools keep their logs outside the project.
+    pub fn excludes(&self, path: &Path) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        if self.matches(path) {
+            return true;
+        }
+        crate::utils::transcript_cwd(path).is_some_and(|cwd| self.matches(&cwd))
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.rules.iter().any(|rule| rule.matches(&path))
+    }
+}
+
+impl AnalyzerFilter {
+    pub fn current() -> &'static AnalyzerFilter {
+        ANALYZER_FILTER.get_or_init(AnalyzerFilter::default)
+    }
+
+    /// Whether an analyzer should run. `--exclude` always wins, `--only`
+    /// replaces the `[analyzers]` section, and anything unlisted runs.
+    pub fn allows(&self, display_name: &str, configured: &HashMap<String, bool>) -> bool {
+        let key = analyzer_key(display_name);
+        let matches = |name: &String| analyzer_key(name) == key;
+        if self.exclude.iter().any(matches) {
+            return false;
+        }
+        if !self.only.is_empty() {
+            return self.only.iter().any(matches);
+        }
+        configured
+            .iter()
+            .find(|(name, _)| matches(name))
+            .is_none_or(|(_, &enabled)| enabled)
+    }
 }
 
 thread_local! {
@@ -199,9 +1011,7 @@ impl Config {
             }
         }
 
-        Ok(dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".splitrail.toml"))
+        crate::paths::config_file()
     }
 
     pub fn load() -> Result<Option<Config>> {
@@ -222,6 +1032,9 @@ impl Config {
         let config_path = Self::config_path()?;
         let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
 
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
         fs::write(&config_path, content).context("Failed to write config file")?;
 
         if !silent {
@@ -244,11 +1057,26 @@ impl Config {
     }
 
     pub fn is_configured(&self) -> bool {
-        !self.server.api_token.is_empty() && !self.server.url.is_empty()
+        !self.is_api_token_missing() && !self.server.url.is_empty()
     }
 
     pub fn is_api_token_missing(&self) -> bool {
-        self.server.api_token.is_empty()
+        self.server.api_token.is_empty() && !self.server.keychain
+    }
+
+    /// Where to look for the API token, in order.
+    pub fn token_providers(&self) -> Vec<Box<dyn TokenProvider + '_>> {
+        let mut providers: Vec<Box<dyn TokenProvider + '_>> = Vec::new();
+        if self.server.keychain {
+            providers.push(Box::new(KeychainToken::new(&self.server.url)));
+        }
+        providers.push(Box::new(FileToken(&self.server.api_token)));
+        providers
+    }
+
+    /// The API token to upload with, from the keychain or the config file.
+    pub fn api_token(&self) -> Result<String> {
+        resolve_token(&self.token_providers())
     }
 
     pub fn is_server_url_missing(&self) -> bool {
@@ -256,6 +1084,109 @@ impl Config {
     }
 }
 
+/// A place the API token can be stored.
+pub trait TokenProvider {
+    /// The stored token, or `None` if this provider doesn't have one.
+    fn api_token(&self) -> Result<Option<String>>;
+}
+
+/// `[server] api_token` in the config file.
+pub struct FileToken<'a>(pub &'a str);
+
+impl TokenProvider for FileToken<'_> {
+    fn api_token(&self) -> Result<Option<String>> {
+        Ok((!self.0.is_empty()).then(|| self.0.to_string()))
+    }
+}
+
+/// The OS keychain (macOS Keychain, Windows Credential Manager, or the
+/// Secret Service on Linux), keyed by server URL and profile.
+pub struct KeychainToken {
+    account: String,
+}
+
+impl KeychainToken {
+    const SERVICE: &'static str = "splitrail";
+
+    /// The entry for `server_url` in the current profile.
+    pub fn new(server_url: &str) -> Self {
+        Self::for_profile(server_url, crate::paths::profile().as_deref())
+    }
+
+    /// The default profile keeps the bare URL its tokens were first stored
+    /// under; named profiles get `<profile>@<url>` so two profiles on one
+    /// server don't overwrite each other's token.
+    fn for_profile(server_url: &str, profile: Option<&str>) -> Self {
+        let account = match profile {
+            Some(profile) => format!("{profile}@{server_url}"),
+            None => server_url.to_string(),
+        };
+        Self { account }
+    }
+
+    fn entry(&self) -> Result<keyring::Entry> {
+        keyring::Entry::new(Self::SERVICE, &self.account).context("Failed to open the keychain")
+    }
+
+    pub fn store(&self, token: &str) -> Result<()> {
+        self.entry()?
+            .set_password(token)
+            .context("Failed to save the API token to the keychain")
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        match self.entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e).context("Failed to remove the API token from the keychain"),
+        }
+    }
+}
+
+impl TokenProvider for KeychainToken {
+    fn api_token(&self) -> Result<Option<String>> {
+        match self.entry()?.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e).context("Failed to read the API token from the keychain"),
+        }
+    }
+}
+
+/// The first token any provider has. A provider that fails is skipped, but
+/// its error is reported if no later provider has a token either.
+fn resolve_token(providers: &[Box<dyn TokenProvider + '_>]) -> Result<String> {
+    let mut first_error = None;
+    for provider in providers {
+        match provider.api_token() {
+            Ok(Some(token)) => return Ok(token),
+            Ok(None) => {}
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| anyhow::anyhow!("No API token configured")))
+}
+
+/// `splitrail config set api-token --keychain`: store the token in the OS
+/// keychain, or in the config file if the keychain isn't available.
+pub fn set_api_token_in_keychain(token: &str) -> Result<()> {
+    let mut config = Config::load()?.unwrap_or_default();
+    match KeychainToken::new(&config.server.url).store(token) {
+        Ok(()) => {
+            config.server.api_token.clear();
+            config.server.keychain = true;
+            println!("🔑 API token saved to the OS keychain");
+        }
+        Err(e) => {
+            eprintln!("⚠️  {e:#}; saving it to the config file instead");
+            config.server.api_token = token.to_string();
+            config.server.keychain = false;
+        }
+    }
+    config.save(false)
+}
+
 #[derive(Debug, Deserialize, Default)]
 struct LegacyConfigFile {
     #[serde(default)]
@@ -277,23 +1208,33 @@ impl UploadState {
             }
         }
 
-        let state_root = dirs::state_dir()
-            .or_else(dirs::data_local_dir)
-            .context("Could not find platform state directory")?;
-
-        Ok(state_root.join("splitrail").join("state.toml"))
+        Ok(crate::paths::data_dir()?.join("state.toml"))
     }
 
     /// Load upload state from the state file.
     ///
     /// If the state file does not exist, attempts to migrate `last_date_uploaded`
     /// from the legacy config location. Falls back to a zero-value default if
-    /// neither source is present.
+    /// neither source is present. An unparseable state file is quarantined and
+    /// whatever keys still parse line by line are kept; if the watermark isn't
+    /// among them, the state is marked with `watermark_lost`.
     pub fn load() -> Result<Self> {
         let state_path = Self::state_path()?;
         if state_path.exists() {
             let content = fs::read_to_string(&state_path).context("Failed to read state file")?;
-            return toml::from_str(&content).context("Failed to parse state file");
+            match toml::from_str(&content) {
+                Ok(state) => return Ok(state),
+                Err(e) => {
+                    crate::persist::quarantine(
+                        &state_path,
+                        "Upload state",
+                        &anyhow::Error::new(e).context("Failed to parse state file"),
+                    );
+                    let state = Self::salvage(&content);
+                    state.save()?;
+                    return Ok(state);
+                }
+            }
         }
 
         if let Some(state) = Self::load_legacy_from_config()? {
@@ -306,16 +1247,31 @@ impl UploadState {
         Ok(Self::default())
     }
 
-    /// Persist the current state to the state file, creating the directory if needed.
-    pub fn save(&self) -> Result<()> {
-        let state_path = Self::state_path()?;
-        if let Some(parent) = state_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create state directory")?;
+    /// Recover the keys of a corrupt state file that still parse on their
+    /// own line.
+    fn salvage(content: &str) -> Self {
+        let mut table = toml::Table::new();
+        for line in content.lines() {
+            if let Ok(entry) = toml::from_str::<toml::Table>(line) {
+                table.extend(entry);
+            }
+        }
+        let watermark_lost = !matches!(
+            table.get("last_date_uploaded"),
+            Some(toml::Value::Integer(date)) if *date > 0
+        );
+        if watermark_lost {
+            table.insert("last_date_uploaded".into(), toml::Value::Integer(0));
         }
+        let mut state: Self = table.try_into().unwrap_or_default();
+        state.watermark_lost = watermark_lost;
+        state
+    }
 
+    /// Persist the current state to the state file, creating the directory if needed.
+    pub fn save(&self) -> Result<()> {
         let content = toml::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&state_path, content).context("Failed to write state file")?;
-        Ok(())
+        crate::persist::write_atomic(&Self::state_path()?, content, "state file")
     }
 
     /// Read `last_date_uploaded` from the old `[upload]` section of the config file, if present.
@@ -358,20 +1314,84 @@ pub fn create_default_config(overwrite: bool) -> Result<()> {
     Ok(())
 }
 
+/// `config show --json`: the config file's path and contents, with the API
+/// token masked. `config` is null when there is no config file.
+pub fn show_config_json() -> Result<()> {
+    #[derive(Serialize)]
+    struct Shown {
+        path: String,
+        config: Option<Config>,
+    }
+
+    let config = Config::load()?.map(|mut config| {
+        if !config.server.api_token.is_empty() {
+            config.server.api_token = "********".to_string();
+        }
+        config
+    });
+    let shown = Shown {
+        path: Config::config_path()?.to_string_lossy().into_owned(),
+        config,
+    };
+    println!("{}", simd_json::to_string_pretty(&shown)?);
+    Ok(())
+}
+
 pub fn show_config() -> Result<()> {
     match Config::load()? {
         Some(config) => {
             println!("🔧 Current configuration:");
+            if let Some(profile) = crate::paths::profile() {
+                println!("   Profile: {profile}");
+            }
             println!(
                 "   API Token: {}",
-                if config.server.api_token.is_empty() {
+                if config.server.keychain {
+                    "Set (keychain)"
+                } else if config.server.api_token.is_empty() {
                     "Not set"
                 } else {
                     "Set"
                 }
             );
+            if config.server.upload_path != default_upload_path()
+                || config.server.schema_version != 0
+            {
+                println!(
+                    "   Upload Endpoint: {}{} (schema version {})",
+                    config.server.url, config.server.upload_path, config.server.schema_version
+                );
+            }
             println!("   Auto Upload: {}", config.upload.auto_upload);
             println!("   Upload Today Only: {}", config.upload.upload_today_only);
+            println!("   Upload Chunk Size: {}", config.upload.chunk_size);
+            println!("   Upload Compression: {}", config.upload.compress);
+            if let Some(proxy_url) = &config.upload.proxy_url {
+                println!("   Upload Proxy: {proxy_url}");
+            }
+            if let Some(ca_bundle_path) = &config.upload.ca_bundle_path {
+                println!("   CA Bundle: {ca_bundle_path}");
+            }
+            println!(
+                "   Privacy Salt Project Hashes: {}",
+                config.privacy.salt_project_hashes
+            );
+            println!(
+                "   Privacy Strip Session Names: {}",
+                config.privacy.strip_session_names
+            );
+            println!(
+                "   Privacy Generalize Models: {}",
+                config.privacy.generalize_models
+            );
+            println!(
+                "   Excluded Paths: {}",
+                if config.exclude.paths.is_empty() {
+                    "None".to_string()
+                } else {
+                    config.exclude.paths.join(", ")
+                }
+            );
             println!("   Number Comma: {}", config.formatting.number_comma);
             println!("   Number Human: {}", config.formatting.number_human);
             println!("   Locale: {}", config.formatting.locale);
@@ -407,16 +1427,168 @@ pub fn show_config() -> Result<()> {
                     config.tui.hidden_columns.join(", ")
                 }
             );
+            println!(
+                "   TUI Columns: {}",
+                if config.tui.columns.is_empty() {
+                    "Default".to_string()
+                } else {
+                    config.tui.columns.join(", ")
+                }
+            );
             println!("   TUI Accent Color: {}", config.tui.accent_color);
             println!("   TUI Color Costs: {}", config.tui.color_costs);
             println!("   TUI Show Header: {}", config.tui.show_header);
+            println!("   TUI Show Trend: {}", config.tui.show_trend);
+            println!("   TUI Trend Days: {}", config.tui.trend_days);
+            println!("   TUI Cost Mode: {}", config.tui.cost_mode.label());
             println!("   Log Level: {}", config.logging.level);
+            let budget_amount = |amount: Option<f64>| {
+                amount.map_or("None".to_string(), |amount| format!("{amount:.2}"))
+            };
+            println!(
+                "   Budget Monthly: {}",
+                budget_amount(config.budget.monthly_usd)
+            );
+            println!(
+                "   Budget Weekly: {}",
+                budget_amount(config.budget.weekly_usd)
+            );
+            println!("   Budget Warn At: {:.0}%", config.budget.warn_at * 100.0);
+            if !config.budget.analyzers.is_empty() {
+                println!("   Per-Tool Budgets: {}", config.budget.analyzers.len());
+            }
+            println!(
+                "   Notifications: {}",
+                if !config.notifications.enabled {
+                    "Off".to_string()
+                } else if config.notifications.daily_thresholds_usd.is_empty() {
+                    "Budgets only".to_string()
+                } else {
+                    let thresholds: Vec<String> = config
+                        .notifications
+                        .daily_thresholds_usd
+                        .iter()
+                        .map(|amount| format!("{amount:.2}"))
+                        .collect();
+                    format!("Daily at {}", thresholds.join(", "))
+                }
+            );
+            if config.history.freeze_deleted_sessions {
+                let frozen = crate::analyzers::ClaudeCodeAnalyzer::new()
+                    .frozen_session_count()
+                    .map_or_else(|e| format!("unknown: {e}"), |count| count.to_string());
+                println!(
+                    "   Freeze Deleted Sessions: true ({frozen} frozen Claude Code transcripts)"
+                );
+            } else {
+                println!("   Freeze Deleted Sessions: false");
+            }
+            println!(
+                "   Cache Max Size: {}",
+                config
+                    .cache
+                    .max_size_mb
+                    .map_or_else(|| "Unlimited".to_string(), |mb| format!("{mb} MB"))
+            );
+            println!(
+                "   Data Directory: {}",
+                crate::paths::data_dir()
+                    .map_or_else(|e| format!("unknown: {e}"), |dir| dir.display().to_string())
+            );
+            println!(
+                "   File Watcher: {}",
+                config.watcher.poll_interval_secs.map_or_else(
+                    || "File system events".to_string(),
+                    |secs| format!("Polling every {secs}s")
+                )
+            );
+            println!(
+                "   Parsing: {}{}",
+                config.parsing.io,
+                config
+                    .parsing
+                    .threads
+                    .map_or_else(String::new, |threads| format!(", up to {threads} threads"))
+            );
+            println!("   Forecast Window: {} days", config.forecast.window_days);
+            println!(
+                "   Git Integration: {}",
+                if config.git.enabled { "On" } else { "Off" }
+            );
+            println!("   Theme: {}", config.theme.preset);
+            match crate::store::store_path().filter(|_| config.cache.sqlite_store) {
+                Some(path) => println!("   SQLite Store: {}", path.display()),
+                None => println!("   SQLite Store: Off"),
+            }
+            println!(
+                "   Log Archive: {}",
+                if config.archive.enabled {
+                    format!(
+                        "On, up to {} MB",
+                        config
+                            .archive
+                            .max_size_mb
+                            .unwrap_or(crate::archive::DEFAULT_MAX_SIZE_MB)
+                    )
+                } else {
+                    "Off".to_string()
+                }
+            );
             if !config.models.is_empty() {
                 println!("   Custom Models: {}", config.models.len());
             }
             if !config.aliases.is_empty() {
                 println!("   Custom Aliases: {}", config.aliases.len());
             }
+            if !config.pricing.is_empty() {
+                println!("   Pricing Overrides: {}", config.pricing.len());
+            }
+            if !config.keys.is_empty() {
+                println!("   Key Bindings: {}", config.keys.len());
+            }
+            let mut disabled: Vec<&str> = config
+                .analyzers
+                .iter()
+                .filter(|(_, enabled)| !**enabled)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            if !disabled.is_empty() {
+                disabled.sort_unstable();
+                println!("   Disabled Analyzers: {}", disabled.join(", "));
+            }
+            let mut paths: Vec<_> = config.paths.iter().collect();
+            paths.sort_by(|a, b| a.0.cmp(b.0));
+            for (analyzer, dirs) in paths {
+                println!("   Data Paths ({analyzer}): {}", dirs.join(", "));
+            }
+            let mut plans: Vec<_> = config.subscriptions.iter().collect();
+            plans.sort_by(|a, b| a.0.cmp(b.0));
+            for (tool, plan) in plans {
+                println!("   Subscription ({tool}): ${:.2}/month", plan.monthly_usd);
+            }
+            if config.pricing_updates.enabled {
+                let cached = crate::models::remote::cached_summary()
+                    .unwrap_or_else(|| "not fetched yet".to_string());
+                println!(
+                    "   Pricing Updates: {} ({cached})",
+                    config.pricing_updates.url
+                );
+            } else {
+                println!("   Pricing Updates: Off");
+            }
+            println!("   Cost Source: {}", config.costs.source.label());
+            println!("   Copilot Plan: {}", config.copilot.plan.label());
+            let token_limit =
+                |limit: Option<u64>| limit.map_or("Auto".to_string(), |limit| limit.to_string());
+            println!(
+                "   Limit Five-Hour Tokens: {}",
+                token_limit(config.limits.five_hour_tokens)
+            );
+            println!(
+                "   Limit Weekly Tokens: {}",
+                token_limit(config.limits.weekly_tokens)
+            );
+            println!("   Idle Gap: {} minutes", config.activity.idle_gap_minutes);
         }
         None => {
             println!("❌ No configuration file found.");
@@ -430,7 +1602,16 @@ pub fn set_config_value(key: &str, value: &str) -> Result<()> {
     let mut config = Config::load()?.unwrap_or_default();
 
     match key {
-        "api-token" => config.set_api_token(value.to_string()),
+        "api-token" => {
+            // A token in the file replaces any in the keychain.
+            if config.server.keychain {
+                if let Err(e) = KeychainToken::new(&config.server.url).delete() {
+                    eprintln!("⚠️  {e:#}");
+                }
+                config.server.keychain = false;
+            }
+            config.set_api_token(value.to_string());
+        }
         "auto-upload" => {
             let enabled = value
                 .parse::<bool>()
@@ -443,6 +1624,76 @@ pub fn set_config_value(key: &str, value: &str) -> Result<()> {
                 .context("Invalid boolean value. Use 'true' or 'false'")?;
             config.set_upload_today_only(enabled);
         }
+        "server-upload-path" => {
+            let path = value.trim();
+            anyhow::ensure!(path.starts_with('/'), "Upload path must start with '/'");
+            config.server.upload_path = path.to_string();
+        }
+        "server-schema-version" => {
+            let version = value
+                .parse::<u32>()
+                .context("Invalid schema version. Use 0 or 1")?;
+            anyhow::ensure!(
+                version <= crate::upload::UPLOAD_SCHEMA_VERSION,
+                "Unknown schema version {version}. The latest is {}",
+                crate::upload::UPLOAD_SCHEMA_VERSION
+            );
+            config.server.schema_version = version;
+        }
+        "upload-chunk-size" => {
+            let size = value
+                .parse::<usize>()
+                .context("Invalid chunk size. Use a whole number of messages")?;
+            anyhow::ensure!(size > 0, "Chunk size must be at least 1");
+            config.upload.chunk_size = size;
+        }
+        "upload-compress" => {
+            config.upload.compress = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "privacy-salt-project-hashes" => {
+            config.privacy.salt_project_hashes = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "privacy-strip-session-names" => {
+            config.privacy.strip_session_names = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "privacy-generalize-models" => {
+            config.privacy.generalize_models = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "exclude-paths" => {
+            let paths: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect();
+            if let Some(invalid) = paths.iter().find(|path| ExcludeRule::parse(path).is_none()) {
+                anyhow::bail!("Invalid glob in exclude-paths: '{invalid}'");
+            }
+            config.exclude.paths = paths;
+        }
+        "upload-proxy-url" => {
+            let url = value.trim();
+            config.upload.proxy_url = match url {
+                "" | "none" => None,
+                url => {
+                    reqwest::Proxy::all(url).with_context(|| format!("Invalid proxy URL {url}"))?;
+                    Some(url.to_string())
+                }
+            };
+        }
+        "upload-ca-bundle-path" => {
+            let path = value.trim();
+            config.upload.ca_bundle_path =
+                (!path.is_empty() && path != "none").then(|| path.to_string());
+        }
         "number-comma" => {
             let enabled = value
                 .parse::<bool>()
@@ -500,6 +1751,23 @@ pub fn set_config_value(key: &str, value: &str) -> Result<()> {
                 .map(ToString::to_string)
                 .collect();
         }
+        "columns" => {
+            let columns: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect();
+            if let Some(unknown) = columns
+                .iter()
+                .find(|column| crate::tui::columns::Column::parse(column).is_none())
+            {
+                anyhow::bail!(
+                    "Unknown column '{unknown}'. Use: cached, hit, input, output, reason, convs, tools, active, per-hour, subagents, premium, apps, models"
+                );
+            }
+            config.tui.columns = columns;
+        }
         "accent-color" => {
             config.tui.accent_color = value.to_string();
         }
@@ -513,9 +1781,194 @@ pub fn set_config_value(key: &str, value: &str) -> Result<()> {
                 .parse::<bool>()
                 .context("Invalid boolean value. Use 'true' or 'false'")?;
         }
+        "show-trend" => {
+            config.tui.show_trend = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "trend-days" => {
+            let days = value
+                .parse::<usize>()
+                .context("Invalid number of days. Use a positive integer")?;
+            anyhow::ensure!(days > 0, "Trend days must be at least 1");
+            config.tui.trend_days = days;
+        }
+        "cost-mode" => {
+            config.tui.cost_mode = match value.trim().to_lowercase().as_str() {
+                "api" => CostMode::Api,
+                "actual" => CostMode::Actual,
+                _ => anyhow::bail!("Invalid cost mode. Use 'api' or 'actual'"),
+            };
+        }
         "log-level" => {
             config.logging.level = value.parse().map_err(anyhow::Error::msg)?;
         }
+        "budget-monthly-usd" => {
+            config.budget.monthly_usd = parse_budget_amount(value)?;
+        }
+        "budget-weekly-usd" => {
+            config.budget.weekly_usd = parse_budget_amount(value)?;
+        }
+        "budget-warn-at" => {
+            let warn_at =
+                value.trim().trim_end_matches('%').parse::<f64>().context(
+                    "Invalid threshold. Use a fraction like 0.8 or a percentage like 80%",
+                )?;
+            // Accept both 0.8 and 80(%).
+            let warn_at = if warn_at > 1.0 {
+                warn_at / 100.0
+            } else {
+                warn_at
+            };
+            anyhow::ensure!(
+                warn_at > 0.0 && warn_at <= 1.0,
+                "Threshold must be above 0 and at most 100%"
+            );
+            config.budget.warn_at = warn_at;
+        }
+        "notifications-enabled" => {
+            config.notifications.enabled = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "notify-daily-thresholds" => {
+            let mut thresholds = value
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| parse_budget_amount(s)?.context("Thresholds must be amounts"))
+                .collect::<Result<Vec<f64>>>()?;
+            thresholds.sort_by(f64::total_cmp);
+            thresholds.dedup();
+            config.notifications.daily_thresholds_usd = thresholds;
+        }
+        "freeze-deleted-sessions" => {
+            config.history.freeze_deleted_sessions = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "cache-max-size-mb" => {
+            config.cache.max_size_mb = match value.trim() {
+                "" | "none" | "0" => None,
+                mb => Some(
+                    mb.parse::<u64>()
+                        .context("Invalid size. Use a whole number of megabytes, or 'none'")?,
+                ),
+            };
+        }
+        "data-dir" => {
+            let dir = value.trim();
+            config.cache.data_dir = (!dir.is_empty() && dir != "none").then(|| dir.to_string());
+        }
+        "sqlite-store" => {
+            config.cache.sqlite_store = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "archive-logs" => {
+            config.archive.enabled = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "archive-max-size-mb" => {
+            config.archive.max_size_mb = match value.trim() {
+                "" | "default" => None,
+                mb => {
+                    let mb = mb
+                        .parse::<u64>()
+                        .context("Invalid size. Use a whole number of megabytes")?;
+                    anyhow::ensure!(mb > 0, "Archive size must be greater than zero");
+                    Some(mb)
+                }
+            };
+        }
+        "pricing-updates" => {
+            config.pricing_updates.enabled = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "pricing-updates-url" => {
+            anyhow::ensure!(
+                value.starts_with("https://") || value.starts_with("http://"),
+                "Pricing manifest URL must start with http:// or https://"
+            );
+            config.pricing_updates.url = value.to_string();
+        }
+        "watcher-poll-interval" => {
+            config.watcher.poll_interval_secs = match value.trim() {
+                "" | "none" | "0" => None,
+                secs => Some(
+                    secs.parse::<u64>()
+                        .context("Invalid interval. Use a whole number of seconds, or 'none'")?,
+                ),
+            };
+        }
+        "parse-io" => {
+            config.parsing.io = value.parse().map_err(anyhow::Error::msg)?;
+        }
+        "parse-threads" => {
+            config.parsing.threads = match value.trim() {
+                "" | "none" | "0" => None,
+                threads => Some(
+                    threads
+                        .parse::<usize>()
+                        .context("Invalid thread count. Use a whole number, or 'none'")?,
+                ),
+            };
+        }
+        "forecast-window-days" => {
+            let days = value
+                .parse::<u32>()
+                .context("Invalid number of days. Use a whole number from 1 to 90")?;
+            anyhow::ensure!(
+                (1..=90).contains(&days),
+                "Forecast window must be between 1 and 90 days"
+            );
+            config.forecast.window_days = days;
+        }
+        "theme" => {
+            let preset = crate::tui::theme::Theme::preset_name(value).with_context(|| {
+                format!(
+                    "Unknown theme '{value}'. Use: {}",
+                    crate::tui::theme::PRESETS.join(", ")
+                )
+            })?;
+            config.theme.preset = preset.to_string();
+        }
+        "git-integration" => {
+            config.git.enabled = value
+                .parse::<bool>()
+                .context("Invalid boolean value. Use 'true' or 'false'")?;
+        }
+        "cost-source" => {
+            config.costs.source = match value.trim().to_lowercase().as_str() {
+                "auto" => CostSourcePreference::Auto,
+                "logged" => CostSourcePreference::Logged,
+                "computed" => CostSourcePreference::Computed,
+                _ => anyhow::bail!("Invalid cost source. Use 'auto', 'logged' or 'computed'"),
+            };
+        }
+        "limit-five-hour-tokens" => {
+            config.limits.five_hour_tokens = parse_token_limit(value)?;
+        }
+        "limit-weekly-tokens" => {
+            config.limits.weekly_tokens = parse_token_limit(value)?;
+        }
+        "idle-gap-minutes" => {
+            let minutes = value
+                .parse::<u32>()
+                .context("Invalid number of minutes. Use a whole number from 1 to 240")?;
+            anyhow::ensure!(
+                (1..=240).contains(&minutes),
+                "Idle gap must be between 1 and 240 minutes"
+            );
+            config.activity.idle_gap_minutes = minutes;
+        }
+        "copilot-plan" => {
+            config.copilot.plan = CopilotPlan::parse(value).context(
+                "Invalid Copilot plan. Use 'free', 'pro', 'pro-plus', 'business' or 'enterprise'",
+            )?;
+        }
         _ => anyhow::bail!("Unknown config key: {}", key),
     }
 
@@ -523,6 +1976,281 @@ pub fn set_config_value(key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
+impl Config {
+    /// Values that parse but are out of range, as `key: problem` lines.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, key: &str, problem: String| {
+            if !ok {
+                problems.push(format!("{key}: {problem}"));
+            }
+        };
+        check(
+            self.server.upload_path.starts_with('/'),
+            "server.upload_path",
+            "must start with '/'".to_string(),
+        );
+        check(
+            self.server.schema_version <= crate::upload::UPLOAD_SCHEMA_VERSION,
+            "server.schema_version",
+            format!("must be at most {}", crate::upload::UPLOAD_SCHEMA_VERSION),
+        );
+        check(
+            self.upload.chunk_size > 0,
+            "upload.chunk_size",
+            "must be at least 1".to_string(),
+        );
+        if let Some(proxy_url) = &self.upload.proxy_url {
+            check(
+                reqwest::Proxy::all(proxy_url).is_ok(),
+                "upload.proxy_url",
+                format!("{proxy_url:?} isn't a valid URL"),
+            );
+        }
+        check(
+            [
+                "daily", "day", "weekly", "week", "monthly", "month", "yearly", "year",
+            ]
+            .contains(&self.tui.default_view.trim().to_lowercase().as_str()),
+            "tui.default_view",
+            "must be daily, weekly, monthly or yearly".to_string(),
+        );
+        for column in &self.tui.columns {
+            check(
+                crate::tui::columns::Column::parse(column).is_some(),
+                "tui.columns",
+                format!("{column:?} isn't a column"),
+            );
+        }
+        check(
+            crate::tui::theme::Theme::preset_name(&self.theme.preset).is_some(),
+            "theme.preset",
+            format!("must be one of {}", crate::tui::theme::PRESETS.join(", ")),
+        );
+        for (role, color) in self.theme.roles() {
+            if let Some(color) = color {
+                check(
+                    crate::tui::parse_color(color).is_some(),
+                    &format!("theme.{role}"),
+                    format!("{color:?} isn't a color name or #rrggbb"),
+                );
+            }
+        }
+        for (action, keys) in &self.keys {
+            check(
+                crate::tui::keymap::Action::from_name(action).is_some(),
+                &format!("keys.{action}"),
+                "isn't a TUI action".to_string(),
+            );
+            for key in keys.split(',') {
+                check(
+                    crate::tui::keymap::KeyBinding::parse(key).is_some(),
+                    &format!("keys.{action}"),
+                    format!("{:?} isn't a key", key.trim()),
+                );
+            }
+        }
+        check(
+            self.tui.trend_days > 0,
+            "tui.trend_days",
+            "must be at least 1".to_string(),
+        );
+        check(
+            self.budget.warn_at > 0.0 && self.budget.warn_at <= 1.0,
+            "budget.warn_at",
+            "must be a fraction between 0 and 1, like 0.8".to_string(),
+        );
+        let budgets = std::iter::once((
+            "budget".to_string(),
+            self.budget.monthly_usd,
+            self.budget.weekly_usd,
+        ))
+        .chain(self.budget.analyzers.iter().map(|(name, limits)| {
+            (
+                format!("budget.analyzers.{name:?}"),
+                limits.monthly_usd,
+                limits.weekly_usd,
+            )
+        }));
+        for (section, monthly, weekly) in budgets {
+            for (key, amount) in [("monthly_usd", monthly), ("weekly_usd", weekly)] {
+                check(
+                    amount.is_none_or(|amount| amount > 0.0),
+                    &format!("{section}.{key}"),
+                    "must be greater than zero".to_string(),
+                );
+            }
+        }
+        check(
+            self.notifications
+                .daily_thresholds_usd
+                .iter()
+                .all(|amount| *amount > 0.0),
+            "notifications.daily_thresholds_usd",
+            "amounts must be greater than zero".to_string(),
+        );
+        check(
+            self.pricing_updates.url.starts_with("https://")
+                || self.pricing_updates.url.starts_with("http://"),
+            "pricing_updates.url",
+            "must start with http:// or https://".to_string(),
+        );
+        check(
+            self.watcher.poll_interval_secs != Some(0),
+            "watcher.poll_interval_secs",
+            "must be at least 1".to_string(),
+        );
+        check(
+            self.parsing.threads != Some(0),
+            "parsing.threads",
+            "must be at least 1".to_string(),
+        );
+        check(
+            (1..=90).contains(&self.forecast.window_days),
+            "forecast.window_days",
+            "must be between 1 and 90".to_string(),
+        );
+        check(
+            (1..=240).contains(&self.activity.idle_gap_minutes),
+            "activity.idle_gap_minutes",
+            "must be between 1 and 240".to_string(),
+        );
+        problems
+    }
+}
+
+/// Everything wrong with a config file: syntax and type errors, keys
+/// splitrail doesn't know, and values out of range. Empty if it's valid.
+pub fn validate_config_text(text: &str) -> Vec<String> {
+    let deserializer = match toml::Deserializer::parse(text) {
+        Ok(deserializer) => deserializer,
+        Err(e) => return vec![e.to_string()],
+    };
+    let mut unknown = Vec::new();
+    let config: Config =
+        match serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string())) {
+            Ok(config) => config,
+            Err(e) => return vec![e.to_string()],
+        };
+
+    let known = toml::Value::try_from(&config).ok();
+    let mut problems: Vec<String> = unknown
+        .into_iter()
+        // Read once to migrate it into the state file.
+        .filter(|path| path != "upload.last_date_uploaded")
+        .map(|path| match suggest_key(known.as_ref(), &path) {
+            Some(suggestion) => format!("{path}: unknown key, did you mean `{suggestion}`?"),
+            None => format!("{path}: unknown key"),
+        })
+        .collect();
+    problems.extend(config.validate());
+    problems
+}
+
+/// A known sibling of an unknown dotted key that is only a typo away.
+fn suggest_key(known: Option<&toml::Value>, path: &str) -> Option<String> {
+    let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+    let mut table = known?.as_table()?;
+    for part in parent.split('.').filter(|part| !part.is_empty()) {
+        table = table.get(part)?.as_table()?;
+    }
+    table
+        .keys()
+        .map(|candidate| (edit_distance(candidate, key), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| match parent {
+            "" => candidate.clone(),
+            parent => format!("{parent}.{candidate}"),
+        })
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// `splitrail config edit`: open a copy of the config in `$VISUAL`/`$EDITOR`
+/// and only replace the real file once the edited copy is valid.
+pub fn edit_config() -> Result<()> {
+    let path = Config::config_path()?;
+    let original = if path.exists() {
+        fs::read_to_string(&path).context("Failed to read config file")?
+    } else {
+        toml::to_string_pretty(&Config::default()).context("Failed to serialize config")?
+    };
+    let mut draft_name = path.file_name().unwrap_or_default().to_os_string();
+    draft_name.push(".edit");
+    let draft = path.with_file_name(draft_name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+    fs::write(&draft, &original).with_context(|| format!("Failed to write {}", draft.display()))?;
+
+    loop {
+        if let Err(e) = open_in_editor(&draft) {
+            if fs::read_to_string(&draft).is_ok_and(|text| text == original) {
+                let _ = fs::remove_file(&draft);
+            }
+            return Err(e);
+        }
+        let text = fs::read_to_string(&draft)
+            .with_context(|| format!("Failed to read {}", draft.display()))?;
+        let problems = validate_config_text(&text);
+        if problems.is_empty() {
+            if text == original && path.exists() {
+                println!("No changes.");
+            } else {
+                fs::write(&path, &text).context("Failed to write config file")?;
+                println!("✅ Configuration saved to: {}", path.display());
+            }
+            let _ = fs::remove_file(&draft);
+            return Ok(());
+        }
+
+        eprintln!("❌ The edited configuration has problems:");
+        for problem in &problems {
+            eprintln!("   • {problem}");
+        }
+        eprint!("Edit again? [Y/n] ");
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if answer.trim().eq_ignore_ascii_case("n") {
+            anyhow::bail!(
+                "Configuration not changed. Your edits are in {}",
+                draft.display()
+            );
+        }
+    }
+}
+
+fn open_in_editor(path: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    // Allow editors with arguments, like `code --wait`.
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("No editor configured")?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor `{editor}`"))?;
+    anyhow::ensure!(status.success(), "Editor `{editor}` exited with {status}");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -585,6 +2313,118 @@ is_estimated = true
         assert_eq!(config.aliases.get("my-alias").unwrap(), "custom-model");
     }
 
+    #[test]
+    fn test_config_with_custom_analyzers() {
+        let toml_str = r#"
+[server]
+url = "https://splitrail.dev"
+api_token = ""
+
+[upload]
+auto_upload = false
+upload_today_only = false
+retry_attempts = 3
+
+[formatting]
+number_comma = false
+number_human = false
+locale = "en"
+decimal_places = 2
+
+[[custom_analyzer]]
+name = "Acme Agent"
+glob = "~/.acme/logs/*.jsonl"
+timestamp = "ts"
+input_tokens = "usage.input"
+
+[[custom_analyzer]]
+name = "Other"
+glob = "/tmp/other/*.jsonl"
+timestamp = "time"
+"#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+
+        assert_eq!(config.custom_analyzers.len(), 2);
+        let acme = &config.custom_analyzers[0];
+        assert_eq!(acme.name, "Acme Agent");
+        assert_eq!(acme.input_tokens.as_deref(), Some("usage.input"));
+        assert!(acme.model.is_none());
+        assert!(acme.cost.is_none());
+        assert_eq!(config.custom_analyzers[1].timestamp, "time");
+    }
+
+    #[test]
+    fn test_config_with_budgets() {
+        let toml_str = r#"
+[server]
+url = "https://splitrail.dev"
+api_token = ""
+
+[upload]
+auto_upload = false
+upload_today_only = false
+retry_attempts = 3
+
+[formatting]
+number_comma = false
+number_human = false
+locale = "en"
+decimal_places = 2
+
+[budget]
+monthly_usd = 200
+
+[budget.analyzers."Claude Code"]
+weekly_usd = 40.5
+"#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+
+        assert_eq!(config.budget.monthly_usd, Some(200.0));
+        assert_eq!(config.budget.weekly_usd, None);
+        assert_eq!(config.budget.warn_at, 0.8);
+        let claude = &config.budget.analyzers["Claude Code"];
+        assert_eq!(claude.weekly_usd, Some(40.5));
+        assert!(!config.budget.is_empty());
+        assert!(Config::default().budget.is_empty());
+    }
+
+    #[test]
+    fn test_set_budget_values() {
+        let (_dir, _path, _state_path) = setup_test_config();
+
+        set_config_value("budget-monthly-usd", "$150").unwrap();
+        set_config_value("budget-warn-at", "90%").unwrap();
+        let config = Config::load().unwrap().unwrap();
+        assert_eq!(config.budget.monthly_usd, Some(150.0));
+        assert!((config.budget.warn_at - 0.9).abs() < 1e-9);
+
+        set_config_value("budget-monthly-usd", "none").unwrap();
+        assert_eq!(Config::load().unwrap().unwrap().budget.monthly_usd, None);
+        assert!(set_config_value("budget-weekly-usd", "-5").is_err());
+        for invalid in ["0", "-0.5", "150", "101%", "NaN"] {
+            assert!(set_config_value("budget-warn-at", invalid).is_err());
+        }
+        assert!((Config::load().unwrap().unwrap().budget.warn_at - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_set_notification_thresholds() {
+        let (_dir, _path, _state_path) = setup_test_config();
+
+        set_config_value("notifications-enabled", "true").unwrap();
+        set_config_value("notify-daily-thresholds", "25, $10,25").unwrap();
+        let config = Config::load().unwrap().unwrap();
+        assert!(config.notifications.enabled);
+        assert_eq!(config.notifications.daily_thresholds_usd, vec![10.0, 25.0]);
+
+        assert!(set_config_value("notify-daily-thresholds", "10,abc").is_err());
+        set_config_value("notify-daily-thresholds", "").unwrap();
+        let config = Config::load().unwrap().unwrap();
+        assert!(config.notifications.daily_thresholds_usd.is_empty());
+    }
+
     #[test]
     fn default_config_round_trip() {
         let (_dir, config_path, _state_path) = setup_test_config();
@@ -620,6 +2460,17 @@ is_estimated = true
         set_config_value("api-token", "TEST_TOKEN").expect("set api-token");
         set_config_value("auto-upload", "true").expect("set auto-upload");
         set_config_value("upload-today-only", "true").expect("set upload-today-only");
+        set_config_value("server-upload-path", "/ingest/v1").expect("set server-upload-path");
+        assert!(set_config_value("server-upload-path", "ingest").is_err());
+        set_config_value("server-schema-version", "1").expect("set server-schema-version");
+        assert!(set_config_value("server-schema-version", "99").is_err());
+        set_config_value("upload-chunk-size", "500").expect("set upload-chunk-size");
+        assert!(set_config_value("upload-chunk-size", "0").is_err());
+        set_config_value("upload-compress", "true").expect("set upload-compress");
+        set_config_value("upload-proxy-url", "http://proxy.corp:3128")
+            .expect("set upload-proxy-url");
+        set_config_value("upload-ca-bundle-path", "~/corp-ca.pem")
+            .expect("set upload-ca-bundle-path");
         set_config_value("number-comma", "true").expect("set number-comma");
         set_config_value("number-human", "true").expect("set number-human");
         set_config_value("locale", "de").expect("set locale");
@@ -632,10 +2483,48 @@ is_estimated = true
         set_config_value("default-tab", "Cline").expect("set default-tab");
         set_config_value("confirm-quit", "true").expect("set confirm-quit");
         set_config_value("hidden-columns", "reason, models").expect("set hidden-columns");
+        set_config_value("columns", "models, input, output").expect("set columns");
+        assert!(set_config_value("columns", "models, vibes").is_err());
         set_config_value("accent-color", "magenta").expect("set accent-color");
         set_config_value("color-costs", "true").expect("set color-costs");
         set_config_value("show-header", "false").expect("set show-header");
+        set_config_value("show-trend", "true").expect("set show-trend");
+        set_config_value("trend-days", "14").expect("set trend-days");
+        set_config_value("cost-mode", "actual").expect("set cost-mode");
+        set_config_value("freeze-deleted-sessions", "true").expect("set freeze-deleted-sessions");
+        set_config_value("pricing-updates", "true").expect("set pricing-updates");
+        set_config_value("cache-max-size-mb", "512").expect("set cache-max-size-mb");
+        set_config_value("data-dir", "~/Sync/splitrail").expect("set data-dir");
+        set_config_value("sqlite-store", "true").expect("set sqlite-store");
+        set_config_value("archive-logs", "true").expect("set archive-logs");
+        set_config_value("archive-max-size-mb", "256").expect("set archive-max-size-mb");
+        assert!(set_config_value("archive-max-size-mb", "0").is_err());
+        set_config_value("pricing-updates-url", "https://example.com/prices.json")
+            .expect("set pricing-updates-url");
         set_config_value("log-level", "error").expect("set log-level");
+        set_config_value("watcher-poll-interval", "10").expect("set watcher-poll-interval");
+        set_config_value("parse-io", "Sequential").expect("set parse-io");
+        assert!(set_config_value("parse-io", "fast").is_err());
+        set_config_value("parse-threads", "2").expect("set parse-threads");
+        assert!(set_config_value("parse-threads", "many").is_err());
+        set_config_value("forecast-window-days", "30").expect("set forecast-window-days");
+        assert!(set_config_value("forecast-window-days", "0").is_err());
+        set_config_value("git-integration", "true").expect("set git-integration");
+        set_config_value("privacy-strip-session-names", "true").expect("set privacy flag");
+        set_config_value("exclude-paths", "acme-corp, **/secret/**").expect("set exclude-paths");
+        assert!(set_config_value("exclude-paths", "[unclosed").is_err());
+        assert!(set_config_value("privacy-generalize-models", "yes").is_err());
+        set_config_value("theme", "Light").expect("set theme");
+        assert!(set_config_value("theme", "neon").is_err());
+        set_config_value("cost-source", "Computed").expect("set cost-source");
+        assert!(set_config_value("cost-source", "billed").is_err());
+        set_config_value("copilot-plan", "Pro+").expect("set copilot-plan");
+        assert!(set_config_value("copilot-plan", "team").is_err());
+        set_config_value("limit-five-hour-tokens", "2_000_000").expect("set five-hour limit");
+        set_config_value("limit-weekly-tokens", "auto").expect("clear weekly limit");
+        assert!(set_config_value("limit-weekly-tokens", "0").is_err());
+        set_config_value("idle-gap-minutes", "30").expect("set idle-gap-minutes");
+        assert!(set_config_value("idle-gap-minutes", "0").is_err());
 
         let cfg = Config::load()
             .expect("load config")
@@ -644,6 +2533,15 @@ is_estimated = true
         assert_eq!(cfg.server.api_token, "TEST_TOKEN");
         assert!(cfg.upload.auto_upload);
         assert!(cfg.upload.upload_today_only);
+        assert_eq!(cfg.server.upload_path, "/ingest/v1");
+        assert_eq!(cfg.server.schema_version, 1);
+        assert_eq!(cfg.upload.chunk_size, 500);
+        assert!(cfg.upload.compress);
+        assert_eq!(
+            cfg.upload.proxy_url.as_deref(),
+            Some("http://proxy.corp:3128")
+        );
+        assert_eq!(cfg.upload.ca_bundle_path.as_deref(), Some("~/corp-ca.pem"));
         assert!(cfg.formatting.number_comma);
         assert!(cfg.formatting.number_human);
         assert_eq!(cfg.formatting.locale, "de");
@@ -656,10 +2554,37 @@ is_estimated = true
         assert_eq!(cfg.tui.default_tab, "Cline");
         assert!(cfg.tui.confirm_quit);
         assert_eq!(cfg.tui.hidden_columns, vec!["reason", "models"]);
+        assert_eq!(cfg.tui.columns, vec!["models", "input", "output"]);
         assert_eq!(cfg.tui.accent_color, "magenta");
         assert!(cfg.tui.color_costs);
         assert!(!cfg.tui.show_header);
+        assert!(cfg.tui.show_trend);
+        assert_eq!(cfg.tui.trend_days, 14);
+        assert_eq!(cfg.tui.cost_mode, CostMode::Actual);
+        assert!(cfg.history.freeze_deleted_sessions);
+        assert!(cfg.pricing_updates.enabled);
+        assert_eq!(cfg.cache.max_size_mb, Some(512));
+        assert_eq!(cfg.cache.data_dir.as_deref(), Some("~/Sync/splitrail"));
+        assert!(cfg.cache.sqlite_store);
+        assert!(cfg.archive.enabled);
+        assert_eq!(cfg.archive.max_size_mb, Some(256));
+        assert_eq!(cfg.pricing_updates.url, "https://example.com/prices.json");
+        assert_eq!(cfg.pricing_updates.refresh_hours, 24);
         assert_eq!(cfg.logging.level, LogLevel::Error);
+        assert_eq!(cfg.watcher.poll_interval_secs, Some(10));
+        assert_eq!(cfg.parsing.io, crate::parse_io::IoStrategy::Sequential);
+        assert_eq!(cfg.parsing.threads, Some(2));
+        assert_eq!(cfg.forecast.window_days, 30);
+        assert!(cfg.git.enabled);
+        assert!(cfg.privacy.strip_session_names);
+        assert_eq!(cfg.exclude.paths, vec!["acme-corp", "**/secret/**"]);
+        assert!(!cfg.privacy.generalize_models);
+        assert_eq!(cfg.theme.preset, "light");
+        assert_eq!(cfg.costs.source, CostSourcePreference::Computed);
+        assert_eq!(cfg.copilot.plan, CopilotPlan::ProPlus);
+        assert_eq!(cfg.limits.five_hour_tokens, Some(2_000_000));
+        assert_eq!(cfg.limits.weekly_tokens, None);
+        assert_eq!(cfg.activity.idle_gap_minutes, 30);
 
         let err = set_config_value("unknown-key", "value").unwrap_err();
         let msg = format!("{err}");
@@ -709,6 +2634,35 @@ decimal_places = 2
         assert!(saved_state.contains("last_date_uploaded = 1234"));
     }
 
+    #[test]
+    fn corrupt_upload_state_is_quarantined() {
+        let (_dir, _config_path, state_path) = setup_test_config();
+        fs::write(&state_path, "last_date_uploaded = [").expect("write corrupt state");
+
+        let state = UploadState::load().expect("load after corruption");
+        assert_eq!(state.last_date_uploaded, 0);
+        assert!(state.watermark_lost);
+        assert!(crate::persist::quarantine_path(&state_path).exists());
+        // The mark is saved, so it survives until an upload clears it.
+        assert!(UploadState::load().expect("reload").watermark_lost);
+    }
+
+    #[test]
+    fn corrupt_upload_state_keeps_the_watermark_when_it_parses() {
+        let (_dir, _config_path, state_path) = setup_test_config();
+        fs::write(
+            &state_path,
+            "last_date_uploaded = 1234\nproject_salt = \"abc\"\nclaude_subagent_backfill_completed = tr",
+        )
+        .expect("write corrupt state");
+
+        let state = UploadState::load().expect("load after corruption");
+        assert_eq!(state.last_date_uploaded, 1234);
+        assert_eq!(state.project_salt, "abc");
+        assert!(!state.watermark_lost);
+        assert!(crate::persist::quarantine_path(&state_path).exists());
+    }
+
     #[test]
     fn config_toml_parses_tui_section() {
         let toml_str = r#"
@@ -737,4 +2691,182 @@ hide_empty_periods = true
         assert!(config.tui.hide_empty_periods);
         assert_eq!(config.logging.level, LogLevel::Warn);
     }
+
+    #[test]
+    fn token_comes_from_the_first_provider_that_has_one() {
+        struct Unavailable;
+        impl TokenProvider for Unavailable {
+            fn api_token(&self) -> Result<Option<String>> {
+                anyhow::bail!("keychain locked")
+            }
+        }
+
+        let fallback: Vec<Box<dyn TokenProvider>> =
+            vec![Box::new(Unavailable), Box::new(FileToken("from-file"))];
+        assert_eq!(resolve_token(&fallback).unwrap(), "from-file");
+
+        let nothing: Vec<Box<dyn TokenProvider>> =
+            vec![Box::new(Unavailable), Box::new(FileToken(""))];
+        assert_eq!(
+            resolve_token(&nothing).unwrap_err().to_string(),
+            "keychain locked"
+        );
+
+        let mut config = Config::default();
+        assert!(config.is_api_token_missing());
+        config.server.keychain = true;
+        assert!(config.is_configured());
+        assert_eq!(config.token_providers().len(), 2);
+    }
+
+    #[test]
+    fn keychain_accounts_are_separate_per_profile() {
+        let url = "https://splitrail.dev";
+        assert_eq!(KeychainToken::for_profile(url, None).account, url);
+        assert_eq!(
+            KeychainToken::for_profile(url, Some("work")).account,
+            "work@https://splitrail.dev"
+        );
+    }
+
+    #[test]
+    fn config_text_validation_reports_actionable_problems() {
+        let valid = toml::to_string_pretty(&Config::default()).unwrap();
+        assert_eq!(validate_config_text(&valid), Vec::<String>::new());
+
+        let text = valid
+            .replace("show_header = true", "show_headr = true")
+            .replace("chunk_size = 3000", "chunk_size = 0")
+            + "\n[mystery]\nvalue = 1\n"
+            + "\n[keys]\nsessions = \"v\"\nteleport = \"x\"\nquit = \"q, ctrl+nope\"\n";
+        let problems = validate_config_text(&text);
+        assert!(
+            problems.contains(
+                &"tui.show_headr: unknown key, did you mean `tui.show_header`?".to_string()
+            ),
+            "{problems:?}"
+        );
+        assert!(
+            problems.contains(&"mystery: unknown key".to_string()),
+            "{problems:?}"
+        );
+        assert!(
+            problems.contains(&"upload.chunk_size: must be at least 1".to_string()),
+            "{problems:?}"
+        );
+        assert!(
+            problems.contains(&"keys.teleport: isn't a TUI action".to_string()),
+            "{problems:?}"
+        );
+        assert!(
+            problems.contains(&"keys.quit: \"ctrl+nope\" isn't a key".to_string()),
+            "{problems:?}"
+        );
+        assert!(
+            !problems
+                .iter()
+                .any(|problem| problem.starts_with("keys.sessions")),
+            "{problems:?}"
+        );
+
+        let wrong_type = valid.replace("decimal_places = 2", "decimal_places = \"two\"");
+        let problems = validate_config_text(&wrong_type);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("decimal_places"), "{problems:?}");
+
+        // Presets are matched the way the TUI reads them.
+        let mut config = Config::default();
+        config.theme.preset = "NoColor".to_string();
+        assert!(config.validate().is_empty(), "{:?}", config.validate());
+        config.theme.preset = "neon".to_string();
+        assert_eq!(config.validate().len(), 1);
+    }
+
+    #[test]
+    fn analyzer_selection_combines_config_and_flags() {
+        assert_eq!(analyzer_key("Claude Code"), "claude_code");
+        assert_eq!(analyzer_key("GitHub Copilot"), "copilot");
+        assert_eq!(analyzer_key("Acme Agent (beta)"), "acme_agent_beta");
+
+        let configured: HashMap<String, bool> = toml::from_str(
+            r#"
+"Codex CLI" = false
+gemini_cli = true
+copilot = false
+"#,
+        )
+        .unwrap();
+
+        let none = AnalyzerFilter::default();
+        assert!(none.allows("Claude Code", &configured));
+        assert!(!none.allows("Codex CLI", &configured));
+        assert!(!none.allows("GitHub Copilot", &configured));
+        assert!(none.allows("GitHub Copilot CLI", &configured));
+
+        let only = AnalyzerFilter {
+            only: vec!["codex_cli".to_string()],
+            exclude: Vec::new(),
+        };
+        assert!(only.allows("Codex CLI", &configured));
+        assert!(!only.allows("Gemini CLI", &configured));
+
+        let exclude = AnalyzerFilter {
+            only: vec!["Claude Code".to_string(), "gemini_cli".to_string()],
+            exclude: vec!["gemini_cli".to_string()],
+        };
+        assert!(exclude.allows("Claude Code", &configured));
+        assert!(!exclude.allows("Gemini CLI", &configured));
+    }
+    #[test]
+    fn exclude_rules_match_globs_and_substrings() {
+        let rules = ExcludeRules::from_config(&ExcludeConfig {
+            paths: vec![
+                "acme-corp".to_string(),
+                "/data/**/secret-*.json".to_string(),
+                "[unclosed".to_string(),
+            ],
+        });
+        assert!(!rules.is_empty());
+        assert!(rules.excludes(Path::new(
+            "/home/me/.claude/projects/-home-me-src-acme-corp-api/1.jsonl"
+        )));
+        assert!(rules.excludes(Path::new("/data/a/b/secret-1.json")));
+        assert!(!rules.excludes(Path::new("/data/a/b/public-1.json")));
+        assert!(!rules.excludes(Path::new(
+            "/home/me/.claude/projects/-home-me-src-splitrail/1.jsonl"
+        )));
+
+        assert!(!ExcludeRules::default().excludes(Path::new("/anything")));
+    }
+
+    #[test]
+    fn exclude_rules_match_the_recorded_project_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude = dir.path().join("claude.jsonl");
+        fs::write(
+            &claude,
+            "{\"type\":\"summary\"}\n{\"type\":\"user\",\"cwd\":\"/home/me/work/confidential/api\"}\n",
+        )
+        .unwrap();
+        let codex = dir.path().join("rollout.jsonl");
+        fs::write(
+            &codex,
+            "{\"type\":\"session_meta\",\"payload\":{\"cwd\":\"/home/me/src/client-acme\"}}\n",
+        )
+        .unwrap();
+
+        let rules = ExcludeRules::from_config(&ExcludeConfig {
+            paths: vec![
+                "/home/me/work/confidential/**".to_string(),
+                "client-acme".to_string(),
+            ],
+        });
+        assert!(rules.excludes(&claude));
+        assert!(rules.excludes(&codex));
+
+        let other = ExcludeRules::from_config(&ExcludeConfig {
+            paths: vec!["/home/me/personal/**".to_string()],
+        });
+        assert!(!other.excludes(&claude));
+    }
 }
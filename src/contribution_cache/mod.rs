@@ -2,7 +2,7 @@
 //!
 //! Provides memory-efficient caching strategies for different analyzer types:
 //! - [`SingleMessageContribution`]: 32 bytes for 1-message-per-file analyzers (OpenCode)
-//! - [`SingleSessionContribution`]: ~72 bytes for 1-session-per-file analyzers (most)
+//! - [`SingleSessionContribution`]: ~200 bytes for 1-session-per-file analyzers (most)
 //! - [`MultiSessionContribution`]: ~100+ bytes for all-in-one-file analyzers (Piebald)
 
 mod multi_session;
@@ -18,7 +18,7 @@ use std::path::Path;
 use dashmap::DashMap;
 use xxhash_rust::xxh3::xxh3_64;
 
-use crate::types::{AnalyzerStatsView, CompactDate, DailyStats};
+use crate::types::{AnalyzerStatsView, CompactDate, DailyStats, ModelStats, SessionAggregate};
 
 // ============================================================================
 // PathHash - Cache key type
@@ -60,7 +60,7 @@ pub enum ContributionStrategy {
     SingleMessage,
 
     /// 1 file = 1 session = many messages (e.g., Claude Code, Cline, Copilot)
-    /// Uses `SingleSessionContribution` (~72 bytes per file)
+    /// Uses `SingleSessionContribution` (~200 bytes per file)
     SingleSession,
 
     /// 1 file = many sessions (e.g., Piebald with SQLite)
@@ -77,7 +77,7 @@ pub enum ContributionStrategy {
 pub struct ContributionCache {
     /// Cache for single-message-per-file analyzers (32 bytes per entry)
     single_message: DashMap<PathHash, SingleMessageContribution>,
-    /// Cache for single-session-per-file analyzers (~72 bytes per entry)
+    /// Cache for single-session-per-file analyzers (~200 bytes per entry)
     single_session: DashMap<PathHash, SingleSessionContribution>,
     /// Cache for multi-session-per-file analyzers (~100+ bytes per entry)
     multi_session: DashMap<PathHash, MultiSessionContribution>,
@@ -155,6 +155,39 @@ impl ContributionCache {
         self.multi_session.get(key).map(|r| r.clone())
     }
 
+    // --- Strategy-agnostic lookup ---
+
+    /// Whether the contribution cached for `key` holds messages of the session
+    /// `session_id`. False when nothing is cached for the file.
+    pub fn covers_session(&self, key: &PathHash, session_id: &str) -> bool {
+        let session_hash = SessionHash::from_str(session_id);
+        if let Some(c) = self.single_message.get(key) {
+            return c.session_hash == session_hash;
+        }
+        if let Some(c) = self.single_session.get(key) {
+            return c.session_hash == session_hash;
+        }
+        self.multi_session.get(key).is_some_and(|c| {
+            c.session_aggregates
+                .iter()
+                .any(|s| s.session_id == session_id)
+        })
+    }
+
+    /// Whether the contribution cached for `key` has usage dated `day`.
+    /// False when nothing is cached for the file.
+    pub fn covers_day(&self, key: &PathHash, day: CompactDate) -> bool {
+        if let Some(c) = self.single_message.get(key) {
+            return c.date() == day;
+        }
+        if let Some(c) = self.single_session.get(key) {
+            return c.date == day;
+        }
+        self.multi_session
+            .get(key)
+            .is_some_and(|c| c.daily_stats.contains_key(&day.to_string()))
+    }
+
     // --- Strategy-agnostic removal ---
 
     /// Try to remove a contribution from any cache, returning which type was found.
@@ -201,7 +234,9 @@ impl AnalyzerStatsView {
         // Single message contributes to AI message count and stats
         if contrib.model.is_some() {
             day_stats.ai_messages += 1;
+            day_stats.hourly_messages[contrib.hour()] += 1;
             day_stats.stats += contrib.to_tui_stats();
+            day_stats.prompt_cache += contrib.prompt_cache();
         }
 
         // Find session by hash and update
@@ -223,7 +258,10 @@ impl AnalyzerStatsView {
         if let Some(day_stats) = self.daily_stats.get_mut(&date_str) {
             if contrib.model.is_some() {
                 day_stats.ai_messages = day_stats.ai_messages.saturating_sub(1);
+                let hour = &mut day_stats.hourly_messages[contrib.hour()];
+                *hour = hour.saturating_sub(1);
                 day_stats.stats -= contrib.to_tui_stats();
+                day_stats.prompt_cache -= contrib.prompt_cache();
             }
 
             // Remove if empty
@@ -260,6 +298,17 @@ impl AnalyzerStatsView {
 
         day_stats.ai_messages += contrib.ai_message_count;
         day_stats.stats += contrib.stats;
+        day_stats.prompt_cache += contrib.prompt_cache;
+        for model in &contrib.model_stats {
+            day_stats
+                .model_stats
+                .entry(model.model.clone())
+                .or_insert_with(|| ModelStats::new(model.model.clone()))
+                .add_model_stats(model);
+        }
+        for &(hour, count) in contrib.hourly_messages.iter() {
+            day_stats.hourly_messages[hour as usize] += count;
+        }
 
         // Find session by hash and update
         if let Some(existing) = self.session_aggregates.iter_mut().find(|s| {
@@ -281,6 +330,19 @@ impl AnalyzerStatsView {
                 .ai_messages
                 .saturating_sub(contrib.ai_message_count);
             day_stats.stats -= contrib.stats;
+            day_stats.prompt_cache -= contrib.prompt_cache;
+            for &(hour, count) in contrib.hourly_messages.iter() {
+                let slot = &mut day_stats.hourly_messages[hour as usize];
+                *slot = slot.saturating_sub(count);
+            }
+            for model in &contrib.model_stats {
+                if let Some(existing) = day_stats.model_stats.get_mut(&model.model) {
+                    existing.sub_model_stats(model);
+                    if existing.message_count == 0 {
+                        day_stats.model_stats.remove(&model.model);
+                    }
+                }
+            }
 
             // Remove if empty
             if day_stats.user_messages == 0
@@ -330,6 +392,7 @@ impl AnalyzerStatsView {
                 if new_session.first_timestamp < existing.first_timestamp {
                     existing.first_timestamp = new_session.first_timestamp;
                     existing.date = new_session.date;
+                    existing.stable_id = new_session.stable_id.clone();
                 }
                 if existing.session_name.is_none() {
                     existing.session_name = new_session.session_name.clone();
@@ -343,7 +406,8 @@ impl AnalyzerStatsView {
         self.num_conversations += contrib.conversation_count;
 
         // Keep sessions sorted by timestamp
-        self.session_aggregates.sort_by_key(|s| s.first_timestamp);
+        self.session_aggregates
+            .sort_by(SessionAggregate::chronological);
     }
 
     /// Subtract a multi-session contribution from this view.
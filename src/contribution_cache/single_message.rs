@@ -3,10 +3,13 @@
 //! Optimized to 32 bytes for cache alignment using bitfield packing.
 
 use c2rust_bitfields::BitfieldStruct;
+use chrono::{Local, Timelike};
 
 use super::SessionHash;
 use crate::cache::ModelKey;
-use crate::types::{CompactDate, ConversationMessage, TuiStats, intern_model};
+use crate::types::{
+    CompactDate, ConversationMessage, MessageRole, PromptCacheStats, TuiStats, intern_model,
+};
 
 // ============================================================================
 // PackedStatsDate - Bitfield-packed stats and date (22 bytes)
@@ -25,7 +28,8 @@ use crate::types::{CompactDate, ConversationMessage, TuiStats, intern_model};
 // | year_offset       | 2025-2026    | 6         | 63 (2020-2083)    |
 // | month             | 1-12         | 4         | 15                |
 // | day               | 1-31         | 5         | 31                |
-// | duration_ms       | —            | 25        | 33,554,431 (~9.3h)|
+// | saved_millicents  | —            | 20        | 1,048,575 ($10.48)|
+// | hour              | 0-23         | 5         | 31                |
 //
 // Total: 176 bits = 22 bytes
 
@@ -41,7 +45,8 @@ use crate::types::{CompactDate, ConversationMessage, TuiStats, intern_model};
 /// - year_offset:      bits 136-141 (6 bits, years 2020-2083)
 /// - month:            bits 142-145 (4 bits, 1-12)
 /// - day:              bits 146-150 (5 bits, 1-31)
-/// - duration_ms:      bits 151-175 (25 bits, max ~9.3 hours)
+/// - saved_millicents: bits 151-170 (20 bits, prompt-cache savings, max $10.48)
+/// - hour:             bits 171-175 (5 bits, local hour of day 0-23)
 #[repr(C, align(1))]
 #[derive(BitfieldStruct, Clone, Copy, Default)]
 pub struct PackedStatsDate {
@@ -54,7 +59,8 @@ pub struct PackedStatsDate {
     #[bitfield(name = "year_offset", ty = "u8", bits = "136..=141")]
     #[bitfield(name = "month", ty = "u8", bits = "142..=145")]
     #[bitfield(name = "day", ty = "u8", bits = "146..=150")]
-    #[bitfield(name = "duration_ms", ty = "u32", bits = "151..=175")]
+    #[bitfield(name = "saved_millicents", ty = "u32", bits = "151..=170")]
+    #[bitfield(name = "hour", ty = "u8", bits = "171..=175")]
     data: [u8; 22],
 }
 
@@ -70,7 +76,8 @@ impl std::fmt::Debug for PackedStatsDate {
             .field("year_offset", &self.year_offset())
             .field("month", &self.month())
             .field("day", &self.day())
-            .field("duration_ms", &self.duration_ms())
+            .field("saved_millicents", &self.saved_millicents())
+            .field("hour", &self.hour())
             .finish()
     }
 }
@@ -98,9 +105,6 @@ impl PackedStatsDate {
         packed.set_month(date.month());
         packed.set_day(date.day());
 
-        // duration_ms reserved for future use
-        packed.set_duration_ms(0);
-
         packed
     }
 
@@ -124,6 +128,12 @@ impl PackedStatsDate {
             cached_tokens: self.cached_tokens() as u64,
             cost_cents: self.cost_cents() as u32,
             tool_calls: self.tool_calls() as u32,
+            // One-message-per-file tools have no subagent threads.
+            subagent_cost_cents: 0,
+            // Nor premium requests, which only Copilot bills.
+            premium_hundredths: 0,
+            // A lone message has no pauses to measure work blocks by.
+            active_secs: 0,
         }
     }
 }
@@ -156,10 +166,27 @@ impl SingleMessageContribution {
         Self {
             session_hash: SessionHash::from_str(&msg.conversation_hash),
             model: msg.model.as_ref().map(|m| intern_model(m)),
-            packed: PackedStatsDate::pack(&msg.stats, CompactDate::from_local(&msg.date)),
+            packed: {
+                let local = msg.date.with_timezone(&Local);
+                let mut packed = PackedStatsDate::pack(&msg.stats, CompactDate::from_local(&local));
+                packed.set_hour(local.hour() as u8);
+                if msg.role == MessageRole::Assistant {
+                    let saved = crate::utils::prompt_cache_stats(std::slice::from_ref(msg));
+                    packed.set_saved_millicents(
+                        (saved.saved_cost * 100_000.0).round().min(0xF_FFFF as f64) as u32,
+                    );
+                }
+                packed
+            },
         }
     }
 
+    /// Local hour of day (0-23) the message was sent.
+    #[inline]
+    pub fn hour(&self) -> usize {
+        (self.packed.hour() as usize).min(23)
+    }
+
     /// Get the date from the packed representation.
     #[inline]
     pub fn date(&self) -> CompactDate {
@@ -172,6 +199,16 @@ impl SingleMessageContribution {
         self.packed.to_tui_stats()
     }
 
+    /// Prompt-cache savings. Expiries need the conversation's previous
+    /// message, so a lone message never carries one.
+    #[inline]
+    pub fn prompt_cache(self) -> PromptCacheStats {
+        PromptCacheStats {
+            saved_cost: self.packed.saved_millicents() as f64 / 100_000.0,
+            ..PromptCacheStats::default()
+        }
+    }
+
     /// Hash a session_id string for comparison with stored session_hash.
     #[inline]
     pub fn hash_session_id(session_id: &str) -> SessionHash {
@@ -229,8 +266,10 @@ mod size_tests {
         };
         let date = CompactDate::from_parts(2025, 6, 15);
 
-        let packed = PackedStatsDate::pack(&stats, date);
+        let mut packed = PackedStatsDate::pack(&stats, date);
+        packed.set_hour(23);
 
+        assert_eq!(packed.hour(), 23);
         assert_eq!(packed.input_tokens(), 170_749);
         assert_eq!(packed.output_tokens(), 31_999);
         assert_eq!(packed.reasoning_tokens(), 7_005);
@@ -2,28 +2,41 @@
 
 use super::SessionHash;
 use crate::types::{
-    CompactDate, ConversationMessage, MessageRole, ModelCounts, TuiStats, intern_model,
+    CompactDate, ConversationMessage, MessageRole, ModelCounts, ModelStats, PromptCacheStats,
+    TuiStats, intern_model,
 };
+use chrono::{Local, Timelike};
+use tinyvec::TinyVec;
 
 // ============================================================================
 // SingleSessionContribution - For 1 file = 1 session analyzers
 // ============================================================================
 
 /// Contribution for single-session-per-file analyzers.
-/// Uses ~72 bytes instead of ~100+ bytes for full contributions.
+/// Uses ~200 bytes plus per-model stats instead of full daily and session
+/// aggregates.
 /// Designed for most analyzers where each file contains one conversation/session.
 #[derive(Debug, Clone)]
 pub struct SingleSessionContribution {
     /// Aggregated stats from all messages in this session
     pub stats: TuiStats,
+    /// Prompt-cache savings and expiries, kept next to `stats` so live
+    /// updates move them too
+    pub prompt_cache: PromptCacheStats,
     /// Primary date (date of first message)
     pub date: CompactDate,
     /// Models used in this session with reference counts
     pub models: ModelCounts,
+    /// Per-model tokens and cost for the day's model breakdown; the token
+    /// counts also let pricing edits re-cost the session without parsing it.
+    pub model_stats: Vec<ModelStats>,
     /// Hash of conversation_hash for session lookup
     pub session_hash: SessionHash,
     /// Number of AI messages (for daily_stats.ai_messages)
     pub ai_message_count: u32,
+    /// AI messages per local hour of day as `(hour, count)`; most sessions
+    /// span only an hour or two, so this rarely leaves inline storage.
+    pub hourly_messages: TinyVec<[(u8, u32); 2]>,
 }
 
 impl SingleSessionContribution {
@@ -31,9 +44,11 @@ impl SingleSessionContribution {
     pub fn from_messages(messages: &[ConversationMessage]) -> Self {
         let mut stats = TuiStats::default();
         let mut models = ModelCounts::new();
+        let mut model_stats: Vec<ModelStats> = Vec::new();
         let mut ai_message_count = 0u32;
         let mut first_date = CompactDate::default();
         let mut session_hash = SessionHash::default();
+        let mut hourly_messages: TinyVec<[(u8, u32); 2]> = TinyVec::new();
 
         for (i, msg) in messages.iter().enumerate() {
             if i == 0 {
@@ -45,18 +60,52 @@ impl SingleSessionContribution {
                 ai_message_count += 1;
                 stats += TuiStats::from(&msg.stats);
 
+                let hour = msg.date.with_timezone(&Local).hour() as u8;
+                match hourly_messages.iter_mut().find(|(h, _)| *h == hour) {
+                    Some((_, count)) => *count += 1,
+                    None => hourly_messages.push((hour, 1)),
+                }
+
                 if let Some(model) = &msg.model {
                     models.increment(intern_model(model), 1);
+                    let index = match model_stats.iter().position(|m| m.model == *model) {
+                        Some(index) => index,
+                        None => {
+                            model_stats.push(ModelStats::new(model.clone()));
+                            model_stats.len() - 1
+                        }
+                    };
+                    model_stats[index].add_message(&msg.stats);
+                    model_stats[index].saved_cost +=
+                        crate::utils::prompt_cache_stats(std::slice::from_ref(msg)).saved_cost;
                 }
             }
         }
 
+        stats.active_secs =
+            crate::stats::active::active_seconds(messages.iter().map(|msg| msg.date));
+
         Self {
             stats,
+            prompt_cache: crate::utils::prompt_cache_stats(messages),
             date: first_date,
             models,
+            model_stats,
             session_hash,
             ai_message_count,
+            hourly_messages,
         }
     }
+
+    /// Re-price the session's tokens at current rates, see
+    /// [`ModelStats::reprice`]. Returns the change in dollars.
+    pub fn reprice(&mut self, before: &crate::models::PricingSnapshot) -> f64 {
+        let change: f64 = self
+            .model_stats
+            .iter_mut()
+            .map(|model| model.reprice(before))
+            .sum();
+        self.stats.adjust_cost(change);
+        change
+    }
 }
@@ -65,6 +65,7 @@ pub fn make_message(
         },
         uuid: None,
         session_name: Some(format!("Session {}", session_id)),
+        stable_session_id: None,
     }
 }
 
@@ -85,11 +86,13 @@ pub fn make_view_with_session(analyzer_name: &str, session_id: &str) -> Analyzer
         daily_stats: BTreeMap::new(),
         session_aggregates: vec![SessionAggregate {
             session_id: session_id.to_string(),
+            stable_id: String::new(),
             first_timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
             analyzer_name: Arc::clone(&analyzer_name),
             stats: TuiStats::default(),
             models: crate::types::ModelCounts::new(),
             session_name: Some(format!("Session {}", session_id)),
+            project: None,
             date: CompactDate::from_str("2025-01-01").unwrap(),
         }],
         num_conversations: 0,
@@ -0,0 +1,92 @@
+//! Problems met while reading tool data: files that failed to parse and
+//! entries that were skipped. Besides going to stderr, where the TUI's
+//! alternate screen hides them, they're collected here for the TUI's
+//! diagnostics pane and `splitrail doctor`.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Everything that went wrong reading one file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseIssue {
+    pub analyzer: String,
+    pub path: PathBuf,
+    /// Distinct problems seen in the file; re-reading a file doesn't add to it.
+    pub count: usize,
+    pub last_error: String,
+}
+
+#[derive(Default)]
+struct FileIssues {
+    errors: BTreeSet<String>,
+    last_error: String,
+}
+
+type Issues = BTreeMap<(String, PathBuf), FileIssues>;
+
+fn issues_store() -> &'static Mutex<Issues> {
+    static ISSUES: OnceLock<Mutex<Issues>> = OnceLock::new();
+    ISSUES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Record a problem with one of an analyzer's files and warn about it once.
+pub fn parse_warning(analyzer: &str, path: &Path, message: impl Into<String>) {
+    let message = message.into();
+    {
+        let mut issues = issues_store().lock();
+        let entry = issues
+            .entry((analyzer.to_string(), path.to_path_buf()))
+            .or_default();
+        entry.errors.insert(message.clone());
+        entry.last_error = message.clone();
+    }
+    crate::utils::warn_once(message);
+}
+
+/// Files with problems, most problems first.
+pub fn parse_issues() -> Vec<ParseIssue> {
+    let mut issues: Vec<ParseIssue> = issues_store()
+        .lock()
+        .iter()
+        .map(|((analyzer, path), file)| ParseIssue {
+            analyzer: analyzer.clone(),
+            path: path.clone(),
+            count: file.errors.len(),
+            last_error: file.last_error.clone(),
+        })
+        .collect();
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.count));
+    issues
+}
+
+/// Number of files with problems, for the TUI's help line.
+pub fn parse_issue_count() -> usize {
+    issues_store().lock().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_warnings_are_counted_once_per_distinct_problem() {
+        let path = Path::new("/diagnostics-test/session.jsonl");
+        parse_warning("Test Tool", path, "line 3: expected value");
+        parse_warning("Test Tool", path, "line 9: expected value");
+        // Re-reading the file reports the same problem again.
+        parse_warning("Test Tool", path, "line 3: expected value");
+
+        let issue = parse_issues()
+            .into_iter()
+            .find(|issue| issue.path == path)
+            .expect("issue recorded");
+        assert_eq!(issue.analyzer, "Test Tool");
+        assert_eq!(issue.count, 2);
+        assert_eq!(issue.last_error, "line 3: expected value");
+        assert!(parse_issue_count() >= 1);
+    }
+}
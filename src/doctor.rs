@@ -0,0 +1,516 @@
+//! `splitrail doctor`: a checklist for "why isn't tool X showing up". Checks
+//! every analyzer's data directories and re-parses its files, then splitrail's
+//! own config, data directory, caches and upload server.
+
+use crate::analyzer::AnalyzerRegistry;
+use crate::config::Config;
+use crate::diagnostics::{self, ParseIssue};
+use anyhow::{Context, Result};
+use crossterm::style::Stylize;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long the upload server gets to answer.
+const SERVER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+    /// Nothing to check, e.g. a tool that isn't installed.
+    Skip,
+}
+
+impl CheckStatus {
+    fn symbol(self, color: bool) -> String {
+        let symbol = match self {
+            Self::Ok => "✓",
+            Self::Warn => "!",
+            Self::Fail => "✗",
+            Self::Skip => "-",
+        };
+        if !color {
+            return symbol.to_string();
+        }
+        match self {
+            Self::Ok => symbol.green().to_string(),
+            Self::Warn => symbol.yellow().to_string(),
+            Self::Fail => symbol.red().to_string(),
+            Self::Skip => symbol.dark_grey().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzerReport {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Directories the analyzer reads, whether or not they exist.
+    pub data_dirs: Vec<PathBuf>,
+    pub files: usize,
+    /// Files that parsed without any problems.
+    pub parsed_files: usize,
+    pub messages: usize,
+    pub conversations: u64,
+    /// Set when the analyzer as a whole failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AnalyzerReport {
+    fn summary(&self) -> String {
+        if let Some(error) = &self.error {
+            return format!("failed: {error}");
+        }
+        let dirs = || {
+            self.data_dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if self.files == 0 {
+            return match self.status {
+                CheckStatus::Skip if self.data_dirs.is_empty() => {
+                    "no data directory found".to_string()
+                }
+                CheckStatus::Skip => format!("not found in {}", dirs()),
+                _ => format!("no sessions found in {}", dirs()),
+            };
+        }
+        format!(
+            "{} files, {} parsed cleanly, {} messages in {} conversations",
+            self.files, self.parsed_files, self.messages, self.conversations
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnosis {
+    pub analyzers: Vec<AnalyzerReport>,
+    pub environment: Vec<Check>,
+    pub parse_issues: Vec<ParseIssue>,
+}
+
+impl Diagnosis {
+    /// Check and parse every analyzer in the registry, available or not.
+    /// Environment checks are left to the caller. Must be called within a
+    /// rayon threadpool context for parallelism.
+    pub fn collect(registry: &AnalyzerRegistry) -> Self {
+        let names = registry.display_names();
+        let parsed: Vec<_> = names
+            .par_iter()
+            .filter_map(|name| registry.get_analyzer_by_display_name(name))
+            .map(|analyzer| {
+                let data_dirs = analyzer.get_watch_directories();
+                let sources = analyzer.discover_data_sources();
+                let files = sources.as_ref().map_or(0, Vec::len);
+                let result = sources.and_then(|sources| {
+                    if sources.is_empty() {
+                        Ok(None)
+                    } else {
+                        analyzer.get_stats_with_sources(sources).map(Some)
+                    }
+                });
+                (analyzer.display_name(), data_dirs, files, result)
+            })
+            .collect();
+
+        let parse_issues: Vec<ParseIssue> = diagnostics::parse_issues()
+            .into_iter()
+            .filter(|issue| names.contains(&issue.analyzer.as_str()))
+            .collect();
+
+        let analyzers = parsed
+            .into_iter()
+            .map(|(name, data_dirs, files, result)| {
+                let problem_files = parse_issues
+                    .iter()
+                    .filter(|issue| issue.analyzer == name)
+                    .count();
+                let mut report = AnalyzerReport {
+                    name: name.to_string(),
+                    status: CheckStatus::Ok,
+                    data_dirs,
+                    files,
+                    parsed_files: files.saturating_sub(problem_files),
+                    messages: 0,
+                    conversations: 0,
+                    error: None,
+                };
+                match result {
+                    Ok(Some(stats)) => {
+                        report.messages = stats.messages.len();
+                        report.conversations = stats.num_conversations;
+                        if problem_files > 0 {
+                            report.status = CheckStatus::Warn;
+                        }
+                    }
+                    // A directory without sessions usually means the tool
+                    // moved its data; no directory means it isn't installed.
+                    Ok(None) if report.data_dirs.iter().any(|dir| dir.exists()) => {
+                        report.status = CheckStatus::Warn;
+                    }
+                    Ok(None) => report.status = CheckStatus::Skip,
+                    Err(e) => {
+                        report.status = CheckStatus::Fail;
+                        report.error = Some(format!("{e:#}"));
+                    }
+                }
+                report
+            })
+            .collect();
+
+        Self {
+            analyzers,
+            environment: Vec::new(),
+            parse_issues,
+        }
+    }
+
+    /// The checklist, with ANSI colors when `color` is set.
+    pub fn render(&self, color: bool) -> String {
+        let mut out = String::from("Tools\n");
+        for report in &self.analyzers {
+            let _ = writeln!(
+                out,
+                "  {} {}: {}",
+                report.status.symbol(color),
+                report.name,
+                report.summary()
+            );
+        }
+        if !self.environment.is_empty() {
+            out.push_str("\nEnvironment\n");
+        }
+        for check in &self.environment {
+            let _ = writeln!(
+                out,
+                "  {} {}: {}",
+                check.status.symbol(color),
+                check.name,
+                check.detail
+            );
+        }
+
+        if self.parse_issues.is_empty() {
+            out.push_str("\nEvery file parsed cleanly.\n");
+            return out;
+        }
+        let _ = writeln!(out, "\nFiles with problems ({}):", self.parse_issues.len());
+        for issue in &self.parse_issues {
+            let _ = writeln!(
+                out,
+                "  [{}] {} ({} {})",
+                issue.analyzer,
+                issue.path.display(),
+                issue.count,
+                if issue.count == 1 {
+                    "problem"
+                } else {
+                    "problems"
+                }
+            );
+            let _ = writeln!(out, "    {}", issue.last_error);
+        }
+        out
+    }
+}
+
+fn config_check() -> Check {
+    const NAME: &str = "Config";
+    let path = match Config::config_path() {
+        Ok(path) => path,
+        Err(e) => return Check::new(NAME, CheckStatus::Fail, format!("{e:#}")),
+    };
+    if !path.exists() {
+        return Check::new(
+            NAME,
+            CheckStatus::Skip,
+            format!("no file at {}; using defaults", path.display()),
+        );
+    }
+    match std::fs::read_to_string(&path) {
+        Ok(text) => match crate::config::validate_config_text(&text).as_slice() {
+            [] => Check::new(
+                NAME,
+                CheckStatus::Ok,
+                format!("{} is valid", path.display()),
+            ),
+            problems => Check::new(
+                NAME,
+                CheckStatus::Fail,
+                format!("{}: {}", path.display(), problems.join("; ")),
+            ),
+        },
+        Err(e) => Check::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("can't read {}: {e}", path.display()),
+        ),
+    }
+}
+
+fn data_dir_check() -> Check {
+    const NAME: &str = "Data directory";
+    let probe = |dir: &Path| -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let file = dir.join(".splitrail-doctor");
+        std::fs::write(&file, b"")?;
+        std::fs::remove_file(&file)?;
+        Ok(())
+    };
+    match crate::paths::data_dir() {
+        Ok(dir) => match probe(&dir) {
+            Ok(()) => Check::new(
+                NAME,
+                CheckStatus::Ok,
+                format!("{} is writable", dir.display()),
+            ),
+            Err(e) => Check::new(
+                NAME,
+                CheckStatus::Fail,
+                format!("{} isn't writable: {e:#}", dir.display()),
+            ),
+        },
+        Err(e) => Check::new(NAME, CheckStatus::Fail, format!("{e:#}")),
+    }
+}
+
+/// Open a SQLite file read-only and run its quick integrity check.
+fn sqlite_check(name: &str, path: Option<PathBuf>) -> Check {
+    let Some(path) = path.filter(|path| path.exists()) else {
+        return Check::new(name, CheckStatus::Skip, "not created yet");
+    };
+    let result =
+        rusqlite::Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|connection| {
+                connection.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0))
+            });
+    let size = std::fs::metadata(&path).map_or(0, |meta| meta.len());
+    match result {
+        Ok(verdict) if verdict == "ok" => Check::new(
+            name,
+            CheckStatus::Ok,
+            format!("{} ({:.1} MB)", path.display(), size as f64 / 1_048_576.0),
+        ),
+        Ok(verdict) => Check::new(
+            name,
+            CheckStatus::Fail,
+            format!("{} is damaged: {verdict}", path.display()),
+        ),
+        Err(e) => Check::new(
+            name,
+            CheckStatus::Fail,
+            format!("can't open {}: {e}", path.display()),
+        ),
+    }
+}
+
+fn pricing_cache_check() -> Check {
+    const NAME: &str = "Pricing cache";
+    match crate::models::remote::cache_path() {
+        Some(path) if path.exists() => match crate::models::remote::cached_summary() {
+            Some(summary) => Check::new(NAME, CheckStatus::Ok, summary),
+            None => Check::new(
+                NAME,
+                CheckStatus::Warn,
+                format!(
+                    "{} is unreadable; it will be downloaded again",
+                    path.display()
+                ),
+            ),
+        },
+        _ => Check::new(NAME, CheckStatus::Skip, "built-in prices only"),
+    }
+}
+
+async fn server_check(config: &Config) -> Check {
+    const NAME: &str = "Upload server";
+    let url = config.server.url.trim();
+    if url.is_empty() {
+        return Check::new(NAME, CheckStatus::Skip, "no server URL configured");
+    }
+    let started = Instant::now();
+    let request = crate::upload::get_http_client().get(url).send();
+    match tokio::time::timeout(SERVER_TIMEOUT, request).await {
+        Ok(Ok(response)) => {
+            let detail = format!(
+                "{url} answered HTTP {} in {} ms",
+                response.status().as_u16(),
+                started.elapsed().as_millis()
+            );
+            if config.is_api_token_missing() {
+                Check::new(
+                    NAME,
+                    CheckStatus::Warn,
+                    format!("{detail}, but no API token is set"),
+                )
+            } else {
+                Check::new(NAME, CheckStatus::Ok, detail)
+            }
+        }
+        Ok(Err(e)) => Check::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("{url} unreachable: {:#}", anyhow::Error::from(e)),
+        ),
+        Err(_) => Check::new(
+            NAME,
+            CheckStatus::Fail,
+            format!("{url} didn't answer within {}s", SERVER_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+pub async fn run(registry: &AnalyzerRegistry, output: Option<&Path>, json: bool) -> Result<()> {
+    use std::io::IsTerminal;
+
+    // Problems are collected for the report; don't also stream them to stderr.
+    crate::utils::set_log_level(crate::utils::LogLevel::Error);
+    let mut diagnosis = rayon::ThreadPoolBuilder::new()
+        .build()?
+        .install(|| Diagnosis::collect(registry));
+
+    let config = Config::load().ok().flatten().unwrap_or_default();
+    diagnosis.environment = vec![
+        config_check(),
+        data_dir_check(),
+        pricing_cache_check(),
+        sqlite_check(
+            "History store",
+            crate::analyzers::claude_code_history::history_path().ok(),
+        ),
+        sqlite_check(
+            "SQLite store",
+            crate::store::store_path().filter(|_| config.cache.sqlite_store),
+        ),
+        server_check(&config).await,
+    ];
+
+    let report = if json {
+        simd_json::to_string_pretty(&diagnosis)? + "\n"
+    } else {
+        diagnosis.render(output.is_none() && std::io::stdout().is_terminal())
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(path, report)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            if !json {
+                println!(
+                    "Wrote diagnosis to {} ({} files with problems)",
+                    path.display(),
+                    diagnosis.parse_issues.len()
+                );
+            }
+        }
+        None => print!("{report}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzers::ConfigurableAnalyzer;
+    use crate::config::CustomAnalyzerConfig;
+
+    fn custom_analyzer(name: &str, glob: String) -> ConfigurableAnalyzer {
+        ConfigurableAnalyzer::new(CustomAnalyzerConfig {
+            name: name.to_string(),
+            glob,
+            timestamp: "ts".to_string(),
+            model: None,
+            input_tokens: None,
+            output_tokens: None,
+            cost: None,
+            session: None,
+        })
+    }
+
+    #[test]
+    fn diagnosis_lists_files_with_skipped_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("good.jsonl"),
+            "{\"ts\": \"2025-06-01T10:00:00Z\"}\n",
+        )
+        .unwrap();
+        let bad = dir.path().join("bad.jsonl");
+        std::fs::write(&bad, "{\"ts\": \"2025-06-02T10:00:00Z\"}\nnot json\n").unwrap();
+
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(custom_analyzer(
+            "Doctor Test Agent",
+            format!("{}/*.jsonl", dir.path().display()),
+        ));
+
+        let diagnosis = Diagnosis::collect(&registry);
+        let report = &diagnosis.analyzers[0];
+        assert_eq!(report.status, CheckStatus::Warn);
+        assert_eq!((report.files, report.parsed_files), (2, 1));
+        assert_eq!(report.messages, 2);
+        assert_eq!(diagnosis.parse_issues.len(), 1);
+        assert_eq!(diagnosis.parse_issues[0].path, bad);
+        assert!(diagnosis.render(false).contains(
+            "! Doctor Test Agent: 2 files, 1 parsed cleanly, 2 messages in 2 conversations"
+        ));
+    }
+
+    #[test]
+    fn missing_and_empty_data_directories_are_told_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty");
+        std::fs::create_dir(&empty).unwrap();
+
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(custom_analyzer(
+            "Doctor Empty Agent",
+            format!("{}/*.jsonl", empty.display()),
+        ));
+        registry.register(custom_analyzer(
+            "Doctor Missing Agent",
+            format!("{}/missing/*.jsonl", dir.path().display()),
+        ));
+
+        let diagnosis = Diagnosis::collect(&registry);
+        let status = |name: &str| {
+            diagnosis
+                .analyzers
+                .iter()
+                .find(|report| report.name == name)
+                .map(|report| report.status)
+        };
+        assert_eq!(status("Doctor Empty Agent"), Some(CheckStatus::Warn));
+        assert_eq!(status("Doctor Missing Agent"), Some(CheckStatus::Skip));
+        assert!(diagnosis.render(false).contains(&format!(
+            "! Doctor Empty Agent: no sessions found in {}",
+            empty.display()
+        )));
+    }
+}
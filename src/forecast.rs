@@ -0,0 +1,218 @@
+//! End-of-month spend projected from the trailing daily average.
+//!
+//! The average covers the `window_days` full days before today, with days
+//! without usage counted as zero. A tool first used inside the window is
+//! averaged from its first day instead, so a week-old install isn't diluted
+//! by the days before it existed.
+
+use crate::budget::{ALL_TOOLS, spent_in, to_compact};
+use crate::types::{DateRange, SharedAnalyzerView};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+
+pub const DEFAULT_WINDOW_DAYS: u32 = 7;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    /// Tool name, or [`ALL_TOOLS`] for the total.
+    pub scope: String,
+    /// Days the average was actually taken over; shorter than the configured
+    /// window for tools with less history.
+    pub window_days: u32,
+    pub average_daily: f64,
+    pub month_to_date: f64,
+    /// Days of the month remaining after today.
+    pub days_left: u32,
+    pub projected: f64,
+}
+
+/// Forecast the total and then every tool with usage this month or inside
+/// the window, in view order.
+pub fn evaluate(views: &[SharedAnalyzerView], window_days: u32, today: NaiveDate) -> Vec<Forecast> {
+    let mut names: Vec<String> = Vec::new();
+    for view in views {
+        let name = view.read().analyzer_name.to_string();
+        if !names.iter().any(|known| known.eq_ignore_ascii_case(&name)) {
+            names.push(name);
+        }
+    }
+
+    let mut forecasts = vec![forecast(views, None, window_days, today)];
+    forecasts.extend(
+        names
+            .iter()
+            .map(|name| forecast(views, Some(name), window_days, today))
+            .filter(|forecast| forecast.month_to_date > 0.0 || forecast.average_daily > 0.0),
+    );
+    forecasts
+}
+
+fn forecast(
+    views: &[SharedAnalyzerView],
+    scope: Option<&str>,
+    window_days: u32,
+    today: NaiveDate,
+) -> Forecast {
+    let window_days = window_days.max(1);
+    let month_start = today.with_day(1).unwrap_or(today);
+    let window_start = today
+        .checked_sub_days(Days::new(window_days as u64))
+        .unwrap_or(today);
+    let start = first_day(views, scope).map_or(window_start, |first| first.max(window_start));
+    let spent_today = spent_in(views, scope, day_range(today, today));
+
+    // With no history before today, today is the only evidence there is.
+    let (days, average_daily) = match today.pred_opt().filter(|yesterday| start <= *yesterday) {
+        Some(yesterday) => {
+            let days = (yesterday - start).num_days() as u32 + 1;
+            let spent = spent_in(views, scope, day_range(start, yesterday));
+            (days, spent / days as f64)
+        }
+        None => (1, spent_today),
+    };
+
+    let month_to_date = spent_in(views, scope, day_range(month_start, today));
+    let days_left = days_in_month(today) - today.day();
+    // Today is still running, so it counts for at least an average day.
+    let projected =
+        month_to_date + (average_daily - spent_today).max(0.0) + average_daily * days_left as f64;
+
+    Forecast {
+        scope: scope.unwrap_or(ALL_TOOLS).to_string(),
+        window_days: days,
+        average_daily,
+        month_to_date,
+        days_left,
+        projected,
+    }
+}
+
+fn day_range(since: NaiveDate, until: NaiveDate) -> DateRange {
+    DateRange {
+        since: Some(to_compact(since)),
+        until: Some(to_compact(until)),
+    }
+}
+
+/// Earliest day with any spend for the scope.
+fn first_day(views: &[SharedAnalyzerView], scope: Option<&str>) -> Option<NaiveDate> {
+    views
+        .iter()
+        .filter_map(|view| {
+            let view = view.read();
+            if scope.is_some_and(|name| !view.analyzer_name.eq_ignore_ascii_case(name)) {
+                return None;
+            }
+            view.daily_stats
+                .values()
+                .filter(|day| day.stats.cost_cents > 0)
+                .filter_map(|day| {
+                    NaiveDate::from_ymd_opt(
+                        day.date.year() as i32,
+                        day.date.month() as u32,
+                        day.date.day() as u32,
+                    )
+                })
+                .min()
+        })
+        .min()
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next| next.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AnalyzerStatsView, CompactDate, DailyStats, TuiStats};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    fn view(name: &str, days: &[(&str, u32)]) -> SharedAnalyzerView {
+        let daily_stats = days
+            .iter()
+            .map(|(date, cost_cents)| {
+                (
+                    date.to_string(),
+                    DailyStats {
+                        date: CompactDate::from_str(date).unwrap(),
+                        stats: TuiStats {
+                            cost_cents: *cost_cents,
+                            ..TuiStats::default()
+                        },
+                        ..DailyStats::default()
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        Arc::new(parking_lot::RwLock::new(AnalyzerStatsView {
+            daily_stats,
+            session_aggregates: Vec::new(),
+            num_conversations: 0,
+            analyzer_name: Arc::from(name),
+        }))
+    }
+
+    #[test]
+    fn averages_the_window_counting_idle_days_as_zero() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 20).unwrap();
+        let views = vec![
+            view(
+                "Claude Code",
+                &[
+                    ("2025-05-01", 100),
+                    ("2025-06-01", 2000),
+                    ("2025-06-14", 1400),
+                    ("2025-06-19", 700),
+                    ("2025-06-20", 100),
+                ],
+            ),
+            // First used inside the window: averaged over its own three days.
+            view("Codex CLI", &[("2025-06-17", 900)]),
+            view("Gemini CLI", &[("2025-04-01", 500)]),
+        ];
+
+        let forecasts = evaluate(&views, 7, today);
+
+        assert_eq!(forecasts.len(), 3);
+        let total = &forecasts[0];
+        assert_eq!(total.scope, ALL_TOOLS);
+        assert_eq!(total.window_days, 7);
+        assert_eq!(total.average_daily, 30.0 / 7.0);
+        assert_eq!(total.month_to_date, 51.0);
+        assert_eq!(total.days_left, 10);
+        let average = 30.0 / 7.0;
+        assert_eq!(total.projected, 51.0 + (average - 1.0) + average * 10.0);
+
+        let claude = &forecasts[1];
+        assert_eq!(claude.scope, "Claude Code");
+        assert_eq!(claude.average_daily, 3.0);
+        assert_eq!(claude.projected, 42.0 + 2.0 + 30.0);
+
+        let codex = &forecasts[2];
+        assert_eq!(codex.window_days, 3);
+        assert_eq!(codex.average_daily, 3.0);
+        assert_eq!(codex.projected, 9.0 + 3.0 + 30.0);
+    }
+
+    #[test]
+    fn first_day_of_use_projects_from_today() {
+        let today = NaiveDate::from_ymd_opt(2025, 2, 27).unwrap();
+        let views = vec![view("Claude Code", &[("2025-02-27", 500)])];
+
+        let forecasts = evaluate(&views, 30, today);
+
+        assert_eq!(forecasts[0].window_days, 1);
+        assert_eq!(forecasts[0].days_left, 1);
+        assert_eq!(forecasts[0].projected, 10.0);
+    }
+}
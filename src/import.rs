@@ -0,0 +1,368 @@
+//! `splitrail import`: convert history from other usage trackers into
+//! records the Imported analyzer reads.
+//!
+//! Each import is normalized into [`ImportedRow`]s and written to
+//! `imports/<kind>-<name>.jsonl` in the data directory, so importing a newer
+//! export of the same file replaces the older one. Records are deduplicated
+//! across files too, so overlapping exports don't count twice.
+
+use crate::analyzers::imported::{ImportedRow, imports_dir};
+use crate::types::CompactDate;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    /// `ccusage daily|weekly|monthly|session --json` output.
+    Ccusage,
+    /// The generic format: CSV with a header row, a JSON array, or JSONL,
+    /// with [`ImportedRow`]'s field names.
+    Generic,
+}
+
+impl ImportKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Ccusage => "ccusage",
+            Self::Generic => "file",
+        }
+    }
+}
+
+/// What an import wrote.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub path: PathBuf,
+    pub records: usize,
+    pub days: usize,
+    pub cost: f64,
+    pub first_day: Option<String>,
+    pub last_day: Option<String>,
+    /// Records after `--until` that were left out.
+    pub skipped: usize,
+}
+
+/// Import `source` (`-` for stdin), keeping records up to and including
+/// `until`.
+pub fn run(kind: ImportKind, source: &Path, until: Option<CompactDate>) -> Result<ImportSummary> {
+    let mut bytes = if source == Path::new("-") {
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut bytes)
+            .context("Failed to read stdin")?;
+        bytes
+    } else {
+        std::fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?
+    };
+    let rows = match kind {
+        ImportKind::Ccusage => from_ccusage(&mut bytes)?,
+        ImportKind::Generic if looks_like_json(&bytes) => from_json(&mut bytes)?,
+        ImportKind::Generic => from_csv(&String::from_utf8_lossy(&bytes))?,
+    };
+
+    let mut summary = ImportSummary::default();
+    let mut days = BTreeSet::new();
+    let mut lines = String::new();
+    for row in rows {
+        let message = row
+            .to_message()
+            .with_context(|| format!("Invalid date '{}'", row.date))?;
+        let day = CompactDate::from_local(&message.date);
+        if until.is_some_and(|until| day > until) {
+            summary.skipped += 1;
+            continue;
+        }
+        summary.records += 1;
+        summary.cost += message.stats.cost;
+        days.insert(day);
+        lines.push_str(&simd_json::to_string(&row)?);
+        lines.push('\n');
+    }
+    if summary.records == 0 {
+        bail!("No usage records to import from {}", source.display());
+    }
+    summary.days = days.len();
+    summary.first_day = days.first().map(ToString::to_string);
+    summary.last_day = days.last().map(ToString::to_string);
+
+    let name = match source.file_stem() {
+        Some(stem) if source != Path::new("-") => sanitize(&stem.to_string_lossy()),
+        _ => "stdin".to_string(),
+    };
+    summary.path = imports_dir()?.join(format!("{}-{name}.jsonl", kind.name()));
+    crate::persist::write_atomic(&summary.path, lines, "import")?;
+    Ok(summary)
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn looks_like_json(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| matches!(b, b'[' | b'{'))
+}
+
+/// Period entries in each of ccusage's reports, and the field naming their
+/// period.
+const CCUSAGE_REPORTS: &[(&str, &str)] = &[
+    ("daily", "date"),
+    ("weekly", "week"),
+    ("monthly", "month"),
+    ("sessions", "lastActivity"),
+];
+
+/// Rows from a ccusage JSON report: one per model per period, or one per
+/// period when it has no per-model breakdown.
+pub fn from_ccusage(bytes: &mut [u8]) -> Result<Vec<ImportedRow>> {
+    let value = simd_json::to_owned_value(bytes).context("ccusage output is not valid JSON")?;
+    let Some((entries, date_key)) = CCUSAGE_REPORTS.iter().find_map(|(report, date_key)| {
+        value
+            .get(*report)
+            .and_then(|entries| entries.as_array())
+            .map(|entries| (entries, *date_key))
+    }) else {
+        bail!(
+            "Unrecognized ccusage output; expected the JSON from `ccusage daily --json` \
+             (or weekly, monthly or session)"
+        );
+    };
+
+    let mut rows = Vec::new();
+    for entry in entries {
+        let Some(period) = entry.get(date_key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        // Monthly reports name the month only.
+        let date = if period.len() == 7 {
+            format!("{period}-01")
+        } else {
+            period.to_string()
+        };
+        let base = ImportedRow {
+            date,
+            tool: Some("ccusage".to_string()),
+            session: entry
+                .get("sessionId")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            ..ImportedRow::default()
+        };
+
+        let breakdowns = entry
+            .get("modelBreakdowns")
+            .and_then(|v| v.as_array())
+            .filter(|breakdowns| !breakdowns.is_empty());
+        match breakdowns {
+            Some(breakdowns) => rows.extend(breakdowns.iter().map(|breakdown| {
+                ImportedRow {
+                    model: breakdown
+                        .get("modelName")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                    cost: breakdown.get("cost").and_then(|v| v.cast_f64()),
+                    ..ccusage_tokens(breakdown, base.clone())
+                }
+            })),
+            None => {
+                let models = entry.get("modelsUsed").and_then(|v| v.as_array());
+                rows.push(ImportedRow {
+                    model: models
+                        .filter(|models| models.len() == 1)
+                        .and_then(|models| models[0].as_str())
+                        .map(str::to_string),
+                    cost: entry.get("totalCost").and_then(|v| v.cast_f64()),
+                    ..ccusage_tokens(entry, base.clone())
+                });
+            }
+        }
+    }
+    Ok(rows)
+}
+
+fn ccusage_tokens(value: &OwnedValue, row: ImportedRow) -> ImportedRow {
+    let tokens = |key: &str| value.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    ImportedRow {
+        input_tokens: tokens("inputTokens"),
+        output_tokens: tokens("outputTokens"),
+        cache_creation_tokens: tokens("cacheCreationTokens"),
+        cache_read_tokens: tokens("cacheReadTokens"),
+        ..row
+    }
+}
+
+/// Rows from a JSON array of records or from JSONL.
+pub fn from_json(bytes: &mut [u8]) -> Result<Vec<ImportedRow>> {
+    let first = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    if first.is_some_and(|i| bytes[i] == b'[') {
+        return simd_json::serde::from_slice(bytes).context("Invalid JSON import");
+    }
+    bytes
+        .split_mut(|&b| b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.iter().all(|b| b.is_ascii_whitespace()))
+        .map(|(i, line)| {
+            simd_json::serde::from_slice(line)
+                .with_context(|| format!("Invalid JSON import on line {}", i + 1))
+        })
+        .collect()
+}
+
+/// Rows from CSV with a header row naming [`ImportedRow`]'s fields. Other
+/// columns are ignored.
+pub fn from_csv(text: &str) -> Result<Vec<ImportedRow>> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let columns: Vec<String> = split_csv_line(header)
+        .into_iter()
+        .map(|column| column.trim().to_lowercase())
+        .collect();
+    if !columns.iter().any(|c| c == "date" || c == "timestamp") {
+        bail!("CSV import needs a 'date' column");
+    }
+
+    let mut rows = Vec::new();
+    for (i, line) in lines {
+        let mut row = ImportedRow::default();
+        for (column, value) in columns.iter().zip(split_csv_line(line)) {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid {column} '{value}' on line {}", i + 1))
+            };
+            match column.as_str() {
+                "date" | "timestamp" => row.date = value.to_string(),
+                "tool" => row.tool = Some(value.to_string()),
+                "model" => row.model = Some(value.to_string()),
+                "session" => row.session = Some(value.to_string()),
+                "input_tokens" => row.input_tokens = number()?,
+                "output_tokens" => row.output_tokens = number()?,
+                "cache_creation_tokens" => row.cache_creation_tokens = number()?,
+                "cache_read_tokens" => row.cache_read_tokens = number()?,
+                "cost" => {
+                    row.cost = Some(
+                        value
+                            .trim_start_matches('$')
+                            .parse()
+                            .with_context(|| format!("Invalid cost '{value}' on line {}", i + 1))?,
+                    )
+                }
+                _ => {}
+            }
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Split one CSV line, honoring double-quoted fields with `""` escapes.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ccusage_daily_reports_split_by_model() {
+        let mut json = br#"{
+            "daily": [
+                {"date": "2025-06-01", "inputTokens": 30, "outputTokens": 300, "totalCost": 1.5,
+                 "modelsUsed": ["claude-sonnet-4-20250514", "claude-opus-4-20250514"],
+                 "modelBreakdowns": [
+                    {"modelName": "claude-sonnet-4-20250514", "inputTokens": 10, "outputTokens": 100,
+                     "cacheCreationTokens": 5, "cacheReadTokens": 50, "cost": 0.5},
+                    {"modelName": "claude-opus-4-20250514", "inputTokens": 20, "outputTokens": 200,
+                     "cacheCreationTokens": 0, "cacheReadTokens": 0, "cost": 1.0}
+                 ]},
+                {"date": "2025-06-02", "inputTokens": 7, "outputTokens": 8, "totalCost": 0.25,
+                 "modelsUsed": ["claude-sonnet-4-20250514"]}
+            ],
+            "totals": {}
+        }"#
+        .to_vec();
+        let rows = from_ccusage(&mut json).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].model.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(rows[0].cache_read_tokens, 50);
+        assert_eq!(rows[1].cost, Some(1.0));
+        assert_eq!(rows[2].date, "2025-06-02");
+        assert_eq!(rows[2].model.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(rows[2].input_tokens, 7);
+
+        let mut monthly = br#"{"monthly": [{"month": "2025-05", "totalCost": 3.0}]}"#.to_vec();
+        assert_eq!(from_ccusage(&mut monthly).unwrap()[0].date, "2025-05-01");
+        assert!(from_ccusage(&mut b"{\"blocks\": []}".to_vec()).is_err());
+    }
+
+    #[test]
+    fn generic_csv_and_json_share_field_names() {
+        let csv = "date,model,input_tokens,output_tokens,cost,notes\n\
+                   2025-06-01,gpt-5,100,20,$0.50,\"first, day\"\n\
+                   \n\
+                   2025-06-02T10:00:00Z,gpt-5,1,2,,\n";
+        let rows = from_csv(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].cost, Some(0.5));
+        assert_eq!(rows[1].cost, None);
+        assert_eq!(rows[1].output_tokens, 2);
+        assert!(from_csv("date,input_tokens\n2025-06-01,lots\n").is_err());
+
+        let mut jsonl = br#"{"date": "2025-06-01", "model": "gpt-5", "input_tokens": 100}
+{"date": "2025-06-02", "inputTokens": 5}"#
+            .to_vec();
+        let rows = from_json(&mut jsonl).unwrap();
+        assert_eq!(rows[0].input_tokens, 100);
+        assert_eq!(rows[1].input_tokens, 5);
+        let mut array = br#"[{"date": "2025-06-01", "cost": 2}]"#.to_vec();
+        assert_eq!(from_json(&mut array).unwrap()[0].cost, Some(2.0));
+    }
+
+    #[test]
+    fn split_csv_line_handles_quotes() {
+        assert_eq!(
+            split_csv_line(r#"a,"b, c","say ""hi""",,"#),
+            ["a", "b, c", r#"say "hi""#, "", ""]
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Spend-reduction suggestions for `splitrail report insights`.
+//!
+//! Each rule looks at one aspect of a [`UsageSummary`] (cache hit rate, model
+//! mix, reasoning share, expensive sessions) and returns an [`Insight`] only
+//! when the data clearly supports it. Rules stay quiet below [`MIN_SPEND`] so
+//! that light usage doesn't produce noise.
+
+use crate::models::Provider;
+use crate::tui::logic::aggregate_model_stats;
+use crate::types::{
+    DateRange, ModelStats, PromptCacheStats, SharedAnalyzerView, TuiStats, resolve_model,
+};
+use serde::Serialize;
+
+/// Dollars a rule needs to be looking at before it says anything.
+const MIN_SPEND: f64 = 5.0;
+/// Sessions with at most this many assistant replies count as quick tasks.
+const SHORT_SESSION_MESSAGES: u32 = 5;
+const SHORT_SESSION_SHARE: f64 = 0.25;
+const LOW_CACHE_HIT_RATE: f64 = 0.5;
+/// Input plus cached tokens a tool needs before its hit rate is meaningful.
+const MIN_CACHEABLE_TOKENS: u64 = 1_000_000;
+const MIN_EXPIRY_COST: f64 = 1.0;
+const MIN_EXPIRY_MISSES: u32 = 3;
+const HIGH_REASONING_SHARE: f64 = 0.6;
+const TOP_SESSIONS: usize = 5;
+const TOP_SESSIONS_SHARE: f64 = 0.4;
+
+/// Premium model families and the cheaper tier to suggest instead.
+const PREMIUM_FAMILIES: &[(&str, &str, &str)] = &[
+    ("opus", "Opus", "Sonnet"),
+    ("-pro", "Pro-tier model", "a standard-tier model"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Insight {
+    pub rule: &'static str,
+    pub message: String,
+    /// Dollars the suggestion concerns; used to put the biggest wins first.
+    pub impact: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolUsage {
+    pub name: String,
+    pub stats: TuiStats,
+    pub prompt_cache: PromptCacheStats,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub analyzer: String,
+    pub name: String,
+    pub cost: f64,
+    pub messages: u32,
+    /// Model with the most replies in the session.
+    pub model: String,
+}
+
+/// Everything the rules look at, already restricted to the report's dates.
+#[derive(Debug, Clone, Default)]
+pub struct UsageSummary {
+    pub tools: Vec<ToolUsage>,
+    pub models: Vec<ModelStats>,
+    pub sessions: Vec<SessionSummary>,
+    pub total_cost: f64,
+    pub currency_symbol: String,
+}
+
+impl UsageSummary {
+    pub fn from_views(
+        views: &[SharedAnalyzerView],
+        range: DateRange,
+        currency_symbol: &str,
+    ) -> Self {
+        let mut summary = Self {
+            currency_symbol: currency_symbol.to_string(),
+            ..Self::default()
+        };
+        let mut days = Vec::new();
+        for view in views {
+            let view = view.read().within(range);
+            let mut tool = ToolUsage {
+                name: view.analyzer_name.to_string(),
+                ..ToolUsage::default()
+            };
+            for day in view.daily_stats.values() {
+                tool.stats += day.stats;
+                tool.prompt_cache += day.prompt_cache;
+            }
+            summary.total_cost += tool.stats.cost();
+            summary.tools.push(tool);
+
+            summary
+                .sessions
+                .extend(view.session_aggregates.iter().map(|session| {
+                    let model = session
+                        .models
+                        .iter()
+                        .max_by_key(|(_, count)| *count)
+                        .map(|(key, _)| resolve_model(*key).to_string())
+                        .unwrap_or_default();
+                    SessionSummary {
+                        analyzer: view.analyzer_name.to_string(),
+                        name: session
+                            .session_name
+                            .clone()
+                            .unwrap_or_else(|| session.session_id.clone()),
+                        cost: session.stats.cost(),
+                        messages: session.models.iter().map(|(_, count)| *count).sum(),
+                        model,
+                    }
+                }));
+            days.extend(view.daily_stats.into_values());
+        }
+        summary.models = aggregate_model_stats(&days);
+        summary
+    }
+
+    fn money(&self, amount: f64) -> String {
+        format!("{}{amount:.2}", self.currency_symbol)
+    }
+}
+
+type Rule = fn(&UsageSummary) -> Vec<Insight>;
+
+const RULES: &[Rule] = &[
+    premium_models_on_quick_tasks,
+    low_cache_hit_rate,
+    cache_expiry_losses,
+    reasoning_heavy_models,
+    concentrated_session_spend,
+];
+
+/// Run every rule, biggest dollar impact first.
+pub fn evaluate(summary: &UsageSummary) -> Vec<Insight> {
+    let mut insights: Vec<Insight> = RULES.iter().flat_map(|rule| rule(summary)).collect();
+    insights.sort_by(|a, b| b.impact.total_cmp(&a.impact));
+    insights
+}
+
+fn percent(fraction: f64) -> String {
+    format!("{:.0}%", fraction * 100.0)
+}
+
+fn premium_models_on_quick_tasks(summary: &UsageSummary) -> Vec<Insight> {
+    PREMIUM_FAMILIES
+        .iter()
+        .filter_map(|(needle, family, alternative)| {
+            let sessions = summary
+                .sessions
+                .iter()
+                .filter(|session| session.model.to_lowercase().contains(needle));
+            let (mut spend, mut short_spend) = (0.0, 0.0);
+            for session in sessions {
+                spend += session.cost;
+                if session.messages <= SHORT_SESSION_MESSAGES {
+                    short_spend += session.cost;
+                }
+            }
+            if spend < MIN_SPEND || short_spend / spend < SHORT_SESSION_SHARE {
+                return None;
+            }
+            Some(Insight {
+                rule: "premium-models-on-quick-tasks",
+                message: format!(
+                    "{} of {family} spend ({}) was in sessions with {SHORT_SESSION_MESSAGES} or fewer replies. Consider {alternative} for quick tasks.",
+                    percent(short_spend / spend),
+                    summary.money(short_spend),
+                ),
+                impact: short_spend,
+            })
+        })
+        .collect()
+}
+
+fn low_cache_hit_rate(summary: &UsageSummary) -> Vec<Insight> {
+    summary
+        .tools
+        .iter()
+        .filter_map(|tool| {
+            let cacheable = tool.stats.input_tokens + tool.stats.cached_tokens;
+            if cacheable < MIN_CACHEABLE_TOKENS || tool.stats.cost() < MIN_SPEND {
+                return None;
+            }
+            let hit_rate = tool.stats.cached_tokens as f64 / cacheable as f64;
+            (hit_rate < LOW_CACHE_HIT_RATE).then(|| Insight {
+                rule: "low-cache-hit-rate",
+                message: format!(
+                    "Only {} of {}'s input tokens were served from the prompt cache. Keeping system prompts and tool lists stable, and not editing early context mid-session, makes more of each request cacheable.",
+                    percent(hit_rate),
+                    tool.name
+                ),
+                impact: tool.stats.cost() * (LOW_CACHE_HIT_RATE - hit_rate),
+            })
+        })
+        .collect()
+}
+
+fn cache_expiry_losses(summary: &UsageSummary) -> Vec<Insight> {
+    let mut total = PromptCacheStats::default();
+    for tool in &summary.tools {
+        total += tool.prompt_cache;
+    }
+    if total.expiry_cost < MIN_EXPIRY_COST || total.expiry_misses < MIN_EXPIRY_MISSES {
+        return Vec::new();
+    }
+    vec![Insight {
+        rule: "cache-expiry-losses",
+        message: format!(
+            "Prompt caches expired {} times after idle gaps, costing {} to re-write. Resume paused sessions before the cache TTL runs out, or start a fresh session instead.",
+            total.expiry_misses,
+            summary.money(total.expiry_cost)
+        ),
+        impact: total.expiry_cost,
+    }]
+}
+
+fn reasoning_heavy_models(summary: &UsageSummary) -> Vec<Insight> {
+    summary
+        .models
+        .iter()
+        .filter_map(|model| {
+            let generated = model.output_tokens + model.reasoning_tokens;
+            if model.cost < MIN_SPEND || generated == 0 {
+                return None;
+            }
+            let share = model.reasoning_tokens as f64 / generated as f64;
+            (share >= HIGH_REASONING_SHARE).then(|| Insight {
+                rule: "reasoning-heavy-model",
+                message: format!(
+                    "{} of {}'s generated tokens were reasoning. A lower reasoning effort is often enough for routine edits{}.",
+                    percent(share),
+                    model.model,
+                    match Provider::for_model(&model.model) {
+                        Provider::Anthropic => " (or disable extended thinking)",
+                        _ => "",
+                    }
+                ),
+                impact: model.cost * share,
+            })
+        })
+        .collect()
+}
+
+fn concentrated_session_spend(summary: &UsageSummary) -> Vec<Insight> {
+    // With fewer sessions the top few make up most of the spend anyway.
+    if summary.sessions.len() < TOP_SESSIONS * 4 || summary.total_cost < MIN_SPEND {
+        return Vec::new();
+    }
+    let mut sessions: Vec<&SessionSummary> = summary.sessions.iter().collect();
+    sessions.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    let top_spend: f64 = sessions.iter().take(TOP_SESSIONS).map(|s| s.cost).sum();
+    let share = top_spend / summary.total_cost;
+    if share < TOP_SESSIONS_SHARE {
+        return Vec::new();
+    }
+    let largest = sessions[0];
+    vec![Insight {
+        rule: "concentrated-session-spend",
+        message: format!(
+            "Your {TOP_SESSIONS} most expensive sessions were {} of spend (largest: {} in {} \"{}\"). Long sessions re-send their whole context every turn, so compacting or starting fresh sooner keeps each reply cheaper.",
+            percent(share),
+            summary.money(largest.cost),
+            largest.analyzer,
+            largest.name
+        ),
+        impact: top_spend,
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> UsageSummary {
+        UsageSummary {
+            currency_symbol: "$".to_string(),
+            ..UsageSummary::default()
+        }
+    }
+
+    fn session(model: &str, cost: f64, messages: u32) -> SessionSummary {
+        SessionSummary {
+            analyzer: "Claude Code".to_string(),
+            name: format!("{model} session"),
+            cost,
+            messages,
+            model: model.to_string(),
+        }
+    }
+
+    fn tool(cost_cents: u32, input: u64, cached: u64) -> ToolUsage {
+        ToolUsage {
+            name: "Codex CLI".to_string(),
+            stats: TuiStats {
+                input_tokens: input,
+                cached_tokens: cached,
+                cost_cents,
+                ..TuiStats::default()
+            },
+            prompt_cache: PromptCacheStats::default(),
+        }
+    }
+
+    #[test]
+    fn flags_premium_spend_on_short_sessions() {
+        let mut usage = summary();
+        usage.sessions = vec![
+            session("claude-opus-4-1", 6.0, 2),
+            session("claude-opus-4-1", 4.0, 40),
+            session("claude-sonnet-4-5", 50.0, 1),
+        ];
+
+        let insights = premium_models_on_quick_tasks(&usage);
+        assert_eq!(insights.len(), 1);
+        assert_eq!(
+            insights[0].message,
+            "60% of Opus spend ($6.00) was in sessions with 5 or fewer replies. Consider Sonnet for quick tasks."
+        );
+
+        usage.sessions[0].messages = 30;
+        assert!(premium_models_on_quick_tasks(&usage).is_empty());
+    }
+
+    #[test]
+    fn flags_tools_with_poor_cache_reuse() {
+        let mut usage = summary();
+        usage.tools = vec![tool(2000, 3_000_000, 1_000_000)];
+        let insights = low_cache_hit_rate(&usage);
+        assert_eq!(insights.len(), 1);
+        assert!(insights[0].message.starts_with("Only 25% of Codex CLI's"));
+
+        usage.tools = vec![tool(2000, 1_000_000, 3_000_000)];
+        assert!(low_cache_hit_rate(&usage).is_empty());
+        // Too little usage to judge.
+        usage.tools = vec![tool(100, 3_000_000, 0)];
+        assert!(low_cache_hit_rate(&usage).is_empty());
+    }
+
+    #[test]
+    fn flags_repeated_cache_expiry() {
+        let mut usage = summary();
+        let mut expired = tool(0, 0, 0);
+        expired.prompt_cache = PromptCacheStats {
+            expiry_misses: 4,
+            expiry_cost: 2.5,
+            ..PromptCacheStats::default()
+        };
+        usage.tools = vec![expired.clone()];
+        let insights = cache_expiry_losses(&usage);
+        assert_eq!(insights.len(), 1);
+        assert!(insights[0].message.contains("expired 4 times"));
+        assert!(insights[0].message.contains("$2.50"));
+
+        expired.prompt_cache.expiry_misses = 1;
+        usage.tools = vec![expired];
+        assert!(cache_expiry_losses(&usage).is_empty());
+    }
+
+    #[test]
+    fn flags_models_dominated_by_reasoning() {
+        let mut usage = summary();
+        usage.models = vec![
+            ModelStats {
+                cost: 20.0,
+                output_tokens: 200,
+                reasoning_tokens: 800,
+                ..ModelStats::new("gpt-5".to_string())
+            },
+            ModelStats {
+                cost: 20.0,
+                output_tokens: 800,
+                reasoning_tokens: 200,
+                ..ModelStats::new("claude-sonnet-4-5".to_string())
+            },
+        ];
+        let insights = reasoning_heavy_models(&usage);
+        assert_eq!(insights.len(), 1);
+        assert!(insights[0].message.starts_with("80% of gpt-5's"));
+        assert!((insights[0].impact - 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flags_spend_concentrated_in_a_few_sessions() {
+        let mut usage = summary();
+        usage.sessions = (0..20)
+            .map(|i| session("claude-sonnet-4-5", if i < 2 { 30.0 } else { 2.0 }, 50))
+            .collect();
+        usage.total_cost = usage.sessions.iter().map(|s| s.cost).sum();
+
+        let insights = concentrated_session_spend(&usage);
+        assert_eq!(insights.len(), 1);
+        assert!(
+            insights[0]
+                .message
+                .starts_with("Your 5 most expensive sessions were 69% of spend (largest: $30.00")
+        );
+
+        for session in &mut usage.sessions {
+            session.cost = 5.0;
+        }
+        usage.total_cost = 100.0;
+        assert!(concentrated_session_spend(&usage).is_empty());
+    }
+
+    #[test]
+    fn evaluate_orders_by_impact() {
+        let mut usage = summary();
+        usage.sessions = vec![session("claude-opus-4-1", 8.0, 1)];
+        let mut expired = tool(0, 0, 0);
+        expired.prompt_cache = PromptCacheStats {
+            expiry_misses: 5,
+            expiry_cost: 12.0,
+            ..PromptCacheStats::default()
+        };
+        usage.tools = vec![expired];
+
+        let rules: Vec<&str> = evaluate(&usage).iter().map(|i| i.rule).collect();
+        assert_eq!(
+            rules,
+            vec!["cache-expiry-losses", "premium-models-on-quick-tasks"]
+        );
+    }
+}
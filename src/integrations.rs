@@ -0,0 +1,3 @@
+//! Opt-in integrations that relate usage to data outside the agent tools.
+
+pub mod git;
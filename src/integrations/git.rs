@@ -0,0 +1,265 @@
+//! Commits landed while agent sessions were running, per repository.
+//!
+//! Claude Code records the working directory on every transcript line. Each
+//! transcript is one session spanning its first to last message; a commit
+//! in that directory's repository counts for the session when its committer
+//! time falls inside the session or within [`GRACE_MINUTES`] after it, since
+//! the commit often comes right after the agent finishes. Commits are read
+//! from `HEAD` by shelling out to `git`, so only history merged into the
+//! checked-out branch counts.
+
+use crate::analyzer::AnalyzerRegistry;
+use crate::types::{CompactDate, DateRange};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Serialize;
+use simd_json::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const GRACE_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentSession {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub time: DateTime<Utc>,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectGitStats {
+    /// Top-level directory of the repository.
+    pub repository: String,
+    pub sessions: usize,
+    pub cost: f64,
+    pub commits: usize,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+    pub cost_per_commit: Option<f64>,
+    /// Cost per thousand lines added or deleted.
+    pub cost_per_kloc: Option<f64>,
+}
+
+/// Claude Code sessions started inside `range`, with per-repository commit
+/// counts, most expensive repository first. Sessions outside a git
+/// repository are left out.
+pub fn report(registry: &AnalyzerRegistry, range: DateRange) -> Result<Vec<ProjectGitStats>> {
+    let Some(analyzer) = registry.get_analyzer_by_display_name("Claude Code") else {
+        return Ok(Vec::new());
+    };
+    let sources = analyzer.discover_data_sources()?;
+    let pool = rayon::ThreadPoolBuilder::new().build()?;
+    let parsed = pool.install(|| analyzer.parse_sources_parallel_with_paths(&sources));
+
+    let mut by_repository: BTreeMap<PathBuf, Vec<AgentSession>> = BTreeMap::new();
+    let mut roots: BTreeMap<PathBuf, Option<PathBuf>> = BTreeMap::new();
+    for (path, messages) in parsed {
+        let (Some(start), Some(end)) = (
+            messages.iter().map(|m| m.date).min(),
+            messages.iter().map(|m| m.date).max(),
+        ) else {
+            continue;
+        };
+        if !range.contains(CompactDate::from_local(&start.with_timezone(&Local))) {
+            continue;
+        }
+        let Some(cwd) = crate::utils::transcript_cwd(&path) else {
+            continue;
+        };
+        let root = roots
+            .entry(cwd.clone())
+            .or_insert_with(|| repository_root(&cwd))
+            .clone();
+        let Some(root) = root else {
+            continue;
+        };
+        by_repository.entry(root).or_default().push(AgentSession {
+            start,
+            end,
+            cost: messages.iter().map(|m| m.stats.cost).sum(),
+        });
+    }
+
+    let mut projects = Vec::new();
+    for (root, sessions) in by_repository {
+        let since = sessions.iter().map(|s| s.start).min().unwrap_or_default();
+        let until = sessions.iter().map(|s| s.end).max().unwrap_or_default()
+            + Duration::minutes(GRACE_MINUTES);
+        let commits = commits_between(&root, since, until)
+            .with_context(|| format!("Failed to read git history of {}", root.display()))?;
+        projects.push(attribute(&root.display().to_string(), &sessions, &commits));
+    }
+    projects.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    Ok(projects)
+}
+
+/// Totals for one repository: every session's cost and the commits that
+/// landed during any of them.
+pub fn attribute(
+    repository: &str,
+    sessions: &[AgentSession],
+    commits: &[Commit],
+) -> ProjectGitStats {
+    let grace = Duration::minutes(GRACE_MINUTES);
+    let landed: Vec<&Commit> = commits
+        .iter()
+        .filter(|commit| {
+            sessions
+                .iter()
+                .any(|session| session.start <= commit.time && commit.time <= session.end + grace)
+        })
+        .collect();
+    let cost: f64 = sessions.iter().map(|s| s.cost).sum();
+    let lines_added = landed.iter().map(|c| c.lines_added).sum();
+    let lines_deleted = landed.iter().map(|c| c.lines_deleted).sum();
+    let lines_changed = lines_added + lines_deleted;
+    ProjectGitStats {
+        repository: repository.to_string(),
+        sessions: sessions.len(),
+        cost,
+        commits: landed.len(),
+        lines_added,
+        lines_deleted,
+        cost_per_commit: (!landed.is_empty()).then(|| cost / landed.len() as f64),
+        cost_per_kloc: (lines_changed > 0).then(|| cost / lines_changed as f64 * 1000.0),
+    }
+}
+
+fn repository_root(cwd: &Path) -> Option<PathBuf> {
+    if !cwd.is_dir() {
+        return None;
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(cwd)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+fn commits_between(root: &Path, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Commit>> {
+    let format = "%Y-%m-%d %H:%M:%S +0000";
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "log",
+            "HEAD",
+            "--no-merges",
+            "--numstat",
+            "--format=commit %ct",
+        ])
+        .arg(format!("--since={}", since.format(format)))
+        .arg(format!("--until={}", until.format(format)))
+        .output()
+        .context("Failed to run git")?;
+    // A repository without commits has no HEAD yet.
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git log --numstat --format="commit %ct"` output. Binary files,
+/// shown as `-`, add no lines.
+fn parse_log(log: &str) -> Vec<Commit> {
+    let mut commits: Vec<Commit> = Vec::new();
+    for line in log.lines() {
+        if let Some(timestamp) = line.strip_prefix("commit ") {
+            if let Some(time) = timestamp
+                .trim()
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            {
+                commits.push(Commit {
+                    time,
+                    lines_added: 0,
+                    lines_deleted: 0,
+                });
+            }
+            continue;
+        }
+        let mut fields = line.split('\t');
+        if let (Some(added), Some(deleted), Some(commit)) =
+            (fields.next(), fields.next(), commits.last_mut())
+        {
+            commit.lines_added += added.parse::<u64>().unwrap_or(0);
+            commit.lines_deleted += deleted.parse::<u64>().unwrap_or(0);
+        }
+    }
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().into()
+    }
+
+    #[test]
+    fn parses_numstat_log() {
+        let log = "commit 1750000000\n\n12\t3\tsrc/main.rs\n-\t-\tlogo.png\ncommit 1750003600\n\n0\t7\tREADME.md\n";
+        let commits = parse_log(log);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].time.timestamp(), 1_750_000_000);
+        assert_eq!((commits[0].lines_added, commits[0].lines_deleted), (12, 3));
+        assert_eq!((commits[1].lines_added, commits[1].lines_deleted), (0, 7));
+    }
+
+    #[test]
+    fn counts_commits_during_sessions_and_the_grace_period() {
+        let sessions = vec![
+            AgentSession {
+                start: at("2025-06-10T09:00:00Z"),
+                end: at("2025-06-10T10:00:00Z"),
+                cost: 6.0,
+            },
+            AgentSession {
+                start: at("2025-06-11T14:00:00Z"),
+                end: at("2025-06-11T14:30:00Z"),
+                cost: 3.0,
+            },
+        ];
+        let commit = |time: &str, added, deleted| Commit {
+            time: at(time),
+            lines_added: added,
+            lines_deleted: deleted,
+        };
+        let commits = vec![
+            commit("2025-06-10T08:59:00Z", 100, 0),
+            commit("2025-06-10T09:30:00Z", 40, 10),
+            commit("2025-06-10T10:10:00Z", 20, 0),
+            commit("2025-06-10T10:20:00Z", 500, 0),
+            commit("2025-06-11T14:15:00Z", 25, 5),
+        ];
+
+        let stats = attribute("/code/app", &sessions, &commits);
+
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.cost, 9.0);
+        assert_eq!(stats.commits, 3);
+        assert_eq!((stats.lines_added, stats.lines_deleted), (85, 15));
+        assert_eq!(stats.cost_per_commit, Some(3.0));
+        assert_eq!(stats.cost_per_kloc, Some(90.0));
+
+        let idle = attribute("/code/app", &sessions, &[]);
+        assert_eq!(idle.cost_per_commit, None);
+        assert_eq!(idle.cost_per_kloc, None);
+    }
+}
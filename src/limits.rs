@@ -0,0 +1,242 @@
+//! How much of the current subscription usage windows is used: the
+//! five-hour and weekly limits of Claude and ChatGPT plans.
+//!
+//! Codex logs the share of each window it has used with every token count,
+//! so its figures are the tool's own. Claude Code logs nothing of the kind,
+//! so its five-hour window is rebuilt from message timestamps the way the
+//! plans run it: a window opens on the hour of the first message after the
+//! previous one closed. Without a configured token limit, it is measured
+//! against the busiest earlier window.
+
+use crate::analyzer::AnalyzerRegistry;
+use crate::config::LimitsConfig;
+use crate::types::{Application, ConversationMessage, MessageRole};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+
+/// How far back logs are read: the weekly window, plus a day of earlier
+/// five-hour windows to compare the current one with.
+const HISTORY: TimeDelta = TimeDelta::days(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    FiveHour,
+    Weekly,
+}
+
+impl WindowKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowKind::FiveHour => "5h",
+            WindowKind::Weekly => "weekly",
+        }
+    }
+
+    fn length(self) -> TimeDelta {
+        match self {
+            WindowKind::FiveHour => TimeDelta::hours(5),
+            WindowKind::Weekly => TimeDelta::days(7),
+        }
+    }
+}
+
+/// One tool's usage of one window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageWindow {
+    pub tool: String,
+    pub kind: WindowKind,
+    pub resets_at: DateTime<Utc>,
+    /// Input and output tokens sent in the window, when counted locally.
+    pub tokens: Option<u64>,
+    /// Fraction of the window's limit used, when a limit is known.
+    pub used: Option<f64>,
+}
+
+/// Current windows of every tool that reports or implies one.
+pub fn evaluate(
+    registry: &AnalyzerRegistry,
+    config: &LimitsConfig,
+    now: DateTime<Utc>,
+) -> Vec<UsageWindow> {
+    let since = std::time::SystemTime::from(now - HISTORY);
+    let mut windows = Vec::new();
+
+    let claude = Application::ClaudeCode.display_name();
+    if registry.get_analyzer_by_display_name(claude).is_some()
+        && let Ok(messages) = registry.load_recent_messages(claude, since)
+    {
+        windows.extend(claude_windows(&messages, config, now));
+    }
+
+    let codex = Application::CodexCli.display_name();
+    if let Some(analyzer) = registry.get_analyzer_by_display_name(codex)
+        && let Ok(sources) = analyzer.discover_data_sources()
+    {
+        // The newest rollout holds the latest report.
+        let newest = sources
+            .iter()
+            .filter_map(|source| {
+                let modified = std::fs::metadata(&source.path).ok()?.modified().ok()?;
+                (modified >= since).then_some((modified, &source.path))
+            })
+            .max();
+        if let Some((_, path)) = newest
+            && let Ok(reported) = crate::analyzers::codex_cli::latest_rate_limits(path)
+        {
+            // A window that has reset since the report is empty again.
+            windows.extend(reported.into_iter().filter(|window| window.resets_at > now));
+        }
+    }
+
+    windows
+}
+
+/// Claude Code's current five-hour and rolling weekly windows.
+pub fn claude_windows(
+    messages: &[ConversationMessage],
+    config: &LimitsConfig,
+    now: DateTime<Utc>,
+) -> Vec<UsageWindow> {
+    let mut usage: Vec<(DateTime<Utc>, u64)> = messages
+        .iter()
+        .filter(|message| {
+            message.application == Application::ClaudeCode
+                && message.role == MessageRole::Assistant
+                && message.date <= now
+        })
+        .map(|message| {
+            (
+                message.date,
+                message.stats.input_tokens + message.stats.output_tokens,
+            )
+        })
+        .collect();
+    usage.sort_unstable();
+
+    // (start, tokens) of each five-hour window, oldest first.
+    let mut blocks: Vec<(DateTime<Utc>, u64)> = Vec::new();
+    for &(date, tokens) in &usage {
+        match blocks.last_mut() {
+            Some((start, total)) if date < *start + WindowKind::FiveHour.length() => {
+                *total += tokens
+            }
+            _ => blocks.push((
+                date.duration_trunc(TimeDelta::hours(1)).unwrap_or(date),
+                tokens,
+            )),
+        }
+    }
+
+    let mut windows = Vec::new();
+    let tool = Application::ClaudeCode.display_name().to_string();
+    if let Some(&(start, tokens)) = blocks.last()
+        && now < start + WindowKind::FiveHour.length()
+    {
+        let busiest = blocks[..blocks.len() - 1]
+            .iter()
+            .map(|(_, tokens)| *tokens)
+            .max();
+        let limit = config
+            .five_hour_tokens
+            .or(busiest)
+            .filter(|limit| *limit > 0);
+        windows.push(UsageWindow {
+            tool: tool.clone(),
+            kind: WindowKind::FiveHour,
+            resets_at: start + WindowKind::FiveHour.length(),
+            tokens: Some(tokens),
+            used: limit.map(|limit| tokens as f64 / limit as f64),
+        });
+    }
+
+    let week_start = now - WindowKind::Weekly.length();
+    let week: Vec<&(DateTime<Utc>, u64)> = usage
+        .iter()
+        .filter(|(date, _)| *date > week_start)
+        .collect();
+    if let Some((oldest, _)) = week.first() {
+        let tokens: u64 = week.iter().map(|(_, tokens)| tokens).sum();
+        windows.push(UsageWindow {
+            tool,
+            kind: WindowKind::Weekly,
+            // Rolling: usage frees up as the oldest message ages out.
+            resets_at: *oldest + WindowKind::Weekly.length(),
+            tokens: Some(tokens),
+            used: config
+                .weekly_tokens
+                .filter(|limit| *limit > 0)
+                .map(|limit| tokens as f64 / limit as f64),
+        });
+    }
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Stats;
+
+    fn message(at: &str, tokens: u64) -> ConversationMessage {
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: at.parse().unwrap(),
+            project_hash: String::new(),
+            conversation_hash: String::new(),
+            local_hash: None,
+            global_hash: at.to_string(),
+            model: None,
+            stats: Stats {
+                input_tokens: tokens,
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        }
+    }
+
+    #[test]
+    fn five_hour_window_opens_on_the_hour_and_compares_with_the_busiest() {
+        let messages = vec![
+            message("2025-06-01T08:20:00Z", 4000),
+            message("2025-06-01T12:50:00Z", 4000),
+            // Past 13:00, so a new window opening at 14:00.
+            message("2025-06-01T14:10:00Z", 1000),
+            message("2025-06-01T15:30:00Z", 1000),
+        ];
+        let now = "2025-06-01T16:00:00Z".parse().unwrap();
+
+        let windows = claude_windows(&messages, &LimitsConfig::default(), now);
+        let five_hour = &windows[0];
+        assert_eq!(five_hour.kind, WindowKind::FiveHour);
+        assert_eq!(
+            five_hour.resets_at,
+            "2025-06-01T19:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(five_hour.tokens, Some(2000));
+        assert_eq!(five_hour.used, Some(0.25));
+
+        let weekly = &windows[1];
+        assert_eq!(weekly.kind, WindowKind::Weekly);
+        assert_eq!(weekly.tokens, Some(10_000));
+        assert_eq!(weekly.used, None);
+
+        let config = LimitsConfig {
+            five_hour_tokens: Some(4000),
+            weekly_tokens: Some(20_000),
+        };
+        let windows = claude_windows(&messages, &config, now);
+        assert_eq!(windows[0].used, Some(0.5));
+        assert_eq!(windows[1].used, Some(0.5));
+    }
+
+    #[test]
+    fn no_five_hour_window_once_it_has_closed() {
+        let messages = vec![message("2025-06-01T08:20:00Z", 4000)];
+        let now = "2025-06-01T13:00:00Z".parse().unwrap();
+
+        let windows = claude_windows(&messages, &LimitsConfig::default(), now);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].kind, WindowKind::Weekly);
+    }
+}
@@ -1,23 +1,52 @@
 use anyhow::{Context, Result};
-use clap::{Args, Parser, Subcommand};
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use parking_lot::Mutex;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use analyzer::AnalyzerRegistry;
 use analyzers::{
-    AntigravityCliAnalyzer, ClaudeCodeAnalyzer, ClineAnalyzer, CodexCliAnalyzer, CopilotAnalyzer,
-    CopilotCliAnalyzer, GeminiCliAnalyzer, KiloCliAnalyzer, KiloCodeAnalyzer, OpenCodeAnalyzer,
-    PiAgentAnalyzer, PiebaldAnalyzer, QwenCodeAnalyzer, RooCodeAnalyzer, ZooCodeAnalyzer,
+    AntigravityCliAnalyzer, ClaudeCodeAnalyzer, ClineAnalyzer, CodexCliAnalyzer,
+    ConfigurableAnalyzer, CopilotAnalyzer, CopilotCliAnalyzer, GeminiCliAnalyzer, ImportedAnalyzer,
+    KiloCliAnalyzer, KiloCodeAnalyzer, OpenCodeAnalyzer, PiAgentAnalyzer, PiebaldAnalyzer,
+    QwenCodeAnalyzer, RooCodeAnalyzer, ZooCodeAnalyzer,
 };
 
 mod analyzer;
 mod analyzers;
+mod application;
+mod archive;
+mod backup;
+mod bench;
+mod budget;
 mod cache;
 mod config;
 mod contribution_cache;
+mod diagnostics;
+mod doctor;
+mod forecast;
+mod import;
+mod insights;
+mod integrations;
+mod limits;
 mod mcp;
 mod models;
+mod notify;
+mod parse_io;
+mod paths;
+mod persist;
+mod premium;
+mod query;
+mod report;
 mod reqwest_simd_json;
+mod rpc;
+mod stats;
+mod statusline;
+mod store;
+mod subscription;
+mod sync;
+mod team;
 mod tui;
 mod types;
 mod upload;
@@ -31,18 +60,24 @@ use crate::config::UploadState;
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+/// Fast, cross-platform token usage tracker and cost monitor for agentic coding tools
 #[derive(Parser)]
 #[command(name = "splitrail")]
 #[command(version)]
 #[command(disable_help_subcommand = true)]
+#[command(after_help = supported_tools_help())]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Output stats as JSON instead of running the TUI
-    #[arg(long)]
+    /// Output JSON instead of text; without a subcommand, print stats instead of running the TUI
+    #[arg(long, global = true)]
     json: bool,
 
+    /// Render without colors or Unicode box-drawing characters (also set by NO_COLOR)
+    #[arg(long, global = true)]
+    plain: bool,
+
     /// Use comma-separated number formatting
     #[arg(long)]
     number_comma: bool,
@@ -58,6 +93,56 @@ struct Cli {
     /// Number of decimal places for human-readable formatting
     #[arg(long)]
     decimal_places: Option<usize>,
+
+    /// Only run these analyzers, e.g. --only claude_code,codex_cli
+    #[arg(long, global = true, value_delimiter = ',', value_parser = HintedValueParser { values: analyzer_key_hints, help: "analyzer" }, hide_possible_values = true)]
+    only: Vec<String>,
+
+    /// Skip these analyzers, e.g. --exclude copilot
+    #[arg(long, global = true, value_delimiter = ',', value_parser = HintedValueParser { values: analyzer_key_hints, help: "analyzer" }, hide_possible_values = true)]
+    exclude: Vec<String>,
+
+    /// Show the TUI from a one-time load without watching for changes (useful over SSH or on battery)
+    #[arg(long)]
+    no_watch: bool,
+
+    /// Use a named profile's config, cache and upload state (also set by SPLITRAIL_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    #[command(flatten)]
+    date_range: DateRangeArgs,
+}
+
+/// Restrict stats to a window of local dates (inclusive).
+#[derive(Args, Clone, Copy, Default)]
+struct DateRangeArgs {
+    /// Only include usage on or after this date (YYYY-MM-DD, today, yesterday, or e.g. 7d)
+    #[arg(long, value_parser = parse_date_bound)]
+    since: Option<types::CompactDate>,
+
+    /// Only include usage on or before this date (YYYY-MM-DD, today, yesterday, or e.g. 7d)
+    #[arg(long, value_parser = parse_date_bound)]
+    until: Option<types::CompactDate>,
+}
+
+impl DateRangeArgs {
+    fn to_range(self) -> types::DateRange {
+        let range = types::DateRange {
+            since: self.since,
+            until: self.until,
+        };
+        if !range.is_valid() {
+            eprintln!("Error: --since must not be after --until");
+            std::process::exit(2);
+        }
+        range
+    }
+}
+
+fn parse_date_bound(value: &str) -> Result<types::CompactDate, String> {
+    types::DateRange::parse_bound(value)
+        .ok_or_else(|| format!("expected YYYY-MM-DD, today, yesterday, or <N>d, got '{value}'"))
 }
 
 #[derive(Subcommand)]
@@ -70,6 +155,403 @@ enum Commands {
     Stats(StatsArgs),
     /// Run as an MCP (Model Context Protocol) server
     Mcp,
+    /// Serve usage stats over line-delimited JSON-RPC on stdio for editor integrations
+    ///
+    /// Methods: getDailyStats, getSessions, subscribeUpdates, unsubscribeUpdates.
+    Rpc,
+    /// Print a shell completion script to stdout
+    ///
+    /// e.g. `splitrail completions zsh > ~/.zfunc/_splitrail`
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page, or install man pages for every subcommand
+    Man(ManArgs),
+    /// Print today's cost and usage on one line for shell prompts or tmux
+    ///
+    /// Reads the snapshot kept up to date by a running TUI, so it returns
+    /// instantly. Prints "splitrail: stale" when no recent snapshot exists.
+    ///
+    /// e.g. `set -g status-right '#(splitrail statusline --tmux)'`
+    Statusline(StatuslineArgs),
+    /// Check spending against the `[budget]` limits in the config file
+    Budget(BudgetArgs),
+    /// Project this month's spend from the trailing daily average
+    Forecast(ForecastArgs),
+    /// Reports built from your usage history
+    ///
+    /// Without a subcommand, writes a summary of the last day, week or month to post
+    /// in a team channel, e.g. `splitrail report --period week --format html --output usage.html`.
+    Report(ReportArgs),
+    /// Post a usage summary to a Slack, Discord or Teams webhook
+    ///
+    /// e.g. from cron: `0 9 * * MON splitrail notify --webhook https://hooks.slack.com/services/...`.
+    Notify(NotifyArgs),
+    /// Compare usage in one period with the period before it, per tool
+    ///
+    /// e.g. `splitrail diff --period month`, or explicit ranges with
+    /// `splitrail diff --from 2025-05-01..2025-05-31 --to 2025-06-01..2025-06-30`.
+    Diff(DiffArgs),
+    /// Filter, group, and total individual messages
+    ///
+    /// e.g. `splitrail query "model ~ 'sonnet' AND date >= 2025-06-01" --group-by model --sum cost,output_tokens`
+    ///
+    /// Fields: analyzer, model, date, month, project, session, role, cost, input_tokens,
+    /// output_tokens, reasoning_tokens, cache_creation_tokens, cache_read_tokens,
+    /// cached_tokens, total_tokens, tool_calls, lines_added, lines_deleted.
+    /// Operators: = != < <= > >= and ~ (contains), combined with AND, OR, NOT, and parentheses.
+    Query(QueryArgs),
+    /// Combine teammates' `splitrail stats` exports into per-user and per-tool totals
+    ///
+    /// Each file is named after its owner, e.g. `splitrail merge alice.json bob.json`.
+    Merge(MergeArgs),
+    /// Carry upload progress and usage history between machines
+    ///
+    /// e.g. `splitrail sync export ~/Dropbox/splitrail.tar.gz` on one machine,
+    /// then `splitrail sync import ~/Dropbox/splitrail.tar.gz` on the other.
+    Sync(SyncArgs),
+    /// Bring in history from other usage trackers, shown as the "Imported" tool
+    ///
+    /// e.g. `ccusage daily --json > ccusage.json && splitrail import ccusage ccusage.json`.
+    /// Imported records stay on this machine; they aren't uploaded.
+    /// `splitrail import --raw` restores a `splitrail export --raw` backup instead.
+    Import(ImportArgs),
+    /// Back up every parsed message, to restore with `splitrail import --raw`
+    ///
+    /// e.g. `splitrail export --raw ~/backups/splitrail.ndjson.gz`, so usage stays counted after
+    /// a tool deletes its old logs.
+    Export(ExportArgs),
+    /// Copy Claude Code and Gemini CLI logs into the archive now
+    ///
+    /// Keeps gzipped copies under `archive/` in the data directory after the tools delete their
+    /// old logs. `splitrail config set archive-logs true` does this while the TUI runs.
+    Archive,
+    /// Parse an analyzer's logs from scratch and print what was found
+    ///
+    /// Only prints totals to compare with the TUI; a running TUI is not refreshed. Press `R` in
+    /// the TUI to rescan the current tab in place.
+    Rescan(RescanArgs),
+    /// Check each tool's data, splitrail's own files and the upload server
+    ///
+    /// Re-reads every tool's data and lists files that failed to parse, e.g. `splitrail doctor --output splitrail-diagnosis.txt` to attach to a bug report.
+    Doctor(DoctorArgs),
+    /// Time discovery, parsing, deduplication, aggregation and persistence for each tool
+    ///
+    /// Runs a full load of your real data and prints where the time went, e.g. to attach to a
+    /// report about slow startup.
+    Bench,
+}
+
+#[derive(Args)]
+struct DoctorArgs {
+    /// Write the report to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    /// Filter expression; every message matches when omitted
+    filter: Option<String>,
+
+    /// Comma-separated fields to group by
+    #[arg(long, value_delimiter = ',')]
+    group_by: Vec<String>,
+
+    /// Comma-separated numeric fields to total
+    #[arg(long, value_delimiter = ',', default_value = "cost")]
+    sum: Vec<String>,
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    /// JSON files written by `splitrail stats`
+    #[arg(required = true)]
+    files: Vec<std::path::PathBuf>,
+
+    #[command(flatten)]
+    date_range: DateRangeArgs,
+}
+
+#[derive(Args)]
+struct SyncArgs {
+    #[command(subcommand)]
+    subcommand: SyncSubcommands,
+}
+
+#[derive(Subcommand)]
+enum SyncSubcommands {
+    /// Bundle the upload state, status snapshot, and history store into an archive
+    Export {
+        /// Archive to write (a .tar.gz)
+        archive: std::path::PathBuf,
+    },
+    /// Merge an archive from another machine into the local data
+    Import {
+        /// Archive written by `splitrail sync export`
+        archive: std::path::PathBuf,
+    },
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct ImportArgs {
+    /// Restore a backup written by `splitrail export --raw`, merging it with
+    /// messages restored before
+    #[arg(long, value_name = "FILE")]
+    raw: Option<std::path::PathBuf>,
+
+    #[command(subcommand)]
+    subcommand: Option<ImportSubcommands>,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Write every parsed message as gzipped NDJSON to this file
+    #[arg(long, value_name = "FILE")]
+    raw: std::path::PathBuf,
+}
+
+#[derive(Subcommand)]
+enum ImportSubcommands {
+    /// Import the JSON from `ccusage daily --json` (or weekly, monthly or session)
+    Ccusage {
+        /// ccusage output, or `-` for stdin
+        path: std::path::PathBuf,
+        /// Only import usage on or before this date, e.g. the day before your
+        /// oldest Claude Code log, so days splitrail reads itself don't count twice
+        #[arg(long, value_parser = parse_date_bound)]
+        until: Option<types::CompactDate>,
+    },
+    /// Import CSV (with a header row), a JSON array or JSONL
+    ///
+    /// Fields: date (YYYY-MM-DD or RFC 3339), tool, model, session, input_tokens,
+    /// output_tokens, cache_creation_tokens, cache_read_tokens, cost. Only date is required;
+    /// cost is computed from the tokens when missing.
+    File {
+        /// File to import, or `-` for stdin
+        path: std::path::PathBuf,
+        /// Only import usage on or before this date
+        #[arg(long, value_parser = parse_date_bound)]
+        until: Option<types::CompactDate>,
+    },
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+struct ReportArgs {
+    /// Period the summary covers, ending today
+    #[arg(long, value_enum, default_value = "week")]
+    period: report::Period,
+
+    /// Summary format
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: report::Format,
+
+    /// Write the summary to this file instead of stdout
+    #[arg(long, short)]
+    output: Option<std::path::PathBuf>,
+
+    #[command(subcommand)]
+    subcommand: Option<ReportSubcommands>,
+}
+
+#[derive(Subcommand)]
+enum ReportSubcommands {
+    /// Suggest concrete ways to reduce spend, based on the last 30 days unless a range is given
+    Insights {
+        #[command(flatten)]
+        date_range: DateRangeArgs,
+    },
+    /// Commits and lines landed during Claude Code sessions, and their cost, per repository
+    ///
+    /// Opt-in, since it runs `git` in the directories sessions worked in:
+    /// `splitrail config set git-integration true`.
+    Git {
+        #[command(flatten)]
+        date_range: DateRangeArgs,
+    },
+    /// Share of file reads, writes, and edits per language, overall, per day, and per project
+    Languages {
+        #[command(flatten)]
+        date_range: DateRangeArgs,
+    },
+}
+
+#[derive(Args)]
+struct NotifyArgs {
+    /// Incoming webhook URL to post to
+    #[arg(long, value_name = "URL")]
+    webhook: String,
+
+    /// Period the summary covers, ending today
+    #[arg(long, value_enum, default_value = "week")]
+    period: report::Period,
+
+    /// Payload to send; guessed from the webhook URL when omitted, JSON otherwise
+    #[arg(long, value_enum)]
+    style: Option<upload::webhook::Style>,
+
+    /// File with the message text, using placeholders like {{cost}}, {{tools}} and {{report}}
+    #[arg(long, value_name = "FILE")]
+    template: Option<std::path::PathBuf>,
+
+    /// Print the payload instead of posting it
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct RescanArgs {
+    /// Only rescan this analyzer (e.g., "Claude Code"); all available ones otherwise
+    #[arg(long, value_parser = HintedValueParser { values: analyzer_name_hints, help: "analyzer" }, hide_possible_values = true)]
+    analyzer: Option<String>,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Length of the periods compared: the last day, week or month against the one before
+    #[arg(long, value_enum, default_value = "week")]
+    period: report::Period,
+
+    /// Baseline range (SINCE..UNTIL), compared against --to instead of --period
+    #[arg(long, value_name = "RANGE", value_parser = parse_date_range, requires = "to")]
+    from: Option<types::DateRange>,
+
+    /// Range compared against the --from baseline
+    #[arg(long, value_name = "RANGE", value_parser = parse_date_range, requires = "from")]
+    to: Option<types::DateRange>,
+}
+
+fn parse_date_range(value: &str) -> Result<types::DateRange, String> {
+    types::DateRange::parse(value)
+        .filter(|range| !range.is_unbounded())
+        .ok_or_else(|| format!("expected SINCE..UNTIL or a single day, got '{value}'"))
+}
+
+#[derive(Args)]
+struct BudgetArgs {
+    #[command(subcommand)]
+    subcommand: BudgetSubcommands,
+}
+
+#[derive(Subcommand)]
+enum BudgetSubcommands {
+    /// Show spending for the current month and week against each budget
+    Status,
+}
+
+#[derive(Args)]
+struct ForecastArgs {
+    /// Days to average over; defaults to `forecast.window_days` (7)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=90))]
+    window: Option<u32>,
+}
+
+#[derive(Args)]
+struct StatuslineArgs {
+    /// Use tmux `#[fg=...]` color directives suitable for status-right
+    #[arg(long, default_value_t = false)]
+    tmux: bool,
+
+    /// Treat the snapshot as stale after this many seconds
+    #[arg(long, default_value_t = 180)]
+    max_age: u64,
+
+    /// Parse all data and rewrite the snapshot first (slow; for cron or when no TUI is running)
+    #[arg(long, default_value_t = false)]
+    refresh: bool,
+}
+
+#[derive(Args)]
+struct ManArgs {
+    /// Write splitrail.1 and one page per subcommand into the man directory
+    #[arg(long, default_value_t = false)]
+    install: bool,
+
+    /// Directory to install into (default: ~/.local/share/man/man1)
+    #[arg(long, requires = "install")]
+    dir: Option<std::path::PathBuf>,
+}
+
+/// Accepts any string but advertises a set of known values, so shells can
+/// complete them while the command itself reports unknown values.
+///
+/// Every hint carries a short description; besides being shown by zsh/fish,
+/// it makes clap_complete escape values containing spaces ("Claude Code").
+#[derive(Clone)]
+struct HintedValueParser {
+    values: fn() -> Vec<&'static str>,
+    help: &'static str,
+}
+
+impl TypedValueParser for HintedValueParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            (self.values)()
+                .into_iter()
+                .map(|value| PossibleValue::new(value).help(self.help)),
+        ))
+    }
+}
+
+/// Built-in tools and the keys `--only`, `--exclude` and `[analyzers]` take.
+fn supported_tools_help() -> String {
+    let tools: Vec<String> = application::APPS
+        .iter()
+        .filter(|info| info.application != types::Application::Custom)
+        .map(|info| format!("{} ({})", info.name, info.slug))
+        .collect();
+    format!(
+        "Supported tools: {}, plus any [[custom_analyzer]] from the config file.",
+        tools.join(", ")
+    )
+}
+
+fn analyzer_name_hints() -> Vec<&'static str> {
+    let config = config::Config::load().ok().flatten();
+    registry_with_all_analyzers(config.as_ref()).display_names()
+}
+
+/// Slugs for built-in analyzers, names for custom ones.
+fn analyzer_key_hints() -> Vec<&'static str> {
+    analyzer_name_hints()
+        .into_iter()
+        .map(|name| types::Application::from_name(name).map_or(name, |app| app.slug()))
+        .collect()
+}
+
+/// Exit with a usage error if `--only`/`--exclude` name an unknown analyzer.
+fn check_analyzer_filter(filter: &config::AnalyzerFilter) {
+    let known: Vec<String> = analyzer_name_hints()
+        .into_iter()
+        .map(config::analyzer_key)
+        .collect();
+    for name in filter.only.iter().chain(&filter.exclude) {
+        if !known.contains(&config::analyzer_key(name)) {
+            eprintln!(
+                "Error: unknown analyzer '{name}'. Expected one of: {}",
+                known.join(", ")
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+fn config_key_hints() -> Vec<&'static str> {
+    config::CONFIG_KEYS.to_vec()
 }
 
 #[derive(Args)]
@@ -79,7 +561,7 @@ struct UploadArgs {
     full: bool,
 
     /// Force re-upload for a specific analyzer (e.g., "Claude Code").
-    #[arg(long)]
+    #[arg(long, value_parser = HintedValueParser { values: analyzer_name_hints, help: "analyzer" }, hide_possible_values = true)]
     force_analyzer: Option<String>,
 
     /// Re-upload only messages with zero cost (useful for fixing pricing errors).
@@ -89,6 +571,10 @@ struct UploadArgs {
     /// Show what would be uploaded without actually uploading.
     #[arg(long, default_value_t = false)]
     dry_run: bool,
+
+    /// Print the exact JSON request bodies instead of uploading (implies --dry-run).
+    #[arg(long, default_value_t = false)]
+    show_payload: bool,
 }
 
 #[derive(Args)]
@@ -106,6 +592,33 @@ struct StatsArgs {
     /// Pretty-print JSON instead of a single line
     #[arg(long, default_value_t = false)]
     pretty: bool,
+
+    /// Output per-model totals across all tools and days instead of per-tool stats
+    #[arg(long, default_value_t = false, conflicts_with = "include_messages")]
+    by_model: bool,
+
+    /// Output a day-of-week × hour-of-day message grid per tool (and for all tools combined)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["include_messages", "by_model"])]
+    heatmap: bool,
+
+    /// Output cost and tokens per provider (Anthropic, OpenAI, ...) in total, per day, and per month
+    #[arg(long, default_value_t = false, conflicts_with_all = ["include_messages", "by_model", "heatmap"])]
+    by_provider: bool,
+
+    /// Tokens per busy hour, average response size, and replies by hour of day per tool (and for all tools combined)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["include_messages", "by_model", "heatmap", "by_provider"])]
+    throughput: bool,
+
+    /// Output prompt-cache hit ratios and estimated savings per tool, in total, per day, and per model
+    #[arg(long, default_value_t = false, conflicts_with_all = ["include_messages", "by_model", "heatmap", "by_provider", "throughput"])]
+    cache: bool,
+
+    /// Price subscribed tools at API rates or at their `[subscriptions]` fee (default: the config's cost-mode)
+    #[arg(long, value_enum)]
+    cost_mode: Option<subscription::CostMode>,
+
+    #[command(flatten)]
+    date_range: DateRangeArgs,
 }
 
 #[derive(Subcommand)]
@@ -117,44 +630,94 @@ enum ConfigSubcommands {
     },
     /// Show current configuration
     Show,
+    /// Open the configuration in $EDITOR and check it before saving
+    Edit,
     /// Set configuration value
     Set {
-        /// Configuration key (api-token, auto-upload, upload-today-only, number-comma, number-human, locale, decimal-places, currency-symbol, cost-decimal-places, reverse-sort-default, hide-empty-periods, default-view, default-tab, confirm-quit, hidden-columns, accent-color, color-costs, show-header, log-level)
+        /// Configuration key (api-token, auto-upload, upload-today-only, server-upload-path, server-schema-version, upload-chunk-size, upload-compress, upload-proxy-url, upload-ca-bundle-path, privacy-salt-project-hashes, privacy-strip-session-names, privacy-generalize-models, exclude-paths, number-comma, number-human, locale, decimal-places, currency-symbol, cost-decimal-places, reverse-sort-default, hide-empty-periods, default-view, default-tab, confirm-quit, hidden-columns, columns, accent-color, color-costs, show-header, show-trend, trend-days, cost-mode, log-level, budget-monthly-usd, budget-weekly-usd, budget-warn-at, notifications-enabled, notify-daily-thresholds, freeze-deleted-sessions, cache-max-size-mb, data-dir, sqlite-store, archive-logs, archive-max-size-mb, pricing-updates, pricing-updates-url, watcher-poll-interval, parse-io, parse-threads, forecast-window-days, git-integration, theme, cost-source, copilot-plan, limit-five-hour-tokens, limit-weekly-tokens, idle-gap-minutes)
+        #[arg(value_parser = HintedValueParser { values: config_key_hints, help: "config key" }, hide_possible_values = true)]
         key: String,
         /// Configuration value
         value: String,
+        /// Store the API token in the OS keychain instead of the config file
+        #[arg(long, default_value_t = false)]
+        keychain: bool,
     },
 }
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let cli = Cli::try_parse().unwrap_or_else(|e| exit_with_clap_error(e));
+    // NO_COLOR alone is honored by crossterm; --plain also drops Unicode glyphs.
+    if cli.plain {
+        utils::set_plain_output(true);
+    }
+    let profile = cli.profile.clone().or_else(|| {
+        std::env::var(paths::PROFILE_ENV)
+            .ok()
+            .filter(|name| !name.is_empty())
+    });
+    if let Some(profile) = profile
+        && let Err(e) = paths::set_profile(&profile)
+    {
+        eprintln!("Error: {e:#}");
+        std::process::exit(2);
+    }
+    paths::migrate_legacy_files();
 
     // Load config file to get defaults
     let config = config::Config::load().unwrap_or(None).unwrap_or_default();
     utils::set_log_level(config.logging.level);
 
+    let analyzer_filter = config::AnalyzerFilter {
+        only: cli.only,
+        exclude: cli.exclude,
+    };
+    check_analyzer_filter(&analyzer_filter);
+    config::set_analyzer_filter(analyzer_filter);
+    let _ = config::set_exclude_rules(config::ExcludeRules::from_config(&config.exclude));
+    parse_io::set_parse_io(config.parsing.parse_io());
+
+    // Layer remote manifest rates under the config's own model settings
+    if config.pricing_updates.enabled
+        && let Some(rates) = models::remote::refresh(&config.pricing_updates).await
+    {
+        models::set_manifest_rates(rates);
+    }
+
+    models::set_cost_source(config.costs.source);
+    stats::active::set_idle_gap_minutes(config.activity.idle_gap_minutes);
+
     // Initialize external models from config
-    models::init_external_models(config.models.clone(), config.aliases.clone());
+    models::init_external_models(
+        config.models.clone(),
+        config.aliases.clone(),
+        &config.pricing,
+    );
 
     // Create format options merging config defaults with CLI overrides
-    let format_options = utils::NumberFormatOptions {
-        use_comma: cli.number_comma || config.formatting.number_comma,
-        use_human: cli.number_human || config.formatting.number_human,
-        locale: cli.locale.unwrap_or(config.formatting.locale),
-        decimal_places: cli
-            .decimal_places
-            .unwrap_or(config.formatting.decimal_places),
-        currency_symbol: config.formatting.currency_symbol,
-        cost_decimal_places: config.formatting.cost_decimal_places,
+    let format_overrides = config::FormatOverrides {
+        number_comma: cli.number_comma,
+        number_human: cli.number_human,
+        locale: cli.locale,
+        decimal_places: cli.decimal_places,
     };
+    let format_options = config.formatting.number_format(&format_overrides);
 
+    let json = cli.json;
     match cli.command {
         None => {
-            if cli.json {
+            if json {
                 if let Err(e) = run_stats(StatsArgs {
                     include_messages: false,
                     pretty: true,
+                    by_model: false,
+                    heatmap: false,
+                    by_provider: false,
+                    throughput: false,
+                    cache: false,
+                    cost_mode: None,
+                    date_range: cli.date_range,
                 })
                 .await
                 {
@@ -163,12 +726,16 @@ async fn main() {
                 }
             } else {
                 // No subcommand - run default behavior
-                run_default(format_options).await;
+                run_default(format_overrides, cli.date_range.to_range(), cli.no_watch).await;
             }
         }
         Some(Commands::Upload(args)) => {
-            match run_upload(args).await.context("Failed to run upload") {
+            match run_upload(args, json).await.context("Failed to run upload") {
                 Ok(_) => {}
+                Err(e) if json => {
+                    eprintln!("Error: {e:#}");
+                    std::process::exit(1);
+                }
                 Err(e) => {
                     tui::show_upload_error(&format!("{e:#}"));
                     std::process::exit(1);
@@ -176,7 +743,7 @@ async fn main() {
             }
         }
         Some(Commands::Config(config_args)) => {
-            handle_config_subcommand(config_args).await;
+            handle_config_subcommand(config_args, json).await;
         }
         Some(Commands::Stats(stats_args)) => {
             if let Err(e) = run_stats(stats_args).await {
@@ -184,70 +751,349 @@ async fn main() {
                 std::process::exit(1);
             }
         }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "splitrail",
+                &mut std::io::stdout(),
+            );
+        }
+        Some(Commands::Budget(args)) => {
+            let BudgetSubcommands::Status = args.subcommand;
+            if let Err(e) = run_budget_status(&config.budget, json, &format_options) {
+                eprintln!("Error checking budgets: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Forecast(args)) => {
+            let window_days = args.window.unwrap_or(config.forecast.window_days);
+            if let Err(e) = run_forecast(window_days, json, &format_options) {
+                eprintln!("Error forecasting spend: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Statusline(args)) => {
+            run_statusline(args, json, &format_options);
+        }
+        Some(Commands::Report(args)) => {
+            let result = match args.subcommand {
+                None => run_summary_report(
+                    args.period,
+                    args.format,
+                    args.output.as_deref(),
+                    json,
+                    &format_options,
+                ),
+                Some(ReportSubcommands::Insights { date_range }) => {
+                    run_insights_report(date_range, json, &format_options)
+                }
+                Some(ReportSubcommands::Git { date_range }) => {
+                    run_git_report(&config.git, date_range, json, &format_options)
+                }
+                Some(ReportSubcommands::Languages { date_range }) => {
+                    run_languages_report(date_range, json)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error building report: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Notify(args)) => {
+            if let Err(e) = run_notify(args, &format_options).await {
+                eprintln!("Notify failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Diff(args)) => {
+            if let Err(e) = run_diff(args, json, &format_options) {
+                eprintln!("Error comparing periods: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Query(args)) => {
+            if let Err(e) = run_query(args, json, &format_options) {
+                eprintln!("Query failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Merge(args)) => {
+            if let Err(e) = run_merge(args, json, &format_options) {
+                eprintln!("Merge failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Import(args)) => {
+            if let Err(e) = run_import(args, json) {
+                eprintln!("Import failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Export(args)) => {
+            if let Err(e) = run_export(args, json) {
+                eprintln!("Export failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Archive) => {
+            if let Err(e) = run_archive(json) {
+                eprintln!("Archive failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Rescan(args)) => {
+            if let Err(e) = run_rescan(args, json, &format_options) {
+                eprintln!("Rescan failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Sync(args)) => {
+            if let Err(e) = run_sync(args, json, config.history.freeze_deleted_sessions) {
+                eprintln!("Sync failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Doctor(args)) => {
+            let registry = create_analyzer_registry();
+            if let Err(e) = doctor::run(&registry, args.output.as_deref(), json).await {
+                eprintln!("Doctor failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Bench) => {
+            if let Err(e) = run_bench(json) {
+                eprintln!("Benchmark failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Man(args)) => {
+            if let Err(e) = run_man(args) {
+                eprintln!("Error generating man pages: {e:#}");
+                std::process::exit(1);
+            }
+        }
         Some(Commands::Mcp) => {
             if let Err(e) = mcp::run_mcp_server().await {
                 eprintln!("MCP server error: {e:#}");
                 std::process::exit(1);
             }
         }
+        Some(Commands::Rpc) => {
+            if let Err(e) = rpc::run_rpc_server().await {
+                eprintln!("RPC server error: {e:#}");
+                std::process::exit(1);
+            }
+        }
     }
 }
 
-pub fn create_analyzer_registry() -> AnalyzerRegistry {
+/// Print a clap parse error or help message and exit. Long help (`--help`)
+/// goes through `$PAGER` (default `less -FRX`) when stdout is a terminal.
+/// With `--json`, help and version text go to stderr so stdout stays JSON.
+fn exit_with_clap_error(err: clap::Error) -> ! {
+    use std::io::IsTerminal;
+
+    let is_long_help = err.kind() == clap::error::ErrorKind::DisplayHelp
+        && std::env::args().any(|arg| arg == "--help");
+    let plain = utils::no_color_env() || std::env::args().any(|arg| arg == "--plain");
+    if std::env::args().any(|arg| arg == "--json") {
+        if plain || !std::io::stderr().is_terminal() {
+            eprint!("{}", err.render());
+        } else {
+            eprint!("{}", err.render().ansi());
+        }
+        std::process::exit(err.exit_code());
+    }
+    let rendered = if plain {
+        err.render().to_string()
+    } else {
+        err.render().ansi().to_string()
+    };
+    if is_long_help && std::io::stdout().is_terminal() && page_output(&rendered) {
+        std::process::exit(0);
+    }
+    if plain {
+        if err.use_stderr() {
+            eprint!("{rendered}");
+        } else {
+            print!("{rendered}");
+        }
+        std::process::exit(err.exit_code());
+    }
+    err.exit()
+}
+
+/// Pipe text through the user's pager. Returns false if no pager could be run,
+/// so the caller can fall back to printing directly.
+fn page_output(text: &str) -> bool {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -FRX".to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+
+    let Ok(mut child) = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may exit before reading everything (e.g. user pressed q).
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().is_ok()
+}
+
+fn run_man(args: ManArgs) -> Result<()> {
+    let cmd = Cli::command();
+    if !args.install {
+        clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?;
+        return Ok(());
+    }
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        // The usual per-user man directory on every Unix, including macOS,
+        // whose data directory (~/Library/Application Support) man never reads.
+        #[cfg(unix)]
+        None => dirs::home_dir()
+            .context("Could not find the home directory for man pages")?
+            .join(".local")
+            .join("share")
+            .join("man")
+            .join("man1"),
+        #[cfg(not(unix))]
+        None => dirs::data_local_dir()
+            .context("Could not find a data directory for man pages")?
+            .join("man")
+            .join("man1"),
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    clap_mangen::generate_to(cmd, &dir)
+        .with_context(|| format!("Failed to write man pages to {}", dir.display()))?;
+
+    println!("✅ Man pages installed to: {}", dir.display());
+    println!("   Make sure its parent directory is on your MANPATH, then run `man splitrail`.");
+    Ok(())
+}
+
+/// Analyzers that read their data directories from `[paths]`.
+const PATH_OVERRIDE_ANALYZERS: &[&str] = &["claude_code", "codex_cli", "gemini_cli"];
+
+/// Every built-in analyzer plus the configured custom ones, before
+/// `[analyzers]`, `--only` and `--exclude` are applied.
+fn registry_with_all_analyzers(config: Option<&config::Config>) -> AnalyzerRegistry {
     let mut registry = AnalyzerRegistry::new();
+    let paths = |application: types::Application| {
+        config.map_or_else(Vec::new, |config| {
+            config.analyzer_paths(application.display_name())
+        })
+    };
 
     // Register available analyzers
-    registry.register(ClaudeCodeAnalyzer::new());
+    registry.register(
+        ClaudeCodeAnalyzer::new()
+            .freeze_deleted_sessions(
+                config.is_some_and(|config| config.history.freeze_deleted_sessions),
+            )
+            .history_size_limit(config.and_then(|config| config.cache.max_size_mb))
+            .projects_dirs(paths(types::Application::ClaudeCode)),
+    );
     registry.register(ClineAnalyzer::new());
     registry.register(RooCodeAnalyzer::new());
     registry.register(ZooCodeAnalyzer::new());
     registry.register(KiloCodeAnalyzer::new());
     registry.register(KiloCliAnalyzer::new());
-    registry.register(GeminiCliAnalyzer::new());
+    registry.register(GeminiCliAnalyzer::new().tmp_dirs(paths(types::Application::GeminiCli)));
     registry.register(QwenCodeAnalyzer::new());
-    registry.register(CodexCliAnalyzer::new());
+    registry.register(CodexCliAnalyzer::new().session_dirs(paths(types::Application::CodexCli)));
     registry.register(CopilotAnalyzer::new());
     registry.register(CopilotCliAnalyzer::new());
     registry.register(OpenCodeAnalyzer::new());
     registry.register(PiAgentAnalyzer::new());
     registry.register(PiebaldAnalyzer::new());
     registry.register(AntigravityCliAnalyzer::new());
+    registry.register(ImportedAnalyzer::new());
+
+    // User-defined JSONL analyzers from `[[custom_analyzer]]` config sections
+    if let Some(config) = config {
+        for custom in &config.custom_analyzers {
+            registry.register(ConfigurableAnalyzer::new(custom.clone()));
+        }
+    }
 
     registry
 }
 
-async fn run_default(format_options: utils::NumberFormatOptions) {
-    let registry = create_analyzer_registry();
+pub fn create_analyzer_registry() -> AnalyzerRegistry {
+    let config = config::Config::load().ok().flatten();
+    let mut registry = registry_with_all_analyzers(config.as_ref());
 
-    // Create file watcher
-    let file_watcher = match watcher::FileWatcher::new(&registry) {
-        Ok(watcher) => watcher,
-        Err(e) => {
-            eprintln!("Error setting up file watcher: {e}");
-            std::process::exit(1);
+    let config = config.unwrap_or_default();
+    let known: Vec<String> = registry
+        .display_names()
+        .into_iter()
+        .map(config::analyzer_key)
+        .collect();
+    for name in config.analyzers.keys() {
+        if !known.contains(&config::analyzer_key(name)) {
+            utils::warn_once(format!(
+                "Unknown analyzer '{name}' in [analyzers]. Expected one of: {}",
+                known.join(", ")
+            ));
         }
-    };
+    }
+    for name in config.paths.keys() {
+        if !PATH_OVERRIDE_ANALYZERS.contains(&config::analyzer_key(name).as_str()) {
+            utils::warn_once(format!(
+                "Ignoring [paths] {name}: data directories can be overridden for {}",
+                PATH_OVERRIDE_ANALYZERS.join(", ")
+            ));
+        }
+    }
 
-    // Create real-time stats manager using temporary rayon threadpool for parallel loading
-    let mut stats_manager = {
-        let pool = rayon::ThreadPoolBuilder::new()
-            .build()
-            .expect("Failed to create rayon threadpool");
+    let filter = config::AnalyzerFilter::current();
+    registry.retain(|analyzer| filter.allows(analyzer.display_name(), &config.analyzers));
+    registry
+}
 
-        let result = pool.install(|| watcher::RealtimeStatsManager::new(registry));
+async fn run_default(
+    format_overrides: config::FormatOverrides,
+    date_range: types::DateRange,
+    no_watch: bool,
+) {
+    let registry = create_analyzer_registry();
 
-        // Pool is dropped here, releasing threads
-        match result {
-            Ok(manager) => manager,
+    // Create file watcher, unless the stats should stay a snapshot
+    let file_watcher = if no_watch {
+        None
+    } else {
+        match watcher::FileWatcher::new(&registry) {
+            Ok(watcher) => Some(watcher),
             Err(e) => {
-                eprintln!("Error loading analyzer stats: {e}");
+                eprintln!("Error setting up file watcher: {e}");
                 std::process::exit(1);
             }
         }
     };
 
-    // Release memory from parallel parsing back to OS
-    release_unused_memory();
+    // Create real-time stats manager; analyzers load in the background and
+    // appear in the TUI as each one finishes
+    let mut stats_manager = watcher::RealtimeStatsManager::start(registry);
+    let snapshot_persister =
+        statusline::SnapshotPersister::spawn(stats_manager.get_stats_receiver());
+    spawn_sigterm_handler(snapshot_persister.clone());
+    let persister_for_panic = snapshot_persister.clone();
+    tui::install_panic_hook(move || flush_with_timeout(&persister_for_panic));
 
     // Create upload status for TUI
     let upload_status = Arc::new(Mutex::new(tui::UploadStatus::None));
@@ -260,6 +1106,23 @@ async fn run_default(format_options: utils::NumberFormatOptions) {
 
     // Check if auto-upload is enabled and start background upload
     let config = config::Config::load().unwrap_or(None).unwrap_or_default();
+    // A snapshot run does no background work beyond the startup load.
+    if config.archive.enabled && !no_watch {
+        let max_size_mb = config.archive.max_size_mb;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(archive::INTERVAL);
+            loop {
+                interval.tick().await;
+                let result = tokio::task::spawn_blocking(move || {
+                    archive::run(&create_analyzer_registry(), max_size_mb)
+                })
+                .await;
+                if let Ok(Err(e)) = result {
+                    utils::warn_once(format!("WARNING: couldn't archive logs: {e:#}"));
+                }
+            }
+        });
+    }
     if config.upload.auto_upload {
         if config.is_configured() {
             // For initial auto-upload, load full stats separately (sync, no threadpool for background task)
@@ -293,21 +1156,77 @@ async fn run_default(format_options: utils::NumberFormatOptions) {
         }
     }
 
-    // Start real-time TUI with file watcher
+    if config.is_configured() {
+        if no_watch {
+            upload::outbox::refresh_pending();
+        } else {
+            upload::outbox::spawn_retry_loop(config.clone(), upload_status.clone());
+        }
+    }
+
+    // Start real-time TUI with file watcher
     if let Err(e) = tui::run_tui(
         stats_manager.get_stats_receiver(),
-        &format_options,
+        &config.formatting.number_format(&format_overrides),
+        format_overrides,
         config.tui.clone(),
+        config.theme.clone(),
+        config.keys.clone(),
         upload_status.clone(),
         update_status,
         file_watcher,
         stats_manager,
+        date_range,
+        config.budget.clone(),
+        config.forecast.window_days,
+        config.copilot.plan,
+        config.limits.clone(),
+        config.notifications.clone(),
+        config.subscriptions.clone(),
     ) {
         eprintln!("Error displaying TUI: {e}");
     }
+    snapshot_persister.flush();
 }
 
-async fn run_upload(args: UploadArgs) -> Result<()> {
+/// Restore the terminal and flush the status snapshot before exiting on
+/// SIGTERM or SIGHUP, so a TUI stopped by a service manager, `kill` or a
+/// closed terminal leaves a usable shell and current numbers behind.
+fn spawn_sigterm_handler(snapshot_persister: statusline::SnapshotPersister) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+        let (Ok(mut terminate), Ok(mut hangup)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+        ) else {
+            return;
+        };
+        let code = tokio::select! {
+            _ = terminate.recv() => 143,
+            _ = hangup.recv() => 129,
+        };
+        tui::restore_terminal();
+        snapshot_persister.flush();
+        std::process::exit(code);
+    });
+    #[cfg(not(unix))]
+    drop(snapshot_persister);
+}
+
+/// Flush from a panic hook without risking a hang: the panicking thread may
+/// hold a lock the flush needs, so give up after a moment.
+fn flush_with_timeout(snapshot_persister: &statusline::SnapshotPersister) {
+    let snapshot_persister = snapshot_persister.clone();
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        snapshot_persister.flush();
+        let _ = done_tx.send(());
+    });
+    let _ = done_rx.recv_timeout(std::time::Duration::from_secs(1));
+}
+
+async fn run_upload(args: UploadArgs, json: bool) -> Result<()> {
     let registry = create_analyzer_registry();
 
     // Load stats using temporary rayon threadpool for parallel parsing
@@ -321,6 +1240,7 @@ async fn run_upload(args: UploadArgs) -> Result<()> {
 
     // Release memory from parallel parsing back to OS
     release_unused_memory();
+    store::record(&stats);
 
     // Load config file to get formatting options and upload date
     let config_file = config::Config::load().unwrap_or(None).unwrap_or_default();
@@ -335,9 +1255,26 @@ async fn run_upload(args: UploadArgs) -> Result<()> {
 
     match config::Config::load() {
         Ok(Some(config)) if config.is_configured() => {
-            let last_date_uploaded = UploadState::load()
-                .context("Failed to load upload state")?
-                .last_date_uploaded;
+            // Held until the watermark is saved, so an auto-upload in a
+            // running TUI can't send the same messages alongside this one.
+            let _upload_lock = if args.dry_run || args.show_payload {
+                None
+            } else {
+                Some(
+                    upload::lock::UploadLock::acquire(|holder| match holder {
+                        Some(pid) => eprintln!(
+                            "Waiting for another splitrail (pid {pid}) to finish uploading..."
+                        ),
+                        None => eprintln!("Waiting for another splitrail to finish uploading..."),
+                    })
+                    .await?,
+                )
+            };
+            let upload_state = UploadState::load().context("Failed to load upload state")?;
+            if upload_state.watermark_lost && !args.full {
+                anyhow::bail!(upload::WATERMARK_LOST);
+            }
+            let last_date_uploaded = upload_state.last_date_uploaded;
             let messages_to_upload = if args.full {
                 // --full flag: Flatten all messages from all analyzers
                 stats
@@ -380,18 +1317,64 @@ async fn run_upload(args: UploadArgs) -> Result<()> {
             };
 
             // Apply zero-cost filter if requested
-            let messages_to_upload = if args.zero_cost {
+            let mut messages_to_upload = if args.zero_cost {
                 utils::filter_zero_cost_messages(messages_to_upload)
             } else {
                 messages_to_upload
             };
+            upload::retain_uploadable(&mut messages_to_upload);
+            upload::privacy::scrub(&mut messages_to_upload, &config.privacy)?;
+
+            if args.show_payload {
+                return upload::show_payload(&messages_to_upload, &config);
+            }
+
+            let mut summary = upload::UploadSummary::new(&messages_to_upload, args.dry_run);
 
             // If dry-run, show summary and exit without uploading
             if args.dry_run {
-                tui::show_upload_dry_run(&messages_to_upload, &format_options);
+                if json {
+                    println!("{}", simd_json::to_string_pretty(&summary)?);
+                } else {
+                    tui::show_upload_dry_run(&summary, &format_options);
+                }
                 return Ok(());
             }
 
+            // Batches left over from failed uploads go first, ignoring their backoff.
+            upload::outbox::refresh_pending();
+            if upload::outbox::pending_count() > 0 {
+                let flushed = upload::outbox::flush(&config, true).await;
+                summary.queued_sent = flushed.sent_messages;
+                summary.queued_pending = flushed.pending;
+                if flushed.sent_messages > 0 && !json {
+                    println!(
+                        "Sent {} queued messages from earlier failed uploads",
+                        utils::format_number(flushed.sent_messages as u64, &format_options)
+                    );
+                }
+                if flushed.rejected > 0 {
+                    eprintln!(
+                        "{} upload batches were rejected by the server and set aside as .rejected files in {}",
+                        flushed.rejected,
+                        upload::outbox::outbox_dir()?.display()
+                    );
+                }
+                if let Some(error) = flushed.error {
+                    eprintln!(
+                        "{} upload batches are still queued for retry: {error:#}",
+                        flushed.pending
+                    );
+                }
+            }
+
+            if json {
+                upload::upload_message_stats(&messages_to_upload, &config, |_| {})
+                    .await
+                    .context("Failed to upload messages")?;
+                println!("{}", simd_json::to_string_pretty(&summary)?);
+                return Ok(());
+            }
             let progress_callback = tui::create_upload_progress_callback(&format_options);
             upload::upload_message_stats(&messages_to_upload, &config, progress_callback)
                 .await
@@ -417,6 +1400,7 @@ async fn run_upload(args: UploadArgs) -> Result<()> {
 }
 
 async fn run_stats(args: StatsArgs) -> Result<()> {
+    let date_range = args.date_range.to_range();
     let registry = create_analyzer_registry();
 
     // Load stats using temporary rayon threadpool for parallel parsing
@@ -430,6 +1414,208 @@ async fn run_stats(args: StatsArgs) -> Result<()> {
 
     // Release memory from parallel parsing back to OS
     release_unused_memory();
+    store::record(&stats);
+
+    let config = config::Config::load().ok().flatten().unwrap_or_default();
+    let cost_basis = subscription::CostBasis {
+        mode: args.cost_mode.unwrap_or(config.tui.cost_mode),
+        subscriptions: config.subscriptions,
+    };
+    for analyzer_stats in &mut stats.analyzer_stats {
+        // Before trimming, since fees are spread over whole months.
+        cost_basis.apply_to_stats(analyzer_stats);
+        analyzer_stats.retain_range(date_range);
+    }
+
+    if args.by_model {
+        let models = tui::logic::aggregate_model_stats(
+            stats
+                .analyzer_stats
+                .iter()
+                .flat_map(|analyzer_stats| analyzer_stats.daily_stats.values()),
+        );
+        let json = if args.pretty {
+            simd_json::to_string_pretty(&models)?
+        } else {
+            simd_json::to_string(&models)?
+        };
+        println!("{json}");
+        return Ok(());
+    }
+
+    if args.by_provider {
+        #[derive(serde::Serialize)]
+        struct ProviderRollup {
+            totals: Vec<types::ProviderStats>,
+            daily: BTreeMap<String, Vec<types::ProviderStats>>,
+            monthly: BTreeMap<String, Vec<types::ProviderStats>>,
+        }
+
+        let all_days = || {
+            stats
+                .analyzer_stats
+                .iter()
+                .flat_map(|analyzer_stats| analyzer_stats.daily_stats.iter())
+        };
+        let mut days: BTreeMap<&str, Vec<&types::DailyStats>> = BTreeMap::new();
+        let mut months: BTreeMap<&str, Vec<&types::DailyStats>> = BTreeMap::new();
+        for (date, day) in all_days() {
+            days.entry(date.as_str()).or_default().push(day);
+            months.entry(&date[..7]).or_default().push(day);
+        }
+        let rollup = |group: Vec<&types::DailyStats>| {
+            tui::logic::aggregate_provider_stats(&tui::logic::aggregate_model_stats(group))
+        };
+        let output = ProviderRollup {
+            totals: rollup(all_days().map(|(_, day)| day).collect()),
+            daily: days
+                .into_iter()
+                .map(|(date, group)| (date.to_string(), rollup(group)))
+                .filter(|(_, providers)| !providers.is_empty())
+                .collect(),
+            monthly: months
+                .into_iter()
+                .map(|(month, group)| (month.to_string(), rollup(group)))
+                .filter(|(_, providers)| !providers.is_empty())
+                .collect(),
+        };
+        let json = if args.pretty {
+            simd_json::to_string_pretty(&output)?
+        } else {
+            simd_json::to_string(&output)?
+        };
+        println!("{json}");
+        return Ok(());
+    }
+
+    if args.heatmap {
+        #[derive(serde::Serialize)]
+        struct HeatmapEntry<'a> {
+            analyzer: &'a str,
+            /// Rows are Monday..Sunday, columns are local hours 0..23.
+            messages: [[u32; 24]; 7],
+        }
+
+        let mut entries = vec![HeatmapEntry {
+            analyzer: budget::ALL_TOOLS,
+            messages: tui::aggregation::HeatGrid::from_days(
+                stats
+                    .analyzer_stats
+                    .iter()
+                    .flat_map(|analyzer_stats| analyzer_stats.daily_stats.values()),
+            )
+            .cells,
+        }];
+        entries.extend(
+            stats
+                .analyzer_stats
+                .iter()
+                .map(|analyzer_stats| HeatmapEntry {
+                    analyzer: &analyzer_stats.analyzer_name,
+                    messages: tui::aggregation::HeatGrid::from_days(
+                        analyzer_stats.daily_stats.values(),
+                    )
+                    .cells,
+                }),
+        );
+        let json = if args.pretty {
+            simd_json::to_string_pretty(&entries)?
+        } else {
+            simd_json::to_string(&entries)?
+        };
+        println!("{json}");
+        return Ok(());
+    }
+
+    if args.throughput {
+        #[derive(serde::Serialize)]
+        struct ThroughputEntry<'a> {
+            analyzer: &'a str,
+            throughput: stats::throughput::Throughput,
+        }
+
+        let mut entries = vec![ThroughputEntry {
+            analyzer: budget::ALL_TOOLS,
+            throughput: stats::throughput::Throughput::from_days(
+                stats
+                    .analyzer_stats
+                    .iter()
+                    .flat_map(|analyzer_stats| analyzer_stats.daily_stats.values()),
+            ),
+        }];
+        entries.extend(
+            stats
+                .analyzer_stats
+                .iter()
+                .map(|analyzer_stats| ThroughputEntry {
+                    analyzer: &analyzer_stats.analyzer_name,
+                    throughput: stats::throughput::Throughput::from_days(
+                        analyzer_stats.daily_stats.values(),
+                    ),
+                }),
+        );
+        let json = if args.pretty {
+            simd_json::to_string_pretty(&entries)?
+        } else {
+            simd_json::to_string(&entries)?
+        };
+        println!("{json}");
+        return Ok(());
+    }
+
+    if args.cache {
+        use stats::cache::CacheEfficiency;
+
+        #[derive(serde::Serialize)]
+        struct CacheEntry<'a> {
+            analyzer: &'a str,
+            total: CacheEfficiency,
+            daily: BTreeMap<&'a str, CacheEfficiency>,
+            models: BTreeMap<String, CacheEfficiency>,
+        }
+
+        fn entry<'a>(
+            analyzer: &'a str,
+            days: Vec<(&'a String, &'a types::DailyStats)>,
+        ) -> CacheEntry<'a> {
+            // Several tools' days share a date in the combined entry.
+            let mut daily: BTreeMap<&str, CacheEfficiency> = BTreeMap::new();
+            for (date, day) in &days {
+                *daily.entry(date.as_str()).or_default() += CacheEfficiency::from_day(day);
+            }
+            daily.retain(|_, cache| cache.hit_ratio.is_some());
+            CacheEntry {
+                analyzer,
+                total: CacheEfficiency::from_periods(days.iter().map(|(_, day)| *day)),
+                daily,
+                models: tui::logic::aggregate_model_stats(days.iter().map(|(_, day)| *day))
+                    .iter()
+                    .map(|model| (model.model.clone(), CacheEfficiency::from_model(model)))
+                    .collect(),
+            }
+        }
+        let mut entries = vec![entry(
+            budget::ALL_TOOLS,
+            stats
+                .analyzer_stats
+                .iter()
+                .flat_map(|analyzer_stats| analyzer_stats.daily_stats.iter())
+                .collect(),
+        )];
+        entries.extend(stats.analyzer_stats.iter().map(|analyzer_stats| {
+            entry(
+                &analyzer_stats.analyzer_name,
+                analyzer_stats.daily_stats.iter().collect(),
+            )
+        }));
+        let json = if args.pretty {
+            simd_json::to_string_pretty(&entries)?
+        } else {
+            simd_json::to_string(&entries)?
+        };
+        println!("{json}");
+        return Ok(());
+    }
 
     if !args.include_messages {
         for analyzer_stats in &mut stats.analyzer_stats {
@@ -448,7 +1634,814 @@ async fn run_stats(args: StatsArgs) -> Result<()> {
     Ok(())
 }
 
-async fn handle_config_subcommand(config_args: ConfigArgs) {
+fn run_statusline(args: StatuslineArgs, json: bool, format_options: &utils::NumberFormatOptions) {
+    if args.refresh {
+        let registry = create_analyzer_registry();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        let refreshed = pool
+            .install(|| registry.load_all_stats_views_parallel())
+            .and_then(|views| statusline::HotSnapshot::from_views(&views.analyzer_stats).save());
+        if let Err(e) = refreshed {
+            eprintln!("Error refreshing status snapshot: {e:#}");
+        }
+    }
+
+    // Any read failure is reported the same way as an old snapshot.
+    let snapshot = statusline::HotSnapshot::load()
+        .ok()
+        .flatten()
+        .filter(|snapshot| snapshot.is_fresh(chrono::Local::now(), args.max_age));
+    if json {
+        // `null` when stale, like the text form's "splitrail: stale".
+        match simd_json::to_string(&snapshot) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("Error serializing status snapshot: {e}"),
+        }
+        return;
+    }
+    println!(
+        "{}",
+        statusline::render(snapshot.as_ref(), args.tmux, format_options)
+    );
+}
+
+fn run_query(
+    args: QueryArgs,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let parse_fields = |names: &[String]| -> Result<Vec<query::Field>> {
+        names
+            .iter()
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| query::Field::parse(name))
+            .collect()
+    };
+    let sums = parse_fields(&args.sum)?;
+    if let Some(field) = sums.iter().find(|field| !field.is_numeric()) {
+        anyhow::bail!("--sum needs numeric fields, but '{}' is text", field.name());
+    }
+    let query = query::Query {
+        filter: query::parse_filter(args.filter.as_deref().unwrap_or_default())?,
+        group_by: parse_fields(&args.group_by)?,
+        sums,
+    };
+
+    let registry = create_analyzer_registry();
+    let stats = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_parallel())?
+    };
+    let rows = query.run(&stats);
+
+    if json {
+        let rows: Vec<BTreeMap<&str, simd_json::OwnedValue>> = rows
+            .iter()
+            .map(|row| {
+                let mut object = BTreeMap::new();
+                for (field, value) in query.group_by.iter().zip(&row.group) {
+                    object.insert(field.name(), value.clone().into());
+                }
+                object.insert("messages", row.messages.into());
+                for (field, total) in query.sums.iter().zip(&row.sums) {
+                    object.insert(field.name(), (*total).into());
+                }
+                object
+            })
+            .collect();
+        println!("{}", simd_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No messages matched.");
+        return Ok(());
+    }
+    let format_total = |field: query::Field, total: f64| {
+        if field == query::Field::Cost {
+            format!("{}{total:.2}", format_options.currency_symbol)
+        } else {
+            utils::format_number(total.round().max(0.0) as u64, format_options)
+        }
+    };
+    let header: Vec<String> = query
+        .group_by
+        .iter()
+        .map(|field| field.name().to_string())
+        .chain(std::iter::once("messages".to_string()))
+        .chain(query.sums.iter().map(|field| field.name().to_string()))
+        .collect();
+    let mut lines = vec![header];
+    for row in &rows {
+        lines.push(
+            row.group
+                .iter()
+                .cloned()
+                .chain(std::iter::once(utils::format_number(
+                    row.messages,
+                    format_options,
+                )))
+                .chain(
+                    query
+                        .sums
+                        .iter()
+                        .zip(&row.sums)
+                        .map(|(field, total)| format_total(*field, *total)),
+                )
+                .collect(),
+        );
+    }
+    let widths: Vec<usize> = (0..lines[0].len())
+        .map(|column| {
+            lines
+                .iter()
+                .map(|line| line[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let group_columns = query.group_by.len();
+    for line in &lines {
+        let cells: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                if column < group_columns {
+                    format!("{cell:<width$}")
+                } else {
+                    format!("{cell:>width$}")
+                }
+            })
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+    Ok(())
+}
+
+fn run_merge(
+    args: MergeArgs,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let exports = args
+        .files
+        .iter()
+        .map(|path| team::load_export(path))
+        .collect::<Result<Vec<_>>>()?;
+    let report = team::merge(&exports, args.date_range.to_range());
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let cost = |amount: f64| format!("{}{amount:.2}", format_options.currency_symbol);
+    let row = |name: &str, totals: &team::Totals, extra: String| {
+        println!(
+            "  {name:<24} {:>12} {:>14} {:>10}  {extra}",
+            cost(totals.cost),
+            utils::format_number(totals.tokens(), format_options),
+            utils::format_number(totals.messages, format_options),
+        );
+    };
+    println!("Team usage for {}", report.range);
+    println!(
+        "  {:<24} {:>12} {:>14} {:>10}  Tools",
+        "User", "Cost", "Tokens", "Messages"
+    );
+    for member in &report.members {
+        let tools: Vec<&str> = member.tools.keys().map(String::as_str).collect();
+        row(&member.user, &member.totals, tools.join(", "));
+    }
+    println!();
+    println!(
+        "  {:<24} {:>12} {:>14} {:>10}  Users",
+        "Tool", "Cost", "Tokens", "Messages"
+    );
+    for tool in &report.tools {
+        row(&tool.tool, &tool.totals, tool.users.to_string());
+    }
+    println!();
+    row(
+        "Total",
+        &report.totals,
+        match report.members.len() {
+            1 => "1 user".to_string(),
+            users => format!("{users} users"),
+        },
+    );
+    Ok(())
+}
+
+fn run_import(args: ImportArgs, json: bool) -> Result<()> {
+    let (kind, path, until) = match (args.raw, args.subcommand) {
+        (Some(path), _) => {
+            let summary = backup::import(&path)?;
+            return print_backup_summary(&summary, "Restored", json);
+        }
+        (None, Some(ImportSubcommands::Ccusage { path, until })) => {
+            (import::ImportKind::Ccusage, path, until)
+        }
+        (None, Some(ImportSubcommands::File { path, until })) => {
+            (import::ImportKind::Generic, path, until)
+        }
+        (None, None) => {
+            anyhow::bail!("Say what to import: `ccusage <FILE>`, `file <FILE>` or `--raw <FILE>`")
+        }
+    };
+    let summary = import::run(kind, &path, until)?;
+    if json {
+        println!("{}", simd_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+    println!(
+        "✅ Imported {} records over {} days ({} to {}), ${:.2} in total",
+        summary.records,
+        summary.days,
+        summary.first_day.as_deref().unwrap_or("?"),
+        summary.last_day.as_deref().unwrap_or("?"),
+        summary.cost
+    );
+    println!("   Saved to {}", summary.path.display());
+    if summary.skipped > 0 {
+        println!("   {} records after --until were left out", summary.skipped);
+    }
+    if kind == import::ImportKind::Ccusage && until.is_none() {
+        println!(
+            "   Note: ccusage reads the same Claude Code logs splitrail does. If those logs \
+             still exist, re-import with --until set to the day before the oldest one, or \
+             those days count twice."
+        );
+    }
+    Ok(())
+}
+
+fn run_export(args: ExportArgs, json: bool) -> Result<()> {
+    let registry = create_analyzer_registry();
+    let stats = registry.load_all_stats_parallel()?;
+    let summary = backup::export(&stats, &args.raw)?;
+    print_backup_summary(&summary, "Exported", json)
+}
+
+fn run_archive(json: bool) -> Result<()> {
+    let config = config::Config::load().unwrap_or(None).unwrap_or_default();
+    let summary = archive::run(&create_analyzer_registry(), config.archive.max_size_mb)?;
+    if json {
+        println!("{}", simd_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+    println!(
+        "✅ Archived {} files to {} ({:.1} MB)",
+        summary.copied,
+        summary.dir.display(),
+        summary.size_bytes as f64 / (1024.0 * 1024.0)
+    );
+    if summary.evicted + summary.skipped > 0 {
+        println!(
+            "   {} older files left out to stay under the size cap (archive-max-size-mb)",
+            summary.evicted + summary.skipped
+        );
+    }
+    Ok(())
+}
+
+fn run_rescan(
+    args: RescanArgs,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let registry = create_analyzer_registry();
+    let names: Vec<&'static str> = match &args.analyzer {
+        Some(name) => vec![
+            registry
+                .display_names()
+                .into_iter()
+                .find(|known| known.eq_ignore_ascii_case(name))
+                .with_context(|| {
+                    format!(
+                        "Unknown analyzer '{name}'. Use one of: {}",
+                        registry.display_names().join(", ")
+                    )
+                })?,
+        ],
+        None => registry
+            .available_analyzers()
+            .into_iter()
+            .map(|analyzer| analyzer.display_name())
+            .collect(),
+    };
+
+    let mut rows = Vec::with_capacity(names.len());
+    for name in names {
+        let started = std::time::Instant::now();
+        let sources = registry.rescan_analyzer(name)?;
+        let (sessions, messages, cost) =
+            registry.get_cached_view(name).map_or((0, 0, 0.0), |view| {
+                let view = view.read();
+                let (messages, cost) =
+                    view.daily_stats
+                        .values()
+                        .fold((0u64, 0.0), |(n, cost), day| {
+                            (
+                                n + u64::from(day.user_messages + day.ai_messages),
+                                cost + day.stats.cost(),
+                            )
+                        });
+                (view.session_aggregates.len(), messages, cost)
+            });
+        rows.push((name, sources, sessions, messages, cost, started.elapsed()));
+    }
+
+    if json {
+        let rows: Vec<_> = rows
+            .iter()
+            .map(|(name, sources, sessions, messages, cost, elapsed)| {
+                simd_json::json!({
+                    "analyzer": *name,
+                    "sources": *sources,
+                    "sessions": *sessions,
+                    "messages": *messages,
+                    "cost": *cost,
+                    "elapsedMs": elapsed.as_millis() as u64,
+                })
+            })
+            .collect();
+        println!("{}", simd_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No analyzers with data found.");
+    }
+    for (name, sources, sessions, messages, cost, elapsed) in rows {
+        println!(
+            "{name}: {} files, {} sessions, {} messages, {}{cost:.prec$} ({:.1}s)",
+            utils::format_number(sources as u64, format_options),
+            utils::format_number(sessions as u64, format_options),
+            utils::format_number(messages, format_options),
+            format_options.currency_symbol,
+            elapsed.as_secs_f64(),
+            prec = format_options.cost_decimal_places,
+        );
+    }
+    Ok(())
+}
+
+fn print_backup_summary(summary: &backup::BackupSummary, verb: &str, json: bool) -> Result<()> {
+    if json {
+        println!("{}", simd_json::to_string_pretty(summary)?);
+        return Ok(());
+    }
+    let total: usize = summary.messages.values().sum();
+    println!(
+        "✅ {verb} {total} messages from {} tools ({})",
+        summary.messages.len(),
+        summary.path.display()
+    );
+    for (tool, count) in &summary.messages {
+        match summary.added.get(tool) {
+            Some(added) => println!("   {tool}: {count} ({added} new)"),
+            None => println!("   {tool}: {count}"),
+        }
+    }
+    Ok(())
+}
+
+fn run_sync(args: SyncArgs, json: bool, freeze_deleted_sessions: bool) -> Result<()> {
+    match args.subcommand {
+        SyncSubcommands::Export { archive } => {
+            let entries = sync::export(&archive)?;
+            if json {
+                let output = simd_json::json!({
+                    "archive": archive.to_string_lossy(),
+                    "entries": entries,
+                });
+                println!("{}", simd_json::to_string_pretty(&output)?);
+                return Ok(());
+            }
+            println!(
+                "✅ Exported {} to {}",
+                entries.join(", "),
+                archive.display()
+            );
+        }
+        SyncSubcommands::Import { archive } => {
+            let summary = sync::import(&archive)?;
+            if json {
+                println!("{}", simd_json::to_string_pretty(&summary)?);
+                return Ok(());
+            }
+            println!("✅ Imported {}", archive.display());
+            if summary.snapshot_replaced {
+                println!("   Status snapshot replaced with the newer one");
+            }
+            println!(
+                "   {} Claude Code history entries added",
+                summary.history_added
+            );
+            if summary.history_added > 0 && !freeze_deleted_sessions {
+                println!(
+                    "   Note: imported sessions are pruned on the next scan unless \
+                     `splitrail config set freeze-deleted-sessions true` is set"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Summarize the `period` ending today.
+fn build_summary_report(period: report::Period) -> Result<report::Report> {
+    let registry = create_analyzer_registry();
+    let views = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_views_parallel())?
+    };
+    let config = config::Config::load().ok().flatten().unwrap_or_default();
+    let columns =
+        tui::columns::ColumnLayout::from_config(&config.tui.columns, &config.tui.hidden_columns);
+    Ok(report::Report::build(
+        &views.analyzer_stats,
+        period,
+        chrono::Local::now().date_naive(),
+    )
+    .with_columns(&columns))
+}
+
+fn run_summary_report(
+    period: report::Period,
+    format: report::Format,
+    output: Option<&std::path::Path>,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let report = build_summary_report(period)?;
+    let rendered = if json {
+        simd_json::to_string_pretty(&report)?
+    } else {
+        report.render(format, format_options)
+    };
+    match output {
+        Some(path) => {
+            persist::write_atomic(path, rendered, "report")?;
+            eprintln!("✅ Wrote {}", path.display());
+        }
+        None => println!("{rendered}"),
+    }
+    Ok(())
+}
+
+async fn run_notify(args: NotifyArgs, format_options: &utils::NumberFormatOptions) -> Result<()> {
+    use upload::webhook;
+
+    let template = match &args.template {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None => webhook::DEFAULT_TEMPLATE.to_string(),
+    };
+    let style = args
+        .style
+        .unwrap_or_else(|| webhook::Style::detect(&args.webhook));
+    let report = tokio::task::spawn_blocking(move || build_summary_report(args.period)).await??;
+    let (body, content_type) = webhook::payload(&report, style, &template, format_options)?;
+    if args.dry_run {
+        println!("{body}");
+        return Ok(());
+    }
+    webhook::post(&args.webhook, body, content_type).await?;
+    println!(
+        "✅ Posted the {} ({})",
+        report.title.to_lowercase(),
+        report.range
+    );
+    Ok(())
+}
+
+fn run_diff(args: DiffArgs, json: bool, format_options: &utils::NumberFormatOptions) -> Result<()> {
+    use stats::compare::{self, Metric, Periods};
+
+    let periods = match (args.from, args.to) {
+        (Some(previous), Some(current)) => Periods { previous, current },
+        _ => Periods::trailing(args.period.days(), chrono::Local::now().date_naive()),
+    };
+    let registry = create_analyzer_registry();
+    let views = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_views_parallel())?
+    };
+    let comparisons = compare::compare(&views.analyzer_stats, periods);
+
+    if json {
+        let output = simd_json::json!({
+            "previous": periods.previous.to_string(),
+            "current": periods.current.to_string(),
+            "analyzers": comparisons,
+        });
+        println!("{}", simd_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("{} → {}", periods.previous, periods.current);
+    if comparisons.is_empty() {
+        println!("  No usage in either period.");
+    }
+    let figure = |metric: Metric, value: f64| match metric {
+        Metric::Cost => format!(
+            "{}{value:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        ),
+        _ => utils::format_number(value as u64, format_options),
+    };
+    for comparison in &comparisons {
+        println!();
+        println!("{}", comparison.analyzer);
+        for (metric, previous, current, change) in comparison.rows() {
+            if previous == 0.0 && current == 0.0 {
+                continue;
+            }
+            println!(
+                "  {:<17} {:>14} → {:<14} {}",
+                metric.label(),
+                figure(metric, previous),
+                figure(metric, current),
+                compare::format_change(change, current)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_insights_report(
+    date_range: DateRangeArgs,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let mut range = date_range.to_range();
+    if range.is_unbounded() {
+        // 29 days back plus today.
+        range.since = types::DateRange::parse_bound("29d");
+    }
+
+    let registry = create_analyzer_registry();
+    let views = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_views_parallel())?
+    };
+    let summary = insights::UsageSummary::from_views(
+        &views.analyzer_stats,
+        range,
+        &format_options.currency_symbol,
+    );
+    let insights = insights::evaluate(&summary);
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&insights)?);
+        return Ok(());
+    }
+
+    println!("Spend insights for {range}");
+    if insights.is_empty() {
+        println!("  Nothing stands out. Usage in this period already looks efficient.");
+    }
+    for insight in &insights {
+        println!("  • {}", insight.message);
+    }
+    Ok(())
+}
+
+fn run_git_report(
+    git_config: &config::GitConfig,
+    date_range: DateRangeArgs,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    anyhow::ensure!(
+        git_config.enabled,
+        "The git integration is off. Turn it on with 'splitrail config set git-integration true'."
+    );
+    let range = date_range.to_range();
+    let registry = create_analyzer_registry();
+    let projects = integrations::git::report(&registry, range)?;
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&projects)?);
+        return Ok(());
+    }
+
+    if projects.is_empty() {
+        println!("No Claude Code sessions in a git repository.");
+        return Ok(());
+    }
+    let money = |amount: f64| {
+        format!(
+            "{}{amount:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        )
+    };
+    for project in &projects {
+        println!("{}", project.repository);
+        println!(
+            "  {} sessions, {}, {} commits, +{} -{} lines",
+            project.sessions,
+            money(project.cost),
+            project.commits,
+            utils::format_number(project.lines_added, format_options),
+            utils::format_number(project.lines_deleted, format_options),
+        );
+        if let Some(per_commit) = project.cost_per_commit {
+            println!("  {} per commit", money(per_commit));
+        }
+        if let Some(per_kloc) = project.cost_per_kloc {
+            println!("  {} per 1k lines changed", money(per_kloc));
+        }
+    }
+    Ok(())
+}
+
+fn run_languages_report(date_range: DateRangeArgs, json: bool) -> Result<()> {
+    /// Languages named per line before the rest are summed up.
+    const SUMMARY_LANGUAGES: usize = 4;
+
+    let range = date_range.to_range();
+    let registry = create_analyzer_registry();
+    let analyzers = registry
+        .available_analyzers()
+        .iter()
+        .map(|analyzer| {
+            let name = analyzer.display_name().to_string();
+            let messages = registry.load_analyzer_messages(&name)?;
+            Ok((name, messages))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let report = stats::languages::report(&analyzers, range);
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if report.is_empty() {
+        println!("No file reads, writes, or edits with a file extension in {range}.");
+        return Ok(());
+    }
+    let summary = |languages| stats::languages::summary_line(languages, SUMMARY_LANGUAGES);
+    println!("Languages for {range}");
+    println!("  {}", summary(&report.overall));
+    println!();
+    println!("By day");
+    for day in &report.days {
+        println!("  {}  {}", day.date, summary(&day.languages));
+    }
+    println!();
+    println!("By project");
+    for project in &report.projects {
+        println!(
+            "  {} {}  {} operations  {}",
+            project.analyzer,
+            project.project,
+            project.operations,
+            summary(&project.languages)
+        );
+    }
+    Ok(())
+}
+
+fn run_budget_status(
+    budget_config: &config::BudgetConfig,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    if budget_config.is_empty() {
+        if json {
+            println!("[]");
+        } else {
+            println!(
+                "No budgets configured. Set one with 'splitrail config set budget-monthly-usd 200'."
+            );
+        }
+        return Ok(());
+    }
+
+    let registry = create_analyzer_registry();
+    let views = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_views_parallel())?
+    };
+    let statuses = budget::evaluate(
+        budget_config,
+        &views.analyzer_stats,
+        chrono::Local::now().date_naive(),
+    );
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&statuses)?);
+        return Ok(());
+    }
+
+    let scope_width = statuses.iter().map(|s| s.scope.len()).max().unwrap_or(0);
+    let money = |amount: f64| {
+        format!(
+            "{}{amount:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        )
+    };
+    for status in &statuses {
+        let marker = match status.level {
+            budget::BudgetLevel::Ok => "ok",
+            budget::BudgetLevel::Warning => "warning",
+            budget::BudgetLevel::Exceeded => "EXCEEDED",
+        };
+        println!(
+            "{:<scope_width$}  {:<5}  {} {:>4.0}%  {} / {}  {marker}",
+            status.scope,
+            status.period.label(),
+            budget::progress_bar(status.fraction(), 20),
+            status.fraction() * 100.0,
+            money(status.spent),
+            money(status.limit),
+        );
+    }
+    Ok(())
+}
+
+fn run_forecast(
+    window_days: u32,
+    json: bool,
+    format_options: &utils::NumberFormatOptions,
+) -> Result<()> {
+    let registry = create_analyzer_registry();
+    let views = {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .build()
+            .expect("Failed to create rayon threadpool");
+        pool.install(|| registry.load_all_stats_views_parallel())?
+    };
+    let forecasts = forecast::evaluate(
+        &views.analyzer_stats,
+        window_days,
+        chrono::Local::now().date_naive(),
+    );
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&forecasts)?);
+        return Ok(());
+    }
+
+    let scope_width = forecasts.iter().map(|f| f.scope.len()).max().unwrap_or(0);
+    let money = |amount: f64| {
+        format!(
+            "{}{amount:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        )
+    };
+    for forecast in &forecasts {
+        println!(
+            "{:<scope_width$}  {} so far, {}/day over {} days, {} days left  ->  {} this month",
+            forecast.scope,
+            money(forecast.month_to_date),
+            money(forecast.average_daily),
+            forecast.window_days,
+            forecast.days_left,
+            money(forecast.projected),
+        );
+    }
+    Ok(())
+}
+
+fn run_bench(json: bool) -> Result<()> {
+    let registry = create_analyzer_registry();
+    let timings = rayon::ThreadPoolBuilder::new()
+        .build()?
+        .install(|| bench::run(&registry))?;
+
+    if json {
+        println!("{}", simd_json::to_string_pretty(&timings)?);
+        return Ok(());
+    }
+    for line in bench::render(&timings) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+async fn handle_config_subcommand(config_args: ConfigArgs, json: bool) {
     match config_args.subcommand {
         ConfigSubcommands::Init { overwrite } => {
             if let Err(e) = config::create_default_config(overwrite) {
@@ -457,13 +2450,33 @@ async fn handle_config_subcommand(config_args: ConfigArgs) {
             }
         }
         ConfigSubcommands::Show => {
-            if let Err(e) = config::show_config() {
+            let shown = if json {
+                config::show_config_json()
+            } else {
+                config::show_config()
+            };
+            if let Err(e) = shown {
                 eprintln!("Error showing config: {e}");
                 std::process::exit(1);
             }
         }
-        ConfigSubcommands::Set { key, value } => {
-            if let Err(e) = config::set_config_value(&key, &value) {
+        ConfigSubcommands::Edit => {
+            if let Err(e) = config::edit_config() {
+                eprintln!("Error editing config: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        ConfigSubcommands::Set {
+            key,
+            value,
+            keychain,
+        } => {
+            let result = match (keychain, key.as_str()) {
+                (true, "api-token") => config::set_api_token_in_keychain(&value),
+                (true, _) => Err(anyhow::anyhow!("--keychain only applies to api-token")),
+                (false, _) => config::set_config_value(&key, &value),
+            };
+            if let Err(e) = result {
                 eprintln!("Error setting config: {e}");
                 std::process::exit(1);
             }
@@ -486,3 +2499,42 @@ pub fn release_unused_memory() {
 /// No-op when mimalloc is disabled.
 #[cfg(not(feature = "mimalloc"))]
 pub fn release_unused_memory() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ValueEnum;
+
+    #[test]
+    fn completions_and_man_pages_generate_for_the_whole_cli() {
+        Cli::command().debug_assert();
+
+        for shell in clap_complete::Shell::value_variants() {
+            let mut script = Vec::new();
+            clap_complete::generate(*shell, &mut Cli::command(), "splitrail", &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("statusline"), "{shell} completions");
+        }
+
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Cli::command())
+            .render(&mut page)
+            .unwrap();
+        assert!(String::from_utf8(page).unwrap().contains("splitrail"));
+    }
+
+    #[test]
+    fn every_built_in_tool_is_registered() {
+        let names = registry_with_all_analyzers(None).display_names();
+        for info in application::APPS
+            .iter()
+            .filter(|info| info.application != types::Application::Custom)
+        {
+            assert!(names.contains(&info.name), "{} isn't registered", info.name);
+        }
+
+        let help = Cli::command().render_help().to_string();
+        assert!(help.contains("Pi Agent (pi_agent)"), "{help}");
+        assert!(help.contains("OpenCode (opencode)"), "{help}");
+    }
+}
@@ -6,6 +6,8 @@ use std::sync::{Arc, OnceLock};
 
 use crate::utils::warn_once;
 
+pub mod remote;
+
 /// Represents different pricing tier structures for various models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricingTier {
@@ -135,6 +137,291 @@ pub struct ModelInfo {
     pub is_estimated: bool,
 }
 
+/// Who bills for a model. Open-weight models are grouped together since they
+/// are usually served by whichever inference host the tool is pointed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    Anthropic,
+    #[serde(rename = "openai")]
+    OpenAI,
+    Google,
+    OpenWeights,
+    Other,
+}
+
+/// Name fragments of open-weight model families.
+const OPEN_WEIGHT_FAMILIES: &[&str] = &[
+    "gpt-oss",
+    "gemma",
+    "qwen",
+    "deepseek",
+    "glm",
+    "kimi",
+    "minimax",
+    "llama",
+    "mistral",
+    "codestral",
+    "devstral",
+    "mimo",
+    "step-",
+    "solar",
+    "longcat",
+    "phi-",
+    "granite",
+    "nemotron",
+];
+
+impl Provider {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Anthropic => "Anthropic",
+            Self::OpenAI => "OpenAI",
+            Self::Google => "Google",
+            Self::OpenWeights => "Open weights",
+            Self::Other => "Other",
+        }
+    }
+
+    /// Classify a model by name, ignoring router prefixes such as
+    /// `openrouter/` and Bedrock region prefixes such as `us.anthropic.`.
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        let name = model
+            .rsplit_once('/')
+            .map_or(model.as_str(), |(_, name)| name);
+
+        if name.contains("claude") {
+            Self::Anthropic
+        } else if OPEN_WEIGHT_FAMILIES
+            .iter()
+            .any(|family| name.contains(family))
+        {
+            Self::OpenWeights
+        } else if name.starts_with("gpt-")
+            || name.starts_with("chatgpt")
+            || name.contains("codex")
+            || name
+                .strip_prefix('o')
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            Self::OpenAI
+        } else if name.starts_with("gemini") {
+            Self::Google
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Simple per-model rates from the config's `[pricing]` section, e.g.
+/// `[pricing."claude-sonnet-4-5"] input_per_1m = 3.0`. Set fields replace the
+/// built-in base rates; unset ones keep them. Rates above the base, such as
+/// long-context tiers, other service tiers and older dated prices, are
+/// scaled by the same factor so the model keeps its shape. Unknown models
+/// need both the input and output rates.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PriceOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_per_1m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_per_1m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_per_1m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_per_1m: Option<f64>,
+}
+
+/// A model's standard rates for the first tier, which `[pricing]` rates
+/// replace.
+struct BaseRates {
+    input: f64,
+    output: f64,
+    cache_read: Option<f64>,
+    cache_write: Option<f64>,
+}
+
+impl BaseRates {
+    fn of(info: &ModelInfo) -> Self {
+        let (input, output) = match &info.pricing {
+            PricingStructure::Flat {
+                input_per_1m,
+                output_per_1m,
+            } => (*input_per_1m, *output_per_1m),
+            PricingStructure::Tiered(tiered) => tiered
+                .tiers
+                .first()
+                .map_or((0.0, 0.0), |tier| (tier.input_per_1m, tier.output_per_1m)),
+        };
+        let (cache_read, cache_write) = match &info.caching {
+            CachingSupport::None => (None, None),
+            CachingSupport::OpenAI {
+                cached_input_per_1m,
+            } => (Some(*cached_input_per_1m), None),
+            CachingSupport::Anthropic {
+                cache_write_per_1m,
+                cache_read_per_1m,
+            }
+            | CachingSupport::OpenAIWithWrites {
+                cache_write_per_1m,
+                cache_read_per_1m,
+            } => (Some(*cache_read_per_1m), Some(*cache_write_per_1m)),
+            CachingSupport::Tiered(tiered) => (
+                tiered.tiers.first().map(|tier| tier.cached_input_per_1m),
+                None,
+            ),
+        };
+        Self {
+            input,
+            output,
+            cache_read,
+            cache_write,
+        }
+    }
+}
+
+/// `rate` moved by the factor that turns `base` into `new`, or `new` itself
+/// when there's no base rate to scale from.
+fn scale_rate(rate: f64, base: Option<f64>, new: Option<f64>) -> f64 {
+    match (new, base) {
+        (None, _) => rate,
+        (Some(new), Some(base)) if base > 0.0 => rate * new / base,
+        (Some(new), _) => new,
+    }
+}
+
+impl PriceOverride {
+    /// `base` with this override applied to its standard pricing, and the
+    /// same change scaled into its tiers, service tiers and dated pricing.
+    fn apply_to(&self, base: &ModelInfo) -> ModelInfo {
+        let rates = BaseRates::of(base);
+        let changed = *self != Self::default();
+        ModelInfo {
+            pricing: self.scale_pricing(&base.pricing, &rates),
+            caching: self.scale_caching(&base.caching, &rates),
+            service_tiers: base
+                .service_tiers
+                .iter()
+                .map(|(tier, pricing)| {
+                    let pricing = ServiceTierPricing {
+                        pricing: self.scale_pricing(&pricing.pricing, &rates),
+                        caching: self.scale_caching(&pricing.caching, &rates),
+                    };
+                    (*tier, pricing)
+                })
+                .collect(),
+            dated_pricing: base
+                .dated_pricing
+                .iter()
+                .map(|dated| DatedPricing {
+                    valid_until: dated.valid_until,
+                    pricing: self.scale_pricing(&dated.pricing, &rates),
+                    caching: self.scale_caching(&dated.caching, &rates),
+                })
+                .collect(),
+            input_token_semantics: base.input_token_semantics,
+            is_estimated: base.is_estimated && !changed,
+        }
+    }
+
+    fn scale_pricing(&self, pricing: &PricingStructure, rates: &BaseRates) -> PricingStructure {
+        let input = |rate| scale_rate(rate, Some(rates.input), self.input_per_1m);
+        let output = |rate| scale_rate(rate, Some(rates.output), self.output_per_1m);
+        match pricing {
+            PricingStructure::Flat {
+                input_per_1m,
+                output_per_1m,
+            } => PricingStructure::Flat {
+                input_per_1m: input(*input_per_1m),
+                output_per_1m: output(*output_per_1m),
+            },
+            PricingStructure::Tiered(tiered) => PricingStructure::Tiered(TieredPricing {
+                tiers: tiered
+                    .tiers
+                    .iter()
+                    .map(|tier| PricingTier {
+                        max_tokens: tier.max_tokens,
+                        input_per_1m: input(tier.input_per_1m),
+                        output_per_1m: output(tier.output_per_1m),
+                    })
+                    .collect(),
+                bracket_pricing: tiered.bracket_pricing,
+            }),
+        }
+    }
+
+    /// Tiered cache reads have no write rate, so a write override on them is
+    /// ignored; [`Registry::apply_overrides`] warns about it.
+    fn scale_caching(&self, caching: &CachingSupport, rates: &BaseRates) -> CachingSupport {
+        let read = |rate| scale_rate(rate, rates.cache_read, self.cache_read_per_1m);
+        let write = |rate| scale_rate(rate, rates.cache_write, self.cache_write_per_1m);
+        match caching {
+            CachingSupport::None => match (self.cache_read_per_1m, self.cache_write_per_1m) {
+                (read, Some(write)) => CachingSupport::Anthropic {
+                    cache_write_per_1m: write,
+                    cache_read_per_1m: read.unwrap_or(0.0),
+                },
+                (Some(read), None) => CachingSupport::OpenAI {
+                    cached_input_per_1m: read,
+                },
+                (None, None) => CachingSupport::None,
+            },
+            CachingSupport::OpenAI {
+                cached_input_per_1m,
+            } => match self.cache_write_per_1m {
+                Some(write) => CachingSupport::Anthropic {
+                    cache_write_per_1m: write,
+                    cache_read_per_1m: read(*cached_input_per_1m),
+                },
+                None => CachingSupport::OpenAI {
+                    cached_input_per_1m: read(*cached_input_per_1m),
+                },
+            },
+            CachingSupport::Anthropic {
+                cache_write_per_1m,
+                cache_read_per_1m,
+            } => CachingSupport::Anthropic {
+                cache_write_per_1m: write(*cache_write_per_1m),
+                cache_read_per_1m: read(*cache_read_per_1m),
+            },
+            CachingSupport::OpenAIWithWrites {
+                cache_write_per_1m,
+                cache_read_per_1m,
+            } => CachingSupport::OpenAIWithWrites {
+                cache_write_per_1m: write(*cache_write_per_1m),
+                cache_read_per_1m: read(*cache_read_per_1m),
+            },
+            CachingSupport::Tiered(tiered) => CachingSupport::Tiered(TieredCaching {
+                tiers: tiered
+                    .tiers
+                    .iter()
+                    .map(|tier| CachingTier {
+                        max_tokens: tier.max_tokens,
+                        cached_input_per_1m: read(tier.cached_input_per_1m),
+                    })
+                    .collect(),
+                bracket_pricing: tiered.bracket_pricing,
+            }),
+        }
+    }
+
+    /// Pricing for a model splitrail doesn't know yet.
+    fn to_model_info(&self, name: &str) -> Option<ModelInfo> {
+        let base = ModelInfo {
+            pricing: PricingStructure::Flat {
+                input_per_1m: self.input_per_1m?,
+                output_per_1m: self.output_per_1m?,
+            },
+            caching: CachingSupport::None,
+            service_tiers: HashMap::new(),
+            dated_pricing: Vec::new(),
+            input_token_semantics: input_token_semantics_for_model(name),
+            is_estimated: false,
+        };
+        Some(self.apply_to(&base))
+    }
+}
+
 /// Global registry for models and aliases
 struct Registry {
     index: HashMap<String, Arc<ModelInfo>>,
@@ -146,7 +433,40 @@ impl Registry {
         let mut index = HashMap::new();
         let mut aliases = HashMap::new();
         populate_defaults(&mut index, &mut aliases);
-        Self { index, aliases }
+        let mut registry = Self { index, aliases };
+        if let Some(rates) = MANIFEST_RATES.get() {
+            registry.apply_manifest(rates);
+        }
+        registry
+    }
+
+    /// Layer rates from a remote pricing manifest over the built-in table.
+    /// Tiered models are left alone since a manifest only carries flat rates,
+    /// and dated pricing is kept because it describes usage before today.
+    fn apply_manifest(&mut self, rates: &HashMap<String, PriceOverride>) {
+        for (name, price) in rates {
+            match self.canonical_name(name) {
+                Some(canonical) => {
+                    let base = &self.index[&canonical];
+                    if matches!(base.pricing, PricingStructure::Tiered(_))
+                        || matches!(base.caching, CachingSupport::Tiered(_))
+                    {
+                        continue;
+                    }
+                    let info = ModelInfo {
+                        dated_pricing: base.dated_pricing.clone(),
+                        is_estimated: false,
+                        ..price.apply_to(base)
+                    };
+                    self.index.insert(canonical, Arc::new(info));
+                }
+                None => {
+                    if let Some(info) = price.to_model_info(name) {
+                        self.index.insert(name.clone(), Arc::new(info));
+                    }
+                }
+            }
+        }
     }
 
     fn merge(
@@ -168,6 +488,52 @@ impl Registry {
         }
     }
 
+    /// Apply `[pricing]` overrides. An alias overrides the model it points to,
+    /// so every other name for that model picks up the new rates too.
+    fn apply_overrides(&mut self, overrides: &HashMap<String, PriceOverride>) {
+        for (name, price) in overrides {
+            let info = match self.canonical_name(name) {
+                Some(canonical) => {
+                    let base = &self.index[&canonical];
+                    if price.cache_write_per_1m.is_some()
+                        && matches!(base.caching, CachingSupport::Tiered(_))
+                    {
+                        warn_once(format!(
+                            "WARNING: [pricing] cache_write_per_1m for `{name}` is ignored: the model has tiered cache reads and no cache writes."
+                        ));
+                    }
+                    let info = price.apply_to(base);
+                    self.index.insert(canonical, Arc::new(info));
+                    continue;
+                }
+                None => price.to_model_info(name),
+            };
+            match info {
+                Some(info) => {
+                    self.index.insert(name.clone(), Arc::new(info));
+                }
+                None => warn_once(format!(
+                    "WARNING: [pricing] entry for unknown model `{name}` needs both input_per_1m and output_per_1m; ignoring it."
+                )),
+            }
+        }
+    }
+
+    /// Follow aliases from `name` to a model in the index.
+    fn canonical_name(&self, name: &str) -> Option<String> {
+        let mut current = name;
+        let mut visited = HashSet::new();
+        loop {
+            if self.index.contains_key(current) {
+                return Some(current.to_string());
+            }
+            if !visited.insert(current) {
+                return None;
+            }
+            current = self.aliases.get(current)?.as_str();
+        }
+    }
+
     fn validate_model_info(info: &ModelInfo) -> bool {
         Self::validate_pricing_and_caching(&info.pricing, &info.caching)
             && info
@@ -224,15 +590,101 @@ impl Registry {
 
 static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
 static FREE_MODEL_INFO: OnceLock<Arc<ModelInfo>> = OnceLock::new();
+static MANIFEST_RATES: OnceLock<HashMap<String, PriceOverride>> = OnceLock::new();
+
+/// Which cost wins when a tool logs its own, set by `[costs] source`.
+///
+/// Only analyzers that can log a cost go through [`resolve_cost`]: Cline and
+/// its forks, OpenCode, Pi Agent, Copilot Chat, imports, and custom analyzers.
+/// The rest (Claude Code, Codex CLI, Gemini CLI, Qwen Code, Copilot CLI,
+/// Piebald, Antigravity) never log one and are always priced at model rates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostSourcePreference {
+    /// The logged cost when there is one, model rates otherwise.
+    #[default]
+    Auto,
+    /// Only logged costs; messages from those analyzers without one count as free.
+    Logged,
+    /// Always price tokens at model rates, ignoring logged costs.
+    Computed,
+}
+
+impl CostSourcePreference {
+    pub fn label(self) -> &'static str {
+        match self {
+            CostSourcePreference::Auto => "auto",
+            CostSourcePreference::Logged => "logged",
+            CostSourcePreference::Computed => "computed",
+        }
+    }
+}
+
+/// Where a message's cost came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostSource {
+    /// Reported by the tool itself, e.g. a provider's billed price.
+    Logged,
+    /// Priced from token counts at model rates.
+    Computed,
+}
+
+static COST_SOURCE: RwLock<CostSourcePreference> = RwLock::new(CostSourcePreference::Auto);
+
+/// Choose between logged and computed costs for messages parsed from now on.
+pub fn set_cost_source(preference: CostSourcePreference) {
+    *COST_SOURCE.write() = preference;
+}
+
+/// A message's cost from what its tool `logged`, falling back to `compute`
+/// (model rates) as the `[costs] source` setting allows.
+pub fn resolve_cost(logged: Option<f64>, compute: impl FnOnce() -> f64) -> (f64, CostSource) {
+    resolve_cost_with(*COST_SOURCE.read(), logged, compute)
+}
+
+fn resolve_cost_with(
+    preference: CostSourcePreference,
+    logged: Option<f64>,
+    compute: impl FnOnce() -> f64,
+) -> (f64, CostSource) {
+    match (preference, logged) {
+        (CostSourcePreference::Computed, _) | (CostSourcePreference::Auto, None) => {
+            (compute(), CostSource::Computed)
+        }
+        (_, Some(cost)) => (cost, CostSource::Logged),
+        (CostSourcePreference::Logged, None) => (0.0, CostSource::Computed),
+    }
+}
+
+/// Install rates from the remote pricing manifest. Must run before the
+/// registry is first used; later calls are ignored.
+pub fn set_manifest_rates(rates: HashMap<String, PriceOverride>) {
+    let _ = MANIFEST_RATES.set(rates);
+}
 
 /// Merge external model configuration into the global registry.
 pub fn init_external_models(
     external_models: HashMap<String, ModelInfo>,
     external_aliases: HashMap<String, String>,
+    pricing: &HashMap<String, PriceOverride>,
 ) {
     let rwlock = REGISTRY.get_or_init(|| RwLock::new(Registry::new_with_defaults()));
     let mut registry = rwlock.write();
     registry.merge(external_models, external_aliases);
+    registry.apply_overrides(pricing);
+}
+
+/// Replace all external model configuration, restoring built-in pricing for
+/// any model whose override was removed since the last call.
+pub fn reload_external_models(
+    external_models: HashMap<String, ModelInfo>,
+    external_aliases: HashMap<String, String>,
+    pricing: &HashMap<String, PriceOverride>,
+) {
+    let mut fresh = Registry::new_with_defaults();
+    fresh.merge(external_models, external_aliases);
+    fresh.apply_overrides(pricing);
+    *get_registry_lock().write() = fresh;
 }
 
 fn get_registry_lock() -> &'static RwLock<Registry> {
@@ -2417,13 +2869,17 @@ pub fn calculate_total_cost_for_service_tier_at(
     effective_at: Option<DateTime<Utc>>,
 ) -> f64 {
     match get_model_info(model_name) {
-        Some(model_info) => {
-            let (pricing, caching) =
-                pricing_for_service_tier(&model_info, service_tier, effective_at);
-            input_cost_for_pricing(pricing, input_tokens)
-                + output_cost_for_pricing(pricing, output_tokens)
-                + cache_cost_for_caching(caching, cache_creation_tokens, cache_read_tokens)
-        }
+        Some(model_info) => total_cost_for_info(
+            &model_info,
+            service_tier,
+            [
+                input_tokens,
+                output_tokens,
+                cache_creation_tokens,
+                cache_read_tokens,
+            ],
+            effective_at,
+        ),
         None => {
             warn_once(format!(
                 "WARNING: Unknown model: {model_name}. Defaulting to $0."
@@ -2433,6 +2889,67 @@ pub fn calculate_total_cost_for_service_tier_at(
     }
 }
 
+/// Input, output, cache write and cache read tokens priced at `model_info`'s rates.
+fn total_cost_for_info(
+    model_info: &ModelInfo,
+    service_tier: ServiceTier,
+    [
+        input_tokens,
+        output_tokens,
+        cache_creation_tokens,
+        cache_read_tokens,
+    ]: [u64; 4],
+    effective_at: Option<DateTime<Utc>>,
+) -> f64 {
+    let (pricing, caching) = pricing_for_service_tier(model_info, service_tier, effective_at);
+    input_cost_for_pricing(pricing, input_tokens)
+        + output_cost_for_pricing(pricing, output_tokens)
+        + cache_cost_for_caching(caching, cache_creation_tokens, cache_read_tokens)
+}
+
+/// Rates of some models as they stood when taken, so usage priced before a
+/// pricing reload can be re-priced by the difference afterwards.
+pub struct PricingSnapshot {
+    rates: HashMap<String, Option<Arc<ModelInfo>>>,
+}
+
+impl PricingSnapshot {
+    pub fn take<'a>(models: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            rates: models
+                .into_iter()
+                .map(|model| (model.to_string(), get_model_info(model)))
+                .collect(),
+        }
+    }
+
+    /// A snapshot in which `models` had no pricing yet.
+    #[cfg(test)]
+    pub fn unpriced<'a>(models: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            rates: models
+                .into_iter()
+                .map(|model| (model.to_string(), None))
+                .collect(),
+        }
+    }
+
+    /// How much more `tokens` (input, output, cache write, cache read) of
+    /// `model` cost at current rates than at the snapshot's. Zero for models
+    /// the snapshot doesn't cover.
+    pub fn cost_change(&self, model: &str, tokens: [u64; 4]) -> f64 {
+        let Some(before) = self.rates.get(model) else {
+            return 0.0;
+        };
+        let cost = |info: Option<&Arc<ModelInfo>>| {
+            info.map_or(0.0, |info| {
+                total_cost_for_info(info, ServiceTier::Standard, tokens, None)
+            })
+        };
+        cost(get_model_info(model).as_ref()) - cost(before.as_ref())
+    }
+}
+
 fn calculate_tiered_cost(
     tokens: u64,
     tiers: &[PricingTier],
@@ -2529,14 +3046,15 @@ where
 #[cfg(test)]
 mod tests {
     use super::{
-        CachingSupport, CachingTier, InputTokenSemantics, ModelInfo, PricingStructure, PricingTier,
+        CachingSupport, CachingTier, CostSource, CostSourcePreference, InputTokenSemantics,
+        ModelInfo, PriceOverride, PricingSnapshot, PricingStructure, PricingTier, Provider,
         Registry, ServiceTier, TieredCaching, TieredPricing, calculate_cache_cost,
         calculate_cache_cost_for_service_tier, calculate_cache_cost_for_service_tier_at,
         calculate_input_cost, calculate_input_cost_for_service_tier,
         calculate_input_cost_for_service_tier_at, calculate_output_cost,
         calculate_output_cost_for_service_tier, calculate_output_cost_for_service_tier_at,
-        calculate_total_cost_for_service_tier_at, get_model_info, get_registry_lock,
-        init_external_models,
+        calculate_total_cost, calculate_total_cost_for_service_tier_at, get_model_info,
+        get_registry_lock, init_external_models, resolve_cost_with,
     };
 
     use chrono::{TimeZone, Utc};
@@ -2600,6 +3118,19 @@ mod tests {
         assert_eq!(canonical, "super-expensive-o3");
     }
 
+    #[test]
+    fn pricing_snapshot_prices_only_the_change() {
+        let tokens = [1_000_000, 100_000, 0, 0];
+        let unchanged = PricingSnapshot::take(["claude-sonnet-4"]);
+        assert_eq!(unchanged.cost_change("claude-sonnet-4", tokens), 0.0);
+        assert_eq!(unchanged.cost_change("gpt-5", tokens), 0.0);
+
+        let added = PricingSnapshot::unpriced(["claude-sonnet-4"]);
+        let expected = calculate_total_cost("claude-sonnet-4", 1_000_000, 100_000, 0, 0);
+        assert!(expected > 0.0);
+        assert!((added.cost_change("claude-sonnet-4", tokens) - expected).abs() < 1e-9);
+    }
+
     #[test]
     fn init_external_models_accepts_multiple_calls() {
         let _guard = registry_test_guard();
@@ -2626,7 +3157,7 @@ mod tests {
             "review-first-model".to_string(),
         );
 
-        init_external_models(first_models, first_aliases);
+        init_external_models(first_models, first_aliases, &HashMap::new());
         assert!(get_model_info("review-first-alias").is_some());
 
         let mut second_models = HashMap::new();
@@ -2650,7 +3181,7 @@ mod tests {
             "review-second-model".to_string(),
         );
 
-        init_external_models(second_models, second_aliases);
+        init_external_models(second_models, second_aliases, &HashMap::new());
 
         assert!(get_model_info("review-second-alias").is_some());
 
@@ -2685,7 +3216,7 @@ mod tests {
             "review-chain-model".to_string(),
         );
 
-        init_external_models(models, aliases);
+        init_external_models(models, aliases, &HashMap::new());
 
         let model_info = get_model_info("review-chain-a").expect("alias chain should resolve");
         match &model_info.pricing {
@@ -2696,6 +3227,115 @@ mod tests {
         reset_global_registry();
     }
 
+    #[test]
+    fn models_map_to_billing_providers() {
+        for (model, provider) in [
+            ("claude-sonnet-4-5-20250929", Provider::Anthropic),
+            ("us.anthropic.claude-opus-4-1", Provider::Anthropic),
+            ("gpt-5-codex", Provider::OpenAI),
+            ("o4-mini", Provider::OpenAI),
+            ("openai/gpt-oss-120b", Provider::OpenWeights),
+            ("gemini-2.5-pro", Provider::Google),
+            ("z-ai/glm-4.6", Provider::OpenWeights),
+            ("qwen3-coder-plus", Provider::OpenWeights),
+            ("grok-code-fast-1", Provider::Other),
+        ] {
+            assert_eq!(Provider::for_model(model), provider, "{model}");
+        }
+    }
+
+    #[test]
+    fn pricing_overrides_patch_rates_and_add_models() {
+        let mut registry = Registry::new_with_defaults();
+        let overrides = HashMap::from([
+            (
+                // An alias overrides the model it points to.
+                "claude-4.8-opus".to_string(),
+                PriceOverride {
+                    output_per_1m: Some(30.0),
+                    cache_read_per_1m: Some(0.25),
+                    ..PriceOverride::default()
+                },
+            ),
+            (
+                "acme-coder-1".to_string(),
+                PriceOverride {
+                    input_per_1m: Some(0.5),
+                    output_per_1m: Some(2.0),
+                    cache_read_per_1m: Some(0.05),
+                    ..PriceOverride::default()
+                },
+            ),
+            (
+                "acme-incomplete".to_string(),
+                PriceOverride {
+                    input_per_1m: Some(0.5),
+                    ..PriceOverride::default()
+                },
+            ),
+        ]);
+        registry.apply_overrides(&overrides);
+
+        let opus = registry.canonical_name("claude-4.8-opus").unwrap();
+        let opus = &registry.index[&opus];
+        match opus.pricing {
+            PricingStructure::Flat {
+                input_per_1m,
+                output_per_1m,
+            } => {
+                approx_eq(input_per_1m, 5.0);
+                approx_eq(output_per_1m, 30.0);
+            }
+            _ => panic!("Expected flat pricing"),
+        }
+        match opus.caching {
+            CachingSupport::Anthropic {
+                cache_write_per_1m,
+                cache_read_per_1m,
+            } => {
+                approx_eq(cache_write_per_1m, 6.25);
+                approx_eq(cache_read_per_1m, 0.25);
+            }
+            _ => panic!("Expected Anthropic caching"),
+        }
+
+        let acme = &registry.index["acme-coder-1"];
+        assert!(!acme.is_estimated);
+        assert!(matches!(
+            acme.caching,
+            CachingSupport::OpenAI { cached_input_per_1m } if cached_input_per_1m == 0.05
+        ));
+        assert!(!registry.index.contains_key("acme-incomplete"));
+    }
+
+    #[test]
+    fn pricing_overrides_scale_tiered_models() {
+        let mut registry = Registry::new_with_defaults();
+        registry.apply_overrides(&HashMap::from([(
+            "gemini-2.5-pro".to_string(),
+            PriceOverride {
+                input_per_1m: Some(2.5),
+                cache_read_per_1m: Some(0.62),
+                ..PriceOverride::default()
+            },
+        )]));
+
+        let gemini = &registry.index["gemini-2.5-pro"];
+        let PricingStructure::Tiered(pricing) = &gemini.pricing else {
+            panic!("Expected tiered pricing");
+        };
+        // Doubling the base input rate doubles the long-context one too, and
+        // output keeps its built-in tiers.
+        approx_eq(pricing.tiers[0].input_per_1m, 2.5);
+        approx_eq(pricing.tiers[1].input_per_1m, 5.0);
+        approx_eq(pricing.tiers[1].output_per_1m, 15.0);
+        let CachingSupport::Tiered(caching) = &gemini.caching else {
+            panic!("Expected tiered caching");
+        };
+        approx_eq(caching.tiers[0].cached_input_per_1m, 0.62);
+        approx_eq(caching.tiers[1].cached_input_per_1m, 1.25);
+    }
+
     #[test]
     fn invalid_external_tier_configs_are_skipped() {
         let _guard = registry_test_guard();
@@ -2746,7 +3386,7 @@ mod tests {
             "review-invalid-tier-model".to_string(),
         );
 
-        init_external_models(models, aliases);
+        init_external_models(models, aliases, &HashMap::new());
 
         assert!(get_model_info("review-invalid-tier-model").is_none());
         assert!(get_model_info("review-invalid-tier-alias").is_none());
@@ -3554,4 +4194,25 @@ mod tests {
         approx_eq(output_cost, 0.0);
         approx_eq(cache_cost, 0.0);
     }
+
+    #[test]
+    fn cost_source_preference_picks_logged_or_computed_cost() {
+        let compute = || 2.0;
+        assert_eq!(
+            resolve_cost_with(CostSourcePreference::Auto, Some(1.0), compute),
+            (1.0, CostSource::Logged)
+        );
+        assert_eq!(
+            resolve_cost_with(CostSourcePreference::Auto, None, compute),
+            (2.0, CostSource::Computed)
+        );
+        assert_eq!(
+            resolve_cost_with(CostSourcePreference::Logged, None, compute),
+            (0.0, CostSource::Computed)
+        );
+        assert_eq!(
+            resolve_cost_with(CostSourcePreference::Computed, Some(1.0), compute),
+            (2.0, CostSource::Computed)
+        );
+    }
 }
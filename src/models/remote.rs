@@ -0,0 +1,276 @@
+//! Model rates from a remote pricing manifest.
+//!
+//! When `[pricing_updates]` is enabled, startup fetches a LiteLLM-format JSON
+//! manifest, caches the rates it understands in `pricing.json` in the cache directory,
+//! and layers them over the built-in table. Offline runs reuse the cached
+//! rates, or the built-in table when nothing was ever fetched.
+
+use super::PriceOverride;
+use crate::config::PricingUpdatesConfig;
+use crate::upload::get_http_client;
+use crate::utils::warn_once;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Failed fetches are retried at most this often, so offline runs don't pay
+/// the request timeout on every startup.
+const RETRY_AFTER_MINUTES: i64 = 60;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+const CACHE_FORMAT: crate::persist::JsonFormat = crate::persist::JsonFormat {
+    name: "pricing cache",
+    migrations: &[crate::persist::add_version_header],
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedManifest {
+    source: String,
+    /// Last successful fetch; `None` when every attempt so far has failed.
+    fetched_at: Option<DateTime<Utc>>,
+    checked_at: DateTime<Utc>,
+    models: HashMap<String, PriceOverride>,
+}
+
+impl CachedManifest {
+    fn needs_fetch(&self, url: &str, refresh_hours: u32, now: DateTime<Utc>) -> bool {
+        if self.source != url {
+            return true;
+        }
+        let stale = self
+            .fetched_at
+            .is_none_or(|fetched| now - fetched >= ChronoDuration::hours(refresh_hours as i64));
+        stale && now - self.checked_at >= ChronoDuration::minutes(RETRY_AFTER_MINUTES)
+    }
+}
+
+pub fn cache_path() -> Option<PathBuf> {
+    crate::paths::cache_dir()
+        .ok()
+        .map(|dir| dir.join("pricing.json"))
+}
+
+fn read_cache(path: &Path) -> Option<CachedManifest> {
+    crate::persist::read_json(path, &CACHE_FORMAT)
+        .ok()
+        .flatten()
+}
+
+fn write_cache(path: &Path, manifest: &CachedManifest) -> Result<()> {
+    crate::persist::write_json(path, &CACHE_FORMAT, manifest)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+/// Rates to layer over the built-in table, fetching a fresh manifest first
+/// when the cached one is out of date. `None` means use built-in rates only.
+pub async fn refresh(config: &PricingUpdatesConfig) -> Option<HashMap<String, PriceOverride>> {
+    let path = cache_path()?;
+    let cached = read_cache(&path);
+    let now = Utc::now();
+    if let Some(cached) = cached
+        .as_ref()
+        .filter(|cached| !cached.needs_fetch(&config.url, config.refresh_hours, now))
+    {
+        return Some(cached.models.clone());
+    }
+
+    let manifest = match fetch(&config.url).await {
+        Ok(models) => CachedManifest {
+            source: config.url.clone(),
+            fetched_at: Some(now),
+            checked_at: now,
+            models,
+        },
+        Err(e) => {
+            warn_once(format!(
+                "WARNING: couldn't fetch the pricing manifest from {}: {e:#}. Using {} rates.",
+                config.url,
+                if cached.is_some() {
+                    "cached"
+                } else {
+                    "built-in"
+                }
+            ));
+            match cached {
+                Some(cached) => CachedManifest {
+                    checked_at: now,
+                    ..cached
+                },
+                None => CachedManifest {
+                    source: config.url.clone(),
+                    fetched_at: None,
+                    checked_at: now,
+                    models: HashMap::new(),
+                },
+            }
+        }
+    };
+    if let Err(e) = write_cache(&path, &manifest) {
+        warn_once(format!(
+            "WARNING: couldn't cache the pricing manifest: {e:#}"
+        ));
+    }
+    (!manifest.models.is_empty()).then_some(manifest.models)
+}
+
+async fn fetch(url: &str) -> Result<HashMap<String, PriceOverride>> {
+    let response = get_http_client()
+        .get(url)
+        .header("User-Agent", "splitrail")
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!("server returned {}", response.status());
+    }
+    let mut body = response.bytes().await?.to_vec();
+    let models = parse_manifest(&mut body)?;
+    anyhow::ensure!(!models.is_empty(), "manifest contains no usable rates");
+    Ok(models)
+}
+
+/// Pull flat rates out of a manifest. Entries may use LiteLLM's per-token
+/// fields or splitrail's own `*_per_1m` fields. Provider-prefixed duplicates
+/// (`openrouter/...`) and non-chat models are skipped, as are entries
+/// missing an input or output rate.
+fn parse_manifest(body: &mut [u8]) -> Result<HashMap<String, PriceOverride>> {
+    let value: OwnedValue = simd_json::to_owned_value(body).context("invalid manifest JSON")?;
+    let entries = value
+        .as_object()
+        .context("manifest must be a JSON object keyed by model name")?;
+
+    let mut models = HashMap::new();
+    for (name, entry) in entries.iter() {
+        if name.contains('/') {
+            continue;
+        }
+        if entry
+            .get_str("mode")
+            .is_some_and(|mode| mode != "chat" && mode != "responses")
+        {
+            continue;
+        }
+        let per_1m = |native: &str, per_token: &str| {
+            // `cast_f64` so integer rates such as `0` or `15` are accepted too.
+            let rate = |key: &str| entry.get(key).and_then(|value| value.cast_f64());
+            rate(native)
+                .or_else(|| rate(per_token).map(|cost| cost * 1_000_000.0))
+                .filter(|rate| rate.is_finite() && *rate >= 0.0)
+        };
+        let price = PriceOverride {
+            input_per_1m: per_1m("input_per_1m", "input_cost_per_token"),
+            output_per_1m: per_1m("output_per_1m", "output_cost_per_token"),
+            cache_read_per_1m: per_1m("cache_read_per_1m", "cache_read_input_token_cost"),
+            cache_write_per_1m: per_1m("cache_write_per_1m", "cache_creation_input_token_cost"),
+        };
+        if price.input_per_1m.is_some() && price.output_per_1m.is_some() {
+            models.insert(name.to_string(), price);
+        }
+    }
+    Ok(models)
+}
+
+/// One-line description of the cached manifest for `splitrail config show`.
+pub fn cached_summary() -> Option<String> {
+    let cached = read_cache(&cache_path()?)?;
+    let fetched = cached.fetched_at?.with_timezone(&Local);
+    Some(format!(
+        "{} models, fetched {}",
+        cached.models.len(),
+        fetched.format("%Y-%m-%d %H:%M")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CachingSupport, PricingStructure, Registry};
+
+    const LITELLM_SAMPLE: &str = r#"{
+        "sample_spec": {"input_cost_per_token": "see docs", "mode": "chat"},
+        "claude-opus-4-1": {
+            "input_cost_per_token": 1e-05,
+            "output_cost_per_token": 5e-05,
+            "cache_read_input_token_cost": 1e-06,
+            "cache_creation_input_token_cost": 1.25e-05,
+            "mode": "chat"
+        },
+        "brand-new-model": {"input_cost_per_token": 2e-06, "output_cost_per_token": 8e-06},
+        "native-model": {"input_per_1m": 1.5, "output_per_1m": 6},
+        "openrouter/brand-new-model": {"input_cost_per_token": 9e-06, "output_cost_per_token": 9e-06},
+        "text-embedding-3-large": {"input_cost_per_token": 1.3e-07, "output_cost_per_token": 0.0, "mode": "embedding"}
+    }"#;
+
+    #[test]
+    fn parses_litellm_and_native_rates() {
+        let models = parse_manifest(&mut LITELLM_SAMPLE.as_bytes().to_vec()).unwrap();
+
+        let mut names: Vec<&str> = models.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            ["brand-new-model", "claude-opus-4-1", "native-model"]
+        );
+
+        let opus = &models["claude-opus-4-1"];
+        assert!((opus.input_per_1m.unwrap() - 10.0).abs() < 1e-9);
+        assert!((opus.cache_write_per_1m.unwrap() - 12.5).abs() < 1e-9);
+        assert_eq!(models["native-model"].output_per_1m, Some(6.0));
+    }
+
+    #[test]
+    fn manifest_rates_patch_flat_models_and_add_new_ones() {
+        let mut models = parse_manifest(&mut LITELLM_SAMPLE.as_bytes().to_vec()).unwrap();
+        models.insert(
+            "gemini-2.5-pro".to_string(),
+            PriceOverride {
+                input_per_1m: Some(99.0),
+                output_per_1m: Some(99.0),
+                ..PriceOverride::default()
+            },
+        );
+        let mut registry = Registry {
+            index: HashMap::new(),
+            aliases: HashMap::new(),
+        };
+        super::super::populate_defaults(&mut registry.index, &mut registry.aliases);
+        registry.apply_manifest(&models);
+
+        let opus = &registry.index["claude-opus-4-1"];
+        assert!(matches!(
+            opus.pricing,
+            PricingStructure::Flat { input_per_1m, .. } if (input_per_1m - 10.0).abs() < 1e-9
+        ));
+        assert!(matches!(opus.caching, CachingSupport::Anthropic { .. }));
+        assert!(registry.index.contains_key("brand-new-model"));
+        // Tiered models keep their built-in brackets.
+        assert!(matches!(
+            registry.index["gemini-2.5-pro"].pricing,
+            PricingStructure::Tiered(_)
+        ));
+    }
+
+    #[test]
+    fn refetches_when_stale_but_backs_off_after_failures() {
+        let now = Utc::now();
+        let url = "https://example.com/prices.json";
+        let manifest = |fetched_hours_ago: Option<i64>, checked_minutes_ago: i64| CachedManifest {
+            source: url.to_string(),
+            fetched_at: fetched_hours_ago.map(|hours| now - ChronoDuration::hours(hours)),
+            checked_at: now - ChronoDuration::minutes(checked_minutes_ago),
+            models: HashMap::new(),
+        };
+
+        assert!(!manifest(Some(2), 120).needs_fetch(url, 24, now));
+        assert!(manifest(Some(30), 30 * 60).needs_fetch(url, 24, now));
+        assert!(!manifest(Some(30), 10).needs_fetch(url, 24, now));
+        assert!(!manifest(None, 10).needs_fetch(url, 24, now));
+        assert!(manifest(None, 90).needs_fetch(url, 24, now));
+        assert!(manifest(Some(1), 1).needs_fetch("https://other.example/prices.json", 24, now));
+    }
+}
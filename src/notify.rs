@@ -0,0 +1,228 @@
+//! Desktop notifications for spend thresholds and budgets.
+//!
+//! [`SpendAlerts`] turns successive stats refreshes into alerts, firing only
+//! when a threshold is newly crossed. Whatever is already over the line on the
+//! first check is recorded silently, so restarting the TUI does not repeat
+//! yesterday's (or this morning's) notifications.
+
+use crate::budget::{ALL_TOOLS, BudgetLevel, BudgetPeriod, BudgetStatus};
+use crate::config::NotificationsConfig;
+use crate::types::CompactDate;
+use anyhow::Result;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub title: String,
+    pub body: String,
+}
+
+pub trait Notifier {
+    fn notify(&self, alert: &Alert) -> Result<()>;
+}
+
+/// Sends alerts through the OS notification service.
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("splitrail")
+            .summary(&alert.title)
+            .body(&alert.body)
+            .show()?;
+        Ok(())
+    }
+}
+
+/// Deliver alerts on a background thread; notification services can block
+/// (D-Bus on Linux) and must never stall the UI. Failures are dropped since
+/// there is nowhere useful to report them from inside the TUI.
+pub fn dispatch(alerts: Vec<Alert>) {
+    if alerts.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for alert in &alerts {
+            let _ = DesktopNotifier.notify(alert);
+        }
+    });
+}
+
+/// Tracks which thresholds have already been reported.
+#[derive(Debug, Default)]
+pub struct SpendAlerts {
+    /// Daily thresholds in ascending order.
+    thresholds: Vec<f64>,
+    currency_symbol: String,
+    /// Day the `crossed` count applies to; `None` until the first check.
+    day: Option<CompactDate>,
+    /// How many of `thresholds` today's spend has reached.
+    crossed: usize,
+    budget_levels: HashMap<(String, BudgetPeriod), BudgetLevel>,
+}
+
+impl SpendAlerts {
+    pub fn new(config: &NotificationsConfig, currency_symbol: &str) -> Self {
+        let mut thresholds: Vec<f64> = config
+            .daily_thresholds_usd
+            .iter()
+            .copied()
+            .filter(|amount| *amount > 0.0)
+            .collect();
+        thresholds.sort_by(f64::total_cmp);
+        Self {
+            thresholds,
+            currency_symbol: currency_symbol.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Compare today's spend across all tools against the daily thresholds.
+    /// Only the highest newly crossed threshold is reported.
+    pub fn check_daily(&mut self, today: CompactDate, spent: f64) -> Option<Alert> {
+        let crossed = self
+            .thresholds
+            .iter()
+            .take_while(|amount| spent >= **amount)
+            .count();
+        let previous = match self.day {
+            Some(day) if day == today => self.crossed,
+            // A new day starts from zero; the very first check only primes.
+            Some(_) => 0,
+            None => crossed,
+        };
+        self.day = Some(today);
+        // Spend can dip when a log is deleted; don't re-alert when it climbs back.
+        self.crossed = crossed.max(previous);
+
+        (crossed > previous).then(|| {
+            let threshold = self.thresholds[crossed - 1];
+            Alert {
+                title: format!("Daily spend passed {}", self.money(threshold)),
+                body: format!("You've spent {} across all tools today.", self.money(spent)),
+            }
+        })
+    }
+
+    /// Report budgets whose level went up (ok -> warning -> exceeded) since the last check.
+    pub fn check_budgets(&mut self, statuses: &[BudgetStatus]) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for status in statuses {
+            let key = (status.scope.clone(), status.period);
+            let previous = self.budget_levels.insert(key, status.level);
+            let Some(previous) = previous else {
+                continue;
+            };
+            if status.level <= previous {
+                continue;
+            }
+            let subject = if status.scope == ALL_TOOLS {
+                format!("{}ly budget", capitalize(status.period.label()))
+            } else {
+                format!("{} {}ly budget", status.scope, status.period.label())
+            };
+            let title = match status.level {
+                BudgetLevel::Exceeded => format!("{subject} exceeded"),
+                _ => format!("{subject} at {:.0}%", status.fraction() * 100.0),
+            };
+            alerts.push(Alert {
+                title,
+                body: format!(
+                    "{} of {} spent this {}.",
+                    self.money(status.spent),
+                    self.money(status.limit),
+                    status.period.label()
+                ),
+            });
+        }
+        alerts
+    }
+
+    fn money(&self, amount: f64) -> String {
+        format!("{}{amount:.2}", self.currency_symbol)
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alerts() -> SpendAlerts {
+        SpendAlerts::new(
+            &NotificationsConfig {
+                enabled: true,
+                daily_thresholds_usd: vec![25.0, 10.0, 50.0],
+            },
+            "$",
+        )
+    }
+
+    fn date(s: &str) -> CompactDate {
+        CompactDate::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn daily_thresholds_fire_once_per_crossing() {
+        let mut alerts = alerts();
+        let day = date("2025-06-01");
+
+        // Startup primes silently even when already over a threshold.
+        assert_eq!(alerts.check_daily(day, 12.0), None);
+        assert_eq!(alerts.check_daily(day, 14.0), None);
+
+        let alert = alerts.check_daily(day, 55.0).unwrap();
+        assert_eq!(alert.title, "Daily spend passed $50.00");
+        assert_eq!(alert.body, "You've spent $55.00 across all tools today.");
+        assert_eq!(alerts.check_daily(day, 60.0), None);
+
+        // The next day starts over.
+        let next = date("2025-06-02");
+        assert_eq!(alerts.check_daily(next, 5.0), None);
+        assert_eq!(
+            alerts.check_daily(next, 10.0).unwrap().title,
+            "Daily spend passed $10.00"
+        );
+    }
+
+    #[test]
+    fn budget_alerts_only_on_escalation() {
+        let status = |scope: &str, spent: f64, level| BudgetStatus {
+            scope: scope.to_string(),
+            period: BudgetPeriod::Monthly,
+            limit: 100.0,
+            spent,
+            level,
+        };
+        let mut alerts = alerts();
+
+        assert!(
+            alerts
+                .check_budgets(&[status(ALL_TOOLS, 50.0, BudgetLevel::Ok)])
+                .is_empty()
+        );
+        let warning = alerts.check_budgets(&[status(ALL_TOOLS, 85.0, BudgetLevel::Warning)]);
+        assert_eq!(warning[0].title, "Monthly budget at 85%");
+        assert_eq!(warning[0].body, "$85.00 of $100.00 spent this month.");
+
+        let exceeded = alerts.check_budgets(&[
+            status(ALL_TOOLS, 120.0, BudgetLevel::Exceeded),
+            status("Codex CLI", 120.0, BudgetLevel::Exceeded),
+        ]);
+        assert_eq!(exceeded.len(), 1, "first sighting of a scope only primes");
+        assert_eq!(exceeded[0].title, "Monthly budget exceeded");
+        assert!(
+            alerts
+                .check_budgets(&[status(ALL_TOOLS, 130.0, BudgetLevel::Exceeded)])
+                .is_empty()
+        );
+    }
+}
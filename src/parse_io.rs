@@ -0,0 +1,245 @@
+//! How source files are read during a full parse: in parallel on rayon's
+//! pool, one at a time, or on a smaller pool capped by `[parsing] threads`.
+//!
+//! Every analyzer parses its sources through [`parse_each`], so the setting
+//! applies to all of them. Sequential and capped parsing run on shared pools,
+//! which bounds reads across all analyzers loading at once rather than per
+//! analyzer, which is what keeps a spinning disk from seeking between files.
+
+use crate::analyzer::DataSource;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IoStrategy {
+    /// Sequential on network mounts and spinning disks, parallel otherwise.
+    #[default]
+    Auto,
+    Parallel,
+    Sequential,
+}
+
+impl std::str::FromStr for IoStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "parallel" => Ok(Self::Parallel),
+            "sequential" => Ok(Self::Sequential),
+            _ => Err("Invalid IO strategy. Use 'auto', 'parallel' or 'sequential'".to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for IoStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto",
+            Self::Parallel => "parallel",
+            Self::Sequential => "sequential",
+        })
+    }
+}
+
+/// `[parsing]` settings for this process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseIo {
+    pub strategy: IoStrategy,
+    /// Most files read at once when parsing in parallel; rayon's default
+    /// (one per core) when unset.
+    pub threads: Option<usize>,
+}
+
+static PARSE_IO: OnceLock<ParseIo> = OnceLock::new();
+static SEQUENTIAL_POOL: OnceLock<Option<rayon::ThreadPool>> = OnceLock::new();
+static CAPPED_POOL: OnceLock<Option<rayon::ThreadPool>> = OnceLock::new();
+
+/// Apply `[parsing]` to every parse in this process.
+pub fn set_parse_io(settings: ParseIo) {
+    let _ = PARSE_IO.set(settings);
+}
+
+impl ParseIo {
+    pub fn current() -> ParseIo {
+        *PARSE_IO.get_or_init(ParseIo::default)
+    }
+
+    /// The strategy to use for `sources`, resolving `auto` from the storage
+    /// the first source lives on.
+    pub fn strategy_for(&self, sources: &[DataSource]) -> IoStrategy {
+        match self.strategy {
+            IoStrategy::Auto => match sources.first() {
+                Some(source) if is_slow_storage(&source.path) => IoStrategy::Sequential,
+                _ => IoStrategy::Parallel,
+            },
+            strategy => strategy,
+        }
+    }
+}
+
+fn single_pool(
+    slot: &'static OnceLock<Option<rayon::ThreadPool>>,
+    threads: usize,
+) -> Option<&'static rayon::ThreadPool> {
+    slot.get_or_init(|| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("splitrail-parse-{i}"))
+            .build()
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Run `parse` over every source under the configured IO strategy, keeping
+/// source order. Without a thread cap, parallel parsing uses the caller's
+/// rayon pool, so must be called within one like any other parse.
+pub fn parse_each<T: Send>(
+    sources: &[DataSource],
+    parse: impl Fn(&DataSource) -> T + Sync + Send,
+) -> Vec<T> {
+    let settings = ParseIo::current();
+    let pool = match settings.strategy_for(sources) {
+        IoStrategy::Sequential => single_pool(&SEQUENTIAL_POOL, 1),
+        _ => settings
+            .threads
+            .and_then(|threads| single_pool(&CAPPED_POOL, threads)),
+    };
+    match pool {
+        Some(pool) => pool.install(|| sources.par_iter().map(&parse).collect()),
+        None => sources.par_iter().map(parse).collect(),
+    }
+}
+
+/// File systems that reach the disk over a network or a VM boundary, where
+/// parallel reads mostly queue up behind each other.
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "drvfs",
+    "virtiofs",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+/// Whether `path` is on a network mount or a spinning disk. Only detected
+/// on Linux; elsewhere `auto` always parses in parallel.
+#[cfg(target_os = "linux")]
+pub fn is_slow_storage(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Ok(mounts) = std::fs::read_to_string("/proc/mounts")
+        && let Some(fs_type) = mount_fs_type(&mounts, &path)
+        && NETWORK_FILESYSTEMS.contains(&fs_type)
+    {
+        return true;
+    }
+
+    let Ok(metadata) = std::fs::metadata(&path) else {
+        return false;
+    };
+    let dev = metadata.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    // Partitions have no queue of their own; their disk is the parent entry.
+    let device = std::path::PathBuf::from(format!("/sys/dev/block/{major}:{minor}"));
+    [
+        device.join("queue/rotational"),
+        device.join("../queue/rotational"),
+    ]
+    .iter()
+    .find_map(|flag| std::fs::read_to_string(flag).ok())
+    .is_some_and(|flag| flag.trim() == "1")
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_slow_storage(_path: &Path) -> bool {
+    false
+}
+
+/// File system type of the longest mount point containing `path`, from the
+/// contents of `/proc/mounts`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn mount_fs_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as \040.
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then_some((mount_point.len(), fs_type))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/sda2 / ext4 rw,relatime 0 0
+server:/export /home/me/remote nfs4 rw,relatime 0 0
+C:\\134 /mnt/c 9p rw,noatime 0 0
+/dev/sdb1 /home/me/remote\\040data ext4 rw 0 0
+";
+
+    #[test]
+    fn mount_fs_type_uses_the_longest_matching_mount_point() {
+        let fs_type = |path: &str| mount_fs_type(MOUNTS, Path::new(path));
+        assert_eq!(fs_type("/home/me/.claude/projects"), Some("ext4"));
+        assert_eq!(fs_type("/home/me/remote/.codex/sessions"), Some("nfs4"));
+        assert_eq!(fs_type("/mnt/c/Users/me/.claude"), Some("9p"));
+        assert_eq!(fs_type("/home/me/remote data/x"), Some("ext4"));
+        // Prefixes only match whole path components.
+        assert_eq!(fs_type("/home/me/remoteish"), Some("ext4"));
+    }
+
+    #[test]
+    fn explicit_strategies_ignore_the_device() {
+        let sources = [DataSource {
+            path: "/nonexistent/session.jsonl".into(),
+        }];
+        for strategy in [IoStrategy::Parallel, IoStrategy::Sequential] {
+            let settings = ParseIo {
+                strategy,
+                threads: None,
+            };
+            assert_eq!(settings.strategy_for(&sources), strategy);
+        }
+        assert_eq!(
+            "Sequential".parse::<IoStrategy>(),
+            Ok(IoStrategy::Sequential)
+        );
+        assert!("fast".parse::<IoStrategy>().is_err());
+    }
+
+    #[test]
+    fn parse_each_keeps_source_order() {
+        let sources: Vec<DataSource> = (0..50)
+            .map(|i| DataSource {
+                path: format!("/tmp/{i}").into(),
+            })
+            .collect();
+        let parsed = parse_each(&sources, |source| source.path.clone());
+        assert_eq!(
+            parsed,
+            sources.iter().map(|s| s.path.clone()).collect::<Vec<_>>()
+        );
+    }
+}
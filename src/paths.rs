@@ -0,0 +1,418 @@
+//! Where splitrail keeps its own files.
+//!
+//! - Config: `$XDG_CONFIG_HOME/splitrail/config.toml`
+//! - Cache (the pricing manifest): `$XDG_CACHE_HOME/splitrail/`
+//! - State (upload watermark, status snapshot, Claude Code history, upload
+//!   outbox, SQLite store): `$XDG_STATE_HOME/splitrail/`
+//!
+//! The XDG variables are honored on every platform; without them each
+//! platform's usual directories are used. `SPLITRAIL_HOME` puts all three
+//! under one directory instead. Files left in the old `~/.splitrail.toml` and
+//! `~/.splitrail/` layout are moved into place on startup.
+//!
+//! A named profile (`--profile work` or `SPLITRAIL_PROFILE`) gets its own
+//! config, cache and state under `profiles/<name>/` in each of those
+//! directories, so e.g. work usage can upload to a team server while personal
+//! usage stays local.
+
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable that keeps config, cache and state in one directory.
+pub const HOME_ENV: &str = "SPLITRAIL_HOME";
+
+/// Environment variable that overrides `[cache] data_dir`.
+pub const DATA_DIR_ENV: &str = "SPLITRAIL_DATA_DIR";
+
+/// Environment variable naming the profile when `--profile` isn't given.
+pub const PROFILE_ENV: &str = "SPLITRAIL_PROFILE";
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Use the named profile's files for the rest of the process. Must be called
+/// before anything resolves a path.
+pub fn set_profile(name: &str) -> Result<()> {
+    validate_profile(name)?;
+    let _ = PROFILE.set(name.to_string());
+    Ok(())
+}
+
+/// The profile in use, if any.
+pub fn profile() -> Option<String> {
+    PROFILE.get().cloned()
+}
+
+/// Profile names become directory names, so keep them to one safe segment.
+fn validate_profile(name: &str) -> Result<()> {
+    anyhow::ensure!(
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "Invalid profile name '{name}': use letters, digits, '-' and '_'"
+    );
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Roots {
+    pub config_file: PathBuf,
+    pub cache_dir: PathBuf,
+    pub state_dir: PathBuf,
+}
+
+/// The variables that decide [`Roots`], read once so tests can supply their own.
+#[derive(Debug, Default)]
+struct Env {
+    splitrail_home: Option<OsString>,
+    config_home: Option<OsString>,
+    cache_home: Option<OsString>,
+    state_home: Option<OsString>,
+    profile: Option<String>,
+}
+
+impl Env {
+    fn current() -> Self {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        Self {
+            splitrail_home: var(HOME_ENV),
+            config_home: var("XDG_CONFIG_HOME"),
+            cache_home: var("XDG_CACHE_HOME"),
+            state_home: var("XDG_STATE_HOME"),
+            profile: profile(),
+        }
+    }
+}
+
+impl Roots {
+    fn for_profile(self, name: &str) -> Self {
+        let profile_dir = |dir: &Path| dir.join("profiles").join(name);
+        let config_dir = self
+            .config_file
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        Self {
+            config_file: profile_dir(&config_dir).join("config.toml"),
+            cache_dir: profile_dir(&self.cache_dir),
+            state_dir: profile_dir(&self.state_dir),
+        }
+    }
+}
+
+fn resolve_roots(env: &Env) -> Result<Roots> {
+    let roots = resolve_base_roots(env)?;
+    Ok(match &env.profile {
+        Some(name) => roots.for_profile(name),
+        None => roots,
+    })
+}
+
+fn resolve_base_roots(env: &Env) -> Result<Roots> {
+    if let Some(home) = &env.splitrail_home {
+        let home = PathBuf::from(crate::utils::expand_home(&home.to_string_lossy()));
+        return Ok(Roots {
+            config_file: home.join("config.toml"),
+            cache_dir: home.join("cache"),
+            state_dir: home.join("state"),
+        });
+    }
+
+    // The XDG spec says relative values are invalid and must be ignored.
+    let base = |xdg: &Option<OsString>, platform: Option<PathBuf>, kind: &str| {
+        xdg.as_ref()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or(platform)
+            .map(|dir| dir.join("splitrail"))
+            .with_context(|| format!("Could not find platform {kind} directory"))
+    };
+    Ok(Roots {
+        config_file: base(&env.config_home, dirs::config_dir(), "config")?.join("config.toml"),
+        cache_dir: base(&env.cache_home, dirs::cache_dir(), "cache")?,
+        state_dir: base(
+            &env.state_home,
+            dirs::state_dir().or_else(dirs::data_local_dir),
+            "state",
+        )?,
+    })
+}
+
+/// Resolved once per process.
+pub fn roots() -> Result<&'static Roots> {
+    static ROOTS: OnceLock<Roots> = OnceLock::new();
+    if let Some(roots) = ROOTS.get() {
+        return Ok(roots);
+    }
+    let roots = resolve_roots(&Env::current())?;
+    Ok(ROOTS.get_or_init(|| roots))
+}
+
+pub fn config_file() -> Result<PathBuf> {
+    Ok(roots()?.config_file.clone())
+}
+
+pub fn cache_dir() -> Result<PathBuf> {
+    Ok(roots()?.cache_dir.clone())
+}
+
+pub fn state_dir() -> Result<PathBuf> {
+    Ok(roots()?.state_dir.clone())
+}
+
+/// Directory holding splitrail's runtime data: the upload state, the status
+/// snapshot, and the Claude Code history store. Resolved once per process so
+/// a config edit can't split one run's data across two directories.
+pub fn data_dir() -> Result<PathBuf> {
+    static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+    if let Some(dir) = DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+    let configured = Config::load()
+        .ok()
+        .flatten()
+        .and_then(|config| config.cache.data_dir);
+    let dir = resolve_data_dir(
+        std::env::var_os(DATA_DIR_ENV),
+        configured.as_deref(),
+        profile().as_deref(),
+    )?;
+    Ok(DATA_DIR.get_or_init(|| dir).clone())
+}
+
+/// An explicit data directory still gets the `profiles/<name>` segment, so
+/// profiles sharing `SPLITRAIL_DATA_DIR` keep separate upload state and
+/// history. The state directory already has it.
+fn resolve_data_dir(
+    env: Option<OsString>,
+    configured: Option<&str>,
+    profile: Option<&str>,
+) -> Result<PathBuf> {
+    let explicit = env
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            configured
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| PathBuf::from(crate::utils::expand_home(dir)))
+        });
+    match (explicit, profile) {
+        (Some(dir), Some(name)) => Ok(dir.join("profiles").join(name)),
+        (Some(dir), None) => Ok(dir),
+        (None, _) => state_dir(),
+    }
+}
+
+/// Move files from the old home-directory layout, telling the user about each.
+/// Nothing is moved under `SPLITRAIL_HOME`, which may well be a throwaway
+/// directory, or into a profile, since the old files belong to the default
+/// one. Nothing already present at the new location is replaced.
+pub fn migrate_legacy_files() {
+    let env = Env::current();
+    if env.splitrail_home.is_some() || env.profile.is_some() {
+        return;
+    }
+    let (Some(home), Ok(roots)) = (dirs::home_dir(), roots()) else {
+        return;
+    };
+    for (from, to) in migrate(&home, roots) {
+        eprintln!("Moved {} to {}", from.display(), to.display());
+    }
+}
+
+fn migrate(home: &Path, roots: &Roots) -> Vec<(PathBuf, PathBuf)> {
+    let legacy_dir = home.join(".splitrail");
+    let mut moves = vec![
+        (home.join(".splitrail.toml"), roots.config_file.clone()),
+        (
+            legacy_dir.join("pricing.json"),
+            roots.cache_dir.join("pricing.json"),
+        ),
+        (legacy_dir.join("outbox"), roots.state_dir.join("outbox")),
+    ];
+    for name in ["splitrail.db", "splitrail.db-wal", "splitrail.db-shm"] {
+        moves.push((legacy_dir.join(name), roots.state_dir.join(name)));
+    }
+
+    let mut moved = Vec::new();
+    for (from, to) in moves {
+        if from.symlink_metadata().is_err() || to.symlink_metadata().is_ok() {
+            continue;
+        }
+        match move_path(&from, &to) {
+            Ok(()) => moved.push((from, to)),
+            Err(e) => crate::utils::warn_once(format!("{e:#}")),
+        }
+    }
+    // Only succeeds once everything splitrail knows about has moved out.
+    let _ = fs::remove_dir(&legacy_dir);
+    moved
+}
+
+/// `rename`, falling back to copy-and-delete across filesystems.
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let failed = || format!("Failed to move {} to {}", from.display(), to.display());
+    if from.is_dir() {
+        fs::create_dir_all(to).with_context(failed)?;
+        for entry in fs::read_dir(from).with_context(failed)? {
+            let entry = entry.with_context(failed)?;
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::remove_dir(from).with_context(failed)
+    } else {
+        fs::copy(from, to).with_context(failed)?;
+        fs::remove_file(from).with_context(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roots_prefer_splitrail_home_then_xdg() {
+        let roots = resolve_roots(&Env {
+            splitrail_home: Some("/opt/splitrail".into()),
+            config_home: Some("/xdg/config".into()),
+            ..Env::default()
+        })
+        .unwrap();
+        assert_eq!(
+            roots.config_file,
+            PathBuf::from("/opt/splitrail/config.toml")
+        );
+        assert_eq!(roots.cache_dir, PathBuf::from("/opt/splitrail/cache"));
+        assert_eq!(roots.state_dir, PathBuf::from("/opt/splitrail/state"));
+
+        let roots = resolve_roots(&Env {
+            config_home: Some("/xdg/config".into()),
+            cache_home: Some("/xdg/cache".into()),
+            state_home: Some("relative/state".into()),
+            ..Env::default()
+        })
+        .unwrap();
+        assert_eq!(
+            roots.config_file,
+            PathBuf::from("/xdg/config/splitrail/config.toml")
+        );
+        assert_eq!(roots.cache_dir, PathBuf::from("/xdg/cache/splitrail"));
+        assert!(roots.state_dir.is_absolute());
+        assert!(roots.state_dir.ends_with("splitrail"));
+    }
+
+    #[test]
+    fn profiles_get_their_own_directories() {
+        let roots = resolve_roots(&Env {
+            splitrail_home: Some("/opt/splitrail".into()),
+            profile: Some("work".to_string()),
+            ..Env::default()
+        })
+        .unwrap();
+        assert_eq!(
+            roots.config_file,
+            PathBuf::from("/opt/splitrail/profiles/work/config.toml")
+        );
+        assert_eq!(
+            roots.cache_dir,
+            PathBuf::from("/opt/splitrail/cache/profiles/work")
+        );
+        assert_eq!(
+            roots.state_dir,
+            PathBuf::from("/opt/splitrail/state/profiles/work")
+        );
+
+        let roots = resolve_roots(&Env {
+            config_home: Some("/xdg/config".into()),
+            profile: Some("personal".to_string()),
+            ..Env::default()
+        })
+        .unwrap();
+        assert_eq!(
+            roots.config_file,
+            PathBuf::from("/xdg/config/splitrail/profiles/personal/config.toml")
+        );
+
+        assert!(validate_profile("work_2-b").is_ok());
+        assert!(validate_profile("../work").is_err());
+        assert!(validate_profile("").is_err());
+    }
+
+    #[test]
+    fn data_dir_prefers_env_then_config_then_state_dir() {
+        assert_eq!(
+            resolve_data_dir(Some("/env/splitrail".into()), Some("/cfg/splitrail"), None).unwrap(),
+            PathBuf::from("/env/splitrail")
+        );
+        assert_eq!(
+            resolve_data_dir(Some(OsString::new()), Some(" /cfg/splitrail "), None).unwrap(),
+            PathBuf::from("/cfg/splitrail")
+        );
+        let default = resolve_data_dir(None, Some(""), None).unwrap();
+        assert!(default.ends_with("splitrail"));
+    }
+
+    #[test]
+    fn explicit_data_dirs_are_split_by_profile() {
+        assert_eq!(
+            resolve_data_dir(Some("/env/splitrail".into()), None, Some("work")).unwrap(),
+            PathBuf::from("/env/splitrail/profiles/work")
+        );
+        assert_eq!(
+            resolve_data_dir(None, Some("/cfg/splitrail"), Some("personal")).unwrap(),
+            PathBuf::from("/cfg/splitrail/profiles/personal")
+        );
+    }
+
+    #[test]
+    fn legacy_files_move_without_replacing_newer_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        let legacy = home.join(".splitrail");
+        fs::create_dir_all(legacy.join("outbox")).unwrap();
+        fs::write(home.join(".splitrail.toml"), "old config").unwrap();
+        fs::write(legacy.join("pricing.json"), "{}").unwrap();
+        fs::write(legacy.join("splitrail.db"), "db").unwrap();
+        fs::write(legacy.join("outbox/1.json"), "batch").unwrap();
+
+        let roots = Roots {
+            config_file: dir.path().join("config/splitrail/config.toml"),
+            cache_dir: dir.path().join("cache/splitrail"),
+            state_dir: dir.path().join("state/splitrail"),
+        };
+        fs::create_dir_all(&roots.cache_dir).unwrap();
+        fs::write(roots.cache_dir.join("pricing.json"), "newer").unwrap();
+
+        let moved = migrate(&home, &roots);
+        assert_eq!(moved.len(), 3);
+        assert_eq!(
+            fs::read_to_string(&roots.config_file).unwrap(),
+            "old config"
+        );
+        assert_eq!(
+            fs::read_to_string(roots.state_dir.join("outbox/1.json")).unwrap(),
+            "batch"
+        );
+        assert!(roots.state_dir.join("splitrail.db").exists());
+        assert_eq!(
+            fs::read_to_string(roots.cache_dir.join("pricing.json")).unwrap(),
+            "newer"
+        );
+        // The stale pricing cache stays behind, so the old directory does too.
+        assert!(legacy.join("pricing.json").exists());
+        assert!(!home.join(".splitrail.toml").exists());
+
+        assert!(migrate(&home, &roots).is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! Versioned files splitrail writes for itself, and what happens when one
+//! can't be read.
+//!
+//! JSON files carry a `formatVersion` field. Reading one runs the format's
+//! migrations from the version on disk up to the current one, so an upgrade
+//! never needs the file deleted by hand; a file from a newer splitrail is
+//! left alone rather than overwritten with an older layout. A file that
+//! can't be parsed or migrated is renamed with a `.bad` suffix (kept for bug
+//! reports) and the caller starts fresh.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Key holding the format version in versioned JSON files.
+pub const VERSION_KEY: &str = "formatVersion";
+
+/// Upgrades a parsed file by one version, in place.
+pub type Migration = fn(&mut OwnedValue) -> Result<()>;
+
+/// The layout history of one JSON file.
+pub struct JsonFormat {
+    /// What the file holds, for warnings, e.g. "status snapshot".
+    pub name: &'static str,
+    /// `migrations[n]` upgrades version `n` to `n + 1`; the current version
+    /// is the number of migrations. Files from before versioning are version 0.
+    pub migrations: &'static [Migration],
+}
+
+impl JsonFormat {
+    pub fn version(&self) -> u64 {
+        self.migrations.len() as u64
+    }
+}
+
+/// The path a corrupt file is moved to.
+pub fn quarantine_path(path: &Path) -> PathBuf {
+    let mut bad = path.as_os_str().to_owned();
+    bad.push(".bad");
+    PathBuf::from(bad)
+}
+
+/// Move an unreadable file out of the way so the next write starts fresh,
+/// replacing any earlier quarantined copy.
+pub fn quarantine(path: &Path, what: &str, reason: &anyhow::Error) {
+    let bad = quarantine_path(path);
+    let outcome = match std::fs::rename(path, &bad) {
+        Ok(()) => format!("moved it to {} and starting fresh", bad.display()),
+        Err(e) => format!("couldn't move it aside: {e}"),
+    };
+    crate::utils::warn_once(format!(
+        "WARNING: {what} at {} is unreadable ({reason:#}); {outcome}.",
+        path.display()
+    ));
+}
+
+/// Parse and migrate a versioned file's contents. `Ok(None)` means it was
+/// written by a newer splitrail; errors mean it is corrupt.
+pub fn decode_json<T: DeserializeOwned>(
+    bytes: &mut [u8],
+    format: &JsonFormat,
+) -> Result<Option<T>> {
+    let mut value: OwnedValue = simd_json::to_owned_value(bytes).context("not valid JSON")?;
+    if value.as_object().is_none() {
+        anyhow::bail!("not a JSON object");
+    }
+    let version = match value.get(VERSION_KEY) {
+        None => 0,
+        Some(version) => version.as_u64().context("invalid format version")?,
+    };
+    if version > format.version() {
+        return Ok(None);
+    }
+    for (from, migrate) in format.migrations.iter().enumerate().skip(version as usize) {
+        migrate(&mut value).with_context(|| format!("migrating from version {from}"))?;
+    }
+    let decoded = simd_json::serde::from_owned_value(value).context("unexpected layout")?;
+    Ok(Some(decoded))
+}
+
+/// Read a versioned file. Missing files, files from a newer splitrail and
+/// corrupt files (which are quarantined) all read as `None`.
+pub fn read_json<T: DeserializeOwned>(path: &Path, format: &JsonFormat) -> Result<Option<T>> {
+    let mut bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", format.name)),
+    };
+    match decode_json(&mut bytes, format) {
+        Ok(Some(decoded)) => Ok(Some(decoded)),
+        Ok(None) => {
+            crate::utils::warn_once(format!(
+                "WARNING: {} at {} was written by a newer splitrail; ignoring it.",
+                format.name,
+                path.display()
+            ));
+            Ok(None)
+        }
+        Err(e) => {
+            quarantine(path, format.name, &e);
+            Ok(None)
+        }
+    }
+}
+
+/// Serialize `value` with the current format version.
+pub fn encode_json<T: Serialize>(value: &T, format: &JsonFormat) -> Result<String> {
+    let mut encoded = simd_json::serde::to_owned_value(value)?;
+    encoded
+        .as_object_mut()
+        .context("versioned files must serialize to a JSON object")?
+        .insert(VERSION_KEY.into(), OwnedValue::from(format.version()));
+    Ok(simd_json::to_string(&encoded)?)
+}
+
+/// Write `value` with the current format version; see [`write_atomic`]. A
+/// file already written by a newer splitrail is kept, and nothing is written.
+pub fn write_json<T: Serialize>(path: &Path, format: &JsonFormat, value: &T) -> Result<()> {
+    if let Some(version) = version_on_disk(path)
+        && version > format.version()
+    {
+        crate::utils::warn_once(format!(
+            "WARNING: {} at {} was written by a newer splitrail; not overwriting it.",
+            format.name,
+            path.display()
+        ));
+        return Ok(());
+    }
+    write_atomic(path, encode_json(value, format)?, format.name)
+}
+
+/// The format version of the file at `path`, if it exists and has one.
+fn version_on_disk(path: &Path) -> Option<u64> {
+    let mut bytes = std::fs::read(path).ok()?;
+    let value: OwnedValue = simd_json::to_owned_value(&mut bytes).ok()?;
+    value.get(VERSION_KEY)?.as_u64()
+}
+
+/// Replace `path` through a temporary file and a rename, so another process
+/// reading it never sees half a file. The temporary name includes the pid,
+/// so concurrent writers don't clobber each other's halves either.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>, what: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = PathBuf::from(tmp_path);
+    std::fs::write(&tmp_path, contents).with_context(|| format!("Failed to write {what}"))?;
+    std::fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {what}"))
+}
+
+/// Migration for files written before versioning whose layout didn't
+/// change: the version header is all they lack.
+pub fn add_version_header(_value: &mut OwnedValue) -> Result<()> {
+    Ok(())
+}
+
+/// Whether a SQLite error means the file itself is damaged or isn't a
+/// database, as opposed to being busy or unwritable.
+pub fn is_corrupt_sqlite(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<rusqlite::Error>(),
+            Some(rusqlite::Error::SqliteFailure(failure, _))
+                if matches!(
+                    failure.code,
+                    rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase
+                )
+        )
+    })
+}
+
+/// Bring a SQLite database up to date. `migrations[n]` upgrades schema
+/// version `n` (kept in `user_version`) to `n + 1`; `what` names the
+/// database in errors, e.g. "SQLite store".
+pub fn migrate_sqlite(
+    connection: &rusqlite::Connection,
+    migrations: &[&str],
+    what: &str,
+) -> Result<()> {
+    let latest = migrations.len() as i64;
+    let version: i64 = connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .with_context(|| format!("Failed to read {what} version"))?;
+    if version > latest {
+        anyhow::bail!("the {what} was written by a newer splitrail (version {version})");
+    }
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize) {
+        connection
+            .execute_batch(migration)
+            .with_context(|| format!("Failed to upgrade {what} from version {from}"))?;
+    }
+    if version < latest {
+        connection
+            .pragma_update(None, "user_version", latest)
+            .with_context(|| format!("Failed to record {what} version"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        total: u64,
+        label: String,
+    }
+
+    /// Version 1 renamed `count` to `total`; version 2 added `label`.
+    const SAMPLE: JsonFormat = JsonFormat {
+        name: "sample file",
+        migrations: &[
+            |value| {
+                let object = value.as_object_mut().context("not an object")?;
+                let count = object.remove("count").context("missing count")?;
+                object.insert("total".into(), count);
+                Ok(())
+            },
+            |value| {
+                let object = value.as_object_mut().context("not an object")?;
+                object.insert("label".into(), OwnedValue::from("migrated"));
+                Ok(())
+            },
+        ],
+    };
+
+    #[test]
+    fn old_files_are_migrated_and_current_ones_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.json");
+
+        std::fs::write(&path, r#"{"count": 3}"#).unwrap();
+        let migrated: Sample = read_json(&path, &SAMPLE).unwrap().unwrap();
+        assert_eq!(
+            migrated,
+            Sample {
+                total: 3,
+                label: "migrated".to_string()
+            }
+        );
+
+        write_json(&path, &SAMPLE, &migrated).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains(r#""formatVersion":2"#)
+        );
+        assert_eq!(read_json::<Sample>(&path, &SAMPLE).unwrap(), Some(migrated));
+    }
+
+    #[test]
+    fn corrupt_files_are_quarantined_and_newer_ones_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sample.json");
+
+        let newer = r#"{"total": 1, "label": "x", "formatVersion": 9}"#;
+        std::fs::write(&path, newer).unwrap();
+        assert_eq!(read_json::<Sample>(&path, &SAMPLE).unwrap(), None);
+        assert!(path.exists());
+        let current = Sample {
+            total: 2,
+            label: "y".to_string(),
+        };
+        write_json(&path, &SAMPLE, &current).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+
+        std::fs::write(&path, "{\"total\": 1, \"lab").unwrap();
+        assert_eq!(read_json::<Sample>(&path, &SAMPLE).unwrap(), None);
+        assert!(!path.exists());
+        assert!(quarantine_path(&path).exists());
+
+        // A migration that fails quarantines too.
+        std::fs::write(&path, r#"{"formatVersion": 0}"#).unwrap();
+        assert_eq!(read_json::<Sample>(&path, &SAMPLE).unwrap(), None);
+        assert!(!path.exists());
+
+        assert_eq!(
+            read_json::<Sample>(&dir.path().join("missing.json"), &SAMPLE).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! GitHub Copilot premium requests and the overage they cost past a plan's
+//! monthly allowance.
+//!
+//! Copilot bills several models per request rather than per token: each
+//! prompt uses one premium request times the model's multiplier. Multipliers
+//! are those of the paid plans, where the base models (GPT-4.1, GPT-4o,
+//! GPT-5 mini) are included at 0.
+
+use crate::budget::to_compact;
+use crate::types::{DateRange, SharedAnalyzerView};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Price of each premium request past the plan's allowance.
+pub const OVERAGE_USD_PER_REQUEST: f64 = 0.04;
+
+/// Multipliers by model name prefix, most specific first.
+const MULTIPLIERS: &[(&str, f64)] = &[
+    ("gpt-4.1", 0.0),
+    ("gpt-4o", 0.0),
+    ("gpt-5-mini", 0.0),
+    ("grok-code-fast", 0.0),
+    ("gpt-5-codex-mini", 0.33),
+    ("gpt-5.1-codex-mini", 0.33),
+    ("claude-haiku-4.5", 0.33),
+    ("o3-mini", 0.33),
+    ("o4-mini", 0.33),
+    ("gemini-2.0-flash", 0.25),
+    ("claude-3.7-sonnet-thought", 1.25),
+    ("claude-opus-4.5", 3.0),
+    ("claude-opus-4", 10.0),
+    ("o1", 10.0),
+    ("gpt-4.5", 50.0),
+];
+
+/// Premium requests one prompt to `model` uses. Models not in the table
+/// count as one request.
+pub fn multiplier(model: &str) -> f64 {
+    let model = model.trim().to_lowercase().replace(' ', "-");
+    MULTIPLIERS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(1.0, |(_, multiplier)| *multiplier)
+}
+
+/// The Copilot plan whose allowance premium requests are counted against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CopilotPlan {
+    Free,
+    #[default]
+    Pro,
+    ProPlus,
+    Business,
+    Enterprise,
+}
+
+impl CopilotPlan {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace('_', "-").as_str() {
+            "free" => Some(CopilotPlan::Free),
+            "pro" => Some(CopilotPlan::Pro),
+            "pro-plus" | "pro+" => Some(CopilotPlan::ProPlus),
+            "business" => Some(CopilotPlan::Business),
+            "enterprise" => Some(CopilotPlan::Enterprise),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CopilotPlan::Free => "Free",
+            CopilotPlan::Pro => "Pro",
+            CopilotPlan::ProPlus => "Pro+",
+            CopilotPlan::Business => "Business",
+            CopilotPlan::Enterprise => "Enterprise",
+        }
+    }
+
+    /// Premium requests included each month (per seat on Business and
+    /// Enterprise).
+    pub fn included(self) -> u32 {
+        match self {
+            CopilotPlan::Free => 50,
+            CopilotPlan::Pro | CopilotPlan::Business => 300,
+            CopilotPlan::ProPlus => 1500,
+            CopilotPlan::Enterprise => 1000,
+        }
+    }
+}
+
+/// Premium requests used this month against a plan's allowance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PremiumUsage {
+    pub plan: CopilotPlan,
+    pub used: f64,
+    /// Requests past the allowance, billed at [`OVERAGE_USD_PER_REQUEST`].
+    pub overage_requests: f64,
+    pub overage_cost: f64,
+}
+
+impl PremiumUsage {
+    pub fn new(plan: CopilotPlan, used: f64) -> Self {
+        let overage_requests = (used - plan.included() as f64).max(0.0);
+        Self {
+            plan,
+            used,
+            overage_requests,
+            overage_cost: overage_requests * OVERAGE_USD_PER_REQUEST,
+        }
+    }
+}
+
+/// Premium requests used by every tool from the first of `today`'s month,
+/// or `None` when there were none.
+pub fn month_to_date(
+    views: &[SharedAnalyzerView],
+    plan: CopilotPlan,
+    today: NaiveDate,
+) -> Option<PremiumUsage> {
+    let month = DateRange {
+        since: Some(to_compact(today.with_day(1).unwrap_or(today))),
+        until: Some(to_compact(today)),
+    };
+    let hundredths: u64 = views
+        .iter()
+        .map(|view| {
+            view.read()
+                .daily_stats
+                .values()
+                .filter(|day| month.contains(day.date))
+                .map(|day| day.stats.premium_hundredths as u64)
+                .sum::<u64>()
+        })
+        .sum();
+    (hundredths > 0).then(|| PremiumUsage::new(plan, hundredths as f64 / 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multipliers_match_the_most_specific_model() {
+        assert_eq!(multiplier("gpt-4.1"), 0.0);
+        assert_eq!(multiplier("GPT-5 mini"), 0.0);
+        assert_eq!(multiplier("gpt-5"), 1.0);
+        assert_eq!(multiplier("claude-sonnet-4"), 1.0);
+        assert_eq!(multiplier("claude-3.7-sonnet-thought"), 1.25);
+        assert_eq!(multiplier("claude-opus-4.1"), 10.0);
+        assert_eq!(multiplier("claude-opus-4.5"), 3.0);
+        assert_eq!(multiplier("o4-mini"), 0.33);
+        assert_eq!(multiplier("some-new-model"), 1.0);
+    }
+
+    #[test]
+    fn overage_only_counts_requests_past_the_allowance() {
+        let within = PremiumUsage::new(CopilotPlan::Pro, 120.5);
+        assert_eq!(within.overage_requests, 0.0);
+        assert_eq!(within.overage_cost, 0.0);
+
+        let over = PremiumUsage::new(CopilotPlan::Pro, 350.0);
+        assert_eq!(over.overage_requests, 50.0);
+        assert!((over.overage_cost - 2.0).abs() < 1e-9);
+
+        assert_eq!(CopilotPlan::parse("Pro+"), Some(CopilotPlan::ProPlus));
+        assert_eq!(CopilotPlan::parse("pro_plus"), Some(CopilotPlan::ProPlus));
+        assert_eq!(CopilotPlan::parse("team"), None);
+    }
+}
@@ -0,0 +1,544 @@
+//! Ad-hoc filtering and grouping for `splitrail query`.
+//!
+//! A filter compares message fields against values and combines the
+//! comparisons with `AND`, `OR`, `NOT`, and parentheses:
+//!
+//! ```text
+//! model ~ 'sonnet' AND date >= 2025-06-01 AND NOT analyzer = 'Codex CLI'
+//! ```
+//!
+//! `=`/`!=` compare text case-insensitively, `~` matches a substring, and
+//! `<`, `<=`, `>`, `>=` compare numbers numerically and text (dates included)
+//! lexicographically. `date` values also accept `today`, `yesterday`, and `<N>d`.
+
+use crate::types::{ConversationMessage, DateRange, MessageRole, MultiAnalyzerStats};
+use anyhow::{Context, Result, bail, ensure};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Analyzer,
+    Model,
+    Date,
+    Month,
+    Project,
+    Session,
+    Role,
+    Cost,
+    InputTokens,
+    OutputTokens,
+    ReasoningTokens,
+    CacheCreationTokens,
+    CacheReadTokens,
+    CachedTokens,
+    TotalTokens,
+    ToolCalls,
+    LinesAdded,
+    LinesDeleted,
+}
+
+impl Field {
+    const ALL: &[Field] = &[
+        Field::Analyzer,
+        Field::Model,
+        Field::Date,
+        Field::Month,
+        Field::Project,
+        Field::Session,
+        Field::Role,
+        Field::Cost,
+        Field::InputTokens,
+        Field::OutputTokens,
+        Field::ReasoningTokens,
+        Field::CacheCreationTokens,
+        Field::CacheReadTokens,
+        Field::CachedTokens,
+        Field::TotalTokens,
+        Field::ToolCalls,
+        Field::LinesAdded,
+        Field::LinesDeleted,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Analyzer => "analyzer",
+            Field::Model => "model",
+            Field::Date => "date",
+            Field::Month => "month",
+            Field::Project => "project",
+            Field::Session => "session",
+            Field::Role => "role",
+            Field::Cost => "cost",
+            Field::InputTokens => "input_tokens",
+            Field::OutputTokens => "output_tokens",
+            Field::ReasoningTokens => "reasoning_tokens",
+            Field::CacheCreationTokens => "cache_creation_tokens",
+            Field::CacheReadTokens => "cache_read_tokens",
+            Field::CachedTokens => "cached_tokens",
+            Field::TotalTokens => "total_tokens",
+            Field::ToolCalls => "tool_calls",
+            Field::LinesAdded => "lines_added",
+            Field::LinesDeleted => "lines_deleted",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = match name.as_str() {
+            "tool" => "analyzer",
+            "conversation" => "session",
+            other => other,
+        };
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|field| field.name() == name)
+            .with_context(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|field| field.name()).collect();
+                format!("Unknown field '{name}'. Fields: {}", names.join(", "))
+            })
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Field::Cost
+                | Field::InputTokens
+                | Field::OutputTokens
+                | Field::ReasoningTokens
+                | Field::CacheCreationTokens
+                | Field::CacheReadTokens
+                | Field::CachedTokens
+                | Field::TotalTokens
+                | Field::ToolCalls
+                | Field::LinesAdded
+                | Field::LinesDeleted
+        )
+    }
+
+    fn text(self, row: &Row) -> String {
+        let message = row.message;
+        match self {
+            Field::Analyzer => row.analyzer.to_string(),
+            Field::Model => message.model.clone().unwrap_or_default(),
+            Field::Date => row.date.clone(),
+            Field::Month => row.date[..7].to_string(),
+            Field::Project => message.project_hash.clone(),
+            Field::Session => message.conversation_hash.clone(),
+            Field::Role => match message.role {
+                MessageRole::User => "user".to_string(),
+                MessageRole::Assistant => "assistant".to_string(),
+            },
+            numeric => numeric.number(row).to_string(),
+        }
+    }
+
+    fn number(self, row: &Row) -> f64 {
+        let stats = &row.message.stats;
+        match self {
+            Field::Cost => stats.cost,
+            Field::InputTokens => stats.input_tokens as f64,
+            Field::OutputTokens => stats.output_tokens as f64,
+            Field::ReasoningTokens => stats.reasoning_tokens as f64,
+            Field::CacheCreationTokens => stats.cache_creation_tokens as f64,
+            Field::CacheReadTokens => stats.cache_read_tokens as f64,
+            Field::CachedTokens => stats.cached_tokens as f64,
+            Field::TotalTokens => {
+                (stats.input_tokens
+                    + stats.output_tokens
+                    + stats.reasoning_tokens
+                    + stats.cache_creation_tokens
+                    + stats.cache_read_tokens
+                    + stats.cached_tokens) as f64
+            }
+            Field::ToolCalls => stats.tool_calls as f64,
+            Field::LinesAdded => stats.lines_added as f64,
+            Field::LinesDeleted => stats.lines_deleted as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+struct Row<'a> {
+    analyzer: &'a str,
+    message: &'a ConversationMessage,
+    /// Local `YYYY-MM-DD`.
+    date: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare { field: Field, op: Op, value: Value },
+}
+
+impl Expr {
+    fn matches(&self, row: &Row) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(row) && b.matches(row),
+            Expr::Or(a, b) => a.matches(row) || b.matches(row),
+            Expr::Not(inner) => !inner.matches(row),
+            Expr::Compare { field, op, value } => {
+                let ordering = match value {
+                    Value::Number(expected) => field.number(row).partial_cmp(expected),
+                    Value::Text(expected) => {
+                        let actual = field.text(row).to_lowercase();
+                        if *op == Op::Contains {
+                            return actual.contains(expected.as_str());
+                        }
+                        Some(actual.as_str().cmp(expected.as_str()))
+                    }
+                };
+                let Some(ordering) = ordering else {
+                    return false;
+                };
+                match op {
+                    Op::Eq => ordering.is_eq(),
+                    Op::Ne => ordering.is_ne(),
+                    Op::Lt => ordering.is_lt(),
+                    Op::Le => ordering.is_le(),
+                    Op::Gt => ordering.is_gt(),
+                    Op::Ge => ordering.is_ge(),
+                    Op::Contains => false,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => text.push(next),
+                        None => bail!("Unterminated string starting with {c}{text}"),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                chars.next();
+                let followed_by_eq = chars.next_if_eq(&'=').is_some();
+                let op = match (c, followed_by_eq) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('<', true) => Op::Le,
+                    ('<', false) if chars.next_if_eq(&'>').is_some() => Op::Ne,
+                    ('<', false) => Op::Lt,
+                    ('>', true) => Op::Ge,
+                    ('>', false) => Op::Gt,
+                    ('~', false) => Op::Contains,
+                    _ => bail!("Unknown operator starting with '{c}'"),
+                };
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "()'\"=!<>~".contains(next) {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                ensure!(self.next() == Some(Token::Close), "Expected ')'");
+                Ok(expr)
+            }
+            Some(Token::Word(name)) => {
+                let field = Field::parse(&name)?;
+                let Some(Token::Op(op)) = self.next() else {
+                    bail!("Expected an operator after '{name}'");
+                };
+                let raw = match self.next() {
+                    Some(Token::Word(value) | Token::Quoted(value)) => value,
+                    _ => bail!("Expected a value after '{name}'"),
+                };
+                Ok(Expr::Compare {
+                    field,
+                    op,
+                    value: comparison_value(field, op, &raw)?,
+                })
+            }
+            Some(token) => bail!("Unexpected {token:?}"),
+            None => bail!("Filter ended early"),
+        }
+    }
+}
+
+fn comparison_value(field: Field, op: Op, raw: &str) -> Result<Value> {
+    if field.is_numeric() {
+        ensure!(op != Op::Contains, "'~' only works on text fields");
+        let number = raw
+            .trim_start_matches('$')
+            .parse::<f64>()
+            .with_context(|| format!("'{}' needs a number, got '{raw}'", field.name()))?;
+        return Ok(Value::Number(number));
+    }
+    if field == Field::Date && op != Op::Contains {
+        let date = DateRange::parse_bound(raw).with_context(|| {
+            format!("expected YYYY-MM-DD, today, yesterday, or <N>d, got '{raw}'")
+        })?;
+        return Ok(Value::Text(date.to_string()));
+    }
+    Ok(Value::Text(raw.to_lowercase()))
+}
+
+pub fn parse_filter(input: &str) -> Result<Option<Expr>> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.or()?;
+    if let Some(extra) = parser.tokens.get(parser.pos) {
+        bail!("Unexpected {extra:?} after the end of the filter");
+    }
+    Ok(Some(expr))
+}
+
+/// One output row: the group's key values, its message count, and one
+/// total per summed field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryRow {
+    pub group: Vec<String>,
+    pub messages: u64,
+    pub sums: Vec<f64>,
+}
+
+pub struct Query {
+    pub filter: Option<Expr>,
+    pub group_by: Vec<Field>,
+    pub sums: Vec<Field>,
+}
+
+impl Query {
+    /// Filter and aggregate every message, one row per distinct group in
+    /// ascending key order.
+    pub fn run(&self, stats: &MultiAnalyzerStats) -> Vec<QueryRow> {
+        let mut groups: BTreeMap<Vec<String>, QueryRow> = BTreeMap::new();
+        for analyzer_stats in &stats.analyzer_stats {
+            for message in &analyzer_stats.messages {
+                let row = Row {
+                    analyzer: &analyzer_stats.analyzer_name,
+                    message,
+                    date: crate::types::CompactDate::from_local(&message.date).to_string(),
+                };
+                if self
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| !filter.matches(&row))
+                {
+                    continue;
+                }
+                let key: Vec<String> = self.group_by.iter().map(|field| field.text(&row)).collect();
+                let entry = groups.entry(key.clone()).or_insert_with(|| QueryRow {
+                    group: key,
+                    messages: 0,
+                    sums: vec![0.0; self.sums.len()],
+                });
+                entry.messages += 1;
+                for (total, field) in entry.sums.iter_mut().zip(&self.sums) {
+                    *total += field.number(&row);
+                }
+            }
+        }
+        groups.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgenticCodingToolStats, Application, Stats};
+    use chrono::{TimeZone, Utc};
+
+    fn message(model: &str, day: u32, output_tokens: u64, cost: f64) -> ConversationMessage {
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap(),
+            project_hash: "project".to_string(),
+            conversation_hash: "session".to_string(),
+            local_hash: None,
+            global_hash: format!("{model}-{day}"),
+            model: Some(model.to_string()),
+            stats: Stats {
+                output_tokens,
+                cost,
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        }
+    }
+
+    fn sample() -> MultiAnalyzerStats {
+        let tool = |name: &str, messages| AgenticCodingToolStats {
+            daily_stats: BTreeMap::new(),
+            num_conversations: 1,
+            messages,
+            analyzer_name: name.to_string(),
+        };
+        MultiAnalyzerStats {
+            analyzer_stats: vec![
+                tool(
+                    "Claude Code",
+                    vec![
+                        message("claude-sonnet-4-20250514", 1, 100, 1.0),
+                        message("claude-sonnet-4-20250514", 10, 200, 2.0),
+                        message("claude-opus-4-1", 10, 50, 5.0),
+                    ],
+                ),
+                tool("Codex CLI", vec![message("gpt-5", 10, 400, 0.5)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn parses_precedence_and_parentheses() {
+        let filter = parse_filter("NOT model ~ gpt AND (cost > 1 OR date <= 2025-06-01)")
+            .unwrap()
+            .unwrap();
+        let Expr::And(left, right) = filter else {
+            panic!("AND should bind loosest here: {filter:?}");
+        };
+        assert!(matches!(*left, Expr::Not(_)));
+        assert!(matches!(*right, Expr::Or(_, _)));
+
+        assert!(parse_filter("  ").unwrap().is_none());
+        assert!(parse_filter("cost > lots").is_err());
+        assert!(parse_filter("colour = 'red'").is_err());
+        assert!(parse_filter("model = 'x' extra").is_err());
+        assert!(parse_filter("(model = 'x'").is_err());
+    }
+
+    #[test]
+    fn filters_groups_and_sums_messages() {
+        let query = Query {
+            filter: parse_filter("model ~ 'sonnet' AND date >= 2025-06-02").unwrap(),
+            group_by: vec![Field::Model],
+            sums: vec![Field::Cost, Field::OutputTokens],
+        };
+        assert_eq!(
+            query.run(&sample()),
+            vec![QueryRow {
+                group: vec!["claude-sonnet-4-20250514".to_string()],
+                messages: 1,
+                sums: vec![2.0, 200.0],
+            }]
+        );
+
+        let by_tool = Query {
+            filter: parse_filter("analyzer != 'claude code' OR cost >= 5").unwrap(),
+            group_by: vec![Field::Analyzer, Field::Date],
+            sums: vec![Field::Cost],
+        };
+        let rows = by_tool.run(&sample());
+        let groups: Vec<_> = rows.iter().map(|row| row.group.join(" / ")).collect();
+        assert_eq!(
+            groups,
+            ["Claude Code / 2025-06-10", "Codex CLI / 2025-06-10"]
+        );
+        assert_eq!(rows[0].sums, vec![5.0]);
+    }
+}
@@ -0,0 +1,658 @@
+//! `splitrail report --period week --format markdown|html`: a summary of a
+//! day, week or month of usage to post in a team channel or send upward.
+//!
+//! Built from the same per-day rollups and session aggregates as the TUI, so
+//! it needs no raw messages. Markdown draws the daily trend with block
+//! characters; HTML is one self-contained file with an inline SVG chart.
+
+use crate::tui::columns::{Column, ColumnLayout};
+use crate::tui::logic::aggregate_model_stats;
+use crate::types::{CompactDate, DateRange, SharedAnalyzerView, resolve_model};
+use crate::utils::{NumberFormatOptions, format_number};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Sessions listed under "Top sessions".
+const TOP_SESSIONS: usize = 10;
+
+/// Models named in the model mix before the rest are summed up.
+const TOP_MODELS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn days(self) -> u64 {
+        match self {
+            Period::Day => 1,
+            Period::Week => 7,
+            Period::Month => 30,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Period::Day => "Daily",
+            Period::Week => "Weekly",
+            Period::Month => "Monthly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub title: String,
+    pub range: String,
+    pub cost: f64,
+    pub sessions: usize,
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub tool_calls: u64,
+    pub tools: Vec<ToolRow>,
+    pub top_sessions: Vec<SessionRow>,
+    pub models: Vec<ModelRow>,
+    /// Every day in the range, including ones without usage.
+    pub days: Vec<DayRow>,
+    /// Token and tool-call columns of the "By tool" table, in order.
+    #[serde(skip)]
+    pub columns: Vec<Column>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolRow {
+    pub name: String,
+    pub cost: f64,
+    pub sessions: usize,
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub tool_calls: u64,
+}
+
+impl ToolRow {
+    /// The value shown under a [`Report::columns`] entry.
+    fn column(&self, column: Column) -> u64 {
+        match column {
+            Column::Cached => self.cached_tokens,
+            Column::Input => self.input_tokens,
+            Column::Output => self.output_tokens,
+            Column::Reason => self.reasoning_tokens,
+            Column::Tools => self.tool_calls,
+            _ => 0,
+        }
+    }
+}
+
+/// Header of a [`Report::columns`] entry.
+fn column_header(column: Column) -> &'static str {
+    match column {
+        Column::Cached => "Cached tokens",
+        Column::Input => "Input tokens",
+        Column::Output => "Output tokens",
+        Column::Reason => "Reasoning tokens",
+        Column::Tools => "Tool calls",
+        _ => "",
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRow {
+    pub tool: String,
+    pub name: String,
+    pub date: String,
+    pub model: String,
+    pub cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelRow {
+    pub model: String,
+    pub messages: u32,
+    pub cost: f64,
+    /// Share of the period's cost, 0 to 1.
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayRow {
+    pub date: String,
+    pub cost: f64,
+    pub messages: u64,
+}
+
+impl Report {
+    /// Summarize the `period` ending on `today`.
+    pub fn build(views: &[SharedAnalyzerView], period: Period, today: NaiveDate) -> Self {
+        let start = today - chrono::Days::new(period.days() - 1);
+        let compact = |date: NaiveDate| CompactDate::from_str(&date.to_string());
+        let range = DateRange {
+            since: compact(start),
+            until: compact(today),
+        };
+        let mut report = Report {
+            title: format!("{} usage report", period.title()),
+            range: range.to_string(),
+            columns: vec![Column::Input, Column::Output],
+            ..Report::default()
+        };
+
+        let mut days = Vec::new();
+        let mut sessions = Vec::new();
+        for view in views {
+            let view = view.read().within(range);
+            let mut tool = ToolRow {
+                name: view.analyzer_name.to_string(),
+                sessions: view.session_aggregates.len(),
+                ..ToolRow::default()
+            };
+            for day in view.daily_stats.values() {
+                tool.cost += day.stats.cost();
+                tool.messages += u64::from(day.user_messages + day.ai_messages);
+                tool.input_tokens += day.stats.input_tokens;
+                tool.output_tokens += day.stats.output_tokens;
+                tool.cached_tokens += day.stats.cached_tokens;
+                tool.reasoning_tokens += day.stats.reasoning_tokens;
+                tool.tool_calls += u64::from(day.stats.tool_calls);
+            }
+            sessions.extend(view.session_aggregates.iter().map(|session| {
+                SessionRow {
+                    tool: view.analyzer_name.to_string(),
+                    name: session
+                        .session_name
+                        .clone()
+                        .unwrap_or_else(|| session.display_id().to_string()),
+                    date: session.date.to_string(),
+                    model: session
+                        .models
+                        .iter()
+                        .max_by_key(|(_, count)| *count)
+                        .map(|(key, _)| resolve_model(*key).to_string())
+                        .unwrap_or_default(),
+                    cost: session.stats.cost(),
+                }
+            }));
+            days.extend(view.daily_stats.into_values());
+            if tool.messages > 0 {
+                report.tools.push(tool);
+            }
+        }
+
+        report
+            .tools
+            .sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.name.cmp(&b.name)));
+        for tool in &report.tools {
+            report.cost += tool.cost;
+            report.sessions += tool.sessions;
+            report.messages += tool.messages;
+            report.input_tokens += tool.input_tokens;
+            report.output_tokens += tool.output_tokens;
+            report.tool_calls += tool.tool_calls;
+        }
+
+        sessions.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        sessions.truncate(TOP_SESSIONS);
+        report.top_sessions = sessions;
+
+        let share = |cost: f64| {
+            if report.cost > 0.0 {
+                cost / report.cost
+            } else {
+                0.0
+            }
+        };
+        let mut models = aggregate_model_stats(&days).into_iter();
+        report.models = models
+            .by_ref()
+            .take(TOP_MODELS)
+            .map(|model| ModelRow {
+                share: share(model.cost),
+                model: model.model,
+                messages: model.message_count,
+                cost: model.cost,
+            })
+            .collect();
+        let (messages, cost) = models.fold((0, 0.0), |(messages, cost), model| {
+            (messages + model.message_count, cost + model.cost)
+        });
+        if messages > 0 {
+            report.models.push(ModelRow {
+                model: "Other".to_string(),
+                messages,
+                cost,
+                share: share(cost),
+            });
+        }
+
+        report.days = start
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| {
+                let key = date.to_string();
+                let (cost, messages) = days.iter().filter(|day| day.date.to_string() == key).fold(
+                    (0.0, 0),
+                    |(cost, messages), day| {
+                        (
+                            cost + day.stats.cost(),
+                            messages + u64::from(day.user_messages + day.ai_messages),
+                        )
+                    },
+                );
+                DayRow {
+                    date: key,
+                    cost,
+                    messages,
+                }
+            })
+            .collect();
+        report
+    }
+
+    /// Show the TUI's visible token and tool-call columns, in its order, in
+    /// the "By tool" table. Columns a report has no totals for are left out.
+    pub fn with_columns(mut self, layout: &ColumnLayout) -> Self {
+        self.columns = layout
+            .visible()
+            .into_iter()
+            .filter(|column| {
+                matches!(
+                    column,
+                    Column::Cached
+                        | Column::Input
+                        | Column::Output
+                        | Column::Reason
+                        | Column::Tools
+                )
+            })
+            .collect();
+        self
+    }
+
+    pub fn render(&self, format: Format, options: &NumberFormatOptions) -> String {
+        match format {
+            Format::Markdown => self.to_markdown(options),
+            Format::Html => self.to_html(options),
+        }
+    }
+
+    fn to_markdown(&self, options: &NumberFormatOptions) -> String {
+        let money = |amount: f64| {
+            format!(
+                "{}{amount:.prec$}",
+                options.currency_symbol,
+                prec = options.cost_decimal_places
+            )
+        };
+        let number = |n: u64| format_number(n, options);
+        let mut out = String::new();
+        let _ = writeln!(out, "# {} ({})\n", self.title, self.range);
+        let _ = writeln!(
+            out,
+            "**{}** across {} sessions and {} messages. {} input and {} output tokens, {} tool calls.\n",
+            money(self.cost),
+            number(self.sessions as u64),
+            number(self.messages),
+            number(self.input_tokens),
+            number(self.output_tokens),
+            number(self.tool_calls)
+        );
+        if self.tools.is_empty() {
+            out.push_str("No usage in this period.\n");
+            return out;
+        }
+
+        out.push_str("## By tool\n\n| Tool | Cost | Sessions | Messages |");
+        for column in &self.columns {
+            let _ = write!(out, " {} |", column_header(*column));
+        }
+        out.push_str("\n| --- | ---: | ---: | ---: |");
+        out.push_str(&" ---: |".repeat(self.columns.len()));
+        out.push('\n');
+        for tool in &self.tools {
+            let _ = write!(
+                out,
+                "| {} | {} | {} | {} |",
+                markdown_cell(&tool.name),
+                money(tool.cost),
+                number(tool.sessions as u64),
+                number(tool.messages)
+            );
+            for column in &self.columns {
+                let _ = write!(out, " {} |", number(tool.column(*column)));
+            }
+            out.push('\n');
+        }
+
+        out.push_str("\n## Top sessions\n\n");
+        out.push_str("| Session | Tool | Date | Model | Cost |\n");
+        out.push_str("| --- | --- | --- | --- | ---: |\n");
+        for session in &self.top_sessions {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                markdown_cell(&session.name),
+                markdown_cell(&session.tool),
+                session.date,
+                markdown_cell(&session.model),
+                money(session.cost)
+            );
+        }
+
+        out.push_str("\n## Model mix\n\n");
+        out.push_str("| Model | Messages | Cost | Share |\n");
+        out.push_str("| --- | ---: | ---: | ---: |\n");
+        for model in &self.models {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.0}% |",
+                markdown_cell(&model.model),
+                number(model.messages.into()),
+                money(model.cost),
+                model.share * 100.0
+            );
+        }
+
+        if self.days.len() > 1 {
+            const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+            let max = self.days.iter().map(|day| day.cost).fold(0.0, f64::max);
+            out.push_str("\n## Daily cost\n\n");
+            out.push_str("| Date | Cost | |\n");
+            out.push_str("| --- | ---: | --- |\n");
+            for day in &self.days {
+                let bar = if max > 0.0 {
+                    let level = (day.cost / max * (BARS.len() - 1) as f64).round() as usize;
+                    BARS[level].to_string().repeat(level + 1)
+                } else {
+                    String::new()
+                };
+                let _ = writeln!(out, "| {} | {} | {bar} |", day.date, money(day.cost));
+            }
+        }
+        out
+    }
+
+    fn to_html(&self, options: &NumberFormatOptions) -> String {
+        let money = |amount: f64| {
+            format!(
+                "{}{amount:.prec$}",
+                escape_html(&options.currency_symbol),
+                prec = options.cost_decimal_places
+            )
+        };
+        let number = |n: u64| format_number(n, options);
+        let mut out = String::new();
+        let title = format!("{} ({})", escape_html(&self.title), self.range);
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+             body {{ font-family: system-ui, sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }}\n\
+             table {{ border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }}\n\
+             th, td {{ padding: 0.35rem 0.6rem; border-bottom: 1px solid #d0d7de; text-align: left; }}\n\
+             td.num, th.num {{ text-align: right; font-variant-numeric: tabular-nums; }}\n\
+             .total {{ font-size: 1.1rem; }}\n\
+             svg rect {{ fill: #0969da; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>"
+        );
+        let _ = writeln!(
+            out,
+            "<p class=\"total\"><strong>{}</strong> across {} sessions and {} messages. {} input and {} output tokens, {} tool calls.</p>",
+            money(self.cost),
+            number(self.sessions as u64),
+            number(self.messages),
+            number(self.input_tokens),
+            number(self.output_tokens),
+            number(self.tool_calls)
+        );
+        if self.tools.is_empty() {
+            out.push_str("<p>No usage in this period.</p>\n</body>\n</html>\n");
+            return out;
+        }
+
+        if self.days.len() > 1 {
+            out.push_str("<h2>Daily cost</h2>\n");
+            out.push_str(&self.trend_svg(&money));
+        }
+
+        out.push_str("<h2>By tool</h2>\n<table>\n<tr><th>Tool</th><th class=\"num\">Cost</th><th class=\"num\">Sessions</th><th class=\"num\">Messages</th>");
+        for column in &self.columns {
+            let _ = write!(out, "<th class=\"num\">{}</th>", column_header(*column));
+        }
+        out.push_str("</tr>\n");
+        for tool in &self.tools {
+            let _ = write!(
+                out,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>",
+                escape_html(&tool.name),
+                money(tool.cost),
+                number(tool.sessions as u64),
+                number(tool.messages)
+            );
+            for column in &self.columns {
+                let _ = write!(
+                    out,
+                    "<td class=\"num\">{}</td>",
+                    number(tool.column(*column))
+                );
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h2>Top sessions</h2>\n<table>\n<tr><th>Session</th><th>Tool</th><th>Date</th><th>Model</th><th class=\"num\">Cost</th></tr>\n");
+        for session in &self.top_sessions {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>",
+                escape_html(&session.name),
+                escape_html(&session.tool),
+                session.date,
+                escape_html(&session.model),
+                money(session.cost)
+            );
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h2>Model mix</h2>\n<table>\n<tr><th>Model</th><th class=\"num\">Messages</th><th class=\"num\">Cost</th><th class=\"num\">Share</th></tr>\n");
+        for model in &self.models {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.0}%</td></tr>",
+                escape_html(&model.model),
+                number(model.messages.into()),
+                money(model.cost),
+                model.share * 100.0
+            );
+        }
+        out.push_str("</table>\n</body>\n</html>\n");
+        out
+    }
+
+    /// Bar chart of the daily cost, one bar per day with its date and amount
+    /// as a tooltip.
+    fn trend_svg(&self, money: &dyn Fn(f64) -> String) -> String {
+        const WIDTH: f64 = 900.0;
+        const HEIGHT: f64 = 160.0;
+        let max = self.days.iter().map(|day| day.cost).fold(0.0, f64::max);
+        let slot = WIDTH / self.days.len() as f64;
+        let mut svg = format!(
+            "<svg viewBox=\"0 0 {WIDTH} {}\" width=\"100%\" role=\"img\" aria-label=\"Daily cost\">\n",
+            HEIGHT + 20.0
+        );
+        for (i, day) in self.days.iter().enumerate() {
+            let height = if max > 0.0 {
+                day.cost / max * HEIGHT
+            } else {
+                0.0
+            };
+            let x = i as f64 * slot;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{height:.1}\"><title>{} {}</title></rect>",
+                x + slot * 0.1,
+                HEIGHT - height,
+                slot * 0.8,
+                day.date,
+                money(day.cost)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "<text x=\"0\" y=\"{}\" font-size=\"12\">{}</text>\n<text x=\"{WIDTH}\" y=\"{}\" font-size=\"12\" text-anchor=\"end\">{}</text>\n</svg>",
+            HEIGHT + 16.0,
+            self.days.first().map_or("", |day| day.date.as_str()),
+            HEIGHT + 16.0,
+            self.days.last().map_or("", |day| day.date.as_str()),
+        );
+        svg
+    }
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AnalyzerViewBuilder, Application, ConversationMessage, MessageRole, Stats};
+    use chrono::{Local, TimeZone};
+    use std::sync::Arc;
+
+    fn message(day: u32, session: &str, model: &str, cost: f64) -> ConversationMessage {
+        let date = Local
+            .with_ymd_and_hms(2025, 6, day, 12, 0, 0)
+            .unwrap()
+            .to_utc();
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date,
+            project_hash: "project".to_string(),
+            conversation_hash: session.to_string(),
+            local_hash: None,
+            global_hash: format!("{session}-{day}-{model}"),
+            model: Some(model.to_string()),
+            stats: Stats {
+                input_tokens: 100,
+                output_tokens: 10,
+                cost,
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: Some(format!("Fix <{session}>")),
+            stable_session_id: None,
+        }
+    }
+
+    fn views() -> Vec<SharedAnalyzerView> {
+        let mut builder = AnalyzerViewBuilder::new(Arc::from("Claude Code"));
+        builder.add_batch(vec![
+            message(1, "old", "claude-opus-4-1", 50.0),
+            message(9, "a", "claude-sonnet-4-5", 2.0),
+            message(10, "b", "claude-opus-4-1", 6.0),
+            message(10, "b", "claude-sonnet-4-5", 1.0),
+        ]);
+        vec![builder.finish()]
+    }
+
+    fn options() -> NumberFormatOptions {
+        NumberFormatOptions {
+            use_comma: true,
+            use_human: false,
+            locale: "en".to_string(),
+            decimal_places: 2,
+            currency_symbol: "$".to_string(),
+            cost_decimal_places: 2,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 10).unwrap()
+    }
+
+    #[test]
+    fn week_report_covers_seven_days() {
+        let report = Report::build(&views(), Period::Week, today());
+        assert_eq!(report.range, "2025-06-04..2025-06-10");
+        assert_eq!(report.cost, 9.0);
+        assert_eq!(report.sessions, 2);
+        assert_eq!(report.messages, 3);
+        assert_eq!(report.days.len(), 7);
+        assert_eq!(report.days[5].cost, 2.0);
+        assert_eq!(report.days[6].cost, 7.0);
+        assert_eq!(report.top_sessions[0].name, "Fix <b>");
+        assert_eq!(report.top_sessions[0].cost, 7.0);
+        assert_eq!(report.models[0].model, "claude-opus-4-1");
+        assert!((report.models[0].share - 6.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn renders_markdown_and_escaped_html() {
+        let report = Report::build(&views(), Period::Week, today());
+        let markdown = report.render(Format::Markdown, &options());
+        assert!(markdown.starts_with("# Weekly usage report (2025-06-04..2025-06-10)"));
+        assert!(markdown.contains("| Claude Code | $9.00 | 2 | 3 | 300 | 30 |"));
+        assert!(markdown.contains("| 2025-06-10 | $7.00 | ████████ |"));
+
+        let html = report.render(Format::Html, &options());
+        assert!(html.contains("<td>Fix &lt;b&gt;</td>"));
+        assert!(!html.contains("Fix <b>"));
+        assert_eq!(html.matches("<rect ").count(), 7);
+    }
+
+    #[test]
+    fn by_tool_columns_follow_the_column_layout() {
+        let layout = ColumnLayout::from_config(
+            &["tools".to_string(), "apps".to_string(), "input".to_string()],
+            &[],
+        );
+        let report = Report::build(&views(), Period::Week, today()).with_columns(&layout);
+        assert_eq!(report.columns, vec![Column::Tools, Column::Input]);
+        let markdown = report.render(Format::Markdown, &options());
+        assert!(
+            markdown.contains("| Tool | Cost | Sessions | Messages | Tool calls | Input tokens |")
+        );
+        assert!(markdown.contains("| Claude Code | $9.00 | 2 | 3 | 0 | 300 |"));
+    }
+
+    #[test]
+    fn empty_period_says_so() {
+        let report = Report::build(
+            &views(),
+            Period::Day,
+            NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        );
+        assert!(report.tools.is_empty());
+        assert!(
+            report
+                .render(Format::Markdown, &options())
+                .contains("No usage in this period.")
+        );
+    }
+}
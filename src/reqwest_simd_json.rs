@@ -1,4 +1,6 @@
 use anyhow::Result;
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use reqwest::{RequestBuilder, Response};
 use serde::Serialize;
 
@@ -8,6 +10,11 @@ pub trait ReqwestSimdJsonExt {
     fn simd_json<T>(self, json: &T) -> RequestBuilder
     where
         T: Serialize + ?Sized;
+
+    /// Like `simd_json`, but gzip the body and send `Content-Encoding: gzip`
+    fn simd_json_gzip<T>(self, json: &T) -> RequestBuilder
+    where
+        T: Serialize + ?Sized;
 }
 
 /// Extension trait to add simd-json parsing support to reqwest responses
@@ -29,6 +36,19 @@ impl ReqwestSimdJsonExt for RequestBuilder {
         self.header(reqwest::header::CONTENT_TYPE, "application/json")
             .body(body)
     }
+
+    fn simd_json_gzip<T>(self, json: &T) -> RequestBuilder
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        simd_json::to_writer(&mut encoder, json).expect("Failed to serialize JSON");
+        let body = encoder.finish().expect("Failed to compress JSON");
+
+        self.header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            .body(body)
+    }
 }
 
 impl ResponseSimdJsonExt for Response {
@@ -74,4 +94,30 @@ mod tests {
         let body_bytes = body.as_bytes().expect("Body should be bytes");
         assert_eq!(body_bytes, b"{\"value\":42}");
     }
+
+    #[test]
+    fn simd_json_gzip_compresses_body_and_sets_encoding() {
+        let request = reqwest::Client::new()
+            .post("http://example.com")
+            .simd_json_gzip(&vec!["message"; 1000])
+            .build()
+            .expect("Failed to build request");
+
+        assert_eq!(request.headers()[reqwest::header::CONTENT_ENCODING], "gzip");
+        assert_eq!(
+            request.headers()[reqwest::header::CONTENT_TYPE],
+            "application/json"
+        );
+
+        let compressed = request.body().and_then(|body| body.as_bytes()).unwrap();
+        let expected = simd_json::to_vec(&vec!["message"; 1000]).unwrap();
+        assert!(compressed.len() < expected.len() / 10);
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::GzDecoder::new(compressed),
+            &mut decompressed,
+        )
+        .unwrap();
+        assert_eq!(decompressed, expected);
+    }
 }
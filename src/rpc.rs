@@ -0,0 +1,502 @@
+//! Line-delimited JSON-RPC 2.0 over stdio for editor integrations.
+//!
+//! Every request, response, and notification is a single JSON object on its
+//! own line. Supported methods:
+//!
+//! - `getDailyStats` `{analyzer?, since?, until?}` - per-day totals for one
+//!   tool, or for all tools combined when `analyzer` is omitted.
+//! - `getSessions` `{analyzer?, since?, until?, limit?}` - sessions, newest first.
+//! - `subscribeUpdates` / `unsubscribeUpdates` - while subscribed, a
+//!   `statsUpdated` notification with today's totals is sent whenever the
+//!   underlying data changes.
+//!
+//! Dates use the same forms as `--since`/`--until` (`YYYY-MM-DD`, `today`, `7d`).
+
+use crate::statusline::HotSnapshot;
+use crate::subscription::CostBasis;
+use crate::tui::build_display_stats;
+use crate::tui::logic::has_data_shared;
+use crate::types::{
+    DailyStats, DateRange, MultiAnalyzerStatsView, SessionAggregate, SharedAnalyzerView, TuiStats,
+    resolve_model,
+};
+use crate::watcher::{FileWatcher, RealtimeStatsManager, WatcherEvent};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use simd_json::OwnedValue;
+use simd_json::prelude::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Serialize)]
+struct SuccessResponse<'a, T> {
+    jsonrpc: &'static str,
+    id: &'a OwnedValue,
+    result: T,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse<'a> {
+    jsonrpc: &'static str,
+    id: &'a OwnedValue,
+    error: RpcError,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct Notification<T> {
+    jsonrpc: &'static str,
+    method: &'static str,
+    params: T,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct StatsParams {
+    analyzer: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DailyStatsResult {
+    analyzer: String,
+    days: BTreeMap<String, DailyStats>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    session_id: String,
+    /// Same across runs and machines; see `SessionAggregate::stable_id`.
+    stable_id: String,
+    display_id: String,
+    analyzer: String,
+    name: Option<String>,
+    first_timestamp: String,
+    date: String,
+    models: Vec<String>,
+    stats: TuiStats,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribeResult {
+    subscribed: bool,
+    /// Today's totals at the time of subscribing, so clients can render immediately.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    today: Option<HotSnapshot>,
+}
+
+/// Per-connection state.
+#[derive(Debug, Default)]
+pub(crate) struct RpcState {
+    pub subscribed: bool,
+}
+
+type MethodResult = std::result::Result<String, (i64, String)>;
+
+/// Handle one request line. Returns the response line, or `None` for
+/// JSON-RPC notifications (requests without an `id`).
+pub(crate) fn handle_line(
+    line: &str,
+    stats: &MultiAnalyzerStatsView,
+    state: &mut RpcState,
+) -> Option<String> {
+    let mut bytes = line.as_bytes().to_vec();
+    let request = match simd_json::to_owned_value(&mut bytes) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                &OwnedValue::null(),
+                PARSE_ERROR,
+                e.to_string(),
+            ));
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+        return Some(error_response(
+            id.as_ref().unwrap_or(&OwnedValue::null()),
+            INVALID_REQUEST,
+            "Request must be an object with a string 'method'".to_string(),
+        ));
+    };
+    let params = request.get("params").cloned();
+
+    let outcome = match method {
+        "getDailyStats" => parse_params(params).and_then(|p| get_daily_stats(stats, p, &id)),
+        "getSessions" => parse_params(params).and_then(|p| get_sessions(stats, p, &id)),
+        "subscribeUpdates" => {
+            state.subscribed = true;
+            let today = HotSnapshot::from_views(&stats.analyzer_stats);
+            Ok(success(
+                &id,
+                SubscribeResult {
+                    subscribed: true,
+                    today: Some(today),
+                },
+            ))
+        }
+        "unsubscribeUpdates" => {
+            state.subscribed = false;
+            Ok(success(
+                &id,
+                SubscribeResult {
+                    subscribed: false,
+                    today: None,
+                },
+            ))
+        }
+        other => Err((METHOD_NOT_FOUND, format!("Unknown method: {other}"))),
+    };
+
+    let id = id?;
+    Some(outcome.unwrap_or_else(|(code, message)| error_response(&id, code, message)))
+}
+
+/// `statsUpdated` notification line carrying today's totals.
+pub(crate) fn update_notification(stats: &MultiAnalyzerStatsView) -> String {
+    to_line(&Notification {
+        jsonrpc: "2.0",
+        method: "statsUpdated",
+        params: HotSnapshot::from_views(&stats.analyzer_stats),
+    })
+}
+
+fn parse_params<T: DeserializeOwned + Default>(
+    params: Option<OwnedValue>,
+) -> std::result::Result<T, (i64, String)> {
+    match params {
+        None => Ok(T::default()),
+        Some(value) if value.is_null() => Ok(T::default()),
+        Some(value) => simd_json::serde::from_owned_value(value)
+            .map_err(|e| (INVALID_PARAMS, format!("Invalid params: {e}"))),
+    }
+}
+
+fn parse_range(params: &StatsParams) -> std::result::Result<DateRange, (i64, String)> {
+    let bound = |value: &Option<String>, name: &str| {
+        value
+            .as_deref()
+            .map(|s| {
+                DateRange::parse_bound(s)
+                    .ok_or_else(|| (INVALID_PARAMS, format!("Invalid '{name}' date: {s}")))
+            })
+            .transpose()
+    };
+    let range = DateRange {
+        since: bound(&params.since, "since")?,
+        until: bound(&params.until, "until")?,
+    };
+    if !range.is_valid() {
+        return Err((INVALID_PARAMS, "'since' is after 'until'".to_string()));
+    }
+    Ok(range)
+}
+
+/// Resolve the requested tab ("All Tools" when no analyzer is given),
+/// restricted to the requested date range.
+fn select_view(
+    stats: &MultiAnalyzerStatsView,
+    params: &StatsParams,
+) -> std::result::Result<Option<SharedAnalyzerView>, (i64, String)> {
+    let range = parse_range(params)?;
+    let with_data: Vec<SharedAnalyzerView> = stats
+        .analyzer_stats
+        .iter()
+        .filter(|view| has_data_shared(view))
+        .cloned()
+        .collect();
+    let display = build_display_stats(&with_data, range, &CostBasis::default());
+
+    let Some(name) = params.analyzer.as_deref() else {
+        return Ok(display.into_iter().next());
+    };
+    if let Some(view) = display
+        .into_iter()
+        .find(|view| view.read().analyzer_name.eq_ignore_ascii_case(name))
+    {
+        return Ok(Some(view));
+    }
+    // A known tool without data yet is an empty result, not an error.
+    if stats
+        .analyzer_stats
+        .iter()
+        .any(|view| view.read().analyzer_name.eq_ignore_ascii_case(name))
+    {
+        return Ok(None);
+    }
+    Err((INVALID_PARAMS, format!("Unknown analyzer: {name}")))
+}
+
+fn get_daily_stats(
+    stats: &MultiAnalyzerStatsView,
+    params: StatsParams,
+    id: &Option<OwnedValue>,
+) -> MethodResult {
+    let view = select_view(stats, &params)?;
+    let result = match view {
+        Some(view) => {
+            let view = view.read();
+            DailyStatsResult {
+                analyzer: view.analyzer_name.to_string(),
+                days: view.daily_stats.clone(),
+            }
+        }
+        None => DailyStatsResult {
+            analyzer: params
+                .analyzer
+                .unwrap_or_else(|| crate::budget::ALL_TOOLS.to_string()),
+            days: BTreeMap::new(),
+        },
+    };
+    Ok(success(id, result))
+}
+
+fn get_sessions(
+    stats: &MultiAnalyzerStatsView,
+    params: StatsParams,
+    id: &Option<OwnedValue>,
+) -> MethodResult {
+    let Some(view) = select_view(stats, &params)? else {
+        return Ok(success(id, Vec::<SessionInfo>::new()));
+    };
+    let view = view.read();
+    let mut newest_first: Vec<_> = view.session_aggregates.as_slice().iter().collect();
+    newest_first.sort_by(|a, b| SessionAggregate::chronological(b, a));
+    let sessions: Vec<SessionInfo> = newest_first
+        .into_iter()
+        .take(params.limit.unwrap_or(usize::MAX))
+        .map(|session| SessionInfo {
+            session_id: session.session_id.clone(),
+            stable_id: session.stable_id.clone(),
+            display_id: session.display_id().to_string(),
+            analyzer: session.analyzer_name.to_string(),
+            name: session.session_name.clone(),
+            first_timestamp: session.first_timestamp.to_rfc3339(),
+            date: session.date.to_string(),
+            models: session
+                .models
+                .iter()
+                .map(|(key, _)| resolve_model(*key).to_string())
+                .collect(),
+            stats: session.stats,
+        })
+        .collect();
+    Ok(success(id, sessions))
+}
+
+fn success<T: Serialize>(id: &Option<OwnedValue>, result: T) -> String {
+    let null = OwnedValue::null();
+    to_line(&SuccessResponse {
+        jsonrpc: "2.0",
+        id: id.as_ref().unwrap_or(&null),
+        result,
+    })
+}
+
+fn error_response(id: &OwnedValue, code: i64, message: String) -> String {
+    to_line(&ErrorResponse {
+        jsonrpc: "2.0",
+        id,
+        error: RpcError { code, message },
+    })
+}
+
+fn to_line<T: Serialize>(value: &T) -> String {
+    simd_json::to_string(value).unwrap_or_else(|e| {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":null,"error":{{"code":-32603,"message":"{}"}}}}"#,
+            e.to_string().replace('"', "'")
+        )
+    })
+}
+
+/// Serve JSON-RPC on stdin/stdout until stdin closes, keeping stats live
+/// through the same file watcher the TUI uses.
+pub async fn run_rpc_server() -> Result<()> {
+    let registry = crate::create_analyzer_registry();
+    let mut file_watcher = FileWatcher::new(&registry)?;
+    let mut stats_manager = {
+        let pool = rayon::ThreadPoolBuilder::new().build()?;
+        pool.install(|| RealtimeStatsManager::new(registry))?
+    };
+    let mut stats_rx = stats_manager.get_stats_receiver();
+
+    let (watcher_tx, mut watcher_rx) = mpsc::unbounded_channel::<WatcherEvent>();
+    tokio::spawn(async move {
+        while let Some(event) = watcher_rx.recv().await {
+            if let Err(e) = stats_manager.handle_watcher_event(event).await {
+                eprintln!("Error handling watcher event: {e}");
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    let mut state = RpcState::default();
+    let mut poll = tokio::time::interval(Duration::from_millis(200));
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let stats = stats_rx.borrow().clone();
+                if let Some(response) = handle_line(&line, &stats, &mut state) {
+                    write_line(&mut stdout, &response).await?;
+                }
+            }
+            changed = stats_rx.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let stats = stats_rx.borrow_and_update().clone();
+                if state.subscribed {
+                    write_line(&mut stdout, &update_notification(&stats)).await?;
+                }
+            }
+            _ = poll.tick() => {
+                file_watcher.check_health();
+                while let Some(event) = file_watcher.try_recv() {
+                    let _ = watcher_tx.send(event);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_line(stdout: &mut tokio::io::Stdout, line: &str) -> Result<()> {
+    stdout.write_all(line.as_bytes()).await?;
+    stdout.write_all(b"\n").await?;
+    stdout.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgenticCodingToolStats, CompactDate, MultiAnalyzerStats};
+
+    fn stats() -> MultiAnalyzerStatsView {
+        let day = |date: &str, cost_cents: u32| DailyStats {
+            date: CompactDate::from_str(date).unwrap(),
+            conversations: 1,
+            stats: TuiStats {
+                cost_cents,
+                input_tokens: 10,
+                ..TuiStats::default()
+            },
+            ..DailyStats::default()
+        };
+        MultiAnalyzerStats {
+            analyzer_stats: vec![
+                AgenticCodingToolStats {
+                    daily_stats: BTreeMap::from([
+                        ("2025-01-01".to_string(), day("2025-01-01", 100)),
+                        ("2025-01-02".to_string(), day("2025-01-02", 200)),
+                    ]),
+                    num_conversations: 2,
+                    messages: vec![],
+                    analyzer_name: "Claude Code".to_string(),
+                },
+                AgenticCodingToolStats {
+                    daily_stats: BTreeMap::new(),
+                    num_conversations: 0,
+                    messages: vec![],
+                    analyzer_name: "Codex CLI".to_string(),
+                },
+            ],
+        }
+        .into_view()
+    }
+
+    fn call(line: &str, state: &mut RpcState) -> OwnedValue {
+        let mut response = handle_line(line, &stats(), state)
+            .expect("request with id gets a response")
+            .into_bytes();
+        simd_json::to_owned_value(&mut response).unwrap()
+    }
+
+    #[test]
+    fn get_daily_stats_combines_tools_and_applies_range() {
+        let mut state = RpcState::default();
+        let response = call(
+            r#"{"jsonrpc":"2.0","id":1,"method":"getDailyStats","params":{"since":"2025-01-02"}}"#,
+            &mut state,
+        );
+
+        assert_eq!(response["id"].as_u64(), Some(1));
+        let result = &response["result"];
+        assert_eq!(result["analyzer"].as_str(), Some("All Tools"));
+        let days = result["days"].as_object().unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days["2025-01-02"]["stats"]["costCents"].as_u64(), Some(200));
+    }
+
+    #[test]
+    fn known_tool_without_data_is_empty_and_unknown_tool_is_an_error() {
+        let mut state = RpcState::default();
+        let empty = call(
+            r#"{"id":"a","method":"getSessions","params":{"analyzer":"codex cli"}}"#,
+            &mut state,
+        );
+        assert_eq!(empty["result"].as_array().map(Vec::len), Some(0));
+
+        let unknown = call(
+            r#"{"id":"b","method":"getDailyStats","params":{"analyzer":"Nope"}}"#,
+            &mut state,
+        );
+        assert_eq!(unknown["error"]["code"].as_i64(), Some(INVALID_PARAMS));
+        assert_eq!(unknown["id"].as_str(), Some("b"));
+    }
+
+    #[test]
+    fn protocol_errors_and_notifications() {
+        let mut state = RpcState::default();
+        assert_eq!(
+            call("{not json", &mut state)["error"]["code"].as_i64(),
+            Some(PARSE_ERROR)
+        );
+        assert_eq!(
+            call(r#"{"id":2,"method":"launchRockets"}"#, &mut state)["error"]["code"].as_i64(),
+            Some(METHOD_NOT_FOUND)
+        );
+        assert_eq!(
+            call(
+                r#"{"id":3,"method":"getSessions","params":{"bogus":1}}"#,
+                &mut state
+            )["error"]["code"]
+                .as_i64(),
+            Some(INVALID_PARAMS)
+        );
+        // Notifications (no id) never get a response but still take effect.
+        assert!(handle_line(r#"{"method":"subscribeUpdates"}"#, &stats(), &mut state).is_none());
+        assert!(state.subscribed);
+
+        let line = update_notification(&stats());
+        assert!(line.contains(r#""method":"statsUpdated""#));
+        assert!(!line.contains('\n'));
+    }
+}
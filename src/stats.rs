@@ -0,0 +1,11 @@
+//! Statistics derived from the per-day rollups (and, for single-day
+//! breakdowns, from raw messages), shared by the TUI and the `splitrail
+//! stats` exports.
+
+pub mod active;
+pub mod cache;
+pub mod compare;
+pub mod hourly;
+pub mod languages;
+pub mod streaks;
+pub mod throughput;
@@ -0,0 +1,92 @@
+//! Active time: how long someone was actually at work in a session,
+//! estimated from message timestamps.
+//!
+//! Messages closer together than the idle gap belong to one work block; a
+//! longer pause ends the block. A block lasts from its first message to its
+//! last, and at least [`MIN_BLOCK_SECS`] so a lone prompt still counts.
+//! Sessions are measured separately, so two sessions running side by side
+//! both count.
+
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+pub const DEFAULT_IDLE_GAP_MINUTES: u32 = 15;
+
+/// Shortest a work block can be.
+const MIN_BLOCK_SECS: i64 = 60;
+
+static IDLE_GAP_MINUTES: AtomicU32 = AtomicU32::new(DEFAULT_IDLE_GAP_MINUTES);
+
+/// Set the pause that ends a work block, for messages aggregated from now on.
+pub fn set_idle_gap_minutes(minutes: u32) {
+    IDLE_GAP_MINUTES.store(minutes.max(1), Ordering::Relaxed);
+}
+
+/// Active seconds in one session's messages, in any order.
+pub fn active_seconds(timestamps: impl IntoIterator<Item = DateTime<Utc>>) -> u32 {
+    active_seconds_with(
+        timestamps,
+        IDLE_GAP_MINUTES.load(Ordering::Relaxed) as i64 * 60,
+    )
+}
+
+fn active_seconds_with(timestamps: impl IntoIterator<Item = DateTime<Utc>>, gap_secs: i64) -> u32 {
+    let mut timestamps: Vec<i64> = timestamps.into_iter().map(|at| at.timestamp()).collect();
+    timestamps.sort_unstable();
+    let Some(&first) = timestamps.first() else {
+        return 0;
+    };
+
+    let mut total = 0i64;
+    let (mut block_start, mut previous) = (first, first);
+    for &at in &timestamps[1..] {
+        if at - previous > gap_secs {
+            total += (previous - block_start).max(MIN_BLOCK_SECS);
+            block_start = at;
+        }
+        previous = at;
+    }
+    total += (previous - block_start).max(MIN_BLOCK_SECS);
+    total.clamp(0, u32::MAX as i64) as u32
+}
+
+/// Cost per active hour, or `None` without active time.
+pub fn cost_per_hour(cost: f64, active_secs: u32) -> Option<f64> {
+    (active_secs > 0).then(|| cost / (active_secs as f64 / 3600.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_750_000_000, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    #[test]
+    fn pauses_longer_than_the_gap_split_work_blocks() {
+        let gap = 15 * 60;
+        // 0-10 is one block, 40-50 another: 20 minutes, not 50.
+        let times = [at(0), at(5), at(10), at(40), at(45), at(50)];
+        assert_eq!(active_seconds_with(times, gap), 20 * 60);
+        // Order doesn't matter.
+        let shuffled = [at(45), at(0), at(50), at(10), at(40), at(5)];
+        assert_eq!(active_seconds_with(shuffled, gap), 20 * 60);
+        // A wider gap joins them.
+        assert_eq!(active_seconds_with(times, 30 * 60), 50 * 60);
+    }
+
+    #[test]
+    fn lone_messages_count_a_minute() {
+        assert_eq!(active_seconds_with([], 900), 0);
+        assert_eq!(active_seconds_with([at(0)], 900), 60);
+        assert_eq!(active_seconds_with([at(0), at(60)], 900), 120);
+    }
+
+    #[test]
+    fn cost_per_hour_needs_active_time() {
+        assert_eq!(cost_per_hour(5.0, 0), None);
+        assert_eq!(cost_per_hour(5.0, 1800), Some(10.0));
+    }
+}
@@ -0,0 +1,141 @@
+//! Prompt-cache efficiency: how much of the prompt was read back from cache,
+//! and what that saved compared to sending it as fresh input.
+//!
+//! Tools that split cache traffic (Claude Code and the like) report reads in
+//! `cache_read_tokens` and count writes plus reads in `cached_tokens`. Others
+//! (Codex, Gemini CLI) only fill `cached_tokens`, and with reads alone.
+//! [`cache_reads`] covers both.
+
+use crate::models;
+use crate::types::{DailyStats, ModelStats};
+use serde::Serialize;
+
+/// Tokens read back from the prompt cache.
+pub fn cache_reads(cache_creation_tokens: u64, cache_read_tokens: u64, cached_tokens: u64) -> u64 {
+    cache_read_tokens.max(cached_tokens.saturating_sub(cache_creation_tokens))
+}
+
+/// Cost avoided by reading `reads` tokens from cache instead of paying the
+/// full input price. Priced per message, since long prompts can fall in a
+/// dearer tier.
+pub fn read_savings(model: &str, reads: u64) -> f64 {
+    if reads == 0 {
+        return 0.0;
+    }
+    (models::calculate_input_cost(model, reads) - models::calculate_cache_cost(model, 0, reads))
+        .max(0.0)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEfficiency {
+    /// Prompt tokens sent as fresh input.
+    pub input_tokens: u64,
+    pub cache_write_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Share of prompt tokens read from cache; `None` without any prompt.
+    pub hit_ratio: Option<f64>,
+    pub saved_cost: f64,
+}
+
+impl CacheEfficiency {
+    pub fn from_model(model: &ModelStats) -> Self {
+        Self {
+            input_tokens: model.input_tokens,
+            cache_write_tokens: model.cache_creation_tokens,
+            cache_read_tokens: cache_reads(
+                model.cache_creation_tokens,
+                model.cache_read_tokens,
+                model.cached_tokens,
+            ),
+            hit_ratio: None,
+            saved_cost: model.saved_cost,
+        }
+        .with_hit_ratio()
+    }
+
+    /// A day, or any period whose per-model stats were merged into one.
+    pub fn from_day(day: &DailyStats) -> Self {
+        Self::from_periods([day])
+    }
+
+    pub fn from_periods<'a>(days: impl IntoIterator<Item = &'a DailyStats>) -> Self {
+        let mut efficiency = Self::default();
+        for model in days.into_iter().flat_map(|day| day.model_stats.values()) {
+            efficiency += Self::from_model(model);
+        }
+        efficiency
+    }
+
+    fn with_hit_ratio(mut self) -> Self {
+        let prompt = self.input_tokens + self.cache_write_tokens + self.cache_read_tokens;
+        self.hit_ratio = (prompt > 0).then(|| self.cache_read_tokens as f64 / prompt as f64);
+        self
+    }
+}
+
+impl std::ops::AddAssign for CacheEfficiency {
+    fn add_assign(&mut self, rhs: Self) {
+        self.input_tokens += rhs.input_tokens;
+        self.cache_write_tokens += rhs.cache_write_tokens;
+        self.cache_read_tokens += rhs.cache_read_tokens;
+        self.saved_cost += rhs.saved_cost;
+        *self = self.with_hit_ratio();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_come_from_whichever_field_the_tool_fills() {
+        // Claude Code: cached = writes + reads.
+        assert_eq!(cache_reads(300, 700, 1_000), 700);
+        // Codex and Gemini CLI: only cached, all reads.
+        assert_eq!(cache_reads(0, 0, 900), 900);
+        assert_eq!(cache_reads(0, 0, 0), 0);
+    }
+
+    #[test]
+    fn hit_ratio_covers_fresh_input_writes_and_reads() {
+        let mut day = DailyStats::default();
+        day.model_stats.insert(
+            "claude-sonnet-4-5".into(),
+            ModelStats {
+                input_tokens: 100,
+                cache_creation_tokens: 300,
+                cache_read_tokens: 600,
+                cached_tokens: 900,
+                saved_cost: 1.5,
+                ..ModelStats::new("claude-sonnet-4-5".into())
+            },
+        );
+        day.model_stats.insert(
+            "gpt-5".into(),
+            ModelStats {
+                input_tokens: 1_000,
+                cached_tokens: 3_000,
+                saved_cost: 0.5,
+                ..ModelStats::new("gpt-5".into())
+            },
+        );
+
+        let efficiency = CacheEfficiency::from_day(&day);
+        assert_eq!(efficiency.cache_read_tokens, 3_600);
+        assert_eq!(efficiency.cache_write_tokens, 300);
+        assert_eq!(efficiency.hit_ratio, Some(3_600.0 / 5_000.0));
+        assert_eq!(efficiency.saved_cost, 2.0);
+
+        assert_eq!(
+            CacheEfficiency::from_day(&DailyStats::default()).hit_ratio,
+            None
+        );
+    }
+
+    #[test]
+    fn reading_from_cache_saves_the_price_difference() {
+        assert!(read_savings("claude-sonnet-4-5", 1_000_000) > 0.0);
+        assert_eq!(read_savings("claude-sonnet-4-5", 0), 0.0);
+    }
+}
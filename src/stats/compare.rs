@@ -0,0 +1,287 @@
+//! One period against another: totals for each and the change between them,
+//! to spot what moved after switching models or tools.
+//!
+//! Periods are trailing windows by default (the last 7 days against the 7
+//! before them), since comparing a half-finished month with a whole one
+//! always looks like a drop.
+
+use crate::types::{CompactDate, DailyStats, DateRange, SharedAnalyzerView};
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub cost: f64,
+    pub messages: u64,
+    pub conversations: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cached_tokens: u64,
+    pub tool_calls: u64,
+}
+
+impl Totals {
+    pub fn from_days<'a>(days: impl IntoIterator<Item = &'a DailyStats>, range: DateRange) -> Self {
+        let mut totals = Self::default();
+        for day in days.into_iter().filter(|day| range.contains(day.date)) {
+            totals.cost += day.stats.cost();
+            totals.messages += u64::from(day.user_messages + day.ai_messages);
+            totals.conversations += u64::from(day.conversations);
+            totals.input_tokens += day.stats.input_tokens;
+            totals.output_tokens += day.stats.output_tokens;
+            totals.reasoning_tokens += day.stats.reasoning_tokens;
+            totals.cached_tokens += day.stats.cached_tokens;
+            totals.tool_calls += u64::from(day.stats.tool_calls);
+        }
+        totals
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.cost += other.cost;
+        self.messages += other.messages;
+        self.conversations += other.conversations;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.tool_calls += other.tool_calls;
+    }
+
+    /// Figures in display order; cost first, in dollars.
+    pub fn metrics(&self) -> [(Metric, f64); 8] {
+        [
+            (Metric::Cost, self.cost),
+            (Metric::Messages, self.messages as f64),
+            (Metric::Conversations, self.conversations as f64),
+            (Metric::InputTokens, self.input_tokens as f64),
+            (Metric::OutputTokens, self.output_tokens as f64),
+            (Metric::ReasoningTokens, self.reasoning_tokens as f64),
+            (Metric::CachedTokens, self.cached_tokens as f64),
+            (Metric::ToolCalls, self.tool_calls as f64),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Cost,
+    Messages,
+    Conversations,
+    InputTokens,
+    OutputTokens,
+    ReasoningTokens,
+    CachedTokens,
+    ToolCalls,
+}
+
+impl Metric {
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Cost => "Cost",
+            Metric::Messages => "Messages",
+            Metric::Conversations => "Conversations",
+            Metric::InputTokens => "Input tokens",
+            Metric::OutputTokens => "Output tokens",
+            Metric::ReasoningTokens => "Reasoning tokens",
+            Metric::CachedTokens => "Cached tokens",
+            Metric::ToolCalls => "Tool calls",
+        }
+    }
+}
+
+/// One analyzer's (or every analyzer's) totals in both periods.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    pub analyzer: String,
+    pub previous: Totals,
+    pub current: Totals,
+}
+
+impl Comparison {
+    pub fn from_days<'a>(
+        analyzer: &str,
+        days: impl IntoIterator<Item = &'a DailyStats> + Clone,
+        periods: Periods,
+    ) -> Self {
+        Self {
+            analyzer: analyzer.to_string(),
+            previous: Totals::from_days(days.clone(), periods.previous),
+            current: Totals::from_days(days, periods.current),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.messages == 0 && self.current.messages == 0
+    }
+
+    /// Each figure in both periods, with its relative change.
+    pub fn rows(&self) -> Vec<(Metric, f64, f64, Option<f64>)> {
+        self.previous
+            .metrics()
+            .into_iter()
+            .zip(self.current.metrics())
+            .map(|((metric, previous), (_, current))| {
+                (metric, previous, current, change(previous, current))
+            })
+            .collect()
+    }
+}
+
+/// Every analyzer with usage in either period, then their sum as "All tools"
+/// when there's more than one.
+pub fn compare(views: &[SharedAnalyzerView], periods: Periods) -> Vec<Comparison> {
+    let mut comparisons: Vec<Comparison> = views
+        .iter()
+        .map(|view| {
+            let view = view.read();
+            Comparison::from_days(&view.analyzer_name, view.daily_stats.values(), periods)
+        })
+        .filter(|comparison| !comparison.is_empty())
+        .collect();
+    if comparisons.len() > 1 {
+        let mut total = Comparison {
+            analyzer: "All tools".to_string(),
+            previous: Totals::default(),
+            current: Totals::default(),
+        };
+        for comparison in &comparisons {
+            total.previous.add(&comparison.previous);
+            total.current.add(&comparison.current);
+        }
+        comparisons.push(total);
+    }
+    comparisons
+}
+
+/// Relative change from `previous` to `current`, e.g. 0.35 for +35%. None
+/// when there's nothing to compare against.
+pub fn change(previous: f64, current: f64) -> Option<f64> {
+    (previous > 0.0).then(|| (current - previous) / previous)
+}
+
+/// `+35%`, `-12%`, `±0%`, or `new` when the previous period had none.
+pub fn format_change(change: Option<f64>, current: f64) -> String {
+    match change {
+        Some(change) if change.abs() < 0.005 => "±0%".to_string(),
+        Some(change) => format!("{:+.0}%", change * 100.0),
+        None if current > 0.0 => "new".to_string(),
+        None => "–".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Periods {
+    pub previous: DateRange,
+    pub current: DateRange,
+}
+
+impl Periods {
+    /// The `days` ending on `today` against the `days` before them.
+    pub fn trailing(days: u64, today: NaiveDate) -> Self {
+        let days = days.max(1);
+        let compact = |date: NaiveDate| CompactDate::from_str(&date.to_string());
+        let current_start = today - chrono::Days::new(days - 1);
+        let previous_end = current_start - chrono::Days::new(1);
+        let previous_start = previous_end - chrono::Days::new(days - 1);
+        Self {
+            previous: DateRange {
+                since: compact(previous_start),
+                until: compact(previous_end),
+            },
+            current: DateRange {
+                since: compact(current_start),
+                until: compact(today),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TuiStats;
+
+    fn day(date: &str, cost_cents: u32, messages: u32) -> DailyStats {
+        DailyStats {
+            date: CompactDate::from_str(date).unwrap(),
+            ai_messages: messages,
+            conversations: 1,
+            stats: TuiStats {
+                cost_cents,
+                output_tokens: u64::from(messages) * 100,
+                ..TuiStats::default()
+            },
+            ..DailyStats::default()
+        }
+    }
+
+    #[test]
+    fn trailing_periods_are_adjacent_and_equal_length() {
+        let periods = Periods::trailing(7, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        assert_eq!(periods.current.to_string(), "2025-02-25..2025-03-03");
+        assert_eq!(periods.previous.to_string(), "2025-02-18..2025-02-24");
+    }
+
+    #[test]
+    fn compares_totals_across_periods() {
+        let days = [
+            day("2025-02-20", 1000, 10),
+            day("2025-02-26", 800, 6),
+            day("2025-03-03", 550, 5),
+        ];
+        let periods = Periods::trailing(7, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        let comparison = Comparison::from_days("Claude Code", &days, periods);
+
+        assert_eq!(comparison.previous.cost, 10.0);
+        assert_eq!(comparison.current.cost, 13.5);
+        let rows = comparison.rows();
+        let (metric, _, _, cost_change) = rows[0];
+        assert_eq!(metric, Metric::Cost);
+        assert_eq!(format_change(cost_change, 13.5), "+35%");
+        let (_, _, current, messages_change) = rows[1];
+        assert_eq!(current, 11.0);
+        assert_eq!(format_change(messages_change, current), "+10%");
+        assert_eq!(format_change(rows[5].3, rows[5].2), "–");
+    }
+
+    #[test]
+    fn sums_analyzers_and_skips_idle_ones() {
+        use crate::types::AnalyzerStatsView;
+        use parking_lot::RwLock;
+        use std::sync::Arc;
+
+        let view = |name: &str, days: Vec<DailyStats>| -> SharedAnalyzerView {
+            Arc::new(RwLock::new(AnalyzerStatsView {
+                daily_stats: days
+                    .into_iter()
+                    .map(|day| (day.date.to_string(), day))
+                    .collect(),
+                session_aggregates: Vec::new(),
+                num_conversations: 0,
+                analyzer_name: Arc::from(name),
+            }))
+        };
+        let views = [
+            view("Claude Code", vec![day("2025-03-02", 200, 2)]),
+            view("Codex CLI", vec![day("2025-02-24", 100, 1)]),
+            view("Gemini CLI", vec![day("2024-12-01", 100, 1)]),
+        ];
+        let periods = Periods::trailing(7, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        let comparisons = compare(&views, periods);
+
+        let names: Vec<&str> = comparisons.iter().map(|c| c.analyzer.as_str()).collect();
+        assert_eq!(names, ["Claude Code", "Codex CLI", "All tools"]);
+        assert_eq!(comparisons[2].previous.cost, 1.0);
+        assert_eq!(comparisons[2].current.cost, 2.0);
+    }
+
+    #[test]
+    fn formats_unchanged_and_new_figures() {
+        assert_eq!(format_change(change(4.0, 4.0), 4.0), "±0%");
+        assert_eq!(format_change(change(8.0, 7.04), 7.04), "-12%");
+        assert_eq!(format_change(change(0.0, 3.0), 3.0), "new");
+    }
+}
@@ -0,0 +1,108 @@
+//! One day's usage split by local clock hour.
+//!
+//! Per-day rollups only keep reply counts per hour, so tokens and cost per
+//! hour come from the day's raw messages, bucketed by their local timestamp.
+
+use crate::types::{ConversationMessage, MessageRole};
+use chrono::{Local, NaiveDate, Timelike};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HourStats {
+    pub user_messages: u64,
+    pub ai_messages: u64,
+    /// Input, output and cached tokens.
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+impl HourStats {
+    pub fn is_empty(&self) -> bool {
+        self.user_messages == 0 && self.ai_messages == 0 && self.tokens == 0 && self.cost == 0.0
+    }
+}
+
+/// Hours 0-23 of `day` in local time; messages from other days are skipped.
+pub fn day_by_hour(messages: &[ConversationMessage], day: NaiveDate) -> [HourStats; 24] {
+    let mut hours = [HourStats::default(); 24];
+    for message in messages {
+        let local = message.date.with_timezone(&Local);
+        if local.date_naive() != day {
+            continue;
+        }
+        let hour = &mut hours[local.hour() as usize];
+        match message.role {
+            MessageRole::User => hour.user_messages += 1,
+            MessageRole::Assistant => hour.ai_messages += 1,
+        }
+        let stats = &message.stats;
+        hour.tokens += stats.input_tokens + stats.output_tokens + stats.cached_tokens;
+        hour.cost += stats.cost;
+    }
+    hours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Application, Stats};
+    use chrono::{TimeZone, Utc};
+
+    fn message(
+        day: NaiveDate,
+        hour: u32,
+        role: MessageRole,
+        tokens: u64,
+        cost: f64,
+    ) -> ConversationMessage {
+        let local = Local
+            .from_local_datetime(&day.and_hms_opt(hour, 30, 0).unwrap())
+            .earliest()
+            .unwrap();
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: local.with_timezone(&Utc),
+            project_hash: "project".to_string(),
+            conversation_hash: "conversation".to_string(),
+            local_hash: None,
+            global_hash: format!("{day}-{hour}-{tokens}"),
+            model: None,
+            stats: Stats {
+                input_tokens: tokens,
+                output_tokens: tokens,
+                cost,
+                ..Stats::default()
+            },
+            role,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        }
+    }
+
+    #[test]
+    fn buckets_one_day_by_local_hour() {
+        let day = NaiveDate::from_ymd_opt(2025, 6, 10).unwrap();
+        let next_day = day.succ_opt().unwrap();
+        let messages = vec![
+            message(day, 9, MessageRole::User, 0, 0.0),
+            message(day, 9, MessageRole::Assistant, 100, 0.5),
+            message(day, 9, MessageRole::Assistant, 50, 0.25),
+            message(day, 23, MessageRole::Assistant, 10, 0.1),
+            message(next_day, 9, MessageRole::Assistant, 1000, 5.0),
+        ];
+
+        let hours = day_by_hour(&messages, day);
+
+        assert_eq!(
+            hours[9],
+            HourStats {
+                user_messages: 1,
+                ai_messages: 2,
+                tokens: 300,
+                cost: 0.75,
+            }
+        );
+        assert_eq!(hours[23].ai_messages, 1);
+        assert_eq!(hours.iter().filter(|hour| !hour.is_empty()).count(), 2);
+    }
+}
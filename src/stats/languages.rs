@@ -0,0 +1,213 @@
+//! Languages agents work in, from the file extensions of read, write, and
+//! edit tool calls.
+//!
+//! Views don't keep extensions, so the report is built from raw messages.
+//! Projects are labelled with the directory their analyzer recorded, or a
+//! shortened hash for tools that don't log one.
+
+use crate::types::{CompactDate, ConversationMessage, DateRange};
+use crate::utils::languages;
+use chrono::Local;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Characters of a project hash shown when its directory isn't known.
+const PROJECT_LABEL_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageShare {
+    pub language: String,
+    pub operations: u64,
+    /// Fraction of the group's file operations, 0-1.
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayLanguages {
+    pub date: String,
+    pub languages: Vec<LanguageShare>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectLanguages {
+    pub analyzer: String,
+    pub project: String,
+    pub operations: u64,
+    pub languages: Vec<LanguageShare>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageReport {
+    pub overall: Vec<LanguageShare>,
+    pub days: Vec<DayLanguages>,
+    /// Busiest project first.
+    pub projects: Vec<ProjectLanguages>,
+}
+
+impl LanguageReport {
+    pub fn is_empty(&self) -> bool {
+        self.overall.is_empty()
+    }
+}
+
+/// Language shares within `range` overall, per local day, and per project,
+/// from `(analyzer name, messages)` pairs.
+pub fn report(
+    analyzers: &[(String, Vec<ConversationMessage>)],
+    range: DateRange,
+) -> LanguageReport {
+    let mut overall = BTreeMap::new();
+    let mut days: BTreeMap<CompactDate, BTreeMap<String, u64>> = BTreeMap::new();
+    let mut projects: BTreeMap<(&str, &str), BTreeMap<String, u64>> = BTreeMap::new();
+    for (analyzer, messages) in analyzers {
+        for message in messages {
+            if message.stats.file_extensions.is_empty() {
+                continue;
+            }
+            let date = CompactDate::from_local(&message.date.with_timezone(&Local));
+            if !range.contains(date) {
+                continue;
+            }
+            let project = projects
+                .entry((analyzer.as_str(), message.project_hash.as_str()))
+                .or_default();
+            let day = days.entry(date).or_default();
+            for (ext, count) in &message.stats.file_extensions {
+                for counts in [&mut overall, &mut *day, &mut *project] {
+                    *counts.entry(ext.clone()).or_default() += count;
+                }
+            }
+        }
+    }
+
+    let mut projects: Vec<ProjectLanguages> = projects
+        .into_iter()
+        .map(|((analyzer, project), extensions)| ProjectLanguages {
+            analyzer: analyzer.to_string(),
+            project: project_label(project),
+            operations: extensions.values().sum(),
+            languages: shares(&extensions),
+        })
+        .collect();
+    projects.sort_by_key(|project| std::cmp::Reverse(project.operations));
+
+    LanguageReport {
+        overall: shares(&overall),
+        days: days
+            .into_iter()
+            .map(|(date, extensions)| DayLanguages {
+                date: date.to_string(),
+                languages: shares(&extensions),
+            })
+            .collect(),
+        projects,
+    }
+}
+
+fn project_label(project_hash: &str) -> String {
+    match crate::utils::project_dir(project_hash) {
+        Some(dir) => dir.to_string(),
+        None => project_hash.chars().take(PROJECT_LABEL_LEN).collect(),
+    }
+}
+
+fn shares(file_extensions: &BTreeMap<String, u64>) -> Vec<LanguageShare> {
+    let languages = languages(file_extensions);
+    let total: u64 = languages.iter().map(|(_, n)| n).sum();
+    languages
+        .into_iter()
+        .map(|(language, operations)| LanguageShare {
+            language,
+            operations,
+            share: operations as f64 / total as f64,
+        })
+        .collect()
+}
+
+/// One line such as `Rust 60%, Markdown 20%, 2 more 20%`, listing at most
+/// `limit` languages.
+pub fn summary_line(languages: &[LanguageShare], limit: usize) -> String {
+    let mut parts: Vec<String> = languages
+        .iter()
+        .take(limit)
+        .map(|l| format!("{} {:.0}%", l.language, l.share * 100.0))
+        .collect();
+    if languages.len() > limit {
+        let rest: f64 = languages[limit..].iter().map(|l| l.share).sum();
+        parts.push(format!(
+            "{} more {:.0}%",
+            languages.len() - limit,
+            rest * 100.0
+        ));
+    }
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Application, MessageRole, Stats};
+    use chrono::{TimeZone, Utc};
+
+    fn message(project: &str, day: u32, extensions: &[(&str, u64)]) -> ConversationMessage {
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap(),
+            project_hash: project.to_string(),
+            conversation_hash: "c".to_string(),
+            local_hash: None,
+            global_hash: format!("{project}{day}"),
+            model: None,
+            stats: Stats {
+                file_extensions: extensions
+                    .iter()
+                    .map(|(ext, n)| (ext.to_string(), *n))
+                    .collect(),
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        }
+    }
+
+    #[test]
+    fn splits_operations_by_language_day_and_project() {
+        let analyzers = vec![(
+            "Claude Code".to_string(),
+            vec![
+                message("aaaaaaaaaaaa", 10, &[("rs", 3), ("md", 1)]),
+                message("aaaaaaaaaaaa", 11, &[("rs", 3)]),
+                message("bbbbbbbbbbbb", 11, &[("ts", 1), ("tsx", 2)]),
+                message("bbbbbbbbbbbb", 11, &[]),
+            ],
+        )];
+        crate::utils::remember_project("bbbbbbbbbbbb", "/home/me/src/web");
+        let report = report(&analyzers, DateRange::default());
+
+        let overall: Vec<(&str, u64)> = report
+            .overall
+            .iter()
+            .map(|l| (l.language.as_str(), l.operations))
+            .collect();
+        assert_eq!(
+            overall,
+            vec![("Rust", 6), ("TypeScript", 3), ("Markdown", 1)]
+        );
+        assert!((report.overall[0].share - 0.6).abs() < 1e-9);
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.projects.len(), 2);
+        assert_eq!(report.projects[0].project, "aaaaaaaa");
+        assert_eq!(report.projects[0].operations, 7);
+        assert_eq!(report.projects[1].project, "/home/me/src/web");
+        assert_eq!(
+            summary_line(&report.overall, 2),
+            "Rust 60%, TypeScript 30%, 1 more 10%"
+        );
+    }
+}
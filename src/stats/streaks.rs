@@ -0,0 +1,168 @@
+//! Streaks and milestones for the achievements panel: days in a row with
+//! agent usage, lifetime token milestones, and the biggest day's spend.
+//!
+//! A day counts toward a streak when it has any message or cost. Today not
+//! being used yet doesn't break the current streak; it only stops growing.
+
+use crate::types::DailyStats;
+use chrono::{NaiveDate, TimeDelta};
+use std::collections::BTreeMap;
+
+/// Lifetime token counts worth celebrating.
+pub const TOKEN_MILESTONES: [u64; 6] = [
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Achievements {
+    /// Days in a row up to today, or up to yesterday if today is still empty.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Last day of the longest streak, the most recent on ties.
+    pub longest_streak_end: Option<NaiveDate>,
+    pub active_days: u32,
+    /// Input, output and cached tokens.
+    pub lifetime_tokens: u64,
+    pub milestone_reached: Option<u64>,
+    pub next_milestone: Option<u64>,
+    /// The most expensive day and its cost in cents, the earliest on ties.
+    pub biggest_day: Option<(NaiveDate, u32)>,
+}
+
+impl Achievements {
+    pub fn from_days(daily_stats: &BTreeMap<String, DailyStats>, today: NaiveDate) -> Self {
+        let mut achievements = Self::default();
+        let mut previous: Option<NaiveDate> = None;
+        let mut run = 0u32;
+
+        // Keys are ISO dates, so the map iterates oldest first.
+        for (key, day) in daily_stats {
+            let Ok(date) = NaiveDate::parse_from_str(key, "%Y-%m-%d") else {
+                continue;
+            };
+            let stats = &day.stats;
+            achievements.lifetime_tokens +=
+                stats.input_tokens + stats.output_tokens + stats.cached_tokens;
+            if day.user_messages + day.ai_messages == 0 && stats.cost_cents == 0 {
+                continue;
+            }
+
+            achievements.active_days += 1;
+            if achievements
+                .biggest_day
+                .is_none_or(|(_, cents)| stats.cost_cents > cents)
+                && stats.cost_cents > 0
+            {
+                achievements.biggest_day = Some((date, stats.cost_cents));
+            }
+
+            run = match previous {
+                Some(previous) if date - previous == TimeDelta::days(1) => run + 1,
+                _ => 1,
+            };
+            previous = Some(date);
+            if run >= achievements.longest_streak {
+                achievements.longest_streak = run;
+                achievements.longest_streak_end = Some(date);
+            }
+        }
+
+        if let Some(last) = previous
+            && (last == today || last == today - TimeDelta::days(1))
+        {
+            achievements.current_streak = run;
+        }
+
+        achievements.milestone_reached = TOKEN_MILESTONES
+            .iter()
+            .rev()
+            .copied()
+            .find(|milestone| achievements.lifetime_tokens >= *milestone);
+        achievements.next_milestone = TOKEN_MILESTONES
+            .iter()
+            .copied()
+            .find(|milestone| achievements.lifetime_tokens < *milestone);
+        achievements
+    }
+
+    /// Progress from the last milestone reached toward the next, 0 to 1.
+    pub fn milestone_progress(&self) -> Option<f64> {
+        let next = self.next_milestone?;
+        let floor = self.milestone_reached.unwrap_or(0);
+        Some((self.lifetime_tokens - floor) as f64 / (next - floor) as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TuiStats;
+
+    fn days(entries: &[(&str, u32, u64)]) -> BTreeMap<String, DailyStats> {
+        entries
+            .iter()
+            .map(|&(date, cost_cents, input_tokens)| {
+                let day = DailyStats {
+                    ai_messages: u32::from(cost_cents > 0 || input_tokens > 0),
+                    stats: TuiStats {
+                        cost_cents,
+                        input_tokens,
+                        ..TuiStats::default()
+                    },
+                    ..DailyStats::default()
+                };
+                (date.to_string(), day)
+            })
+            .collect()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn streaks_count_consecutive_used_days() {
+        let stats = days(&[
+            ("2025-06-01", 100, 400_000),
+            ("2025-06-02", 250, 400_000),
+            ("2025-06-03", 50, 400_000),
+            // A gap-filled empty day breaks the run.
+            ("2025-06-04", 0, 0),
+            ("2025-06-05", 250, 100_000),
+            ("2025-06-06", 10, 100_000),
+        ]);
+
+        // Today is still empty, so the streak through yesterday holds.
+        let achievements = Achievements::from_days(&stats, date("2025-06-07"));
+        assert_eq!(achievements.current_streak, 2);
+        assert_eq!(achievements.longest_streak, 3);
+        assert_eq!(achievements.longest_streak_end, Some(date("2025-06-03")));
+        assert_eq!(achievements.active_days, 5);
+        // The first of two equally expensive days.
+        assert_eq!(achievements.biggest_day, Some((date("2025-06-02"), 250)));
+
+        // A day without use ends it.
+        let achievements = Achievements::from_days(&stats, date("2025-06-08"));
+        assert_eq!(achievements.current_streak, 0);
+    }
+
+    #[test]
+    fn milestones_track_lifetime_tokens() {
+        let stats = days(&[("2025-06-01", 100, 5_500_000)]);
+        let achievements = Achievements::from_days(&stats, date("2025-06-01"));
+        assert_eq!(achievements.lifetime_tokens, 5_500_000);
+        assert_eq!(achievements.milestone_reached, Some(1_000_000));
+        assert_eq!(achievements.next_milestone, Some(10_000_000));
+        assert_eq!(achievements.milestone_progress(), Some(0.5));
+
+        let empty = Achievements::from_days(&BTreeMap::new(), date("2025-06-01"));
+        assert_eq!(empty.milestone_reached, None);
+        assert_eq!(empty.milestone_progress(), Some(0.0));
+        assert_eq!(empty.biggest_day, None);
+    }
+}
@@ -0,0 +1,143 @@
+//! How fast tokens flow: tokens per busy hour, average response size, and
+//! which hours of the day see the most replies.
+//!
+//! Everything comes from [`DailyStats::hourly_messages`], the per-hour reply
+//! counts taken from message timestamps, so no raw messages need to be kept.
+//! A busy hour is one clock hour on one day with at least one reply, however
+//! many tools replied in it. It's coarser than the idle-gap active time of
+//! [`crate::stats::active`], which measures work blocks to the second.
+
+use crate::types::{CompactDate, DailyStats};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Throughput {
+    /// Input, output and cached tokens.
+    pub tokens: u64,
+    pub output_tokens: u64,
+    pub responses: u64,
+    pub busy_hours: u64,
+    pub tokens_per_busy_hour: f64,
+    /// Output tokens per assistant reply.
+    pub average_response_tokens: f64,
+    /// Replies by local hour of day (index 0-23), summed over every day.
+    pub hourly_responses: [u64; 24],
+    pub busiest_hour: Option<usize>,
+}
+
+impl Throughput {
+    /// Days may come from several tools; those sharing a date share their
+    /// busy hours.
+    pub fn from_days<'a>(days: impl IntoIterator<Item = &'a DailyStats>) -> Self {
+        let mut throughput = Self::default();
+        let mut busy: HashSet<(CompactDate, usize)> = HashSet::new();
+        for day in days {
+            let stats = &day.stats;
+            throughput.tokens += stats.input_tokens + stats.output_tokens + stats.cached_tokens;
+            throughput.output_tokens += stats.output_tokens;
+            throughput.responses += day.ai_messages as u64;
+            for (hour, (total, &count)) in throughput
+                .hourly_responses
+                .iter_mut()
+                .zip(&day.hourly_messages)
+                .enumerate()
+            {
+                *total += count as u64;
+                if count > 0 {
+                    busy.insert((day.date, hour));
+                }
+            }
+        }
+        throughput.busy_hours = busy.len() as u64;
+
+        if throughput.busy_hours > 0 {
+            throughput.tokens_per_busy_hour =
+                throughput.tokens as f64 / throughput.busy_hours as f64;
+        }
+        if throughput.responses > 0 {
+            throughput.average_response_tokens =
+                throughput.output_tokens as f64 / throughput.responses as f64;
+        }
+        // Earliest hour on ties.
+        throughput.busiest_hour = throughput
+            .hourly_responses
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .max_by(|(a_hour, a), (b_hour, b)| a.cmp(b).then(b_hour.cmp(a_hour)))
+            .map(|(hour, _)| hour);
+        throughput
+    }
+
+    pub fn max_hourly(&self) -> u64 {
+        self.hourly_responses.iter().copied().max().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TuiStats;
+
+    fn day(date: &str, hours: &[(usize, u32)], output_tokens: u64) -> DailyStats {
+        let mut day = DailyStats {
+            date: CompactDate::from_str(date).unwrap(),
+            stats: TuiStats {
+                input_tokens: 1_000,
+                output_tokens,
+                cached_tokens: 4_000,
+                ..TuiStats::default()
+            },
+            ..DailyStats::default()
+        };
+        for &(hour, count) in hours {
+            day.hourly_messages[hour] = count;
+            day.ai_messages += count;
+        }
+        day
+    }
+
+    #[test]
+    fn throughput_counts_each_hour_of_each_day_once() {
+        let days = [
+            day("2025-06-01", &[(9, 3), (14, 1)], 800),
+            day("2025-06-02", &[(9, 2), (22, 2)], 1_200),
+        ];
+        let throughput = Throughput::from_days(&days);
+
+        assert_eq!(throughput.tokens, 12_000);
+        assert_eq!(throughput.busy_hours, 4);
+        assert_eq!(throughput.tokens_per_busy_hour, 3_000.0);
+        assert_eq!(throughput.responses, 8);
+        assert_eq!(throughput.average_response_tokens, 250.0);
+        assert_eq!(throughput.hourly_responses[9], 5);
+        assert_eq!(throughput.hourly_responses[22], 2);
+        assert_eq!(throughput.busiest_hour, Some(9));
+        assert_eq!(throughput.max_hourly(), 5);
+    }
+
+    #[test]
+    fn busiest_hour_prefers_the_earliest_on_ties_and_empty_data_has_none() {
+        let throughput = Throughput::from_days(&[day("2025-06-01", &[(20, 2), (7, 2)], 0)]);
+        assert_eq!(throughput.busiest_hour, Some(7));
+
+        let empty = Throughput::from_days(&[]);
+        assert_eq!(empty.busiest_hour, None);
+        assert_eq!(empty.tokens_per_busy_hour, 0.0);
+        assert_eq!(empty.average_response_tokens, 0.0);
+    }
+
+    #[test]
+    fn tools_replying_in_the_same_hour_share_it() {
+        let days = [
+            day("2025-06-01", &[(9, 3)], 0),
+            day("2025-06-01", &[(9, 1), (10, 1)], 0),
+        ];
+        let throughput = Throughput::from_days(&days);
+
+        assert_eq!(throughput.busy_hours, 2);
+        assert_eq!(throughput.hourly_responses[9], 4);
+    }
+}
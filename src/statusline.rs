@@ -0,0 +1,295 @@
+//! One-line usage summary for shell prompts and the tmux status bar.
+//!
+//! Parsing every analyzer's history is far too slow to run every few seconds,
+//! so the TUI keeps a small "hot snapshot" of today's totals on disk and
+//! `splitrail statusline` only reads it back. A missing or outdated snapshot is
+//! reported with [`STALE_SENTINEL`] rather than silently showing old numbers.
+//!
+//! The snapshot is written by a background [`SnapshotPersister`] rather than
+//! the draw loop: bursts of changes are coalesced into one write, and it's
+//! flushed on exit and on SIGTERM so a killed TUI leaves current numbers.
+
+use crate::config::UploadState;
+use crate::persist::{self, JsonFormat};
+use crate::types::{CompactDate, MultiAnalyzerStatsView, SharedAnalyzerView};
+use crate::utils::{NumberFormatOptions, format_number};
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Printed instead of the totals when the snapshot is missing or too old.
+pub const STALE_SENTINEL: &str = "splitrail: stale";
+
+/// How often a running TUI rewrites the snapshot even when nothing changed,
+/// so readers can tell "no new usage" apart from "nobody is updating this".
+pub const HEARTBEAT_SECS: u64 = 60;
+
+/// Changes arriving within this long of each other share one write.
+const COALESCE_INTERVAL: Duration = Duration::from_secs(2);
+
+const SNAPSHOT_FORMAT: JsonFormat = JsonFormat {
+    name: "status snapshot",
+    migrations: &[persist::add_version_header],
+};
+
+/// Today's totals across all analyzers, as last written by the TUI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotSnapshot {
+    /// Unix timestamp (seconds) of when the snapshot was written.
+    pub updated_at: i64,
+    /// Local date the totals belong to.
+    pub date: CompactDate,
+    pub cost_cents: u64,
+    pub tokens: u64,
+    pub messages: u64,
+    pub conversations: u64,
+}
+
+impl HotSnapshot {
+    /// Sum today's row of every analyzer view.
+    pub fn from_views(views: &[SharedAnalyzerView]) -> Self {
+        let date = CompactDate::from_local(&Local::now());
+        let key = date.to_string();
+        let mut snapshot = Self {
+            updated_at: Utc::now().timestamp(),
+            date,
+            cost_cents: 0,
+            tokens: 0,
+            messages: 0,
+            conversations: 0,
+        };
+
+        for view in views {
+            let view = view.read();
+            if let Some(day) = view.daily_stats.get(&key) {
+                snapshot.cost_cents += day.stats.cost_cents as u64;
+                snapshot.tokens +=
+                    day.stats.input_tokens + day.stats.output_tokens + day.stats.cached_tokens;
+                snapshot.messages += day.ai_messages as u64;
+                snapshot.conversations += day.conversations as u64;
+            }
+        }
+
+        snapshot
+    }
+
+    /// Location of the snapshot file, next to the upload state file.
+    pub fn path() -> Result<PathBuf> {
+        let state_path = UploadState::state_path()?;
+        let dir = state_path
+            .parent()
+            .context("State file path has no parent directory")?;
+        Ok(dir.join("snapshot.json"))
+    }
+
+    /// `None` when there is no usable snapshot; a corrupt one is quarantined.
+    pub fn load() -> Result<Option<Self>> {
+        persist::read_json(&Self::path()?, &SNAPSHOT_FORMAT)
+    }
+
+    /// Parse a snapshot file's contents, e.g. from a sync archive. `None`
+    /// when it was written by a newer splitrail.
+    pub fn decode(content: &mut [u8]) -> Result<Option<Self>> {
+        persist::decode_json(content, &SNAPSHOT_FORMAT)
+    }
+
+    /// Write the snapshot atomically so a concurrent reader never sees half a file.
+    pub fn save(&self) -> Result<()> {
+        persist::write_json(&Self::path()?, &SNAPSHOT_FORMAT, self)
+    }
+
+    /// A snapshot is fresh when it covers today and was written within `max_age_secs`.
+    pub fn is_fresh(&self, now: chrono::DateTime<Local>, max_age_secs: u64) -> bool {
+        let age = now.timestamp() - self.updated_at;
+        self.date == CompactDate::from_local(&now) && age <= max_age_secs as i64
+    }
+}
+
+/// Writes the snapshot from the TUI's stats channel in the background.
+#[derive(Clone)]
+pub struct SnapshotPersister {
+    stats: watch::Receiver<MultiAnalyzerStatsView>,
+}
+
+impl SnapshotPersister {
+    /// Start persisting: at most one write per [`COALESCE_INTERVAL`] while
+    /// stats change, and one every [`HEARTBEAT_SECS`] regardless. Nothing is
+    /// written while analyzers are still loading, so the partial totals of a
+    /// cold start never replace the last complete snapshot.
+    pub fn spawn(stats: watch::Receiver<MultiAnalyzerStatsView>) -> Self {
+        let mut changes = stats.clone();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(COALESCE_INTERVAL);
+            let mut dirty = true;
+            let mut last_write: Option<Instant> = None;
+            loop {
+                tokio::select! {
+                    changed = changes.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        dirty = true;
+                    }
+                    _ = tick.tick() => {
+                        let heartbeat_due = last_write.is_none_or(|at| {
+                            at.elapsed() >= Duration::from_secs(HEARTBEAT_SECS)
+                        });
+                        if (dirty || heartbeat_due) && write_snapshot(&changes.borrow()) {
+                            dirty = false;
+                            last_write = Some(Instant::now());
+                        }
+                    }
+                }
+            }
+        });
+        Self { stats }
+    }
+
+    /// Write the current totals now, e.g. before exiting.
+    pub fn flush(&self) {
+        write_snapshot(&self.stats.borrow());
+    }
+}
+
+/// Returns whether a complete snapshot was written.
+fn write_snapshot(stats: &MultiAnalyzerStatsView) -> bool {
+    if !stats.loading.is_empty() {
+        return false;
+    }
+    if let Err(e) = HotSnapshot::from_views(&stats.analyzer_stats).save() {
+        crate::utils::warn_once(format!("{e:#}"));
+    }
+    true
+}
+
+/// Render the status line, or [`STALE_SENTINEL`] when `snapshot` is `None`.
+///
+/// With `tmux` set, colors use tmux `#[...]` style directives (which tmux
+/// interprets in `#()` output) instead of ANSI escapes, and literal `#` is
+/// doubled so tmux does not treat it as a format.
+pub fn render(
+    snapshot: Option<&HotSnapshot>,
+    tmux: bool,
+    format_options: &NumberFormatOptions,
+) -> String {
+    let Some(snapshot) = snapshot else {
+        return if tmux {
+            format!("#[fg=red,bold]{STALE_SENTINEL}#[default]")
+        } else {
+            STALE_SENTINEL.to_string()
+        };
+    };
+
+    let human = NumberFormatOptions {
+        use_human: true,
+        decimal_places: 1,
+        ..format_options.clone()
+    };
+    let cost = format!(
+        "{}{:.prec$}",
+        format_options.currency_symbol,
+        snapshot.cost_cents as f64 / 100.0,
+        prec = format_options.cost_decimal_places
+    );
+    let tokens = format!("{} tok", format_number(snapshot.tokens, &human));
+    let messages = format!("{} msgs", format_number(snapshot.messages, &human));
+
+    if tmux {
+        let escape = |s: &str| s.replace('#', "##");
+        format!(
+            "#[fg=yellow]{}#[default] #[fg=colour245]{} · {}#[default]",
+            escape(&cost),
+            escape(&tokens),
+            escape(&messages)
+        )
+    } else {
+        format!("{cost} · {tokens} · {messages}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn format_options() -> NumberFormatOptions {
+        NumberFormatOptions {
+            use_comma: false,
+            use_human: false,
+            locale: "en".to_string(),
+            decimal_places: 2,
+            currency_symbol: "$".to_string(),
+            cost_decimal_places: 2,
+        }
+    }
+
+    fn snapshot_at(now: chrono::DateTime<Local>) -> HotSnapshot {
+        HotSnapshot {
+            updated_at: now.timestamp(),
+            date: CompactDate::from_local(&now),
+            cost_cents: 1234,
+            tokens: 1_250_000,
+            messages: 42,
+            conversations: 3,
+        }
+    }
+
+    #[test]
+    fn renders_plain_and_tmux_lines() {
+        let snapshot = snapshot_at(Local::now());
+        assert_eq!(
+            render(Some(&snapshot), false, &format_options()),
+            "$12.34 · 1.2m tok · 42 msgs"
+        );
+        assert_eq!(
+            render(Some(&snapshot), true, &format_options()),
+            "#[fg=yellow]$12.34#[default] #[fg=colour245]1.2m tok · 42 msgs#[default]"
+        );
+    }
+
+    #[test]
+    fn tmux_output_escapes_hash_in_currency() {
+        let options = NumberFormatOptions {
+            currency_symbol: "#".to_string(),
+            ..format_options()
+        };
+        let line = render(Some(&snapshot_at(Local::now())), true, &options);
+        assert!(line.starts_with("#[fg=yellow]##12.34#[default]"));
+    }
+
+    #[test]
+    fn stale_sentinel_for_missing_snapshot() {
+        assert_eq!(render(None, false, &format_options()), STALE_SENTINEL);
+        assert_eq!(
+            render(None, true, &format_options()),
+            "#[fg=red,bold]splitrail: stale#[default]"
+        );
+    }
+
+    #[test]
+    fn freshness_requires_same_day_and_recent_write() {
+        let written = Local.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let snapshot = snapshot_at(written);
+
+        assert!(snapshot.is_fresh(written + chrono::Duration::seconds(60), 180));
+        assert!(!snapshot.is_fresh(written + chrono::Duration::seconds(181), 180));
+        assert!(!snapshot.is_fresh(
+            Local.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap(),
+            u64::MAX >> 1
+        ));
+    }
+
+    #[test]
+    fn partial_stats_are_never_persisted() {
+        let loading = MultiAnalyzerStatsView {
+            analyzer_stats: Vec::new(),
+            loading: vec!["Codex CLI".to_string()],
+            versions: Vec::new(),
+        };
+        assert!(!write_snapshot(&loading));
+    }
+}
@@ -0,0 +1,531 @@
+//! Optional SQLite mirror of parsed usage for ad-hoc SQL.
+//!
+//! With `[cache] sqlite_store = true`, every full parse is written to
+//! `splitrail.db` in the state directory: one row per message, plus per-day totals for
+//! each tool and each model. Each tool's rows are replaced wholesale in one
+//! transaction, so readers always see a complete parse, and WAL mode lets
+//! `sqlite3` or a notebook query the file while splitrail writes to it.
+//!
+//! ```sql
+//! SELECT model, SUM(cost) FROM messages
+//! WHERE local_date >= '2025-06-01' GROUP BY model ORDER BY 2 DESC;
+//! ```
+
+use crate::types::{
+    AgenticCodingToolStats, CompactDate, ConversationMessage, DailyStats, MessageRole,
+    MultiAnalyzerStats,
+};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, params};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS messages (
+        global_hash TEXT PRIMARY KEY NOT NULL,
+        analyzer TEXT NOT NULL,
+        date TEXT NOT NULL,
+        local_date TEXT NOT NULL,
+        project_hash TEXT NOT NULL,
+        conversation_hash TEXT NOT NULL,
+        session_name TEXT,
+        model TEXT,
+        role TEXT NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        reasoning_tokens INTEGER NOT NULL,
+        cache_creation_tokens INTEGER NOT NULL,
+        cache_read_tokens INTEGER NOT NULL,
+        cached_tokens INTEGER NOT NULL,
+        cost REAL NOT NULL,
+        tool_calls INTEGER NOT NULL,
+        files_read INTEGER NOT NULL,
+        files_edited INTEGER NOT NULL,
+        lines_added INTEGER NOT NULL,
+        lines_deleted INTEGER NOT NULL,
+        terminal_commands INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS messages_analyzer_date ON messages(analyzer, local_date);
+    CREATE TABLE IF NOT EXISTS daily_stats (
+        analyzer TEXT NOT NULL,
+        date TEXT NOT NULL,
+        user_messages INTEGER NOT NULL,
+        ai_messages INTEGER NOT NULL,
+        conversations INTEGER NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        reasoning_tokens INTEGER NOT NULL,
+        cached_tokens INTEGER NOT NULL,
+        cost REAL NOT NULL,
+        tool_calls INTEGER NOT NULL,
+        PRIMARY KEY (analyzer, date)
+    );
+    CREATE TABLE IF NOT EXISTS daily_model_stats (
+        analyzer TEXT NOT NULL,
+        date TEXT NOT NULL,
+        model TEXT NOT NULL,
+        messages INTEGER NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        reasoning_tokens INTEGER NOT NULL,
+        cache_creation_tokens INTEGER NOT NULL,
+        cache_read_tokens INTEGER NOT NULL,
+        cached_tokens INTEGER NOT NULL,
+        cost REAL NOT NULL,
+        tool_calls INTEGER NOT NULL,
+        PRIMARY KEY (analyzer, date, model)
+    );";
+
+/// `MIGRATIONS[n]` upgrades a store at `PRAGMA user_version` n to n + 1.
+/// Stores from before versioning are at version 0 and already have `SCHEMA`,
+/// which is idempotent, so replaying it is harmless.
+const MIGRATIONS: &[&str] = &[SCHEMA, "ALTER TABLE messages ADD COLUMN session_id TEXT;"];
+
+pub fn store_path() -> Option<PathBuf> {
+    crate::paths::state_dir()
+        .ok()
+        .map(|dir| dir.join("splitrail.db"))
+}
+
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    /// Open or create the store. A file that isn't a readable database is
+    /// quarantined and rebuilt, since the next parse repopulates it anyway.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        match Self::open_once(path) {
+            Err(error) if crate::persist::is_corrupt_sqlite(&error) => {
+                crate::persist::quarantine(path, "SQLite store", &error);
+                for suffix in ["-wal", "-shm"] {
+                    let mut sidecar = path.as_os_str().to_owned();
+                    sidecar.push(suffix);
+                    let _ = std::fs::remove_file(PathBuf::from(sidecar));
+                }
+                Self::open_once(path)
+            }
+            result => result,
+        }
+    }
+
+    fn open_once(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path).context("Failed to open SQLite store")?;
+        connection
+            .busy_timeout(std::time::Duration::from_secs(5))
+            .context("Failed to configure SQLite store")?;
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .context("Failed to configure SQLite store")?;
+        crate::persist::migrate_sqlite(&connection, MIGRATIONS, "SQLite store")?;
+        Ok(Self { connection })
+    }
+
+    /// Replace every row of the tools in `stats`, leaving other tools alone.
+    /// Returns the number of messages written.
+    pub fn replace(&mut self, stats: &MultiAnalyzerStats) -> Result<usize> {
+        let transaction = self
+            .connection
+            .transaction()
+            .context("Failed to begin SQLite store transaction")?;
+        let mut written = 0;
+        for analyzer_stats in &stats.analyzer_stats {
+            written += write_analyzer(&transaction, analyzer_stats)?;
+        }
+        transaction
+            .commit()
+            .context("Failed to commit SQLite store transaction")?;
+        Ok(written)
+    }
+}
+
+fn write_analyzer(connection: &Connection, stats: &AgenticCodingToolStats) -> Result<usize> {
+    let analyzer = stats.analyzer_name.as_str();
+    clear_analyzer(connection, analyzer)?;
+    insert_messages(connection, analyzer, &stats.messages)?;
+    insert_days(connection, analyzer, &stats.daily_stats)?;
+    Ok(stats.messages.len())
+}
+
+fn clear_analyzer(connection: &Connection, analyzer: &str) -> Result<()> {
+    for table in ["messages", "daily_stats", "daily_model_stats"] {
+        connection
+            .execute(
+                &format!("DELETE FROM {table} WHERE analyzer = ?1"),
+                [analyzer],
+            )
+            .with_context(|| format!("Failed to clear {table} for {analyzer}"))?;
+    }
+    Ok(())
+}
+
+fn insert_messages(
+    connection: &Connection,
+    analyzer: &str,
+    messages: &[ConversationMessage],
+) -> Result<()> {
+    let mut insert_message = connection
+        .prepare_cached(
+            "INSERT OR REPLACE INTO messages VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14,
+                ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23
+            )",
+        )
+        .context("Failed to prepare message insert")?;
+    for message in messages {
+        let s = &message.stats;
+        insert_message
+            .execute(params![
+                message.global_hash,
+                analyzer,
+                message.date.to_rfc3339(),
+                CompactDate::from_local(&message.date).to_string(),
+                message.project_hash,
+                message.conversation_hash,
+                message.session_name,
+                message.model,
+                match message.role {
+                    MessageRole::User => "user",
+                    MessageRole::Assistant => "assistant",
+                },
+                s.input_tokens as i64,
+                s.output_tokens as i64,
+                s.reasoning_tokens as i64,
+                s.cache_creation_tokens as i64,
+                s.cache_read_tokens as i64,
+                s.cached_tokens as i64,
+                s.cost,
+                s.tool_calls,
+                s.files_read as i64,
+                s.files_edited as i64,
+                s.lines_added as i64,
+                s.lines_deleted as i64,
+                s.terminal_commands as i64,
+                message.stable_session_id,
+            ])
+            .context("Failed to store message")?;
+    }
+    Ok(())
+}
+
+fn insert_days(
+    connection: &Connection,
+    analyzer: &str,
+    daily_stats: &BTreeMap<String, DailyStats>,
+) -> Result<()> {
+    let mut insert_day = connection
+        .prepare_cached(
+            "INSERT INTO daily_stats VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )
+        .context("Failed to prepare daily insert")?;
+    let mut insert_model = connection
+        .prepare_cached(
+            "INSERT INTO daily_model_stats VALUES
+             (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )
+        .context("Failed to prepare daily model insert")?;
+    for (date, day) in daily_stats {
+        insert_day
+            .execute(params![
+                analyzer,
+                date,
+                day.user_messages,
+                day.ai_messages,
+                day.conversations,
+                day.stats.input_tokens as i64,
+                day.stats.output_tokens as i64,
+                day.stats.reasoning_tokens as i64,
+                day.stats.cached_tokens as i64,
+                day.stats.cost(),
+                day.stats.tool_calls,
+            ])
+            .context("Failed to store daily stats")?;
+        for model in day.model_stats.values() {
+            insert_model
+                .execute(params![
+                    analyzer,
+                    date,
+                    model.model,
+                    model.message_count,
+                    model.input_tokens as i64,
+                    model.output_tokens as i64,
+                    model.reasoning_tokens as i64,
+                    model.cache_creation_tokens as i64,
+                    model.cache_read_tokens as i64,
+                    model.cached_tokens as i64,
+                    model.cost,
+                    model.tool_calls,
+                ])
+                .context("Failed to store daily model stats")?;
+        }
+    }
+    Ok(())
+}
+
+/// Mirror `stats` into the SQLite store when `[cache] sqlite_store` is on.
+/// Failures are reported once and never abort the command that parsed them.
+pub fn record(stats: &MultiAnalyzerStats) {
+    let enabled = crate::config::Config::load()
+        .ok()
+        .flatten()
+        .is_some_and(|config| config.cache.sqlite_store);
+    if !enabled {
+        return;
+    }
+    let Some(path) = store_path() else {
+        return;
+    };
+    if let Err(e) = Store::open(&path).and_then(|mut store| store.replace(stats)) {
+        crate::utils::warn_once(format!(
+            "WARNING: couldn't update the SQLite store at {}: {e:#}",
+            path.display()
+        ));
+    }
+}
+
+enum Write {
+    Clear(String),
+    Messages(String, Vec<ConversationMessage>),
+    Days(String, BTreeMap<String, DailyStats>),
+}
+
+/// Mirrors a streamed parse into the store as it happens, so the rows come
+/// from the same batches the views are built from instead of a second full
+/// parse. Writes go to a dedicated thread through a bounded channel and land
+/// in a single transaction that commits on [`Self::finish`], so readers still
+/// only ever see complete parses.
+pub struct StoreWriter {
+    sender: mpsc::SyncSender<Write>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl StoreWriter {
+    /// Start a writer when `[cache] sqlite_store` is on.
+    pub fn spawn() -> Option<Self> {
+        let enabled = crate::config::Config::load()
+            .ok()
+            .flatten()
+            .is_some_and(|config| config.cache.sqlite_store);
+        if !enabled {
+            return None;
+        }
+        store_path().map(Self::spawn_at)
+    }
+
+    fn spawn_at(path: PathBuf) -> Self {
+        // A few batches of slack keeps parsing from waiting on every insert
+        // without letting a slow disk pile parsed messages up in memory.
+        let (sender, receiver) = mpsc::sync_channel(4);
+        let thread = std::thread::spawn(move || {
+            let result = Store::open(&path).and_then(|mut store| {
+                let transaction = store
+                    .connection
+                    .transaction()
+                    .context("Failed to begin SQLite store transaction")?;
+                for write in &receiver {
+                    match write {
+                        Write::Clear(analyzer) => clear_analyzer(&transaction, &analyzer)?,
+                        Write::Messages(analyzer, mut messages) => {
+                            crate::utils::assign_stable_session_ids(&analyzer, &mut messages);
+                            insert_messages(&transaction, &analyzer, &messages)?;
+                        }
+                        Write::Days(analyzer, days) => insert_days(&transaction, &analyzer, &days)?,
+                    }
+                }
+                transaction
+                    .commit()
+                    .context("Failed to commit SQLite store transaction")
+            });
+            if let Err(e) = result {
+                crate::utils::warn_once(format!(
+                    "WARNING: couldn't update the SQLite store at {}: {e:#}",
+                    path.display()
+                ));
+                // Keep accepting writes so the parse never blocks on a dead store.
+                for _ in receiver {}
+            }
+        });
+        Self { sender, thread }
+    }
+
+    /// Drop every stored row of `analyzer` ahead of its fresh parse.
+    pub fn clear(&self, analyzer: &str) {
+        let _ = self.sender.send(Write::Clear(analyzer.to_string()));
+    }
+
+    /// Store one parse batch. Batches never split a conversation, so each
+    /// one holds everything needed to give its sessions their stable IDs.
+    pub fn messages(&self, analyzer: &str, messages: Vec<ConversationMessage>) {
+        let _ = self
+            .sender
+            .send(Write::Messages(analyzer.to_string(), messages));
+    }
+
+    /// Store `analyzer`'s per-day totals once its parse is complete.
+    pub fn days(&self, analyzer: &str, daily_stats: BTreeMap<String, DailyStats>) {
+        let _ = self
+            .sender
+            .send(Write::Days(analyzer.to_string(), daily_stats));
+    }
+
+    /// Commit everything written and wait for the writer thread to exit.
+    pub fn finish(self) {
+        drop(self.sender);
+        let _ = self.thread.join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Application, ConversationMessage, Stats};
+    use chrono::{TimeZone, Utc};
+    use tempfile::tempdir;
+
+    fn tool(name: &str, hashes: &[&str]) -> AgenticCodingToolStats {
+        let messages: Vec<ConversationMessage> = hashes
+            .iter()
+            .map(|hash| ConversationMessage {
+                application: Application::ClaudeCode,
+                date: Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+                project_hash: "project".to_string(),
+                conversation_hash: "session".to_string(),
+                local_hash: None,
+                global_hash: format!("{name}-{hash}"),
+                model: Some("claude-sonnet-4-20250514".to_string()),
+                stats: Stats {
+                    output_tokens: 100,
+                    cost: 0.25,
+                    ..Stats::default()
+                },
+                role: MessageRole::Assistant,
+                uuid: None,
+                session_name: None,
+                stable_session_id: None,
+            })
+            .collect();
+        AgenticCodingToolStats {
+            daily_stats: crate::utils::aggregate_by_date(&messages),
+            num_conversations: 1,
+            messages,
+            analyzer_name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn replacing_a_tool_keeps_the_others() {
+        let directory = tempdir().unwrap();
+        let mut store = Store::open(&directory.path().join("splitrail.db")).unwrap();
+        let first = MultiAnalyzerStats {
+            analyzer_stats: vec![tool("Claude Code", &["a", "b"]), tool("Codex CLI", &["c"])],
+        };
+        assert_eq!(store.replace(&first).unwrap(), 3);
+        let rescan = MultiAnalyzerStats {
+            analyzer_stats: vec![tool("Claude Code", &["a"])],
+        };
+        assert_eq!(store.replace(&rescan).unwrap(), 1);
+
+        let query = |sql: &str| -> f64 {
+            store
+                .connection
+                .query_row(sql, [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(query("SELECT COUNT(*) * 1.0 FROM messages"), 2.0);
+        assert_eq!(
+            query("SELECT SUM(cost) FROM daily_stats WHERE analyzer = 'Claude Code'"),
+            0.25
+        );
+        assert_eq!(
+            query("SELECT SUM(output_tokens) * 1.0 FROM daily_model_stats"),
+            200.0
+        );
+    }
+
+    #[test]
+    fn stores_from_before_versioning_gain_session_ids() {
+        let directory = tempdir().unwrap();
+        let path = directory.path().join("splitrail.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(SCHEMA)
+            .unwrap();
+
+        let mut store = Store::open(&path).unwrap();
+        let mut stats = tool("Claude Code", &["a"]);
+        stats.messages[0].stable_session_id = Some("stable".to_string());
+        store
+            .replace(&MultiAnalyzerStats {
+                analyzer_stats: vec![stats],
+            })
+            .unwrap();
+
+        let session_id: String = store
+            .connection
+            .query_row("SELECT session_id FROM messages", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(session_id, "stable");
+        let version: i64 = store
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn streamed_batches_match_a_full_replace() {
+        let directory = tempdir().unwrap();
+        let replaced = directory.path().join("replaced.db");
+        let streamed = directory.path().join("streamed.db");
+        let mut stats = tool("Claude Code", &["a", "b", "c"]);
+        crate::utils::assign_stable_session_ids("Claude Code", &mut stats.messages);
+        Store::open(&replaced)
+            .unwrap()
+            .replace(&MultiAnalyzerStats {
+                analyzer_stats: vec![stats.clone()],
+            })
+            .unwrap();
+
+        let mut stale = Store::open(&streamed).unwrap();
+        stale
+            .replace(&MultiAnalyzerStats {
+                analyzer_stats: vec![tool("Claude Code", &["old"])],
+            })
+            .unwrap();
+        drop(stale);
+        let writer = StoreWriter::spawn_at(streamed.clone());
+        writer.clear("Claude Code");
+        writer.messages(
+            "Claude Code",
+            tool("Claude Code", &["a", "b", "c"]).messages,
+        );
+        writer.days("Claude Code", stats.daily_stats.clone());
+        writer.finish();
+
+        let rows = |path: &Path| -> Vec<(String, Option<String>, f64)> {
+            let store = Store::open(path).unwrap();
+            let mut statement = store
+                .connection
+                .prepare(
+                    "SELECT global_hash, session_id,
+                     (SELECT SUM(cost) FROM daily_stats) + (SELECT SUM(cost) FROM daily_model_stats)
+                     FROM messages ORDER BY global_hash",
+                )
+                .unwrap();
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        let expected = rows(&replaced);
+        assert_eq!(expected.len(), 3);
+        assert!(expected[0].1.is_some());
+        assert_eq!(rows(&streamed), expected);
+    }
+}
@@ -0,0 +1,240 @@
+//! Flat-fee plans from the `[subscriptions]` config section.
+//!
+//! Splitrail prices usage at API rates, which overstates what Claude Max or
+//! ChatGPT Plus users actually pay. In [`CostMode::Actual`] a subscribed tool's
+//! cost is its monthly fee instead, spread evenly over the days the tool was
+//! used that month. Model, session, and message costs are scaled by the same
+//! per-day factor so every breakdown still adds up to the day's total.
+
+use crate::config::SubscriptionConfig;
+use crate::types::{AgenticCodingToolStats, AnalyzerStatsView, CompactDate, DailyStats};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CostMode {
+    /// What the usage would have cost at API rates.
+    #[default]
+    Api,
+    /// Subscription fees for subscribed tools, API rates for the rest.
+    Actual,
+}
+
+impl CostMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Api => "API-equivalent cost",
+            Self::Actual => "actual cost",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Api => Self::Actual,
+            Self::Actual => Self::Api,
+        }
+    }
+}
+
+/// The cost mode together with the plans it applies.
+#[derive(Debug, Clone, Default)]
+pub struct CostBasis {
+    pub mode: CostMode,
+    pub subscriptions: HashMap<String, SubscriptionConfig>,
+}
+
+impl CostBasis {
+    /// Monthly fee to charge for `analyzer`, or `None` when it is priced at API rates.
+    pub fn monthly_fee(&self, analyzer: &str) -> Option<f64> {
+        if self.mode == CostMode::Api {
+            return None;
+        }
+        self.subscriptions
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(analyzer))
+            .map(|(_, plan)| plan.monthly_usd.max(0.0))
+    }
+
+    pub fn apply_to_view(&self, view: &AnalyzerStatsView) -> Option<AnalyzerStatsView> {
+        let fee = self.monthly_fee(&view.analyzer_name)?;
+        let mut view = view.clone();
+        let factors = rescale_days(&mut view.daily_stats, fee);
+        for session in &mut view.session_aggregates {
+            let factor = factors.get(&session.date).copied().unwrap_or(0.0);
+            session.stats.scale_cost(factor);
+        }
+        Some(view)
+    }
+
+    /// Factor [`Self::apply_to_view`] multiplies `day`'s API-rate costs by,
+    /// or `None` when the tool is priced at API rates.
+    pub fn day_factor(&self, view: &AnalyzerStatsView, day: CompactDate) -> Option<f64> {
+        let fee = self.monthly_fee(&view.analyzer_name)?;
+        let usage_days = usage_days_by_month(&view.daily_stats);
+        let factor = view
+            .daily_stats
+            .get(&day.to_string())
+            .map_or(0.0, |stats| share_and_factor(stats, &usage_days, fee).1);
+        Some(factor)
+    }
+
+    pub fn apply_to_stats(&self, stats: &mut AgenticCodingToolStats) {
+        let Some(fee) = self.monthly_fee(&stats.analyzer_name) else {
+            return;
+        };
+        let factors = rescale_days(&mut stats.daily_stats, fee);
+        for message in &mut stats.messages {
+            let date = CompactDate::from_local(&message.date);
+            message.stats.cost *= factors.get(&date).copied().unwrap_or(0.0);
+        }
+    }
+}
+
+fn is_usage_day(day: &DailyStats) -> bool {
+    day.ai_messages > 0 || day.stats.cost_cents > 0
+}
+
+/// Days with usage in each (year, month).
+fn usage_days_by_month(days: &BTreeMap<String, DailyStats>) -> HashMap<(u16, u8), u32> {
+    let mut usage_days: HashMap<(u16, u8), u32> = HashMap::new();
+    for day in days.values().filter(|day| is_usage_day(day)) {
+        *usage_days
+            .entry((day.date.year(), day.date.month()))
+            .or_default() += 1;
+    }
+    usage_days
+}
+
+/// `day`'s share of the month's fee, and the factor its API-rate costs are
+/// multiplied by to reach it.
+fn share_and_factor(
+    day: &DailyStats,
+    usage_days: &HashMap<(u16, u8), u32>,
+    monthly_fee: f64,
+) -> (f64, f64) {
+    let share = if is_usage_day(day) {
+        monthly_fee / usage_days[&(day.date.year(), day.date.month())] as f64
+    } else {
+        0.0
+    };
+    let api_cost = day.stats.cost();
+    let factor = if api_cost > 0.0 {
+        share / api_cost
+    } else {
+        0.0
+    };
+    (share, factor)
+}
+
+/// Replace each day's cost with its share of the month's fee, returning the
+/// factor every API-rate cost on that day was multiplied by.
+fn rescale_days(
+    days: &mut BTreeMap<String, DailyStats>,
+    monthly_fee: f64,
+) -> HashMap<CompactDate, f64> {
+    let usage_days = usage_days_by_month(days);
+    let mut factors = HashMap::new();
+    for day in days.values_mut() {
+        let (share, factor) = share_and_factor(day, &usage_days, monthly_fee);
+        // Scaling first keeps the subagent share of the day's cost.
+        day.stats.scale_cost(factor);
+        day.stats.set_cost(share);
+        for model in day.model_stats.values_mut() {
+            model.cost *= factor;
+        }
+        factors.insert(day.date, factor);
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ModelStats, TuiStats};
+
+    fn day(date: &str, cost_cents: u32) -> (String, DailyStats) {
+        let mut model = ModelStats::new("claude-opus-4-1".to_string());
+        model.cost = cost_cents as f64 / 100.0;
+        (
+            date.to_string(),
+            DailyStats {
+                date: CompactDate::from_str(date).unwrap(),
+                ai_messages: 1,
+                stats: TuiStats {
+                    cost_cents,
+                    ..TuiStats::default()
+                },
+                model_stats: BTreeMap::from([(model.model.clone(), model)]),
+                ..DailyStats::default()
+            },
+        )
+    }
+
+    fn basis(mode: CostMode) -> CostBasis {
+        CostBasis {
+            mode,
+            subscriptions: HashMap::from([(
+                "claude code".to_string(),
+                SubscriptionConfig { monthly_usd: 200.0 },
+            )]),
+        }
+    }
+
+    #[test]
+    fn spreads_the_fee_over_each_months_usage_days() {
+        let mut days = BTreeMap::from([
+            day("2025-05-31", 1000),
+            day("2025-06-01", 3000),
+            day("2025-06-02", 1000),
+            day("2025-06-03", 0),
+            day("2025-06-05", 4000),
+        ]);
+        days.get_mut("2025-06-03").unwrap().ai_messages = 0;
+        days.get_mut("2025-06-01")
+            .unwrap()
+            .stats
+            .subagent_cost_cents = 1500;
+
+        let factors = rescale_days(&mut days, 200.0);
+
+        assert_eq!(days["2025-05-31"].stats.cost_cents, 20000);
+        assert_eq!(days["2025-06-01"].stats.cost_cents, 6667);
+        // Subagents keep their half of the day.
+        assert_eq!(days["2025-06-01"].stats.subagent_cost_cents, 3333);
+        assert_eq!(days["2025-06-03"].stats.cost_cents, 0);
+        let june: u32 = days
+            .values()
+            .filter(|day| day.date.month() == 6)
+            .map(|day| day.stats.cost_cents)
+            .sum();
+        assert!((19999..=20001).contains(&june));
+        let model_cost = days["2025-06-05"].model_stats["claude-opus-4-1"].cost;
+        assert!((model_cost - 200.0 / 3.0).abs() < 1e-9);
+        assert!(
+            (factors[&CompactDate::from_str("2025-06-02").unwrap()] - 200.0 / 30.0).abs() < 1e-9
+        );
+    }
+
+    #[test]
+    fn only_subscribed_tools_in_actual_mode_are_rescaled() {
+        let actual = basis(CostMode::Actual);
+        assert_eq!(actual.monthly_fee("Claude Code"), Some(200.0));
+        assert_eq!(actual.monthly_fee("Codex CLI"), None);
+        assert_eq!(basis(CostMode::Api).monthly_fee("Claude Code"), None);
+    }
+
+    #[test]
+    fn day_factor_matches_the_rescaled_view() {
+        let view = AnalyzerStatsView {
+            daily_stats: BTreeMap::from([day("2025-06-01", 3000), day("2025-06-02", 1000)]),
+            session_aggregates: Vec::new(),
+            num_conversations: 0,
+            analyzer_name: "Claude Code".into(),
+        };
+        let date = CompactDate::from_str("2025-06-02").unwrap();
+        let factor = basis(CostMode::Actual).day_factor(&view, date).unwrap();
+        assert!((factor - 100.0 / 10.0).abs() < 1e-9);
+        assert_eq!(basis(CostMode::Api).day_factor(&view, date), None);
+    }
+}
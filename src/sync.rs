@@ -0,0 +1,217 @@
+//! Portable bundles of splitrail's local data for `splitrail sync`.
+//!
+//! An archive is a gzipped tarball of the upload state, the status snapshot,
+//! and the Claude Code history store, small enough to pass between machines
+//! through Dropbox or syncthing. Importing merges rather than overwrites: the
+//! newer snapshot wins and history entries missing locally are added. A
+//! project hash salt is adopted when there is none locally, so both machines
+//! salt alike. The upload watermark stays local: each machine uploads its own
+//! messages, and adopting another's later watermark would hide the ones
+//! created here but not uploaded yet.
+
+use crate::analyzers::claude_code_history::{
+    HISTORY_FILE_NAME, export_store, history_path, import_store,
+};
+use crate::config::UploadState;
+use crate::statusline::HotSnapshot;
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const STATE_ENTRY: &str = "state.toml";
+const SNAPSHOT_ENTRY: &str = "snapshot.json";
+
+/// What an import changed locally.
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub snapshot_replaced: bool,
+    pub history_added: usize,
+}
+
+/// Bundle the local data into `archive`, returning the entries written.
+pub fn export(archive: &Path) -> Result<Vec<&'static str>> {
+    export_to(archive, &history_path()?)
+}
+
+/// Merge an archive written by [`export`] into the local data.
+pub fn import(archive: &Path) -> Result<ImportSummary> {
+    import_from(archive, &history_path()?)
+}
+
+/// Scratch file beside `path` for an intermediate copy of the history store.
+fn staging_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}.tmp", std::process::id()));
+    PathBuf::from(name)
+}
+
+fn export_to(archive: &Path, history: &Path) -> Result<Vec<&'static str>> {
+    let partial = staging_path(archive);
+    let file = File::create(&partial)
+        .with_context(|| format!("Failed to create {}", partial.display()))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mut entries = Vec::new();
+
+    let result = (|| -> Result<()> {
+        let state_path = UploadState::state_path()?;
+        if state_path.exists() {
+            builder.append_path_with_name(&state_path, STATE_ENTRY)?;
+            entries.push(STATE_ENTRY);
+        }
+        let snapshot_path = HotSnapshot::path()?;
+        if snapshot_path.exists() {
+            builder.append_path_with_name(&snapshot_path, SNAPSHOT_ENTRY)?;
+            entries.push(SNAPSHOT_ENTRY);
+        }
+        // Archive a vacuumed copy so a TUI writing to the store can't tear it.
+        let copy = staging_path(history);
+        let _ = fs::remove_file(&copy);
+        let copied = export_store(history, &copy);
+        if let Ok(true) = copied {
+            let appended = builder.append_path_with_name(&copy, HISTORY_FILE_NAME);
+            let _ = fs::remove_file(&copy);
+            appended?;
+            entries.push(HISTORY_FILE_NAME);
+        }
+        copied?;
+        anyhow::ensure!(!entries.is_empty(), "No splitrail data found to export");
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })();
+
+    match result.and_then(|()| Ok(fs::rename(&partial, archive)?)) {
+        Ok(()) => Ok(entries),
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            Err(e.context(format!("Failed to write {}", archive.display())))
+        }
+    }
+}
+
+fn import_from(archive: &Path, history: &Path) -> Result<ImportSummary> {
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(file));
+    let mut summary = ImportSummary::default();
+
+    for entry in tarball.entries().context("Failed to read sync archive")? {
+        let mut entry = entry.context("Failed to read sync archive")?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        match name.as_str() {
+            STATE_ENTRY => {
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                let incoming: UploadState =
+                    toml::from_str(&content).context("Failed to parse archived upload state")?;
+                let mut state = UploadState::load()?;
+                if state.project_salt.is_empty() && !incoming.project_salt.is_empty() {
+                    state.project_salt = incoming.project_salt;
+                    state.save()?;
+                }
+            }
+            SNAPSHOT_ENTRY => {
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+                let incoming = HotSnapshot::decode(&mut content)
+                    .context("Failed to parse archived status snapshot")?;
+                // Snapshots from a newer splitrail are skipped like unknown entries.
+                if let Some(incoming) = incoming
+                    && HotSnapshot::load()?
+                        .is_none_or(|local| incoming.updated_at > local.updated_at)
+                {
+                    incoming.save()?;
+                    summary.snapshot_replaced = true;
+                }
+            }
+            HISTORY_FILE_NAME => {
+                let copy = staging_path(history);
+                if let Some(parent) = copy.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                entry.unpack(&copy)?;
+                let added = import_store(history, &copy);
+                let _ = fs::remove_file(&copy);
+                summary.history_added = added?;
+            }
+            // Entries from newer versions are skipped so old builds can still import.
+            _ => {}
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::set_test_state_path;
+    use crate::types::CompactDate;
+    use tempfile::tempdir;
+
+    fn snapshot(updated_at: i64, cost_cents: u64) -> HotSnapshot {
+        HotSnapshot {
+            updated_at,
+            date: CompactDate::from_str("2025-06-01").unwrap(),
+            cost_cents,
+            tokens: 0,
+            messages: 0,
+            conversations: 0,
+        }
+    }
+
+    #[test]
+    fn import_merges_without_touching_the_upload_watermark() {
+        let laptop = tempdir().unwrap();
+        set_test_state_path(laptop.path().join("state.toml"));
+        UploadState {
+            last_date_uploaded: 2_000,
+            claude_subagent_backfill_completed: true,
+            project_salt: "laptop salt".to_string(),
+            watermark_lost: false,
+        }
+        .save()
+        .unwrap();
+        snapshot(100, 500).save().unwrap();
+        let archive = laptop.path().join("splitrail-sync.tar.gz");
+        let entries = export_to(&archive, &laptop.path().join(HISTORY_FILE_NAME)).unwrap();
+        assert_eq!(entries, vec![STATE_ENTRY, SNAPSHOT_ENTRY]);
+
+        let desktop = tempdir().unwrap();
+        set_test_state_path(desktop.path().join("state.toml"));
+        UploadState {
+            last_date_uploaded: 1_000,
+            claude_subagent_backfill_completed: false,
+            ..UploadState::default()
+        }
+        .save()
+        .unwrap();
+        snapshot(50, 100).save().unwrap();
+        let history = desktop.path().join(HISTORY_FILE_NAME);
+
+        let summary = import_from(&archive, &history).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                snapshot_replaced: true,
+                history_added: 0,
+            }
+        );
+        let state = UploadState::load().unwrap();
+        // Messages made here after 1_000 still have to be uploaded.
+        assert_eq!(state.last_date_uploaded, 1_000);
+        assert!(!state.claude_subagent_backfill_completed);
+        assert_eq!(state.project_salt, "laptop salt");
+        assert_eq!(HotSnapshot::load().unwrap().unwrap().cost_cents, 500);
+
+        // Re-importing the same archive changes nothing.
+        assert_eq!(
+            import_from(&archive, &history).unwrap(),
+            ImportSummary::default()
+        );
+    }
+}
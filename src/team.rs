@@ -0,0 +1,226 @@
+//! Combined usage reports for `splitrail merge`.
+//!
+//! Each teammate runs `splitrail stats > alice.json` and hands over the file;
+//! the file name (minus extension) becomes their name in the report. Only the
+//! per-day aggregates are read, so the exports can be shared without
+//! `--include-messages`.
+
+use crate::types::{DateRange, MultiAnalyzerStats};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub cost: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cached_tokens: u64,
+    pub messages: u64,
+    pub conversations: u64,
+}
+
+impl Totals {
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.reasoning_tokens + self.cached_tokens
+    }
+
+    fn add(&mut self, other: &Totals) {
+        self.cost += other.cost;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.messages += other.messages;
+        self.conversations += other.conversations;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberReport {
+    pub user: String,
+    pub totals: Totals,
+    /// Tool name to that user's totals with it.
+    pub tools: BTreeMap<String, Totals>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolReport {
+    pub tool: String,
+    pub totals: Totals,
+    pub users: usize,
+}
+
+/// Members and tools, each sorted by cost, most expensive first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamReport {
+    pub range: String,
+    pub members: Vec<MemberReport>,
+    pub tools: Vec<ToolReport>,
+    pub totals: Totals,
+}
+
+/// Read one teammate's export, named after the file.
+pub fn load_export(path: &Path) -> Result<(String, MultiAnalyzerStats)> {
+    let user = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .filter(|stem| !stem.is_empty())
+        .with_context(|| format!("Can't name a teammate after {}", path.display()))?;
+    let mut content =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let stats = simd_json::from_slice(&mut content)
+        .with_context(|| format!("{} isn't a `splitrail stats` JSON export", path.display()))?;
+    Ok((user, stats))
+}
+
+/// Combine exports into per-user and per-tool totals. Exports sharing a name
+/// are added together.
+pub fn merge(exports: &[(String, MultiAnalyzerStats)], range: DateRange) -> TeamReport {
+    let mut members: BTreeMap<&str, BTreeMap<String, Totals>> = BTreeMap::new();
+    for (user, stats) in exports {
+        let tools = members.entry(user).or_default();
+        for analyzer in &stats.analyzer_stats {
+            let totals = tools.entry(analyzer.analyzer_name.clone()).or_default();
+            for day in analyzer
+                .daily_stats
+                .values()
+                .filter(|day| range.contains(day.date))
+            {
+                totals.add(&Totals {
+                    cost: day.stats.cost(),
+                    input_tokens: day.stats.input_tokens,
+                    output_tokens: day.stats.output_tokens,
+                    reasoning_tokens: day.stats.reasoning_tokens,
+                    cached_tokens: day.stats.cached_tokens,
+                    messages: day.ai_messages as u64,
+                    conversations: day.conversations as u64,
+                });
+            }
+        }
+        tools.retain(|_, totals| totals.messages > 0 || totals.cost > 0.0);
+    }
+
+    let mut tools: BTreeMap<&str, (Totals, BTreeSet<&str>)> = BTreeMap::new();
+    let mut totals = Totals::default();
+    let mut member_reports: Vec<MemberReport> = members
+        .iter()
+        .map(|(user, by_tool)| {
+            let mut member_totals = Totals::default();
+            for (tool, tool_totals) in by_tool {
+                member_totals.add(tool_totals);
+                let (combined, users) = tools.entry(tool).or_default();
+                combined.add(tool_totals);
+                users.insert(user);
+            }
+            totals.add(&member_totals);
+            MemberReport {
+                user: user.to_string(),
+                totals: member_totals,
+                tools: by_tool.clone(),
+            }
+        })
+        .collect();
+    member_reports.sort_by(|a, b| b.totals.cost.total_cmp(&a.totals.cost));
+
+    let mut tool_reports: Vec<ToolReport> = tools
+        .into_iter()
+        .map(|(tool, (totals, users))| ToolReport {
+            tool: tool.to_string(),
+            totals,
+            users: users.len(),
+        })
+        .collect();
+    tool_reports.sort_by(|a, b| b.totals.cost.total_cmp(&a.totals.cost));
+
+    TeamReport {
+        range: if range.is_unbounded() {
+            "all time".to_string()
+        } else {
+            range.to_string()
+        },
+        members: member_reports,
+        tools: tool_reports,
+        totals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgenticCodingToolStats, CompactDate, DailyStats, TuiStats};
+
+    fn export(tools: &[(&str, &str, u32)]) -> MultiAnalyzerStats {
+        let mut by_tool: BTreeMap<&str, AgenticCodingToolStats> = BTreeMap::new();
+        for (tool, date, cost_cents) in tools {
+            let stats = by_tool
+                .entry(tool)
+                .or_insert_with(|| AgenticCodingToolStats {
+                    daily_stats: BTreeMap::new(),
+                    num_conversations: 0,
+                    messages: Vec::new(),
+                    analyzer_name: tool.to_string(),
+                });
+            stats.daily_stats.insert(
+                date.to_string(),
+                DailyStats {
+                    date: CompactDate::from_str(date).unwrap(),
+                    ai_messages: 2,
+                    conversations: 1,
+                    stats: TuiStats {
+                        cost_cents: *cost_cents,
+                        output_tokens: 100,
+                        ..TuiStats::default()
+                    },
+                    ..DailyStats::default()
+                },
+            );
+        }
+        MultiAnalyzerStats {
+            analyzer_stats: by_tool.into_values().collect(),
+        }
+    }
+
+    #[test]
+    fn totals_per_user_and_per_tool() {
+        let alice = export(&[
+            ("Claude Code", "2025-06-01", 1000),
+            ("Claude Code", "2025-06-02", 500),
+            ("Codex CLI", "2025-06-02", 200),
+        ]);
+        let bob = export(&[("Claude Code", "2025-06-02", 3000)]);
+        // Exports go through JSON, so make sure the stats output parses back.
+        let bob: MultiAnalyzerStats =
+            simd_json::from_slice(&mut simd_json::to_vec(&bob).unwrap()).unwrap();
+
+        let report = merge(
+            &[("alice".to_string(), alice), ("bob".to_string(), bob)],
+            DateRange {
+                since: CompactDate::from_str("2025-06-02"),
+                until: None,
+            },
+        );
+
+        let members: Vec<_> = report
+            .members
+            .iter()
+            .map(|member| (member.user.as_str(), member.totals.cost))
+            .collect();
+        assert_eq!(members, [("bob", 30.0), ("alice", 7.0)]);
+        assert_eq!(report.members[1].tools["Codex CLI"].messages, 2);
+
+        let claude = &report.tools[0];
+        assert_eq!(
+            (claude.tool.as_str(), claude.users, claude.totals.cost),
+            ("Claude Code", 2, 35.0)
+        );
+        assert_eq!(report.totals.cost, 37.0);
+        assert_eq!(report.totals.tokens(), 300);
+    }
+}
@@ -1,42 +1,71 @@
+pub mod aggregation;
+pub mod columns;
+pub mod keymap;
 pub mod logic;
 #[cfg(test)]
 mod tests;
+pub mod theme;
 
-use crate::config::TuiConfig;
-use crate::models::is_model_estimated;
+use crate::analyzer::AnalyzerRegistry;
+use crate::budget::{BudgetLevel, BudgetStatus};
+use crate::config::{
+    BudgetConfig, FormatOverrides, LimitsConfig, NotificationsConfig, SubscriptionConfig,
+    ThemeConfig, TuiConfig,
+};
+use crate::diagnostics::ParseIssue;
+use crate::forecast::Forecast;
+use crate::limits::UsageWindow;
+use crate::models::{Provider, is_model_estimated};
+use crate::notify::SpendAlerts;
+use crate::premium::{CopilotPlan, PremiumUsage};
+use crate::stats::cache::CacheEfficiency;
+use crate::stats::compare::{Comparison, Metric, Periods};
+use crate::stats::hourly::HourStats;
+use crate::stats::streaks::Achievements;
+use crate::stats::throughput::Throughput;
+use crate::subscription::{CostBasis, CostMode};
 use crate::types::{
-    AnalyzerStatsView, CompactDate, DailyStats, MultiAnalyzerStatsView, SharedAnalyzerView,
+    AnalyzerStatsView, Application, CompactDate, ConversationMessage, DailyStats, DateRange,
+    MessageRole, ModelStats, MultiAnalyzerStatsView, PromptCacheStats, SharedAnalyzerView,
     resolve_model,
 };
+use crate::upload::UploadProgress;
 use crate::utils::{
     NumberFormatOptions, format_date_for_display, format_number, format_number_fit,
 };
-use crate::watcher::{FileWatcher, RealtimeStatsManager, WatcherEvent};
+use crate::watcher::{FileWatcher, LiveSession, RealtimeStatsManager, WatcherEvent, WatcherHealth};
+use aggregation::{
+    DailyTrend, HeatGrid, Histogram, PeriodBests, SessionLengths, ToolCounts, ToolUsage,
+    get_aggregate_stats,
+};
 use anyhow::Result;
 use chrono::{Datelike, Local, NaiveDate};
-use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use columns::{Column, ColumnLayout};
+use crossterm::event::{self, Event, KeyCode};
 use crossterm::style::{Print, ResetColor, SetForegroundColor};
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
 use crossterm::{ExecutableCommand, execute};
+use keymap::{Action, Keymap};
 use logic::{
-    SessionAggregate, aggregate_daily_stats_by_month, aggregate_daily_stats_by_week,
-    aggregate_daily_stats_by_year, date_matches_buffer, filtered_aggregate_keys, has_data_shared,
-    is_empty_period,
+    SessionAggregate, aggregate_model_stats, aggregate_provider_stats, date_matches_buffer,
+    filtered_aggregate_keys, follow_selected_tab, has_data_shared, is_empty_period,
+    remap_tab_state, tab_names,
 };
 use parking_lot::Mutex;
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::widgets::{Block, Cell, Clear, Paragraph, Row, Sparkline, Table, TableState, Tabs};
 use ratatui::{Frame, Terminal};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{Write, stdout};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use theme::Theme;
 use tokio::sync::{mpsc, watch};
 
 #[derive(Debug, Clone)]
@@ -45,6 +74,8 @@ pub enum UploadStatus {
     Uploading {
         current: usize,
         total: usize,
+        chunk: usize,
+        total_chunks: usize,
         dots: usize,
     },
     Uploaded,
@@ -152,38 +183,18 @@ impl PeriodFilter {
 enum StatsViewMode {
     Aggregate,
     Session,
-}
-
-enum AggregateStatsData<'a> {
-    Borrowed(&'a BTreeMap<String, DailyStats>),
-    Owned(BTreeMap<String, DailyStats>),
-}
-
-impl AggregateStatsData<'_> {
-    fn as_map(&self) -> &BTreeMap<String, DailyStats> {
-        match self {
-            Self::Borrowed(stats) => stats,
-            Self::Owned(stats) => stats,
-        }
-    }
-}
-
-fn get_aggregate_stats<'a>(
-    view: &'a AnalyzerStatsView,
-    aggregate_view_mode: AggregateViewMode,
-) -> AggregateStatsData<'a> {
-    match aggregate_view_mode {
-        AggregateViewMode::Daily => AggregateStatsData::Borrowed(&view.daily_stats),
-        AggregateViewMode::Weekly => {
-            AggregateStatsData::Owned(aggregate_daily_stats_by_week(&view.daily_stats))
-        }
-        AggregateViewMode::Monthly => {
-            AggregateStatsData::Owned(aggregate_daily_stats_by_month(&view.daily_stats))
-        }
-        AggregateViewMode::Yearly => {
-            AggregateStatsData::Owned(aggregate_daily_stats_by_year(&view.daily_stats))
-        }
-    }
+    /// Per-model totals across all days.
+    Models,
+    /// Histograms of messages and tokens per session.
+    Lengths,
+    /// Messages by day of week and hour of day.
+    Heat,
+    /// Throughput: tokens per busy hour, response size, busiest hours.
+    Insights,
+    /// Streaks, token milestones and the biggest day.
+    Achievements,
+    /// The last day/week/month/year against the one before it.
+    Compare,
 }
 
 fn aggregate_total_rows(
@@ -228,15 +239,431 @@ fn aggregate_key_at(
         .nth(index)
 }
 
-fn filtered_session_count(view: &AnalyzerStatsView, period_filter: Option<PeriodFilter>) -> usize {
-    period_filter
-        .map(|filter| {
-            view.session_aggregates
-                .iter()
-                .filter(|session| filter.matches_compact_date(session.date))
-                .count()
+fn filtered_session_count(
+    view: &AnalyzerStatsView,
+    period_filter: Option<PeriodFilter>,
+    search: &str,
+) -> usize {
+    if period_filter.is_none() && search.is_empty() {
+        return view.session_aggregates.len();
+    }
+    visible_sessions(&view.session_aggregates, period_filter, search, false).len()
+}
+
+/// Whether the session's name or ID, tool, project directory, or one of its
+/// models contains `search`, ignoring case. An empty search matches everything.
+fn session_matches_search(session: &SessionAggregate, search: &str) -> bool {
+    if search.is_empty() {
+        return true;
+    }
+    let search = search.to_lowercase();
+    let contains = |text: &str| text.to_lowercase().contains(&search);
+    session.session_name.as_deref().is_some_and(contains)
+        || contains(&session.session_id)
+        || contains(&session.analyzer_name)
+        || session.project.as_deref().is_some_and(contains)
+        || session
+            .models
+            .iter()
+            .any(|&(model, _)| contains(resolve_model(model)))
+}
+
+/// Sessions in the order the session table lists them.
+fn visible_sessions<'a>(
+    sessions: &'a [SessionAggregate],
+    period_filter: Option<PeriodFilter>,
+    search: &str,
+    sort_reversed: bool,
+) -> Vec<&'a SessionAggregate> {
+    let mut visible: Vec<_> = sessions
+        .iter()
+        .filter(|session| {
+            period_filter.is_none_or(|filter| filter.matches_compact_date(session.date))
+        })
+        .filter(|session| session_matches_search(session, search))
+        .collect();
+    if sort_reversed {
+        visible.reverse();
+    }
+    visible
+}
+
+/// Work a pane hands to a background thread so the draw loop stays
+/// responsive, picked up with [`Self::poll`] once it's done.
+struct BackgroundLoad<T> {
+    result: Option<Result<T, String>>,
+    receiver: std::sync::mpsc::Receiver<Result<T, String>>,
+    /// What's loading, for the error shown if the thread dies.
+    what: &'static str,
+}
+
+impl<T: Send + 'static> BackgroundLoad<T> {
+    fn spawn(
+        what: &'static str,
+        load: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+    ) -> Self {
+        let (tx, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(load().map_err(|e| format!("{e:#}")));
+        });
+        Self {
+            result: None,
+            receiver,
+            what,
+        }
+    }
+
+    /// Pick up the background load result; returns true when it arrived.
+    fn poll(&mut self) -> bool {
+        if self.result.is_some() {
+            return false;
+        }
+        match self.receiver.try_recv() {
+            Ok(result) => self.result = Some(result),
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.result = Some(Err(format!("{} loader exited unexpectedly", self.what)))
+            }
+        }
+        true
+    }
+
+    /// `None` while still loading.
+    fn result(&self) -> Option<&Result<T, String>> {
+        self.result.as_ref()
+    }
+}
+
+/// The analyzers a pane covers: every available one for the "All Tools" tab.
+fn pane_analyzers(registry: &AnalyzerRegistry, name: &str) -> Vec<String> {
+    if name == crate::budget::ALL_TOOLS {
+        registry
+            .available_analyzers()
+            .iter()
+            .map(|a| a.display_name().to_string())
+            .collect()
+    } else {
+        vec![name.to_string()]
+    }
+}
+
+/// Per-message pane opened with Enter in the session view. Views only hold
+/// session aggregates, so the session's files are re-parsed on a background
+/// thread.
+struct SessionDetail {
+    title: String,
+    messages: BackgroundLoad<Vec<ConversationMessage>>,
+    table_state: TableState,
+}
+
+impl SessionDetail {
+    fn open(session: &SessionAggregate, registry: Arc<AnalyzerRegistry>) -> Self {
+        let analyzer_name = session.analyzer_name.to_string();
+        let session_id = session.session_id.clone();
+        Self {
+            title: format!(
+                "{} • {}",
+                session.analyzer_name,
+                session
+                    .session_name
+                    .as_deref()
+                    .unwrap_or(&session.session_id)
+            ),
+            messages: BackgroundLoad::spawn("Session", move || {
+                registry.load_session_messages(&analyzer_name, &session_id)
+            }),
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn poll(&mut self) -> bool {
+        self.messages.poll()
+    }
+
+    fn len(&self) -> usize {
+        match self.messages.result() {
+            Some(Ok(messages)) => messages.len(),
+            _ => 0,
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.len().saturating_sub(1);
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state
+            .select(Some(selected.saturating_add_signed(delta).min(last)));
+    }
+}
+
+/// Column picker opened with 'C': edits a copy of the layout, which replaces
+/// the live one (and is saved to the config) on Enter.
+struct ColumnChooser {
+    layout: ColumnLayout,
+    selected: usize,
+}
+
+impl ColumnChooser {
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.layout.entries().len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// Save the chosen columns as `tui.columns`, replacing any `hidden_columns`.
+fn save_columns(layout: &ColumnLayout) -> anyhow::Result<()> {
+    let mut config = crate::config::Config::load()?.unwrap_or_default();
+    config.tui.columns = layout.to_config();
+    config.tui.hidden_columns.clear();
+    config.save(true)
+}
+
+/// Hour-by-hour pane opened with Enter on a day in the daily view. Tokens
+/// and cost per hour need timestamps the rollups don't keep, so the files
+/// written since that day are re-parsed on a background thread. Costs are
+/// scaled by each tool's `cost_factors` entry so they add up to the day's
+/// actual cost when the `$` toggle asks for it.
+struct DayDetail {
+    title: String,
+    day: CompactDate,
+    hours: BackgroundLoad<[HourStats; 24]>,
+    table_state: TableState,
+}
+
+impl DayDetail {
+    fn open(
+        analyzer_name: &str,
+        day: CompactDate,
+        registry: Arc<AnalyzerRegistry>,
+        cost_factors: HashMap<String, f64>,
+    ) -> Self {
+        let name = analyzer_name.to_string();
+        let hours = BackgroundLoad::spawn("Hourly", move || {
+            let date =
+                compact_date_to_naive(day).ok_or_else(|| anyhow::anyhow!("Invalid date {day}"))?;
+            // A file last written before the day started can't hold its messages.
+            let since = date
+                .and_hms_opt(0, 0, 0)
+                .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+                .map_or(std::time::UNIX_EPOCH, std::time::SystemTime::from);
+            let mut hours = [HourStats::default(); 24];
+            for name in pane_analyzers(&registry, &name) {
+                let messages = registry.load_recent_messages(&name, since)?;
+                let day_hours = crate::stats::hourly::day_by_hour(&messages, date);
+                let factor = cost_factors.get(&name).copied().unwrap_or(1.0);
+                for (total, hour) in hours.iter_mut().zip(day_hours) {
+                    total.user_messages += hour.user_messages;
+                    total.ai_messages += hour.ai_messages;
+                    total.tokens += hour.tokens;
+                    total.cost += hour.cost * factor;
+                }
+            }
+            Ok(hours)
+        });
+
+        Self {
+            title: format!("{analyzer_name} • {day} by hour"),
+            day,
+            hours,
+            table_state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn poll(&mut self) -> bool {
+        if !self.hours.poll() {
+            return false;
+        }
+        // Start on the first hour with any usage.
+        if let Some(Ok(hours)) = self.hours.result() {
+            let first = hours.iter().position(|hour| !hour.is_empty());
+            self.table_state.select(Some(first.unwrap_or(0)));
+        }
+        true
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        self.table_state
+            .select(Some(selected.saturating_add_signed(delta).min(23)));
+    }
+}
+
+/// Shortest gap between recomputing subscription usage windows from recent
+/// logs.
+const USAGE_WINDOW_REFRESH: Duration = Duration::from_secs(120);
+
+/// View versions of the tools usage windows are read from; a change means
+/// the contribution cache took in new messages for them.
+fn usage_window_versions(stats: &MultiAnalyzerStatsView) -> Vec<Option<u64>> {
+    let tools = [
+        Application::ClaudeCode.display_name(),
+        Application::CodexCli.display_name(),
+    ];
+    stats
+        .analyzer_stats
+        .iter()
+        .filter(|view| tools.contains(&&*view.read().analyzer_name))
+        .map(|view| stats.version_of(view))
+        .collect()
+}
+
+/// How long after its last write a session stops being shown as live.
+const LIVE_SESSION_IDLE: Duration = Duration::from_secs(120);
+
+/// Running totals for the session an agent is writing to, shown in a strip
+/// above the tables while it stays active (toggle with `L`).
+struct LivePanel {
+    analyzer_name: String,
+    session: SessionAggregate,
+    /// When the panel started following this session.
+    since: Option<chrono::DateTime<Local>>,
+}
+
+impl LivePanel {
+    fn find(
+        live: &LiveSession,
+        views: &[SharedAnalyzerView],
+        since: Option<chrono::DateTime<Local>>,
+    ) -> Option<Self> {
+        let view = views
+            .iter()
+            .find(|view| *view.read().analyzer_name == *live.analyzer_name)?
+            .read();
+        let session = view
+            .session_aggregates
+            .iter()
+            .find(|session| session.session_id == live.session_id)?
+            .clone();
+        Some(Self {
+            analyzer_name: live.analyzer_name.clone(),
+            session,
+            since,
         })
-        .unwrap_or_else(|| view.session_aggregates.len())
+    }
+}
+
+/// Tool category and file composition pane toggled with `T` beside the
+/// aggregate and session tables. Views only carry total tool calls, so each
+/// tool's messages are re-parsed on a background thread and reduced to
+/// per-day and per-session counts, kept in a [`ToolUsageCache`] until the
+/// tool's view changes.
+struct ToolPanel {
+    analyzer_name: String,
+    /// View version of each tool the pane covers when `usage` was loaded.
+    versions: Vec<(String, Option<u64>)>,
+    usage: BackgroundLoad<ToolUsage>,
+    /// The last finished load, shown while a refresh is in flight.
+    previous: Option<Result<ToolUsage, String>>,
+    loaded_at: std::time::Instant,
+}
+
+/// Each tool's usage with the view version it was parsed at, shared by the
+/// pane's loads so tab switches and reopening only parse tools that changed.
+type ToolUsageCache = Arc<Mutex<HashMap<String, (u64, Arc<ToolUsage>)>>>;
+
+/// Live updates re-parse a changed tool at most this often while the pane
+/// is open, since a busy agent changes its tool's view every few seconds.
+const TOOL_PANEL_REFRESH: Duration = Duration::from_secs(30);
+
+impl ToolPanel {
+    fn open(
+        analyzer_name: &str,
+        registry: Arc<AnalyzerRegistry>,
+        stats: &MultiAnalyzerStatsView,
+        cache: &ToolUsageCache,
+    ) -> Self {
+        let versions = Self::versions(analyzer_name, &registry, stats);
+        let load = versions.clone();
+        let cache = Arc::clone(cache);
+        Self {
+            analyzer_name: analyzer_name.to_string(),
+            versions,
+            usage: BackgroundLoad::spawn("Tool usage", move || {
+                let mut usage = ToolUsage::default();
+                for (name, version) in load {
+                    let cached = version.and_then(|version| {
+                        cache
+                            .lock()
+                            .get(&name)
+                            .filter(|(at, _)| *at == version)
+                            .map(|(_, usage)| Arc::clone(usage))
+                    });
+                    let tool = match cached {
+                        Some(tool) => tool,
+                        None => {
+                            let mut tool = ToolUsage::default();
+                            tool.add_messages(&registry.load_analyzer_messages(&name)?);
+                            let tool = Arc::new(tool);
+                            if let Some(version) = version {
+                                cache.lock().insert(name, (version, Arc::clone(&tool)));
+                            }
+                            tool
+                        }
+                    };
+                    usage.merge(&tool);
+                }
+                Ok(usage)
+            }),
+            previous: None,
+            loaded_at: std::time::Instant::now(),
+        }
+    }
+
+    fn versions(
+        analyzer_name: &str,
+        registry: &AnalyzerRegistry,
+        stats: &MultiAnalyzerStatsView,
+    ) -> Vec<(String, Option<u64>)> {
+        pane_analyzers(registry, analyzer_name)
+            .into_iter()
+            .map(|name| {
+                let version = stats
+                    .analyzer_stats
+                    .iter()
+                    .find(|view| *view.read().analyzer_name == *name)
+                    .and_then(|view| stats.version_of(view));
+                (name, version)
+            })
+            .collect()
+    }
+
+    /// Reload once a covered tool's view has changed, keeping the current
+    /// counts on screen meanwhile. Returns whether a reload started.
+    fn refresh(
+        &mut self,
+        registry: &Arc<AnalyzerRegistry>,
+        stats: &MultiAnalyzerStatsView,
+        cache: &ToolUsageCache,
+    ) -> bool {
+        if self.usage.result().is_none()
+            || self.loaded_at.elapsed() < TOOL_PANEL_REFRESH
+            || Self::versions(&self.analyzer_name, registry, stats) == self.versions
+        {
+            return false;
+        }
+        let previous = self.usage.result.take();
+        *self = Self::open(&self.analyzer_name, Arc::clone(registry), stats, cache);
+        self.previous = previous;
+        true
+    }
+
+    /// `None` while the first load is still running.
+    fn result(&self) -> Option<&Result<ToolUsage, String>> {
+        self.usage.result().or(self.previous.as_ref())
+    }
+}
+
+/// Number of data rows (excluding separator and totals) in the session or
+/// models list for the given view mode.
+fn list_view_len(
+    view: &AnalyzerStatsView,
+    mode: StatsViewMode,
+    period_filter: Option<PeriodFilter>,
+    search: &str,
+) -> usize {
+    match mode {
+        StatsViewMode::Models => aggregate_model_stats(view.daily_stats.values()).len(),
+        _ => filtered_session_count(view, period_filter, search),
+    }
 }
 
 fn clamp_table_selection(table_state: &mut TableState, total_rows: usize) {
@@ -343,31 +770,181 @@ struct UiState<'a> {
     session_period_filters: &'a mut [Option<PeriodFilter>],
     date_jump_active: bool,
     date_jump_buffer: &'a str,
+    /// Text typed into the date-range picker while it is open.
+    range_input: Option<&'a str>,
+    /// Session view search, and whether it is being typed.
+    session_search: &'a str,
+    session_search_active: bool,
+    date_range: DateRange,
+    budgets: &'a [BudgetStatus],
+    /// End-of-month projection across all tools.
+    forecast: Option<&'a Forecast>,
+    /// Copilot premium requests this month, when there were any.
+    premium: Option<&'a PremiumUsage>,
+    /// Current subscription usage windows.
+    usage_windows: &'a [UsageWindow],
+    session_detail: Option<&'a mut SessionDetail>,
+    day_detail: Option<&'a mut DayDetail>,
+    tool_panel: Option<&'a ToolPanel>,
+    /// Files with parse problems and the scroll offset, while the pane is open.
+    diagnostics: Option<(&'a [ParseIssue], usize)>,
+    parse_issue_count: usize,
+    /// Days covered by the trend charts; `None` while they are hidden.
+    trend_days: Option<usize>,
+    cost_mode: CostMode,
+    live_session: Option<&'a LivePanel>,
+    /// `None` with `--no-watch`, when nothing on screen updates live.
+    watcher_health: Option<&'a WatcherHealth>,
+    /// Analyzers still loading at startup, shown as placeholder tabs.
+    loading: &'a [String],
     sort_reversed: bool,
     hide_empty_periods: bool,
     show_totals: bool,
     quit_pending: bool,
-    accent: Color,
-    hidden_cols: &'a std::collections::HashSet<String>,
+    theme: Theme,
+    keymap: &'a Keymap,
+    columns: &'a [Column],
+    column_chooser: Option<&'a ColumnChooser>,
     color_costs: bool,
     show_header: bool,
+    analyzer_styles: &'a AnalyzerStyles,
 }
 
 /// Build the tab data shown in the TUI, prepending a synthetic "All Tools"
 /// view ahead of the individual analyzer tabs.
+///
+/// When `date_range` is bounded every tab is replaced by a copy restricted to
+/// that window, so tables, totals, and the session view all agree. Likewise
+/// subscribed tools are re-priced when `cost_basis` asks for actual cost.
 pub(crate) fn build_display_stats(
     filtered_stats: &[SharedAnalyzerView],
+    date_range: DateRange,
+    cost_basis: &CostBasis,
 ) -> Vec<SharedAnalyzerView> {
-    if filtered_stats.is_empty() {
-        return Vec::new();
+    DisplayStatsCache::default().update(filtered_stats, |_| None, date_range, cost_basis)
+}
+
+/// Tab data kept between stats updates, so an update only re-prices,
+/// re-ranges and re-merges the tools whose view version changed.
+#[derive(Default)]
+pub(crate) struct DisplayStatsCache {
+    basis: Option<(DateRange, CostMode)>,
+    tabs: Vec<CachedTab>,
+    all_tools: Option<SharedAnalyzerView>,
+}
+
+struct CachedTab {
+    source: SharedAnalyzerView,
+    version: Option<u64>,
+    display: SharedAnalyzerView,
+}
+
+impl DisplayStatsCache {
+    /// Same result as [`build_display_stats`]. `version_of` gives each
+    /// view's version from the stats channel; `None` forces a recompute.
+    pub(crate) fn update(
+        &mut self,
+        filtered_stats: &[SharedAnalyzerView],
+        version_of: impl Fn(&SharedAnalyzerView) -> Option<u64>,
+        date_range: DateRange,
+        cost_basis: &CostBasis,
+    ) -> Vec<SharedAnalyzerView> {
+        if filtered_stats.is_empty() {
+            *self = Self::default();
+            return Vec::new();
+        }
+        let basis = (date_range, cost_basis.mode);
+        if self.basis != Some(basis) {
+            *self = Self {
+                basis: Some(basis),
+                ..Self::default()
+            };
+        }
+
+        // Names of tools whose tab was recomputed, added or dropped.
+        let mut changed: Vec<Arc<str>> = Vec::new();
+        let mut previous = std::mem::take(&mut self.tabs);
+        for source in filtered_stats {
+            let version = version_of(source);
+            let unchanged = previous.iter().position(|tab| {
+                Arc::ptr_eq(&tab.source, source) && version.is_some() && tab.version == version
+            });
+            let tab = match unchanged {
+                Some(index) => previous.swap_remove(index),
+                None => {
+                    let display = display_view(source, date_range, cost_basis);
+                    changed.push(Arc::clone(&display.read().analyzer_name));
+                    CachedTab {
+                        source: Arc::clone(source),
+                        version,
+                        display,
+                    }
+                }
+            };
+            self.tabs.push(tab);
+        }
+        changed.extend(
+            previous
+                .iter()
+                .map(|tab| Arc::clone(&tab.display.read().analyzer_name)),
+        );
+
+        let all_tools = match &self.all_tools {
+            Some(all_tools) if changed.is_empty() => Arc::clone(all_tools),
+            _ => {
+                let all_tools = self.all_tools.take().unwrap_or_else(|| {
+                    Arc::new(parking_lot::RwLock::new(AnalyzerStatsView {
+                        daily_stats: BTreeMap::new(),
+                        session_aggregates: Vec::new(),
+                        num_conversations: 0,
+                        analyzer_name: Arc::from("All Tools"),
+                    }))
+                });
+                merge_all_tools(&mut all_tools.write(), &self.tabs, &changed);
+                self.all_tools = Some(Arc::clone(&all_tools));
+                all_tools
+            }
+        };
+
+        let mut display_stats = Vec::with_capacity(self.tabs.len() + 1);
+        display_stats.push(all_tools);
+        display_stats.extend(self.tabs.iter().map(|tab| Arc::clone(&tab.display)));
+        display_stats
+    }
+}
+
+/// One tool's tab: re-priced for `cost_basis`, then restricted to `date_range`.
+fn display_view(
+    stats: &SharedAnalyzerView,
+    date_range: DateRange,
+    cost_basis: &CostBasis,
+) -> SharedAnalyzerView {
+    // Subscription fees are spread over whole months, so rescale costs
+    // before the date range trims any of those months.
+    let priced = if cost_basis.mode == CostMode::Api {
+        None
+    } else {
+        cost_basis.apply_to_view(&stats.read())
+    };
+    match (priced, date_range.is_unbounded()) {
+        (None, true) => Arc::clone(stats),
+        (Some(view), true) => Arc::new(parking_lot::RwLock::new(view)),
+        (None, false) => Arc::new(parking_lot::RwLock::new(stats.read().within(date_range))),
+        (Some(view), false) => Arc::new(parking_lot::RwLock::new(view.within(date_range))),
     }
+}
 
+/// Recombine the "All Tools" daily totals from every tab, and swap in the
+/// sessions of the `changed` tools while keeping everyone else's.
+fn merge_all_tools(all_tools: &mut AnalyzerStatsView, tabs: &[CachedTab], changed: &[Arc<str>]) {
     let mut combined_daily_stats = BTreeMap::new();
-    let mut combined_sessions = Vec::new();
     let mut combined_conversations = 0u64;
+    all_tools
+        .session_aggregates
+        .retain(|session| !changed.contains(&session.analyzer_name));
 
-    for stats in filtered_stats {
-        let view = stats.read();
+    for tab in tabs {
+        let view = tab.display.read();
         combined_conversations += view.num_conversations;
         let app_name = view.analyzer_name.to_string();
 
@@ -383,27 +960,27 @@ pub(crate) fn build_display_stats(
             *entry.apps.entry(app_name.clone()).or_insert(0) += 1;
         }
 
-        combined_sessions.extend(view.session_aggregates.iter().cloned().map(|mut session| {
-            let base_name = session
-                .session_name
-                .clone()
-                .unwrap_or_else(|| session.session_id.clone());
-            session.session_name = Some(format!("[{}] {}", session.analyzer_name, base_name));
-            session
-        }));
+        if !changed.contains(&view.analyzer_name) {
+            continue;
+        }
+        all_tools
+            .session_aggregates
+            .extend(view.session_aggregates.iter().cloned().map(|mut session| {
+                let base_name = session
+                    .session_name
+                    .clone()
+                    .unwrap_or_else(|| session.session_id.clone());
+                session.session_name = Some(base_name);
+                session
+            }));
     }
 
-    combined_sessions.sort_by_key(|session| session.first_timestamp);
-
-    let mut display_stats = Vec::with_capacity(filtered_stats.len() + 1);
-    display_stats.push(Arc::new(parking_lot::RwLock::new(AnalyzerStatsView {
-        daily_stats: combined_daily_stats,
-        session_aggregates: combined_sessions,
-        num_conversations: combined_conversations,
-        analyzer_name: Arc::from("All Tools"),
-    })));
-    display_stats.extend(filtered_stats.iter().cloned());
-    display_stats
+    // Stable, so the kept sessions and the new ones merge as two sorted runs.
+    all_tools
+        .session_aggregates
+        .sort_by(SessionAggregate::chronological);
+    all_tools.daily_stats = combined_daily_stats;
+    all_tools.num_conversations = combined_conversations;
 }
 
 /// Column width for all token count columns (Cached, Input, Output, Reasoning).
@@ -417,6 +994,9 @@ pub(crate) fn build_display_stats(
 /// by `format_number_fit` which falls back to human-readable format.
 const TOKEN_COL_WIDTH: u16 = 12;
 
+/// Column width for the tool name in the session table.
+const SESSION_TOOL_COL_WIDTH: u16 = 18;
+
 /// Column width for conversation and tool-call counts.
 ///
 /// Human-readable totals can require seven characters (for example, "204.92k").
@@ -424,16 +1004,78 @@ const TOKEN_COL_WIDTH: u16 = 12;
 /// being clipped on the left.
 const COUNT_COL_WIDTH: u16 = 7;
 
+/// Column width for costs ("$12345.67").
+const COST_COL_WIDTH: u16 = 10;
+
+/// Column width for Copilot premium requests, as wide as the header.
+const PREMIUM_COL_WIDTH: u16 = 12;
+
+/// Column width for active time ("1234h 05m").
+const ACTIVE_COL_WIDTH: u16 = 9;
+
+/// Column width for prompt-cache hit ratios ("100%").
+const HIT_COL_WIDTH: u16 = 5;
+
+/// A prompt-cache hit ratio as a whole percentage, or "-" without any prompt.
+fn format_hit_ratio(efficiency: &CacheEfficiency) -> String {
+    match efficiency.hit_ratio {
+        Some(ratio) => format!("{:.0}%", ratio * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+/// The thread drawing the TUI while it owns the terminal, `None` otherwise.
+static TUI_THREAD: parking_lot::Mutex<Option<std::thread::ThreadId>> =
+    parking_lot::Mutex::new(None);
+
+/// Leave raw mode and the alternate screen if the TUI still holds them. Safe
+/// to call more than once, e.g. from a signal handler racing a normal exit.
+pub fn restore_terminal() {
+    if TUI_THREAD.lock().take().is_some() {
+        let _ = disable_raw_mode();
+        let _ = stdout().execute(LeaveAlternateScreen);
+        let _ = stdout().execute(crossterm::cursor::Show);
+    }
+}
+
+/// Restore the terminal before a panic on the TUI thread prints its message,
+/// so the message is readable and the shell usable afterwards. Panics in
+/// background tasks are left alone; tokio contains them and the TUI keeps
+/// running. `on_panic` runs after the restore, e.g. to flush state.
+pub fn install_panic_hook(on_panic: impl Fn() + Send + Sync + 'static) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let on_tui_thread = *TUI_THREAD.lock() == Some(std::thread::current().id());
+        if on_tui_thread {
+            restore_terminal();
+            on_panic();
+        }
+        default_hook(info);
+    }));
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn run_tui(
     stats_receiver: watch::Receiver<MultiAnalyzerStatsView>,
     format_options: &NumberFormatOptions,
+    format_overrides: FormatOverrides,
     tui_config: TuiConfig,
+    theme_config: ThemeConfig,
+    key_config: HashMap<String, String>,
     upload_status: Arc<Mutex<UploadStatus>>,
     update_status: Arc<Mutex<crate::version_check::UpdateStatus>>,
-    file_watcher: FileWatcher,
+    file_watcher: Option<FileWatcher>,
     mut stats_manager: RealtimeStatsManager,
+    date_range: DateRange,
+    budget_config: BudgetConfig,
+    forecast_window_days: u32,
+    copilot_plan: CopilotPlan,
+    limits_config: LimitsConfig,
+    notifications: NotificationsConfig,
+    subscriptions: HashMap<String, SubscriptionConfig>,
 ) -> Result<()> {
     enable_raw_mode()?;
+    *TUI_THREAD.lock() = Some(std::thread::current().id());
     stdout().execute(EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
@@ -444,6 +1086,8 @@ pub fn run_tui(
     let mut stats_view_mode = StatsViewMode::Aggregate;
 
     let (watcher_tx, mut watcher_rx) = mpsc::unbounded_channel::<WatcherEvent>();
+    let live_receiver = stats_manager.get_live_session_receiver();
+    let registry = stats_manager.registry();
 
     tokio::spawn(async move {
         while let Some(event) = watcher_rx.recv().await {
@@ -460,7 +1104,10 @@ pub fn run_tui(
             &mut terminal,
             stats_receiver,
             format_options,
+            format_overrides,
             tui_config,
+            theme_config,
+            key_config,
             &mut selected_tab,
             &mut scroll_offset,
             &mut aggregate_view_mode,
@@ -469,9 +1116,19 @@ pub fn run_tui(
             update_status,
             file_watcher,
             watcher_tx,
+            live_receiver,
+            registry,
+            date_range,
+            budget_config,
+            forecast_window_days,
+            copilot_plan,
+            limits_config,
+            notifications,
+            subscriptions,
         ))
     });
 
+    *TUI_THREAD.lock() = None;
     disable_raw_mode()?;
     terminal.backend_mut().execute(LeaveAlternateScreen)?;
     result
@@ -482,40 +1139,64 @@ async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     mut stats_receiver: watch::Receiver<MultiAnalyzerStatsView>,
     format_options: &NumberFormatOptions,
+    format_overrides: FormatOverrides,
     tui_config: TuiConfig,
+    theme_config: ThemeConfig,
+    key_config: HashMap<String, String>,
     selected_tab: &mut usize,
     scroll_offset: &mut usize,
     aggregate_view_mode: &mut AggregateViewMode,
     stats_view_mode: &mut StatsViewMode,
     upload_status: Arc<Mutex<UploadStatus>>,
     update_status: Arc<Mutex<crate::version_check::UpdateStatus>>,
-    file_watcher: FileWatcher,
+    mut file_watcher: Option<FileWatcher>,
     watcher_tx: mpsc::UnboundedSender<WatcherEvent>,
+    mut live_receiver: watch::Receiver<Option<LiveSession>>,
+    registry: Arc<AnalyzerRegistry>,
+    mut date_range: DateRange,
+    mut budget_config: BudgetConfig,
+    mut forecast_window_days: u32,
+    mut copilot_plan: CopilotPlan,
+    mut limits_config: LimitsConfig,
+    notifications: NotificationsConfig,
+    subscriptions: HashMap<String, SubscriptionConfig>,
 ) -> Result<()> {
+    // Formatting and budgets follow edits to the config file.
+    let mut live_format_options = format_options.clone();
     let mut table_states: Vec<TableState> = Vec::new();
     let mut session_window_offsets: Vec<usize> = Vec::new();
     let mut session_period_filters: Vec<Option<PeriodFilter>> = Vec::new();
     let mut date_jump_active = false;
     let mut date_jump_buffer = String::new();
+    let mut range_input_active = false;
+    let mut range_input_buffer = String::new();
+    let mut session_search_active = false;
+    let mut session_search = String::new();
     let mut sort_reversed = tui_config.reverse_sort_default;
     let mut hide_empty_periods = tui_config.hide_empty_periods;
     let mut show_totals = true;
     let mut quit_pending = false;
     // Appearance settings (constant for the session).
-    let accent = parse_accent(&tui_config.accent_color);
+    let plain = crate::utils::plain_output();
+    let colorless = plain || crate::utils::no_color_env();
+    let mut theme = if colorless {
+        Theme::NO_COLOR
+    } else {
+        Theme::from_config(&theme_config, &tui_config.accent_color)
+    };
+    let mut keymap = Keymap::from_config(&key_config);
     let color_costs = tui_config.color_costs;
     let show_header = tui_config.show_header;
-    let hidden_cols: std::collections::HashSet<String> = tui_config
-        .hidden_columns
-        .iter()
-        .map(|s| match s.trim().to_lowercase().as_str() {
-            "inp" => "input".to_string(),
-            "outp" => "output".to_string(),
-            "reasoning" => "reason".to_string(),
-            "conversations" | "conv" => "convs".to_string(),
-            other => other.to_string(),
-        })
-        .collect();
+    let trend_days = tui_config.trend_days.max(1);
+    let mut show_trend = tui_config.show_trend;
+    let mut cost_basis = CostBasis {
+        mode: tui_config.cost_mode,
+        subscriptions,
+    };
+    let analyzer_styles = AnalyzerStyles::from_config(&tui_config);
+    let mut column_layout =
+        ColumnLayout::from_config(&tui_config.columns, &tui_config.hidden_columns);
+    let mut column_chooser: Option<ColumnChooser> = None;
     let mut current_stats = stats_receiver.borrow().clone();
 
     // Initialize table states for current stats
@@ -533,6 +1214,29 @@ async fn run_app(
         format!("{:?}", *status)
     };
     let mut dots_counter = 0; // Counter for dots animation (advance every 5 frames = 500ms)
+    // Force a budget and forecast refresh on the first iteration.
+    let mut last_periodic_refresh: Option<std::time::Instant> = None;
+    let mut budgets: Vec<BudgetStatus> = Vec::new();
+    let mut forecast: Option<Forecast> = None;
+    let mut premium: Option<PremiumUsage> = None;
+    // Usage windows re-read recent logs, so they load on a background thread,
+    // at most every `USAGE_WINDOW_REFRESH` and only once their tools' views
+    // changed or a window reset.
+    let mut usage_windows: Vec<UsageWindow> = Vec::new();
+    let mut usage_window_loader: Option<std::sync::mpsc::Receiver<Vec<UsageWindow>>> = None;
+    let mut usage_windows_loaded: Option<(std::time::Instant, Vec<Option<u64>>)> = None;
+    let mut session_detail: Option<SessionDetail> = None;
+    let mut day_detail: Option<DayDetail> = None;
+    let mut tool_panel: Option<ToolPanel> = None;
+    let tool_usage_cache = ToolUsageCache::default();
+    // Scroll offset of the parse problems pane toggled with `E`, while open.
+    let mut diagnostics_scroll: Option<usize> = None;
+    let mut live_session: Option<LiveSession> = None;
+    let mut live_started: Option<chrono::DateTime<Local>> = None;
+    let mut show_live = true;
+    let mut spend_alerts = notifications
+        .enabled
+        .then(|| SpendAlerts::new(&notifications, &format_options.currency_symbol));
 
     // Filter analyzer stats to only include those with data - calculate once and update when stats change
     // SharedAnalyzerView = Arc<RwLock<AnalyzerStatsView>> - clone is cheap (just Arc pointer)
@@ -542,22 +1246,41 @@ async fn run_app(
         .filter(|stats| has_data_shared(stats))
         .cloned()
         .collect();
-    let mut display_stats = build_display_stats(&filtered_stats);
+    let mut display_cache = DisplayStatsCache::default();
+    let mut display_stats = display_cache.update(
+        &filtered_stats,
+        |view| current_stats.version_of(view),
+        date_range,
+        &cost_basis,
+    );
 
     // Open on the configured default tab (matched by tool name; empty or
-    // "All Tools" keeps the combined first tab).
-    if !tui_config.default_tab.trim().is_empty()
-        && let Some(idx) = display_stats.iter().position(|v| {
-            v.read()
-                .analyzer_name
-                .as_ref()
-                .eq_ignore_ascii_case(tui_config.default_tab.trim())
-        })
-    {
-        *selected_tab = idx;
-    }
+    // "All Tools" keeps the combined first tab). Its analyzer may still be
+    // loading, so keep looking until it appears or loading finishes.
+    let default_tab = tui_config.default_tab.trim().to_string();
+    let mut default_tab_pending = !default_tab.is_empty();
+    let mut select_default_tab =
+        |display_stats: &[SharedAnalyzerView], loading: &[String], selected_tab: &mut usize| {
+            if !default_tab_pending {
+                return;
+            }
+            if let Some(idx) = display_stats.iter().position(|v| {
+                v.read()
+                    .analyzer_name
+                    .as_ref()
+                    .eq_ignore_ascii_case(&default_tab)
+            }) {
+                *selected_tab = idx;
+                default_tab_pending = false;
+            } else if loading.is_empty() {
+                default_tab_pending = false;
+            }
+        };
+    select_default_tab(&display_stats, &current_stats.loading, selected_tab);
 
     loop {
+        let format_options = &live_format_options;
+
         // Check for update status changes
         let current_update_status = {
             let status = update_status.lock();
@@ -578,29 +1301,194 @@ async fn run_app(
                 .filter(|stats| has_data_shared(stats))
                 .cloned()
                 .collect();
-            display_stats = build_display_stats(&filtered_stats);
+            let old_names = tab_names(&display_stats);
+            display_stats = display_cache.update(
+                &filtered_stats,
+                |view| current_stats.version_of(view),
+                date_range,
+                &cost_basis,
+            );
+            // A tool getting its first data mid-run inserts a tab; keep the
+            // selection and per-tab state attached to the same analyzer.
+            let new_names = tab_names(&display_stats);
+            if old_names != new_names {
+                *selected_tab = follow_selected_tab(&old_names, &new_names, *selected_tab);
+                select_default_tab(&display_stats, &current_stats.loading, selected_tab);
+                let mut fresh = TableState::default();
+                fresh.select(Some(0));
+                table_states = remap_tab_state(&old_names, &new_names, &table_states, fresh);
+                session_window_offsets =
+                    remap_tab_state(&old_names, &new_names, &session_window_offsets, 0);
+                session_period_filters =
+                    remap_tab_state(&old_names, &new_names, &session_period_filters, None);
+            }
             update_table_states(&mut table_states, &current_stats, selected_tab);
             update_window_offsets(&mut session_window_offsets, &table_states.len());
             update_period_filters(&mut session_period_filters, &table_states.len());
 
+            last_periodic_refresh = None;
+            needs_redraw = true;
+        }
+
+        // Follow the session an agent is writing to; the panel hides once
+        // it has been idle for a while.
+        if live_receiver.has_changed().unwrap_or(false) {
+            let latest = live_receiver.borrow_and_update().clone();
+            if let Some(latest) = latest {
+                let same_session = live_session.as_ref().is_some_and(|live| {
+                    live.analyzer_name == latest.analyzer_name
+                        && live.session_id == latest.session_id
+                });
+                if !same_session {
+                    live_started = Some(Local::now());
+                }
+                live_session = Some(latest);
+                needs_redraw = true;
+            }
+        }
+        if live_session
+            .as_ref()
+            .is_some_and(|live| live.updated_at.elapsed() >= LIVE_SESSION_IDLE)
+        {
+            live_session = None;
+            needs_redraw = true;
+        }
+
+        // Refresh budgets, the forecast and spend alerts when stats change and
+        // on the status snapshot's heartbeat, so they roll over at day, week
+        // and month boundaries. The snapshot itself is written in the
+        // background by `statusline::SnapshotPersister`.
+        if last_periodic_refresh
+            .is_none_or(|at| at.elapsed() >= Duration::from_secs(crate::statusline::HEARTBEAT_SECS))
+        {
+            let snapshot =
+                crate::statusline::HotSnapshot::from_views(&current_stats.analyzer_stats);
+            forecast = crate::forecast::evaluate(
+                &current_stats.analyzer_stats,
+                forecast_window_days,
+                Local::now().date_naive(),
+            )
+            .into_iter()
+            .next();
+            premium = crate::premium::month_to_date(
+                &current_stats.analyzer_stats,
+                copilot_plan,
+                Local::now().date_naive(),
+            );
+            needs_redraw = true;
+            // Without a watcher the windows are loaded once, like the stats.
+            let versions = usage_window_versions(&current_stats);
+            let now = chrono::Utc::now();
+            let stale = |(at, loaded): &(std::time::Instant, Vec<Option<u64>>)| {
+                file_watcher.is_some()
+                    && at.elapsed() >= USAGE_WINDOW_REFRESH
+                    && (*loaded != versions
+                        || versions.contains(&None)
+                        || usage_windows.iter().any(|window| window.resets_at <= now))
+            };
+            if usage_window_loader.is_none() && usage_windows_loaded.as_ref().is_none_or(stale) {
+                let (tx, receiver) = std::sync::mpsc::channel();
+                let config = limits_config.clone();
+                let registry = Arc::clone(&registry);
+                std::thread::spawn(move || {
+                    let _ = tx.send(crate::limits::evaluate(&registry, &config, now));
+                });
+                usage_window_loader = Some(receiver);
+                usage_windows_loaded = Some((std::time::Instant::now(), versions));
+            }
+            if !budget_config.is_empty() {
+                budgets = crate::budget::evaluate(
+                    &budget_config,
+                    &current_stats.analyzer_stats,
+                    Local::now().date_naive(),
+                );
+                needs_redraw = true;
+            }
+            if let Some(spend_alerts) = spend_alerts.as_mut() {
+                let mut alerts = spend_alerts.check_budgets(&budgets);
+                alerts.extend(
+                    spend_alerts.check_daily(snapshot.date, snapshot.cost_cents as f64 / 100.0),
+                );
+                crate::notify::dispatch(alerts);
+            }
+            last_periodic_refresh = Some(std::time::Instant::now());
+        }
+
+        if session_detail.as_mut().is_some_and(SessionDetail::poll) {
+            needs_redraw = true;
+        }
+        if day_detail.as_mut().is_some_and(DayDetail::poll) {
             needs_redraw = true;
         }
 
+        // Follow tab switches by loading the new tab's tool usage, and live
+        // updates by reloading the tools whose views changed.
+        if let Some(panel) = tool_panel.as_mut() {
+            if let Some(view) = display_stats.get(*selected_tab) {
+                let name = view.read().analyzer_name.clone();
+                if panel.analyzer_name != *name {
+                    *panel = ToolPanel::open(
+                        &name,
+                        Arc::clone(&registry),
+                        &current_stats,
+                        &tool_usage_cache,
+                    );
+                    needs_redraw = true;
+                }
+            }
+            panel.refresh(&registry, &current_stats, &tool_usage_cache);
+            if panel.usage.poll() {
+                needs_redraw = true;
+            }
+        }
+
         // Check for file watcher events; hand off processing so UI thread stays responsive
-        while let Some(watcher_event) = file_watcher.try_recv() {
-            let _ = watcher_tx.send(watcher_event);
+        let mut config_changed = false;
+        if let Some(file_watcher) = &mut file_watcher {
+            if file_watcher.check_health() {
+                needs_redraw = true;
+            }
+            while let Some(watcher_event) = file_watcher.try_recv() {
+                config_changed |= matches!(watcher_event, WatcherEvent::ConfigChanged);
+                let _ = watcher_tx.send(watcher_event);
+            }
+        }
+        if let Some(loader) = &usage_window_loader {
+            match loader.try_recv() {
+                Ok(windows) => {
+                    usage_windows = windows;
+                    usage_window_loader = None;
+                    needs_redraw = true;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => usage_window_loader = None,
+            }
+        }
+        // A config that doesn't parse (e.g. mid-save) keeps the current settings.
+        if config_changed && let Ok(Some(config)) = crate::config::Config::load() {
+            live_format_options = config.formatting.number_format(&format_overrides);
+            budget_config = config.budget;
+            forecast_window_days = config.forecast.window_days;
+            copilot_plan = config.copilot.plan;
+            limits_config = config.limits;
+            usage_windows_loaded = None;
+            column_layout =
+                ColumnLayout::from_config(&config.tui.columns, &config.tui.hidden_columns);
+            if !colorless {
+                theme = Theme::from_config(&config.theme, &tui_config.accent_color);
+            }
+            keymap = Keymap::from_config(&config.keys);
+            budgets.clear();
+            last_periodic_refresh = None;
+            needs_redraw = true;
+            continue;
         }
 
         // Check if upload status has changed or advance dots animation
         let current_upload_status = {
             let mut status = upload_status.lock();
             // Advance dots animation for uploading status every 500ms (5 frames at 100ms)
-            if let UploadStatus::Uploading {
-                current: _,
-                total: _,
-                dots,
-            } = &mut *status
-            {
+            if let UploadStatus::Uploading { dots, .. } = &mut *status {
                 // Always animate dots during upload
                 dots_counter += 1;
                 if dots_counter >= 5 {
@@ -621,6 +1509,12 @@ async fn run_app(
 
         // Only redraw if something has changed
         if needs_redraw {
+            let live_panel = live_session
+                .as_ref()
+                .filter(|_| show_live)
+                .and_then(|live| LivePanel::find(live, &filtered_stats, live_started));
+            let parse_issues = diagnostics_scroll.map(|_| crate::diagnostics::parse_issues());
+            let visible_columns = column_layout.visible();
             terminal.draw(|frame| {
                 let mut ui_state = UiState {
                     table_states: &mut table_states,
@@ -632,14 +1526,35 @@ async fn run_app(
                     session_period_filters: &mut session_period_filters,
                     date_jump_active,
                     date_jump_buffer: &date_jump_buffer,
+                    range_input: range_input_active.then_some(range_input_buffer.as_str()),
+                    session_search: &session_search,
+                    session_search_active,
+                    date_range,
+                    budgets: &budgets,
+                    forecast: forecast.as_ref(),
+                    premium: premium.as_ref(),
+                    usage_windows: &usage_windows,
+                    session_detail: session_detail.as_mut(),
+                    day_detail: day_detail.as_mut(),
+                    tool_panel: tool_panel.as_ref(),
+                    diagnostics: parse_issues.as_deref().zip(diagnostics_scroll),
+                    parse_issue_count: crate::diagnostics::parse_issue_count(),
+                    trend_days: show_trend.then_some(trend_days),
+                    cost_mode: cost_basis.mode,
+                    live_session: live_panel.as_ref(),
+                    watcher_health: file_watcher.as_ref().map(FileWatcher::health),
+                    loading: &current_stats.loading,
                     sort_reversed,
                     hide_empty_periods,
                     show_totals,
                     quit_pending,
-                    accent,
-                    hidden_cols: &hidden_cols,
+                    theme,
+                    keymap: &keymap,
+                    columns: &visible_columns,
+                    column_chooser: column_chooser.as_ref(),
                     color_costs,
                     show_header,
+                    analyzer_styles: &analyzer_styles,
                 };
                 draw_ui(
                     frame,
@@ -649,6 +1564,9 @@ async fn run_app(
                     upload_status.clone(),
                     update_status.clone(),
                 );
+                if plain {
+                    theme::make_plain(frame.buffer_mut());
+                }
             })?;
             needs_redraw = false;
         }
@@ -670,14 +1588,68 @@ async fn run_app(
                 _ => continue,
             };
 
-            // Handle quitting. Esc is intentionally *not* a quit key; it acts as
-            // a context-aware "go back"/cancel below.
-            if matches!(key.code, KeyCode::Char('q')) {
-                if tui_config.confirm_quit && !quit_pending {
-                    quit_pending = true;
-                    needs_redraw = true;
-                    continue;
-                }
+            // The column chooser is modal; `q` closes it rather than quitting.
+            let action = keymap.action(&key);
+            if let Some(chooser) = column_chooser.as_mut() {
+                match (action, key.code) {
+                    (_, KeyCode::Char(' ')) => chooser.layout.toggle(chooser.selected),
+                    (_, KeyCode::Char('J')) => {
+                        chooser.selected = chooser.layout.shift(chooser.selected, false);
+                    }
+                    (_, KeyCode::Char('K')) => {
+                        chooser.selected = chooser.layout.shift(chooser.selected, true);
+                    }
+                    (Some(Action::Down), _) => chooser.move_selection(1),
+                    (Some(Action::Up), _) => chooser.move_selection(-1),
+                    (Some(Action::Open), _) => {
+                        if let Some(chooser) = column_chooser.take() {
+                            // Keep the layout for this run even if the config can't be written.
+                            let _ = save_columns(&chooser.layout);
+                            column_layout = chooser.layout;
+                        }
+                    }
+                    (Some(Action::Back | Action::Quit | Action::Columns), _) => {
+                        column_chooser = None;
+                    }
+                    _ => {}
+                }
+                needs_redraw = true;
+                continue;
+            }
+
+            // The search box takes every key, `q` included.
+            if session_search_active {
+                match key.code {
+                    KeyCode::Char(c) => session_search.push(c),
+                    KeyCode::Backspace => {
+                        session_search.pop();
+                    }
+                    KeyCode::Enter => session_search_active = false,
+                    KeyCode::Esc => {
+                        session_search_active = false;
+                        session_search.clear();
+                    }
+                    _ => {}
+                }
+                // Start from the top of the narrowed list.
+                if let Some(table_state) = table_states.get_mut(*selected_tab) {
+                    table_state.select(Some(0));
+                }
+                if let Some(offset) = session_window_offsets.get_mut(*selected_tab) {
+                    *offset = 0;
+                }
+                needs_redraw = true;
+                continue;
+            }
+
+            // Handle quitting. Esc is intentionally *not* a quit key; it acts as
+            // a context-aware "go back"/cancel below.
+            if action == Some(Action::Quit) {
+                if tui_config.confirm_quit && !quit_pending {
+                    quit_pending = true;
+                    needs_redraw = true;
+                    continue;
+                }
                 break;
             }
             // Any other key cancels a pending quit confirmation.
@@ -687,7 +1659,7 @@ async fn run_app(
             }
 
             // Handle update notification dismissal
-            if matches!(key.code, KeyCode::Char('u')) {
+            if action == Some(Action::DismissUpdate) {
                 let mut status = update_status.lock();
                 if matches!(
                     *status,
@@ -703,6 +1675,94 @@ async fn run_app(
                 continue;
             }
 
+            if range_input_active {
+                match key.code {
+                    KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '-' || c == '.' => {
+                        range_input_buffer.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        range_input_buffer.pop();
+                    }
+                    KeyCode::Enter => {
+                        // Invalid input keeps the picker open so it can be corrected.
+                        if let Some(range) = DateRange::parse(&range_input_buffer) {
+                            date_range = range;
+                            range_input_active = false;
+                            display_stats = display_cache.update(
+                                &filtered_stats,
+                                |view| current_stats.version_of(view),
+                                date_range,
+                                &cost_basis,
+                            );
+                            for table_state in &mut table_states {
+                                table_state.select(Some(0));
+                            }
+                            session_period_filters.fill(None);
+                        }
+                    }
+                    KeyCode::Esc => {
+                        range_input_active = false;
+                    }
+                    _ => {}
+                }
+                needs_redraw = true;
+                continue;
+            }
+
+            if let Some(scroll) = diagnostics_scroll.as_mut() {
+                match action {
+                    Some(Action::Back | Action::Diagnostics) => diagnostics_scroll = None,
+                    Some(Action::Down) => {
+                        *scroll = (*scroll + 1)
+                            .min(crate::diagnostics::parse_issue_count().saturating_sub(1));
+                    }
+                    Some(Action::Up) => *scroll = scroll.saturating_sub(1),
+                    _ => {}
+                }
+                needs_redraw = true;
+                continue;
+            }
+
+            if let Some(detail) = session_detail.as_mut() {
+                match action {
+                    Some(Action::Back | Action::Open) => session_detail = None,
+                    Some(Action::Down) => detail.move_selection(1),
+                    Some(Action::Up) => detail.move_selection(-1),
+                    Some(Action::PageDown) => detail.move_selection(10),
+                    Some(Action::PageUp) => detail.move_selection(-10),
+                    Some(Action::Top) => detail.move_selection(isize::MIN),
+                    Some(Action::Bottom) => detail.move_selection(isize::MAX),
+                    _ if key.code == KeyCode::Backspace => session_detail = None,
+                    _ => {}
+                }
+                needs_redraw = true;
+                continue;
+            }
+
+            if let Some(detail) = day_detail.as_mut() {
+                match action {
+                    Some(Action::Back) => day_detail = None,
+                    // On to the day's sessions, as Enter does for other periods.
+                    Some(Action::Open) => {
+                        session_period_filters[*selected_tab] = Some(PeriodFilter::Day(detail.day));
+                        *stats_view_mode = StatsViewMode::Session;
+                        session_window_offsets[*selected_tab] = 0;
+                        if let Some(table_state) = table_states.get_mut(*selected_tab) {
+                            table_state.select(Some(0));
+                        }
+                        day_detail = None;
+                    }
+                    Some(Action::Down) => detail.move_selection(1),
+                    Some(Action::Up) => detail.move_selection(-1),
+                    Some(Action::Top) => detail.move_selection(isize::MIN),
+                    Some(Action::Bottom) => detail.move_selection(isize::MAX),
+                    _ if key.code == KeyCode::Backspace => day_detail = None,
+                    _ => {}
+                }
+                needs_redraw = true;
+                continue;
+            }
+
             if date_jump_active {
                 match key.code {
                     KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '-' || c == '/' => {
@@ -753,8 +1813,8 @@ async fn run_app(
                 continue;
             }
 
-            match key.code {
-                KeyCode::Left | KeyCode::Char('h') if *selected_tab > 0 => {
+            match action {
+                Some(Action::PrevTab) if *selected_tab > 0 => {
                     *selected_tab -= 1;
 
                     if let StatsViewMode::Session = *stats_view_mode
@@ -765,6 +1825,7 @@ async fn run_app(
                         let target_len = filtered_session_count(
                             &view,
                             session_period_filters.get(*selected_tab).copied().flatten(),
+                            &session_search,
                         );
                         if target_len > 0 {
                             table_state.select(Some(target_len.saturating_sub(1)));
@@ -773,9 +1834,7 @@ async fn run_app(
 
                     needs_redraw = true;
                 }
-                KeyCode::Right | KeyCode::Char('l')
-                    if *selected_tab < display_stats.len().saturating_sub(1) =>
-                {
+                Some(Action::NextTab) if *selected_tab < display_stats.len().saturating_sub(1) => {
                     *selected_tab += 1;
 
                     if let StatsViewMode::Session = *stats_view_mode
@@ -786,6 +1845,7 @@ async fn run_app(
                         let target_len = filtered_session_count(
                             &view,
                             session_period_filters.get(*selected_tab).copied().flatten(),
+                            &session_search,
                         );
                         if target_len > 0 {
                             table_state.select(Some(target_len.saturating_sub(1)));
@@ -794,7 +1854,7 @@ async fn run_app(
 
                     needs_redraw = true;
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
+                Some(Action::Down) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab)
                         && let Some(selected) = table_state.selected()
                     {
@@ -824,17 +1884,24 @@ async fn run_app(
                                     }
                                 }
                             }
-                            StatsViewMode::Session => {
+                            StatsViewMode::Lengths
+                            | StatsViewMode::Heat
+                            | StatsViewMode::Insights
+                            | StatsViewMode::Achievements
+                            | StatsViewMode::Compare => {}
+                            StatsViewMode::Session | StatsViewMode::Models => {
                                 let filtered_len = display_stats
                                     .get(*selected_tab)
                                     .map(|view| {
                                         let v = view.read();
-                                        filtered_session_count(
+                                        list_view_len(
                                             &v,
+                                            *stats_view_mode,
                                             session_period_filters
                                                 .get(*selected_tab)
                                                 .copied()
                                                 .flatten(),
+                                            &session_search,
                                         )
                                     })
                                     .unwrap_or(0);
@@ -854,7 +1921,7 @@ async fn run_app(
                         }
                     }
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
+                Some(Action::Up) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab)
                         && let Some(selected) = table_state.selected()
                         && selected > 0
@@ -879,17 +1946,24 @@ async fn run_app(
                                     needs_redraw = true;
                                 }
                             }
-                            StatsViewMode::Session => {
+                            StatsViewMode::Lengths
+                            | StatsViewMode::Heat
+                            | StatsViewMode::Insights
+                            | StatsViewMode::Achievements
+                            | StatsViewMode::Compare => {}
+                            StatsViewMode::Session | StatsViewMode::Models => {
                                 let filtered_len = display_stats
                                     .get(*selected_tab)
                                     .map(|view| {
                                         let v = view.read();
-                                        filtered_session_count(
+                                        list_view_len(
                                             &v,
+                                            *stats_view_mode,
                                             session_period_filters
                                                 .get(*selected_tab)
                                                 .copied()
                                                 .flatten(),
+                                            &session_search,
                                         )
                                     })
                                     .unwrap_or(0);
@@ -907,13 +1981,13 @@ async fn run_app(
                         }
                     }
                 }
-                KeyCode::Home => {
+                Some(Action::Top) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab) {
                         table_state.select(Some(0));
                         needs_redraw = true;
                     }
                 }
-                KeyCode::End => {
+                Some(Action::Bottom) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab) {
                         match *stats_view_mode {
                             StatsViewMode::Aggregate => {
@@ -928,17 +2002,24 @@ async fn run_app(
                                     needs_redraw = true;
                                 }
                             }
-                            StatsViewMode::Session => {
+                            StatsViewMode::Lengths
+                            | StatsViewMode::Heat
+                            | StatsViewMode::Insights
+                            | StatsViewMode::Achievements
+                            | StatsViewMode::Compare => {}
+                            StatsViewMode::Session | StatsViewMode::Models => {
                                 let filtered_len = display_stats
                                     .get(*selected_tab)
                                     .map(|view| {
                                         let v = view.read();
-                                        filtered_session_count(
+                                        list_view_len(
                                             &v,
+                                            *stats_view_mode,
                                             session_period_filters
                                                 .get(*selected_tab)
                                                 .copied()
                                                 .flatten(),
+                                            &session_search,
                                         )
                                     })
                                     .unwrap_or(0);
@@ -952,7 +2033,7 @@ async fn run_app(
                         }
                     }
                 }
-                KeyCode::PageDown => {
+                Some(Action::PageDown) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab)
                         && let Some(selected) = table_state.selected()
                     {
@@ -971,17 +2052,24 @@ async fn run_app(
                                     needs_redraw = true;
                                 }
                             }
-                            StatsViewMode::Session => {
+                            StatsViewMode::Lengths
+                            | StatsViewMode::Heat
+                            | StatsViewMode::Insights
+                            | StatsViewMode::Achievements
+                            | StatsViewMode::Compare => {}
+                            StatsViewMode::Session | StatsViewMode::Models => {
                                 let filtered_len = display_stats
                                     .get(*selected_tab)
                                     .map(|view| {
                                         let v = view.read();
-                                        filtered_session_count(
+                                        list_view_len(
                                             &v,
+                                            *stats_view_mode,
                                             session_period_filters
                                                 .get(*selected_tab)
                                                 .copied()
                                                 .flatten(),
+                                            &session_search,
                                         )
                                     })
                                     .unwrap_or(0);
@@ -997,7 +2085,7 @@ async fn run_app(
                         }
                     }
                 }
-                KeyCode::PageUp => {
+                Some(Action::PageUp) => {
                     if let Some(table_state) = table_states.get_mut(*selected_tab)
                         && let Some(selected) = table_state.selected()
                     {
@@ -1006,17 +2094,26 @@ async fn run_app(
                         needs_redraw = true;
                     }
                 }
-                KeyCode::Char('/') => {
-                    if let StatsViewMode::Aggregate = *stats_view_mode {
+                Some(Action::Search) => match *stats_view_mode {
+                    StatsViewMode::Aggregate => {
                         date_jump_active = true;
                         date_jump_buffer.clear();
                         needs_redraw = true;
                     }
-                }
-                KeyCode::Char('m') => {
+                    StatsViewMode::Session => {
+                        session_search_active = true;
+                        needs_redraw = true;
+                    }
+                    _ => {}
+                },
+                Some(Action::CyclePeriod) => {
                     *aggregate_view_mode = aggregate_view_mode.next();
 
-                    if matches!(*stats_view_mode, StatsViewMode::Session) {
+                    // The comparison follows the period, so it stays open.
+                    if !matches!(
+                        *stats_view_mode,
+                        StatsViewMode::Aggregate | StatsViewMode::Compare
+                    ) {
                         *stats_view_mode = StatsViewMode::Aggregate;
                     }
 
@@ -1035,9 +2132,15 @@ async fn run_app(
 
                     needs_redraw = true;
                 }
-                KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Sessions) => {
                     *stats_view_mode = match *stats_view_mode {
-                        StatsViewMode::Aggregate => {
+                        StatsViewMode::Aggregate
+                        | StatsViewMode::Models
+                        | StatsViewMode::Lengths
+                        | StatsViewMode::Heat
+                        | StatsViewMode::Insights
+                        | StatsViewMode::Achievements
+                        | StatsViewMode::Compare => {
                             session_period_filters[*selected_tab] = None;
                             StatsViewMode::Session
                         }
@@ -1056,6 +2159,7 @@ async fn run_app(
                             let target_len = filtered_session_count(
                                 &v,
                                 session_period_filters.get(*selected_tab).copied().flatten(),
+                                &session_search,
                             );
                             if target_len > 0 {
                                 table_state.select(Some(target_len.saturating_sub(1)));
@@ -1069,15 +2173,26 @@ async fn run_app(
                 // drill-down (session) view it returns to the aggregate view.
                 // At the top-level aggregate view it does nothing (date-jump
                 // cancellation is handled earlier, before this match).
-                KeyCode::Esc => {
-                    if let StatsViewMode::Session = *stats_view_mode {
+                // A search is cleared before leaving the session view.
+                Some(Action::Back)
+                    if matches!(*stats_view_mode, StatsViewMode::Session)
+                        && !session_search.is_empty() =>
+                {
+                    session_search.clear();
+                    if let Some(table_state) = table_states.get_mut(*selected_tab) {
+                        table_state.select(Some(0));
+                    }
+                    needs_redraw = true;
+                }
+                Some(Action::Back) => {
+                    if !matches!(*stats_view_mode, StatsViewMode::Aggregate) {
                         *stats_view_mode = StatsViewMode::Aggregate;
                         date_jump_active = false;
                         date_jump_buffer.clear();
                         needs_redraw = true;
                     }
                 }
-                KeyCode::Enter => {
+                Some(Action::Open) => {
                     if let StatsViewMode::Aggregate = *stats_view_mode
                         && let Some(current_stats) = display_stats.get(*selected_tab)
                         && let Some(table_state) = table_states.get_mut(*selected_tab)
@@ -1099,7 +2214,23 @@ async fn run_app(
                                 PeriodFilter::from_period_key(&key, *aggregate_view_mode)
                             });
 
-                            if let Some(period_filter) = period_filter {
+                            if let Some(PeriodFilter::Day(day)) = period_filter {
+                                let cost_factors = filtered_stats
+                                    .iter()
+                                    .filter_map(|source| {
+                                        let source = source.read();
+                                        let factor = cost_basis.day_factor(&source, day)?;
+                                        Some((source.analyzer_name.to_string(), factor))
+                                    })
+                                    .collect();
+                                day_detail = Some(DayDetail::open(
+                                    &view.analyzer_name,
+                                    day,
+                                    Arc::clone(&registry),
+                                    cost_factors,
+                                ));
+                                needs_redraw = true;
+                            } else if let Some(period_filter) = period_filter {
                                 session_period_filters[*selected_tab] = Some(period_filter);
                                 *stats_view_mode = StatsViewMode::Session;
                                 session_window_offsets[*selected_tab] = 0;
@@ -1107,13 +2238,107 @@ async fn run_app(
                                 needs_redraw = true;
                             }
                         }
+                    } else if let StatsViewMode::Session = *stats_view_mode
+                        && let Some(current_stats) = display_stats.get(*selected_tab)
+                        && let Some(selected_idx) = table_states
+                            .get(*selected_tab)
+                            .and_then(TableState::selected)
+                    {
+                        let view = current_stats.read();
+                        let sessions = visible_sessions(
+                            &view.session_aggregates,
+                            session_period_filters.get(*selected_tab).copied().flatten(),
+                            &session_search,
+                            sort_reversed,
+                        );
+                        // The separator and totals rows have no session behind them.
+                        if let Some(session) = sessions.get(selected_idx) {
+                            session_detail =
+                                Some(SessionDetail::open(session, Arc::clone(&registry)));
+                            needs_redraw = true;
+                        }
+                    }
+                }
+                Some(Action::Models) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Models => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Models,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    if let Some(table_state) = table_states.get_mut(*selected_tab) {
+                        table_state.select(Some(0));
                     }
+                    needs_redraw = true;
+                }
+                Some(Action::Lengths) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Lengths => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Lengths,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
+                }
+                Some(Action::Heat) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Heat => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Heat,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
+                }
+                Some(Action::Insights) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Insights => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Insights,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
+                }
+                Some(Action::Achievements) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Achievements => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Achievements,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
+                }
+                Some(Action::Compare) => {
+                    *stats_view_mode = match *stats_view_mode {
+                        StatsViewMode::Compare => StatsViewMode::Aggregate,
+                        _ => StatsViewMode::Compare,
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
                 }
-                KeyCode::Char('r') => {
+                Some(Action::Tools) => {
+                    tool_panel = match tool_panel {
+                        Some(_) => None,
+                        None => display_stats.get(*selected_tab).map(|view| {
+                            ToolPanel::open(
+                                &view.read().analyzer_name,
+                                Arc::clone(&registry),
+                                &current_stats,
+                                &tool_usage_cache,
+                            )
+                        }),
+                    };
+                    needs_redraw = true;
+                }
+                Some(Action::Diagnostics) => {
+                    diagnostics_scroll = Some(0);
+                    needs_redraw = true;
+                }
+                Some(Action::ReverseSort) => {
                     sort_reversed = !sort_reversed;
                     needs_redraw = true;
                 }
-                KeyCode::Char('e') => {
+                Some(Action::ToggleEmpty) => {
                     hide_empty_periods = !hide_empty_periods;
                     if matches!(*stats_view_mode, StatsViewMode::Aggregate)
                         && let Some(current_stats) = display_stats.get(*selected_tab)
@@ -1127,10 +2352,59 @@ async fn run_app(
                     }
                     needs_redraw = true;
                 }
-                KeyCode::Char('s') => {
+                Some(Action::ToggleSummary) => {
                     show_totals = !show_totals;
                     needs_redraw = true;
                 }
+                Some(Action::Trend) => {
+                    show_trend = !show_trend;
+                    needs_redraw = true;
+                }
+                Some(Action::Columns)
+                    if matches!(
+                        *stats_view_mode,
+                        StatsViewMode::Aggregate | StatsViewMode::Session
+                    ) =>
+                {
+                    column_chooser = Some(ColumnChooser {
+                        layout: column_layout.clone(),
+                        selected: 0,
+                    });
+                    needs_redraw = true;
+                }
+                Some(Action::CostMode) => {
+                    cost_basis.mode = cost_basis.mode.toggled();
+                    display_stats = display_cache.update(
+                        &filtered_stats,
+                        |view| current_stats.version_of(view),
+                        date_range,
+                        &cost_basis,
+                    );
+                    needs_redraw = true;
+                }
+                Some(Action::Live) => {
+                    show_live = !show_live;
+                    needs_redraw = true;
+                }
+                Some(Action::Rescan) => {
+                    // The combined tab rescans every tool.
+                    let analyzer_name = display_stats
+                        .get(*selected_tab)
+                        .map(|view| view.read().analyzer_name.to_string())
+                        .filter(|name| name != crate::budget::ALL_TOOLS);
+                    let _ = watcher_tx.send(WatcherEvent::Rescan(analyzer_name));
+                }
+                Some(Action::DateRange) => {
+                    range_input_active = true;
+                    range_input_buffer = if date_range.is_unbounded() {
+                        String::new()
+                    } else {
+                        date_range.to_string()
+                    };
+                    date_jump_active = false;
+                    date_jump_buffer.clear();
+                    needs_redraw = true;
+                }
                 _ => {}
             }
         }
@@ -1169,7 +2443,7 @@ fn draw_ui(
                 Constraint::Min(3),                                           // Main table
             ];
             if ui_state.show_totals {
-                constraints.push(Constraint::Length(9)); // Summary stats
+                constraints.push(Constraint::Length(summary_height(ui_state))); // Summary stats
             }
             constraints.push(Constraint::Length(if has_error { 4 } else { 2 })); // Help text
             (
@@ -1183,7 +2457,7 @@ fn draw_ui(
                 Constraint::Min(3),                                           // Main table
             ];
             if ui_state.show_totals {
-                constraints.push(Constraint::Length(9)); // Summary stats
+                constraints.push(Constraint::Length(summary_height(ui_state))); // Summary stats
             }
             constraints.push(Constraint::Length(if has_error { 4 } else { 2 })); // Help text
             (
@@ -1208,13 +2482,13 @@ fn draw_ui(
         Line::styled(
             "AGENTIC DEVELOPMENT TOOL ACTIVITY ANALYSIS",
             Style::default()
-                .fg(ui_state.accent)
+                .fg(ui_state.theme.accent)
                 .add_modifier(Modifier::BOLD),
         ),
         Line::styled(
             "==========================================",
             Style::default()
-                .fg(ui_state.accent)
+                .fg(ui_state.theme.accent)
                 .add_modifier(Modifier::BOLD),
         ),
     ]));
@@ -1225,12 +2499,14 @@ fn draw_ui(
         let status = update_status.lock();
         if let crate::version_check::UpdateStatus::Available { latest, current } = &*status {
             let banner = Paragraph::new(format!(
-                " New version available: {} -> {} (press 'u' to dismiss)",
-                current, latest
+                " New version available: {} -> {} (press '{}' to dismiss)",
+                current,
+                latest,
+                ui_state.keymap.label(Action::DismissUpdate)
             ))
             .style(
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(ui_state.theme.warning)
                     .add_modifier(Modifier::BOLD),
             );
             frame.render_widget(banner, chunks[1]);
@@ -1238,24 +2514,49 @@ fn draw_ui(
     }
 
     if has_data {
-        // Tabs
+        // Tabs; analyzers still loading trail the others and can't be selected
+        let styles = ui_state.analyzer_styles;
         let tab_titles: Vec<Line> = display_stats
             .iter()
             .map(|stats| {
                 let s = stats.read();
-                Line::from(format!(" {} ({}) ", s.analyzer_name, s.num_conversations))
+                Line::styled(
+                    format!(
+                        " {} ({}) ",
+                        styles.label(&s.analyzer_name),
+                        s.num_conversations
+                    ),
+                    Style::default().fg(styles.color(&s.analyzer_name, &ui_state.theme)),
+                )
             })
+            .chain(ui_state.loading.iter().map(|name| {
+                Line::styled(
+                    format!(" {} (loading…) ", styles.label(name)),
+                    ui_state.theme.dim(),
+                )
+            }))
             .collect();
 
         let tabs = Tabs::new(tab_titles)
             .select(ui_state.selected_tab)
-            // .style(Style::default().add_modifier(Modifier::DIM))
-            .highlight_style(Style::default().fg(Color::Black).bg(ui_state.accent))
+            // .style(ui_state.theme.dim())
+            .highlight_style(ui_state.theme.selected_tab())
             .padding("", "")
             .divider(" | ");
 
         frame.render_widget(tabs, chunks[1 + chunk_offset]);
 
+        let main_area = match ui_state.live_session {
+            Some(live) => {
+                let [live_area, rest] =
+                    Layout::vertical([Constraint::Length(3), Constraint::Min(3)])
+                        .areas(chunks[2 + chunk_offset]);
+                draw_live_session(frame, live_area, live, format_options, ui_state);
+                rest
+            }
+            None => chunks[2 + chunk_offset],
+        };
+
         // Get current analyzer stats
         if let Some(current_stats) = display_stats.get(ui_state.selected_tab)
             && let Some(current_table_state) = ui_state.table_states.get_mut(ui_state.selected_tab)
@@ -1263,11 +2564,64 @@ fn draw_ui(
             // Draw main table - hold read lock only for this scope
             let has_estimated_models = {
                 let view = current_stats.read();
-                match ui_state.stats_view_mode {
+                let show_tool_panel = ui_state.tool_panel.is_some()
+                    && match ui_state.stats_view_mode {
+                        StatsViewMode::Aggregate => ui_state.day_detail.is_none(),
+                        StatsViewMode::Session => ui_state.session_detail.is_none(),
+                        _ => false,
+                    };
+                let (table_area, tool_area) = if show_tool_panel {
+                    let [table, tools] =
+                        Layout::horizontal([Constraint::Min(0), Constraint::Length(34)])
+                            .areas(main_area);
+                    (table, Some(tools))
+                } else {
+                    (main_area, None)
+                };
+                let has_estimated = match ui_state.stats_view_mode {
+                    StatsViewMode::Aggregate if ui_state.day_detail.is_some() => {
+                        if let Some(detail) = ui_state.day_detail.as_deref_mut() {
+                            draw_day_detail(
+                                frame,
+                                main_area,
+                                detail,
+                                format_options,
+                                &ui_state.theme,
+                            );
+                        }
+                        false
+                    }
                     StatsViewMode::Aggregate => {
+                        let table_area = match ui_state.trend_days {
+                            Some(days) => {
+                                let [trend, table] =
+                                    Layout::vertical([Constraint::Length(5), Constraint::Min(3)])
+                                        .areas(table_area);
+                                let end = ui_state
+                                    .date_range
+                                    .until
+                                    .and_then(|date| {
+                                        NaiveDate::from_ymd_opt(
+                                            date.year() as i32,
+                                            date.month() as u32,
+                                            date.day() as u32,
+                                        )
+                                    })
+                                    .unwrap_or_else(|| Local::now().date_naive());
+                                draw_trend(
+                                    frame,
+                                    trend,
+                                    &DailyTrend::ending(&view.daily_stats, end, days),
+                                    format_options,
+                                    ui_state.theme.accent,
+                                );
+                                table
+                            }
+                            None => table_area,
+                        };
                         let (_, has_estimated) = draw_aggregate_stats_table(
                             frame,
-                            chunks[2 + chunk_offset],
+                            table_area,
                             &view,
                             format_options,
                             current_table_state,
@@ -1279,28 +2633,143 @@ fn draw_ui(
                             },
                             ui_state.hide_empty_periods,
                             ui_state.sort_reversed,
-                            ui_state.accent,
-                            ui_state.hidden_cols,
+                            &ui_state.theme,
+                            ui_state.columns,
                             ui_state.color_costs,
                         );
                         has_estimated
                     }
+                    StatsViewMode::Session if ui_state.session_detail.is_some() => {
+                        if let Some(detail) = ui_state.session_detail.as_deref_mut() {
+                            draw_session_detail(
+                                frame,
+                                main_area,
+                                detail,
+                                format_options,
+                                &ui_state.theme,
+                            );
+                        }
+                        false
+                    }
                     StatsViewMode::Session => {
                         draw_session_stats_table(
                             frame,
-                            chunks[2 + chunk_offset],
+                            table_area,
                             &view.session_aggregates,
                             format_options,
                             current_table_state,
                             &mut ui_state.session_window_offsets[ui_state.selected_tab],
                             ui_state.session_period_filters[ui_state.selected_tab],
+                            ui_state.session_search,
                             ui_state.sort_reversed,
+                            ui_state.analyzer_styles,
+                            ui_state.columns,
+                            &ui_state.theme,
                         );
                         false // Session view doesn't track estimated models yet
                     }
+                    StatsViewMode::Models => draw_model_stats_table(
+                        frame,
+                        main_area,
+                        &aggregate_model_stats(view.daily_stats.values()),
+                        format_options,
+                        current_table_state,
+                        ui_state.sort_reversed,
+                        &ui_state.theme,
+                    ),
+                    StatsViewMode::Lengths => {
+                        draw_session_lengths(
+                            frame,
+                            main_area,
+                            &SessionLengths::from_sessions(&view.session_aggregates),
+                            format_options,
+                            ui_state.theme.accent,
+                        );
+                        false
+                    }
+                    StatsViewMode::Heat => {
+                        draw_heat_grid(
+                            frame,
+                            main_area,
+                            &HeatGrid::from_days(view.daily_stats.values()),
+                            format_options,
+                            ui_state.theme.accent,
+                        );
+                        false
+                    }
+                    StatsViewMode::Insights => {
+                        draw_insights(
+                            frame,
+                            main_area,
+                            &Throughput::from_days(view.daily_stats.values()),
+                            format_options,
+                            ui_state.theme.accent,
+                        );
+                        false
+                    }
+                    StatsViewMode::Achievements => {
+                        draw_achievements(
+                            frame,
+                            main_area,
+                            &Achievements::from_days(
+                                &view.daily_stats,
+                                chrono::Local::now().date_naive(),
+                            ),
+                            format_options,
+                            ui_state.theme.accent,
+                        );
+                        false
+                    }
+                    StatsViewMode::Compare => {
+                        let days = match ui_state.aggregate_view_mode {
+                            AggregateViewMode::Daily => 1,
+                            AggregateViewMode::Weekly => 7,
+                            AggregateViewMode::Monthly => 30,
+                            AggregateViewMode::Yearly => 365,
+                        };
+                        let periods = Periods::trailing(days, Local::now().date_naive());
+                        draw_compare(
+                            frame,
+                            main_area,
+                            &Comparison::from_days(
+                                &view.analyzer_name,
+                                view.daily_stats.values(),
+                                periods,
+                            ),
+                            periods,
+                            format_options,
+                            &ui_state.theme,
+                        );
+                        false
+                    }
+                };
+
+                if let (Some(area), Some(panel)) = (tool_area, ui_state.tool_panel) {
+                    let selection = current_table_state
+                        .selected()
+                        .and_then(|index| selected_tool_counts(&view, panel, ui_state, index));
+                    draw_tool_panel(
+                        frame,
+                        area,
+                        panel,
+                        selection,
+                        format_options,
+                        &ui_state.theme,
+                    );
                 }
+                has_estimated
             }; // Read lock on current_stats released here BEFORE draw_summary_stats
 
+            if let Some((issues, scroll)) = ui_state.diagnostics {
+                frame.render_widget(Clear, main_area);
+                draw_diagnostics(frame, main_area, issues, scroll, &ui_state.theme);
+            }
+
+            if let Some(chooser) = ui_state.column_chooser {
+                frame.render_widget(Clear, main_area);
+                draw_column_chooser(frame, main_area, chooser, &ui_state.theme);
+            }
+
             // Summary stats - pass all filtered stats for aggregation (only if visible)
             // When in Session mode with a day filter, only show totals for that day
             // NOTE: This acquires its own read locks, so we must not hold any above
@@ -1311,7 +2780,13 @@ fn draw_ui(
                         .get(ui_state.selected_tab)
                         .copied()
                         .flatten(),
-                    StatsViewMode::Aggregate => None,
+                    StatsViewMode::Aggregate
+                    | StatsViewMode::Models
+                    | StatsViewMode::Lengths
+                    | StatsViewMode::Heat
+                    | StatsViewMode::Insights
+                    | StatsViewMode::Achievements
+                    | StatsViewMode::Compare => None,
                 };
                 draw_summary_stats(
                     frame,
@@ -1319,6 +2794,13 @@ fn draw_ui(
                     tool_stats,
                     format_options,
                     period_filter,
+                    ui_state.date_range,
+                    ui_state.budgets,
+                    ui_state.forecast,
+                    ui_state.premium,
+                    ui_state.usage_windows,
+                    ui_state.cost_mode,
+                    &ui_state.theme,
                 );
                 4 + chunk_offset
             } else {
@@ -1335,6 +2817,16 @@ fn draw_ui(
             ])
             .split(help_area);
 
+            let keys = ui_state.keymap;
+            let key = |action| keys.label(action);
+            let tabs = keys.pair_label(Action::PrevTab, Action::NextTab);
+            let nav = keys.pair_label(Action::Up, Action::Down);
+            let (back, quit, sessions, summary) = (
+                key(Action::Back),
+                key(Action::Quit),
+                key(Action::Sessions),
+                key(Action::ToggleSummary),
+            );
             let base_help_text = match ui_state.stats_view_mode {
                 StatsViewMode::Aggregate => {
                     let jump_label = match ui_state.aggregate_view_mode {
@@ -1345,28 +2837,132 @@ fn draw_ui(
                     };
 
                     format!(
-                        "Use ←/→ or h/l to switch tabs • ↑/↓ or j/k to navigate • r to reverse sort • e to toggle empty periods • s to toggle summary • / for {jump_label} • m to cycle day/week/month/year • Enter to drill into period • Ctrl+T for all sessions • q to quit"
+                        "Use {tabs} to switch tabs • {nav} to navigate • {} to reverse sort • {} to toggle empty periods • {summary} to toggle summary • {} to filter dates • {} for {jump_label} • {} to cycle day/week/month/year • {} to drill into period • {sessions} for all sessions • {} for trend chart • {} to choose columns • {} for API/actual cost • {} for live session • {} for tools & file types • {} for models • {} for session lengths • {} for time of day • {} for insights • {} for streaks • {} to compare periods • {} to rescan • {quit} to quit",
+                        key(Action::ReverseSort),
+                        key(Action::ToggleEmpty),
+                        key(Action::DateRange),
+                        key(Action::Search),
+                        key(Action::CyclePeriod),
+                        key(Action::Open),
+                        key(Action::Trend),
+                        key(Action::Columns),
+                        key(Action::CostMode),
+                        key(Action::Live),
+                        key(Action::Tools),
+                        key(Action::Models),
+                        key(Action::Lengths),
+                        key(Action::Heat),
+                        key(Action::Insights),
+                        key(Action::Achievements),
+                        key(Action::Compare),
+                        key(Action::Rescan),
                     )
                 }
-                StatsViewMode::Session => {
-                    "Use ←/→ or h/l to switch tabs • ↑/↓ or j/k to navigate • r to reverse sort • e to toggle empty periods • s to toggle summary • m to cycle day/week/month/year • Esc or Ctrl+T for aggregate view • q to quit".to_string()
-                }
+                StatsViewMode::Session => format!(
+                    "Use {tabs} to switch tabs • {nav} to navigate • {} to reverse sort • {} to toggle empty periods • {summary} to toggle summary • {} to cycle day/week/month/year • {} to search • {} to choose columns • {} for messages • {} for tools & file types • {back} or {sessions} for aggregate view • {quit} to quit",
+                    key(Action::ReverseSort),
+                    key(Action::ToggleEmpty),
+                    key(Action::CyclePeriod),
+                    key(Action::Search),
+                    key(Action::Columns),
+                    key(Action::Open),
+                    key(Action::Tools),
+                ),
+                StatsViewMode::Models => format!(
+                    "Use {tabs} to switch tabs • {nav} to navigate • {} to reverse sort • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::ReverseSort),
+                    key(Action::Models),
+                ),
+                StatsViewMode::Lengths => format!(
+                    "Use {tabs} to switch tabs • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::Lengths),
+                ),
+                StatsViewMode::Heat => format!(
+                    "Use {tabs} to switch tabs • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::Heat),
+                ),
+                StatsViewMode::Insights => format!(
+                    "Use {tabs} to switch tabs • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::Insights),
+                ),
+                StatsViewMode::Achievements => format!(
+                    "Use {tabs} to switch tabs • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::Achievements),
+                ),
+                StatsViewMode::Compare => format!(
+                    "Use {tabs} to switch tabs • {} to cycle day/week/month/year • {summary} to toggle summary • {back} or {} for aggregate view • {sessions} for all sessions • {quit} to quit",
+                    key(Action::CyclePeriod),
+                    key(Action::Compare),
+                ),
+            };
+
+            let base_help_text = if ui_state.diagnostics.is_some() {
+                format!(
+                    "{nav} to scroll • {back} or {} to close • {quit} to quit",
+                    key(Action::Diagnostics)
+                )
+            } else if ui_state.session_detail.is_some()
+                && matches!(ui_state.stats_view_mode, StatsViewMode::Session)
+            {
+                format!("{nav} to scroll messages • {back} to return to sessions • {quit} to quit")
+            } else if ui_state.day_detail.is_some()
+                && matches!(ui_state.stats_view_mode, StatsViewMode::Aggregate)
+            {
+                format!(
+                    "{nav} to move • {} for the day's sessions • {back} to return • {quit} to quit",
+                    key(Action::Open)
+                )
+            } else if ui_state.parse_issue_count > 0 {
+                format!(
+                    "{base_help_text} • {} for {} {} with parse problems",
+                    key(Action::Diagnostics),
+                    ui_state.parse_issue_count,
+                    if ui_state.parse_issue_count == 1 {
+                        "file"
+                    } else {
+                        "files"
+                    }
+                )
+            } else {
+                base_help_text
             };
 
             let help_text = if ui_state.quit_pending {
-                "Quit splitrail?  Press q again to confirm  •  any other key to cancel".to_string()
+                format!(
+                    "Quit splitrail?  Press {quit} again to confirm  •  any other key to cancel"
+                )
+            } else if ui_state.session_search_active {
+                format!(
+                    "Search sessions by name, tool, or model: {}▏  Enter to keep • Esc to clear",
+                    ui_state.session_search
+                )
+            } else if !ui_state.session_search.is_empty()
+                && matches!(ui_state.stats_view_mode, StatsViewMode::Session)
+                && ui_state.session_detail.is_none()
+            {
+                format!(
+                    "Showing sessions matching \"{}\" • / to edit • Esc to clear",
+                    ui_state.session_search
+                )
+            } else if let Some(input) = ui_state.range_input {
+                format!(
+                    "Date range (YYYY-MM-DD..YYYY-MM-DD, 7d.., today; empty for all): {input}▏  Enter to apply • Esc to cancel"
+                )
             } else if has_estimated_models {
                 format!("{} • * = estimated pricing", base_help_text)
             } else {
                 base_help_text
             };
 
-            let help_style = if ui_state.quit_pending {
+            let help_style = if ui_state.quit_pending
+                || ui_state.range_input.is_some()
+                || ui_state.session_search_active
+            {
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(ui_state.theme.warning)
                     .add_modifier(Modifier::BOLD)
             } else {
-                Style::default().add_modifier(Modifier::DIM)
+                ui_state.theme.dim()
             };
             let help = Paragraph::new(help_text)
                 .style(help_style)
@@ -1380,6 +2976,8 @@ fn draw_ui(
                 UploadStatus::Uploading {
                     current,
                     total,
+                    chunk,
+                    total_chunks,
                     dots,
                 } => {
                     let dots_str = match dots % 4 {
@@ -1390,36 +2988,66 @@ fn draw_ui(
                     };
                     (
                         format!(
-                            "Uploading {}/{} messages{}",
+                            "Uploading {}/{} messages{}{}",
                             format_number(*current as u64, format_options),
                             format_number(*total as u64, format_options),
+                            chunk_label(*chunk, *total_chunks),
                             dots_str
                         ),
-                        Style::default().add_modifier(Modifier::DIM),
+                        ui_state.theme.dim(),
                     )
                 }
                 UploadStatus::Uploaded => (
                     "✓ Uploaded successfully".to_string(),
-                    Style::default().fg(Color::Green),
+                    Style::default().fg(ui_state.theme.success),
+                ),
+                UploadStatus::Failed(error) => (
+                    format!("✕ {error}"),
+                    Style::default().fg(ui_state.theme.error),
                 ),
-                UploadStatus::Failed(error) => {
-                    (format!("✕ {error}"), Style::default().fg(Color::Red))
-                }
                 UploadStatus::MissingApiToken => (
                     "No API token for uploading".to_string(),
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(ui_state.theme.warning),
                 ),
                 UploadStatus::MissingServerUrl => (
                     "No server URL for uploading".to_string(),
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(ui_state.theme.warning),
                 ),
                 UploadStatus::MissingConfig => (
                     "Upload config incomplete".to_string(),
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(ui_state.theme.warning),
                 ),
             };
             drop(status); // Release lock before rendering
 
+            let pending = crate::upload::outbox::pending_count();
+            let (status_text, status_style) = match pending {
+                0 => (status_text, status_style),
+                _ if status_text.is_empty() => (
+                    format!(
+                        "⏳ {pending} upload batch{} waiting to retry",
+                        if pending == 1 { "" } else { "es" }
+                    ),
+                    Style::default().fg(ui_state.theme.warning),
+                ),
+                _ => (
+                    format!("{status_text} • {pending} queued for retry"),
+                    status_style,
+                ),
+            };
+
+            // A stalled watcher matters more than upload progress: the numbers
+            // on screen are no longer live.
+            let (status_text, status_style) = match ui_state.watcher_health {
+                Some(WatcherHealth::Reconnecting { .. }) => (
+                    "⚠ Live updates paused — reconnecting".to_string(),
+                    Style::default().fg(ui_state.theme.warning),
+                ),
+                Some(WatcherHealth::Live) => (status_text, status_style),
+                None if status_text.is_empty() => ("Snapshot".to_string(), ui_state.theme.dim()),
+                None => (status_text, status_style),
+            };
+
             if !status_text.is_empty() {
                 let status_widget = Paragraph::new(status_text)
                     .style(status_style)
@@ -1431,14 +3059,21 @@ fn draw_ui(
     } else {
         // No data message
         let no_data_message = Paragraph::new(Text::styled(
-            "You don't have any agentic development tool data.  Once you start using Claude Code / Codex CLI / Gemini CLI / Qwen Code / Cline / Roo Code / Kilo Code / GitHub Copilot / GitHub Copilot CLI / OpenCode / Pi Agent, you'll see some data here.",
-            Style::default().add_modifier(Modifier::DIM),
+            if ui_state.loading.is_empty() {
+                "You don't have any agentic development tool data.  Once you start using Claude Code / Codex CLI / Gemini CLI / Qwen Code / Cline / Roo Code / Kilo Code / GitHub Copilot / GitHub Copilot CLI / OpenCode / Pi Agent, you'll see some data here.".to_string()
+            } else {
+                format!("Loading {}…", ui_state.loading.join(", "))
+            },
+            ui_state.theme.dim(),
         ));
         frame.render_widget(no_data_message, chunks[1]);
 
         // Help text for no-data view
-        let help =
-            Paragraph::new("Press q to quit").style(Style::default().add_modifier(Modifier::DIM));
+        let help = Paragraph::new(format!(
+            "Press {} to quit",
+            ui_state.keymap.label(Action::Quit)
+        ))
+        .style(ui_state.theme.dim());
         frame.render_widget(help, chunks[2]);
     }
 }
@@ -1459,147 +3094,256 @@ fn parse_accent(s: &str) -> Color {
     }
 }
 
-/// Heatmap color for a cost cell: low -> green, mid -> yellow, high -> red.
-fn cost_heat(cents: u32, max: u32) -> Color {
-    if max == 0 {
-        return Color::Green;
+/// Parse a color name or `#rrggbb` hex string, returning None if unrecognized.
+pub(crate) fn parse_color(s: &str) -> Option<Color> {
+    let s = s.trim().to_lowercase();
+    if let Some(hex) = s.strip_prefix('#')
+        && hex.len() == 6
+        && let Ok(rgb) = u32::from_str_radix(hex, 16)
+    {
+        return Some(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+    }
+    match s.as_str() {
+        "cyan" => Some(Color::Cyan),
+        "green" => Some(Color::Green),
+        "magenta" | "purple" => Some(Color::Magenta),
+        "blue" => Some(Color::Blue),
+        "red" => Some(Color::Red),
+        "yellow" => Some(Color::Yellow),
+        "white" => Some(Color::White),
+        "gray" | "grey" => Some(Color::Gray),
+        "light_cyan" | "lightcyan" => Some(Color::LightCyan),
+        "light_green" | "lightgreen" => Some(Color::LightGreen),
+        "light_blue" | "lightblue" => Some(Color::LightBlue),
+        "light_magenta" | "lightmagenta" => Some(Color::LightMagenta),
+        "light_red" | "lightred" => Some(Color::LightRed),
+        "light_yellow" | "lightyellow" => Some(Color::LightYellow),
+        "black" => Some(Color::Black),
+        "dark_gray" | "darkgray" | "dark_grey" | "darkgrey" => Some(Color::DarkGray),
+        "default" | "reset" | "none" => Some(Color::Reset),
+        _ => None,
     }
-    let t = (cents as f64 / max as f64).clamp(0.0, 1.0);
-    let r = (90.0 + t * (235.0 - 90.0)) as u8;
-    let g = (200.0 - t * (200.0 - 80.0)) as u8;
-    let b = (110.0 - t * (110.0 - 70.0)) as u8;
-    Color::Rgb(r, g, b)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn draw_aggregate_stats_table(
-    frame: &mut Frame,
-    area: Rect,
-    stats: &AnalyzerStatsView,
-    format_options: &NumberFormatOptions,
-    table_state: &mut TableState,
-    aggregate_view_mode: AggregateViewMode,
-    date_filter: &str,
-    hide_empty_periods: bool,
-    sort_reversed: bool,
-    accent: Color,
-    hidden: &std::collections::HashSet<String>,
-    color_costs: bool,
-) -> (usize, bool) {
-    let period_header = match aggregate_view_mode {
-        AggregateViewMode::Daily => "Date",
-        AggregateViewMode::Weekly => "Week",
-        AggregateViewMode::Monthly => "Month",
-        AggregateViewMode::Yearly => "Year",
-    };
-
-    let aggregate_stats = get_aggregate_stats(stats, aggregate_view_mode);
-    let aggregate_stats = aggregate_stats.as_map();
-    let visible_periods =
-        filtered_aggregate_keys(aggregate_stats, hide_empty_periods, sort_reversed);
-    clamp_table_selection(table_state, visible_periods.len() + 2);
+/// Fallback colors for tools without a built-in or configured color.
+const ANALYZER_PALETTE: [Color; 8] = [
+    Color::LightBlue,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightYellow,
+    Color::LightCyan,
+    Color::LightRed,
+    Color::Blue,
+    Color::Magenta,
+];
+
+/// Per-tool color and icon used in tab titles and the session Tool column.
+pub(crate) struct AnalyzerStyles {
+    colors: std::collections::HashMap<String, Color>,
+    icons: std::collections::HashMap<String, String>,
+}
+
+impl AnalyzerStyles {
+    pub(crate) fn from_config(tui_config: &TuiConfig) -> Self {
+        Self {
+            colors: tui_config
+                .analyzer_colors
+                .iter()
+                .filter_map(|(name, color)| parse_color(color).map(|c| (name.clone(), c)))
+                .collect(),
+            icons: tui_config.analyzer_icons.clone(),
+        }
+    }
+
+    /// Color for a tool: configured override, then a built-in default, then a
+    /// palette entry chosen by name hash so it stays stable across runs.
+    /// Colorless themes use the terminal's own color for every tool.
+    pub(crate) fn color(&self, analyzer_name: &str, theme: &Theme) -> Color {
+        if theme.is_colorless() {
+            return Color::Reset;
+        }
+        if let Some(color) = self.colors.get(analyzer_name) {
+            return *color;
+        }
+        if analyzer_name == crate::budget::ALL_TOOLS {
+            return theme.all_tools();
+        }
+        match crate::types::Application::from_name(analyzer_name).and_then(|app| app.info().color) {
+            Some(color) => color,
+            None => {
+                let hash = xxhash_rust::xxh3::xxh3_64(analyzer_name.as_bytes());
+                let idx = hash as usize % ANALYZER_PALETTE.len();
+                ANALYZER_PALETTE[idx]
+            }
+        }
+    }
+
+    /// Tool name prefixed with its configured icon, if any.
+    pub(crate) fn label(&self, analyzer_name: &str) -> String {
+        match self.icons.get(analyzer_name) {
+            Some(icon) if !icon.is_empty() => format!("{icon} {analyzer_name}"),
+            _ => analyzer_name.to_string(),
+        }
+    }
+}
+
+/// Cost spent by subagents, or a dim dash when there was none.
+fn subagent_cost_cell(
+    cents: u64,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+    style: Style,
+) -> Line<'static> {
+    if cents == 0 {
+        return Line::from(Span::styled("-", theme.dim())).right_aligned();
+    }
+    Line::from(Span::styled(
+        format!(
+            "{}{:.prec$}",
+            format_options.currency_symbol,
+            cents as f64 / 100.0,
+            prec = format_options.cost_decimal_places
+        ),
+        style.fg(theme.cost),
+    ))
+    .right_aligned()
+}
+
+/// Copilot premium requests, with hundredths only when a multiplier left
+/// any, or a dim dash when there were none.
+fn premium_cell(
+    hundredths: u64,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+    style: Style,
+) -> Line<'static> {
+    if hundredths == 0 {
+        return Line::from(Span::styled("-", theme.dim())).right_aligned();
+    }
+    let text = if hundredths.is_multiple_of(100) {
+        format_number(hundredths / 100, format_options)
+    } else {
+        format!("{:.2}", hundredths as f64 / 100.0)
+    };
+    Line::from(Span::styled(text, style.fg(theme.count))).right_aligned()
+}
+
+/// Active time as hours and minutes, or a dim dash when there was none.
+fn active_cell(active_secs: u64, theme: &Theme, style: Style) -> Line<'static> {
+    if active_secs == 0 {
+        return Line::from(Span::styled("-", theme.dim())).right_aligned();
+    }
+    Line::from(Span::styled(format_active_time(active_secs), style)).right_aligned()
+}
+
+/// "45m" under an hour, "2h 05m" from there.
+pub fn format_active_time(active_secs: u64) -> String {
+    let minutes = active_secs.div_ceil(60);
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Cost per active hour, or a dim dash without active time.
+fn per_hour_cell(
+    cost_cents: u64,
+    active_secs: u64,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+    style: Style,
+) -> Line<'static> {
+    let active_secs = u32::try_from(active_secs).unwrap_or(u32::MAX);
+    match crate::stats::active::cost_per_hour(cost_cents as f64 / 100.0, active_secs) {
+        Some(per_hour) => Line::from(Span::styled(
+            format!(
+                "{}{per_hour:.prec$}",
+                format_options.currency_symbol,
+                prec = format_options.cost_decimal_places
+            ),
+            style.fg(theme.cost),
+        ))
+        .right_aligned(),
+        None => Line::from(Span::styled("-", theme.dim())).right_aligned(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_aggregate_stats_table(
+    frame: &mut Frame,
+    area: Rect,
+    stats: &AnalyzerStatsView,
+    format_options: &NumberFormatOptions,
+    table_state: &mut TableState,
+    aggregate_view_mode: AggregateViewMode,
+    date_filter: &str,
+    hide_empty_periods: bool,
+    sort_reversed: bool,
+    theme: &Theme,
+    columns: &[Column],
+    color_costs: bool,
+) -> (usize, bool) {
+    let period_header = match aggregate_view_mode {
+        AggregateViewMode::Daily => "Date",
+        AggregateViewMode::Weekly => "Week",
+        AggregateViewMode::Monthly => "Month",
+        AggregateViewMode::Yearly => "Year",
+    };
+
+    let aggregate_stats = get_aggregate_stats(stats, aggregate_view_mode);
+    let aggregate_stats = aggregate_stats.as_map();
+    let visible_periods =
+        filtered_aggregate_keys(aggregate_stats, hide_empty_periods, sort_reversed);
+    clamp_table_selection(table_state, visible_periods.len() + 2);
 
     // The Apps column is only meaningful in the combined "All Tools" view, where
     // each period records which tools contributed. On single-tool tabs it is
     // always empty, so collapse it entirely instead of reserving a blank gap.
     let has_apps = aggregate_stats.values().any(|s| !s.apps.is_empty());
-    let show = |c: &str| {
-        if c == "apps" && !has_apps {
-            return false;
-        }
-        !hidden.contains(c)
-    };
+    // Likewise premium requests only exist where Copilot was used.
+    let has_premium = aggregate_stats
+        .values()
+        .any(|s| s.stats.premium_hundredths > 0);
+    // And active time only where tools report whole sessions.
+    let has_active = aggregate_stats.values().any(|s| s.stats.active_secs > 0);
+    let columns: Vec<Column> = columns
+        .iter()
+        .copied()
+        .filter(|column| *column != Column::Apps || has_apps)
+        .filter(|column| *column != Column::Premium || has_premium)
+        .filter(|column| !matches!(column, Column::Active | Column::PerHour) || has_active)
+        .collect();
 
     let mut header_cells = vec![
         Cell::new(""),
         Cell::new(period_header),
         Cell::new(Text::from("Cost").right_aligned()),
     ];
-    if show("cached") {
-        header_cells.push(Cell::new(Text::from("Cached Tks").right_aligned()));
-    }
-    if show("input") {
-        header_cells.push(Cell::new(Text::from("Inp Tks").right_aligned()));
-    }
-    if show("output") {
-        header_cells.push(Cell::new(Text::from("Outp Tks").right_aligned()));
-    }
-    if show("reason") {
-        header_cells.push(Cell::new(Text::from("Reason Tks").right_aligned()));
-    }
-    if show("convs") {
-        header_cells.push(Cell::new(Text::from("Convs").right_aligned()));
-    }
-    if show("tools") {
-        header_cells.push(Cell::new(Text::from("Tools").right_aligned()));
-    }
-    if show("apps") {
-        header_cells.push(Cell::new("Apps"));
-    }
-    if show("models") {
-        header_cells.push(Cell::new("Models"));
+    for column in &columns {
+        header_cells.push(match column {
+            Column::Apps | Column::Models => Cell::new(column.label()),
+            _ => Cell::new(Text::from(column.label()).right_aligned()),
+        });
     }
     let header = Row::new(header_cells)
         .style(Style::default().add_modifier(Modifier::BOLD))
         .height(1);
 
-    // Find best values for highlighting
-    // TODO: Let's refactor this.
-
-    let mut best_cost_cents: u32 = 0;
-    let mut best_cost_i = 0;
-    let mut best_cached_tokens: u64 = 0;
-    let mut best_cached_tokens_i = 0;
-    let mut best_input_tokens: u64 = 0;
-    let mut best_input_tokens_i = 0;
-    let mut best_output_tokens: u64 = 0;
-    let mut best_output_tokens_i = 0;
-    let mut best_reasoning_tokens: u64 = 0;
-    let mut best_reasoning_tokens_i = 0;
-    let mut best_conversations = 0;
-    let mut best_conversations_i = 0;
-    let mut best_tool_calls: u32 = 0;
-    let mut best_tool_calls_i = 0;
-
-    for (i, period_stats) in aggregate_stats.values().enumerate() {
-        if period_stats.stats.cost_cents > best_cost_cents {
-            best_cost_cents = period_stats.stats.cost_cents;
-            best_cost_i = i;
-        }
-        if period_stats.stats.cached_tokens > best_cached_tokens {
-            best_cached_tokens = period_stats.stats.cached_tokens;
-            best_cached_tokens_i = i;
-        }
-        if period_stats.stats.input_tokens > best_input_tokens {
-            best_input_tokens = period_stats.stats.input_tokens;
-            best_input_tokens_i = i;
-        }
-        if period_stats.stats.output_tokens > best_output_tokens {
-            best_output_tokens = period_stats.stats.output_tokens;
-            best_output_tokens_i = i;
-        }
-        if period_stats.stats.reasoning_tokens > best_reasoning_tokens {
-            best_reasoning_tokens = period_stats.stats.reasoning_tokens;
-            best_reasoning_tokens_i = i;
-        }
-        if period_stats.conversations > best_conversations {
-            best_conversations = period_stats.conversations;
-            best_conversations_i = i;
-        }
-        if period_stats.stats.tool_calls > best_tool_calls {
-            best_tool_calls = period_stats.stats.tool_calls;
-            best_tool_calls_i = i;
-        }
-    }
+    let bests = PeriodBests::from_periods(aggregate_stats);
 
     let mut rows = Vec::new();
     let mut total_cost_cents: u64 = 0;
+    let mut total_subagent_cents: u64 = 0;
+    let mut total_premium_hundredths: u64 = 0;
+    let mut total_active_secs: u64 = 0;
     let mut total_cached: u64 = 0;
     let mut total_input: u64 = 0;
     let mut total_output: u64 = 0;
     let mut total_reasoning: u64 = 0;
     let mut total_tool_calls: u64 = 0;
     let mut total_conversations: u64 = 0;
+    let mut total_cache = CacheEfficiency::default();
 
     for (i, period) in visible_periods.iter().enumerate() {
         let period_stats = aggregate_stats
@@ -1611,12 +3355,17 @@ fn draw_aggregate_stats_table(
         }
 
         total_cost_cents += period_stats.stats.cost_cents as u64;
+        total_subagent_cents += period_stats.stats.subagent_cost_cents as u64;
+        total_premium_hundredths += period_stats.stats.premium_hundredths as u64;
+        total_active_secs += period_stats.stats.active_secs as u64;
         total_cached += period_stats.stats.cached_tokens;
         total_input += period_stats.stats.input_tokens;
         total_output += period_stats.stats.output_tokens;
         total_reasoning += period_stats.stats.reasoning_tokens;
         total_tool_calls += period_stats.stats.tool_calls as u64;
         total_conversations += period_stats.conversations as u64;
+        let cache = CacheEfficiency::from_day(period_stats);
+        total_cache += cache;
 
         let mut models_vec: Vec<String> = period_stats
             .models
@@ -1642,10 +3391,7 @@ fn draw_aggregate_stats_table(
         // Create styled cells with colors matching original implementation
         let period_text = format_aggregate_period_for_display(period, aggregate_view_mode);
         let period_cell = if is_empty_row {
-            Line::from(Span::styled(
-                period_text,
-                Style::default().add_modifier(Modifier::DIM),
-            ))
+            Line::from(Span::styled(period_text, theme.dim()))
         } else {
             Line::from(Span::raw(period_text))
         };
@@ -1657,13 +3403,14 @@ fn draw_aggregate_stats_table(
             prec = format_options.cost_decimal_places
         );
         let cost_style = if is_empty_row {
-            Style::default().add_modifier(Modifier::DIM)
+            theme.dim()
         } else if color_costs {
-            Style::default().fg(cost_heat(period_stats.stats.cost_cents, best_cost_cents))
-        } else if i == best_cost_i {
-            Style::default().fg(Color::Red)
+            Style::default()
+                .fg(theme.cost_heat(period_stats.stats.cost_cents, bests.max_cost_cents))
+        } else if bests.cost == Some(period.as_str()) {
+            Style::default().fg(theme.best)
         } else {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(theme.cost)
         };
         let cost_cell = Line::from(Span::styled(cost_str, cost_style)).right_aligned();
 
@@ -1672,30 +3419,33 @@ fn draw_aggregate_stats_table(
         let cached_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.cached_tokens, format_options, tw),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_cached_tokens_i {
+        } else if bests.cached_tokens == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.cached_tokens, format_options, tw),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.cached_tokens, format_options, tw),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
         }
         .right_aligned();
 
+        let hit_cell =
+            Line::from(Span::styled(format_hit_ratio(&cache), theme.dim())).right_aligned();
+
         let input_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.input_tokens, format_options, tw),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_input_tokens_i {
+        } else if bests.input_tokens == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.input_tokens, format_options, tw),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::raw(format_number_fit(
@@ -1709,12 +3459,12 @@ fn draw_aggregate_stats_table(
         let output_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.output_tokens, format_options, tw),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_output_tokens_i {
+        } else if bests.output_tokens == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.output_tokens, format_options, tw),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::raw(format_number_fit(
@@ -1728,12 +3478,12 @@ fn draw_aggregate_stats_table(
         let reasoning_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.reasoning_tokens, format_options, tw),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_reasoning_tokens_i {
+        } else if bests.reasoning_tokens == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number_fit(period_stats.stats.reasoning_tokens, format_options, tw),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::raw(format_number_fit(
@@ -1747,12 +3497,12 @@ fn draw_aggregate_stats_table(
         let conv_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number(period_stats.conversations as u64, format_options),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_conversations_i {
+        } else if bests.conversations == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number(period_stats.conversations as u64, format_options),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::raw(format_number(
@@ -1765,65 +3515,79 @@ fn draw_aggregate_stats_table(
         let tool_cell = if is_empty_row {
             Line::from(Span::styled(
                 format_number(period_stats.stats.tool_calls as u64, format_options),
-                Style::default().add_modifier(Modifier::DIM),
+                theme.dim(),
             ))
-        } else if i == best_tool_calls_i {
+        } else if bests.tool_calls == Some(period.as_str()) {
             Line::from(Span::styled(
                 format_number(period_stats.stats.tool_calls as u64, format_options),
-                Style::default().fg(Color::Red),
+                Style::default().fg(theme.best),
             ))
         } else {
             Line::from(Span::styled(
                 format_number(period_stats.stats.tool_calls as u64, format_options),
-                Style::default().fg(Color::Green),
+                Style::default().fg(theme.count),
             ))
         }
         .right_aligned();
 
-        let models_cell = Line::from(Span::styled(
-            models,
-            Style::default().add_modifier(Modifier::DIM),
-        ));
+        let models_cell = Line::from(Span::styled(models, theme.dim()));
 
-        let apps_cell = Line::from(Span::styled(
-            apps,
-            Style::default().add_modifier(Modifier::DIM),
-        ));
+        let apps_cell = Line::from(Span::styled(apps, theme.dim()));
+
+        let subagent_cell = subagent_cost_cell(
+            period_stats.stats.subagent_cost_cents as u64,
+            format_options,
+            theme,
+            Style::default(),
+        );
+        let premium_cell = premium_cell(
+            period_stats.stats.premium_hundredths as u64,
+            format_options,
+            theme,
+            Style::default(),
+        );
+        let active_cell = active_cell(
+            period_stats.stats.active_secs as u64,
+            theme,
+            Style::default(),
+        );
+        let per_hour_cell = per_hour_cell(
+            period_stats.stats.cost_cents as u64,
+            period_stats.stats.active_secs as u64,
+            format_options,
+            theme,
+            Style::default(),
+        );
 
         // Create arrow indicator for currently selected row
         let arrow_cell = if table_state.selected() == Some(i) {
             Line::from(Span::styled(
                 "→",
-                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
             ))
         } else {
             Line::from(Span::raw(""))
         };
 
         let mut row_cells = vec![arrow_cell, period_cell, cost_cell];
-        if show("cached") {
-            row_cells.push(cached_cell);
-        }
-        if show("input") {
-            row_cells.push(input_cell);
-        }
-        if show("output") {
-            row_cells.push(output_cell);
-        }
-        if show("reason") {
-            row_cells.push(reasoning_cell);
-        }
-        if show("convs") {
-            row_cells.push(conv_cell);
-        }
-        if show("tools") {
-            row_cells.push(tool_cell);
-        }
-        if show("apps") {
-            row_cells.push(apps_cell);
-        }
-        if show("models") {
-            row_cells.push(models_cell);
+        for column in &columns {
+            row_cells.push(match column {
+                Column::Cached => cached_cell.clone(),
+                Column::Hit => hit_cell.clone(),
+                Column::Input => input_cell.clone(),
+                Column::Output => output_cell.clone(),
+                Column::Reason => reasoning_cell.clone(),
+                Column::Convs => conv_cell.clone(),
+                Column::Tools => tool_cell.clone(),
+                Column::Active => active_cell.clone(),
+                Column::PerHour => per_hour_cell.clone(),
+                Column::Subagents => subagent_cell.clone(),
+                Column::Premium => premium_cell.clone(),
+                Column::Apps => apps_cell.clone(),
+                Column::Models => models_cell.clone(),
+            });
         }
         rows.push(Row::new(row_cells));
     }
@@ -1860,41 +3624,24 @@ fn draw_aggregate_stats_table(
 
     // Add separator row before totals
     let token_sep = "─".repeat(TOKEN_COL_WIDTH as usize);
-    let dim = |s: String| {
-        Line::from(Span::styled(
-            s,
-            Style::default().add_modifier(Modifier::DIM),
-        ))
-    };
+    let dim = |s: String| Line::from(Span::styled(s, theme.dim()));
     let mut sep_cells = vec![
         dim(String::new()),
         dim("───────────".into()),
         dim("──────────".into()),
     ];
-    if show("cached") {
-        sep_cells.push(dim(token_sep.clone()));
-    }
-    if show("input") {
-        sep_cells.push(dim(token_sep.clone()));
-    }
-    if show("output") {
-        sep_cells.push(dim(token_sep.clone()));
-    }
-    if show("reason") {
-        sep_cells.push(dim(token_sep.clone()));
-    }
     let count_sep = "─".repeat(COUNT_COL_WIDTH as usize);
-    if show("convs") {
-        sep_cells.push(dim(count_sep.clone()));
-    }
-    if show("tools") {
-        sep_cells.push(dim(count_sep));
-    }
-    if show("apps") {
-        sep_cells.push(dim("─".repeat(all_apps_text.len().max(16))));
-    }
-    if show("models") {
-        sep_cells.push(dim("─".repeat(all_models_text.len().max(18))));
+    for column in &columns {
+        sep_cells.push(dim(match column {
+            Column::Cached | Column::Input | Column::Output | Column::Reason => token_sep.clone(),
+            Column::Hit => "─".repeat(HIT_COL_WIDTH as usize),
+            Column::Convs | Column::Tools => count_sep.clone(),
+            Column::Active => "─".repeat(ACTIVE_COL_WIDTH as usize),
+            Column::PerHour | Column::Subagents => "─".repeat(COST_COL_WIDTH as usize),
+            Column::Premium => "─".repeat(PREMIUM_COL_WIDTH as usize),
+            Column::Apps => "─".repeat(all_apps_text.len().max(16)),
+            Column::Models => "─".repeat(all_models_text.len().max(18)),
+        }));
     }
     rows.push(Row::new(sep_cells));
 
@@ -1906,7 +3653,9 @@ fn draw_aggregate_stats_table(
         if table_state.selected() == Some(rows.len()) {
             Line::from(Span::styled(
                 "→",
-                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
             ))
         } else {
             Line::from(Span::raw(""))
@@ -1926,81 +3675,62 @@ fn draw_aggregate_stats_table(
                 format_options.currency_symbol,
                 prec = format_options.cost_decimal_places
             ),
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.cost).add_modifier(Modifier::BOLD),
         ))
         .right_aligned(),
     ];
-    if show("cached") {
-        totals_cells.push(
-            Line::from(Span::styled(
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let dim_bold = theme.dim().add_modifier(Modifier::BOLD);
+    for column in &columns {
+        totals_cells.push(match column {
+            Column::Cached => Line::from(Span::styled(
                 format_number_fit(total_cached, format_options, tw),
-                Style::default()
-                    .add_modifier(Modifier::DIM)
-                    .add_modifier(Modifier::BOLD),
+                dim_bold,
             ))
             .right_aligned(),
-        );
-    }
-    if show("input") {
-        totals_cells.push(
-            Line::from(Span::styled(
+            Column::Hit => {
+                Line::from(Span::styled(format_hit_ratio(&total_cache), dim_bold)).right_aligned()
+            }
+            Column::Input => Line::from(Span::styled(
                 format_number_fit(total_input, format_options, tw),
-                Style::default().add_modifier(Modifier::BOLD),
+                bold,
             ))
             .right_aligned(),
-        );
-    }
-    if show("output") {
-        totals_cells.push(
-            Line::from(Span::styled(
+            Column::Output => Line::from(Span::styled(
                 format_number_fit(total_output, format_options, tw),
-                Style::default().add_modifier(Modifier::BOLD),
+                bold,
             ))
             .right_aligned(),
-        );
-    }
-    if show("reason") {
-        totals_cells.push(
-            Line::from(Span::styled(
+            Column::Reason => Line::from(Span::styled(
                 format_number_fit(total_reasoning, format_options, tw),
-                Style::default().add_modifier(Modifier::BOLD),
+                bold,
             ))
             .right_aligned(),
-        );
-    }
-    if show("convs") {
-        totals_cells.push(
-            Line::from(Span::styled(
+            Column::Convs => Line::from(Span::styled(
                 format_number(total_conversations, format_options),
-                Style::default().add_modifier(Modifier::BOLD),
+                bold,
             ))
             .right_aligned(),
-        );
-    }
-    if show("tools") {
-        totals_cells.push(
-            Line::from(Span::styled(
+            Column::Tools => Line::from(Span::styled(
                 format_number(total_tool_calls, format_options),
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
+                bold.fg(theme.count),
             ))
             .right_aligned(),
-        );
-    }
-    if show("apps") {
-        totals_cells.push(Line::from(Span::styled(
-            all_apps_text,
-            Style::default().add_modifier(Modifier::DIM),
-        )));
-    }
-    if show("models") {
-        totals_cells.push(Line::from(Span::styled(
-            all_models_text,
-            Style::default().add_modifier(Modifier::DIM),
-        )));
+            Column::Subagents => {
+                subagent_cost_cell(total_subagent_cents, format_options, theme, bold)
+            }
+            Column::Active => active_cell(total_active_secs, theme, bold),
+            Column::PerHour => per_hour_cell(
+                total_cost_cents,
+                total_active_secs,
+                format_options,
+                theme,
+                bold,
+            ),
+            Column::Premium => premium_cell(total_premium_hundredths, format_options, theme, bold),
+            Column::Apps => Line::from(Span::styled(all_apps_text.clone(), theme.dim())),
+            Column::Models => Line::from(Span::styled(all_models_text.clone(), theme.dim())),
+        });
     }
     rows.push(Row::new(totals_cells));
 
@@ -2008,38 +3738,28 @@ fn draw_aggregate_stats_table(
     let total_rows = rows.len();
 
     let mut widths = vec![
-        Constraint::Length(1),  // Arrow
-        Constraint::Length(11), // Date/Month
-        Constraint::Length(10), // Cost
+        Constraint::Length(1),              // Arrow
+        Constraint::Length(11),             // Date/Month
+        Constraint::Length(COST_COL_WIDTH), // Cost
     ];
-    if show("cached") {
-        widths.push(Constraint::Length(TOKEN_COL_WIDTH));
-    }
-    if show("input") {
-        widths.push(Constraint::Length(TOKEN_COL_WIDTH));
-    }
-    if show("output") {
-        widths.push(Constraint::Length(TOKEN_COL_WIDTH));
-    }
-    if show("reason") {
-        widths.push(Constraint::Length(TOKEN_COL_WIDTH));
-    }
-    if show("convs") {
-        widths.push(Constraint::Length(COUNT_COL_WIDTH));
-    }
-    if show("tools") {
-        widths.push(Constraint::Length(COUNT_COL_WIDTH));
-    }
-    if show("apps") {
-        widths.push(Constraint::Min(16));
-    }
-    if show("models") {
-        widths.push(Constraint::Min(10));
+    for column in &columns {
+        widths.push(match column {
+            Column::Cached | Column::Input | Column::Output | Column::Reason => {
+                Constraint::Length(TOKEN_COL_WIDTH)
+            }
+            Column::Hit => Constraint::Length(HIT_COL_WIDTH),
+            Column::Convs | Column::Tools => Constraint::Length(COUNT_COL_WIDTH),
+            Column::Active => Constraint::Length(ACTIVE_COL_WIDTH),
+            Column::PerHour | Column::Subagents => Constraint::Length(COST_COL_WIDTH),
+            Column::Premium => Constraint::Length(PREMIUM_COL_WIDTH),
+            Column::Apps => Constraint::Min(16),
+            Column::Models => Constraint::Min(10),
+        });
     }
     let table = Table::new(rows, widths)
         .header(header)
         .block(Block::default().title(""))
-        .row_highlight_style(Style::default().fg(accent))
+        .row_highlight_style(Style::default().fg(theme.accent))
         .column_spacing(2);
 
     frame.render_stateful_widget(table, area, table_state);
@@ -2057,36 +3777,41 @@ fn draw_session_stats_table(
     table_state: &mut TableState,
     window_offset: &mut usize,
     period_filter: Option<PeriodFilter>,
+    search: &str,
     sort_reversed: bool,
+    analyzer_styles: &AnalyzerStyles,
+    columns: &[Column],
+    theme: &Theme,
 ) {
-    let header = Row::new(vec![
+    let has_premium = sessions
+        .iter()
+        .any(|session| session.stats.premium_hundredths > 0);
+    let has_active = sessions.iter().any(|session| session.stats.active_secs > 0);
+    let columns: Vec<Column> = columns
+        .iter()
+        .copied()
+        .filter(|column| column.in_sessions())
+        .filter(|column| *column != Column::Premium || has_premium)
+        .filter(|column| !matches!(column, Column::Active | Column::PerHour) || has_active)
+        .collect();
+    let mut header_cells = vec![
         Cell::new(""),
+        Cell::new("Tool"),
         Cell::new("Session"),
         Cell::new("Started"),
         Cell::new(Text::from("Cost").right_aligned()),
-        Cell::new(Text::from("Cached Tks").right_aligned()),
-        Cell::new(Text::from("Inp Tks").right_aligned()),
-        Cell::new(Text::from("Outp Tks").right_aligned()),
-        Cell::new(Text::from("Reason Tks").right_aligned()),
-        Cell::new(Text::from("Tools").right_aligned()),
-        Cell::new("Models"),
-    ])
-    .style(Style::default().add_modifier(Modifier::BOLD))
-    .height(1);
+    ];
+    for column in &columns {
+        header_cells.push(match column {
+            Column::Models => Cell::new(column.label()),
+            _ => Cell::new(Text::from(column.label()).right_aligned()),
+        });
+    }
+    let header = Row::new(header_cells)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .height(1);
 
-    let filtered_sessions: Vec<&SessionAggregate> = {
-        let mut sessions: Vec<_> = match period_filter {
-            Some(filter) => sessions
-                .iter()
-                .filter(|session| filter.matches_compact_date(session.date))
-                .collect(),
-            None => sessions.iter().collect(),
-        };
-        if sort_reversed {
-            sessions.reverse();
-        }
-        sessions
-    };
+    let filtered_sessions = visible_sessions(sessions, period_filter, search, sort_reversed);
 
     let total_session_rows = filtered_sessions.len();
     // Total rows in the table body: sessions + optional separator + totals row
@@ -2135,6 +3860,9 @@ fn draw_session_stats_table(
     let mut best_tool_calls_i: Option<usize> = None;
 
     let mut total_cost_cents: u64 = 0;
+    let mut total_subagent_cents: u64 = 0;
+    let mut total_premium_hundredths: u64 = 0;
+    let mut total_active_secs: u64 = 0;
     let mut total_input_tokens: u64 = 0;
     let mut total_output_tokens: u64 = 0;
     let mut total_cached_tokens: u64 = 0;
@@ -2194,6 +3922,9 @@ fn draw_session_stats_table(
         }
 
         total_cost_cents += session.stats.cost_cents as u64;
+        total_subagent_cents += session.stats.subagent_cost_cents as u64;
+        total_premium_hundredths += session.stats.premium_hundredths as u64;
+        total_active_secs += session.stats.active_secs as u64;
         total_input_tokens += session.stats.input_tokens;
         total_output_tokens += session.stats.output_tokens;
         total_cached_tokens += session.stats.cached_tokens;
@@ -2232,10 +3963,7 @@ fn draw_session_stats_table(
             let local_ts = session.first_timestamp.with_timezone(&Local);
             let ts_str = local_ts.format("%Y-%m-%d %H:%M").to_string();
 
-            let session_cell = Line::from(Span::styled(
-                short_id,
-                Style::default().add_modifier(Modifier::DIM),
-            ));
+            let session_cell = Line::from(Span::styled(short_id, theme.dim()));
 
             let started_cell = Line::from(Span::raw(ts_str));
 
@@ -2247,7 +3975,7 @@ fn draw_session_stats_table(
                         session.stats.cost(),
                         prec = format_options.cost_decimal_places
                     ),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::styled(
@@ -2257,7 +3985,7 @@ fn draw_session_stats_table(
                         session.stats.cost(),
                         prec = format_options.cost_decimal_places
                     ),
-                    Style::default().fg(Color::Yellow),
+                    Style::default().fg(theme.cost),
                 ))
             }
             .right_aligned();
@@ -2267,12 +3995,12 @@ fn draw_session_stats_table(
             let cached_cell = if best_cached_tokens_i == Some(i) {
                 Line::from(Span::styled(
                     format_number_fit(session.stats.cached_tokens, format_options, tw),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::styled(
                     format_number_fit(session.stats.cached_tokens, format_options, tw),
-                    Style::default().add_modifier(Modifier::DIM),
+                    theme.dim(),
                 ))
             }
             .right_aligned();
@@ -2280,7 +4008,7 @@ fn draw_session_stats_table(
             let input_cell = if best_input_tokens_i == Some(i) {
                 Line::from(Span::styled(
                     format_number_fit(session.stats.input_tokens, format_options, tw),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::raw(format_number_fit(
@@ -2294,7 +4022,7 @@ fn draw_session_stats_table(
             let output_cell = if best_output_tokens_i == Some(i) {
                 Line::from(Span::styled(
                     format_number_fit(session.stats.output_tokens, format_options, tw),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::raw(format_number_fit(
@@ -2308,7 +4036,7 @@ fn draw_session_stats_table(
             let reasoning_cell = if best_reasoning_tokens_i == Some(i) {
                 Line::from(Span::styled(
                     format_number_fit(session.stats.reasoning_tokens, format_options, tw),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::raw(format_number_fit(
@@ -2322,12 +4050,12 @@ fn draw_session_stats_table(
             let tools_cell = if best_tool_calls_i == Some(i) {
                 Line::from(Span::styled(
                     format_number(session.stats.tool_calls, format_options),
-                    Style::default().fg(Color::Red),
+                    Style::default().fg(theme.best),
                 ))
             } else {
                 Line::from(Span::styled(
                     format_number(session.stats.tool_calls, format_options),
-                    Style::default().add_modifier(Modifier::DIM),
+                    theme.dim(),
                 ))
             }
             .right_aligned();
@@ -2342,164 +4070,1559 @@ fn draw_session_stats_table(
             models_vec.dedup();
             let models_text = models_vec.join(", ");
 
-            let models_cell = Line::from(Span::styled(
-                models_text,
-                Style::default().add_modifier(Modifier::DIM),
+            let models_cell = Line::from(Span::styled(models_text, theme.dim()));
+
+            let tool_cell = Line::from(Span::styled(
+                analyzer_styles.label(&session.analyzer_name),
+                Style::default().fg(analyzer_styles.color(&session.analyzer_name, theme)),
             ));
 
-            let row = Row::new(vec![
+            let mut row_cells = vec![
                 Line::from(Span::raw("")),
+                tool_cell,
                 session_cell,
                 started_cell,
                 cost_cell,
-                cached_cell,
-                input_cell,
-                output_cell,
-                reasoning_cell,
-                tools_cell,
-                models_cell,
-            ]);
+            ];
+            for column in &columns {
+                row_cells.push(match column {
+                    Column::Cached => cached_cell.clone(),
+                    Column::Input => input_cell.clone(),
+                    Column::Output => output_cell.clone(),
+                    Column::Reason => reasoning_cell.clone(),
+                    Column::Tools => tools_cell.clone(),
+                    Column::Subagents => subagent_cost_cell(
+                        session.stats.subagent_cost_cents as u64,
+                        format_options,
+                        theme,
+                        Style::default(),
+                    ),
+                    Column::Active => {
+                        active_cell(session.stats.active_secs as u64, theme, Style::default())
+                    }
+                    Column::PerHour => per_hour_cell(
+                        session.stats.cost_cents as u64,
+                        session.stats.active_secs as u64,
+                        format_options,
+                        theme,
+                        Style::default(),
+                    ),
+                    Column::Premium => premium_cell(
+                        session.stats.premium_hundredths as u64,
+                        format_options,
+                        theme,
+                        Style::default(),
+                    ),
+                    Column::Models => models_cell.clone(),
+                    Column::Hit | Column::Convs | Column::Apps => continue,
+                });
+            }
 
-            rows.push(row);
+            rows.push(Row::new(row_cells));
         } else if i == total_session_rows && total_session_rows > 0 {
             // Separator row
             let token_sep = "─".repeat(TOKEN_COL_WIDTH as usize);
-            let separator_row = Row::new(vec![
-                Line::from(Span::styled(
-                    "",
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
-                Line::from(Span::styled(
-                    "────────────────────────────────",
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
-                Line::from(Span::styled(
-                    "─────────────────",
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
-                Line::from(Span::styled(
-                    "──────────",
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
-                Line::from(Span::styled(
-                    token_sep.clone(),
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
-                Line::from(Span::styled(
-                    token_sep.clone(),
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
+            let dim = |s: String| Line::from(Span::styled(s, theme.dim()));
+            let mut separator_cells = vec![
+                dim(String::new()),
+                dim("─".repeat(SESSION_TOOL_COL_WIDTH as usize)),
+                dim("────────────────────────────────".into()),
+                dim("─────────────────".into()),
+                dim("──────────".into()),
+            ];
+            for column in &columns {
+                separator_cells.push(dim(match column {
+                    Column::Tools => "─".repeat(COUNT_COL_WIDTH as usize),
+                    Column::Active => "─".repeat(ACTIVE_COL_WIDTH as usize),
+                    Column::PerHour | Column::Subagents => "─".repeat(COST_COL_WIDTH as usize),
+                    Column::Premium => "─".repeat(PREMIUM_COL_WIDTH as usize),
+                    Column::Models => "────────────".into(),
+                    _ => token_sep.clone(),
+                }));
+            }
+            let separator_row = Row::new(separator_cells);
+            rows.push(separator_row);
+        } else {
+            // Totals row
+            let total_cost = total_cost_cents as f64 / 100.0;
+            let tw = TOKEN_COL_WIDTH as usize;
+            let mut totals_cells = vec![
+                Line::from(Span::raw("")),
+                Line::from(Span::raw("")),
                 Line::from(Span::styled(
-                    token_sep.clone(),
-                    Style::default().add_modifier(Modifier::DIM),
+                    format!("Total ({} sessions)", total_session_rows),
+                    Style::default().add_modifier(Modifier::BOLD),
                 )),
+                Line::from(Span::raw("")),
                 Line::from(Span::styled(
-                    token_sep,
-                    Style::default().add_modifier(Modifier::DIM),
-                )),
+                    format!(
+                        "{}{total_cost:.prec$}",
+                        format_options.currency_symbol,
+                        prec = format_options.cost_decimal_places
+                    ),
+                    Style::default().fg(theme.cost).add_modifier(Modifier::BOLD),
+                ))
+                .right_aligned(),
+            ];
+            let bold = Style::default().add_modifier(Modifier::BOLD);
+            for column in &columns {
+                totals_cells.push(match column {
+                    Column::Cached => Line::from(Span::styled(
+                        format_number_fit(total_cached_tokens, format_options, tw),
+                        theme.dim().add_modifier(Modifier::BOLD),
+                    ))
+                    .right_aligned(),
+                    Column::Input => Line::from(Span::styled(
+                        format_number_fit(total_input_tokens, format_options, tw),
+                        bold,
+                    ))
+                    .right_aligned(),
+                    Column::Output => Line::from(Span::styled(
+                        format_number_fit(total_output_tokens, format_options, tw),
+                        bold,
+                    ))
+                    .right_aligned(),
+                    Column::Reason => Line::from(Span::styled(
+                        format_number_fit(total_reasoning_tokens, format_options, tw),
+                        bold,
+                    ))
+                    .right_aligned(),
+                    Column::Tools => Line::from(Span::styled(
+                        format_number(total_tool_calls, format_options),
+                        bold.fg(theme.count),
+                    ))
+                    .right_aligned(),
+                    Column::Subagents => {
+                        subagent_cost_cell(total_subagent_cents, format_options, theme, bold)
+                    }
+                    Column::Active => active_cell(total_active_secs, theme, bold),
+                    Column::PerHour => per_hour_cell(
+                        total_cost_cents,
+                        total_active_secs,
+                        format_options,
+                        theme,
+                        bold,
+                    ),
+                    Column::Premium => {
+                        premium_cell(total_premium_hundredths, format_options, theme, bold)
+                    }
+                    Column::Models => {
+                        Line::from(Span::styled(all_models_text.clone(), theme.dim()))
+                    }
+                    Column::Hit | Column::Convs | Column::Apps => continue,
+                });
+            }
+            let totals_row = Row::new(totals_cells);
+            rows.push(totals_row);
+        }
+    }
+
+    let mut render_state = TableState::default();
+    render_state.select(Some(selected_global.saturating_sub(window_start)));
+
+    let mut widths = vec![
+        Constraint::Length(1),                      // Arrow / highlight symbol space
+        Constraint::Length(SESSION_TOOL_COL_WIDTH), // Tool
+        Constraint::Length(32),                     // Session (increased width for name)
+        Constraint::Length(17),                     // Started
+        Constraint::Length(COST_COL_WIDTH),         // Cost
+    ];
+    for column in &columns {
+        widths.push(match column {
+            Column::Tools => Constraint::Length(COUNT_COL_WIDTH),
+            Column::Active => Constraint::Length(ACTIVE_COL_WIDTH),
+            Column::PerHour | Column::Subagents => Constraint::Length(COST_COL_WIDTH),
+            Column::Premium => Constraint::Length(PREMIUM_COL_WIDTH),
+            Column::Models => Constraint::Min(10),
+            _ => Constraint::Length(TOKEN_COL_WIDTH),
+        });
+    }
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().title(""))
+        .highlight_symbol("→")
+        .row_highlight_style(Style::new().blue())
+        .column_spacing(2);
+
+    frame.render_stateful_widget(table, area, &mut render_state);
+}
+
+/// Per-model breakdown: one row per model with its share of the tab's cost.
+/// Returns whether any listed model uses estimated pricing.
+fn draw_model_stats_table(
+    frame: &mut Frame,
+    area: Rect,
+    models: &[ModelStats],
+    format_options: &NumberFormatOptions,
+    table_state: &mut TableState,
+    sort_reversed: bool,
+    theme: &Theme,
+) -> bool {
+    let header = Row::new(vec![
+        Cell::new(""),
+        Cell::new("Model"),
+        Cell::new("Provider"),
+        Cell::new(Text::from("Msgs").right_aligned()),
+        Cell::new(Text::from("Cost").right_aligned()),
+        Cell::new(Text::from("Share").right_aligned()),
+        Cell::new(Text::from("Cached Tks").right_aligned()),
+        Cell::new(Text::from("Hit %").right_aligned()),
+        Cell::new(Text::from("Saved").right_aligned()),
+        Cell::new(Text::from("Inp Tks").right_aligned()),
+        Cell::new(Text::from("Outp Tks").right_aligned()),
+        Cell::new(Text::from("Reason Tks").right_aligned()),
+        Cell::new(Text::from("Tools").right_aligned()),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(1);
+
+    let mut totals = ModelStats::new(String::new());
+    for model in models {
+        totals.add_model_stats(model);
+    }
+
+    let ordered: Vec<&ModelStats> = if sort_reversed {
+        models.iter().rev().collect()
+    } else {
+        models.iter().collect()
+    };
+
+    let tw = TOKEN_COL_WIDTH as usize;
+    let format_cost = |cost: f64| {
+        format!(
+            "{}{:.prec$}",
+            format_options.currency_symbol,
+            cost,
+            prec = format_options.cost_decimal_places
+        )
+    };
+
+    let mut has_estimated = false;
+    let mut rows: Vec<Row> = ordered
+        .iter()
+        .map(|model| {
+            let estimated = is_model_estimated(&model.model);
+            has_estimated |= estimated;
+            let share = if totals.cost > 0.0 {
+                model.cost / totals.cost * 100.0
+            } else {
+                0.0
+            };
+            Row::new(vec![
+                Line::from(""),
+                Line::from(if estimated {
+                    format!("{}*", model.model)
+                } else {
+                    model.model.clone()
+                }),
                 Line::from(Span::styled(
-                    "─".repeat(COUNT_COL_WIDTH as usize),
-                    Style::default().add_modifier(Modifier::DIM),
+                    Provider::for_model(&model.model).label(),
+                    theme.dim(),
                 )),
+                Line::from(format_number(model.message_count, format_options)).right_aligned(),
                 Line::from(Span::styled(
-                    "────────────",
-                    Style::default().add_modifier(Modifier::DIM),
+                    format_cost(model.cost),
+                    Style::default().fg(theme.cost),
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(format!("{share:.1}%"), theme.dim())).right_aligned(),
+                Line::from(Span::styled(
+                    format_number_fit(model.cached_tokens, format_options, tw),
+                    theme.dim(),
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(
+                    format_hit_ratio(&CacheEfficiency::from_model(model)),
+                    theme.dim(),
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(
+                    format_cost(model.saved_cost),
+                    Style::default().fg(theme.success),
+                ))
+                .right_aligned(),
+                Line::from(format_number_fit(model.input_tokens, format_options, tw))
+                    .right_aligned(),
+                Line::from(format_number_fit(model.output_tokens, format_options, tw))
+                    .right_aligned(),
+                Line::from(format_number_fit(
+                    model.reasoning_tokens,
+                    format_options,
+                    tw,
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(
+                    format_number(model.tool_calls, format_options),
+                    theme.dim(),
+                ))
+                .right_aligned(),
+            ])
+        })
+        .collect();
+
+    if !models.is_empty() {
+        let dim = theme.dim();
+        let token_sep = "─".repeat(TOKEN_COL_WIDTH as usize);
+        rows.push(Row::new(vec![
+            Line::from(""),
+            Line::from(Span::styled("─".repeat(32), dim)),
+            Line::from(Span::styled("─".repeat(12), dim)),
+            Line::from(Span::styled("─".repeat(COUNT_COL_WIDTH as usize), dim)),
+            Line::from(Span::styled("─".repeat(10), dim)),
+            Line::from(Span::styled("─".repeat(6), dim)),
+            Line::from(Span::styled(token_sep.clone(), dim)),
+            Line::from(Span::styled("─".repeat(HIT_COL_WIDTH as usize), dim)),
+            Line::from(Span::styled("─".repeat(10), dim)),
+            Line::from(Span::styled(token_sep.clone(), dim)),
+            Line::from(Span::styled(token_sep.clone(), dim)),
+            Line::from(Span::styled(token_sep, dim)),
+            Line::from(Span::styled("─".repeat(COUNT_COL_WIDTH as usize), dim)),
+        ]));
+    }
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    rows.push(Row::new(vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Total ({} models)", models.len()),
+            bold,
+        )),
+        Line::from(Span::styled(
+            format!("{} providers", aggregate_provider_stats(models).len()),
+            theme.dim().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format_number(totals.message_count, format_options),
+            bold,
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(format_cost(totals.cost), bold.fg(theme.cost))).right_aligned(),
+        Line::from(""),
+        Line::from(Span::styled(
+            format_number_fit(totals.cached_tokens, format_options, tw),
+            theme.dim().add_modifier(Modifier::BOLD),
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_hit_ratio(&CacheEfficiency::from_model(&totals)),
+            theme.dim().add_modifier(Modifier::BOLD),
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_cost(totals.saved_cost),
+            bold.fg(theme.success),
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_number_fit(totals.input_tokens, format_options, tw),
+            bold,
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_number_fit(totals.output_tokens, format_options, tw),
+            bold,
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_number_fit(totals.reasoning_tokens, format_options, tw),
+            bold,
+        ))
+        .right_aligned(),
+        Line::from(Span::styled(
+            format_number(totals.tool_calls, format_options),
+            bold.fg(theme.count),
+        ))
+        .right_aligned(),
+    ]));
+
+    clamp_table_selection(table_state, rows.len());
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),               // Arrow / highlight symbol space
+            Constraint::Min(20),                 // Model
+            Constraint::Length(12),              // Provider
+            Constraint::Length(COUNT_COL_WIDTH), // Msgs
+            Constraint::Length(10),              // Cost
+            Constraint::Length(6),               // Share
+            Constraint::Length(TOKEN_COL_WIDTH), // Cached Tks
+            Constraint::Length(HIT_COL_WIDTH),   // Hit %
+            Constraint::Length(10),              // Saved
+            Constraint::Length(TOKEN_COL_WIDTH), // Input
+            Constraint::Length(TOKEN_COL_WIDTH), // Output
+            Constraint::Length(TOKEN_COL_WIDTH), // Reason Tks
+            Constraint::Length(COUNT_COL_WIDTH), // Tools
+        ],
+    )
+    .header(header)
+    .block(Block::default().title(""))
+    .highlight_symbol("→")
+    .row_highlight_style(Style::new().blue())
+    .column_spacing(2);
+
+    frame.render_stateful_widget(table, area, table_state);
+    has_estimated
+}
+
+/// Label and tool counts for the row selected in the aggregate or session
+/// table, or `None` while usage is loading or on the separator/totals rows.
+fn selected_tool_counts(
+    view: &AnalyzerStatsView,
+    panel: &ToolPanel,
+    ui_state: &UiState,
+    index: usize,
+) -> Option<(String, ToolCounts)> {
+    let Some(Ok(usage)) = panel.result() else {
+        return None;
+    };
+    match ui_state.stats_view_mode {
+        StatsViewMode::Aggregate => {
+            let key = aggregate_key_at(
+                view,
+                ui_state.aggregate_view_mode,
+                index,
+                ui_state.hide_empty_periods,
+                ui_state.sort_reversed,
+            )?;
+            let filter = PeriodFilter::from_period_key(&key, ui_state.aggregate_view_mode)?;
+            Some((
+                format_aggregate_period_for_display(&key, ui_state.aggregate_view_mode),
+                usage.for_days(|date| filter.matches_compact_date(date)),
+            ))
+        }
+        StatsViewMode::Session => {
+            let sessions = visible_sessions(
+                &view.session_aggregates,
+                ui_state.session_period_filters[ui_state.selected_tab],
+                ui_state.session_search,
+                ui_state.sort_reversed,
+            );
+            let session = sessions.get(index)?;
+            Some((
+                session
+                    .session_name
+                    .clone()
+                    .unwrap_or_else(|| session.session_id.clone()),
+                usage
+                    .by_session
+                    .get(&session.session_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Files that failed to parse or had entries skipped, drawn over the main
+/// table while `E` is toggled on.
+fn draw_diagnostics(
+    frame: &mut Frame,
+    area: Rect,
+    issues: &[ParseIssue],
+    scroll: usize,
+    theme: &Theme,
+) {
+    let block = Block::bordered().title(format!(" Parse problems ({}) ", issues.len()));
+    let mut lines = Vec::new();
+    if issues.is_empty() {
+        lines.push(Line::from("Every file parsed cleanly").style(theme.dim()));
+    }
+    for issue in issues.iter().skip(scroll) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{} ", issue.analyzer),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(issue.path.display().to_string()),
+            Span::styled(
+                format!(
+                    "  {} {}",
+                    issue.count,
+                    if issue.count == 1 {
+                        "problem"
+                    } else {
+                        "problems"
+                    }
+                ),
+                Style::default().fg(theme.warning),
+            ),
+        ]));
+        lines.push(Line::from(format!("  {}", issue.last_error)).style(theme.dim()));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_column_chooser(frame: &mut Frame, area: Rect, chooser: &ColumnChooser, theme: &Theme) {
+    let block = Block::bordered().title(" Columns ");
+    let mut lines = vec![
+        Line::from("Space: show/hide  J/K: move  Enter: apply  Esc: cancel").style(theme.dim()),
+        Line::from(""),
+    ];
+    for (index, (column, shown)) in chooser.layout.entries().iter().enumerate() {
+        let selected = index == chooser.selected;
+        let mut style = if *shown {
+            Style::default()
+        } else {
+            theme.dim()
+        };
+        if selected {
+            style = style.fg(theme.accent).add_modifier(Modifier::BOLD);
+        }
+        let note = if column.in_sessions() {
+            ""
+        } else {
+            "  (periods only)"
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "→ " } else { "  " }, style),
+            Span::styled(
+                format!("[{}] {}", if *shown { "x" } else { " " }, column.label()),
+                style,
+            ),
+            Span::styled(note, theme.dim()),
+        ]));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_tool_panel(
+    frame: &mut Frame,
+    area: Rect,
+    panel: &ToolPanel,
+    selection: Option<(String, ToolCounts)>,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+) {
+    let block = Block::bordered().title(" Tools ");
+    let lines: Vec<Line> = match (panel.result(), selection) {
+        (None, _) => vec![Line::from("Loading tool usage…").style(theme.dim())],
+        (Some(Err(e)), _) => {
+            vec![Line::from(format!("Failed to load: {e}")).style(Style::default().fg(theme.error))]
+        }
+        (Some(Ok(_)), None) => {
+            vec![Line::from("Select a row").style(theme.dim())]
+        }
+        (Some(Ok(usage)), Some((label, counts))) => {
+            let inner_width = area.width.saturating_sub(2) as usize;
+            let rows = counts.rows();
+            let max = rows.iter().map(|(_, n)| *n).max().unwrap_or(0);
+            let mut lines = vec![
+                Line::from(Span::styled(
+                    label,
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Line::from(format!(
+                    "{} tool calls",
+                    format_number(counts.tool_calls, format_options)
                 )),
+                Line::from(""),
+            ];
+            for (name, count) in rows {
+                lines.push(count_bar_line(
+                    name,
+                    count,
+                    max,
+                    inner_width,
+                    format_options,
+                    theme,
+                ));
+            }
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Lines by file type",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            let composition = counts.composition();
+            let total: u64 = composition.iter().map(|(_, n)| n).sum();
+            if total == 0 {
+                lines.push(Line::from("No file activity").style(theme.dim()));
+            } else {
+                lines.push(composition_bar(&composition, inner_width, theme));
+                for ((name, count), color) in composition.iter().zip(theme.categories) {
+                    if *count == 0 {
+                        continue;
+                    }
+                    let value = format_number(*count, format_options);
+                    let share = format!("{:>3.0}%", *count as f64 * 100.0 / total as f64);
+                    let padding = inner_width.saturating_sub(2 + 8 + share.len() + 1 + value.len());
+                    lines.push(Line::from(vec![
+                        Span::styled("■ ", Style::default().fg(color)),
+                        Span::raw(format!("{name:<8}")),
+                        Span::raw(share),
+                        Span::raw(" ".repeat(padding + 1)),
+                        Span::styled(value, theme.dim()),
+                    ]));
+                }
+            }
+            let overall = usage.for_days(|_| true).composition();
+            if overall.iter().any(|(_, n)| *n > 0) {
+                lines.push(Line::from(Span::styled("Overall", theme.dim())));
+                lines.push(composition_bar(&overall, inner_width, theme));
+            }
+
+            let commands = counts.command_rows();
+            if !commands.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    "Terminal commands by kind",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                let max = commands.iter().map(|(_, n)| *n).max().unwrap_or(0);
+                for (name, count) in commands {
+                    lines.push(count_bar_line(
+                        name,
+                        count,
+                        max,
+                        inner_width,
+                        format_options,
+                        theme,
+                    ));
+                }
+            }
+
+            let languages = crate::utils::languages(&counts.file_extensions);
+            if !languages.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    "Languages",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                let total: u64 = languages.iter().map(|(_, n)| n).sum();
+                for (name, count) in languages.iter().take(MAX_LANGUAGE_ROWS) {
+                    let value = format_number(*count, format_options);
+                    let share = format!("{:>3.0}%", *count as f64 * 100.0 / total as f64);
+                    let padding = inner_width.saturating_sub(12 + share.len() + 1 + value.len());
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("{name:<12}")),
+                        Span::raw(share),
+                        Span::raw(" ".repeat(padding + 1)),
+                        Span::styled(value, theme.dim()),
+                    ]));
+                }
+                let rest: u64 = languages
+                    .iter()
+                    .skip(MAX_LANGUAGE_ROWS)
+                    .map(|(_, n)| n)
+                    .sum();
+                if rest > 0 {
+                    lines.push(
+                        Line::from(format!(
+                            "{} more, {:.0}%",
+                            languages.len() - MAX_LANGUAGE_ROWS,
+                            rest as f64 * 100.0 / total as f64
+                        ))
+                        .style(theme.dim()),
+                    );
+                }
+            }
+
+            let servers = counts.mcp_by_cost();
+            if !servers.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled(
+                    "MCP servers",
+                    Style::default().add_modifier(Modifier::BOLD),
+                )));
+                for (server, usage) in servers {
+                    let calls = format!(
+                        "{} {}",
+                        format_number(usage.calls, format_options),
+                        if usage.calls == 1 { "call" } else { "calls" }
+                    );
+                    let cost = format!(
+                        "{}{:.prec$}",
+                        format_options.currency_symbol,
+                        usage.cost,
+                        prec = format_options.cost_decimal_places
+                    );
+                    let tokens = format_number(usage.tokens.round() as u64, format_options);
+                    let details = format!("{calls}  {tokens} tok  {cost}");
+                    let padding = inner_width.saturating_sub(server.len() + details.len());
+                    lines.push(Line::from(vec![
+                        Span::raw(server.to_string()),
+                        Span::raw(" ".repeat(padding.max(1))),
+                        Span::styled(details, theme.dim()),
+                    ]));
+                }
+            }
+            lines
+        }
+    };
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// A tools pane row: label, a bar scaled to `max`, and the count.
+fn count_bar_line(
+    name: &str,
+    count: u64,
+    max: u64,
+    inner_width: usize,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+) -> Line<'static> {
+    let value = format_number(count, format_options);
+    let bar_width = inner_width.saturating_sub(18 + value.len() + 1);
+    let filled = if max == 0 {
+        0
+    } else {
+        ((count as f64 / max as f64) * bar_width as f64).round() as usize
+    };
+    let style = if count == 0 {
+        theme.dim()
+    } else {
+        Style::default()
+    };
+    Line::from(vec![
+        Span::styled(format!("{name:<18}"), style),
+        Span::styled("█".repeat(filled), Style::default().fg(theme.accent)),
+        Span::raw(" ".repeat(bar_width - filled + 1)),
+        Span::styled(value, style),
+    ])
+}
+
+/// Languages listed in the tools pane before the rest are summed up.
+const MAX_LANGUAGE_ROWS: usize = 6;
+
+/// One-line stacked bar of the file categories' shares of touched lines.
+fn composition_bar(
+    composition: &[(&'static str, u64); 6],
+    width: usize,
+    theme: &Theme,
+) -> Line<'static> {
+    let values: Vec<u64> = composition.iter().map(|(_, n)| *n).collect();
+    Line::from(
+        aggregation::split_widths(&values, width)
+            .into_iter()
+            .zip(theme.categories)
+            .filter(|(cells, _)| *cells > 0)
+            .map(|(cells, color)| Span::styled("█".repeat(cells), Style::default().fg(color)))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn draw_session_detail(
+    frame: &mut Frame,
+    area: Rect,
+    detail: &mut SessionDetail,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+) {
+    let [title_area, body_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+    let count = match detail.messages.result() {
+        Some(Ok(messages)) if messages.len() == 1 => "  1 message".to_string(),
+        Some(Ok(messages)) => format!("  {} messages", messages.len()),
+        _ => String::new(),
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                detail.title.clone(),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(count, theme.dim()),
+        ])),
+        title_area,
+    );
+
+    let messages = match detail.messages.result() {
+        None => {
+            frame.render_widget(
+                Paragraph::new("Loading messages…").style(Style::default().fg(theme.warning)),
+                body_area,
+            );
+            return;
+        }
+        Some(Err(error)) => {
+            frame.render_widget(
+                Paragraph::new(format!("Failed to load session: {error}"))
+                    .style(Style::default().fg(theme.error)),
+                body_area,
+            );
+            return;
+        }
+        Some(Ok(messages)) => messages,
+    };
+
+    let header = Row::new(vec![
+        Cell::new(""),
+        Cell::new("Time"),
+        Cell::new("Role"),
+        Cell::new("Model"),
+        Cell::new(Text::from("Cost").right_aligned()),
+        Cell::new(Text::from("Cached Tks").right_aligned()),
+        Cell::new(Text::from("Inp Tks").right_aligned()),
+        Cell::new(Text::from("Outp Tks").right_aligned()),
+        Cell::new(Text::from("Reason Tks").right_aligned()),
+        Cell::new(Text::from("Tools").right_aligned()),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(1);
+
+    let tw = TOKEN_COL_WIDTH as usize;
+    let rows: Vec<Row> = messages
+        .iter()
+        .map(|msg| {
+            let (role, role_style) = match msg.role {
+                MessageRole::User => ("user", theme.dim()),
+                MessageRole::Assistant => ("assistant", Style::default()),
+            };
+            Row::new(vec![
+                Line::from(""),
+                Line::from(
+                    msg.date
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                ),
+                Line::from(Span::styled(role, role_style)),
+                Line::from(msg.model.clone().unwrap_or_default()),
+                Line::from(Span::styled(
+                    format!(
+                        "{}{:.prec$}",
+                        format_options.currency_symbol,
+                        msg.stats.cost,
+                        prec = format_options.cost_decimal_places
+                    ),
+                    Style::default().fg(theme.cost),
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(
+                    format_number_fit(msg.stats.cached_tokens, format_options, tw),
+                    theme.dim(),
+                ))
+                .right_aligned(),
+                Line::from(format_number_fit(
+                    msg.stats.input_tokens,
+                    format_options,
+                    tw,
+                ))
+                .right_aligned(),
+                Line::from(format_number_fit(
+                    msg.stats.output_tokens,
+                    format_options,
+                    tw,
+                ))
+                .right_aligned(),
+                Line::from(format_number_fit(
+                    msg.stats.reasoning_tokens,
+                    format_options,
+                    tw,
+                ))
+                .right_aligned(),
+                Line::from(format_number(msg.stats.tool_calls as u64, format_options))
+                    .right_aligned(),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Length(19),              // Time
+            Constraint::Length(9),               // Role
+            Constraint::Fill(1),                 // Model
+            Constraint::Length(10),              // Cost
+            Constraint::Length(TOKEN_COL_WIDTH), // Cached Tks
+            Constraint::Length(TOKEN_COL_WIDTH), // Inp Tks
+            Constraint::Length(TOKEN_COL_WIDTH), // Outp Tks
+            Constraint::Length(TOKEN_COL_WIDTH), // Reason Tks
+            Constraint::Length(COUNT_COL_WIDTH), // Tools
+        ],
+    )
+    .header(header)
+    .highlight_symbol("→")
+    .row_highlight_style(Style::new().blue())
+    .column_spacing(2);
+
+    frame.render_stateful_widget(table, body_area, &mut detail.table_state);
+}
+
+fn draw_day_detail(
+    frame: &mut Frame,
+    area: Rect,
+    detail: &mut DayDetail,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+) {
+    let [title_area, body_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            detail.title.clone(),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )),
+        title_area,
+    );
+
+    let hours = match detail.hours.result() {
+        None => {
+            frame.render_widget(
+                Paragraph::new("Loading messages…").style(Style::default().fg(theme.warning)),
+                body_area,
+            );
+            return;
+        }
+        Some(Err(error)) => {
+            frame.render_widget(
+                Paragraph::new(format!("Failed to load messages: {error}"))
+                    .style(Style::default().fg(theme.error)),
+                body_area,
+            );
+            return;
+        }
+        Some(Ok(hours)) => hours,
+    };
+
+    let header = Row::new(vec![
+        Cell::new(""),
+        Cell::new("Hour"),
+        Cell::new(Text::from("Msgs").right_aligned()),
+        Cell::new(Text::from("Replies").right_aligned()),
+        Cell::new(Text::from("Tokens").right_aligned()),
+        Cell::new(Text::from("Cost").right_aligned()),
+        Cell::new(""),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(1);
+
+    const BAR_WIDTH: usize = 30;
+    let max_cost = hours.iter().map(|hour| hour.cost).fold(0.0, f64::max);
+    let tw = TOKEN_COL_WIDTH as usize;
+    let rows: Vec<Row> = hours
+        .iter()
+        .enumerate()
+        .map(|(hour, stats)| {
+            let bar = if max_cost > 0.0 {
+                "█".repeat((stats.cost / max_cost * BAR_WIDTH as f64).round() as usize)
+            } else {
+                String::new()
+            };
+            let row = Row::new(vec![
+                Line::from(""),
+                Line::from(format!("{hour:02}:00")),
+                Line::from(format_number(stats.user_messages, format_options)).right_aligned(),
+                Line::from(format_number(stats.ai_messages, format_options)).right_aligned(),
+                Line::from(format_number_fit(stats.tokens, format_options, tw)).right_aligned(),
+                Line::from(Span::styled(
+                    format!(
+                        "{}{:.prec$}",
+                        format_options.currency_symbol,
+                        stats.cost,
+                        prec = format_options.cost_decimal_places
+                    ),
+                    Style::default().fg(theme.cost),
+                ))
+                .right_aligned(),
+                Line::from(Span::styled(bar, Style::default().fg(theme.accent))),
             ]);
-            rows.push(separator_row);
+            if stats.is_empty() {
+                row.style(theme.dim())
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Length(5),               // Hour
+            Constraint::Length(COUNT_COL_WIDTH), // Msgs
+            Constraint::Length(COUNT_COL_WIDTH), // Replies
+            Constraint::Length(TOKEN_COL_WIDTH), // Tokens
+            Constraint::Length(10),              // Cost
+            Constraint::Fill(1),                 // Cost bar
+        ],
+    )
+    .header(header)
+    .highlight_symbol("→")
+    .row_highlight_style(Style::new().blue())
+    .column_spacing(2);
+
+    frame.render_stateful_widget(table, body_area, &mut detail.table_state);
+}
+
+/// Weekday × hour grid of assistant messages, shaded relative to the busiest cell.
+fn draw_heat_grid(
+    frame: &mut Frame,
+    area: Rect,
+    grid: &HeatGrid,
+    format_options: &NumberFormatOptions,
+    accent: Color,
+) {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const SHADES: [&str; 5] = ["· ", "░░", "▒▒", "▓▓", "██"];
+    let max = grid.max();
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Messages by local time of day",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    let mut hours = String::from("     ");
+    for hour in (0..24).step_by(3) {
+        hours.push_str(&format!("{hour:<9}"));
+    }
+    lines.push(Line::from(Span::styled(
+        hours,
+        Style::default().add_modifier(Modifier::DIM),
+    )));
+
+    for (day, row) in grid.cells.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{}  ", WEEKDAYS[day]))];
+        for &count in row {
+            let shade = if count == 0 || max == 0 {
+                0
+            } else {
+                // 1..=4, so any activity is visible.
+                1 + (count as u64 * 3).div_ceil(max as u64).min(3) as usize
+            };
+            let style = if shade == 0 {
+                Style::default().add_modifier(Modifier::DIM)
+            } else {
+                Style::default().fg(accent)
+            };
+            spans.push(Span::styled(format!("{} ", SHADES[shade]), style));
+        }
+        spans.push(Span::styled(
+            format!(" {:>7}", format_number(grid.day_total(day), format_options)),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(match grid.peak() {
+        Some((day, hour, count)) => format!(
+            "Busiest: {} {hour:02}:00-{:02}:00 ({} messages)",
+            WEEKDAYS[day],
+            (hour + 1) % 24,
+            format_number(count as u64, format_options)
+        ),
+        None => "No messages in this range".to_string(),
+    }));
+
+    frame.render_widget(Paragraph::new(Text::from(lines)), area);
+}
+
+fn draw_insights(
+    frame: &mut Frame,
+    area: Rect,
+    throughput: &Throughput,
+    format_options: &NumberFormatOptions,
+    accent: Color,
+) {
+    const BARS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+    let human = NumberFormatOptions {
+        use_human: true,
+        decimal_places: 1,
+        ..format_options.clone()
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let figure = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{label:<24}"), dim),
+            Span::styled(value, bold),
+        ])
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled("Throughput", bold)),
+        Line::from(""),
+        figure(
+            "Tokens per busy hour",
+            format_number(throughput.tokens_per_busy_hour.round() as u64, &human),
+        ),
+        figure(
+            "Busy hours",
+            format_number(throughput.busy_hours, format_options),
+        ),
+        figure(
+            "Average response",
+            format!(
+                "{} output tokens",
+                format_number(throughput.average_response_tokens.round() as u64, &human)
+            ),
+        ),
+        figure(
+            "Responses",
+            format_number(throughput.responses, format_options),
+        ),
+        Line::from(""),
+        Line::from(Span::styled("Responses by local hour of day", bold)),
+        Line::from(""),
+    ];
+
+    let max = throughput.max_hourly();
+    const BAR_ROWS: u64 = 4;
+    for row in (0..BAR_ROWS).rev() {
+        let mut spans = Vec::with_capacity(24);
+        for &count in &throughput.hourly_responses {
+            // Eighths of a row filled above this row's floor.
+            let eighths = if max == 0 {
+                0
+            } else {
+                (count * BAR_ROWS * 8)
+                    .div_ceil(max)
+                    .saturating_sub(row * 8)
+                    .min(8)
+            };
+            spans.push(Span::styled(
+                format!("{} ", BARS[eighths as usize].repeat(2)),
+                Style::default().fg(accent),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    let mut hours = String::new();
+    for hour in (0..24).step_by(3) {
+        hours.push_str(&format!("{hour:<9}"));
+    }
+    lines.push(Line::from(Span::styled(hours, dim)));
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(match throughput.busiest_hour {
+        Some(hour) => format!(
+            "Busiest hour: {hour:02}:00-{:02}:00 ({} responses)",
+            (hour + 1) % 24,
+            format_number(throughput.hourly_responses[hour], format_options)
+        ),
+        None => "No responses in this range".to_string(),
+    }));
+
+    frame.render_widget(Paragraph::new(Text::from(lines)), area);
+}
+
+fn draw_compare(
+    frame: &mut Frame,
+    area: Rect,
+    comparison: &Comparison,
+    periods: Periods,
+    format_options: &NumberFormatOptions,
+    theme: &Theme,
+) {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let figure = |metric: Metric, value: f64| match metric {
+        Metric::Cost => format!(
+            "{}{value:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        ),
+        _ => format_number(value as u64, format_options),
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{} ", periods.current), bold),
+            Span::styled(format!("against {}", periods.previous), dim),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "{:<20}{:>16}{:>16}{:>10}",
+                "", "Previous", "Current", "Change"
+            ),
+            dim,
+        )),
+    ];
+    for (metric, previous, current, change) in comparison.rows() {
+        // Spending more is worth a look; spending less is good news.
+        let color = match change {
+            Some(change) if change >= 0.005 => theme.warning,
+            Some(change) if change <= -0.005 => theme.success,
+            _ => Color::Reset,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<20}", metric.label()), dim),
+            Span::raw(format!("{:>16}", figure(metric, previous))),
+            Span::styled(format!("{:>16}", figure(metric, current)), bold),
+            Span::styled(
+                format!(
+                    "{:>10}",
+                    crate::stats::compare::format_change(change, current)
+                ),
+                Style::default().fg(color),
+            ),
+        ]));
+    }
+    if comparison.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("No messages in either period"));
+    }
+
+    frame.render_widget(Paragraph::new(Text::from(lines)), area);
+}
+
+fn draw_achievements(
+    frame: &mut Frame,
+    area: Rect,
+    achievements: &Achievements,
+    format_options: &NumberFormatOptions,
+    accent: Color,
+) {
+    let human = NumberFormatOptions {
+        use_human: true,
+        decimal_places: 1,
+        ..format_options.clone()
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let figure = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{label:<24}"), dim),
+            Span::styled(value, bold),
+        ])
+    };
+    let days = |count: u32| {
+        if count == 1 {
+            "1 day".to_string()
         } else {
-            // Totals row
-            let total_cost = total_cost_cents as f64 / 100.0;
-            let tw = TOKEN_COL_WIDTH as usize;
-            let totals_row = Row::new(vec![
-                Line::from(Span::raw("")),
-                Line::from(Span::styled(
-                    format!("Total ({} sessions)", total_session_rows),
-                    Style::default().add_modifier(Modifier::BOLD),
-                )),
-                Line::from(Span::raw("")),
-                Line::from(Span::styled(
-                    format!(
-                        "{}{total_cost:.prec$}",
-                        format_options.currency_symbol,
-                        prec = format_options.cost_decimal_places
-                    ),
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    format_number_fit(total_cached_tokens, format_options, tw),
-                    Style::default()
-                        .add_modifier(Modifier::DIM)
-                        .add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    format_number_fit(total_input_tokens, format_options, tw),
-                    Style::default().add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    format_number_fit(total_output_tokens, format_options, tw),
-                    Style::default().add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    format_number_fit(total_reasoning_tokens, format_options, tw),
+            format!("{} days", format_number(count, format_options))
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled("Streaks", bold)),
+        Line::from(""),
+        figure("Current streak", days(achievements.current_streak)),
+        figure(
+            "Longest streak",
+            match achievements.longest_streak_end {
+                Some(end) => format!(
+                    "{} (ended {})",
+                    days(achievements.longest_streak),
+                    end.format("%Y-%m-%d")
+                ),
+                None => days(0),
+            },
+        ),
+        figure("Days with usage", days(achievements.active_days)),
+        figure(
+            "Biggest day",
+            match achievements.biggest_day {
+                Some((date, cents)) => format!(
+                    "{}{:.prec$} on {}",
+                    format_options.currency_symbol,
+                    cents as f64 / 100.0,
+                    date.format("%Y-%m-%d"),
+                    prec = format_options.cost_decimal_places
+                ),
+                None => "-".to_string(),
+            },
+        ),
+        Line::from(""),
+        Line::from(Span::styled("Token milestones", bold)),
+        Line::from(""),
+        figure(
+            "Lifetime tokens",
+            format_number(achievements.lifetime_tokens, &human),
+        ),
+    ];
+
+    let reached: Vec<Span> = crate::stats::streaks::TOKEN_MILESTONES
+        .iter()
+        .map(|&milestone| {
+            let label = format!("{} ", format_number(milestone, &human));
+            if achievements.lifetime_tokens >= milestone {
+                Span::styled(format!("★ {label}"), Style::default().fg(accent))
+            } else {
+                Span::styled(format!("☆ {label}"), dim)
+            }
+        })
+        .collect();
+    lines.push(Line::from(reached));
+
+    if let (Some(next), Some(progress)) = (
+        achievements.next_milestone,
+        achievements.milestone_progress(),
+    ) {
+        const BAR_WIDTH: usize = 30;
+        let filled = ((progress * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<24}", "Next milestone"), dim),
+            Span::styled("█".repeat(filled), Style::default().fg(accent)),
+            Span::styled("░".repeat(BAR_WIDTH - filled), dim),
+            Span::styled(
+                format!(
+                    " {:.0}% of {}",
+                    progress * 100.0,
+                    format_number(next, &human)
+                ),
+                bold,
+            ),
+        ]));
+    }
+
+    frame.render_widget(Paragraph::new(Text::from(lines)), area);
+}
+
+fn draw_live_session(
+    frame: &mut Frame,
+    area: Rect,
+    live: &LivePanel,
+    format_options: &NumberFormatOptions,
+    ui_state: &UiState,
+) {
+    let session = &live.session;
+    let stats = &session.stats;
+    let name = session
+        .session_name
+        .as_deref()
+        .unwrap_or(&session.session_id);
+    // Truncate by characters, not bytes, to avoid panicking on multi-byte UTF-8
+    let name = if name.chars().count() > 60 {
+        format!("{}…", name.chars().take(60).collect::<String>())
+    } else {
+        name.to_string()
+    };
+    let title = Line::from(vec![
+        Span::styled(" ● LIVE ", Style::default().fg(ui_state.theme.success)),
+        Span::styled(
+            format!("{} ", ui_state.analyzer_styles.label(&live.analyzer_name)),
+            Style::default()
+                .fg(ui_state
+                    .analyzer_styles
+                    .color(&live.analyzer_name, &ui_state.theme))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("· {name} "),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ]);
+    let tokens = stats.input_tokens + stats.output_tokens + stats.reasoning_tokens;
+    let mut spans = vec![
+        Span::raw(format!("{} tokens", format_number(tokens, format_options))),
+        Span::styled(
+            format!(
+                " ({} cached)",
+                format_number(stats.cached_tokens, format_options)
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+        Span::raw(format!(
+            "  •  {}{:.prec$}",
+            format_options.currency_symbol,
+            stats.cost(),
+            prec = format_options.cost_decimal_places
+        )),
+        Span::raw(format!(
+            "  •  {} tool calls",
+            format_number(stats.tool_calls, format_options)
+        )),
+    ];
+    if let Some(since) = live.since {
+        spans.push(Span::styled(
+            format!("  •  watching since {}", since.format("%H:%M")),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    let block = Block::bordered()
+        .border_style(Style::default().fg(ui_state.theme.success))
+        .title(title);
+    frame.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+}
+
+/// Sparklines of daily cost and tokens, oldest day on the left.
+fn draw_trend(
+    frame: &mut Frame,
+    area: Rect,
+    trend: &DailyTrend,
+    format_options: &NumberFormatOptions,
+    accent: Color,
+) {
+    let [cost_area, tokens_area] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+    let human = NumberFormatOptions {
+        use_human: true,
+        decimal_places: 1,
+        ..format_options.clone()
+    };
+    let days = trend.cost_cents.len();
+    let cost = format!(
+        "{}{:.prec$}",
+        format_options.currency_symbol,
+        trend.total_cost_cents() as f64 / 100.0,
+        prec = format_options.cost_decimal_places
+    );
+
+    let render = |frame: &mut Frame, area: Rect, name: &str, total: String, data: &[u64]| {
+        let block = Block::bordered()
+            .border_style(Style::default().add_modifier(Modifier::DIM))
+            .title(Line::from(vec![
+                Span::styled(
+                    format!(" {name} · last {days}d "),
                     Style::default().add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    format_number(total_tool_calls, format_options),
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ))
-                .right_aligned(),
-                Line::from(Span::styled(
-                    all_models_text.clone(),
+                ),
+                Span::styled(
+                    format!("{total} "),
                     Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]));
+        // Keep the most recent days when the pane is narrower than the window.
+        let width = block.inner(area).width as usize;
+        let data = &data[data.len().saturating_sub(width)..];
+        frame.render_widget(
+            Sparkline::default()
+                .block(block)
+                .data(data)
+                .style(Style::default().fg(accent)),
+            area,
+        );
+    };
+
+    render(frame, cost_area, "Cost", cost, &trend.cost_cents);
+    render(
+        frame,
+        tokens_area,
+        "Tokens",
+        format_number(trend.total_tokens(), &human),
+        &trend.tokens,
+    );
+}
+
+/// Side-by-side histograms of messages and tokens per session.
+fn draw_session_lengths(
+    frame: &mut Frame,
+    area: Rect,
+    lengths: &SessionLengths,
+    format_options: &NumberFormatOptions,
+    accent: Color,
+) {
+    let [messages_area, tokens_area] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+    let human = NumberFormatOptions {
+        use_human: true,
+        decimal_places: 1,
+        ..format_options.clone()
+    };
+    let title = |name: &str, median: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("{name} per session"),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  median {median}"),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ])
+    };
+
+    let render = |frame: &mut Frame, area: Rect, heading: Line<'static>, histogram: &Histogram| {
+        const LABEL_WIDTH: usize = 10;
+        const COUNT_WIDTH: usize = 14;
+        let bar_width = (area.width as usize).saturating_sub(LABEL_WIDTH + COUNT_WIDTH + 4);
+        let max = histogram.max_count().max(1);
+
+        let mut lines = vec![heading, Line::from("")];
+        for (label, count) in &histogram.buckets {
+            let filled = (*count * bar_width).div_ceil(max).min(bar_width);
+            let share = if lengths.sessions > 0 {
+                *count as f64 / lengths.sessions as f64 * 100.0
+            } else {
+                0.0
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{label:>LABEL_WIDTH$}  ")),
+                Span::styled("█".repeat(filled), Style::default().fg(accent)),
+                Span::raw(" ".repeat(bar_width - filled)),
+                Span::raw(format!(
+                    "  {:>6} {:>5}",
+                    format_number(*count as u64, format_options),
+                    format!("{share:.0}%")
                 )),
-            ]);
-            rows.push(totals_row);
+            ]));
         }
-    }
+        frame.render_widget(Paragraph::new(Text::from(lines)), area);
+    };
 
-    let mut render_state = TableState::default();
-    render_state.select(Some(selected_global.saturating_sub(window_start)));
+    render(
+        frame,
+        messages_area,
+        title("Messages", format_number(lengths.messages.median, &human)),
+        &lengths.messages,
+    );
+    render(
+        frame,
+        tokens_area,
+        title("Tokens", format_number(lengths.tokens.median, &human)),
+        &lengths.tokens,
+    );
+}
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(1),               // Arrow / highlight symbol space
-            Constraint::Length(32),              // Session (increased width for name)
-            Constraint::Length(17),              // Started
-            Constraint::Length(10),              // Cost
-            Constraint::Length(TOKEN_COL_WIDTH), // Cached Tks
-            Constraint::Length(TOKEN_COL_WIDTH), // Input
-            Constraint::Length(TOKEN_COL_WIDTH), // Output
-            Constraint::Length(TOKEN_COL_WIDTH), // Reason Tks
-            Constraint::Length(COUNT_COL_WIDTH), // Tools
-            Constraint::Min(10),                 // Models
-        ],
-    )
-    .header(header)
-    .block(Block::default().title(""))
-    .highlight_symbol("→")
-    .row_highlight_style(Style::new().blue())
-    .column_spacing(2);
+/// A usage window's summary row, e.g. `[████░░░░░░] 42%  1.2M tokens ·
+/// resets 15:00`. The bar needs a known limit.
+fn format_usage_window(
+    window: &UsageWindow,
+    format_options: &NumberFormatOptions,
+    now: chrono::DateTime<Local>,
+) -> String {
+    let mut parts = Vec::new();
+    if let Some(used) = window.used {
+        parts.push(format!(
+            "{} {:.0}%",
+            crate::budget::progress_bar(used, 20),
+            used * 100.0
+        ));
+    }
+    if let Some(tokens) = window.tokens {
+        let human = NumberFormatOptions {
+            use_human: true,
+            ..format_options.clone()
+        };
+        parts.push(format!("{} tokens", format_number(tokens, &human)));
+    }
+    let resets = window.resets_at.with_timezone(&Local);
+    let at = if resets - now < chrono::TimeDelta::days(1) {
+        resets.format("%H:%M")
+    } else {
+        resets.format("%a %H:%M")
+    };
+    format!("{} · resets {at}", parts.join("  "))
+}
 
-    frame.render_stateful_widget(table, area, &mut render_state);
+/// Rows of the totals panel: the fixed rows plus one per budget, one for
+/// premium requests and one per usage window.
+fn summary_height(ui_state: &UiState) -> u16 {
+    11 + ui_state.budgets.len() as u16
+        + u16::from(ui_state.premium.is_some())
+        + ui_state.usage_windows.len() as u16
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_summary_stats(
     frame: &mut Frame,
     area: Rect,
     filtered_stats: &[SharedAnalyzerView],
     format_options: &NumberFormatOptions,
     period_filter: Option<PeriodFilter>,
+    date_range: DateRange,
+    budgets: &[BudgetStatus],
+    forecast: Option<&Forecast>,
+    premium: Option<&PremiumUsage>,
+    usage_windows: &[UsageWindow],
+    cost_mode: CostMode,
+    theme: &Theme,
 ) {
     // Aggregate stats from all tools, optionally filtered to a single period
     let mut total_cost_cents: u64 = 0;
@@ -2508,7 +5631,9 @@ fn draw_summary_stats(
     let mut total_output: u64 = 0;
     let mut total_reasoning: u64 = 0;
     let mut total_tool_calls: u64 = 0;
+    let mut prompt_cache = PromptCacheStats::default();
     let mut all_days = HashSet::new();
+    let mut by_model: BTreeMap<String, ModelStats> = BTreeMap::new();
 
     for stats_arc in filtered_stats {
         let stats = stats_arc.read();
@@ -2526,6 +5651,13 @@ fn draw_summary_stats(
             total_output += day_stats.stats.output_tokens;
             total_reasoning += day_stats.stats.reasoning_tokens;
             total_tool_calls += day_stats.stats.tool_calls as u64;
+            prompt_cache += day_stats.prompt_cache;
+            for (model, model_stats) in &day_stats.model_stats {
+                by_model
+                    .entry(model.clone())
+                    .or_insert_with(|| ModelStats::new(model.clone()))
+                    .add_model_stats(model_stats);
+            }
 
             // Collect unique days across all tools that have actual data
             if day_stats.stats.cost_cents > 0
@@ -2545,6 +5677,21 @@ fn draw_summary_stats(
     let total_tokens = total_cached + total_input + total_output;
     let total_cost = total_cost_cents as f64 / 100.0;
     let tools_count = filtered_stats.len();
+    let models: Vec<ModelStats> = by_model.into_values().collect();
+    let providers = aggregate_provider_stats(&models)
+        .iter()
+        .filter(|provider| provider.message_count > 0)
+        .map(|provider| {
+            format!(
+                "{} {}{:.prec$}",
+                provider.provider.label(),
+                format_options.currency_symbol,
+                provider.cost,
+                prec = format_options.cost_decimal_places
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" · ");
 
     // Define summary rows with labels and values
     let summary_rows = vec![
@@ -2565,7 +5712,10 @@ fn draw_summary_stats(
             Color::LightGreen,
         ),
         (
-            "Cost:",
+            match cost_mode {
+                CostMode::Api => "Cost:",
+                CostMode::Actual => "Actual cost:",
+            },
             format!(
                 "{}{total_cost:.prec$}",
                 format_options.currency_symbol,
@@ -2573,13 +5723,63 @@ fn draw_summary_stats(
             ),
             Color::LightYellow,
         ),
+        (
+            "Providers:",
+            if providers.is_empty() {
+                "-".to_string()
+            } else {
+                providers
+            },
+            Color::LightCyan,
+        ),
+        (
+            "Caching:",
+            format_prompt_cache_summary(&prompt_cache, format_options),
+            Color::LightMagenta,
+        ),
         ("Days tracked:", all_days.len().to_string(), Color::White),
+        (
+            "Forecast:",
+            forecast.map_or_else(
+                || "-".to_string(),
+                |forecast| {
+                    format!(
+                        "{}{:.prec$} this month ({}-day avg {}{:.prec$}/day)",
+                        format_options.currency_symbol,
+                        forecast.projected,
+                        forecast.window_days,
+                        format_options.currency_symbol,
+                        forecast.average_daily,
+                        prec = format_options.cost_decimal_places
+                    )
+                },
+            ),
+            Color::LightYellow,
+        ),
     ];
+    let premium_label = "Premium reqs:";
+    let window_labels: Vec<String> = usage_windows
+        .iter()
+        .map(|window| format!("{} {}:", window.tool, window.kind.label()))
+        .collect();
+    let budget_labels: Vec<String> = budgets
+        .iter()
+        .map(|status| {
+            if status.scope == crate::budget::ALL_TOOLS {
+                format!("Budget ({}):", status.period.label())
+            } else {
+                format!("{} ({}):", status.scope, status.period.label())
+            }
+        })
+        .collect();
 
     // Find the maximum label width for alignment
     let max_label_width = summary_rows
         .iter()
         .map(|(label, _, _)| label.len())
+        .chain(budget_labels.iter().map(|label| label.chars().count()))
+        .chain(premium.map(|_| premium_label.len()))
+        .chain(window_labels.iter().map(|label| label.chars().count()))
         .max()
         .unwrap_or(0);
 
@@ -2590,16 +5790,85 @@ fn draw_summary_stats(
             Line::from(vec![
                 Span::raw(format!("{label:<max_label_width$}")),
                 Span::raw("      "), // 6 spaces between label and value
-                Span::styled(value, Style::new().fg(color).bold()),
+                Span::styled(
+                    value,
+                    Style::new().fg(theme.summary.unwrap_or(color)).bold(),
+                ),
             ])
         })
         .collect();
 
+    let money = |amount: f64| {
+        format!(
+            "{}{amount:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        )
+    };
+    for (window, label) in usage_windows.iter().zip(&window_labels) {
+        let color = match window.used {
+            Some(used) if used >= 0.9 => theme.error,
+            Some(used) if used >= 0.7 => theme.warning,
+            _ => theme.success,
+        };
+        summary_lines.push(Line::from(vec![
+            Span::raw(format!("{label:<max_label_width$}")),
+            Span::raw("      "),
+            Span::styled(
+                format_usage_window(window, format_options, Local::now()),
+                Style::new().fg(color).bold(),
+            ),
+        ]));
+    }
+    if let Some(usage) = premium {
+        let mut value = format!(
+            "{:.0} / {} this month ({})",
+            usage.used,
+            usage.plan.included(),
+            usage.plan.label()
+        );
+        if usage.overage_requests > 0.0 {
+            value.push_str(&format!("  ~{} overage", money(usage.overage_cost)));
+        }
+        let color = if usage.overage_requests > 0.0 {
+            theme.warning
+        } else {
+            theme.success
+        };
+        summary_lines.push(Line::from(vec![
+            Span::raw(format!("{premium_label:<max_label_width$}")),
+            Span::raw("      "),
+            Span::styled(value, Style::new().fg(color).bold()),
+        ]));
+    }
+    for (status, label) in budgets.iter().zip(&budget_labels) {
+        let color = match status.level {
+            BudgetLevel::Ok => theme.success,
+            BudgetLevel::Warning => theme.warning,
+            BudgetLevel::Exceeded => theme.error,
+        };
+        let mut value = format!(
+            "{} {:.0}%  {} / {}",
+            crate::budget::progress_bar(status.fraction(), 20),
+            status.fraction() * 100.0,
+            money(status.spent),
+            money(status.limit)
+        );
+        if status.level == BudgetLevel::Exceeded {
+            value.push_str("  over budget");
+        }
+        summary_lines.push(Line::from(vec![
+            Span::raw(format!("{label:<max_label_width$}")),
+            Span::raw("      "),
+            Span::styled(value, Style::new().fg(color).bold()),
+        ]));
+    }
+
     summary_lines.insert(
         0,
         Line::from(vec![Span::styled(
             "-----------------------------",
-            Style::default().dim(),
+            theme.dim(),
         )]),
     );
 
@@ -2609,6 +5878,8 @@ fn draw_summary_stats(
             "Totals for {}",
             format_aggregate_period_for_display(&filter.display_key(), filter.view_mode())
         )
+    } else if !date_range.is_unbounded() {
+        format!("Totals for {date_range}")
     } else {
         "Totals".to_string()
     };
@@ -2622,6 +5893,35 @@ fn draw_summary_stats(
     frame.render_widget(summary_widget, area);
 }
 
+/// One-line summary of what prompt caching saved and what cache expiry cost.
+fn format_prompt_cache_summary(
+    prompt_cache: &PromptCacheStats,
+    format_options: &NumberFormatOptions,
+) -> String {
+    let money = |value: f64| {
+        format!(
+            "{}{value:.prec$}",
+            format_options.currency_symbol,
+            prec = format_options.cost_decimal_places
+        )
+    };
+    let mut summary = format!("saved {}", money(prompt_cache.saved_cost));
+    if prompt_cache.expiry_misses > 0 {
+        summary.push_str(&format!(
+            " • lost {} to expiry ({} {} after >{}m idle)",
+            money(prompt_cache.expiry_cost),
+            format_number(prompt_cache.expiry_misses as u64, format_options),
+            if prompt_cache.expiry_misses == 1 {
+                "re-write"
+            } else {
+                "re-writes"
+            },
+            crate::utils::PROMPT_CACHE_TTL_SECS / 60
+        ));
+    }
+    summary
+}
+
 /// Initialize or resize table states to match the number of analyzers with data.
 ///
 /// Preserves existing table states when resizing and creates new states for
@@ -2690,16 +5990,22 @@ fn update_period_filters(filters: &mut Vec<Option<PeriodFilter>>, filtered_count
 
 /// Build a callback that prints upload progress to stdout with animated dots.
 ///
-/// The callback takes `(current, total)` message counts and prints a status line
-/// that updates in place. Dots animate every 500ms to show activity.
+/// The callback takes the upload's progress and prints a status line that
+/// updates in place. Dots animate every 500ms to show activity.
 pub fn create_upload_progress_callback(
     format_options: &NumberFormatOptions,
-) -> impl Fn(usize, usize) + '_ {
+) -> impl Fn(UploadProgress) + '_ {
     static LAST_CURRENT: AtomicUsize = AtomicUsize::new(0);
     static DOTS: AtomicUsize = AtomicUsize::new(0);
     static LAST_DOTS_UPDATE: AtomicU64 = AtomicU64::new(0);
 
-    move |current: usize, total: usize| {
+    move |progress: UploadProgress| {
+        let UploadProgress {
+            current,
+            total,
+            chunk,
+            total_chunks,
+        } = progress;
         let last = LAST_CURRENT.load(Ordering::Relaxed);
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -2724,13 +6030,27 @@ pub fn create_upload_progress_callback(
             should_update = true;
         }
 
+        // Plain output is often logged, so print whole lines and no animation.
+        if crate::utils::plain_output() {
+            if current != last {
+                println!(
+                    "Uploading {}/{} messages{}",
+                    format_number(current as u64, format_options),
+                    format_number(total as u64, format_options),
+                    chunk_label(chunk, total_chunks)
+                );
+            }
+            return;
+        }
+
         if should_update {
             let current_dots = DOTS.load(Ordering::Relaxed);
             let dots_str = ".".repeat(current_dots);
             print!(
-                "\r\x1b[KUploading {}/{} messages{}",
+                "\r\x1b[KUploading {}/{} messages{}{}",
                 format_number(current as u64, format_options),
                 format_number(total as u64, format_options),
+                chunk_label(chunk, total_chunks),
                 dots_str
             );
             let _ = Write::flush(&mut stdout());
@@ -2738,6 +6058,15 @@ pub fn create_upload_progress_callback(
     }
 }
 
+/// " (chunk 2/5)" for uploads split into several requests.
+fn chunk_label(chunk: usize, total_chunks: usize) -> String {
+    if total_chunks > 1 {
+        format!(" (chunk {chunk}/{total_chunks})")
+    } else {
+        String::new()
+    }
+}
+
 /// Print a success message after upload completes.
 ///
 /// Displays a checkmark and the total number of messages uploaded in green.
@@ -2769,12 +6098,10 @@ pub fn show_upload_error(error: &str) {
 
 /// Display a dry-run summary of what would be uploaded
 pub fn show_upload_dry_run(
-    messages: &[crate::types::ConversationMessage],
+    summary: &crate::upload::UploadSummary,
     format_options: &NumberFormatOptions,
 ) {
-    use std::collections::BTreeMap;
-
-    if messages.is_empty() {
+    if summary.messages == 0 {
         let _ = execute!(
             stdout(),
             SetForegroundColor(crossterm::style::Color::DarkYellow),
@@ -2784,39 +6111,6 @@ pub fn show_upload_dry_run(
         return;
     }
 
-    // Aggregate stats
-    let mut total_input_tokens: u64 = 0;
-    let mut total_output_tokens: u64 = 0;
-    let mut total_cost: f64 = 0.0;
-    let mut models: BTreeMap<String, u64> = BTreeMap::new();
-    let mut applications: BTreeMap<String, u64> = BTreeMap::new();
-    let mut earliest_date: Option<chrono::DateTime<chrono::Utc>> = None;
-    let mut latest_date: Option<chrono::DateTime<chrono::Utc>> = None;
-
-    for msg in messages {
-        total_input_tokens += msg.stats.input_tokens;
-        total_output_tokens += msg.stats.output_tokens;
-        total_cost += msg.stats.cost;
-
-        if let Some(ref model) = msg.model {
-            *models.entry(model.clone()).or_insert(0) += 1;
-        }
-
-        let app_name = format!("{:?}", msg.application);
-        *applications.entry(app_name).or_insert(0) += 1;
-
-        match earliest_date {
-            Some(d) if msg.date < d => earliest_date = Some(msg.date),
-            None => earliest_date = Some(msg.date),
-            _ => {}
-        }
-        match latest_date {
-            Some(d) if msg.date > d => latest_date = Some(msg.date),
-            None => latest_date = Some(msg.date),
-            _ => {}
-        }
-    }
-
     // Print header
     let _ = execute!(
         stdout(),
@@ -2829,20 +6123,20 @@ pub fn show_upload_dry_run(
     println!();
     println!(
         "  Messages:      {}",
-        format_number(messages.len() as u64, format_options)
+        format_number(summary.messages as u64, format_options)
     );
     println!(
         "  Input tokens:  {}",
-        format_number(total_input_tokens, format_options)
+        format_number(summary.input_tokens, format_options)
     );
     println!(
         "  Output tokens: {}",
-        format_number(total_output_tokens, format_options)
+        format_number(summary.output_tokens, format_options)
     );
-    println!("  Total cost:    ${:.4}", total_cost);
+    println!("  Total cost:    ${:.4}", summary.cost);
 
     // Print date range
-    if let (Some(earliest), Some(latest)) = (earliest_date, latest_date) {
+    if let (Some(earliest), Some(latest)) = (summary.earliest, summary.latest) {
         println!();
         println!(
             "  Date range:    {} to {}",
@@ -2852,10 +6146,10 @@ pub fn show_upload_dry_run(
     }
 
     // Print applications breakdown
-    if !applications.is_empty() {
+    if !summary.applications.is_empty() {
         println!();
         println!("  Applications:");
-        for (app, count) in &applications {
+        for (app, count) in &summary.applications {
             println!(
                 "    {}: {} messages",
                 app,
@@ -2865,10 +6159,10 @@ pub fn show_upload_dry_run(
     }
 
     // Print model breakdown
-    if !models.is_empty() {
+    if !summary.models.is_empty() {
         println!();
         println!("  Models:");
-        for (model, count) in &models {
+        for (model, count) in &summary.models {
             println!(
                 "    {}: {} messages",
                 model,
@@ -0,0 +1,610 @@
+/// Period rollups for the aggregate table.
+///
+/// The daily rows in `daily_stats` are the source of truth; weekly, monthly, and
+/// yearly rows are derived from them on demand so incremental updates never have
+/// to maintain more than one map.
+use super::AggregateViewMode;
+use crate::types::{
+    AnalyzerStatsView, CompactDate, ConversationMessage, DailyStats, SessionAggregate, Stats,
+};
+use crate::utils::CommandCategory;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Roll up daily statistics into periods derived by `period_key_fn`.
+///
+/// `period_key_fn` returns a `(period_key, representative_date)` pair for each
+/// daily row. All rows with the same key are then merged via
+/// `DailyStats += &DailyStats`.
+fn aggregate_daily_stats_by_period<F>(
+    daily_stats: &BTreeMap<String, DailyStats>,
+    mut period_key_fn: F,
+) -> BTreeMap<String, DailyStats>
+where
+    F: FnMut(&DailyStats) -> (String, CompactDate),
+{
+    let mut aggregate_stats = BTreeMap::new();
+
+    for day_stats in daily_stats.values() {
+        let (period_key, period_date) = period_key_fn(day_stats);
+
+        let aggregate_entry = aggregate_stats
+            .entry(period_key)
+            .or_insert_with(|| DailyStats {
+                date: period_date,
+                ..DailyStats::default()
+            });
+
+        *aggregate_entry += day_stats;
+    }
+
+    aggregate_stats
+}
+
+/// Roll up daily statistics into monthly totals.
+///
+/// Groups days by `YYYY-MM`, using the first day of the month as the
+/// representative `CompactDate` stored in the aggregated row.
+pub fn aggregate_daily_stats_by_month(
+    daily_stats: &BTreeMap<String, DailyStats>,
+) -> BTreeMap<String, DailyStats> {
+    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
+        let year = day_stats.date.year();
+        let month = day_stats.date.month();
+        (
+            format!("{year:04}-{month:02}"),
+            CompactDate::from_parts(year, month, 1),
+        )
+    })
+}
+
+/// Roll up daily statistics into ISO weekly totals.
+///
+/// Weeks use ISO-8601 semantics, so they start on Monday and are keyed as
+/// `YYYY-Www`, where `YYYY` is the ISO week year.
+pub fn aggregate_daily_stats_by_week(
+    daily_stats: &BTreeMap<String, DailyStats>,
+) -> BTreeMap<String, DailyStats> {
+    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
+        let date = NaiveDate::from_ymd_opt(
+            day_stats.date.year() as i32,
+            day_stats.date.month() as u32,
+            day_stats.date.day() as u32,
+        )
+        .expect("CompactDate should always contain a valid calendar date");
+        let iso_week = date.iso_week();
+        let week_start = NaiveDate::from_isoywd_opt(iso_week.year(), iso_week.week(), Weekday::Mon)
+            .expect("ISO week from a valid date should map back to a valid Monday");
+
+        (
+            format!("{:04}-W{:02}", iso_week.year(), iso_week.week()),
+            CompactDate::from_parts(
+                week_start.year() as u16,
+                week_start.month() as u8,
+                week_start.day() as u8,
+            ),
+        )
+    })
+}
+
+/// Roll up daily statistics into yearly totals.
+///
+/// Yearly rows are keyed as `YYYY` and use January 1st as the representative
+/// `CompactDate`.
+pub fn aggregate_daily_stats_by_year(
+    daily_stats: &BTreeMap<String, DailyStats>,
+) -> BTreeMap<String, DailyStats> {
+    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
+        let year = day_stats.date.year();
+        (format!("{year:04}"), CompactDate::from_parts(year, 1, 1))
+    })
+}
+
+/// Rows for the aggregate table: the daily map itself, or a freshly rolled-up copy.
+pub(super) enum AggregateStatsData<'a> {
+    Borrowed(&'a BTreeMap<String, DailyStats>),
+    Owned(BTreeMap<String, DailyStats>),
+}
+
+impl AggregateStatsData<'_> {
+    pub(super) fn as_map(&self) -> &BTreeMap<String, DailyStats> {
+        match self {
+            Self::Borrowed(stats) => stats,
+            Self::Owned(stats) => stats,
+        }
+    }
+}
+
+/// Roll `daily_stats` up to the periods of the current view mode.
+pub(super) fn get_aggregate_stats<'a>(
+    view: &'a AnalyzerStatsView,
+    aggregate_view_mode: AggregateViewMode,
+) -> AggregateStatsData<'a> {
+    match aggregate_view_mode {
+        AggregateViewMode::Daily => AggregateStatsData::Borrowed(&view.daily_stats),
+        AggregateViewMode::Weekly => {
+            AggregateStatsData::Owned(aggregate_daily_stats_by_week(&view.daily_stats))
+        }
+        AggregateViewMode::Monthly => {
+            AggregateStatsData::Owned(aggregate_daily_stats_by_month(&view.daily_stats))
+        }
+        AggregateViewMode::Yearly => {
+            AggregateStatsData::Owned(aggregate_daily_stats_by_year(&view.daily_stats))
+        }
+    }
+}
+
+/// Period keys holding the highest value of each highlighted column.
+///
+/// Keys rather than row indices are stored so highlighting follows the period
+/// regardless of sort order, hidden empty periods, or the date filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeriodBests<'a> {
+    /// Highest cost in cents, used to scale the cost heat map.
+    pub max_cost_cents: u32,
+    pub cost: Option<&'a str>,
+    pub cached_tokens: Option<&'a str>,
+    pub input_tokens: Option<&'a str>,
+    pub output_tokens: Option<&'a str>,
+    pub reasoning_tokens: Option<&'a str>,
+    pub conversations: Option<&'a str>,
+    pub tool_calls: Option<&'a str>,
+}
+
+impl<'a> PeriodBests<'a> {
+    /// Find the best period for each column. Ties go to the earliest period and
+    /// all-zero columns have no best.
+    pub fn from_periods(aggregate_stats: &'a BTreeMap<String, DailyStats>) -> Self {
+        fn update<'a, T: PartialOrd + Copy + Default>(
+            best: &mut Option<(&'a str, T)>,
+            period: &'a str,
+            value: T,
+        ) {
+            let current = best.map(|(_, v)| v).unwrap_or_default();
+            if value > current {
+                *best = Some((period, value));
+            }
+        }
+
+        let mut cost = None;
+        let mut cached_tokens = None;
+        let mut input_tokens = None;
+        let mut output_tokens = None;
+        let mut reasoning_tokens = None;
+        let mut conversations = None;
+        let mut tool_calls = None;
+
+        for (period, stats) in aggregate_stats {
+            let period = period.as_str();
+            update(&mut cost, period, stats.stats.cost_cents);
+            update(&mut cached_tokens, period, stats.stats.cached_tokens);
+            update(&mut input_tokens, period, stats.stats.input_tokens);
+            update(&mut output_tokens, period, stats.stats.output_tokens);
+            update(&mut reasoning_tokens, period, stats.stats.reasoning_tokens);
+            update(&mut conversations, period, stats.conversations);
+            update(&mut tool_calls, period, stats.stats.tool_calls);
+        }
+
+        Self {
+            max_cost_cents: cost.map(|(_, v)| v).unwrap_or(0),
+            cost: cost.map(|(p, _)| p),
+            cached_tokens: cached_tokens.map(|(p, _)| p),
+            input_tokens: input_tokens.map(|(p, _)| p),
+            output_tokens: output_tokens.map(|(p, _)| p),
+            reasoning_tokens: reasoning_tokens.map(|(p, _)| p),
+            conversations: conversations.map(|(p, _)| p),
+            tool_calls: tool_calls.map(|(p, _)| p),
+        }
+    }
+}
+
+/// Upper bounds (inclusive) and labels for messages-per-session buckets.
+const MESSAGE_BUCKETS: [(u64, &str); 8] = [
+    (1, "1"),
+    (5, "2-5"),
+    (10, "6-10"),
+    (25, "11-25"),
+    (50, "26-50"),
+    (100, "51-100"),
+    (250, "101-250"),
+    (u64::MAX, ">250"),
+];
+
+/// Upper bounds (inclusive) and labels for tokens-per-session buckets.
+const TOKEN_BUCKETS: [(u64, &str); 7] = [
+    (10_000, "0-10k"),
+    (50_000, "10k-50k"),
+    (100_000, "50k-100k"),
+    (500_000, "100k-500k"),
+    (1_000_000, "500k-1m"),
+    (5_000_000, "1m-5m"),
+    (u64::MAX, ">5m"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// `(label, sessions)` from the smallest bucket to the largest.
+    pub buckets: Vec<(&'static str, usize)>,
+    pub median: u64,
+}
+
+impl Histogram {
+    fn new(mut values: Vec<u64>, bounds: &[(u64, &'static str)]) -> Self {
+        let mut buckets: Vec<(&'static str, usize)> =
+            bounds.iter().map(|(_, label)| (*label, 0)).collect();
+        for value in &values {
+            let idx = bounds
+                .iter()
+                .position(|(upper, _)| value <= upper)
+                .unwrap_or(bounds.len() - 1);
+            buckets[idx].1 += 1;
+        }
+        values.sort_unstable();
+        let median = values.get(values.len() / 2).copied().unwrap_or(0);
+        Self { buckets, median }
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.buckets.iter().map(|(_, n)| *n).max().unwrap_or(0)
+    }
+}
+
+/// How long sessions tend to run, by message count and by token volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionLengths {
+    pub sessions: usize,
+    pub messages: Histogram,
+    pub tokens: Histogram,
+}
+
+impl SessionLengths {
+    pub fn from_sessions(sessions: &[SessionAggregate]) -> Self {
+        let messages = sessions
+            .iter()
+            .map(|s| s.models.iter().map(|(_, count)| *count as u64).sum())
+            .collect();
+        let tokens = sessions
+            .iter()
+            .map(|s| s.stats.input_tokens + s.stats.output_tokens + s.stats.cached_tokens)
+            .collect();
+        Self {
+            sessions: sessions.len(),
+            messages: Histogram::new(messages, &MESSAGE_BUCKETS),
+            tokens: Histogram::new(tokens, &TOKEN_BUCKETS),
+        }
+    }
+}
+
+/// Assistant messages by day of week (Monday first) and local hour of day,
+/// summed over every day in a view.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HeatGrid {
+    pub cells: [[u32; 24]; 7],
+}
+
+impl HeatGrid {
+    pub fn from_days<'a>(days: impl IntoIterator<Item = &'a DailyStats>) -> Self {
+        let mut grid = Self::default();
+        for day in days {
+            let Some(date) = NaiveDate::from_ymd_opt(
+                day.date.year() as i32,
+                day.date.month() as u32,
+                day.date.day() as u32,
+            ) else {
+                continue;
+            };
+            let row = &mut grid.cells[date.weekday().num_days_from_monday() as usize];
+            for (cell, count) in row.iter_mut().zip(day.hourly_messages) {
+                *cell += count;
+            }
+        }
+        grid
+    }
+
+    pub fn max(&self) -> u32 {
+        self.cells.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    pub fn day_total(&self, weekday: usize) -> u64 {
+        self.cells[weekday].iter().map(|&n| n as u64).sum()
+    }
+
+    /// Busiest `(weekday, hour, messages)`, earliest in the week on ties.
+    pub fn peak(&self) -> Option<(usize, usize, u32)> {
+        let mut peak: Option<(usize, usize, u32)> = None;
+        for (day, row) in self.cells.iter().enumerate() {
+            for (hour, &count) in row.iter().enumerate() {
+                if count > 0 && peak.is_none_or(|(_, _, best)| count > best) {
+                    peak = Some((day, hour, count));
+                }
+            }
+        }
+        peak
+    }
+}
+
+/// Daily cost and token totals for the trend charts, oldest day first.
+/// Days without activity are zero so the x axis stays evenly spaced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DailyTrend {
+    pub cost_cents: Vec<u64>,
+    pub tokens: Vec<u64>,
+}
+
+impl DailyTrend {
+    /// The `days` days ending on (and including) `end`.
+    pub fn ending(daily_stats: &BTreeMap<String, DailyStats>, end: NaiveDate, days: usize) -> Self {
+        let mut trend = Self::default();
+        let Some(start) = end.checked_sub_days(chrono::Days::new(days.saturating_sub(1) as u64))
+        else {
+            return trend;
+        };
+        for date in start.iter_days().take_while(|date| *date <= end) {
+            let key = date.format("%Y-%m-%d").to_string();
+            let stats = daily_stats
+                .get(&key)
+                .map(|day| day.stats)
+                .unwrap_or_default();
+            trend.cost_cents.push(stats.cost_cents as u64);
+            trend.tokens.push(
+                stats.input_tokens
+                    + stats.output_tokens
+                    + stats.reasoning_tokens
+                    + stats.cached_tokens,
+            );
+        }
+        trend
+    }
+
+    pub fn total_cost_cents(&self) -> u64 {
+        self.cost_cents.iter().sum()
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.tokens.iter().sum()
+    }
+}
+
+/// Calls to one MCP server, with the tokens and cost of the messages that
+/// made them. A message calling several tools is split evenly across its
+/// calls, so a server is charged only for its share of each response.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct McpServerUsage {
+    pub calls: u64,
+    pub tokens: f64,
+    pub cost: f64,
+}
+
+/// How tool calls split across categories. Views only carry the total call
+/// count, so these are summed from raw messages when the tools pane opens.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolCounts {
+    pub tool_calls: u64,
+    pub terminal_commands: u64,
+    pub file_searches: u64,
+    pub file_content_searches: u64,
+    pub files_read: u64,
+    pub files_added: u64,
+    pub files_edited: u64,
+    pub files_deleted: u64,
+    pub todo_writes: u64,
+    pub todo_reads: u64,
+    pub todos_created: u64,
+    pub todos_completed: u64,
+    /// Lines touched per file category, for the composition bar.
+    pub code_lines: u64,
+    pub docs_lines: u64,
+    pub data_lines: u64,
+    pub media_lines: u64,
+    pub config_lines: u64,
+    pub other_lines: u64,
+    pub mcp_servers: BTreeMap<String, McpServerUsage>,
+    /// File operations per extension, see [`Stats::file_extensions`].
+    pub file_extensions: BTreeMap<String, u64>,
+    /// Terminal commands per category, see [`Stats::command_categories`].
+    pub command_categories: BTreeMap<String, u64>,
+}
+
+impl ToolCounts {
+    pub fn add(&mut self, stats: &Stats) {
+        self.tool_calls += stats.tool_calls as u64;
+        self.terminal_commands += stats.terminal_commands;
+        self.file_searches += stats.file_searches;
+        self.file_content_searches += stats.file_content_searches;
+        self.files_read += stats.files_read;
+        self.files_added += stats.files_added;
+        self.files_edited += stats.files_edited;
+        self.files_deleted += stats.files_deleted;
+        self.todo_writes += stats.todo_writes;
+        self.todo_reads += stats.todo_reads;
+        self.todos_created += stats.todos_created;
+        self.todos_completed += stats.todos_completed;
+        self.code_lines += stats.code_lines;
+        self.docs_lines += stats.docs_lines;
+        self.data_lines += stats.data_lines;
+        self.media_lines += stats.media_lines;
+        self.config_lines += stats.config_lines;
+        self.other_lines += stats.other_lines;
+        for (ext, count) in &stats.file_extensions {
+            *self.file_extensions.entry(ext.clone()).or_default() += count;
+        }
+        for (category, count) in &stats.command_categories {
+            *self.command_categories.entry(category.clone()).or_default() += count;
+        }
+
+        let tokens = stats.input_tokens
+            + stats.output_tokens
+            + stats.reasoning_tokens
+            + stats.cache_creation_tokens
+            + stats.cache_read_tokens;
+        let mcp_calls: u64 = stats.mcp_tool_calls.values().sum();
+        let all_calls = (stats.tool_calls as u64).max(mcp_calls);
+        for (server, &calls) in &stats.mcp_tool_calls {
+            let share = calls as f64 / all_calls as f64;
+            let usage = self.mcp_servers.entry(server.clone()).or_default();
+            usage.calls += calls;
+            usage.tokens += tokens as f64 * share;
+            usage.cost += stats.cost * share;
+        }
+    }
+
+    fn merge(&mut self, other: &ToolCounts) {
+        self.tool_calls += other.tool_calls;
+        self.terminal_commands += other.terminal_commands;
+        self.file_searches += other.file_searches;
+        self.file_content_searches += other.file_content_searches;
+        self.files_read += other.files_read;
+        self.files_added += other.files_added;
+        self.files_edited += other.files_edited;
+        self.files_deleted += other.files_deleted;
+        self.todo_writes += other.todo_writes;
+        self.todo_reads += other.todo_reads;
+        self.todos_created += other.todos_created;
+        self.todos_completed += other.todos_completed;
+        self.code_lines += other.code_lines;
+        self.docs_lines += other.docs_lines;
+        self.data_lines += other.data_lines;
+        self.media_lines += other.media_lines;
+        self.config_lines += other.config_lines;
+        self.other_lines += other.other_lines;
+        for (ext, count) in &other.file_extensions {
+            *self.file_extensions.entry(ext.clone()).or_default() += count;
+        }
+        for (category, count) in &other.command_categories {
+            *self.command_categories.entry(category.clone()).or_default() += count;
+        }
+        for (server, other) in &other.mcp_servers {
+            let usage = self.mcp_servers.entry(server.clone()).or_default();
+            usage.calls += other.calls;
+            usage.tokens += other.tokens;
+            usage.cost += other.cost;
+        }
+    }
+
+    /// `(label, count)` per terminal command category that was used, in
+    /// [`CommandCategory::ALL`] order.
+    pub fn command_rows(&self) -> Vec<(&'static str, u64)> {
+        CommandCategory::ALL
+            .iter()
+            .filter_map(|category| {
+                let count = *self.command_categories.get(category.key())?;
+                (count > 0).then_some((category.label(), count))
+            })
+            .collect()
+    }
+
+    /// MCP servers by attributed cost, most expensive first.
+    pub fn mcp_by_cost(&self) -> Vec<(&str, McpServerUsage)> {
+        let mut servers: Vec<(&str, McpServerUsage)> = self
+            .mcp_servers
+            .iter()
+            .map(|(server, usage)| (server.as_str(), *usage))
+            .collect();
+        servers.sort_by(|a, b| {
+            b.1.cost
+                .total_cmp(&a.1.cost)
+                .then(b.1.calls.cmp(&a.1.calls))
+        });
+        servers
+    }
+
+    /// `(label, count)` for each category, in display order.
+    pub fn rows(&self) -> [(&'static str, u64); 11] {
+        [
+            ("Terminal commands", self.terminal_commands),
+            ("File searches", self.file_searches),
+            ("Content searches", self.file_content_searches),
+            ("Files read", self.files_read),
+            ("Files added", self.files_added),
+            ("Files edited", self.files_edited),
+            ("Files deleted", self.files_deleted),
+            ("Todo writes", self.todo_writes),
+            ("Todo reads", self.todo_reads),
+            ("Todos created", self.todos_created),
+            ("Todos completed", self.todos_completed),
+        ]
+    }
+
+    /// `(label, lines)` per file category, in display order.
+    pub fn composition(&self) -> [(&'static str, u64); 6] {
+        [
+            ("Code", self.code_lines),
+            ("Docs", self.docs_lines),
+            ("Data", self.data_lines),
+            ("Config", self.config_lines),
+            ("Media", self.media_lines),
+            ("Other", self.other_lines),
+        ]
+    }
+}
+
+/// Split `width` cells between `values` in proportion, using largest
+/// remainders so the parts always add up to `width` (or 0 when all are zero).
+pub fn split_widths(values: &[u64], width: usize) -> Vec<usize> {
+    let total: u64 = values.iter().sum();
+    if total == 0 {
+        return vec![0; values.len()];
+    }
+    let exact: Vec<f64> = values
+        .iter()
+        .map(|&v| v as f64 * width as f64 / total as f64)
+        .collect();
+    let mut widths: Vec<usize> = exact.iter().map(|w| w.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor())));
+    let assigned: usize = widths.iter().sum();
+    for &idx in order.iter().take(width.saturating_sub(assigned)) {
+        widths[idx] += 1;
+    }
+    widths
+}
+
+/// Tool category counts keyed by local day and by session.
+#[derive(Debug, Clone, Default)]
+pub struct ToolUsage {
+    pub by_day: HashMap<CompactDate, ToolCounts>,
+    pub by_session: HashMap<String, ToolCounts>,
+}
+
+impl ToolUsage {
+    pub fn add_messages<'a>(
+        &mut self,
+        messages: impl IntoIterator<Item = &'a ConversationMessage>,
+    ) {
+        for message in messages {
+            self.by_day
+                .entry(CompactDate::from_local(&message.date))
+                .or_default()
+                .add(&message.stats);
+            self.by_session
+                .entry(message.conversation_hash.clone())
+                .or_default()
+                .add(&message.stats);
+        }
+    }
+
+    /// Fold another tool's usage into this one, e.g. for "All Tools".
+    pub fn merge(&mut self, other: &ToolUsage) {
+        for (date, counts) in &other.by_day {
+            self.by_day.entry(*date).or_default().merge(counts);
+        }
+        for (session, counts) in &other.by_session {
+            self.by_session
+                .entry(session.clone())
+                .or_default()
+                .merge(counts);
+        }
+    }
+
+    /// Sum the days accepted by `matches`, e.g. every day of a selected week.
+    pub fn for_days(&self, mut matches: impl FnMut(CompactDate) -> bool) -> ToolCounts {
+        let mut total = ToolCounts::default();
+        for (date, counts) in &self.by_day {
+            if matches(*date) {
+                total.merge(counts);
+            }
+        }
+        total
+    }
+}
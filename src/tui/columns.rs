@@ -0,0 +1,197 @@
+//! Optional columns of the aggregate and session tables: which are shown and
+//! in what order. The period (or session) and Cost columns always come first.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    Cached,
+    Hit,
+    Input,
+    Output,
+    Reason,
+    Convs,
+    Tools,
+    Active,
+    PerHour,
+    Subagents,
+    Premium,
+    Apps,
+    Models,
+}
+
+impl Column {
+    /// Every column, in the default order.
+    pub const ALL: [Column; 13] = [
+        Column::Cached,
+        Column::Hit,
+        Column::Input,
+        Column::Output,
+        Column::Reason,
+        Column::Convs,
+        Column::Tools,
+        Column::Active,
+        Column::PerHour,
+        Column::Subagents,
+        Column::Premium,
+        Column::Apps,
+        Column::Models,
+    ];
+
+    /// Name used in `columns` and `hidden-columns`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Cached => "cached",
+            Column::Hit => "hit",
+            Column::Input => "input",
+            Column::Output => "output",
+            Column::Reason => "reason",
+            Column::Convs => "convs",
+            Column::Tools => "tools",
+            Column::Active => "active",
+            Column::PerHour => "per-hour",
+            Column::Subagents => "subagents",
+            Column::Premium => "premium",
+            Column::Apps => "apps",
+            Column::Models => "models",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Cached => "Cached Tks",
+            Column::Hit => "Hit %",
+            Column::Input => "Inp Tks",
+            Column::Output => "Outp Tks",
+            Column::Reason => "Reason Tks",
+            Column::Convs => "Convs",
+            Column::Tools => "Tools",
+            Column::Active => "Active",
+            Column::PerHour => "Cost/Hr",
+            Column::Subagents => "Subagent $",
+            Column::Premium => "Premium Reqs",
+            Column::Apps => "Apps",
+            Column::Models => "Models",
+        }
+    }
+
+    /// Parse a column name, accepting the header abbreviations too.
+    pub fn parse(key: &str) -> Option<Self> {
+        match key.trim().to_lowercase().as_str() {
+            "cached" => Some(Column::Cached),
+            "hit" => Some(Column::Hit),
+            "input" | "inp" => Some(Column::Input),
+            "output" | "outp" => Some(Column::Output),
+            "reason" | "reasoning" => Some(Column::Reason),
+            "convs" | "conv" | "conversations" => Some(Column::Convs),
+            "tools" => Some(Column::Tools),
+            "active" | "active-time" => Some(Column::Active),
+            "per-hour" | "cost-per-hour" | "cost/hr" => Some(Column::PerHour),
+            "subagents" | "subagent" | "sidechain" => Some(Column::Subagents),
+            "premium" | "premium-requests" | "premium_requests" => Some(Column::Premium),
+            "apps" => Some(Column::Apps),
+            "models" => Some(Column::Models),
+            _ => None,
+        }
+    }
+
+    /// Whether the session table has this column; hit ratios, conversation
+    /// counts and apps only exist per period.
+    pub fn in_sessions(self) -> bool {
+        !matches!(self, Column::Hit | Column::Convs | Column::Apps)
+    }
+
+    /// Whether the column shows without a `columns` list. Subagent spend is
+    /// opt-in since only Claude Code reports it.
+    pub fn shown_by_default(self) -> bool {
+        self != Column::Subagents
+    }
+}
+
+/// Every column once, in display order, each shown or hidden.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLayout {
+    entries: Vec<(Column, bool)>,
+}
+
+impl ColumnLayout {
+    /// `columns` lists the shown columns in order, with the rest hidden
+    /// after them. When it is empty, the default columns show in their
+    /// default order minus `hidden`. Unknown names are ignored.
+    pub fn from_config(columns: &[String], hidden: &[String]) -> Self {
+        let listed: Vec<Column> = columns
+            .iter()
+            .filter_map(|key| Column::parse(key))
+            .collect();
+        if listed.is_empty() {
+            let hidden: Vec<Column> = hidden.iter().filter_map(|key| Column::parse(key)).collect();
+            return Self {
+                entries: Column::ALL
+                    .iter()
+                    .map(|column| {
+                        (
+                            *column,
+                            column.shown_by_default() && !hidden.contains(column),
+                        )
+                    })
+                    .collect(),
+            };
+        }
+
+        let mut entries: Vec<(Column, bool)> = Vec::new();
+        for column in listed {
+            if !entries.iter().any(|(known, _)| *known == column) {
+                entries.push((column, true));
+            }
+        }
+        for column in Column::ALL {
+            if !entries.iter().any(|(known, _)| *known == column) {
+                entries.push((column, false));
+            }
+        }
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[(Column, bool)] {
+        &self.entries
+    }
+
+    pub fn visible(&self) -> Vec<Column> {
+        self.entries
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(column, _)| *column)
+            .collect()
+    }
+
+    /// Show or hide the entry at `index`. The last shown column stays, since
+    /// an empty `columns` list means the default layout.
+    pub fn toggle(&mut self, index: usize) {
+        let last_shown = self.visible().len() == 1;
+        if let Some((_, shown)) = self.entries.get_mut(index)
+            && !(*shown && last_shown)
+        {
+            *shown = !*shown;
+        }
+    }
+
+    /// Move the entry at `index` one place up (`up`) or down, returning its
+    /// new index.
+    pub fn shift(&mut self, index: usize, up: bool) -> usize {
+        let target = if up {
+            index.saturating_sub(1)
+        } else {
+            (index + 1).min(self.entries.len().saturating_sub(1))
+        };
+        if index < self.entries.len() {
+            self.entries.swap(index, target);
+        }
+        target
+    }
+
+    /// The `columns` config value for this layout.
+    pub fn to_config(&self) -> Vec<String> {
+        self.visible()
+            .into_iter()
+            .map(|column| column.key().to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,355 @@
+//! Key bindings for the TUI's actions, with `[keys]` overrides.
+//!
+//! Each action has default keys. Binding an action in `[keys]` replaces its
+//! defaults, and configured keys win over any default they collide with, so
+//! `sessions = "v"` frees nothing else but `sessions = "q"` takes `q` away
+//! from quitting. Text inputs (search, date range, date jump) read raw keys
+//! and are not remapped.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    PrevTab,
+    NextTab,
+    Up,
+    Down,
+    Top,
+    Bottom,
+    PageUp,
+    PageDown,
+    Open,
+    Back,
+    Search,
+    CyclePeriod,
+    Sessions,
+    Models,
+    Lengths,
+    Heat,
+    Insights,
+    Achievements,
+    Compare,
+    Tools,
+    Diagnostics,
+    ReverseSort,
+    ToggleEmpty,
+    ToggleSummary,
+    Trend,
+    Columns,
+    CostMode,
+    Live,
+    Rescan,
+    DateRange,
+    DismissUpdate,
+}
+
+impl Action {
+    pub const ALL: [Action; 32] = [
+        Action::Quit,
+        Action::PrevTab,
+        Action::NextTab,
+        Action::Up,
+        Action::Down,
+        Action::Top,
+        Action::Bottom,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Open,
+        Action::Back,
+        Action::Search,
+        Action::CyclePeriod,
+        Action::Sessions,
+        Action::Models,
+        Action::Lengths,
+        Action::Heat,
+        Action::Insights,
+        Action::Achievements,
+        Action::Compare,
+        Action::Tools,
+        Action::Diagnostics,
+        Action::ReverseSort,
+        Action::ToggleEmpty,
+        Action::ToggleSummary,
+        Action::Trend,
+        Action::Columns,
+        Action::CostMode,
+        Action::Live,
+        Action::Rescan,
+        Action::DateRange,
+        Action::DismissUpdate,
+    ];
+
+    /// Name used as the key in `[keys]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::PrevTab => "prev_tab",
+            Action::NextTab => "next_tab",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Open => "open",
+            Action::Back => "back",
+            Action::Search => "search",
+            Action::CyclePeriod => "cycle_period",
+            Action::Sessions => "sessions",
+            Action::Models => "models",
+            Action::Lengths => "lengths",
+            Action::Heat => "heat",
+            Action::Insights => "insights",
+            Action::Achievements => "achievements",
+            Action::Compare => "compare",
+            Action::Tools => "tools",
+            Action::Diagnostics => "diagnostics",
+            Action::ReverseSort => "reverse_sort",
+            Action::ToggleEmpty => "toggle_empty",
+            Action::ToggleSummary => "toggle_summary",
+            Action::Trend => "trend",
+            Action::Columns => "columns",
+            Action::CostMode => "cost_mode",
+            Action::Live => "live",
+            Action::Rescan => "rescan",
+            Action::DateRange => "date_range",
+            Action::DismissUpdate => "dismiss_update",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::PrevTab => &["left", "h"],
+            Action::NextTab => &["right", "l"],
+            Action::Up => &["up", "k"],
+            Action::Down => &["down", "j"],
+            Action::Top => &["home"],
+            Action::Bottom => &["end"],
+            Action::PageUp => &["pageup"],
+            Action::PageDown => &["pagedown"],
+            Action::Open => &["enter"],
+            Action::Back => &["esc"],
+            Action::Search => &["/"],
+            Action::CyclePeriod => &["m"],
+            Action::Sessions => &["ctrl+t"],
+            Action::Models => &["M"],
+            Action::Lengths => &["H"],
+            Action::Heat => &["W"],
+            Action::Insights => &["I"],
+            Action::Achievements => &["A"],
+            Action::Compare => &["D"],
+            Action::Tools => &["T"],
+            Action::Diagnostics => &["E"],
+            Action::ReverseSort => &["r"],
+            Action::ToggleEmpty => &["e"],
+            Action::ToggleSummary => &["s"],
+            Action::Trend => &["c"],
+            Action::Columns => &["C"],
+            Action::CostMode => &["$"],
+            Action::Live => &["L"],
+            Action::Rescan => &["R"],
+            Action::DateRange => &["d"],
+            Action::DismissUpdate => &["u"],
+        }
+    }
+}
+
+/// A key plus Ctrl/Alt. Shift is implied by the character itself, so `M`
+/// matches Shift+M.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parse specs like `q`, `M`, `ctrl+t`, `alt+enter`, `pagedown`, `space`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = spec;
+        while let Some((modifier, rest)) = key.split_once('+').filter(|(_, rest)| !rest.is_empty())
+        {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                _ => return None,
+            }
+            key = rest;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "comma" => KeyCode::Char(','),
+                "plus" => KeyCode::Char('+'),
+                other => {
+                    let number = other.strip_prefix('f')?.parse::<u8>().ok()?;
+                    KeyCode::F(number)
+                }
+            },
+        };
+        // Ctrl+T arrives as lowercase `t`.
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        Some(Self { code, modifiers })
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        self.code == key.code && self.modifiers == key.modifiers & relevant
+    }
+
+    /// How the key is written in help text, e.g. `Ctrl+T`, `←`, `Enter`.
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) if self.modifiers.is_empty() => c.to_string(),
+            KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(number) => format!("F{number}"),
+            other => format!("{other:?}"),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt+");
+        }
+        label + &key
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    /// Searched in order: configured bindings first, then the defaults of
+    /// actions left unconfigured.
+    bindings: Vec<(KeyBinding, Action)>,
+}
+
+impl Keymap {
+    /// Build from `[keys]`, where each value is one key or a comma-separated
+    /// list. Unknown actions and unparseable keys are ignored.
+    pub fn from_config(keys: &HashMap<String, String>) -> Self {
+        let mut configured: Vec<(Action, Vec<KeyBinding>)> = keys
+            .iter()
+            .filter_map(|(name, spec)| {
+                let action = Action::from_name(name)?;
+                let bindings: Vec<KeyBinding> =
+                    spec.split(',').filter_map(KeyBinding::parse).collect();
+                (!bindings.is_empty()).then_some((action, bindings))
+            })
+            .collect();
+        // HashMap order is arbitrary; keep collisions between configured keys stable.
+        configured.sort_by_key(|(action, _)| Action::ALL.iter().position(|a| a == action));
+
+        let mut bindings: Vec<(KeyBinding, Action)> = configured
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(|key| (*key, *action)))
+            .collect();
+        for action in Action::ALL {
+            if configured.iter().any(|(known, _)| *known == action) {
+                continue;
+            }
+            bindings.extend(
+                action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|spec| KeyBinding::parse(spec))
+                    .map(|key| (key, action)),
+            );
+        }
+        Self { bindings }
+    }
+
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(binding, _)| binding.matches(key))
+            .map(|(_, action)| *action)
+    }
+
+    /// Keys that trigger `action`, skipping any shadowed by another action.
+    fn keys(&self, action: Action) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .filter(|(index, (binding, bound))| {
+                *bound == action
+                    && !self.bindings[..*index]
+                        .iter()
+                        .any(|(earlier, _)| earlier == binding)
+            })
+            .map(|(_, (binding, _))| *binding)
+            .collect()
+    }
+
+    /// Help-text label such as `j` or `↓ or j`.
+    pub fn label(&self, action: Action) -> String {
+        let labels: Vec<String> = self.keys(action).iter().map(KeyBinding::label).collect();
+        if labels.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            labels.join(" or ")
+        }
+    }
+
+    /// Label for a pair of opposite actions, e.g. `←/→ or h/l`.
+    pub fn pair_label(&self, first: Action, second: Action) -> String {
+        let first_keys = self.keys(first);
+        let second_keys = self.keys(second);
+        if first_keys.len() == second_keys.len() && !first_keys.is_empty() {
+            first_keys
+                .iter()
+                .zip(&second_keys)
+                .map(|(a, b)| format!("{}/{}", a.label(), b.label()))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        } else {
+            format!("{}/{}", self.label(first), self.label(second))
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&HashMap::new())
+    }
+}
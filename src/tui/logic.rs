@@ -1,11 +1,12 @@
 /// Logic module for TUI data processing and aggregation.
 ///
 /// Provides functions to aggregate statistics, filter dates, and check for data presence.
+use crate::models::Provider;
 use crate::types::{
-    CompactDate, ConversationMessage, DailyStats, MessageRole, ModelCounts, Stats, TuiStats,
-    intern_model,
+    CompactDate, ConversationMessage, DailyStats, MessageRole, ModelCounts, ModelStats,
+    ProviderStats, Stats, TuiStats, intern_model,
 };
-use chrono::{Datelike, NaiveDate, Weekday};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
@@ -13,7 +14,7 @@ use std::sync::Arc;
 pub use crate::types::SessionAggregate;
 
 /// Accumulate TUI-relevant stats from a full Stats into a TuiStats.
-/// Only copies the 6 fields displayed in the TUI.
+/// Only copies the fields displayed in the TUI.
 pub fn accumulate_tui_stats(dst: &mut TuiStats, src: &Stats) {
     dst.input_tokens = dst.input_tokens.saturating_add(src.input_tokens);
     dst.output_tokens = dst.output_tokens.saturating_add(src.output_tokens);
@@ -21,6 +22,12 @@ pub fn accumulate_tui_stats(dst: &mut TuiStats, src: &Stats) {
     dst.cached_tokens = dst.cached_tokens.saturating_add(src.cached_tokens);
     dst.add_cost(src.cost);
     dst.tool_calls = dst.tool_calls.saturating_add(src.tool_calls);
+    if src.subagent_messages > 0 {
+        dst.subagent_cost_cents = dst
+            .subagent_cost_cents
+            .saturating_add((src.cost * 100.0).round() as u32);
+    }
+    dst.add_premium_requests(src.premium_requests);
 }
 
 fn parse_period_parts(day: &str) -> Option<(u32, u32, Option<u32>)> {
@@ -253,95 +260,6 @@ pub fn date_matches_buffer(day: &str, buffer: &str) -> bool {
     false
 }
 
-/// Roll up daily statistics into periods derived by `period_key_fn`.
-///
-/// `period_key_fn` returns a `(period_key, representative_date)` pair for each
-/// daily row. All rows with the same key are then merged via
-/// `DailyStats += &DailyStats`.
-fn aggregate_daily_stats_by_period<F>(
-    daily_stats: &BTreeMap<String, DailyStats>,
-    mut period_key_fn: F,
-) -> BTreeMap<String, DailyStats>
-where
-    F: FnMut(&DailyStats) -> (String, CompactDate),
-{
-    let mut aggregate_stats = BTreeMap::new();
-
-    for day_stats in daily_stats.values() {
-        let (period_key, period_date) = period_key_fn(day_stats);
-
-        let aggregate_entry = aggregate_stats
-            .entry(period_key)
-            .or_insert_with(|| DailyStats {
-                date: period_date,
-                ..DailyStats::default()
-            });
-
-        *aggregate_entry += day_stats;
-    }
-
-    aggregate_stats
-}
-
-/// Roll up daily statistics into monthly totals.
-///
-/// Groups days by `YYYY-MM`, using the first day of the month as the
-/// representative `CompactDate` stored in the aggregated row.
-pub fn aggregate_daily_stats_by_month(
-    daily_stats: &BTreeMap<String, DailyStats>,
-) -> BTreeMap<String, DailyStats> {
-    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
-        let year = day_stats.date.year();
-        let month = day_stats.date.month();
-        (
-            format!("{year:04}-{month:02}"),
-            CompactDate::from_parts(year, month, 1),
-        )
-    })
-}
-
-/// Roll up daily statistics into ISO weekly totals.
-///
-/// Weeks use ISO-8601 semantics, so they start on Monday and are keyed as
-/// `YYYY-Www`, where `YYYY` is the ISO week year.
-pub fn aggregate_daily_stats_by_week(
-    daily_stats: &BTreeMap<String, DailyStats>,
-) -> BTreeMap<String, DailyStats> {
-    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
-        let date = NaiveDate::from_ymd_opt(
-            day_stats.date.year() as i32,
-            day_stats.date.month() as u32,
-            day_stats.date.day() as u32,
-        )
-        .expect("CompactDate should always contain a valid calendar date");
-        let iso_week = date.iso_week();
-        let week_start = NaiveDate::from_isoywd_opt(iso_week.year(), iso_week.week(), Weekday::Mon)
-            .expect("ISO week from a valid date should map back to a valid Monday");
-
-        (
-            format!("{:04}-W{:02}", iso_week.year(), iso_week.week()),
-            CompactDate::from_parts(
-                week_start.year() as u16,
-                week_start.month() as u8,
-                week_start.day() as u8,
-            ),
-        )
-    })
-}
-
-/// Roll up daily statistics into yearly totals.
-///
-/// Yearly rows are keyed as `YYYY` and use January 1st as the representative
-/// `CompactDate`.
-pub fn aggregate_daily_stats_by_year(
-    daily_stats: &BTreeMap<String, DailyStats>,
-) -> BTreeMap<String, DailyStats> {
-    aggregate_daily_stats_by_period(daily_stats, |day_stats| {
-        let year = day_stats.date.year();
-        (format!("{year:04}"), CompactDate::from_parts(year, 1, 1))
-    })
-}
-
 /// Return whether a period contains no visible activity for the aggregate table.
 pub fn is_empty_period(stats: &DailyStats) -> bool {
     stats.stats.cost_cents == 0
@@ -392,6 +310,95 @@ pub fn has_data_shared(stats: &crate::types::SharedAnalyzerView) -> bool {
     has_data_view(&stats.read())
 }
 
+/// Tab names in display order, used to carry per-tab UI state across updates.
+pub fn tab_names(display_stats: &[crate::types::SharedAnalyzerView]) -> Vec<Arc<str>> {
+    display_stats
+        .iter()
+        .map(|view| view.read().analyzer_name.clone())
+        .collect()
+}
+
+/// Reorder per-tab state so each entry stays with its analyzer when tabs
+/// appear or disappear. Tabs new to `new_names` start from `fill`.
+pub fn remap_tab_state<T: Clone>(
+    old_names: &[Arc<str>],
+    new_names: &[Arc<str>],
+    state: &[T],
+    fill: T,
+) -> Vec<T> {
+    new_names
+        .iter()
+        .map(|name| {
+            old_names
+                .iter()
+                .position(|old| old == name)
+                .and_then(|index| state.get(index))
+                .cloned()
+                .unwrap_or_else(|| fill.clone())
+        })
+        .collect()
+}
+
+/// Index of the previously selected analyzer among `new_names`. If it is gone,
+/// fall back to the nearest surviving tab that preceded it.
+pub fn follow_selected_tab(
+    old_names: &[Arc<str>],
+    new_names: &[Arc<str>],
+    selected: usize,
+) -> usize {
+    old_names
+        .get(..=selected.min(old_names.len().saturating_sub(1)))
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .find_map(|name| new_names.iter().position(|new| new == name))
+        .unwrap_or(0)
+}
+
+/// Sum per-model stats across the given days, most expensive model first.
+/// Ties (e.g. free models) fall back to message count, then name.
+pub fn aggregate_model_stats<'a>(
+    days: impl IntoIterator<Item = &'a DailyStats>,
+) -> Vec<ModelStats> {
+    let mut by_model: BTreeMap<&str, ModelStats> = BTreeMap::new();
+    for day in days {
+        for (model, stats) in &day.model_stats {
+            by_model
+                .entry(model)
+                .or_insert_with(|| ModelStats::new(model.clone()))
+                .add_model_stats(stats);
+        }
+    }
+
+    let mut models: Vec<ModelStats> = by_model
+        .into_values()
+        .filter(|stats| stats.message_count > 0)
+        .collect();
+    models.sort_by(|a, b| {
+        b.cost
+            .total_cmp(&a.cost)
+            .then(b.message_count.cmp(&a.message_count))
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    models
+}
+
+/// Roll per-model totals (from [`aggregate_model_stats`]) up by provider,
+/// most expensive first.
+pub fn aggregate_provider_stats(models: &[ModelStats]) -> Vec<ProviderStats> {
+    let mut by_provider: BTreeMap<Provider, ProviderStats> = BTreeMap::new();
+    for model in models {
+        let provider = Provider::for_model(&model.model);
+        by_provider
+            .entry(provider)
+            .or_insert_with(|| ProviderStats::new(provider))
+            .add_model_stats(model);
+    }
+    let mut providers: Vec<ProviderStats> = by_provider.into_values().collect();
+    providers.sort_by(|a, b| b.cost.total_cmp(&a.cost).then(a.provider.cmp(&b.provider)));
+    providers
+}
+
 /// Aggregate sessions from a slice of messages with a specified analyzer name.
 /// Used when converting AgenticCodingToolStats to AnalyzerStatsView.
 ///
@@ -402,8 +409,14 @@ pub fn aggregate_sessions_from_messages(
     analyzer_name: Arc<str>,
 ) -> Vec<SessionAggregate> {
     let mut sessions: BTreeMap<String, SessionAggregate> = BTreeMap::new();
+    let mut timestamps: BTreeMap<&str, Vec<DateTime<Utc>>> = BTreeMap::new();
+    let mut firsts = crate::utils::first_messages_by_session(messages);
 
     for msg in messages {
+        timestamps
+            .entry(msg.conversation_hash.as_str())
+            .or_default()
+            .push(msg.date);
         // Use or_insert_with_key to avoid redundant cloning:
         // - Pass owned key to entry() (1 clone of conversation_hash)
         // - Clone key only when inserting a new session (via closure's &key)
@@ -411,11 +424,13 @@ pub fn aggregate_sessions_from_messages(
             .entry(msg.conversation_hash.clone())
             .or_insert_with_key(|key| SessionAggregate {
                 session_id: key.clone(),
+                stable_id: String::new(),
                 first_timestamp: msg.date,
                 analyzer_name: Arc::clone(&analyzer_name),
                 stats: TuiStats::default(),
                 models: ModelCounts::new(),
                 session_name: None,
+                project: crate::utils::project_dir(&msg.project_hash),
                 date: CompactDate::from_local(&msg.date),
             });
 
@@ -439,10 +454,22 @@ pub fn aggregate_sessions_from_messages(
         }
     }
 
+    for (session_id, session) in sessions.iter_mut() {
+        if let Some(timestamps) = timestamps.remove(session_id.as_str()) {
+            session.stats.active_secs = crate::stats::active::active_seconds(timestamps);
+        }
+        if let Some(first) = firsts.remove(session_id.as_str()) {
+            session.stable_id = first
+                .stable_session_id
+                .clone()
+                .unwrap_or_else(|| crate::utils::stable_session_id(&session.analyzer_name, first));
+        }
+    }
+
     let mut result: Vec<SessionAggregate> = sessions.into_values().collect();
 
     // Sort oldest sessions first so newest appear at the bottom
-    result.sort_by_key(|s| s.first_timestamp);
+    result.sort_by(SessionAggregate::chronological);
 
     // Shrink to fit to release excess capacity
     result.shrink_to_fit();
@@ -478,4 +505,60 @@ mod tests {
 
         assert!(!has_data_view(&view));
     }
+
+    #[test]
+    fn aggregate_model_stats_sums_across_days_and_sorts_by_cost() {
+        let mut daily_stats = BTreeMap::new();
+        for (day, model, cost, messages) in [
+            ("2025-01-01", "cheap", 0.5, 3),
+            ("2025-01-01", "pricey", 2.0, 1),
+            ("2025-01-02", "cheap", 2.0, 2),
+            ("2025-01-02", "free", 0.0, 4),
+        ] {
+            let entry: &mut DailyStats = daily_stats.entry(day.to_string()).or_default();
+            let model_stats = entry
+                .model_stats
+                .entry(model.to_string())
+                .or_insert_with(|| ModelStats::new(model.to_string()));
+            model_stats.cost += cost;
+            model_stats.message_count += messages;
+            model_stats.input_tokens += 10;
+        }
+
+        let models = aggregate_model_stats(daily_stats.values());
+        let names: Vec<&str> = models.iter().map(|m| m.model.as_str()).collect();
+        assert_eq!(names, vec!["cheap", "pricey", "free"]);
+        assert_eq!(models[0].message_count, 5);
+        assert_eq!(models[0].input_tokens, 20);
+        assert!((models[0].cost - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn aggregate_provider_stats_groups_models_by_provider() {
+        let models: Vec<ModelStats> = [
+            ("claude-sonnet-4-5", 3.0, 2),
+            ("claude-haiku-4-5", 0.5, 5),
+            ("gpt-5", 4.0, 1),
+            ("qwen3-coder-plus", 0.2, 7),
+        ]
+        .into_iter()
+        .map(|(model, cost, messages)| ModelStats {
+            cost,
+            message_count: messages,
+            output_tokens: 100,
+            ..ModelStats::new(model.to_string())
+        })
+        .collect();
+
+        let providers = aggregate_provider_stats(&models);
+        let order: Vec<Provider> = providers.iter().map(|p| p.provider).collect();
+        assert_eq!(
+            order,
+            vec![Provider::OpenAI, Provider::Anthropic, Provider::OpenWeights]
+        );
+        assert_eq!(providers[1].models, 2);
+        assert_eq!(providers[1].message_count, 7);
+        assert_eq!(providers[1].output_tokens, 200);
+        assert!((providers[1].cost - 3.5).abs() < 1e-9);
+    }
 }
@@ -1,24 +1,36 @@
+use crate::subscription::CostBasis;
 /// Tests for TUI components: table state management, upload progress, date matching, and stats accumulation.
+use crate::tui::aggregation::{
+    DailyTrend, HeatGrid, PeriodBests, SessionLengths, ToolCounts, ToolUsage,
+    aggregate_daily_stats_by_month, aggregate_daily_stats_by_week, aggregate_daily_stats_by_year,
+    split_widths,
+};
+use crate::tui::columns::{Column, ColumnLayout};
+use crate::tui::keymap::{Action, KeyBinding, Keymap};
 use crate::tui::logic::{
-    accumulate_tui_stats, aggregate_daily_stats_by_month, aggregate_daily_stats_by_week,
-    aggregate_daily_stats_by_year, date_matches_buffer, filtered_aggregate_keys,
+    accumulate_tui_stats, date_matches_buffer, filtered_aggregate_keys, follow_selected_tab,
+    remap_tab_state,
 };
+use crate::tui::theme::{Theme, make_plain};
 use crate::tui::{
-    AggregateViewMode, PeriodFilter, build_display_stats, cost_heat,
-    create_upload_progress_callback, draw_aggregate_stats_table, format_month_for_display,
-    format_week_for_display, format_year_for_display, parse_accent, show_upload_error,
-    show_upload_success, update_period_filters, update_table_states, update_window_offsets,
+    AggregateViewMode, AnalyzerStyles, BackgroundLoad, DayDetail, DisplayStatsCache, PeriodFilter,
+    SessionDetail, build_display_stats, create_upload_progress_callback,
+    draw_aggregate_stats_table, draw_day_detail, draw_model_stats_table, draw_session_detail,
+    format_active_time, format_month_for_display, format_usage_window, format_week_for_display,
+    format_year_for_display, parse_accent, show_upload_error, show_upload_success,
+    update_period_filters, update_table_states, update_window_offsets, visible_sessions,
 };
 use crate::types::{
-    AgenticCodingToolStats, AnalyzerStatsView, CompactDate, DailyStats, MultiAnalyzerStats, Stats,
-    TuiStats,
+    AgenticCodingToolStats, AnalyzerStatsView, CompactDate, DailyStats, DateRange, ModelCounts,
+    MultiAnalyzerStats, SessionAggregate, Stats, TuiStats, intern_model,
 };
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::Terminal;
 use ratatui::backend::TestBackend;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::TableState;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 // ============================================================================
@@ -58,19 +70,213 @@ fn accent_color_parsing() {
     assert_eq!(parse_accent("not-a-color"), Color::Cyan); // default
 }
 
+#[test]
+fn keymap_defaults_overrides_and_labels() {
+    let press = |code, modifiers| KeyEvent::new(code, modifiers);
+    let defaults = Keymap::default();
+    assert_eq!(
+        defaults.action(&press(KeyCode::Char('t'), KeyModifiers::CONTROL)),
+        Some(Action::Sessions)
+    );
+    assert_eq!(
+        defaults.action(&press(KeyCode::Char('t'), KeyModifiers::NONE)),
+        None
+    );
+    // Terminals report Shift alongside uppercase letters.
+    assert_eq!(
+        defaults.action(&press(KeyCode::Char('M'), KeyModifiers::SHIFT)),
+        Some(Action::Models)
+    );
+    assert_eq!(
+        defaults.pair_label(Action::PrevTab, Action::NextTab),
+        "←/→ or h/l"
+    );
+    assert_eq!(defaults.label(Action::Sessions), "Ctrl+T");
+
+    assert_eq!(KeyBinding::parse("Ctrl+T"), KeyBinding::parse("ctrl+t"));
+    assert_eq!(
+        KeyBinding::parse("pagedown").map(|key| key.code),
+        Some(KeyCode::PageDown)
+    );
+    assert_eq!(KeyBinding::parse("hyper+x"), None);
+    assert_eq!(KeyBinding::parse("nope"), None);
+
+    let config = HashMap::from([
+        ("sessions".to_string(), "v".to_string()),
+        ("quit".to_string(), "ctrl+c, x".to_string()),
+        ("reverse-sort".to_string(), "e".to_string()),
+    ]);
+    let keymap = Keymap::from_config(&config);
+    assert_eq!(
+        keymap.action(&press(KeyCode::Char('v'), KeyModifiers::NONE)),
+        Some(Action::Sessions)
+    );
+    // A configured action gives up its default keys...
+    assert_eq!(
+        keymap.action(&press(KeyCode::Char('t'), KeyModifiers::CONTROL)),
+        None
+    );
+    assert_eq!(
+        keymap.action(&press(KeyCode::Char('q'), KeyModifiers::NONE)),
+        None
+    );
+    assert_eq!(keymap.label(Action::Quit), "Ctrl+C or x");
+    // ...and takes keys from actions it collides with.
+    assert_eq!(
+        keymap.action(&press(KeyCode::Char('e'), KeyModifiers::NONE)),
+        Some(Action::ReverseSort)
+    );
+    assert_eq!(keymap.label(Action::ToggleEmpty), "(unbound)");
+}
+
+#[test]
+fn theme_presets_and_role_overrides() {
+    let mut config = crate::config::ThemeConfig::default();
+    // The dark preset keeps honoring `tui.accent_color`.
+    assert_eq!(
+        Theme::from_config(&config, "magenta"),
+        Theme {
+            accent: Color::Magenta,
+            ..Theme::DARK
+        }
+    );
+
+    config.preset = "light".to_string();
+    config.best = Some("#ff0080".to_string());
+    config.dim = Some("not-a-color".to_string());
+    let theme = Theme::from_config(&config, "magenta");
+    assert_eq!(theme.accent, Theme::LIGHT.accent);
+    assert_eq!(theme.best, Color::Rgb(0xff, 0x00, 0x80));
+    // Unparseable overrides keep the preset's color.
+    assert_eq!(theme.dim, Theme::LIGHT.dim);
+
+    config.preset = "no-color".to_string();
+    config.best = None;
+    let theme = Theme::from_config(&config, "magenta");
+    assert_eq!(theme.cost, Color::Reset);
+    assert_eq!(
+        theme.selected_tab(),
+        Style::default().add_modifier(Modifier::REVERSED)
+    );
+}
+
+#[test]
+fn analyzer_styles_use_overrides_and_stable_defaults() {
+    let mut tui_config = crate::config::TuiConfig::default();
+    tui_config
+        .analyzer_colors
+        .insert("Codex CLI".to_string(), "#ff0080".to_string());
+    tui_config
+        .analyzer_colors
+        .insert("Gemini CLI".to_string(), "not-a-color".to_string());
+    tui_config
+        .analyzer_icons
+        .insert("Codex CLI".to_string(), "X".to_string());
+    let styles = AnalyzerStyles::from_config(&tui_config);
+    let theme = Theme::DARK;
+
+    assert_eq!(
+        styles.color("Codex CLI", &theme),
+        Color::Rgb(0xff, 0x00, 0x80)
+    );
+    // Unparseable overrides fall back to the built-in color.
+    assert_eq!(styles.color("Gemini CLI", &theme), Color::Rgb(66, 133, 244));
+    // Unknown tools get the same palette color every time.
+    assert_eq!(
+        styles.color("Acme Agent", &theme),
+        styles.color("Acme Agent", &theme)
+    );
+    // All Tools follows the theme, and no-color drops tool colors entirely.
+    assert_eq!(
+        styles.color(crate::budget::ALL_TOOLS, &Theme::LIGHT),
+        Theme::LIGHT.summary.unwrap()
+    );
+    assert_eq!(styles.color("Codex CLI", &Theme::NO_COLOR), Color::Reset);
+
+    assert_eq!(styles.label("Codex CLI"), "X Codex CLI");
+    assert_eq!(styles.label("Gemini CLI"), "Gemini CLI");
+}
+
+#[test]
+fn column_layout_from_config_and_chooser_edits() {
+    let strings = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+
+    // Without `columns`, the default order minus the legacy hidden list.
+    let layout = ColumnLayout::from_config(&[], &strings(&["reasoning", "models"]));
+    assert_eq!(
+        layout.visible(),
+        vec![
+            Column::Cached,
+            Column::Hit,
+            Column::Input,
+            Column::Output,
+            Column::Convs,
+            Column::Tools,
+            Column::Active,
+            Column::PerHour,
+            Column::Premium,
+            Column::Apps,
+        ]
+    );
+    assert_eq!(Column::parse("premium-requests"), Some(Column::Premium));
+    assert_eq!(Column::parse("cost-per-hour"), Some(Column::PerHour));
+
+    // `columns` wins: listed columns in order, the rest hidden after them.
+    let mut layout = ColumnLayout::from_config(
+        &strings(&["models", "outp", "bogus", "models"]),
+        &strings(&["models"]),
+    );
+    assert_eq!(layout.visible(), vec![Column::Models, Column::Output]);
+    assert_eq!(layout.entries().len(), Column::ALL.len());
+    // Subagent spend only shows when asked for.
+    assert!(
+        !ColumnLayout::from_config(&[], &[])
+            .visible()
+            .contains(&Column::Subagents)
+    );
+    assert_eq!(
+        ColumnLayout::from_config(&strings(&["sidechain"]), &[]).visible(),
+        vec![Column::Subagents]
+    );
+    assert_eq!(layout.entries()[2], (Column::Cached, false));
+
+    layout.toggle(2);
+    assert_eq!(layout.shift(2, true), 1);
+    assert_eq!(layout.shift(0, true), 0);
+    assert_eq!(layout.to_config(), vec!["models", "cached", "output"]);
+
+    // Hiding every column would save `[]`, which reads back as the default.
+    let mut layout = ColumnLayout::from_config(&strings(&["input"]), &[]);
+    let index = layout
+        .entries()
+        .iter()
+        .position(|(column, _)| *column == Column::Input)
+        .unwrap();
+    layout.toggle(index);
+    assert_eq!(layout.visible(), vec![Column::Input]);
+    assert_eq!(layout.to_config(), vec!["input"]);
+}
+
 #[test]
 fn cost_heatmap_scales_with_magnitude() {
+    let theme = Theme::DARK;
     // max == 0 -> green floor
-    assert_eq!(cost_heat(50, 0), Color::Green);
+    assert_eq!(theme.cost_heat(50, 0), Color::Rgb(90, 200, 110));
     // higher cost -> more red, less green
     if let (Color::Rgb(lr, lg, _), Color::Rgb(hr, hg, _)) =
-        (cost_heat(10, 100), cost_heat(100, 100))
+        (theme.cost_heat(10, 100), theme.cost_heat(100, 100))
     {
         assert!(hr > lr, "redder at higher cost");
         assert!(hg < lg, "less green at higher cost");
     } else {
         panic!("expected Rgb colors");
     }
+    // The light preset ends on its own red, and no-color leaves costs alone.
+    assert_eq!(
+        Theme::LIGHT.cost_heat(100, 100),
+        Color::Rgb(0xc0, 0x1c, 0x28)
+    );
+    assert_eq!(Theme::NO_COLOR.cost_heat(100, 100), Color::Reset);
 }
 
 // ============================================================================
@@ -94,6 +300,8 @@ fn make_tool_stats(name: &str, has_data: bool) -> AgenticCodingToolStats {
                 },
                 model_stats: BTreeMap::new(),
                 apps: BTreeMap::new(),
+                prompt_cache: Default::default(),
+                hourly_messages: [0; 24],
             },
         );
     }
@@ -126,6 +334,8 @@ fn make_daily_stats(
         },
         model_stats: BTreeMap::new(),
         apps: BTreeMap::new(),
+        prompt_cache: Default::default(),
+        hourly_messages: [0; 24],
     }
 }
 
@@ -224,8 +434,8 @@ fn aggregate_table_preserves_leading_digit_in_large_tool_total() {
                 "",
                 false,
                 false,
-                Color::Cyan,
-                &HashSet::new(),
+                &Theme::DARK,
+                &Column::ALL,
                 false,
             );
         })
@@ -248,6 +458,657 @@ fn aggregate_table_preserves_leading_digit_in_large_tool_total() {
     );
 }
 
+#[test]
+fn plain_output_strips_colors_and_box_drawing() {
+    let mut daily_stats = BTreeMap::new();
+    for day in 1..=3 {
+        let date = format!("2025-01-{day:02}");
+        daily_stats.insert(date.clone(), make_daily_stats(&date, 2, 100, 3));
+    }
+    let stats = AnalyzerStatsView {
+        daily_stats,
+        session_aggregates: Vec::new(),
+        num_conversations: 3,
+        analyzer_name: Arc::from("Test"),
+    };
+    let format_options = crate::utils::NumberFormatOptions {
+        use_comma: false,
+        use_human: false,
+        locale: "en".to_string(),
+        currency_symbol: "$".to_string(),
+        cost_decimal_places: 2,
+        decimal_places: 2,
+    };
+    let mut terminal = Terminal::new(TestBackend::new(160, 12)).unwrap();
+    let mut table_state = TableState::default();
+    table_state.select(Some(0));
+
+    terminal
+        .draw(|frame| {
+            draw_aggregate_stats_table(
+                frame,
+                Rect::new(0, 0, 160, 12),
+                &stats,
+                &format_options,
+                &mut table_state,
+                AggregateViewMode::Daily,
+                "",
+                false,
+                false,
+                &Theme::NO_COLOR,
+                &Column::ALL,
+                false,
+            );
+            make_plain(frame.buffer_mut());
+        })
+        .unwrap();
+
+    let buffer = terminal.backend().buffer();
+    assert!(
+        buffer
+            .content
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset)
+    );
+    let rendered = buffer
+        .content
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect::<String>();
+    assert!(rendered.is_ascii(), "non-ASCII glyph left in: {rendered}");
+    // The selection arrow and the totals separator survive as ASCII.
+    assert!(rendered.contains('>'));
+    assert!(rendered.contains("-------"));
+}
+
+#[test]
+fn session_lengths_bucket_messages_and_tokens() {
+    let session = |messages: u32, tokens: u64| SessionAggregate {
+        session_id: format!("s{messages}"),
+        stable_id: String::new(),
+        first_timestamp: chrono::Utc::now(),
+        analyzer_name: Arc::from("Test"),
+        stats: TuiStats {
+            input_tokens: tokens,
+            ..TuiStats::default()
+        },
+        models: ModelCounts::from_single(intern_model("claude-sonnet-4"), messages),
+        session_name: None,
+        project: None,
+        date: CompactDate::from_str("2025-01-01").unwrap(),
+    };
+    let sessions = vec![
+        session(1, 2_000),
+        session(3, 40_000),
+        session(4, 60_000),
+        session(250, 200_000),
+        session(400, 9_000_000),
+    ];
+
+    let lengths = SessionLengths::from_sessions(&sessions);
+
+    assert_eq!(lengths.sessions, 5);
+    assert_eq!(lengths.messages.buckets[0], ("1", 1));
+    assert_eq!(lengths.messages.buckets[1], ("2-5", 2));
+    // Bounds are inclusive: exactly 250 messages isn't "more than 250".
+    assert_eq!(lengths.messages.buckets[6], ("101-250", 1));
+    assert_eq!(lengths.messages.buckets.last(), Some(&(">250", 1)));
+    assert_eq!(lengths.tokens.buckets.last(), Some(&(">5m", 1)));
+    assert_eq!(lengths.messages.median, 4);
+    assert_eq!(lengths.tokens.buckets[1], ("10k-50k", 1));
+    assert_eq!(lengths.tokens.buckets[2], ("50k-100k", 1));
+    assert_eq!(lengths.tokens.max_count(), 1);
+    assert_eq!(
+        SessionLengths::from_sessions(&[]).messages.median,
+        0,
+        "empty views have no median"
+    );
+}
+
+#[test]
+fn session_detail_lists_messages_for_selected_session() {
+    let session = |id: &str, day: &str| SessionAggregate {
+        session_id: id.to_string(),
+        stable_id: String::new(),
+        first_timestamp: chrono::Utc::now(),
+        analyzer_name: Arc::from("Test"),
+        stats: TuiStats::default(),
+        models: ModelCounts::new(),
+        session_name: None,
+        project: None,
+        date: CompactDate::from_str(day).unwrap(),
+    };
+    let sessions = vec![session("a", "2025-01-01"), session("b", "2025-01-02")];
+    let filter = PeriodFilter::from_period_key("2025-01-02", AggregateViewMode::Daily);
+    assert_eq!(
+        visible_sessions(&sessions, filter, "", false)[0].session_id,
+        "b"
+    );
+    assert_eq!(
+        visible_sessions(&sessions, None, "", true)[0].session_id,
+        "b"
+    );
+
+    let message = crate::types::ConversationMessage {
+        application: crate::types::Application::ClaudeCode,
+        date: chrono::Utc::now(),
+        project_hash: String::new(),
+        conversation_hash: "b".to_string(),
+        local_hash: None,
+        global_hash: "g".to_string(),
+        model: Some("claude-sonnet-4".to_string()),
+        stats: Stats {
+            input_tokens: 1234,
+            cost: 0.42,
+            tool_calls: 3,
+            ..Stats::default()
+        },
+        role: crate::types::MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    };
+    let (_tx, receiver) = std::sync::mpsc::channel();
+    let mut detail = SessionDetail {
+        title: "Test • b".to_string(),
+        messages: BackgroundLoad {
+            result: Some(Ok(vec![message])),
+            receiver,
+            what: "Session",
+        },
+        table_state: TableState::default().with_selected(Some(0)),
+    };
+    detail.move_selection(isize::MAX);
+    assert_eq!(detail.table_state.selected(), Some(0));
+
+    let format_options = crate::utils::NumberFormatOptions {
+        use_comma: false,
+        use_human: false,
+        locale: "en".to_string(),
+        currency_symbol: "$".to_string(),
+        cost_decimal_places: 2,
+        decimal_places: 2,
+    };
+    let mut terminal = Terminal::new(TestBackend::new(160, 6)).unwrap();
+    terminal
+        .draw(|frame| {
+            draw_session_detail(
+                frame,
+                Rect::new(0, 0, 160, 6),
+                &mut detail,
+                &format_options,
+                &Theme::DARK,
+            );
+        })
+        .unwrap();
+    let rendered = terminal
+        .backend()
+        .buffer()
+        .content
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect::<String>();
+    assert!(rendered.contains("1 message"), "{rendered}");
+    assert!(rendered.contains("assistant"));
+    assert!(rendered.contains("claude-sonnet-4"));
+    assert!(rendered.contains("$0.42"));
+}
+
+#[test]
+fn session_search_matches_name_tool_model_and_project() {
+    let session = |id: &str, name: Option<&str>, tool: &str, model: &str| SessionAggregate {
+        session_id: id.to_string(),
+        stable_id: String::new(),
+        first_timestamp: chrono::Utc::now(),
+        analyzer_name: Arc::from(tool),
+        stats: TuiStats::default(),
+        models: ModelCounts::from_single(intern_model(model), 1),
+        session_name: name.map(str::to_string),
+        project: None,
+        date: CompactDate::from_str("2025-01-02").unwrap(),
+    };
+    let mut sessions = vec![
+        session(
+            "a1",
+            Some("Fix login bug"),
+            "Claude Code",
+            "claude-sonnet-4",
+        ),
+        session("b2", None, "Codex CLI", "gpt-5-codex"),
+        session("c3", Some("Refactor parser"), "Qwen Code", "qwen3-coder"),
+    ];
+    sessions[1].project = Some(Arc::from("/home/me/src/splitrail"));
+    let ids = |search: &str| {
+        visible_sessions(&sessions, None, search, false)
+            .iter()
+            .map(|session| session.session_id.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(""), vec!["a1", "b2", "c3"]);
+    assert_eq!(ids("LOGIN"), vec!["a1"]);
+    assert_eq!(ids("codex"), vec!["b2"]);
+    assert_eq!(ids("qwen3"), vec!["c3"]);
+    assert_eq!(ids("b2"), vec!["b2"]);
+    assert_eq!(ids("splitrail"), vec!["b2"]);
+    assert!(ids("nothing").is_empty());
+
+    let day = PeriodFilter::from_period_key("2025-01-03", AggregateViewMode::Daily);
+    assert!(visible_sessions(&sessions, day, "", false).is_empty());
+}
+
+#[test]
+fn background_load_reports_result_once_and_dead_loaders() {
+    let mut load = BackgroundLoad::spawn("Test", || Ok(7));
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !load.poll() {
+        assert!(std::time::Instant::now() < deadline, "load never finished");
+        std::thread::yield_now();
+    }
+    assert_eq!(load.result(), Some(&Ok(7)));
+    assert!(!load.poll());
+
+    let (tx, receiver) = std::sync::mpsc::channel::<Result<u32, String>>();
+    drop(tx);
+    let mut dead = BackgroundLoad {
+        result: None,
+        receiver,
+        what: "Test",
+    };
+    assert!(dead.poll());
+    assert_eq!(
+        dead.result(),
+        Some(&Err("Test loader exited unexpectedly".to_string()))
+    );
+}
+
+#[test]
+fn day_detail_lists_every_hour_of_the_day() {
+    let mut hours = [crate::stats::hourly::HourStats::default(); 24];
+    hours[14] = crate::stats::hourly::HourStats {
+        user_messages: 2,
+        ai_messages: 5,
+        tokens: 12_000,
+        cost: 1.25,
+    };
+    let (_tx, receiver) = std::sync::mpsc::channel();
+    let mut detail = DayDetail {
+        title: "Test • 2025-01-02 by hour".to_string(),
+        day: CompactDate::from_str("2025-01-02").unwrap(),
+        hours: BackgroundLoad {
+            result: Some(Ok(hours)),
+            receiver,
+            what: "Hourly",
+        },
+        table_state: TableState::default().with_selected(Some(14)),
+    };
+    detail.move_selection(isize::MAX);
+    assert_eq!(detail.table_state.selected(), Some(23));
+    detail.move_selection(-9);
+
+    let format_options = crate::utils::NumberFormatOptions {
+        use_comma: false,
+        use_human: false,
+        locale: "en".to_string(),
+        currency_symbol: "$".to_string(),
+        cost_decimal_places: 2,
+        decimal_places: 2,
+    };
+    let mut terminal = Terminal::new(TestBackend::new(100, 26)).unwrap();
+    terminal
+        .draw(|frame| {
+            draw_day_detail(
+                frame,
+                Rect::new(0, 0, 100, 26),
+                &mut detail,
+                &format_options,
+                &Theme::DARK,
+            );
+        })
+        .unwrap();
+    let rendered = terminal
+        .backend()
+        .buffer()
+        .content
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect::<String>();
+    assert!(rendered.contains("2025-01-02 by hour"), "{rendered}");
+    assert!(rendered.contains("00:00"));
+    assert!(rendered.contains("14:00"));
+    assert!(rendered.contains("$1.25"));
+}
+
+#[test]
+fn heat_grid_sums_hours_by_weekday() {
+    let day = |date: &str, hours: &[(usize, u32)]| {
+        let mut stats = DailyStats {
+            date: CompactDate::from_str(date).unwrap(),
+            ..DailyStats::default()
+        };
+        for &(hour, count) in hours {
+            stats.hourly_messages[hour] = count;
+        }
+        stats
+    };
+    // 2025-01-06 and 2025-01-13 are Mondays; 2025-01-12 is a Sunday.
+    let days = [
+        day("2025-01-06", &[(9, 2), (14, 1)]),
+        day("2025-01-13", &[(9, 3)]),
+        day("2025-01-12", &[(23, 4)]),
+    ];
+
+    let grid = HeatGrid::from_days(&days);
+
+    assert_eq!(grid.cells[0][9], 5);
+    assert_eq!(grid.cells[0][14], 1);
+    assert_eq!(grid.cells[6][23], 4);
+    assert_eq!(grid.day_total(0), 6);
+    assert_eq!(grid.max(), 5);
+    assert_eq!(grid.peak(), Some((0, 9, 5)));
+    assert_eq!(HeatGrid::default().peak(), None);
+}
+
+#[test]
+fn tab_state_follows_analyzer_when_a_tab_appears() {
+    let names = |list: &[&str]| {
+        list.iter()
+            .map(|n| Arc::<str>::from(*n))
+            .collect::<Vec<_>>()
+    };
+    let old = names(&["All Tools", "Claude Code", "Codex CLI"]);
+    // Gemini CLI gets its first data and lands between the two.
+    let new = names(&["All Tools", "Claude Code", "Gemini CLI", "Codex CLI"]);
+
+    assert_eq!(follow_selected_tab(&old, &new, 2), 3);
+    assert_eq!(follow_selected_tab(&old, &new, 0), 0);
+    assert_eq!(
+        remap_tab_state(&old, &new, &[10, 20, 30], 0),
+        vec![10, 20, 0, 30]
+    );
+
+    // When the selected tab disappears, fall back to the one before it.
+    let shrunk = names(&["All Tools", "Codex CLI"]);
+    assert_eq!(follow_selected_tab(&old, &shrunk, 1), 0);
+    assert_eq!(follow_selected_tab(&old, &shrunk, 2), 1);
+    assert_eq!(follow_selected_tab(&[], &new, 0), 0);
+}
+
+#[test]
+fn daily_trend_fills_missing_days_with_zero() {
+    let day = |date: &str, cost_cents: u32, tokens: u64| {
+        (
+            date.to_string(),
+            DailyStats {
+                date: CompactDate::from_str(date).unwrap(),
+                stats: TuiStats {
+                    input_tokens: tokens,
+                    cached_tokens: tokens,
+                    cost_cents,
+                    ..TuiStats::default()
+                },
+                ..DailyStats::default()
+            },
+        )
+    };
+    let daily_stats = BTreeMap::from([
+        day("2025-02-26", 900, 1),
+        day("2025-02-28", 150, 10),
+        day("2025-03-02", 250, 20),
+    ]);
+    let end = chrono::NaiveDate::from_ymd_opt(2025, 3, 2).unwrap();
+
+    let trend = DailyTrend::ending(&daily_stats, end, 4);
+
+    // 02-27 .. 03-02: the 26th falls outside; the 27th and 1st have no data.
+    assert_eq!(trend.cost_cents, vec![0, 150, 0, 250]);
+    assert_eq!(trend.tokens, vec![0, 20, 0, 40]);
+    assert_eq!(trend.total_cost_cents(), 400);
+    assert_eq!(trend.total_tokens(), 60);
+}
+
+#[test]
+fn tool_usage_groups_categories_by_day_and_session() {
+    use chrono::TimeZone;
+
+    let message = |day: u32, session: &str, stats: Stats| crate::types::ConversationMessage {
+        application: crate::types::Application::ClaudeCode,
+        date: chrono::Local
+            .with_ymd_and_hms(2025, 1, day, 12, 0, 0)
+            .unwrap()
+            .to_utc(),
+        project_hash: String::new(),
+        conversation_hash: session.to_string(),
+        local_hash: None,
+        global_hash: format!("{session}-{day}"),
+        model: Some("claude-sonnet-4".to_string()),
+        stats,
+        role: crate::types::MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    };
+    let messages = [
+        message(
+            6,
+            "a",
+            Stats {
+                tool_calls: 3,
+                terminal_commands: 2,
+                files_read: 1,
+                ..Stats::default()
+            },
+        ),
+        message(
+            6,
+            "b",
+            Stats {
+                tool_calls: 1,
+                files_edited: 1,
+                ..Stats::default()
+            },
+        ),
+        message(
+            7,
+            "a",
+            Stats {
+                tool_calls: 2,
+                file_searches: 2,
+                ..Stats::default()
+            },
+        ),
+    ];
+
+    let mut usage = ToolUsage::default();
+    usage.add_messages(&messages);
+
+    let session_a = &usage.by_session["a"];
+    assert_eq!(session_a.tool_calls, 5);
+    assert_eq!(session_a.terminal_commands, 2);
+    assert_eq!(session_a.file_searches, 2);
+
+    let monday = CompactDate::from_str("2025-01-06").unwrap();
+    let day = usage.for_days(|date| date == monday);
+    assert_eq!(day.tool_calls, 4);
+    assert_eq!(day.files_edited, 1);
+    assert_eq!(day.file_searches, 0);
+
+    let week = usage.for_days(|_| true);
+    assert_eq!(week.tool_calls, 6);
+
+    // "All Tools" folds each tool's cached usage together.
+    let mut all = ToolUsage::default();
+    all.merge(&usage);
+    all.merge(&usage);
+    assert_eq!(all.by_session["a"].tool_calls, 10);
+    assert_eq!(all.for_days(|date| date == monday).tool_calls, 8);
+    assert_eq!(
+        week.rows()
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(label, _)| *label)
+            .collect::<Vec<_>>(),
+        vec![
+            "Terminal commands",
+            "File searches",
+            "Files read",
+            "Files edited"
+        ]
+    );
+}
+
+#[test]
+fn composition_bar_widths_fill_the_bar() {
+    assert_eq!(split_widths(&[1, 1, 1], 10), vec![4, 3, 3]);
+    assert_eq!(split_widths(&[900, 100, 0], 10), vec![9, 1, 0]);
+    assert_eq!(split_widths(&[0, 0], 8), vec![0, 0]);
+    // Tiny shares still round to whole cells without overflowing.
+    assert_eq!(split_widths(&[1000, 1, 1], 5).iter().sum::<usize>(), 5);
+
+    let mut usage = ToolUsage::default();
+    usage.add_messages(&[crate::types::ConversationMessage {
+        application: crate::types::Application::ClaudeCode,
+        date: chrono::Utc::now(),
+        project_hash: String::new(),
+        conversation_hash: "s".to_string(),
+        local_hash: None,
+        global_hash: "g".to_string(),
+        model: None,
+        stats: Stats {
+            code_lines: 30,
+            docs_lines: 10,
+            ..Stats::default()
+        },
+        role: crate::types::MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    }]);
+    let composition = usage.by_session["s"].composition();
+    assert_eq!(composition[0], ("Code", 30));
+    assert_eq!(composition[1], ("Docs", 10));
+}
+
+#[test]
+fn command_rows_list_used_categories_in_order() {
+    let mut counts = ToolCounts::default();
+    let mut stats = Stats::default();
+    for command in ["cargo test", "git diff", "git commit -m x", "ls"] {
+        stats.add_command(command);
+    }
+    counts.add(&stats);
+    assert_eq!(
+        counts.command_rows(),
+        vec![("Git", 2), ("Tests", 1), ("File ops", 1)]
+    );
+}
+
+#[test]
+fn mcp_servers_are_charged_their_share_of_each_message() {
+    let message = |stats: Stats| crate::types::ConversationMessage {
+        application: crate::types::Application::ClaudeCode,
+        date: chrono::Utc::now(),
+        project_hash: String::new(),
+        conversation_hash: "s".to_string(),
+        local_hash: None,
+        global_hash: "g".to_string(),
+        model: None,
+        stats,
+        role: crate::types::MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    };
+    let mut usage = ToolUsage::default();
+    usage.add_messages(&[
+        // One Bash call and one GitHub call split the message evenly.
+        message(Stats {
+            tool_calls: 2,
+            terminal_commands: 1,
+            input_tokens: 1000,
+            cost: 4.0,
+            mcp_tool_calls: [("github".to_string(), 1)].into(),
+            ..Stats::default()
+        }),
+        message(Stats {
+            tool_calls: 3,
+            input_tokens: 300,
+            cost: 0.3,
+            mcp_tool_calls: [("github".to_string(), 1), ("linear".to_string(), 2)].into(),
+            ..Stats::default()
+        }),
+    ]);
+
+    let counts = &usage.by_session["s"];
+    let servers = counts.mcp_by_cost();
+    assert_eq!(servers.len(), 2);
+    assert_eq!(servers[0].0, "github");
+    assert_eq!(servers[0].1.calls, 2);
+    assert!((servers[0].1.cost - 2.1).abs() < 1e-9);
+    assert!((servers[0].1.tokens - 600.0).abs() < 1e-9);
+    assert_eq!(servers[1].0, "linear");
+    assert_eq!(servers[1].1.calls, 2);
+    assert!((servers[1].1.cost - 0.2).abs() < 1e-9);
+    assert_eq!(usage.for_days(|_| true).mcp_servers, counts.mcp_servers);
+}
+
+#[test]
+fn model_table_shows_cost_share_and_totals() {
+    let models = vec![
+        crate::types::ModelStats {
+            model: "model-a".to_string(),
+            message_count: 3,
+            cost: 3.0,
+            ..Default::default()
+        },
+        crate::types::ModelStats {
+            model: "model-b".to_string(),
+            message_count: 1,
+            cost: 1.0,
+            ..Default::default()
+        },
+    ];
+    let format_options = crate::utils::NumberFormatOptions {
+        use_comma: false,
+        use_human: false,
+        locale: "en".to_string(),
+        currency_symbol: "$".to_string(),
+        cost_decimal_places: 2,
+        decimal_places: 2,
+    };
+    let backend = TestBackend::new(160, 10);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut table_state = TableState::default();
+    table_state.select(Some(50));
+
+    terminal
+        .draw(|frame| {
+            draw_model_stats_table(
+                frame,
+                Rect::new(0, 0, 160, 10),
+                &models,
+                &format_options,
+                &mut table_state,
+                false,
+                &Theme::DARK,
+            );
+        })
+        .unwrap();
+
+    let rendered = terminal
+        .backend()
+        .buffer()
+        .content
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect::<String>();
+    assert!(rendered.contains("75.0%"), "missing share: {rendered}");
+    assert!(rendered.contains("Total (2 models)"));
+    assert!(rendered.contains("$4.00"));
+    // Out-of-range selection is clamped to the totals row.
+    assert_eq!(table_state.selected(), Some(3));
+}
+
 #[test]
 fn test_build_display_stats_prepends_all_tools_view() {
     let multi = MultiAnalyzerStats {
@@ -259,7 +1120,8 @@ fn test_build_display_stats_prepends_all_tools_view() {
     let multi_view = multi.into_view();
     let filtered_stats: Vec<_> = multi_view.analyzer_stats.clone();
 
-    let display_stats = build_display_stats(&filtered_stats);
+    let display_stats =
+        build_display_stats(&filtered_stats, DateRange::default(), &CostBasis::default());
 
     assert_eq!(display_stats.len(), 3);
 
@@ -276,6 +1138,105 @@ fn test_build_display_stats_prepends_all_tools_view() {
     );
 }
 
+#[test]
+fn test_build_display_stats_applies_date_range_to_every_tab() {
+    let mut tool = make_tool_stats("tool-a", true);
+    tool.daily_stats.insert(
+        "2025-02-01".to_string(),
+        make_daily_stats("2025-02-01", 40, 400, 4),
+    );
+    let multi_view = MultiAnalyzerStats {
+        analyzer_stats: vec![tool],
+    }
+    .into_view();
+
+    let range = DateRange::parse("2025-01-15..").unwrap();
+    let display_stats =
+        build_display_stats(&multi_view.analyzer_stats, range, &CostBasis::default());
+
+    assert_eq!(display_stats.len(), 2);
+    for view in &display_stats {
+        let view = view.read();
+        assert_eq!(view.daily_stats.len(), 1);
+        assert!(view.daily_stats.contains_key("2025-02-01"));
+        assert_eq!(view.num_conversations, 4);
+    }
+    // The shared source view is left untouched.
+    assert_eq!(multi_view.analyzer_stats[0].read().daily_stats.len(), 2);
+}
+
+#[test]
+fn test_display_stats_cache_only_recomputes_changed_tools() {
+    let multi_view = MultiAnalyzerStats {
+        analyzer_stats: vec![
+            make_tool_stats("tool-a", true),
+            make_tool_stats("tool-b", true),
+        ],
+    }
+    .into_view();
+    let [tool_a, tool_b] = [0, 1].map(|i| Arc::clone(&multi_view.analyzer_stats[i]));
+    let session = |id: &str, tool: &str| SessionAggregate {
+        session_id: id.to_string(),
+        stable_id: String::new(),
+        first_timestamp: chrono::Utc::now(),
+        analyzer_name: Arc::from(tool),
+        stats: TuiStats::default(),
+        models: ModelCounts::new(),
+        session_name: None,
+        project: None,
+        date: CompactDate::from_str("2025-01-01").unwrap(),
+    };
+    tool_a
+        .write()
+        .session_aggregates
+        .push(session("a1", "tool-a"));
+    tool_b
+        .write()
+        .session_aggregates
+        .push(session("b1", "tool-b"));
+
+    let range = DateRange::parse("2025-01-01..").unwrap();
+    let basis = CostBasis::default();
+    let mut versions = HashMap::from([("tool-a", 1), ("tool-b", 1)]);
+    let mut cache = DisplayStatsCache::default();
+    let update = |cache: &mut DisplayStatsCache, versions: &HashMap<&str, u64>| {
+        cache.update(
+            &multi_view.analyzer_stats,
+            |view| versions.get(&*view.read().analyzer_name).copied(),
+            range,
+            &basis,
+        )
+    };
+
+    let first = update(&mut cache, &versions);
+    let session_ids = |display: &[crate::types::SharedAnalyzerView]| -> Vec<String> {
+        display[0]
+            .read()
+            .session_aggregates
+            .iter()
+            .map(|session| session.session_id.clone())
+            .collect()
+    };
+    assert_eq!(session_ids(&first), ["a1", "b1"]);
+
+    // Nothing changed: every tab is reused as is.
+    let second = update(&mut cache, &versions);
+    assert!(first.iter().zip(&second).all(|(a, b)| Arc::ptr_eq(a, b)));
+
+    // tool-b appended a session; only its tab is rebuilt.
+    tool_b
+        .write()
+        .session_aggregates
+        .push(session("b2", "tool-b"));
+    versions.insert("tool-b", 2);
+    let third = update(&mut cache, &versions);
+    assert!(Arc::ptr_eq(&first[1], &third[1]));
+    assert!(!Arc::ptr_eq(&first[2], &third[2]));
+    assert_eq!(third[2].read().session_aggregates.len(), 2);
+    assert_eq!(session_ids(&third), ["a1", "b1", "b2"]);
+    assert_eq!(third[0].read().num_conversations, 2);
+}
+
 // ============================================================================
 // UPLOAD PROGRESS & MESSAGES (tui.rs helpers)
 // ============================================================================
@@ -293,9 +1254,19 @@ fn test_upload_progress_callback_runs_without_panicking() {
 
     let progress = create_upload_progress_callback(&format_options);
     // First call should trigger dots update based on the timestamp.
-    progress(0, 10);
+    progress(crate::upload::UploadProgress {
+        current: 0,
+        total: 10,
+        chunk: 1,
+        total_chunks: 2,
+    });
     // Second call with changed progress should update even if not enough time has passed.
-    progress(5, 10);
+    progress(crate::upload::UploadProgress {
+        current: 5,
+        total: 10,
+        chunk: 1,
+        total_chunks: 2,
+    });
 }
 
 #[test]
@@ -493,6 +1464,21 @@ fn test_accumulate_tui_stats_preserves_dst_initial_values() {
     assert_eq!(dst.cost(), 0.02);
 }
 
+#[test]
+fn test_accumulate_tui_stats_keeps_fractional_premium_requests() {
+    let mut dst = TuiStats::default();
+    for premium_requests in [1.0, 0.33, 1.25] {
+        let src = Stats {
+            premium_requests,
+            ..Stats::default()
+        };
+        accumulate_tui_stats(&mut dst, &src);
+    }
+
+    assert_eq!(dst.premium_hundredths, 258);
+    assert!((dst.premium_requests() - 2.58).abs() < f64::EPSILON);
+}
+
 #[test]
 fn test_large_tui_stats_accumulation() {
     let mut dst = TuiStats::default();
@@ -728,6 +1714,35 @@ fn test_aggregate_daily_stats_by_week_rolls_up_iso_weeks() {
     assert_eq!(second_week.conversations, 1);
 }
 
+#[test]
+fn test_period_bests_track_period_keys() {
+    let mut daily_stats = BTreeMap::new();
+    daily_stats.insert(
+        "2025-01-06".to_string(),
+        make_daily_stats("2025-01-06", 500, 100, 1),
+    );
+    daily_stats.insert(
+        "2025-01-13".to_string(),
+        make_daily_stats("2025-01-13", 100, 900, 3),
+    );
+    daily_stats.insert(
+        "2025-01-20".to_string(),
+        make_daily_stats("2025-01-20", 0, 0, 0),
+    );
+
+    let weekly = aggregate_daily_stats_by_week(&daily_stats);
+    let bests = PeriodBests::from_periods(&weekly);
+
+    assert_eq!(bests.max_cost_cents, 900);
+    assert_eq!(bests.cost, Some("2025-W03"));
+    assert_eq!(bests.input_tokens, Some("2025-W02"));
+    assert_eq!(bests.conversations, Some("2025-W03"));
+    assert_eq!(bests.tool_calls, Some("2025-W03"));
+    // Columns with no activity anywhere have nothing to highlight.
+    assert_eq!(bests.reasoning_tokens, None);
+    assert_eq!(bests.cached_tokens, None);
+}
+
 #[test]
 fn test_aggregate_daily_stats_by_year_rolls_up_years() {
     let mut daily_stats = BTreeMap::new();
@@ -804,3 +1819,45 @@ fn test_tui_stats_accumulation_with_multiple_analyzers() {
     assert_eq!(dst.tool_calls, 6);
     assert!((dst.cost() - 0.03).abs() < 0.01);
 }
+
+#[test]
+fn usage_window_row_shows_share_tokens_and_reset() {
+    let format_options = crate::utils::NumberFormatOptions {
+        use_comma: false,
+        use_human: false,
+        locale: "en".to_string(),
+        currency_symbol: "$".to_string(),
+        cost_decimal_places: 2,
+        decimal_places: 1,
+    };
+    let now = chrono::Local::now();
+    let mut window = crate::limits::UsageWindow {
+        tool: "Claude Code".to_string(),
+        kind: crate::limits::WindowKind::FiveHour,
+        resets_at: (now + chrono::TimeDelta::hours(2)).with_timezone(&chrono::Utc),
+        tokens: Some(1_200_000),
+        used: Some(0.42),
+    };
+
+    let row = format_usage_window(&window, &format_options, now);
+    assert!(row.contains("42%"), "{row}");
+    assert!(row.contains("1.2m tokens"), "{row}");
+    let reset = (now + chrono::TimeDelta::hours(2))
+        .format("%H:%M")
+        .to_string();
+    assert!(row.ends_with(&format!("resets {reset}")), "{row}");
+
+    // Without a limit there is no share to show.
+    window.used = None;
+    let row = format_usage_window(&window, &format_options, now);
+    assert!(row.starts_with("1.2m tokens"), "{row}");
+}
+
+#[test]
+fn test_format_active_time() {
+    assert_eq!(format_active_time(60), "1m");
+    // Partial minutes round up, so a block never reads as nothing.
+    assert_eq!(format_active_time(61), "2m");
+    assert_eq!(format_active_time(59 * 60), "59m");
+    assert_eq!(format_active_time(2 * 3600 + 5 * 60), "2h 05m");
+}
@@ -0,0 +1,249 @@
+//! Colors for the TUI's semantic roles: a built-in preset, with any role
+//! overridden from `[theme]`.
+
+use super::{parse_accent, parse_color};
+use crate::config::ThemeConfig;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+
+pub const PRESETS: [&str; 4] = ["dark", "light", "solarized", "no-color"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub accent: Color,
+    /// The highest value in each table column.
+    pub best: Color,
+    pub cost: Color,
+    /// Tool-call counts.
+    pub count: Color,
+    /// Secondary text; `None` dims the terminal's own foreground instead.
+    pub dim: Option<Color>,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Summary panel values; `None` gives each row its own color.
+    pub summary: Option<Color>,
+    /// Cost heatmap from the cheapest to the costliest period; `None` leaves
+    /// costs uncolored.
+    pub heat: Option<[(u8, u8, u8); 2]>,
+    /// File categories in the tools pane, in [`ToolCounts::composition`] order.
+    ///
+    /// [`ToolCounts::composition`]: super::aggregation::ToolCounts::composition
+    pub categories: [Color; 6],
+}
+
+impl Theme {
+    /// The classic look, tuned for dark terminals.
+    pub const DARK: Theme = Theme {
+        accent: Color::Cyan,
+        best: Color::Red,
+        cost: Color::Yellow,
+        count: Color::Green,
+        dim: None,
+        success: Color::Green,
+        warning: Color::Yellow,
+        error: Color::Red,
+        summary: None,
+        heat: Some([(90, 200, 110), (235, 80, 70)]),
+        categories: [
+            Color::Cyan,
+            Color::Yellow,
+            Color::Green,
+            Color::Blue,
+            Color::Magenta,
+            Color::DarkGray,
+        ],
+    };
+
+    /// Deeper shades that stay readable on white backgrounds.
+    pub const LIGHT: Theme = Theme {
+        accent: Color::Rgb(0x00, 0x5f, 0xaf),
+        best: Color::Rgb(0xc0, 0x1c, 0x28),
+        cost: Color::Rgb(0x8a, 0x5a, 0x00),
+        count: Color::Rgb(0x1a, 0x7f, 0x37),
+        dim: Some(Color::Rgb(0x6e, 0x77, 0x81)),
+        success: Color::Rgb(0x1a, 0x7f, 0x37),
+        warning: Color::Rgb(0x8a, 0x5a, 0x00),
+        error: Color::Rgb(0xc0, 0x1c, 0x28),
+        summary: Some(Color::Rgb(0x24, 0x29, 0x2f)),
+        heat: Some([(0x1a, 0x7f, 0x37), (0xc0, 0x1c, 0x28)]),
+        categories: [
+            Color::Rgb(0x00, 0x5f, 0xaf),
+            Color::Rgb(0x8a, 0x5a, 0x00),
+            Color::Rgb(0x1a, 0x7f, 0x37),
+            Color::Rgb(0x82, 0x50, 0xdf),
+            Color::Rgb(0xbf, 0x38, 0x89),
+            Color::Rgb(0x6e, 0x77, 0x81),
+        ],
+    };
+
+    /// Ethan Schoonover's Solarized accents, for dark or light variants.
+    pub const SOLARIZED: Theme = Theme {
+        accent: Color::Rgb(0x26, 0x8b, 0xd2),
+        best: Color::Rgb(0xdc, 0x32, 0x2f),
+        cost: Color::Rgb(0xb5, 0x89, 0x00),
+        count: Color::Rgb(0x85, 0x99, 0x00),
+        dim: Some(Color::Rgb(0x93, 0xa1, 0xa1)),
+        success: Color::Rgb(0x85, 0x99, 0x00),
+        warning: Color::Rgb(0xcb, 0x4b, 0x16),
+        error: Color::Rgb(0xdc, 0x32, 0x2f),
+        summary: Some(Color::Rgb(0x2a, 0xa1, 0x98)),
+        heat: Some([(0x85, 0x99, 0x00), (0xdc, 0x32, 0x2f)]),
+        categories: [
+            Color::Rgb(0x26, 0x8b, 0xd2),
+            Color::Rgb(0xb5, 0x89, 0x00),
+            Color::Rgb(0x85, 0x99, 0x00),
+            Color::Rgb(0x6c, 0x71, 0xc4),
+            Color::Rgb(0xd3, 0x36, 0x82),
+            Color::Rgb(0x93, 0xa1, 0xa1),
+        ],
+    };
+
+    /// The terminal's own colors throughout; emphasis comes from bold and dim.
+    pub const NO_COLOR: Theme = Theme {
+        accent: Color::Reset,
+        best: Color::Reset,
+        cost: Color::Reset,
+        count: Color::Reset,
+        dim: None,
+        success: Color::Reset,
+        warning: Color::Reset,
+        error: Color::Reset,
+        summary: Some(Color::Reset),
+        heat: None,
+        categories: [Color::Reset; 6],
+    };
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match Self::preset_name(name)? {
+            "dark" => Some(Self::DARK),
+            "light" => Some(Self::LIGHT),
+            "solarized" => Some(Self::SOLARIZED),
+            _ => Some(Self::NO_COLOR),
+        }
+    }
+
+    /// The [`PRESETS`] entry `name` refers to, in any case and accepting the
+    /// `nocolor` and `none` aliases.
+    pub fn preset_name(name: &str) -> Option<&'static str> {
+        match name.trim().to_lowercase().as_str() {
+            "dark" => Some("dark"),
+            "light" => Some("light"),
+            "solarized" => Some("solarized"),
+            "no-color" | "nocolor" | "none" => Some("no-color"),
+            _ => None,
+        }
+    }
+
+    /// The configured preset (dark when unknown) with its overrides applied.
+    /// The dark preset keeps taking its accent from `tui.accent_color`.
+    pub fn from_config(config: &ThemeConfig, accent_color: &str) -> Self {
+        let mut theme = Self::preset(&config.preset).unwrap_or(Self::DARK);
+        if theme == Self::DARK {
+            theme.accent = parse_accent(accent_color);
+        }
+        for (role, color) in config.roles() {
+            let Some(color) = color.and_then(parse_color) else {
+                continue;
+            };
+            match role {
+                "accent" => theme.accent = color,
+                "best" => theme.best = color,
+                "cost" => theme.cost = color,
+                "count" => theme.count = color,
+                "dim" => theme.dim = Some(color),
+                "success" => theme.success = color,
+                "warning" => theme.warning = color,
+                "error" => theme.error = color,
+                "summary" => theme.summary = Some(color),
+                _ => {}
+            }
+        }
+        theme
+    }
+
+    /// The active tab: reversed accent, or plain reverse video without colors.
+    pub fn selected_tab(&self) -> Style {
+        match self.accent {
+            Color::Reset => Style::default().add_modifier(Modifier::REVERSED),
+            accent => Style::default().fg(Color::Black).bg(accent),
+        }
+    }
+
+    /// Whether the theme keeps to the terminal's own colors.
+    pub fn is_colorless(&self) -> bool {
+        self.accent == Color::Reset
+    }
+
+    /// The all-tools tab, in the summary color where the theme has one.
+    pub fn all_tools(&self) -> Color {
+        self.summary.unwrap_or(Color::White)
+    }
+
+    /// Heatmap color for a cost cell, from the theme's low to its high end by
+    /// `cents` as a share of `max`.
+    pub fn cost_heat(&self, cents: u32, max: u32) -> Color {
+        let Some([low, high]) = self.heat else {
+            return Color::Reset;
+        };
+        let t = if max == 0 {
+            0.0
+        } else {
+            (cents as f64 / max as f64).clamp(0.0, 1.0)
+        };
+        let mix = |from: u8, to: u8| (from as f64 + t * (to as f64 - from as f64)).round() as u8;
+        Color::Rgb(mix(low.0, high.0), mix(low.1, high.1), mix(low.2, high.2))
+    }
+
+    /// Style for secondary text.
+    pub fn dim(&self) -> Style {
+        match self.dim {
+            Some(color) => Style::default().fg(color),
+            None => Style::default().add_modifier(Modifier::DIM),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// Rewrite a rendered frame for `--plain`: every cell in the terminal's own
+/// colors, and box drawing, block and arrow glyphs replaced by ASCII.
+pub fn make_plain(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        cell.set_fg(Color::Reset);
+        cell.set_bg(Color::Reset);
+        if let Some(ascii) = ascii_symbol(cell.symbol()) {
+            cell.set_symbol(ascii);
+        }
+    }
+}
+
+/// ASCII stand-in for a single-width glyph, if it needs one.
+fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let ascii = match symbol {
+        "─" | "━" | "═" | "╌" | "┄" => "-",
+        "│" | "┃" | "║" | "╎" | "┆" => "|",
+        "┌" | "┐" | "└" | "┘" | "├" | "┤" | "┬" | "┴" | "┼" | "╭" | "╮" | "╯" | "╰" | "╔" | "╗"
+        | "╚" | "╝" => "+",
+        "█" | "▉" | "▊" | "▋" | "▌" | "▍" | "▎" | "▇" | "▆" => "#",
+        "▅" | "▄" => "=",
+        "▃" | "▂" => "-",
+        "▁" => "_",
+        "░" | "▒" | "▓" => ".",
+        "▏" => "_",
+        "→" => ">",
+        "←" => "<",
+        "↑" | "▲" => "^",
+        "↓" | "▼" => "v",
+        "•" | "●" | "·" => "*",
+        "…" => ".",
+        "✓" => "+",
+        "✕" | "✗" => "x",
+        _ => return None,
+    };
+    Some(ascii)
+}
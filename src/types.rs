@@ -127,6 +127,95 @@ impl fmt::Display for CompactDate {
     }
 }
 
+/// Inclusive window of local dates selected with `--since`/`--until` or the TUI
+/// range picker. Either end may be open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub since: Option<CompactDate>,
+    pub until: Option<CompactDate>,
+}
+
+impl DateRange {
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, date: CompactDate) -> bool {
+        self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date <= until)
+    }
+
+    /// Parse a single bound: `YYYY-MM-DD`, `today`, `yesterday`, or `<N>d` for N days ago.
+    pub fn parse_bound(s: &str) -> Option<CompactDate> {
+        let s = s.trim();
+        let days_ago = match s.to_ascii_lowercase().as_str() {
+            "today" => Some(0),
+            "yesterday" => Some(1),
+            other => other.strip_suffix('d').and_then(|n| n.parse::<u32>().ok()),
+        };
+        match days_ago {
+            Some(days) => {
+                let date = chrono::Local::now().checked_sub_days(chrono::Days::new(days as u64))?;
+                Some(CompactDate::from_local(&date))
+            }
+            None => CompactDate::from_str(s),
+        }
+    }
+
+    /// Parse `SINCE..UNTIL`, where either side may be empty, or a single bound
+    /// meaning that day only. An empty string is the unbounded range.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let range = match s.split_once("..") {
+            Some((since, until)) => Self {
+                since: Self::parse_optional_bound(since)?,
+                until: Self::parse_optional_bound(until)?,
+            },
+            None => {
+                let day = Self::parse_optional_bound(s)?;
+                Self {
+                    since: day,
+                    until: day,
+                }
+            }
+        };
+        range.is_valid().then_some(range)
+    }
+
+    fn parse_optional_bound(s: &str) -> Option<Option<CompactDate>> {
+        if s.trim().is_empty() {
+            Some(None)
+        } else {
+            Self::parse_bound(s).map(Some)
+        }
+    }
+
+    /// Whether `since` is not after `until`.
+    pub fn is_valid(&self) -> bool {
+        match (self.since, self.until) {
+            (Some(since), Some(until)) => since <= until,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.since, self.until) {
+            (Some(since), Some(until)) if since == until => write!(f, "{since}"),
+            (since, until) => {
+                if let Some(since) = since {
+                    write!(f, "{since}")?;
+                }
+                f.write_str("..")?;
+                if let Some(until) = until {
+                    write!(f, "{until}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 // ============================================================================
 // ModelCounts - Compact reference-counted model tracking
 // ============================================================================
@@ -194,6 +283,9 @@ impl ModelCounts {
 #[derive(Debug, Clone)]
 pub struct SessionAggregate {
     pub session_id: String,
+    /// Reproducible ID for tracking this session outside splitrail; see
+    /// [`crate::utils::stable_session_id`].
+    pub stable_id: String,
     pub first_timestamp: DateTime<Utc>,
     /// Shared across all sessions from the same analyzer (Arc clone is cheap)
     pub analyzer_name: Arc<str>,
@@ -202,10 +294,29 @@ pub struct SessionAggregate {
     /// Inline storage for up to 3 models; interned keys are 4 bytes each.
     pub models: ModelCounts,
     pub session_name: Option<String>,
+    /// Working directory, for analyzers whose logs record one; see
+    /// [`crate::utils::project_dir`].
+    pub project: Option<Arc<str>>,
     pub date: CompactDate,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl SessionAggregate {
+    /// Short form of `stable_id` for display.
+    pub fn display_id(&self) -> &str {
+        &self.stable_id[..self.stable_id.len().min(8)]
+    }
+
+    /// Oldest first, ties broken by `stable_id` so the order is the same on
+    /// every run.
+    pub fn chronological(a: &Self, b: &Self) -> std::cmp::Ordering {
+        a.first_timestamp
+            .cmp(&b.first_timestamp)
+            .then_with(|| a.stable_id.cmp(&b.stable_id))
+    }
+}
+
+/// Metadata for each variant lives in [`crate::application::APPS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Application {
     ClaudeCode,
@@ -223,6 +334,10 @@ pub enum Application {
     PiAgent,
     Piebald,
     AntigravityCli,
+    /// A `[[custom_analyzer]]` declared in the user's config.
+    Custom,
+    /// History brought in from other usage trackers by `splitrail import`.
+    Imported,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -256,6 +371,10 @@ pub struct ConversationMessage {
     pub role: MessageRole,
     pub uuid: Option<String>,
     pub session_name: Option<String>,
+    /// Reproducible ID of this message's session, set when the analyzer's
+    /// sessions are loaded whole; see [`crate::utils::stable_session_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable_session_id: Option<String>,
 }
 
 /// Daily statistics for TUI display.
@@ -278,6 +397,63 @@ pub struct DailyStats {
     /// "All Tools" view so the table can list which apps were used).
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub apps: BTreeMap<String, u32>,
+    /// Prompt-cache savings and expiry losses for this day.
+    #[serde(default)]
+    pub prompt_cache: PromptCacheStats,
+    /// Assistant messages by local hour of day (index 0-23), for the
+    /// time-of-day heat grid.
+    #[serde(default)]
+    pub hourly_messages: [u32; 24],
+}
+
+/// Estimated effect of prompt caching on cost.
+///
+/// An expiry is a cache write that follows a gap longer than the cache TTL
+/// within the same conversation: the prefix had to be written again instead of
+/// being read back, so it is a miss that could have been a hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCacheStats {
+    /// Cost avoided by reading tokens from cache instead of paying full input price.
+    pub saved_cost: f64,
+    /// Number of cache writes that followed a gap longer than the TTL.
+    pub expiry_misses: u32,
+    /// Tokens re-written to cache after an expiry.
+    pub expiry_tokens: u64,
+    /// Extra cost of those re-writes compared to reading the same tokens from cache.
+    pub expiry_cost: f64,
+}
+
+impl std::ops::AddAssign for PromptCacheStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.saved_cost += rhs.saved_cost;
+        self.expiry_misses = self.expiry_misses.saturating_add(rhs.expiry_misses);
+        self.expiry_tokens = self.expiry_tokens.saturating_add(rhs.expiry_tokens);
+        self.expiry_cost += rhs.expiry_cost;
+    }
+}
+
+impl std::ops::SubAssign for PromptCacheStats {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.saved_cost = (self.saved_cost - rhs.saved_cost).max(0.0);
+        self.expiry_misses = self.expiry_misses.saturating_sub(rhs.expiry_misses);
+        self.expiry_tokens = self.expiry_tokens.saturating_sub(rhs.expiry_tokens);
+        self.expiry_cost = (self.expiry_cost - rhs.expiry_cost).max(0.0);
+    }
+}
+
+impl DailyStats {
+    /// Re-price every model's tokens at current rates, see
+    /// [`ModelStats::reprice`]. Returns the change in dollars.
+    pub fn reprice(&mut self, before: &crate::models::PricingSnapshot) -> f64 {
+        let change: f64 = self
+            .model_stats
+            .values_mut()
+            .map(|model| model.reprice(before))
+            .sum();
+        self.stats.adjust_cost(change);
+        change
+    }
 }
 
 impl std::ops::AddAssign<&DailyStats> for DailyStats {
@@ -289,6 +465,10 @@ impl std::ops::AddAssign<&DailyStats> for DailyStats {
             *self.models.entry(model.clone()).or_insert(0) += count;
         }
         self.stats += rhs.stats;
+        self.prompt_cache += rhs.prompt_cache;
+        for (hour, count) in self.hourly_messages.iter_mut().zip(rhs.hourly_messages) {
+            *hour += count;
+        }
         for (model, model_stat) in &rhs.model_stats {
             self.model_stats
                 .entry(model.clone())
@@ -315,6 +495,10 @@ impl std::ops::SubAssign<&DailyStats> for DailyStats {
             }
         }
         self.stats -= rhs.stats;
+        self.prompt_cache -= rhs.prompt_cache;
+        for (hour, count) in self.hourly_messages.iter_mut().zip(rhs.hourly_messages) {
+            *hour = hour.saturating_sub(count);
+        }
         for (model, model_stat) in &rhs.model_stats {
             if let Some(existing) = self.model_stats.get_mut(model) {
                 existing.sub_model_stats(model_stat);
@@ -378,6 +562,41 @@ pub struct Stats {
     pub media_lines: u64,
     pub config_lines: u64,
     pub other_lines: u64,
+
+    // Subagent stats
+    /// Messages from a subagent (sidechain) thread, e.g. Claude Code's Task
+    /// tool. A single message is 1 when it ran in a subagent, 0 otherwise.
+    #[serde(default)]
+    pub subagent_messages: u64,
+
+    // MCP stats
+    /// Tool calls per MCP server, keyed by the server part of
+    /// `mcp__<server>__<tool>` names.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcp_tool_calls: BTreeMap<String, u64>,
+
+    // Language stats
+    /// File operations per lowercase extension without the dot, from the
+    /// paths given to read, write, and edit tools.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_extensions: BTreeMap<String, u64>,
+
+    // Terminal command stats
+    /// Terminal commands per [`crate::utils::CommandCategory`] key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub command_categories: BTreeMap<String, u64>,
+
+    // Cost source stats
+    /// Messages priced from the cost their tool logged rather than from
+    /// model rates. A single message is 1 when its cost was logged, 0 otherwise.
+    #[serde(default)]
+    pub logged_cost_messages: u64,
+
+    // Premium request stats
+    /// GitHub Copilot premium requests, with the model's multiplier applied
+    /// (e.g. 0.33 for a discounted model, 0 for an included one).
+    #[serde(default)]
+    pub premium_requests: f64,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -390,6 +609,46 @@ pub enum FileCategory {
     Other,
 }
 
+impl Stats {
+    /// Set the cost from [`crate::models::resolve_cost`], noting its source.
+    pub fn set_cost(&mut self, (cost, source): (f64, crate::models::CostSource)) {
+        self.cost = cost;
+        self.logged_cost_messages = u64::from(source == crate::models::CostSource::Logged);
+    }
+
+    /// Count `lines` touched in a file of `category` toward the composition stats.
+    pub fn add_composition_lines(&mut self, category: FileCategory, lines: u64) {
+        match category {
+            FileCategory::SourceCode => self.code_lines += lines,
+            FileCategory::Documentation => self.docs_lines += lines,
+            FileCategory::Data => self.data_lines += lines,
+            FileCategory::Media => self.media_lines += lines,
+            FileCategory::Config => self.config_lines += lines,
+            FileCategory::Other => self.other_lines += lines,
+        }
+    }
+
+    /// Count one terminal command toward its category.
+    pub fn add_command(&mut self, command: &str) {
+        let category = crate::utils::classify_command(command);
+        *self
+            .command_categories
+            .entry(category.key().to_string())
+            .or_default() += 1;
+    }
+
+    /// Count one operation on `path` toward its extension. Paths without an
+    /// extension (`Makefile`, directories) are skipped.
+    pub fn add_file_path(&mut self, path: &str) {
+        if let Some(ext) = std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            *self.file_extensions.entry(ext.to_lowercase()).or_default() += 1;
+        }
+    }
+}
+
 impl std::ops::AddAssign for Stats {
     fn add_assign(&mut self, rhs: Self) {
         self.input_tokens += rhs.input_tokens;
@@ -426,6 +685,12 @@ impl std::ops::AddAssign for Stats {
         self.media_lines += rhs.media_lines;
         self.config_lines += rhs.config_lines;
         self.other_lines += rhs.other_lines;
+        self.subagent_messages += rhs.subagent_messages;
+        self.logged_cost_messages += rhs.logged_cost_messages;
+        self.premium_requests += rhs.premium_requests;
+        add_counts(&mut self.mcp_tool_calls, rhs.mcp_tool_calls);
+        add_counts(&mut self.file_extensions, rhs.file_extensions);
+        add_counts(&mut self.command_categories, rhs.command_categories);
     }
 }
 
@@ -469,10 +734,36 @@ impl std::ops::SubAssign for Stats {
         self.media_lines = self.media_lines.saturating_sub(rhs.media_lines);
         self.config_lines = self.config_lines.saturating_sub(rhs.config_lines);
         self.other_lines = self.other_lines.saturating_sub(rhs.other_lines);
+        self.subagent_messages = self.subagent_messages.saturating_sub(rhs.subagent_messages);
+        self.logged_cost_messages = self
+            .logged_cost_messages
+            .saturating_sub(rhs.logged_cost_messages);
+        self.premium_requests = (self.premium_requests - rhs.premium_requests).max(0.0);
+        sub_counts(&mut self.mcp_tool_calls, rhs.mcp_tool_calls);
+        sub_counts(&mut self.file_extensions, rhs.file_extensions);
+        sub_counts(&mut self.command_categories, rhs.command_categories);
+    }
+}
+
+fn add_counts(counts: &mut BTreeMap<String, u64>, rhs: BTreeMap<String, u64>) {
+    for (key, count) in rhs {
+        *counts.entry(key).or_default() += count;
     }
 }
 
-/// Lightweight stats for TUI display only (40 bytes vs 320 bytes for full Stats).
+/// Subtract `rhs`, dropping keys that reach zero.
+fn sub_counts(counts: &mut BTreeMap<String, u64>, rhs: BTreeMap<String, u64>) {
+    for (key, count) in rhs {
+        if let Some(existing) = counts.get_mut(&key) {
+            *existing = existing.saturating_sub(count);
+            if *existing == 0 {
+                counts.remove(&key);
+            }
+        }
+    }
+}
+
+/// Lightweight stats for TUI display only (48 bytes vs 328 bytes for full Stats).
 /// Contains only fields actually rendered in the UI.
 /// Uses u32 for memory efficiency - sufficient for per-session and per-day values.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
@@ -484,6 +775,17 @@ pub struct TuiStats {
     pub cached_tokens: u64,
     pub cost_cents: u32, // Store as cents to avoid f32 precision issues
     pub tool_calls: u32,
+    /// The part of `cost_cents` spent by subagents.
+    #[serde(default)]
+    pub subagent_cost_cents: u32,
+    /// Copilot premium requests in hundredths, since multipliers such as
+    /// 0.33 make them fractional.
+    #[serde(default)]
+    pub premium_hundredths: u32,
+    /// Seconds of active work, see [`crate::stats::active`]. Set per session
+    /// rather than per message.
+    #[serde(default)]
+    pub active_secs: u32,
 }
 
 impl TuiStats {
@@ -506,6 +808,37 @@ impl TuiStats {
             .cost_cents
             .saturating_add((dollars * 100.0).round() as u32);
     }
+
+    /// Move the cost by `dollars`, which may be negative.
+    pub fn adjust_cost(&mut self, dollars: f64) {
+        let cents = (dollars * 100.0).round() as i64;
+        self.cost_cents = (i64::from(self.cost_cents) + cents).clamp(0, i64::from(u32::MAX)) as u32;
+    }
+
+    #[inline]
+    pub fn subagent_cost(&self) -> f64 {
+        self.subagent_cost_cents as f64 / 100.0
+    }
+
+    /// Multiply both the total and the subagent cost by `factor`.
+    pub fn scale_cost(&mut self, factor: f64) {
+        let subagent = self.subagent_cost() * factor;
+        self.set_cost(self.cost() * factor);
+        self.subagent_cost_cents = (subagent * 100.0).round() as u32;
+    }
+
+    #[inline]
+    pub fn premium_requests(&self) -> f64 {
+        self.premium_hundredths as f64 / 100.0
+    }
+
+    /// Add premium requests, rounded to hundredths.
+    #[inline]
+    pub fn add_premium_requests(&mut self, requests: f64) {
+        self.premium_hundredths = self
+            .premium_hundredths
+            .saturating_add((requests * 100.0).round() as u32);
+    }
 }
 
 impl From<&Stats> for TuiStats {
@@ -517,6 +850,13 @@ impl From<&Stats> for TuiStats {
             cached_tokens: s.cached_tokens,
             cost_cents: (s.cost * 100.0).round() as u32,
             tool_calls: s.tool_calls,
+            subagent_cost_cents: if s.subagent_messages > 0 {
+                (s.cost * 100.0).round() as u32
+            } else {
+                0
+            },
+            premium_hundredths: (s.premium_requests * 100.0).round() as u32,
+            active_secs: 0,
         }
     }
 }
@@ -529,6 +869,13 @@ impl std::ops::AddAssign for TuiStats {
         self.cached_tokens = self.cached_tokens.saturating_add(rhs.cached_tokens);
         self.cost_cents = self.cost_cents.saturating_add(rhs.cost_cents);
         self.tool_calls = self.tool_calls.saturating_add(rhs.tool_calls);
+        self.subagent_cost_cents = self
+            .subagent_cost_cents
+            .saturating_add(rhs.subagent_cost_cents);
+        self.premium_hundredths = self
+            .premium_hundredths
+            .saturating_add(rhs.premium_hundredths);
+        self.active_secs = self.active_secs.saturating_add(rhs.active_secs);
     }
 }
 
@@ -540,6 +887,13 @@ impl std::ops::SubAssign for TuiStats {
         self.cached_tokens = self.cached_tokens.saturating_sub(rhs.cached_tokens);
         self.cost_cents = self.cost_cents.saturating_sub(rhs.cost_cents);
         self.tool_calls = self.tool_calls.saturating_sub(rhs.tool_calls);
+        self.subagent_cost_cents = self
+            .subagent_cost_cents
+            .saturating_sub(rhs.subagent_cost_cents);
+        self.premium_hundredths = self
+            .premium_hundredths
+            .saturating_sub(rhs.premium_hundredths);
+        self.active_secs = self.active_secs.saturating_sub(rhs.active_secs);
     }
 }
 
@@ -564,6 +918,49 @@ impl FileCategory {
     }
 }
 
+/// Per-model stats rolled up to the provider that bills for them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStats {
+    pub provider: crate::models::Provider,
+    /// Distinct models used from this provider.
+    pub models: u32,
+    pub message_count: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cached_tokens: u64,
+    pub cost: f64,
+    pub tool_calls: u32,
+}
+
+impl ProviderStats {
+    pub fn new(provider: crate::models::Provider) -> Self {
+        Self {
+            provider,
+            models: 0,
+            message_count: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            reasoning_tokens: 0,
+            cached_tokens: 0,
+            cost: 0.0,
+            tool_calls: 0,
+        }
+    }
+
+    pub fn add_model_stats(&mut self, model: &ModelStats) {
+        self.models += 1;
+        self.message_count += model.message_count;
+        self.input_tokens += model.input_tokens;
+        self.output_tokens += model.output_tokens;
+        self.reasoning_tokens += model.reasoning_tokens;
+        self.cached_tokens += model.cached_tokens;
+        self.cost += model.cost;
+        self.tool_calls += model.tool_calls;
+    }
+}
+
 /// Aggregated statistics for a specific model.
 /// Used in JSON output to show per-model breakdowns.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -579,6 +976,9 @@ pub struct ModelStats {
     pub cached_tokens: u64,
     pub cost: f64,
     pub tool_calls: u32,
+    /// Cost avoided by reading prompt tokens from cache; see [`crate::stats::cache`].
+    #[serde(default)]
+    pub saved_cost: f64,
 }
 
 impl ModelStats {
@@ -614,6 +1014,24 @@ impl ModelStats {
         self.cached_tokens += other.cached_tokens;
         self.cost += other.cost;
         self.tool_calls += other.tool_calls;
+        self.saved_cost += other.saved_cost;
+    }
+
+    /// Re-price these tokens at current rates, moving `cost` by the change
+    /// since `before` so logged costs of unchanged models are kept. Returns
+    /// the change in dollars.
+    pub fn reprice(&mut self, before: &crate::models::PricingSnapshot) -> f64 {
+        let change = before.cost_change(
+            &self.model,
+            [
+                self.input_tokens,
+                self.output_tokens,
+                self.cache_creation_tokens,
+                self.cache_read_tokens,
+            ],
+        );
+        self.cost = (self.cost + change).max(0.0);
+        change
     }
 
     /// Subtract another ModelStats from this one (for incremental updates).
@@ -631,6 +1049,7 @@ impl ModelStats {
         self.cached_tokens = self.cached_tokens.saturating_sub(other.cached_tokens);
         self.cost -= other.cost;
         self.tool_calls = self.tool_calls.saturating_sub(other.tool_calls);
+        self.saved_cost = (self.saved_cost - other.saved_cost).max(0.0);
     }
 }
 
@@ -642,6 +1061,24 @@ pub struct AgenticCodingToolStats {
     pub analyzer_name: String,
 }
 
+impl AgenticCodingToolStats {
+    /// Drop messages and days outside `range`, recounting conversations from
+    /// the days that remain.
+    pub fn retain_range(&mut self, range: DateRange) {
+        if range.is_unbounded() {
+            return;
+        }
+        self.messages
+            .retain(|message| range.contains(CompactDate::from_local(&message.date)));
+        self.daily_stats.retain(|_, day| range.contains(day.date));
+        self.num_conversations = self
+            .daily_stats
+            .values()
+            .map(|day| day.conversations as u64)
+            .sum();
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiAnalyzerStats {
     pub analyzer_stats: Vec<AgenticCodingToolStats>,
@@ -659,6 +1096,32 @@ pub struct AnalyzerStatsView {
     pub analyzer_name: Arc<str>,
 }
 
+impl AnalyzerStatsView {
+    /// Copy of this view restricted to the days and sessions inside `range`.
+    pub fn within(&self, range: DateRange) -> Self {
+        let daily_stats: BTreeMap<String, DailyStats> = self
+            .daily_stats
+            .iter()
+            .filter(|(_, day)| range.contains(day.date))
+            .map(|(key, day)| (key.clone(), day.clone()))
+            .collect();
+        Self {
+            num_conversations: daily_stats
+                .values()
+                .map(|day| day.conversations as u64)
+                .sum(),
+            daily_stats,
+            session_aggregates: self
+                .session_aggregates
+                .iter()
+                .filter(|session| range.contains(session.date))
+                .cloned()
+                .collect(),
+            analyzer_name: Arc::clone(&self.analyzer_name),
+        }
+    }
+}
+
 /// Shared view type - Arc<RwLock<...>> allows mutation without cloning.
 pub type SharedAnalyzerView = Arc<RwLock<AnalyzerStatsView>>;
 
@@ -668,6 +1131,22 @@ pub type SharedAnalyzerView = Arc<RwLock<AnalyzerStatsView>>;
 #[derive(Debug, Clone)]
 pub struct MultiAnalyzerStatsView {
     pub analyzer_stats: Vec<SharedAnalyzerView>,
+    /// Analyzers whose initial load hasn't finished, in registration order.
+    pub loading: Vec<String>,
+    /// Version of each view in `analyzer_stats`, bumped by the registry
+    /// whenever that view changes, so listeners can redo work for just the
+    /// tools that changed. Empty when unknown, meaning every view may have.
+    pub versions: Vec<u64>,
+}
+
+impl MultiAnalyzerStatsView {
+    /// Version of `view` if it is one of these views and versions are tracked.
+    pub fn version_of(&self, view: &SharedAnalyzerView) -> Option<u64> {
+        self.analyzer_stats
+            .iter()
+            .position(|candidate| Arc::ptr_eq(candidate, view))
+            .and_then(|index| self.versions.get(index).copied())
+    }
 }
 
 impl AgenticCodingToolStats {
@@ -688,6 +1167,76 @@ impl AgenticCodingToolStats {
     }
 }
 
+/// Builds an [`AnalyzerStatsView`] from batches of parsed messages, so only
+/// one batch of messages has to be resident at a time. Batches must not split
+/// a conversation. Messages whose `global_hash` was seen in an earlier batch
+/// are skipped; only an 8-byte hash per message is kept for that.
+pub struct AnalyzerViewBuilder {
+    view: AnalyzerStatsView,
+    seen: std::collections::HashSet<u64>,
+}
+
+impl AnalyzerViewBuilder {
+    pub fn new(analyzer_name: Arc<str>) -> Self {
+        Self {
+            view: AnalyzerStatsView {
+                daily_stats: BTreeMap::new(),
+                session_aggregates: Vec::new(),
+                num_conversations: 0,
+                analyzer_name,
+            },
+            seen: std::collections::HashSet::new(),
+        }
+    }
+
+    /// Fold a batch into the view, consuming its messages.
+    pub fn add_batch(&mut self, messages: Vec<ConversationMessage>) {
+        let messages: Vec<ConversationMessage> = messages
+            .into_iter()
+            .filter(|message| {
+                self.seen
+                    .insert(xxhash_rust::xxh3::xxh3_64(message.global_hash.as_bytes()))
+            })
+            .collect();
+        if messages.is_empty() {
+            return;
+        }
+
+        let mut daily_stats = crate::utils::aggregate_by_date(&messages);
+        daily_stats.retain(|date, _| date != "unknown");
+        for (date, day) in daily_stats {
+            match self.view.daily_stats.entry(date) {
+                std::collections::btree_map::Entry::Vacant(entry) => {
+                    entry.insert(day);
+                }
+                std::collections::btree_map::Entry::Occupied(mut entry) => {
+                    *entry.get_mut() += &day;
+                }
+            }
+        }
+        self.view
+            .session_aggregates
+            .extend(aggregate_sessions_from_messages(
+                &messages,
+                Arc::clone(&self.view.analyzer_name),
+            ));
+    }
+
+    pub fn finish(mut self) -> SharedAnalyzerView {
+        self.view
+            .session_aggregates
+            .sort_by(SessionAggregate::chronological);
+        self.view.session_aggregates.shrink_to_fit();
+        self.view.num_conversations = self
+            .view
+            .daily_stats
+            .values()
+            .map(|day| day.conversations as u64)
+            .sum();
+        Arc::new(RwLock::new(self.view))
+    }
+}
+
 impl MultiAnalyzerStats {
     /// Convert to view type, consuming self and dropping all messages.
     pub fn into_view(self) -> MultiAnalyzerStatsView {
@@ -697,6 +1246,8 @@ impl MultiAnalyzerStats {
                 .into_iter()
                 .map(|s| s.into_view())
                 .collect(),
+            loading: Vec::new(),
+            versions: Vec::new(),
         }
     }
 }
@@ -777,6 +1328,7 @@ mod tests {
             role: MessageRole::Assistant,
             uuid: None,
             session_name: Some("Test Session".into()),
+            stable_session_id: None,
         }
     }
 
@@ -881,11 +1433,13 @@ mod tests {
         crate::contribution_cache::MultiSessionContribution {
             session_aggregates: vec![SessionAggregate {
                 session_id: session_id.into(),
+                stable_id: String::new(),
                 first_timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
                 analyzer_name: Arc::from("Test"),
                 stats: TuiStats::default(),
                 models: ModelCounts::from_single(intern_model(model), count),
                 session_name: None,
+                project: None,
                 date: CompactDate::default(),
             }],
             ..Default::default()
@@ -1108,4 +1662,126 @@ mod tests {
         assert_eq!(day1.model_stats["gpt-4"].message_count, 2);
         assert_eq!(day1.model_stats["gpt-4"].input_tokens, 200);
     }
+
+    #[test]
+    fn date_range_parses_bounds_and_filters_inclusively() {
+        let range = DateRange::parse("2025-01-10..2025-01-20").unwrap();
+        assert!(!range.contains(CompactDate::from_parts(2025, 1, 9)));
+        assert!(range.contains(CompactDate::from_parts(2025, 1, 10)));
+        assert!(range.contains(CompactDate::from_parts(2025, 1, 20)));
+        assert!(!range.contains(CompactDate::from_parts(2025, 1, 21)));
+        assert_eq!(range.to_string(), "2025-01-10..2025-01-20");
+
+        let open_end = DateRange::parse("2025-01-10..").unwrap();
+        assert_eq!(open_end.until, None);
+        assert!(open_end.contains(CompactDate::from_parts(2030, 1, 1)));
+        assert_eq!(open_end.to_string(), "2025-01-10..");
+
+        let single = DateRange::parse("2025-01-10").unwrap();
+        assert_eq!(single.since, single.until);
+        assert_eq!(single.to_string(), "2025-01-10");
+
+        assert!(DateRange::parse("").unwrap().is_unbounded());
+        assert_eq!(
+            DateRange::parse("today").unwrap().since,
+            Some(CompactDate::from_local(&chrono::Local::now()))
+        );
+        assert!(DateRange::parse("0d..").is_some());
+        assert!(DateRange::parse("2025-01-20..2025-01-10").is_none());
+        assert!(DateRange::parse("soon").is_none());
+    }
+
+    #[test]
+    fn retain_range_drops_messages_and_days_outside_window() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2025, 3, d, 12, 0, 0).unwrap();
+        let message = |d: u32| ConversationMessage {
+            application: Application::ClaudeCode,
+            date: day(d),
+            project_hash: "p".into(),
+            conversation_hash: format!("c{d}"),
+            local_hash: None,
+            global_hash: format!("g{d}"),
+            model: Some("m".into()),
+            stats: Stats::default(),
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        };
+        let messages = vec![message(1), message(5), message(9)];
+        let mut stats = AgenticCodingToolStats {
+            daily_stats: crate::utils::aggregate_by_date(&messages),
+            num_conversations: 3,
+            messages,
+            analyzer_name: "Claude Code".into(),
+        };
+
+        stats.retain_range(DateRange {
+            since: Some(CompactDate::from_local(
+                &day(2).with_timezone(&chrono::Local),
+            )),
+            until: Some(CompactDate::from_local(
+                &day(8).with_timezone(&chrono::Local),
+            )),
+        });
+
+        assert_eq!(stats.messages.len(), 1);
+        assert_eq!(stats.messages[0].global_hash, "g5");
+        assert_eq!(stats.num_conversations, 1);
+        assert!(stats.daily_stats.values().all(|d| d.date.day() >= 2));
+    }
+
+    #[test]
+    fn view_builder_batches_match_a_single_fold() {
+        let message = |day: u32, conversation: &str, global: &str| ConversationMessage {
+            application: Application::CodexCli,
+            date: Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap(),
+            project_hash: "p".into(),
+            conversation_hash: conversation.into(),
+            local_hash: None,
+            global_hash: global.into(),
+            model: Some("m".into()),
+            stats: Stats {
+                input_tokens: 100,
+                cost: 0.5,
+                ..Stats::default()
+            },
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: None,
+            stable_session_id: None,
+        };
+        let first = vec![message(1, "a", "g1"), message(2, "a", "g2")];
+        // The duplicate of g2 is only counted once.
+        let second = vec![message(2, "b", "g3"), message(2, "b", "g2")];
+
+        let mut batched = AnalyzerViewBuilder::new(Arc::from("Codex CLI"));
+        batched.add_batch(first.clone());
+        batched.add_batch(second.clone());
+        let batched = batched.finish();
+
+        let mut whole = AnalyzerViewBuilder::new(Arc::from("Codex CLI"));
+        whole.add_batch(first.into_iter().chain(second).collect());
+        let whole = whole.finish();
+
+        let (batched, whole) = (batched.read(), whole.read());
+        assert_eq!(batched.num_conversations, 2);
+        assert_eq!(batched.num_conversations, whole.num_conversations);
+        assert_eq!(batched.session_aggregates.len(), 2);
+        for (date, day) in &whole.daily_stats {
+            let other = &batched.daily_stats[date];
+            assert_eq!(other.ai_messages, day.ai_messages);
+            assert_eq!(other.conversations, day.conversations);
+            assert_eq!(other.stats.input_tokens, day.stats.input_tokens);
+        }
+        assert_eq!(batched.daily_stats.len(), whole.daily_stats.len());
+        assert_eq!(
+            batched
+                .daily_stats
+                .values()
+                .map(|d| d.ai_messages)
+                .sum::<u32>(),
+            3
+        );
+    }
 }
@@ -1,10 +1,14 @@
-use crate::config::{Config, UploadState};
+use crate::config::{Config, UploadConfig, UploadState};
 use crate::reqwest_simd_json::{ReqwestSimdJsonExt, ResponseSimdJsonExt};
 use crate::tui::UploadStatus;
-use crate::types::{ConversationMessage, ErrorResponse, MultiAnalyzerStats, UploadResponse};
+use crate::types::{
+    Application, ConversationMessage, ErrorResponse, MultiAnalyzerStats, UploadResponse,
+};
 use crate::utils;
 use anyhow::{Context, Result};
 use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -60,34 +64,233 @@ fn upload_accept_invalid_certs_enabled() -> bool {
     )
 }
 
-fn build_http_client(allow_invalid_certs: bool) -> reqwest::Client {
-    let builder = reqwest::Client::builder().timeout(Duration::from_secs(120));
-    let builder = if allow_invalid_certs {
-        builder.danger_accept_invalid_certs(true)
-    } else {
-        builder
-    };
+/// Build the HTTP client. `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` are honored
+/// unless `[upload] proxy_url` overrides them, and `[upload] ca_bundle_path`
+/// adds PEM root certificates on top of the platform's.
+fn build_http_client(
+    allow_invalid_certs: bool,
+    upload_config: &UploadConfig,
+) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(Duration::from_secs(120));
+    if allow_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(proxy_url) = &upload_config.proxy_url {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .with_context(|| format!("Invalid proxy URL {proxy_url}"))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    if let Some(ca_bundle_path) = &upload_config.ca_bundle_path {
+        let path = utils::expand_home(ca_bundle_path);
+        let pem =
+            std::fs::read(&path).with_context(|| format!("Failed to read CA bundle {path}"))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Failed to parse CA bundle {path}"))?;
+        anyhow::ensure!(!certs.is_empty(), "No certificates found in {path}");
+        builder = builder.tls_certs_merge(certs);
+    }
 
-    builder.build().expect("Failed to create HTTP client")
+    builder.build().context("Failed to create HTTP client")
 }
 
 /// Get the shared HTTP client singleton
 pub fn get_http_client() -> reqwest::Client {
     #[cfg(test)]
     {
-        build_http_client(true)
+        build_http_client(true, &Config::default().upload).expect("Failed to create HTTP client")
     }
 
     #[cfg(not(test))]
     {
         HTTP_CLIENT
-            .get_or_init(|| build_http_client(upload_accept_invalid_certs_enabled()))
+            .get_or_init(|| {
+                let upload_config = Config::load().ok().flatten().unwrap_or_default().upload;
+                let allow_invalid_certs = upload_accept_invalid_certs_enabled();
+                build_http_client(allow_invalid_certs, &upload_config).unwrap_or_else(|e| {
+                    // Requests will most likely fail without the proxy or CA,
+                    // but that error is clearer next to this one.
+                    utils::warn_once(format!("WARNING: {e:#}; using default HTTP settings"));
+                    build_http_client(allow_invalid_certs, &Config::default().upload)
+                        .expect("Failed to create HTTP client")
+                })
+            })
             .clone()
     }
 }
 
+pub mod lock;
+pub mod outbox;
+pub mod privacy;
 #[cfg(test)]
 mod tests;
+pub mod webhook;
+
+/// Latest upload payload format, selected with `[server] schema_version`.
+pub const UPLOAD_SCHEMA_VERSION: u32 = 1;
+
+/// A response that sending the same request again can't change: the server
+/// refused the payload (400, 422) or the API token (401, 403).
+#[derive(Debug)]
+pub struct Rejected {
+    pub status: reqwest::StatusCode,
+    pub message: String,
+}
+
+impl Rejected {
+    fn is_permanent(status: reqwest::StatusCode) -> bool {
+        matches!(status.as_u16(), 400 | 401 | 403 | 422)
+    }
+
+    /// The token was refused, so every other request would be too.
+    pub fn is_auth(&self) -> bool {
+        matches!(self.status.as_u16(), 401 | 403)
+    }
+
+    /// The rejection behind `error`, if that's what it is.
+    pub fn of(error: &anyhow::Error) -> Option<&Self> {
+        error.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Server rejected the upload ({}): {}",
+            self.status, self.message
+        )
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+/// Shown instead of uploading while `UploadState::watermark_lost` is set.
+pub const WATERMARK_LOST: &str = "The upload state file was corrupt and the last upload date couldn't be recovered; run `splitrail upload --full` to re-send everything.";
+
+/// The JSON body of one upload request.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum UploadPayload<'a> {
+    /// Schema version 0: a bare array of messages.
+    Legacy(&'a [&'a ConversationMessage]),
+    Versioned {
+        schema_version: u32,
+        messages: &'a [&'a ConversationMessage],
+    },
+}
+
+impl<'a> UploadPayload<'a> {
+    fn new(chunk: &'a [&'a ConversationMessage], config: &Config) -> Self {
+        match config.server.schema_version {
+            0 => Self::Legacy(chunk),
+            schema_version => Self::Versioned {
+                schema_version,
+                messages: chunk,
+            },
+        }
+    }
+}
+
+/// Drop messages that never leave this machine: history brought in by
+/// `splitrail import` came from another tracker, not from a tool the server
+/// knows.
+pub fn retain_uploadable(messages: &mut Vec<ConversationMessage>) {
+    messages.retain(|message| message.application != Application::Imported);
+}
+
+fn upload_url(config: &Config) -> String {
+    format!(
+        "{}{}",
+        config.server.url.trim_end_matches('/'),
+        config.server.upload_path
+    )
+}
+
+/// Print the exact request bodies an upload of `messages` would send, one
+/// JSON document per line, with the endpoint for each on stderr.
+pub fn show_payload(messages: &[ConversationMessage], config: &Config) -> Result<()> {
+    use std::io::Write;
+
+    let mut sorted_messages: Vec<&ConversationMessage> = messages.iter().collect();
+    sorted_messages.sort_by_key(|m| m.date);
+    let chunks = split_chunks(&sorted_messages, config.upload.chunk_size);
+    let mut stdout = std::io::stdout().lock();
+    for (index, chunk) in chunks.iter().enumerate() {
+        eprintln!(
+            "POST {} (chunk {}/{}, {} messages, schema version {})",
+            upload_url(config),
+            index + 1,
+            chunks.len(),
+            chunk.len(),
+            config.server.schema_version
+        );
+        simd_json::to_writer(&mut stdout, &UploadPayload::new(chunk, config))
+            .context("Failed to serialize upload payload")?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// What an upload sent, or with `--dry-run` would send.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSummary {
+    pub dry_run: bool,
+    pub messages: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    pub earliest: Option<chrono::DateTime<chrono::Utc>>,
+    pub latest: Option<chrono::DateTime<chrono::Utc>>,
+    /// Messages per tool, by display name.
+    pub applications: BTreeMap<String, u64>,
+    pub models: BTreeMap<String, u64>,
+    /// Messages from earlier failed uploads that were sent first.
+    pub queued_sent: usize,
+    /// Batches still waiting in the outbox afterwards.
+    pub queued_pending: usize,
+}
+
+impl UploadSummary {
+    pub fn new(messages: &[ConversationMessage], dry_run: bool) -> Self {
+        let mut summary = Self {
+            dry_run,
+            messages: messages.len(),
+            ..Self::default()
+        };
+        for message in messages {
+            summary.input_tokens += message.stats.input_tokens;
+            summary.output_tokens += message.stats.output_tokens;
+            summary.cost += message.stats.cost;
+            if let Some(model) = &message.model {
+                *summary.models.entry(model.clone()).or_default() += 1;
+            }
+            *summary
+                .applications
+                .entry(message.application.display_name().to_string())
+                .or_default() += 1;
+            summary.earliest = Some(
+                summary
+                    .earliest
+                    .map_or(message.date, |d| d.min(message.date)),
+            );
+            summary.latest = Some(summary.latest.map_or(message.date, |d| d.max(message.date)));
+        }
+        summary
+    }
+}
+
+/// Where an upload stands. Reported repeatedly while a chunk is in flight so
+/// the count can animate, and once more when the server acknowledges it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub current: usize,
+    pub total: usize,
+    /// The chunk in flight, counting from 1.
+    pub chunk: usize,
+    pub total_chunks: usize,
+}
 
 pub async fn upload_message_stats<F>(
     messages: &[ConversationMessage],
@@ -95,22 +298,23 @@ pub async fn upload_message_stats<F>(
     mut progress_callback: F,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize),
+    F: FnMut(UploadProgress),
 {
-    const CHUNK_SIZE: usize = 3000;
     if messages.is_empty() {
         return Ok(());
     }
 
     let upload_debug = upload_debug_enabled();
-    let max_retries = config.upload.retry_attempts.max(1) as usize;
 
     if upload_debug {
         // Printed once per run, and early, so users see it even if the TUI is busy.
         let header1 = "[splitrail upload] debug enabled (SPLITRAIL_UPLOAD_DEBUG=1)";
         let header2 = format!(
-            "[splitrail upload] chunk_size={CHUNK_SIZE} server={} retry_attempts={}",
-            config.server.url, config.upload.retry_attempts
+            "[splitrail upload] chunk_size={} compress={} server={} retry_attempts={}",
+            config.upload.chunk_size,
+            config.upload.compress,
+            config.server.url,
+            config.upload.retry_attempts
         );
         let header3 = "[splitrail upload] Legend: prep_ms=serialize_json wait_ms=server+network parse_ms=decode_response";
         let log_path_display = upload_log_path();
@@ -130,8 +334,6 @@ where
         append_upload_log(&header4);
     }
 
-    let client = get_http_client();
-
     // Sort messages by date before chunking so that earlier chunks contain
     // older messages.  This allows us to save incremental progress: after each
     // successful chunk we persist the latest message timestamp, so a retry
@@ -139,13 +341,58 @@ where
     let mut sorted_messages: Vec<&ConversationMessage> = messages.iter().collect();
     sorted_messages.sort_by_key(|m| m.date);
 
-    let chunks: Vec<Vec<&ConversationMessage>> = sorted_messages
-        .chunks(CHUNK_SIZE)
-        .map(|c| c.to_vec())
-        .collect();
-    let total_messages = messages.len();
-    let mut messages_processed = 0;
+    if let Err((messages_processed, err)) = upload_sorted(
+        &sorted_messages,
+        config,
+        &mut progress_callback,
+        upload_debug,
+    )
+    .await
+    {
+        // A rejected upload would be rejected again, so it isn't queued; its
+        // messages stay above the watermark for the next `splitrail upload`.
+        if Rejected::of(&err).is_some() {
+            return Err(err);
+        }
+        // Park everything the server didn't acknowledge in the outbox so it
+        // survives restarts. The outbox owns those messages from here on, so
+        // the watermark moves past them and they aren't selected again.
+        match outbox::enqueue(&sorted_messages[messages_processed..], &err) {
+            Ok(()) => save_chunk_progress(&sorted_messages, sorted_messages.len(), upload_debug),
+            Err(e) if upload_debug => upload_debug_log(format!(
+                "[splitrail upload] warning: failed to queue unsent messages: {e:#}"
+            )),
+            Err(_) => {}
+        }
+        return Err(err);
+    }
+
+    // No additional save needed here — save_chunk_progress already persisted
+    // the checkpoint after each chunk (including the last one), anchored to
+    // the last uploaded message timestamp + 1ms.  Using Utc::now() would risk
+    // skipping messages created during the upload window.
 
+    Ok(())
+}
+
+/// Upload date-sorted messages chunk by chunk, retrying each chunk with
+/// exponential backoff unless the server [`Rejected`] it. On failure, returns how many leading messages were
+/// acknowledged along with the error.
+async fn upload_sorted<F>(
+    sorted_messages: &[&ConversationMessage],
+    config: &Config,
+    progress_callback: &mut F,
+    upload_debug: bool,
+) -> std::result::Result<(), (usize, anyhow::Error)>
+where
+    F: FnMut(UploadProgress),
+{
+    let max_retries = config.upload.retry_attempts.max(1) as usize;
+    let client = get_http_client();
+
+    let chunks = split_chunks(sorted_messages, config.upload.chunk_size);
+    let total_messages = sorted_messages.len();
+    let mut messages_processed = 0;
     for (chunk_index, chunk) in chunks.iter().enumerate() {
         // For smooth counting, we calculate the current message position based on chunk progress
         let messages_in_chunk = chunk.len();
@@ -187,7 +434,7 @@ where
                 total_messages,
                 upload_debug,
             };
-            match upload_single_chunk(&client, config, chunk, &ctx, &mut progress_callback).await {
+            match upload_single_chunk(&client, config, chunk, &ctx, progress_callback).await {
                 Ok(()) => {
                     last_err = None;
                     break;
@@ -202,7 +449,11 @@ where
                             max_retries,
                         ));
                     }
+                    let rejected = Rejected::of(&e).is_some();
                     last_err = Some(e);
+                    if rejected {
+                        break;
+                    }
                 }
             }
         }
@@ -211,9 +462,9 @@ where
         if let Some(err) = last_err {
             // Save progress for any chunks that already succeeded
             if messages_processed > 0 {
-                save_chunk_progress(&sorted_messages, messages_processed, upload_debug);
+                save_chunk_progress(sorted_messages, messages_processed, upload_debug);
             }
-            return Err(err);
+            return Err((messages_processed, err));
         }
 
         messages_processed += messages_in_chunk;
@@ -221,17 +472,37 @@ where
         // Save incremental progress after each successful chunk so that a
         // later failure (or a manual re-run) only re-uploads the remaining
         // messages instead of re-sending everything from scratch.
-        save_chunk_progress(&sorted_messages, messages_processed, upload_debug);
+        save_chunk_progress(sorted_messages, messages_processed, upload_debug);
     }
 
-    // No additional save needed here — save_chunk_progress already persisted
-    // the checkpoint after each chunk (including the last one), anchored to
-    // the last uploaded message timestamp + 1ms.  Using Utc::now() would risk
-    // skipping messages created during the upload window.
-
     Ok(())
 }
 
+/// Split date-sorted messages into chunks of about `chunk_size`. A chunk is
+/// stretched rather than ending between two messages from the same
+/// millisecond, so the checkpoint saved after it (last timestamp + 1ms)
+/// covers exactly the messages that were acknowledged and an interrupted
+/// upload resumes at the next chunk.
+fn split_chunks<'a>(
+    sorted_messages: &'a [&'a ConversationMessage],
+    chunk_size: usize,
+) -> Vec<&'a [&'a ConversationMessage]> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks = Vec::with_capacity(sorted_messages.len().div_ceil(chunk_size));
+    let mut rest = sorted_messages;
+    while !rest.is_empty() {
+        let mut end = chunk_size.min(rest.len());
+        let last_millis = rest[end - 1].date.timestamp_millis();
+        while end < rest.len() && rest[end].date.timestamp_millis() == last_millis {
+            end += 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
 /// Save incremental upload progress by recording the latest message timestamp
 /// from the successfully uploaded portion.  On the next upload run, only
 /// messages newer than this timestamp will be re-sent.
@@ -250,6 +521,7 @@ fn save_chunk_progress(
         let checkpoint = last_msg.date.timestamp_millis() + 1;
         let mut state = UploadState::load().unwrap_or_default();
         state.last_date_uploaded = state.last_date_uploaded.max(checkpoint);
+        state.watermark_lost = false;
         if let Err(e) = state.save() {
             if upload_debug {
                 upload_debug_log(format!(
@@ -274,6 +546,17 @@ struct ChunkContext {
     upload_debug: bool,
 }
 
+impl ChunkContext {
+    fn progress(&self, current: usize) -> UploadProgress {
+        UploadProgress {
+            current,
+            total: self.total_messages,
+            chunk: self.chunk_index + 1,
+            total_chunks: self.total_chunks,
+        }
+    }
+}
+
 /// Upload a single chunk to the server, with animated progress counter.
 /// Returns Ok(()) on success, or an error if the upload failed.
 #[allow(clippy::needless_pass_by_ref_mut)] // progress_callback is FnMut
@@ -285,7 +568,7 @@ async fn upload_single_chunk<F>(
     progress_callback: &mut F,
 ) -> Result<()>
 where
-    F: FnMut(usize, usize),
+    F: FnMut(UploadProgress),
 {
     let messages_in_chunk = chunk.len();
     let mut current_count = ctx.chunk_start;
@@ -294,18 +577,19 @@ where
     // Start the HTTP request
     let timezone = utils::get_local_timezone();
     let prep_start = Instant::now();
-    let mut http_request = Box::pin(
-        client
-            .post(format!("{}/api/upload-stats", config.server.url))
-            .header(
-                "Authorization",
-                format!("Bearer {}", config.server.api_token),
-            )
-            .header("Content-Type", "application/json")
-            .header("X-Timezone", &timezone)
-            .simd_json(chunk)
-            .send(),
-    );
+    let payload = UploadPayload::new(chunk, config);
+    let request = client
+        .post(upload_url(config))
+        .header("Authorization", format!("Bearer {}", config.api_token()?))
+        .header("Content-Type", "application/json")
+        .header("X-Timezone", &timezone)
+        .header("X-Splitrail-Schema-Version", config.server.schema_version);
+    let request = if config.upload.compress {
+        request.simd_json_gzip(&payload)
+    } else {
+        request.simd_json(&payload)
+    };
+    let mut http_request = Box::pin(request.send());
     let prep_ms = prep_start.elapsed().as_millis();
     let wait_start = Instant::now();
 
@@ -356,20 +640,24 @@ where
                         );
                     }
                 } else {
+                    let status = response.status();
                     let error_text = response
                         .text()
                         .await
                         .unwrap_or_else(|_| "Unknown error".to_string());
+                    let message = match simd_json::from_slice::<ErrorResponse>(&mut error_text.clone().into_bytes()) {
+                        Ok(error_res) => error_res.error,
+                        Err(_) => error_text,
+                    };
 
-                    if let Ok(error_res) = simd_json::from_slice::<ErrorResponse>(&mut error_text.clone().into_bytes()) {
-                        anyhow::bail!("{}", error_res.error);
+                    if Rejected::is_permanent(status) {
+                        return Err(Rejected { status, message }.into());
                     }
-
-                    anyhow::bail!("{}", error_text);
+                    anyhow::bail!("{}", message);
                 }
 
                 // Show final state and exit
-                progress_callback(target_count, ctx.total_messages);
+                progress_callback(ctx.progress(target_count));
                 break;
             }
 
@@ -379,10 +667,10 @@ where
                     // Jump multiple messages at once for very fast counting
                     let jump_size = ((target_count - current_count) / 50).clamp(1, 100);
                     current_count = (current_count + jump_size).min(target_count);
-                    progress_callback(current_count, ctx.total_messages);
+                    progress_callback(ctx.progress(current_count));
                 } else {
                     // Reached target, show animated dots while waiting for HTTP
-                    progress_callback(current_count, ctx.total_messages);
+                    progress_callback(ctx.progress(current_count));
                     // Continue calling the callback to keep dots animating
                     // (TUI handles the actual dots animation timing)
                 }
@@ -403,26 +691,21 @@ fn set_upload_status(status: &Option<Arc<Mutex<UploadStatus>>>, value: UploadSta
 /// Creates an upload progress callback that updates the TUI status.
 fn make_progress_callback(
     upload_status: Option<Arc<Mutex<UploadStatus>>>,
-) -> impl FnMut(usize, usize) {
-    move |current, total| {
+) -> impl FnMut(UploadProgress) {
+    move |progress| {
         if let Some(ref status) = upload_status {
             let mut s = status.lock();
-            match &*s {
-                UploadStatus::Uploading { dots, .. } => {
-                    *s = UploadStatus::Uploading {
-                        current,
-                        total,
-                        dots: *dots,
-                    };
-                }
-                _ => {
-                    *s = UploadStatus::Uploading {
-                        current,
-                        total,
-                        dots: 0,
-                    };
-                }
-            }
+            let dots = match &*s {
+                UploadStatus::Uploading { dots, .. } => *dots,
+                _ => 0,
+            };
+            *s = UploadStatus::Uploading {
+                current: progress.current,
+                total: progress.total,
+                chunk: progress.chunk,
+                total_chunks: progress.total_chunks,
+                dots,
+            };
         }
     }
 }
@@ -451,7 +734,7 @@ async fn handle_upload_result(
 /// This is more efficient than loading all stats and filtering afterwards.
 /// If `on_success` is provided, it will be called after a successful upload.
 pub async fn perform_background_upload_messages<F>(
-    messages: Vec<ConversationMessage>,
+    mut messages: Vec<ConversationMessage>,
     upload_status: Option<Arc<Mutex<UploadStatus>>>,
     initial_delay_ms: Option<u64>,
     on_success: Option<F>,
@@ -469,9 +752,13 @@ pub async fn perform_background_upload_messages<F>(
             return None;
         }
 
+        retain_uploadable(&mut messages);
         if messages.is_empty() {
             return Some(Ok(())); // Nothing to upload
         }
+        if let Err(e) = privacy::scrub(&mut messages, &config.privacy) {
+            return Some(Err(e));
+        }
 
         let result = upload_message_stats(
             &messages,
@@ -512,14 +799,27 @@ pub async fn perform_background_upload(
         if !config.is_configured() {
             return None;
         }
+        // Another process is uploading; the watcher picks up what's left.
+        let lock = lock::UploadLock::try_acquire().ok().flatten()?;
 
+        // The TUI owns the terminal, so problems go to the status bar.
         let upload_state = match UploadState::load() {
             Ok(state) => state,
             Err(e) => {
-                eprintln!("Failed to load upload state: {e:#}");
+                set_upload_status(
+                    &upload_status,
+                    UploadStatus::Failed(format!("Failed to load upload state: {e:#}")),
+                );
                 return None;
             }
         };
+        if upload_state.watermark_lost {
+            set_upload_status(
+                &upload_status,
+                UploadStatus::Failed(WATERMARK_LOST.to_string()),
+            );
+            return None;
+        }
 
         let mut all_messages = Vec::new();
         let backfill_claude = !upload_state.claude_subagent_backfill_completed;
@@ -541,11 +841,11 @@ pub async fn perform_background_upload(
             }
         }
 
-        Some((all_messages, backfill_claude))
+        Some((all_messages, backfill_claude, lock))
     }
     .await;
 
-    if let Some((msgs, backfilled_claude)) = messages {
+    if let Some((msgs, backfilled_claude, _lock)) = messages {
         let backfill_completed = move || {
             if backfilled_claude {
                 let mut state = UploadState::load().unwrap_or_default();
@@ -0,0 +1,100 @@
+//! Keeps concurrent splitrail processes from uploading at the same time.
+//!
+//! The TUI's auto-upload, its outbox retries and `splitrail upload` all read
+//! the upload watermark, send everything newer and then move it forward. Two
+//! of them interleaving would send the same messages twice and could move
+//! the watermark backwards, so each holds `upload.lock` (next to the upload
+//! state) from reading the watermark until it has saved it. Background
+//! uploads skip a round when the lock is taken; `splitrail upload` waits.
+
+use crate::config::UploadState;
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+
+/// Held for the duration of one upload; released on drop.
+#[derive(Debug)]
+pub struct UploadLock {
+    _file: File,
+}
+
+fn lock_path() -> Result<PathBuf> {
+    Ok(UploadState::state_path()?.with_file_name("upload.lock"))
+}
+
+fn open() -> Result<File> {
+    let path = lock_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create state directory")?;
+    }
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+impl UploadLock {
+    /// Take the lock if no other upload holds it.
+    pub fn try_acquire() -> Result<Option<Self>> {
+        let file = open()?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self::claimed(file))),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e).context("Failed to lock upload state"),
+        }
+    }
+
+    /// Take the lock, waiting for another upload to finish first. `on_wait`
+    /// runs once if that happens, with the other process's id when known.
+    pub async fn acquire(on_wait: impl FnOnce(Option<u32>)) -> Result<Self> {
+        if let Some(lock) = Self::try_acquire()? {
+            return Ok(lock);
+        }
+        on_wait(holder());
+        tokio::task::spawn_blocking(|| {
+            let file = open()?;
+            file.lock().context("Failed to lock upload state")?;
+            Ok(Self::claimed(file))
+        })
+        .await?
+    }
+
+    fn claimed(mut file: File) -> Self {
+        // Only informs a waiting process, so failures don't matter.
+        let _ = file
+            .set_len(0)
+            .and_then(|()| write!(file, "{}", std::process::id()));
+        Self { _file: file }
+    }
+}
+
+/// Process id of the current holder, as it recorded it.
+fn holder() -> Option<u32> {
+    let mut contents = String::new();
+    let mut file = File::open(lock_path().ok()?).ok()?;
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_one_upload_holds_the_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::config::set_test_state_path(dir.path().join("state.toml"));
+
+        let first = UploadLock::try_acquire().unwrap().unwrap();
+        assert!(UploadLock::try_acquire().unwrap().is_none());
+        assert_eq!(holder(), Some(std::process::id()));
+
+        drop(first);
+        assert!(UploadLock::try_acquire().unwrap().is_some());
+    }
+}
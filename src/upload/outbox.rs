@@ -0,0 +1,239 @@
+//! Upload batches that couldn't be sent, kept on disk until they can be.
+//!
+//! When a chunk still fails after its in-process retries, it and every chunk
+//! after it are written to `outbox/` in the state directory and the upload watermark
+//! moves past them: from then on the outbox owns those messages, even if the
+//! tool that wrote them deletes its logs. Batches are retried by the next
+//! `splitrail upload` and every minute while the TUI runs, each backing off
+//! exponentially from one minute up to an hour.
+//!
+//! A batch the server [`Rejected`] is renamed to `.rejected` and never sent
+//! again. A rejected API token keeps the batches but stops the TUI's retries
+//! until it restarts with a new token.
+
+use super::Rejected;
+use crate::config::Config;
+use crate::tui::UploadStatus;
+use crate::types::ConversationMessage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::time::Duration;
+
+const MAX_BACKOFF_MINUTES: i64 = 60;
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Batches waiting in the outbox, for the TUI indicator.
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Batch {
+    created_at: DateTime<Utc>,
+    attempts: u32,
+    next_attempt_at: DateTime<Utc>,
+    last_error: String,
+    messages: Vec<ConversationMessage>,
+}
+
+impl Batch {
+    fn backoff(attempts: u32) -> ChronoDuration {
+        let minutes = 1i64 << attempts.saturating_sub(1).min(6);
+        ChronoDuration::minutes(minutes.min(MAX_BACKOFF_MINUTES))
+    }
+}
+
+/// What a flush sent, and what is still waiting.
+#[derive(Debug, Default)]
+pub struct FlushSummary {
+    pub sent_batches: usize,
+    pub sent_messages: usize,
+    pub pending: usize,
+    /// Batches the server rejected, set aside as `.rejected` files.
+    pub rejected: usize,
+    /// The server refused the API token; the flush stopped there.
+    pub token_rejected: bool,
+    pub error: Option<anyhow::Error>,
+}
+
+pub fn outbox_dir() -> Result<PathBuf> {
+    #[cfg(test)]
+    {
+        let state_path = crate::config::UploadState::state_path()?;
+        let parent = state_path
+            .parent()
+            .context("State file path has no parent directory")?;
+        Ok(parent.join("outbox"))
+    }
+
+    #[cfg(not(test))]
+    {
+        Ok(crate::paths::state_dir()?.join("outbox"))
+    }
+}
+
+/// Batches waiting to be sent, as of the last enqueue or flush.
+pub fn pending_count() -> usize {
+    PENDING.load(Ordering::Relaxed)
+}
+
+/// Recount the outbox on disk, e.g. at startup.
+pub fn refresh_pending() -> usize {
+    let count = outbox_dir().map_or(0, |dir| batch_paths(&dir).len());
+    PENDING.store(count, Ordering::Relaxed);
+    count
+}
+
+fn batch_paths(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    // Names start with the creation time, so this is oldest first.
+    paths.sort();
+    paths
+}
+
+fn write_batch(path: &Path, batch: &Batch) -> Result<()> {
+    let partial = path.with_extension("json.tmp");
+    std::fs::write(&partial, simd_json::to_vec(batch)?)
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+    std::fs::rename(&partial, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Park messages the server didn't acknowledge so a later run can send them.
+pub fn enqueue(messages: &[&ConversationMessage], error: &anyhow::Error) -> Result<()> {
+    static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let dir = outbox_dir()?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let now = Utc::now();
+    let batch = Batch {
+        created_at: now,
+        attempts: 1,
+        next_attempt_at: now + Batch::backoff(1),
+        last_error: format!("{error:#}"),
+        messages: messages.iter().map(|&message| message.clone()).collect(),
+    };
+    let name = format!(
+        "{}-{}-{}.json",
+        now.timestamp_millis(),
+        std::process::id(),
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    );
+    write_batch(&dir.join(name), &batch)?;
+    refresh_pending();
+    Ok(())
+}
+
+/// Send every batch that is due, oldest first, or every batch with `force`.
+/// Stops at the first failure, since the rest would most likely fail too.
+pub async fn flush(config: &Config, force: bool) -> FlushSummary {
+    let mut summary = FlushSummary::default();
+    let dir = match outbox_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            summary.error = Some(e);
+            return summary;
+        }
+    };
+    let now = Utc::now();
+    for path in batch_paths(&dir) {
+        let batch = std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut bytes| Ok(simd_json::from_slice::<Batch>(&mut bytes)?));
+        let mut batch = match batch {
+            Ok(batch) => batch,
+            Err(e) => {
+                crate::utils::warn_once(format!(
+                    "Skipping unreadable upload batch {}: {e:#}",
+                    path.display()
+                ));
+                continue;
+            }
+        };
+        if !force && batch.next_attempt_at > now {
+            continue;
+        }
+
+        let mut sorted: Vec<&ConversationMessage> = batch.messages.iter().collect();
+        sorted.sort_by_key(|message| message.date);
+        match super::upload_sorted(&sorted, config, &mut |_| {}, false).await {
+            Ok(()) => {
+                let _ = std::fs::remove_file(&path);
+                summary.sent_batches += 1;
+                summary.sent_messages += sorted.len();
+            }
+            Err((sent, error)) => {
+                summary.sent_messages += sent;
+                let unsent: Vec<ConversationMessage> = sorted[sent..]
+                    .iter()
+                    .map(|&message| message.clone())
+                    .collect();
+                batch.messages = unsent;
+                batch.attempts += 1;
+                batch.next_attempt_at = Utc::now() + Batch::backoff(batch.attempts);
+                batch.last_error = format!("{error:#}");
+                if let Err(e) = write_batch(&path, &batch) {
+                    crate::utils::warn_once(format!("{e:#}"));
+                }
+                let rejected = Rejected::of(&error).map(Rejected::is_auth);
+                summary.error = Some(error);
+                match rejected {
+                    // Later batches may still be accepted.
+                    Some(false) => {
+                        let _ = std::fs::rename(&path, path.with_extension("rejected"));
+                        summary.rejected += 1;
+                    }
+                    Some(true) => {
+                        summary.token_rejected = true;
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+    summary.pending = refresh_pending();
+    summary
+}
+
+/// Keep retrying due batches while the TUI runs. A failure shown in the
+/// status bar is cleared once the outbox drains; a rejection is shown there
+/// instead, and a rejected token ends the retries.
+pub fn spawn_retry_loop(config: Config, upload_status: Arc<Mutex<UploadStatus>>) {
+    refresh_pending();
+    tokio::spawn(async move {
+        // Let the startup upload go first.
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        loop {
+            // Skipped while another upload, possibly in another process, runs.
+            if pending_count() > 0
+                && let Ok(Some(_lock)) = super::lock::UploadLock::try_acquire()
+            {
+                let summary = flush(&config, false).await;
+                if summary.rejected > 0 || summary.token_rejected {
+                    let error = summary.error.map(|e| format!("{e:#}")).unwrap_or_default();
+                    *upload_status.lock() = UploadStatus::Failed(error);
+                    if summary.token_rejected {
+                        return;
+                    }
+                } else if summary.sent_batches > 0 && summary.pending == 0 {
+                    let mut status = upload_status.lock();
+                    if matches!(*status, UploadStatus::Failed(_)) {
+                        *status = UploadStatus::None;
+                    }
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
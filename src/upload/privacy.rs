@@ -0,0 +1,145 @@
+//! Scrubbing applied to messages before they leave the machine, per the
+//! `[privacy]` config section. `splitrail upload --show-payload` prints the
+//! scrubbed requests, so what it shows is exactly what would be sent.
+
+use crate::config::{PrivacyConfig, UploadState};
+use crate::types::ConversationMessage;
+use crate::utils::hash_text;
+use anyhow::{Context, Result};
+
+/// Apply every enabled scrub to `messages` in place.
+pub fn scrub(messages: &mut [ConversationMessage], privacy: &PrivacyConfig) -> Result<()> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let salt = if privacy.salt_project_hashes {
+        Some(project_salt()?)
+    } else {
+        None
+    };
+    for message in messages {
+        scrub_message(message, privacy, salt.as_deref());
+    }
+    Ok(())
+}
+
+fn scrub_message(message: &mut ConversationMessage, privacy: &PrivacyConfig, salt: Option<&str>) {
+    if let Some(salt) = salt {
+        message.project_hash = hash_text(&format!("{salt}:{}", message.project_hash));
+    }
+    if privacy.strip_session_names {
+        message.session_name = None;
+    }
+    if privacy.generalize_models
+        && let Some(model) = &mut message.model
+    {
+        *model = generalize_model(model);
+    }
+}
+
+/// This user's salt, created on first use and kept in the state file so
+/// salted hashes stay stable across runs (and, via `splitrail sync`, machines).
+fn project_salt() -> Result<String> {
+    let mut state = UploadState::load().context("Failed to load upload state")?;
+    if state.project_salt.is_empty() {
+        state.project_salt = new_salt()?;
+        state
+            .save()
+            .context("Failed to save the project hash salt")?;
+    }
+    Ok(state.project_salt)
+}
+
+/// 128 random bits from the OS's cryptographic RNG.
+fn new_salt() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|error| anyhow::anyhow!("No random salt: {error}"))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// A model's family without its version or date, e.g. "claude-sonnet" for
+/// "claude-sonnet-4-5-20250929", "gpt" for "gpt-5.1-codex". Provider
+/// prefixes like "anthropic/" are dropped.
+pub fn generalize_model(model: &str) -> String {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let mut segments = name.split(['-', '_', ':']);
+    let mut family: Vec<&str> = segments.next().into_iter().collect();
+    family.extend(segments.take_while(|segment| !segment.chars().any(|c| c.is_ascii_digit())));
+    family.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Application, MessageRole, Stats};
+
+    fn message() -> ConversationMessage {
+        ConversationMessage {
+            application: Application::ClaudeCode,
+            date: chrono::Utc::now(),
+            project_hash: "project".to_string(),
+            conversation_hash: "conversation".to_string(),
+            local_hash: None,
+            global_hash: "global".to_string(),
+            model: Some("claude-sonnet-4-5-20250929".to_string()),
+            stats: Stats::default(),
+            role: MessageRole::Assistant,
+            uuid: None,
+            session_name: Some("Fix the login bug".to_string()),
+            stable_session_id: None,
+        }
+    }
+
+    #[test]
+    fn models_generalize_to_their_family() {
+        assert_eq!(
+            generalize_model("claude-sonnet-4-5-20250929"),
+            "claude-sonnet"
+        );
+        assert_eq!(generalize_model("claude-opus-4-1"), "claude-opus");
+        assert_eq!(generalize_model("gpt-5.1-codex"), "gpt");
+        assert_eq!(
+            generalize_model("anthropic/Claude-Haiku-4.5"),
+            "claude-haiku"
+        );
+        assert_eq!(generalize_model("grok-code-fast-1"), "grok-code-fast");
+        assert_eq!(generalize_model("o3-mini"), "o3-mini");
+    }
+
+    #[test]
+    fn scrubbing_only_touches_enabled_fields() {
+        let mut scrubbed = message();
+        scrub_message(&mut scrubbed, &PrivacyConfig::default(), None);
+        assert_eq!(scrubbed.project_hash, "project");
+        assert_eq!(scrubbed.session_name.as_deref(), Some("Fix the login bug"));
+        assert_eq!(
+            scrubbed.model.as_deref(),
+            Some("claude-sonnet-4-5-20250929")
+        );
+
+        let privacy = PrivacyConfig {
+            salt_project_hashes: true,
+            strip_session_names: true,
+            generalize_models: true,
+        };
+        scrub_message(&mut scrubbed, &privacy, Some("salt"));
+        assert_eq!(scrubbed.project_hash, hash_text("salt:project"));
+        assert_eq!(scrubbed.session_name, None);
+        assert_eq!(scrubbed.model.as_deref(), Some("claude-sonnet"));
+        // Hashes that identify messages are left alone for deduplication.
+        assert_eq!(scrubbed.global_hash, "global");
+        assert_eq!(scrubbed.conversation_hash, "conversation");
+
+        // Different users' salts give different hashes for the same project.
+        let mut other = message();
+        scrub_message(&mut other, &privacy, Some("other salt"));
+        assert_ne!(other.project_hash, scrubbed.project_hash);
+    }
+
+    #[test]
+    fn salts_are_random() {
+        let salt = new_salt().unwrap();
+        assert_eq!(salt.len(), 32);
+        assert_ne!(salt, new_salt().unwrap());
+    }
+}
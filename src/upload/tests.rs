@@ -39,6 +39,7 @@ fn make_test_message(conversation_hash: &str) -> ConversationMessage {
         role: MessageRole::User,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     }
 }
 
@@ -77,6 +78,31 @@ fn invalid_cert_opt_in_requires_explicit_truthy_value() {
     assert!(allow_invalid_certs_from_env(Some("1")));
 }
 
+#[test]
+fn upload_summary_totals_messages_for_json_output() {
+    let mut first = make_test_message("a");
+    first.stats.input_tokens = 100;
+    first.stats.cost = 0.25;
+    let mut second = make_test_message("b");
+    second.stats.output_tokens = 40;
+    second.stats.cost = 0.5;
+    second.model = None;
+    second.date = first.date - chrono::Duration::hours(1);
+
+    let summary = UploadSummary::new(&[first.clone(), second.clone()], true);
+    assert_eq!(summary.messages, 2);
+    assert_eq!((summary.input_tokens, summary.output_tokens), (100, 40));
+    assert_eq!(summary.cost, 0.75);
+    assert_eq!(summary.earliest, Some(second.date));
+    assert_eq!(summary.latest, Some(first.date));
+    assert_eq!(summary.applications["Claude Code"], 2);
+    assert_eq!(summary.models["test-model"], 1);
+
+    let json = simd_json::to_string(&summary).unwrap();
+    assert!(json.contains(r#""dryRun":true"#), "{json}");
+    assert!(json.contains(r#""queuedPending":0"#), "{json}");
+}
+
 async fn read_http_request(socket: &mut tokio::net::TcpStream) {
     let mut buffer = Vec::new();
     let mut content_length = None;
@@ -176,7 +202,7 @@ async fn upload_message_stats_empty_messages_returns_ok_and_no_progress() {
     let config = Config::default();
     let mut progress_calls = 0usize;
 
-    upload_message_stats(&[], &config, |_, _| {
+    upload_message_stats(&[], &config, |_| {
         progress_calls += 1;
     })
     .await
@@ -218,8 +244,10 @@ async fn upload_message_stats_success_updates_progress_and_config() {
     let progress_values: Arc<Mutex<Vec<(usize, usize)>>> = Arc::new(Mutex::new(Vec::new()));
     let progress_values_clone = progress_values.clone();
 
-    upload_message_stats(&messages, &config, move |current, total| {
-        progress_values_clone.lock().push((current, total));
+    upload_message_stats(&messages, &config, move |progress| {
+        progress_values_clone
+            .lock()
+            .push((progress.current, progress.total));
     })
     .await
     .expect("upload should succeed");
@@ -278,7 +306,7 @@ async fn upload_message_stats_server_error_plain_text_propagates_message() {
 
     let messages = vec![make_test_message("c1")];
 
-    let err = upload_message_stats(&messages, &config, |_c, _t| {})
+    let err = upload_message_stats(&messages, &config, |_| {})
         .await
         .expect_err("upload should fail");
 
@@ -318,7 +346,7 @@ async fn upload_message_stats_server_error_json_uses_error_field() {
 
     let messages = vec![make_test_message("c1")];
 
-    let err = upload_message_stats(&messages, &config, |_c, _t| {})
+    let err = upload_message_stats(&messages, &config, |_| {})
         .await
         .expect_err("upload should fail");
 
@@ -358,12 +386,17 @@ async fn upload_message_stats_large_batch_is_split_into_chunks() {
     config.server.url = base_url;
     config.server.api_token = "TEST_TOKEN".to_string();
 
+    // Distinct timestamps, since chunks never split a millisecond.
+    let base = Utc::now();
     let mut messages = Vec::with_capacity(message_count);
     for i in 0..message_count {
-        messages.push(make_test_message(&format!("c{i}")));
+        messages.push(ConversationMessage {
+            date: base + chrono::Duration::milliseconds(i as i64),
+            ..make_test_message(&format!("c{i}"))
+        });
     }
 
-    upload_message_stats(&messages, &config, |_c, _t| {})
+    upload_message_stats(&messages, &config, |_| {})
         .await
         .expect("upload should succeed");
 
@@ -423,6 +456,30 @@ async fn perform_background_upload_unconfigured_config_keeps_status_unchanged()
     );
 }
 
+#[tokio::test]
+async fn perform_background_upload_reports_a_lost_watermark_in_the_status() {
+    let (_dir, _path, _state_path) = setup_test_config();
+
+    let mut config = Config::default();
+    config.server.api_token = "TEST_TOKEN".to_string();
+    config.save(true).expect("save configured config");
+    let state = UploadState {
+        watermark_lost: true,
+        ..UploadState::default()
+    };
+    state.save().expect("save state");
+
+    let stats = make_stats_with_messages(vec![make_test_message("c1")]);
+    let status = Arc::new(Mutex::new(UploadStatus::None));
+    perform_background_upload(stats, Some(status.clone()), None).await;
+
+    let final_status = status.lock().clone();
+    assert!(
+        matches!(&final_status, UploadStatus::Failed(msg) if msg == WATERMARK_LOST),
+        "got {final_status:?}"
+    );
+}
+
 #[tokio::test]
 async fn perform_background_upload_propagates_upload_errors_to_status() {
     let (_dir, _path, _state_path) = setup_test_config();
@@ -528,7 +585,7 @@ async fn upload_message_stats_retries_on_failure_then_succeeds() {
 
     let messages = vec![make_test_message("retry-c1")];
 
-    upload_message_stats(&messages, &config, |_c, _t| {})
+    upload_message_stats(&messages, &config, |_| {})
         .await
         .expect("upload should succeed after retry");
 
@@ -545,3 +602,319 @@ async fn upload_message_stats_retries_on_failure_then_succeeds() {
         "last_date_uploaded should be updated after successful retry"
     );
 }
+
+#[tokio::test]
+async fn failed_upload_is_queued_and_sent_by_a_later_flush() {
+    let (_dir, _config_path, _state_path) = setup_test_config();
+
+    let request_counter = Arc::new(AtomicUsize::new(0));
+    let failing_url = match start_test_server(
+        "500 Internal Server Error",
+        r#"{"error":"down for maintenance"}"#,
+        1,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping test: unable to bind local HTTP server");
+            return;
+        }
+    };
+
+    let mut config = Config::default();
+    config.server.url = failing_url;
+    config.server.api_token = "TEST_TOKEN".to_string();
+    config.upload.retry_attempts = 1;
+
+    let messages = vec![make_test_message("c1"), make_test_message("c2")];
+    upload_message_stats(&messages, &config, |_| {})
+        .await
+        .expect_err("upload should fail");
+
+    let outbox = outbox::outbox_dir().expect("outbox dir");
+    assert_eq!(std::fs::read_dir(&outbox).expect("outbox").count(), 1);
+    // The outbox owns the messages now, so the next upload must skip them.
+    assert_eq!(
+        UploadState::load().expect("load state").last_date_uploaded,
+        messages
+            .iter()
+            .map(|m| m.date.timestamp_millis())
+            .max()
+            .unwrap()
+            + 1
+    );
+
+    // Not due yet, so a timed flush leaves it alone.
+    let summary = outbox::flush(&config, false).await;
+    assert_eq!((summary.sent_batches, summary.pending), (0, 1));
+
+    let working_url = match start_test_server(
+        "200 OK",
+        r#"{"success":true}"#,
+        1,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => return,
+    };
+    config.server.url = working_url;
+
+    let summary = outbox::flush(&config, true).await;
+    assert!(summary.error.is_none(), "{:?}", summary.error);
+    assert_eq!((summary.sent_batches, summary.sent_messages), (1, 2));
+    assert_eq!(summary.pending, 0);
+    assert_eq!(std::fs::read_dir(&outbox).expect("outbox").count(), 0);
+    assert_eq!(request_counter.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn rejected_uploads_are_reported_without_retrying() {
+    let (_dir, _config_path, _state_path) = setup_test_config();
+
+    let request_counter = Arc::new(AtomicUsize::new(0));
+    let rejecting_url = match start_test_server(
+        "422 Unprocessable Entity",
+        r#"{"error":"unknown schema version"}"#,
+        1,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping test: unable to bind local HTTP server");
+            return;
+        }
+    };
+
+    let mut config = Config::default();
+    config.server.url = rejecting_url;
+    config.server.api_token = "TEST_TOKEN".to_string();
+    config.upload.retry_attempts = 3;
+
+    let messages = vec![make_test_message("c1")];
+    let error = upload_message_stats(&messages, &config, |_| {})
+        .await
+        .expect_err("upload should be rejected");
+    assert!(format!("{error:#}").contains("unknown schema version"));
+    assert_eq!(request_counter.load(Ordering::SeqCst), 1);
+    // Nothing is queued to be rejected again, and the messages stay unsent.
+    let outbox = outbox::outbox_dir().expect("outbox dir");
+    assert!(!outbox.exists());
+    assert_eq!(
+        UploadState::load().expect("load state").last_date_uploaded,
+        0
+    );
+
+    // A queued batch the server rejects is set aside.
+    outbox::enqueue(
+        &messages.iter().collect::<Vec<_>>(),
+        &anyhow::anyhow!("offline"),
+    )
+    .expect("enqueue");
+    config.server.url = match start_test_server(
+        "400 Bad Request",
+        r#"{"error":"malformed"}"#,
+        1,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => return,
+    };
+    let summary = outbox::flush(&config, true).await;
+    assert_eq!((summary.rejected, summary.pending), (1, 0));
+    assert!(!summary.token_rejected);
+    let names: Vec<String> = std::fs::read_dir(&outbox)
+        .expect("outbox")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(
+        names.len() == 1 && names[0].ends_with(".rejected"),
+        "{names:?}"
+    );
+
+    // A rejected token keeps the batch for after it is fixed.
+    outbox::enqueue(
+        &messages.iter().collect::<Vec<_>>(),
+        &anyhow::anyhow!("offline"),
+    )
+    .expect("enqueue");
+    config.server.url = match start_test_server(
+        "401 Unauthorized",
+        r#"{"error":"invalid token"}"#,
+        1,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => return,
+    };
+    let summary = outbox::flush(&config, true).await;
+    assert!(summary.token_rejected);
+    assert_eq!((summary.rejected, summary.pending), (0, 1));
+    assert_eq!(request_counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn chunks_never_split_messages_from_the_same_millisecond() {
+    let base = Utc::now();
+    let at = |offset_ms: i64, hash: &str| ConversationMessage {
+        date: base + chrono::Duration::milliseconds(offset_ms),
+        ..make_test_message(hash)
+    };
+    let messages = [
+        at(0, "a"),
+        at(1, "b"),
+        at(1, "c"),
+        at(1, "d"),
+        at(2, "e"),
+        at(3, "f"),
+    ];
+    let sorted: Vec<&ConversationMessage> = messages.iter().collect();
+
+    let sizes: Vec<usize> = split_chunks(&sorted, 2).iter().map(|c| c.len()).collect();
+    assert_eq!(sizes, [4, 2]);
+    let sizes: Vec<usize> = split_chunks(&sorted, 1).iter().map(|c| c.len()).collect();
+    assert_eq!(sizes, [1, 3, 1, 1]);
+}
+
+#[tokio::test]
+async fn chunked_upload_reports_chunks_and_checkpoints_each_one() {
+    let (_dir, _config_path, _state_path) = setup_test_config();
+
+    let request_counter = Arc::new(AtomicUsize::new(0));
+    let base_url = match start_test_server(
+        "200 OK",
+        r#"{"success":true}"#,
+        3,
+        request_counter.clone(),
+    )
+    .await
+    {
+        Some(url) => url,
+        None => {
+            eprintln!("Skipping test: unable to bind local HTTP server");
+            return;
+        }
+    };
+
+    let mut config = Config::default();
+    config.server.url = base_url;
+    config.server.api_token = "TEST_TOKEN".to_string();
+    config.upload.chunk_size = 2;
+
+    let base = Utc::now();
+    let messages: Vec<ConversationMessage> = (0..5)
+        .map(|i| ConversationMessage {
+            date: base + chrono::Duration::milliseconds(i),
+            ..make_test_message(&format!("c{i}"))
+        })
+        .collect();
+
+    let acknowledged = Arc::new(Mutex::new(Vec::new()));
+    let acknowledged_clone = acknowledged.clone();
+    upload_message_stats(&messages, &config, move |progress| {
+        let mut acknowledged = acknowledged_clone.lock();
+        if acknowledged.last() != Some(&progress) {
+            acknowledged.push(progress);
+        }
+    })
+    .await
+    .expect("upload should succeed");
+
+    assert_eq!(request_counter.load(Ordering::SeqCst), 3);
+    let acknowledged = acknowledged.lock();
+    let final_progress = acknowledged.last().expect("progress reported");
+    assert_eq!(
+        (
+            final_progress.current,
+            final_progress.chunk,
+            final_progress.total_chunks
+        ),
+        (5, 3, 3)
+    );
+    assert!(
+        acknowledged
+            .iter()
+            .any(|p| p.chunk == 2 && p.current == 4 && p.total == 5)
+    );
+    assert_eq!(
+        UploadState::load().expect("load state").last_date_uploaded,
+        messages[4].date.timestamp_millis() + 1
+    );
+}
+
+#[test]
+fn payload_follows_the_configured_schema_version() {
+    let message = make_test_message("c1");
+    let chunk = [&message];
+    let mut config = Config::default();
+
+    let legacy = simd_json::to_string(&UploadPayload::new(&chunk, &config)).unwrap();
+    assert!(legacy.starts_with("[{"), "{legacy}");
+
+    config.server.schema_version = UPLOAD_SCHEMA_VERSION;
+    let mut versioned = simd_json::to_vec(&UploadPayload::new(&chunk, &config)).unwrap();
+    let value: simd_json::OwnedValue = simd_json::from_slice(&mut versioned).unwrap();
+    assert_eq!(value["schema_version"], UPLOAD_SCHEMA_VERSION);
+    assert_eq!(value["messages"][0]["globalHash"], "global-c1");
+
+    config.server.url = "https://stats.example.com/".to_string();
+    config.server.upload_path = "/ingest".to_string();
+    assert_eq!(upload_url(&config), "https://stats.example.com/ingest");
+}
+
+#[test]
+fn http_client_applies_proxy_and_ca_bundle_settings() {
+    let dir = TempDir::new().expect("tempdir");
+    let mut upload_config = Config::default().upload;
+    upload_config.proxy_url = Some("http://proxy.corp:3128".to_string());
+    assert!(build_http_client(false, &upload_config).is_ok());
+
+    upload_config.proxy_url = Some("not a url".to_string());
+    assert!(build_http_client(false, &upload_config).is_err());
+    upload_config.proxy_url = None;
+
+    let missing = dir.path().join("missing.pem");
+    upload_config.ca_bundle_path = Some(missing.display().to_string());
+    let err = build_http_client(false, &upload_config).expect_err("missing bundle");
+    assert!(format!("{err:#}").contains("Failed to read CA bundle"));
+
+    let empty = dir.path().join("empty.pem");
+    std::fs::write(&empty, "no certificates here\n").expect("write bundle");
+    upload_config.ca_bundle_path = Some(empty.display().to_string());
+    assert!(build_http_client(false, &upload_config).is_err());
+}
+
+#[tokio::test]
+async fn webhook_post_reports_rejections() {
+    let request_counter = Arc::new(AtomicUsize::new(0));
+    let Some(ok_url) = start_test_server("200 OK", "ok", 1, request_counter.clone()).await else {
+        eprintln!("Skipping test: unable to bind local HTTP server");
+        return;
+    };
+    webhook::post(&ok_url, r#"{"text":"hi"}"#.to_string(), "application/json")
+        .await
+        .expect("post should succeed");
+
+    let Some(bad_url) =
+        start_test_server("404 Not Found", "no_such_hook", 1, request_counter.clone()).await
+    else {
+        return;
+    };
+    let err = webhook::post(&bad_url, r#"{"text":"hi"}"#.to_string(), "application/json")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Webhook answered 404 Not Found: no_such_hook"
+    );
+    assert_eq!(request_counter.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,335 @@
+//! `splitrail notify --webhook <url>`: post a usage summary to a chat channel,
+//! e.g. weekly from cron.
+//!
+//! The message is rendered from a template with `{{placeholder}}`s filled in
+//! from a [`Report`], then wrapped in the payload the service expects. Slack,
+//! Discord and Teams incoming webhooks are recognized by URL; anything else
+//! gets the report as JSON.
+
+use crate::report::Report;
+use crate::utils::{NumberFormatOptions, format_number};
+use anyhow::{Context, Result};
+use std::fmt::Write;
+
+/// Message used unless `--template` names another. Session names often hold
+/// a prompt, so `{{top_sessions}}` is left for templates to opt into.
+pub const DEFAULT_TEMPLATE: &str = "\
+{{heading}}
+{{cost}} across {{sessions}} sessions and {{messages}} messages
+
+By tool:
+{{tools}}
+
+Models:
+{{models}}";
+
+/// Names a template can use.
+pub const PLACEHOLDERS: &[&str] = &[
+    "heading",
+    "title",
+    "range",
+    "cost",
+    "sessions",
+    "messages",
+    "input_tokens",
+    "output_tokens",
+    "tool_calls",
+    "tools",
+    "models",
+    "top_sessions",
+    "report",
+];
+
+/// Discord rejects messages longer than this.
+const DISCORD_MAX_CHARS: usize = 2000;
+
+/// Sessions and models listed in the default message.
+const LIST_ITEMS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Style {
+    /// `{"text": ...}` with Slack's `*bold*`
+    Slack,
+    /// `{"content": ...}`, cut to Discord's 2000 characters
+    Discord,
+    /// `{"text": ...}` for a Teams incoming webhook
+    Teams,
+    /// The rendered message as `text/markdown`
+    Markdown,
+    /// The full report as JSON
+    Json,
+}
+
+impl Style {
+    /// Guess the service from a webhook URL.
+    pub fn detect(url: &str) -> Self {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if host == "hooks.slack.com" {
+            Style::Slack
+        } else if host == "discord.com" || host == "discordapp.com" {
+            Style::Discord
+        } else if host.ends_with(".webhook.office.com") || host.ends_with(".logic.azure.com") {
+            Style::Teams
+        } else {
+            Style::Json
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        match self {
+            Style::Slack => format!("*{text}*"),
+            _ => format!("**{text}**"),
+        }
+    }
+}
+
+/// Fill `template`'s placeholders from `report`.
+pub fn render(
+    template: &str,
+    report: &Report,
+    style: Style,
+    options: &NumberFormatOptions,
+) -> Result<String> {
+    let money = |amount: f64| {
+        format!(
+            "{}{amount:.prec$}",
+            options.currency_symbol,
+            prec = options.cost_decimal_places
+        )
+    };
+    let number = |n: u64| format_number(n, options);
+    let list = |lines: Vec<String>| {
+        if lines.is_empty() {
+            "• none".to_string()
+        } else {
+            lines.join("\n")
+        }
+    };
+
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .context("Template has a '{{' without a closing '}}'")?;
+        let name = after[..end].trim();
+        let value = match name {
+            "heading" => style.bold(&format!("{} ({})", report.title, report.range)),
+            "title" => report.title.clone(),
+            "range" => report.range.clone(),
+            "cost" => money(report.cost),
+            "sessions" => number(report.sessions as u64),
+            "messages" => number(report.messages),
+            "input_tokens" => number(report.input_tokens),
+            "output_tokens" => number(report.output_tokens),
+            "tool_calls" => number(report.tool_calls),
+            "tools" => list(
+                report
+                    .tools
+                    .iter()
+                    .map(|tool| {
+                        format!(
+                            "• {}: {} ({} sessions)",
+                            tool.name,
+                            money(tool.cost),
+                            number(tool.sessions as u64)
+                        )
+                    })
+                    .collect(),
+            ),
+            "models" => list(
+                report
+                    .models
+                    .iter()
+                    .take(LIST_ITEMS)
+                    .map(|model| {
+                        format!(
+                            "• {}: {} ({:.0}%)",
+                            model.model,
+                            money(model.cost),
+                            model.share * 100.0
+                        )
+                    })
+                    .collect(),
+            ),
+            "top_sessions" => list(
+                report
+                    .top_sessions
+                    .iter()
+                    .take(LIST_ITEMS)
+                    .map(|session| {
+                        format!(
+                            "• {} ({}, {}): {}",
+                            session.name.replace('\n', " "),
+                            session.tool,
+                            session.date,
+                            money(session.cost)
+                        )
+                    })
+                    .collect(),
+            ),
+            "report" => report.render(crate::report::Format::Markdown, options),
+            other => anyhow::bail!(
+                "Unknown template placeholder '{{{{{other}}}}}'. Use one of: {}",
+                PLACEHOLDERS.join(", ")
+            ),
+        };
+        out.push_str(&value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Request body and content type for `style`.
+pub fn payload(
+    report: &Report,
+    style: Style,
+    template: &str,
+    options: &NumberFormatOptions,
+) -> Result<(String, &'static str)> {
+    if style == Style::Json {
+        return Ok((simd_json::to_string(report)?, "application/json"));
+    }
+    let text = render(template, report, style, options)?;
+    let body = match style {
+        Style::Slack | Style::Teams => simd_json::json!({ "text": text }),
+        Style::Discord => simd_json::json!({ "content": truncate(&text, DISCORD_MAX_CHARS) }),
+        Style::Markdown => return Ok((text, "text/markdown; charset=utf-8")),
+        Style::Json => unreachable!(),
+    };
+    Ok((simd_json::to_string(&body)?, "application/json"))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars - 1).collect();
+    out.push('…');
+    out
+}
+
+/// Post `body` to `url`, through the upload proxy and CA settings.
+pub async fn post(url: &str, body: String, content_type: &str) -> Result<()> {
+    let response = super::get_http_client()
+        .post(url)
+        .header("Content-Type", content_type)
+        .body(body)
+        .send()
+        .await
+        .context("Failed to reach the webhook")?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        let mut message = format!("Webhook answered {status}");
+        if !text.trim().is_empty() {
+            let _ = write!(message, ": {}", truncate(text.trim(), 200));
+        }
+        anyhow::bail!(message);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{ModelRow, ToolRow};
+
+    fn report() -> Report {
+        Report {
+            title: "Weekly usage report".to_string(),
+            range: "2025-06-04..2025-06-10".to_string(),
+            cost: 1234.5,
+            sessions: 12,
+            messages: 3400,
+            tools: vec![ToolRow {
+                name: "Claude Code".to_string(),
+                cost: 1234.5,
+                sessions: 12,
+                ..ToolRow::default()
+            }],
+            models: vec![ModelRow {
+                model: "claude-sonnet-4-5".to_string(),
+                messages: 3400,
+                cost: 1234.5,
+                share: 1.0,
+            }],
+            ..Report::default()
+        }
+    }
+
+    fn options() -> NumberFormatOptions {
+        NumberFormatOptions {
+            use_comma: true,
+            use_human: false,
+            locale: "en".to_string(),
+            decimal_places: 2,
+            currency_symbol: "$".to_string(),
+            cost_decimal_places: 2,
+        }
+    }
+
+    #[test]
+    fn detects_services_by_host() {
+        assert_eq!(
+            Style::detect("https://hooks.slack.com/services/T/B/x"),
+            Style::Slack
+        );
+        assert_eq!(
+            Style::detect("https://discord.com/api/webhooks/1/abc"),
+            Style::Discord
+        );
+        assert_eq!(
+            Style::detect("https://acme.webhook.office.com/webhookb2/x"),
+            Style::Teams
+        );
+        assert_eq!(
+            Style::detect("https://example.com/hooks.slack.com"),
+            Style::Json
+        );
+    }
+
+    #[test]
+    fn default_template_renders_in_each_dialect() {
+        let slack = render(DEFAULT_TEMPLATE, &report(), Style::Slack, &options()).unwrap();
+        assert!(slack.starts_with("*Weekly usage report (2025-06-04..2025-06-10)*\n"));
+        assert!(slack.contains("$1234.50 across 12 sessions and 3,400 messages"));
+        assert!(slack.contains("• Claude Code: $1234.50 (12 sessions)"));
+        assert!(!slack.contains("Top sessions"));
+
+        let discord = render(DEFAULT_TEMPLATE, &report(), Style::Discord, &options()).unwrap();
+        assert!(discord.starts_with("**Weekly usage report"));
+    }
+
+    #[test]
+    fn payloads_match_each_service() {
+        let (body, content_type) =
+            payload(&report(), Style::Slack, "{{ cost }} this week", &options()).unwrap();
+        assert_eq!(body, r#"{"text":"$1234.50 this week"}"#);
+        assert_eq!(content_type, "application/json");
+
+        let long = "x".repeat(3000);
+        let (body, _) = payload(&report(), Style::Discord, &long, &options()).unwrap();
+        assert_eq!(body.chars().filter(|c| *c == 'x').count(), 1999);
+
+        let (body, _) = payload(&report(), Style::Json, "", &options()).unwrap();
+        assert!(body.contains(r#""range":"2025-06-04..2025-06-10""#));
+    }
+
+    #[test]
+    fn unknown_placeholders_are_errors() {
+        let err = render("{{ spend }}", &report(), Style::Slack, &options()).unwrap_err();
+        assert!(err.to_string().contains("'{{spend}}'"));
+        assert!(render("{{ cost", &report(), Style::Slack, &options()).is_err());
+    }
+}
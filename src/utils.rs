@@ -1,20 +1,24 @@
 use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use anyhow::Result;
-use chrono::{DateTime, Datelike, Local, Utc};
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
 use num_format::{Locale, ToFormattedString};
 use parking_lot::Mutex;
 use serde::{Deserialize, Deserializer, Serialize};
 use sha2::{Digest, Sha256};
 use xxhash_rust::xxh3::xxh3_64;
 
-use crate::types::{CompactDate, ConversationMessage, DailyStats, MessageRole, ModelStats};
+use crate::models;
+use crate::types::{
+    CompactDate, ConversationMessage, DailyStats, MessageRole, ModelStats, PromptCacheStats,
+};
 
 static WARNED_MESSAGES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
 static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Warn as u8);
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -50,6 +54,22 @@ pub fn set_log_level(level: LogLevel) {
     LOG_LEVEL.store(level as u8, Ordering::Relaxed);
 }
 
+/// Whether `NO_COLOR` is set to a non-empty value (<https://no-color.org>).
+pub fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Switch off ANSI colors and Unicode box-drawing characters in the TUI and
+/// console messages.
+pub fn set_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+    crossterm::style::force_color_output(!plain);
+}
+
+pub fn plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
 pub fn warn_once(message: impl Into<String>) {
     if LOG_LEVEL.load(Ordering::Relaxed) < LogLevel::Warn as u8 {
         return;
@@ -218,12 +238,91 @@ pub fn format_date_for_display(date: &str) -> String {
     }
 }
 
+/// Default lifetime of an Anthropic prompt-cache entry.
+pub const PROMPT_CACHE_TTL_SECS: i64 = 5 * 60;
+
+/// Indices of assistant messages that wrote to the prompt cache after more than
+/// [`PROMPT_CACHE_TTL_SECS`] since the previous assistant message in the same
+/// conversation, i.e. writes that only happened because the cache had expired.
+fn cache_expiry_indices(entries: &[ConversationMessage]) -> HashSet<usize> {
+    let mut by_conversation: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.role == MessageRole::Assistant {
+            by_conversation
+                .entry(entry.conversation_hash.as_str())
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut expired = HashSet::new();
+    for mut indices in by_conversation.into_values() {
+        indices.sort_by_key(|&i| entries[i].date);
+        for pair in indices.windows(2) {
+            let (prev, cur) = (&entries[pair[0]], &entries[pair[1]]);
+            if cur.stats.cache_creation_tokens > 0
+                && (cur.date - prev.date).num_seconds() > PROMPT_CACHE_TTL_SECS
+            {
+                expired.insert(pair[1]);
+            }
+        }
+    }
+    expired
+}
+
+/// One assistant message's prompt-cache savings, plus the expiry it caused
+/// when `expired` (see [`cache_expiry_indices`]).
+fn message_prompt_cache(
+    model: &str,
+    entry: &ConversationMessage,
+    expired: bool,
+) -> PromptCacheStats {
+    let mut cache = PromptCacheStats {
+        saved_cost: crate::stats::cache::read_savings(
+            model,
+            crate::stats::cache::cache_reads(
+                entry.stats.cache_creation_tokens,
+                entry.stats.cache_read_tokens,
+                entry.stats.cached_tokens,
+            ),
+        ),
+        ..PromptCacheStats::default()
+    };
+    if expired {
+        let written = entry.stats.cache_creation_tokens;
+        cache.expiry_misses = 1;
+        cache.expiry_tokens = written;
+        cache.expiry_cost = (models::calculate_cache_cost(model, written, 0)
+            - models::calculate_cache_cost(model, 0, written))
+        .max(0.0);
+    }
+    cache
+}
+
+/// Prompt-cache savings and expiries of `entries` in total, as
+/// [`aggregate_by_date`] counts them per day.
+pub fn prompt_cache_stats(entries: &[ConversationMessage]) -> PromptCacheStats {
+    let cache_expiries = cache_expiry_indices(entries);
+    let mut total = PromptCacheStats::default();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.role == MessageRole::Assistant
+            && let Some(model) = &entry.model
+        {
+            total += message_prompt_cache(model, entry, cache_expiries.contains(&i));
+        }
+    }
+    total
+}
+
 // TODO: Don't use strings here, wasteful.
 pub fn aggregate_by_date(entries: &[ConversationMessage]) -> BTreeMap<String, DailyStats> {
     let mut daily_stats: BTreeMap<String, DailyStats> = BTreeMap::new();
     let mut conversation_start_dates: BTreeMap<String, String> = BTreeMap::new();
+    let cache_expiries = cache_expiry_indices(entries);
+    // Each conversation's message times per day, for active time.
+    let mut day_timestamps: BTreeMap<(String, &str), Vec<DateTime<Utc>>> = BTreeMap::new();
 
-    for entry in entries {
+    for (i, entry) in entries.iter().enumerate() {
         let timestamp = &entry.date.with_timezone(&Local);
         let conversation_hash = &entry.conversation_hash;
         let date = timestamp.format("%Y-%m-%d").to_string();
@@ -240,6 +339,11 @@ pub fn aggregate_by_date(entries: &[ConversationMessage]) -> BTreeMap<String, Da
             })
             .or_insert(date.clone());
 
+        day_timestamps
+            .entry((date.clone(), conversation_hash.as_str()))
+            .or_default()
+            .push(entry.date);
+
         let daily_stats_entry = daily_stats
             .entry(date.clone())
             .or_insert_with(|| DailyStats {
@@ -250,6 +354,7 @@ pub fn aggregate_by_date(entries: &[ConversationMessage]) -> BTreeMap<String, Da
         match entry.role {
             MessageRole::Assistant => {
                 daily_stats_entry.ai_messages += 1;
+                daily_stats_entry.hourly_messages[timestamp.hour() as usize] += 1;
 
                 if let Some(model) = &entry.model {
                     *daily_stats_entry
@@ -257,15 +362,27 @@ pub fn aggregate_by_date(entries: &[ConversationMessage]) -> BTreeMap<String, Da
                         .entry(model.to_string())
                         .or_insert(0) += 1;
 
-                    daily_stats_entry
+                    let cache = message_prompt_cache(model, entry, cache_expiries.contains(&i));
+                    let model_stats = daily_stats_entry
                         .model_stats
                         .entry(model.to_string())
-                        .or_insert_with(|| ModelStats::new(model.to_string()))
-                        .add_message(&entry.stats);
+                        .or_insert_with(|| ModelStats::new(model.to_string()));
+                    model_stats.add_message(&entry.stats);
+                    model_stats.saved_cost += cache.saved_cost;
+                    daily_stats_entry.prompt_cache += cache;
                 }
 
-                // Aggregate TUI-relevant stats only (TuiStats has 6 fields)
+                // Aggregate TUI-relevant stats only
                 daily_stats_entry.stats.add_cost(entry.stats.cost);
+                if entry.stats.subagent_messages > 0 {
+                    daily_stats_entry.stats.subagent_cost_cents = daily_stats_entry
+                        .stats
+                        .subagent_cost_cents
+                        .saturating_add((entry.stats.cost * 100.0).round() as u32);
+                }
+                daily_stats_entry
+                    .stats
+                    .add_premium_requests(entry.stats.premium_requests);
                 daily_stats_entry.stats.input_tokens = daily_stats_entry
                     .stats
                     .input_tokens
@@ -294,6 +411,15 @@ pub fn aggregate_by_date(entries: &[ConversationMessage]) -> BTreeMap<String, Da
         };
     }
 
+    for ((date, _), timestamps) in day_timestamps {
+        if let Some(daily_stats_entry) = daily_stats.get_mut(&date) {
+            daily_stats_entry.stats.active_secs = daily_stats_entry
+                .stats
+                .active_secs
+                .saturating_add(crate::stats::active::active_seconds(timestamps));
+        }
+    }
+
     // Track conversations started on each date and update daily stats
     for start_date in conversation_start_dates.values() {
         if let Some(daily_stats_entry) = daily_stats.get_mut(start_date) {
@@ -373,6 +499,262 @@ pub fn filter_zero_cost_messages(messages: Vec<ConversationMessage>) -> Vec<Conv
         .collect()
 }
 
+/// Working directories behind project hashes, as analyzers come across them.
+static PROJECT_DIRS: std::sync::LazyLock<dashmap::DashMap<String, std::sync::Arc<str>>> =
+    std::sync::LazyLock::new(dashmap::DashMap::new);
+
+/// Record the directory a project hash was computed from, for analyzers whose
+/// logs name one. The first directory seen for a hash is kept.
+pub fn remember_project(project_hash: &str, dir: &str) {
+    if dir.is_empty() || PROJECT_DIRS.contains_key(project_hash) {
+        return;
+    }
+    PROJECT_DIRS
+        .entry(project_hash.to_string())
+        .or_insert_with(|| std::sync::Arc::from(dir));
+}
+
+/// The directory recorded for `project_hash`, if its analyzer logs one.
+pub fn project_dir(project_hash: &str) -> Option<std::sync::Arc<str>> {
+    PROJECT_DIRS.get(project_hash).map(|dir| dir.clone())
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest).to_string_lossy().into_owned();
+    }
+    path.to_string()
+}
+
+/// The working directory recorded on the first JSONL transcript line that
+/// has one: top-level for Claude Code, in the `session_meta` payload for
+/// Codex. Only the head of the file is read.
+pub fn transcript_cwd(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    use simd_json::prelude::*;
+    use std::io::{BufRead, BufReader};
+
+    const HEAD_LINES: usize = 64;
+
+    if path.extension().is_none_or(|ext| ext != "jsonl") {
+        return None;
+    }
+    let file = std::fs::File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .take(HEAD_LINES)
+        .filter(|line| line.contains("\"cwd\""))
+        .find_map(|line| {
+            let value = simd_json::to_owned_value(&mut line.into_bytes()).ok()?;
+            value
+                .get_str("cwd")
+                .or_else(|| value.get("payload")?.get_str("cwd"))
+                .map(std::path::PathBuf::from)
+        })
+}
+
+/// Language or format name for a lowercase file extension, grouping variants
+/// such as `ts`/`tsx`. Unknown extensions come back as `.ext`.
+pub fn language_name(ext: &str) -> String {
+    let name = match ext {
+        "rs" => "Rust",
+        "py" | "pyi" => "Python",
+        "js" | "mjs" | "cjs" | "jsx" => "JavaScript",
+        "ts" | "mts" | "cts" | "tsx" => "TypeScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "scala" => "Scala",
+        "dart" => "Dart",
+        "lua" => "Lua",
+        "zig" => "Zig",
+        "ex" | "exs" => "Elixir",
+        "hs" => "Haskell",
+        "sh" | "bash" | "zsh" | "fish" => "Shell",
+        "ps1" => "PowerShell",
+        "sql" => "SQL",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "md" | "mdx" | "markdown" => "Markdown",
+        "txt" | "rst" | "adoc" => "Text",
+        "json" | "jsonc" | "jsonl" => "JSON",
+        "yaml" | "yml" => "YAML",
+        "toml" => "TOML",
+        "xml" => "XML",
+        "ipynb" => "Jupyter",
+        _ => return format!(".{ext}"),
+    };
+    name.to_string()
+}
+
+/// Per-extension counts grouped by [`language_name`], most used first.
+pub fn languages(file_extensions: &BTreeMap<String, u64>) -> Vec<(String, u64)> {
+    let mut by_language: BTreeMap<String, u64> = BTreeMap::new();
+    for (ext, count) in file_extensions {
+        *by_language.entry(language_name(ext)).or_default() += count;
+    }
+    let mut languages: Vec<(String, u64)> = by_language.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    languages
+}
+
+/// What a terminal command does, from its program and subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandCategory {
+    Git,
+    Test,
+    Build,
+    Package,
+    Files,
+    Search,
+    Network,
+    Other,
+}
+
+impl CommandCategory {
+    pub const ALL: [CommandCategory; 8] = [
+        CommandCategory::Git,
+        CommandCategory::Test,
+        CommandCategory::Build,
+        CommandCategory::Package,
+        CommandCategory::Files,
+        CommandCategory::Search,
+        CommandCategory::Network,
+        CommandCategory::Other,
+    ];
+
+    /// Key stored in [`crate::types::Stats::command_categories`].
+    pub fn key(self) -> &'static str {
+        match self {
+            CommandCategory::Git => "git",
+            CommandCategory::Test => "test",
+            CommandCategory::Build => "build",
+            CommandCategory::Package => "package",
+            CommandCategory::Files => "files",
+            CommandCategory::Search => "search",
+            CommandCategory::Network => "network",
+            CommandCategory::Other => "other",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CommandCategory::Git => "Git",
+            CommandCategory::Test => "Tests",
+            CommandCategory::Build => "Build & lint",
+            CommandCategory::Package => "Packages",
+            CommandCategory::Files => "File ops",
+            CommandCategory::Search => "Search",
+            CommandCategory::Network => "Network",
+            CommandCategory::Other => "Other",
+        }
+    }
+}
+
+/// Categorize a shell command line by the first program that isn't setup
+/// (`cd`, `export`, ...), so `cd app && npm test` counts as a test run.
+pub fn classify_command(command: &str) -> CommandCategory {
+    command
+        .split(['&', '|', ';', '\n'])
+        .find_map(|segment| {
+            let words: Vec<&str> = segment
+                .split_whitespace()
+                .skip_while(|word| {
+                    matches!(*word, "sudo" | "time" | "env" | "nohup" | "exec")
+                        || (word.contains('=') && !word.starts_with('-'))
+                })
+                .collect();
+            let program = words.first()?.rsplit('/').next()?;
+            if matches!(
+                program,
+                "" | "cd" | "pushd" | "popd" | "export" | "source" | "." | "set" | "echo" | "true"
+            ) {
+                return None;
+            }
+            Some(classify_words(&words))
+        })
+        .unwrap_or(CommandCategory::Other)
+}
+
+fn classify_words(words: &[&str]) -> CommandCategory {
+    let program = words[0].rsplit('/').next().unwrap_or(words[0]);
+    let args: Vec<&str> = words[1..]
+        .iter()
+        .copied()
+        .filter(|word| !word.starts_with('-'))
+        .collect();
+    let sub = args.first().copied().unwrap_or("");
+    let script_category = |script: &str| {
+        if script.contains("test") {
+            CommandCategory::Test
+        } else {
+            CommandCategory::Build
+        }
+    };
+    match program {
+        "git" | "gh" => CommandCategory::Git,
+        "cargo" => match sub {
+            "test" | "nextest" | "bench" => CommandCategory::Test,
+            "add" | "install" | "update" | "remove" | "fetch" => CommandCategory::Package,
+            _ => CommandCategory::Build,
+        },
+        "npm" | "pnpm" | "yarn" | "bun" => match sub {
+            "install" | "i" | "ci" | "add" | "remove" | "uninstall" | "update" | "" => {
+                CommandCategory::Package
+            }
+            "run" => script_category(args.get(1).copied().unwrap_or("")),
+            script => script_category(script),
+        },
+        "npx" | "bunx" | "pnpx" => match sub {
+            "jest" | "vitest" | "mocha" | "playwright" | "cypress" => CommandCategory::Test,
+            "" => CommandCategory::Other,
+            _ => CommandCategory::Build,
+        },
+        "uv" | "poetry" if sub == "run" && args.len() > 1 => classify_words(&args[1..]),
+        "python" | "python3" => match sub {
+            "pytest" | "unittest" => CommandCategory::Test,
+            "pip" => CommandCategory::Package,
+            _ => CommandCategory::Other,
+        },
+        "pytest" | "jest" | "vitest" | "mocha" | "rspec" | "tox" | "phpunit" | "ctest" => {
+            CommandCategory::Test
+        }
+        "go" => match sub {
+            "test" => CommandCategory::Test,
+            "get" | "install" | "mod" => CommandCategory::Package,
+            _ => CommandCategory::Build,
+        },
+        "make" | "mvn" | "gradle" | "gradlew" | "dotnet" | "swift" | "bazel" => match sub {
+            "test" | "check" => CommandCategory::Test,
+            _ => CommandCategory::Build,
+        },
+        "cmake" | "ninja" | "tsc" | "gcc" | "g++" | "clang" | "rustc" | "javac" | "xcodebuild"
+        | "eslint" | "prettier" | "ruff" | "black" | "mypy" | "rustfmt" => CommandCategory::Build,
+        "pip" | "pip3" | "uv" | "poetry" | "brew" | "apt" | "apt-get" | "gem" | "composer" => {
+            CommandCategory::Package
+        }
+        "ls" | "cat" | "head" | "tail" | "mkdir" | "rm" | "rmdir" | "mv" | "cp" | "touch"
+        | "chmod" | "chown" | "ln" | "tree" | "wc" | "sed" | "awk" | "stat" | "du" | "tar"
+        | "unzip" | "zip" | "diff" | "file" => CommandCategory::Files,
+        "grep" | "rg" | "ag" | "ack" | "fd" | "find" | "locate" => CommandCategory::Search,
+        "curl" | "wget" | "ssh" | "scp" | "rsync" | "ping" | "nc" | "http" | "dig" | "telnet" => {
+            CommandCategory::Network
+        }
+        _ => CommandCategory::Other,
+    }
+}
+
 pub fn hash_text(text: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(text);
@@ -384,6 +766,62 @@ pub fn fast_hash(text: &str) -> String {
     format!("{:016x}", xxh3_64(text.as_bytes()))
 }
 
+/// A session ID that stays the same across runs and machines, and when a
+/// project directory is moved. `conversation_hash` can't promise that: most
+/// analyzers hash the transcript's full path into it. This is built from the
+/// analyzer, the session's first timestamp and, where the tool records one,
+/// its own ID for the first message instead. Tools without message IDs, like
+/// Codex CLI, are identified by analyzer and timestamp alone.
+pub fn stable_session_id(analyzer_name: &str, first: &ConversationMessage) -> String {
+    let mut id = hash_text(&format!(
+        "{analyzer_name}\n{}\n{}",
+        first.date.timestamp_millis(),
+        message_tool_id(first)
+    ));
+    id.truncate(32);
+    id
+}
+
+fn message_tool_id(message: &ConversationMessage) -> &str {
+    message
+        .uuid
+        .as_deref()
+        .or(message.local_hash.as_deref())
+        .unwrap_or_default()
+}
+
+/// Each session's first message, keyed by `conversation_hash`: the earliest,
+/// with ties broken by the tool's message ID so the choice doesn't depend on
+/// the order messages were parsed in. Views and uploads both pick through
+/// this, so they derive the same [`stable_session_id`].
+pub fn first_messages_by_session(
+    messages: &[ConversationMessage],
+) -> std::collections::HashMap<&str, &ConversationMessage> {
+    let mut firsts: std::collections::HashMap<&str, &ConversationMessage> =
+        std::collections::HashMap::new();
+    for message in messages {
+        let first = firsts
+            .entry(message.conversation_hash.as_str())
+            .or_insert(message);
+        if (message.date, message_tool_id(message)) < (first.date, message_tool_id(first)) {
+            *first = message;
+        }
+    }
+    firsts
+}
+
+/// Set `stable_session_id` on every message. `messages` must hold whole
+/// sessions, since the ID depends on each session's first message.
+pub fn assign_stable_session_ids(analyzer_name: &str, messages: &mut [ConversationMessage]) {
+    let ids: std::collections::HashMap<String, String> = first_messages_by_session(messages)
+        .into_iter()
+        .map(|(session, first)| (session.to_string(), stable_session_id(analyzer_name, first)))
+        .collect();
+    for message in messages.iter_mut() {
+        message.stable_session_id = ids.get(&message.conversation_hash).cloned();
+    }
+}
+
 /// Sequential deduplication by global_hash using HashSet.
 /// Used for post-init processing (incremental updates, uploads).
 pub fn deduplicate_by_global_hash(messages: Vec<ConversationMessage>) -> Vec<ConversationMessage> {
@@ -415,6 +853,28 @@ pub fn deduplicate_by_local_hash(messages: Vec<ConversationMessage>) -> Vec<Conv
         .collect()
 }
 
+/// [`deduplicate_by_local_hash`] across sources parsed separately, keeping
+/// each message in the first source it appears in.
+pub fn deduplicate_grouped_by_local_hash(
+    grouped: Vec<(std::path::PathBuf, Vec<ConversationMessage>)>,
+) -> Vec<(std::path::PathBuf, Vec<ConversationMessage>)> {
+    let mut seen: HashSet<String> = HashSet::new();
+    grouped
+        .into_iter()
+        .map(|(path, messages)| {
+            let messages = messages
+                .into_iter()
+                .filter(|msg| {
+                    msg.local_hash
+                        .as_ref()
+                        .is_none_or(|local_hash| seen.insert(local_hash.clone()))
+                })
+                .collect();
+            (path, messages)
+        })
+        .collect()
+}
+
 /// Custom serde deserializer for RFC3339 timestamp strings to `DateTime<Utc>`
 pub fn deserialize_utc_timestamp<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
@@ -3,6 +3,51 @@ use crate::types::{ConversationMessage, MessageRole, Stats};
 use chrono::{TimeZone, Utc};
 use std::collections::HashSet;
 
+#[test]
+fn test_languages_group_extensions_by_language() {
+    let extensions: BTreeMap<String, u64> = [
+        ("rs".to_string(), 6),
+        ("ts".to_string(), 1),
+        ("tsx".to_string(), 2),
+        ("md".to_string(), 3),
+        ("proto".to_string(), 1),
+    ]
+    .into();
+    assert_eq!(
+        languages(&extensions),
+        vec![
+            ("Rust".to_string(), 6),
+            ("Markdown".to_string(), 3),
+            ("TypeScript".to_string(), 3),
+            (".proto".to_string(), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_classify_command_by_program_and_subcommand() {
+    let cases = [
+        ("git status", CommandCategory::Git),
+        ("cd app && npm test", CommandCategory::Test),
+        ("npm run build", CommandCategory::Build),
+        ("RUST_LOG=debug cargo test -p core", CommandCategory::Test),
+        (
+            "cargo clippy --all-targets 2>&1 | tail -20",
+            CommandCategory::Build,
+        ),
+        ("uv run pytest tests/", CommandCategory::Test),
+        ("pip install requests", CommandCategory::Package),
+        ("/bin/ls -la", CommandCategory::Files),
+        ("rg TODO src", CommandCategory::Search),
+        ("curl -s https://example.com", CommandCategory::Network),
+        ("docker compose up", CommandCategory::Other),
+        ("", CommandCategory::Other),
+    ];
+    for (command, category) in cases {
+        assert_eq!(classify_command(command), category, "{command}");
+    }
+}
+
 #[test]
 fn test_format_number_comma() {
     let options = NumberFormatOptions {
@@ -281,6 +326,7 @@ async fn test_get_messages_later_than() {
         role: MessageRole::User,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg_after = ConversationMessage {
@@ -322,6 +368,7 @@ fn test_aggregate_by_date_basic() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let result = aggregate_by_date(&[msg]);
@@ -332,6 +379,90 @@ fn test_aggregate_by_date_basic() {
     assert_eq!(stats.conversations, 1);
     assert_eq!(stats.stats.input_tokens, 100);
     assert_eq!(stats.stats.cost(), 0.01);
+    let local_hour = chrono::Timelike::hour(&date.with_timezone(&chrono::Local)) as usize;
+    assert_eq!(stats.hourly_messages[local_hour], 1);
+    assert_eq!(stats.hourly_messages.iter().sum::<u32>(), 1);
+}
+
+#[test]
+fn test_aggregate_by_date_prompt_cache_expiry() {
+    let start = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    let msg = |minutes: i64, conversation: &str, creation: u64, read: u64| ConversationMessage {
+        date: start + chrono::Duration::minutes(minutes),
+        application: crate::types::Application::ClaudeCode,
+        project_hash: "p".to_string(),
+        conversation_hash: conversation.to_string(),
+        local_hash: None,
+        global_hash: format!("{conversation}_{minutes}"),
+        model: Some("claude-sonnet-4".to_string()),
+        stats: Stats {
+            cache_creation_tokens: creation,
+            cache_read_tokens: read,
+            ..Stats::default()
+        },
+        role: MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    };
+
+    // Out of order on purpose: expiry detection sorts each conversation by time.
+    let messages = vec![
+        msg(2, "c1", 0, 1_000_000),
+        msg(0, "c1", 1_000_000, 0),
+        // 10 minutes idle: the prefix had to be written again.
+        msg(12, "c1", 1_000_000, 0),
+        // Within the TTL, so a write here is just new context.
+        msg(14, "c1", 1_000, 0),
+        // A different conversation's first write is never an expiry.
+        msg(30, "c2", 1_000_000, 0),
+    ];
+
+    let result = aggregate_by_date(&messages);
+    let day = result.values().next().unwrap();
+    let cache = day.prompt_cache;
+
+    assert_eq!(cache.expiry_misses, 1);
+    assert_eq!(cache.expiry_tokens, 1_000_000);
+    // Sonnet 4: $3.75/M to write vs $0.30/M to read.
+    assert!((cache.expiry_cost - 3.45).abs() < 1e-9);
+    // $3.00/M input vs $0.30/M cache read.
+    assert!((cache.saved_cost - 2.7).abs() < 1e-9);
+    assert!((day.model_stats["claude-sonnet-4"].saved_cost - 2.7).abs() < 1e-9);
+}
+
+#[test]
+fn test_aggregate_by_date_active_time() {
+    let start = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    let msg = |minutes: i64, conversation: &str| ConversationMessage {
+        date: start + chrono::Duration::minutes(minutes),
+        application: crate::types::Application::ClaudeCode,
+        project_hash: "p".to_string(),
+        conversation_hash: conversation.to_string(),
+        local_hash: None,
+        global_hash: format!("{conversation}_{minutes}"),
+        model: Some("claude-sonnet-4".to_string()),
+        stats: Stats::default(),
+        role: MessageRole::Assistant,
+        uuid: None,
+        session_name: None,
+        stable_session_id: None,
+    };
+
+    let messages = vec![
+        // 10 active minutes, then a 30 minute pause that ends the block.
+        msg(0, "c1"),
+        msg(10, "c1"),
+        // A lone message counts a minute.
+        msg(40, "c1"),
+        // Conversations are timed separately.
+        msg(5, "c2"),
+        msg(8, "c2"),
+    ];
+
+    let result = aggregate_by_date(&messages);
+    let active: u32 = result.values().map(|day| day.stats.active_secs).sum();
+    assert_eq!(active, (10 + 1 + 3) * 60);
 }
 
 #[test]
@@ -352,6 +483,7 @@ fn test_aggregate_by_date_gap_filling() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg3 = ConversationMessage {
@@ -409,6 +541,7 @@ fn test_aggregate_by_date_counts_assistant_without_model_as_ai_message() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let result = aggregate_by_date(&[msg]);
@@ -447,6 +580,7 @@ fn test_filter_zero_cost_messages_all_zero_cost() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg2 = ConversationMessage {
@@ -480,6 +614,7 @@ fn test_filter_zero_cost_messages_no_zero_cost() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg2 = ConversationMessage {
@@ -518,6 +653,7 @@ fn test_filter_zero_cost_messages_mixed() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg_nonzero = ConversationMessage {
@@ -571,6 +707,7 @@ fn test_filter_zero_cost_messages_near_zero() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     // Test with cost just under epsilon (should be treated as zero)
@@ -624,6 +761,7 @@ fn test_filter_zero_cost_messages_negative_cost() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     // Test with larger negative cost (should NOT be filtered as zero)
@@ -664,6 +802,7 @@ fn test_deduplicate_by_global_hash() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg2 = ConversationMessage {
@@ -705,6 +844,7 @@ fn test_deduplicate_by_local_hash() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg2 = ConversationMessage {
@@ -742,6 +882,7 @@ fn test_deduplicate_keeps_messages_without_local_hash() {
         role: MessageRole::Assistant,
         uuid: None,
         session_name: None,
+        stable_session_id: None,
     };
 
     let msg_no_hash1 = ConversationMessage {
@@ -788,3 +929,68 @@ fn test_fast_hash_different_inputs() {
     assert_ne!(hash1, hash3);
     assert_ne!(hash2, hash3);
 }
+
+#[test]
+fn test_stable_session_ids_ignore_the_transcript_path() {
+    let message = |conversation: &str, uuid: &str, minute: u32| ConversationMessage {
+        date: Utc.with_ymd_and_hms(2025, 3, 1, 9, minute, 0).unwrap(),
+        application: crate::types::Application::ClaudeCode,
+        project_hash: "p".to_string(),
+        conversation_hash: conversation.to_string(),
+        local_hash: None,
+        global_hash: format!("{conversation}-{uuid}"),
+        model: None,
+        stats: Stats::default(),
+        role: MessageRole::User,
+        uuid: Some(uuid.to_string()),
+        session_name: None,
+        stable_session_id: None,
+    };
+    let mut before = vec![message("old-path", "b", 5), message("old-path", "a", 1)];
+    let mut after = vec![message("new-path", "a", 1), message("new-path", "b", 5)];
+    assign_stable_session_ids("Claude Code", &mut before);
+    assign_stable_session_ids("Claude Code", &mut after);
+
+    let id = before[0].stable_session_id.clone().unwrap();
+    assert_eq!(id.len(), 32);
+    assert_eq!(before[1].stable_session_id.as_ref(), Some(&id));
+    assert_eq!(after[0].stable_session_id.as_ref(), Some(&id));
+    assert_eq!(id, stable_session_id("Claude Code", &before[1]));
+    assert_ne!(id, stable_session_id("Codex CLI", &before[1]));
+}
+
+#[test]
+fn test_views_and_uploads_pick_the_same_first_message() {
+    // Two messages share the first timestamp; whichever was parsed first,
+    // the view's session ID must match the one uploads carry.
+    let message = |uuid: &str| ConversationMessage {
+        date: Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap(),
+        application: crate::types::Application::ClaudeCode,
+        project_hash: "p".to_string(),
+        conversation_hash: "c".to_string(),
+        local_hash: None,
+        global_hash: uuid.to_string(),
+        model: None,
+        stats: Stats::default(),
+        role: MessageRole::User,
+        uuid: Some(uuid.to_string()),
+        session_name: None,
+        stable_session_id: None,
+    };
+    let messages = vec![message("b"), message("a")];
+    let sessions = crate::tui::logic::aggregate_sessions_from_messages(
+        &messages,
+        std::sync::Arc::from("Claude Code"),
+    );
+
+    let mut uploaded = messages.clone();
+    assign_stable_session_ids("Claude Code", &mut uploaded);
+    assert_eq!(
+        uploaded[0].stable_session_id.as_deref(),
+        Some(sessions[0].stable_id.as_str())
+    );
+    assert_eq!(
+        sessions[0].stable_id,
+        stable_session_id("Claude Code", &messages[1])
+    );
+}
@@ -1,18 +1,20 @@
 use anyhow::Result;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use notify_types::event::{Event, EventKind};
+use notify_types::event::{Event, EventKind, ModifyKind, RemoveKind};
 use parking_lot::Mutex;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::time::{Duration, Instant};
+use std::thread::Thread;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::watch;
 
 use crate::analyzer::AnalyzerRegistry;
 use crate::config::{Config, UploadState};
 use crate::tui::UploadStatus;
-use crate::types::MultiAnalyzerStatsView;
+use crate::types::{CompactDate, MultiAnalyzerStatsView};
 use crate::upload;
 
 #[derive(Debug, Clone)]
@@ -21,67 +23,363 @@ pub enum WatcherEvent {
     FileChanged(String, PathBuf),
     /// A file was deleted (analyzer name, file path)
     FileDeleted(String, PathBuf),
+    /// A directory appeared under a watched root (analyzer name, directory
+    /// path). Files written into it before its watch was set up sent no
+    /// events of their own.
+    DirectoryCreated(String, PathBuf),
+    /// The config file was written; pricing overrides may have changed
+    ConfigChanged,
+    /// The watcher was restarted and may have missed events; reload everything
+    Resync,
+    /// The user asked to drop the cache and parse one analyzer (or all of
+    /// them, with `None`) again
+    Rescan(Option<String>),
     /// An error occurred
     Error(String),
 }
 
+/// Whether file system events are still arriving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatcherHealth {
+    Live,
+    /// The backend failed or a watched directory went away; live updates are
+    /// paused until a restart succeeds.
+    Reconnecting {
+        attempts: u32,
+        reason: String,
+    },
+}
+
+/// How often the watcher's health is checked.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound for the exponential restart backoff.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Identity of a watched directory, so a directory that was removed and
+/// recreated (or a volume that was unmounted and remounted) is noticed even
+/// though the path still exists.
+#[cfg(unix)]
+fn dir_identity(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|meta| (meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_identity(path: &Path) -> Option<(u64, u64)> {
+    path.is_dir().then_some((0, 0))
+}
+
+type DirIdentities = HashMap<PathBuf, Option<(u64, u64)>>;
+
+fn snapshot_identities<'a>(dirs: impl IntoIterator<Item = &'a PathBuf>) -> DirIdentities {
+    dirs.into_iter()
+        .map(|dir| (dir.clone(), dir_identity(dir)))
+        .collect()
+}
+
+/// First watched directory whose identity changed since `snapshot`.
+fn changed_directory(snapshot: &DirIdentities) -> Option<&Path> {
+    snapshot
+        .iter()
+        .find(|(dir, identity)| dir_identity(dir) != **identity)
+        .map(|(dir, _)| dir.as_path())
+}
+
+/// Size and modification time of each file the polling backend watches.
+type FileStamps = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+fn stamp_files(roots: &[PathBuf]) -> FileStamps {
+    roots
+        .iter()
+        .flat_map(|root| walkdir::WalkDir::new(root).into_iter().flatten())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((entry.into_path(), (meta.len(), meta.modified().ok())))
+        })
+        .collect()
+}
+
+/// The events notify would have sent for what changed between two polls.
+fn stamp_changes(before: &FileStamps, after: &FileStamps) -> Vec<Event> {
+    let mut events: Vec<Event> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(*stamp))
+        .map(|(path, _)| Event::new(EventKind::Modify(ModifyKind::Any)).add_path(path.clone()))
+        .collect();
+    events.extend(
+        before
+            .keys()
+            .filter(|path| !after.contains_key(*path))
+            .map(|path| Event::new(EventKind::Remove(RemoveKind::Any)).add_path(path.clone())),
+    );
+    events
+}
+
+/// Background thread standing in for notify on file systems that don't
+/// deliver events (network mounts, WSL, some containers). Stops when dropped.
+struct Poller {
+    stop: Arc<AtomicBool>,
+    thread: Thread,
+}
+
+impl Poller {
+    fn spawn(
+        interval: Duration,
+        roots: Vec<PathBuf>,
+        mut handler: impl FnMut(Result<Event, notify::Error>) + Send + 'static,
+    ) -> Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        // Taken up front so changes made right after startup aren't folded
+        // into the baseline.
+        let mut stamps = stamp_files(&roots);
+        let handle = std::thread::Builder::new()
+            .name("splitrail-poll".to_string())
+            .spawn(move || {
+                loop {
+                    std::thread::park_timeout(interval);
+                    if thread_stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let next = stamp_files(&roots);
+                    for event in stamp_changes(&stamps, &next) {
+                        handler(Ok(event));
+                    }
+                    stamps = next;
+                }
+            })?;
+        Ok(Self {
+            stop,
+            thread: handle.thread().clone(),
+        })
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.unpark();
+    }
+}
+
 pub struct FileWatcher {
-    _watcher: RecommendedWatcher,
+    watcher: Option<RecommendedWatcher>,
+    poller: Option<Poller>,
+    /// Poll for changes at this interval instead of using notify.
+    poll_interval: Option<Duration>,
+    event_tx: Sender<WatcherEvent>,
     event_rx: Receiver<WatcherEvent>,
+    dir_to_analyzer: HashMap<PathBuf, String>,
+    config_path: Option<PathBuf>,
+    identities: DirIdentities,
+    /// Set by the notify callback when the backend reports an error.
+    backend_failed: Arc<AtomicBool>,
+    health: WatcherHealth,
+    next_check: Instant,
 }
 
 impl FileWatcher {
     pub fn new(registry: &AnalyzerRegistry) -> Result<Self> {
+        let poll_interval = Config::load()
+            .ok()
+            .flatten()
+            .and_then(|config| config.watcher.poll_interval_secs)
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs);
+        Self::with_mapping(
+            // Get directory to analyzer mapping from registry
+            registry.get_directory_to_analyzer_mapping(),
+            Config::config_path().ok(),
+            poll_interval,
+        )
+    }
+
+    fn with_mapping(
+        dir_to_analyzer: HashMap<PathBuf, String>,
+        config_path: Option<PathBuf>,
+        poll_interval: Option<Duration>,
+    ) -> Result<Self> {
         let (event_tx, event_rx) = mpsc::channel();
 
-        // Get directory to analyzer mapping from registry
-        let dir_to_analyzer = registry.get_directory_to_analyzer_mapping();
-        let watched_dirs: HashSet<_> = dir_to_analyzer.keys().cloned().collect();
+        let mut file_watcher = Self {
+            watcher: None,
+            poller: None,
+            poll_interval,
+            event_tx,
+            event_rx,
+            dir_to_analyzer,
+            config_path,
+            identities: HashMap::new(),
+            backend_failed: Arc::new(AtomicBool::new(false)),
+            health: WatcherHealth::Live,
+            next_check: Instant::now() + HEALTH_CHECK_INTERVAL,
+        };
+        file_watcher.start(true)?;
+        Ok(file_watcher)
+    }
 
-        let mut watcher =
-            notify::recommended_watcher(move |res: Result<Event, notify::Error>| match res {
-                Ok(event) => {
-                    if let Err(e) = handle_fs_event(event, &event_tx, &dir_to_analyzer) {
-                        let _ = event_tx
-                            .send(WatcherEvent::Error(format!("Event handling error: {e}")));
-                    }
+    /// (Re)create the notify backend and watch every directory again.
+    fn start(&mut self, warn: bool) -> Result<()> {
+        self.watcher = None;
+        self.poller = None;
+        self.backend_failed.store(false, Ordering::Relaxed);
+
+        let event_tx = self.event_tx.clone();
+        let dir_to_analyzer = self.dir_to_analyzer.clone();
+        let event_config_path = self.config_path.clone();
+        let backend_failed = Arc::clone(&self.backend_failed);
+
+        let handler = move |res: Result<Event, notify::Error>| match res {
+            Ok(event) => {
+                if let Err(e) = handle_fs_event(
+                    event,
+                    &event_tx,
+                    &dir_to_analyzer,
+                    event_config_path.as_deref(),
+                ) {
+                    let _ =
+                        event_tx.send(WatcherEvent::Error(format!("Event handling error: {e}")));
                 }
-                Err(e) => {
-                    let _ = event_tx.send(WatcherEvent::Error(format!("Watch error: {e}")));
-                }
-            })?;
+            }
+            Err(e) => {
+                backend_failed.store(true, Ordering::Relaxed);
+                let _ = event_tx.send(WatcherEvent::Error(format!("Watch error: {e}")));
+            }
+        };
+
+        let watched_dirs: HashSet<_> = self.dir_to_analyzer.keys().cloned().collect();
+
+        if let Some(interval) = self.poll_interval {
+            // Directories that don't exist yet are simply empty until they do.
+            let roots = watched_dirs
+                .iter()
+                .cloned()
+                .chain(self.config_path.clone())
+                .collect();
+            self.poller = Some(Poller::spawn(interval, roots, handler)?);
+            self.identities = snapshot_identities(&watched_dirs);
+            return Ok(());
+        }
+
+        let mut watcher = notify::recommended_watcher(handler)?;
 
         // Start watching all directories
         for dir in &watched_dirs {
             if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
-                eprintln!(
-                    "Warning: Could not watch directory {}: {}",
-                    dir.display(),
-                    e
-                );
+                if warn {
+                    eprintln!(
+                        "Warning: Could not watch directory {}: {}",
+                        dir.display(),
+                        e
+                    );
+                } else if dir.exists() {
+                    // A directory that is back but can't be watched means the
+                    // restart didn't take; a missing one is picked up once it
+                    // reappears.
+                    return Err(e.into());
+                }
             }
         }
 
-        Ok(Self {
-            _watcher: watcher,
-            event_rx,
-        })
+        // Watch the config file's directory (non-recursively, since editors
+        // often replace the file) so pricing overrides apply live.
+        if let Some(config_dir) = self.config_path.as_deref().and_then(Path::parent)
+            && !watched_dirs.iter().any(|dir| config_dir.starts_with(dir))
+        {
+            let _ = watcher.watch(config_dir, RecursiveMode::NonRecursive);
+        }
+
+        self.identities = snapshot_identities(&watched_dirs);
+        self.watcher = Some(watcher);
+        Ok(())
     }
 
     pub fn try_recv(&self) -> Option<WatcherEvent> {
         self.event_rx.try_recv().ok()
     }
+
+    pub fn health(&self) -> &WatcherHealth {
+        &self.health
+    }
+
+    /// Detect a dead backend or replaced directories and restart the watcher,
+    /// backing off between failed attempts. After a restart a
+    /// [`WatcherEvent::Resync`] is queued, since events may have been missed.
+    /// Cheap to call every tick; returns true when the health state changed.
+    pub fn check_health(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next_check {
+            return false;
+        }
+        self.next_check = now + HEALTH_CHECK_INTERVAL;
+
+        let attempts = match &self.health {
+            WatcherHealth::Reconnecting { attempts, .. } => *attempts,
+            WatcherHealth::Live => {
+                let reason = if self.backend_failed.load(Ordering::Relaxed) {
+                    Some("file watcher backend reported an error".to_string())
+                } else {
+                    changed_directory(&self.identities)
+                        .map(|dir| format!("{} was moved or remounted", dir.display()))
+                };
+                let Some(reason) = reason else {
+                    return false;
+                };
+                self.health = WatcherHealth::Reconnecting {
+                    attempts: 0,
+                    reason,
+                };
+                0
+            }
+        };
+
+        match self.start(false) {
+            Ok(()) => {
+                self.health = WatcherHealth::Live;
+                let _ = self.event_tx.send(WatcherEvent::Resync);
+            }
+            Err(e) => {
+                let attempts = attempts + 1;
+                let backoff = HEALTH_CHECK_INTERVAL
+                    .saturating_mul(1 << attempts.min(6))
+                    .min(MAX_RESTART_BACKOFF);
+                self.next_check = now + backoff;
+                self.health = WatcherHealth::Reconnecting {
+                    attempts,
+                    reason: format!("{e}"),
+                };
+            }
+        }
+        true
+    }
 }
 
 fn handle_fs_event(
     event: Event,
     tx: &Sender<WatcherEvent>,
     dir_to_analyzer: &HashMap<PathBuf, String>,
+    config_path: Option<&Path>,
 ) -> Result<()> {
     match event.kind {
         EventKind::Create(_) | EventKind::Modify(_) => {
+            // A directory created or moved in may already hold files by the
+            // time notify watches it.
+            let may_be_new_dir = matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+            );
             for path in &event.paths {
-                if let Some(analyzer_name) = find_analyzer_for_path(path, dir_to_analyzer) {
+                if config_path == Some(path.as_path()) {
+                    let _ = tx.send(WatcherEvent::ConfigChanged);
+                } else if let Some(analyzer_name) = find_analyzer_for_path(path, dir_to_analyzer) {
+                    if may_be_new_dir && path.is_dir() {
+                        let _ =
+                            tx.send(WatcherEvent::DirectoryCreated(analyzer_name, path.clone()));
+                        continue;
+                    }
                     // Send per-file event for incremental cache update
                     let _ = tx.send(WatcherEvent::FileChanged(analyzer_name, path.clone()));
                 }
@@ -120,15 +418,43 @@ fn find_analyzer_for_path(
     best_match.map(|(_, analyzer_name)| analyzer_name.clone())
 }
 
+/// Stable text form of the pricing-related config sections, used to skip
+/// reloads when the config file changed for unrelated reasons.
+fn pricing_fingerprint(config: &Config) -> String {
+    let models: std::collections::BTreeMap<_, _> = config
+        .models
+        .iter()
+        .map(|(name, info)| (name, format!("{info:?}")))
+        .collect();
+    let aliases: std::collections::BTreeMap<_, _> = config.aliases.iter().collect();
+    let pricing: std::collections::BTreeMap<_, _> = config.pricing.iter().collect();
+    format!("{models:?}{aliases:?}{pricing:?}{:?}", config.costs.source)
+}
+
+/// The session most recently written to, as seen by the file watcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveSession {
+    pub analyzer_name: String,
+    pub session_id: String,
+    pub updated_at: Instant,
+}
+
 pub struct RealtimeStatsManager {
-    registry: AnalyzerRegistry,
+    registry: Arc<AnalyzerRegistry>,
     update_tx: watch::Sender<MultiAnalyzerStatsView>,
     update_rx: watch::Receiver<MultiAnalyzerStatsView>,
+    live_tx: watch::Sender<Option<LiveSession>>,
     last_upload_time: Option<Instant>,
     upload_debounce: Duration,
     upload_status: Option<Arc<Mutex<UploadStatus>>>,
     upload_in_progress: Arc<Mutex<bool>>,
     pending_upload: Arc<Mutex<bool>>,
+    /// Snapshot of the `[models]`/`[aliases]`/`[pricing]` config last applied to the pricing registry.
+    pricing_fingerprint: Option<String>,
+    /// Analyzers the background load started by [`Self::start`] hasn't finished.
+    loading: Arc<Mutex<Vec<String>>>,
+    /// Resolves once that load is done; watcher events wait for it.
+    initial_load: Option<tokio::sync::oneshot::Receiver<()>>,
 }
 
 impl RealtimeStatsManager {
@@ -138,17 +464,81 @@ impl RealtimeStatsManager {
         // Initial stats load using rayon parallel I/O.
         let initial_stats = registry.load_all_stats_views_parallel()?;
         let (update_tx, update_rx) = watch::channel(initial_stats);
+        Ok(Self::with_channel(Arc::new(registry), update_tx, update_rx))
+    }
 
-        Ok(Self {
+    /// Create a stats manager that loads in the background, publishing each
+    /// analyzer's stats as soon as they're ready so the fastest tabs appear
+    /// first. Until then the published stats list the analyzers still loading.
+    pub fn start(registry: AnalyzerRegistry) -> Self {
+        let registry = Arc::new(registry);
+        let pending: Vec<String> = registry
+            .display_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let (update_tx, update_rx) = watch::channel(MultiAnalyzerStatsView {
+            analyzer_stats: Vec::new(),
+            loading: pending.clone(),
+            versions: Vec::new(),
+        });
+        let mut manager = Self::with_channel(Arc::clone(&registry), update_tx.clone(), update_rx);
+        *manager.loading.lock() = pending;
+        let loading = Arc::clone(&manager.loading);
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        manager.initial_load = Some(done_rx);
+
+        std::thread::spawn(move || {
+            let publish = |loading: &[String]| {
+                let _ = update_tx.send(registry.stats_view(loading.to_vec()));
+            };
+            let store = crate::store::StoreWriter::spawn();
+            match rayon::ThreadPoolBuilder::new().build() {
+                Ok(pool) => pool.install(|| {
+                    registry.load_all_stats_views_streaming(store.as_ref(), |name| {
+                        let mut loading = loading.lock();
+                        loading.retain(|pending| pending != name);
+                        publish(&loading);
+                    })
+                }),
+                Err(e) => {
+                    eprintln!("Error loading analyzer stats: {e}");
+                    loading.lock().clear();
+                    publish(&[]);
+                }
+            }
+            if let Some(store) = store {
+                store.finish();
+            }
+            // Pool is dropped above; release memory from parallel parsing too.
+            crate::release_unused_memory();
+            let _ = done_tx.send(());
+        });
+        manager
+    }
+
+    fn with_channel(
+        registry: Arc<AnalyzerRegistry>,
+        update_tx: watch::Sender<MultiAnalyzerStatsView>,
+        update_rx: watch::Receiver<MultiAnalyzerStatsView>,
+    ) -> Self {
+        Self {
             registry,
             update_tx,
             update_rx,
+            live_tx: watch::Sender::new(None),
             last_upload_time: None,
             upload_debounce: Duration::from_secs(3), // Wait 3 seconds after changes before uploading
             upload_status: None,
             upload_in_progress: Arc::new(Mutex::new(false)),
             pending_upload: Arc::new(Mutex::new(false)),
-        })
+            pricing_fingerprint: Config::load()
+                .ok()
+                .flatten()
+                .map(|c| pricing_fingerprint(&c)),
+            loading: Arc::new(Mutex::new(Vec::new())),
+            initial_load: None,
+        }
     }
 
     pub fn set_upload_status(&mut self, status: Arc<Mutex<UploadStatus>>) {
@@ -156,6 +546,10 @@ impl RealtimeStatsManager {
     }
 
     /// Persist the file stats cache to disk (no-op since caching was removed)
+    ///
+    /// Contributions are only kept in memory, so the next start re-parses
+    /// every source however this run ends. Only the status snapshot is
+    /// saved, by [`crate::statusline::SnapshotPersister`].
     pub fn persist_cache(&self) {
         // Caching has been removed - this is kept for API compatibility
     }
@@ -164,7 +558,23 @@ impl RealtimeStatsManager {
         self.update_rx.clone()
     }
 
+    /// The registry behind the published stats, for on-demand parsing that
+    /// can use its contribution cache.
+    pub fn registry(&self) -> Arc<AnalyzerRegistry> {
+        Arc::clone(&self.registry)
+    }
+
+    /// Follows the session an agent is actively writing to. Only incremental
+    /// reloads report one; full reloads can't tell which session changed.
+    pub fn get_live_session_receiver(&self) -> watch::Receiver<Option<LiveSession>> {
+        self.live_tx.subscribe()
+    }
+
     pub async fn handle_watcher_event(&mut self, event: WatcherEvent) -> Result<()> {
+        // Changes seen during startup are applied on top of the loaded stats.
+        if let Some(initial_load) = self.initial_load.take() {
+            let _ = initial_load.await;
+        }
         match event {
             WatcherEvent::FileChanged(analyzer_name, path) => {
                 if self
@@ -198,6 +608,48 @@ impl RealtimeStatsManager {
                     self.reload_analyzer_stats(&analyzer_name).await;
                 }
             }
+            WatcherEvent::DirectoryCreated(analyzer_name, dir) => {
+                let sources = self.registry.discover_sources_under(&analyzer_name, &dir);
+                if sources.is_empty() {
+                    return Ok(());
+                }
+                if self
+                    .registry
+                    .requires_full_reload_for_source_change(&analyzer_name)
+                    || !self.registry.has_cached_contributions(&analyzer_name)
+                {
+                    for source in &sources {
+                        self.registry.mark_file_dirty(&analyzer_name, &source.path);
+                    }
+                    self.reload_analyzer_stats(&analyzer_name).await;
+                } else {
+                    for source in sources {
+                        self.reload_single_file_incremental(&analyzer_name, &source.path)
+                            .await;
+                    }
+                }
+            }
+            WatcherEvent::ConfigChanged => {
+                // A half-written or invalid config keeps the current settings.
+                if let Ok(Some(config)) = Config::load() {
+                    self.reload_exclude_rules(&config).await;
+                    self.reload_pricing(config).await;
+                }
+            }
+            WatcherEvent::Resync => match self.registry.reload_all_stats_views_scoped() {
+                Ok(_) => self.apply_view_update(None).await,
+                Err(e) => eprintln!("Error reloading stats after watcher restart: {e}"),
+            },
+            WatcherEvent::Rescan(Some(analyzer_name)) => {
+                match self.registry.rescan_analyzer(&analyzer_name) {
+                    Ok(_) => self.apply_view_update(None).await,
+                    Err(e) => eprintln!("Error rescanning {analyzer_name}: {e}"),
+                }
+            }
+            WatcherEvent::Rescan(None) => match self.registry.reload_all_stats_views_scoped() {
+                Ok(_) => self.apply_view_update(None).await,
+                Err(e) => eprintln!("Error rescanning: {e}"),
+            },
             WatcherEvent::Error(err) => {
                 eprintln!("File watcher error: {err}");
             }
@@ -206,6 +658,48 @@ impl RealtimeStatsManager {
         Ok(())
     }
 
+    /// Re-apply pricing overrides from the config file if they changed, then
+    /// re-cost today's data so corrections show up without a restart.
+    /// Apply edited `[exclude] paths`, reloading every analyzer so newly
+    /// excluded sessions drop out and no-longer-excluded ones come back.
+    async fn reload_exclude_rules(&mut self, config: &Config) {
+        let rules = crate::config::ExcludeRules::from_config(&config.exclude);
+        if !crate::config::set_exclude_rules(rules) {
+            return;
+        }
+        match self.registry.reload_all_stats_views_scoped() {
+            Ok(_) => self.apply_view_update(None).await,
+            Err(e) => eprintln!("Error reloading stats after [exclude] change: {e}"),
+        }
+    }
+
+    async fn reload_pricing(&mut self, config: Config) {
+        let fingerprint = pricing_fingerprint(&config);
+        if self.pricing_fingerprint.as_ref() == Some(&fingerprint) {
+            return;
+        }
+        self.pricing_fingerprint = Some(fingerprint);
+
+        // Rates the cached costs were computed with, to re-price by the difference.
+        let before = crate::models::PricingSnapshot::take(
+            self.registry.cached_models().iter().map(String::as_str),
+        );
+        crate::models::set_cost_source(config.costs.source);
+        crate::models::reload_external_models(config.models, config.aliases, &config.pricing);
+        self.recost_today(&before).await;
+    }
+
+    /// Re-price today's usage, re-parsing only the files whose cached
+    /// contributions can't be re-priced in memory; see
+    /// [`AnalyzerRegistry::reprice_day`]. Earlier days keep the prices they
+    /// were loaded with until the next restart.
+    async fn recost_today(&mut self, before: &crate::models::PricingSnapshot) {
+        let today = CompactDate::from_local(&chrono::Local::now());
+        if self.registry.reprice_day(today, before) {
+            self.apply_view_update(None).await;
+        }
+    }
+
     /// Helper to reload stats for a specific analyzer and broadcast updates (fallback)
     async fn reload_analyzer_stats(&mut self, analyzer_name: &str) {
         if let Some(analyzer) = self.registry.get_analyzer_by_display_name(analyzer_name) {
@@ -231,7 +725,14 @@ impl RealtimeStatsManager {
     async fn reload_single_file_incremental(&mut self, analyzer_name: &str, path: &Path) {
         // True incremental update - subtract old, add new (sync, no threadpool for single file)
         match self.registry.reload_file_incremental(analyzer_name, path) {
-            Ok(()) => {
+            Ok(active_session) => {
+                if let Some(session_id) = active_session {
+                    self.live_tx.send_replace(Some(LiveSession {
+                        analyzer_name: analyzer_name.to_string(),
+                        session_id,
+                        updated_at: Instant::now(),
+                    }));
+                }
                 self.apply_view_update(None).await;
             }
             Err(e) => {
@@ -249,9 +750,7 @@ impl RealtimeStatsManager {
         full_reload_messages: Option<(String, Vec<crate::types::ConversationMessage>)>,
     ) {
         // Build fresh MultiAnalyzerStatsView from cache - just clones Arc pointers
-        let stats = MultiAnalyzerStatsView {
-            analyzer_stats: self.registry.get_all_cached_views(),
-        };
+        let stats = self.registry.stats_view(self.loading.lock().clone());
 
         // Send the update
         let _ = self.update_tx.send(stats);
@@ -291,13 +790,30 @@ impl RealtimeStatsManager {
 
         self.last_upload_time = Some(now);
 
+        // Another process is uploading; try again on the next change.
+        let upload_lock = match upload::lock::UploadLock::try_acquire() {
+            Ok(Some(lock)) => lock,
+            _ => {
+                *self.pending_upload.lock() = true;
+                return;
+            }
+        };
+
         // Mark upload as in progress
         *self.upload_in_progress.lock() = true;
 
-        let last_date_uploaded = match UploadState::load() {
-            Ok(state) => state.last_date_uploaded,
-            Err(e) => {
-                eprintln!("Failed to load upload state: {e:#}");
+        // The TUI owns the terminal, so problems go to the status bar.
+        let watermark = match UploadState::load() {
+            Ok(state) if state.watermark_lost => Err(crate::upload::WATERMARK_LOST.to_string()),
+            Ok(state) => Ok(state.last_date_uploaded),
+            Err(e) => Err(format!("Failed to load upload state: {e:#}")),
+        };
+        let last_date_uploaded = match watermark {
+            Ok(last_date_uploaded) => last_date_uploaded,
+            Err(message) => {
+                if let Some(status) = &self.upload_status {
+                    *status.lock() = UploadStatus::Failed(message);
+                }
                 *self.upload_in_progress.lock() = false;
                 return;
             }
@@ -328,6 +844,7 @@ impl RealtimeStatsManager {
 
         // Spawn background upload task with only the messages to upload
         tokio::spawn(async move {
+            let _upload_lock = upload_lock;
             upload::perform_background_upload_messages(
                 messages,
                 upload_status,
@@ -372,6 +889,7 @@ mod tests {
             role: MessageRole::Assistant,
             uuid: None,
             session_name: Some("session".into()),
+            stable_session_id: None,
         };
 
         AgenticCodingToolStats {
@@ -423,6 +941,81 @@ mod tests {
         }
     }
 
+    /// Reads one assistant message per line of `path`, each line giving its
+    /// minute offset, so a test can append to the session like an agent does.
+    struct FileAnalyzer {
+        path: PathBuf,
+    }
+
+    impl FileAnalyzer {
+        fn messages(&self) -> Result<Vec<ConversationMessage>> {
+            let start = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+            let content = std::fs::read_to_string(&self.path)?;
+            Ok(content
+                .lines()
+                .map(|line| ConversationMessage {
+                    application: Application::ClaudeCode,
+                    date: start + chrono::Duration::minutes(line.trim().parse().unwrap()),
+                    project_hash: "proj".into(),
+                    conversation_hash: "conv".into(),
+                    local_hash: None,
+                    global_hash: format!("global-{line}"),
+                    model: Some("claude-sonnet-4".into()),
+                    stats: Stats {
+                        input_tokens: 10,
+                        output_tokens: 10,
+                        cache_creation_tokens: 10_000,
+                        cache_read_tokens: 100_000,
+                        ..Stats::default()
+                    },
+                    role: MessageRole::Assistant,
+                    uuid: None,
+                    session_name: Some("session".into()),
+                    stable_session_id: None,
+                })
+                .collect())
+        }
+    }
+
+    #[async_trait]
+    impl Analyzer for FileAnalyzer {
+        fn display_name(&self) -> &'static str {
+            "file-analyzer"
+        }
+
+        fn get_data_glob_patterns(&self) -> Vec<String> {
+            vec![]
+        }
+
+        fn discover_data_sources(&self) -> Result<Vec<DataSource>> {
+            Ok(vec![DataSource {
+                path: self.path.clone(),
+            }])
+        }
+
+        fn parse_source(&self, _source: &DataSource) -> Result<Vec<ConversationMessage>> {
+            self.messages()
+        }
+
+        fn get_stats(&self) -> Result<AgenticCodingToolStats> {
+            let messages = self.messages()?;
+            Ok(AgenticCodingToolStats {
+                daily_stats: crate::utils::aggregate_by_date(&messages),
+                num_conversations: 1,
+                messages,
+                analyzer_name: "file-analyzer".to_string(),
+            })
+        }
+
+        fn get_watch_directories(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+
+        fn contribution_strategy(&self) -> ContributionStrategy {
+            ContributionStrategy::SingleSession
+        }
+    }
+
     #[test]
     fn find_analyzer_prefers_more_specific_directory() {
         let mut mapping = HashMap::new();
@@ -445,7 +1038,7 @@ mod tests {
             NotifyEvent::new(NotifyEventKind::Create(CreateKind::File)).add_path(file_path.clone());
 
         let (tx, rx) = mpsc::channel();
-        handle_fs_event(event, &tx, &mapping).expect("handle_fs_event");
+        handle_fs_event(event, &tx, &mapping, None).expect("handle_fs_event");
 
         let evt = rx.try_recv().expect("event");
         match evt {
@@ -457,6 +1050,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn handle_fs_event_emits_directory_created_for_new_project_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut mapping = HashMap::new();
+        mapping.insert(temp.path().to_path_buf(), "analyzer".to_string());
+        let project = temp.path().join("-home-user-new-repo");
+        std::fs::create_dir(&project).unwrap();
+
+        let event =
+            NotifyEvent::new(NotifyEventKind::Create(CreateKind::Folder)).add_path(project.clone());
+        let (tx, rx) = mpsc::channel();
+        handle_fs_event(event, &tx, &mapping, None).expect("handle_fs_event");
+
+        match rx.try_recv().expect("event") {
+            WatcherEvent::DirectoryCreated(name, path) => {
+                assert_eq!(name, "analyzer");
+                assert_eq!(path, project);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn handle_fs_event_emits_config_changed_for_config_file() {
+        let mut mapping = HashMap::new();
+        mapping.insert(PathBuf::from("/tmp/project"), "analyzer".to_string());
+        let config_path = PathBuf::from("/tmp/home/.splitrail.toml");
+
+        let event = NotifyEvent::new(NotifyEventKind::Create(CreateKind::File))
+            .add_path(config_path.clone())
+            .add_path(PathBuf::from("/tmp/home/.bashrc"));
+
+        let (tx, rx) = mpsc::channel();
+        handle_fs_event(event, &tx, &mapping, Some(&config_path)).expect("handle_fs_event");
+
+        assert!(matches!(rx.try_recv(), Ok(WatcherEvent::ConfigChanged)));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn pricing_fingerprint_ignores_unrelated_config_changes() {
+        let mut config = Config::default();
+        let before = pricing_fingerprint(&config);
+
+        config.tui.accent_color = "green".into();
+        config.upload.auto_upload = true;
+        assert_eq!(pricing_fingerprint(&config), before);
+
+        config
+            .aliases
+            .insert("my-model".into(), "claude-sonnet-4".into());
+        assert_ne!(pricing_fingerprint(&config), before);
+
+        let before = pricing_fingerprint(&config);
+        config.pricing.insert(
+            "my-model".into(),
+            crate::models::PriceOverride {
+                input_per_1m: Some(1.0),
+                ..Default::default()
+            },
+        );
+        assert_ne!(pricing_fingerprint(&config), before);
+    }
+
     #[tokio::test]
     async fn handle_watcher_event_updates_stats_for_data_change() {
         let stats = sample_stats("test-analyzer");
@@ -511,7 +1168,7 @@ mod tests {
             NotifyEvent::new(NotifyEventKind::Remove(RemoveKind::File)).add_path(file_path.clone());
 
         let (tx, rx) = mpsc::channel();
-        handle_fs_event(event, &tx, &mapping).expect("handle_fs_event");
+        handle_fs_event(event, &tx, &mapping, None).expect("handle_fs_event");
 
         let evt = rx.try_recv().expect("event");
         match evt {
@@ -577,6 +1234,41 @@ mod tests {
         assert!(updated.analyzer_stats.is_empty() || !updated.analyzer_stats.is_empty());
     }
 
+    #[tokio::test]
+    async fn file_changed_event_updates_prompt_cache_stats() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("session.jsonl");
+        std::fs::write(&path, "0\n1\n").unwrap();
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(FileAnalyzer { path: path.clone() });
+        let mut manager = RealtimeStatsManager::new(registry).expect("manager");
+
+        let prompt_cache = |manager: &RealtimeStatsManager| {
+            let stats = manager.get_stats_receiver().borrow().clone();
+            let view = stats.analyzer_stats[0].read();
+            let mut total = crate::types::PromptCacheStats::default();
+            for day in view.daily_stats.values() {
+                total += day.prompt_cache;
+            }
+            total
+        };
+        let before = prompt_cache(&manager);
+        assert!(before.saved_cost > 0.0);
+        assert_eq!(before.expiry_misses, 0);
+
+        // Ten minutes later the cache has expired and gets written again.
+        std::fs::write(&path, "0\n1\n11\n").unwrap();
+        manager
+            .handle_watcher_event(WatcherEvent::FileChanged("file-analyzer".into(), path))
+            .await
+            .expect("handle FileChanged");
+
+        let after = prompt_cache(&manager);
+        assert!(after.saved_cost > before.saved_cost);
+        assert_eq!(after.expiry_misses, 1);
+        assert_eq!(after.expiry_tokens, 10_000);
+    }
+
     #[tokio::test]
     async fn persist_cache_does_not_panic() {
         let stats = sample_stats("test-analyzer");
@@ -592,4 +1284,87 @@ mod tests {
         // persist_cache should not panic even if cache is empty
         manager.persist_cache();
     }
+
+    #[test]
+    fn changed_directory_detects_removed_and_recreated_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("sessions");
+        std::fs::create_dir(&dir).unwrap();
+        let missing = temp.path().join("not-yet");
+
+        let snapshot = snapshot_identities([&dir, &missing]);
+        assert_eq!(changed_directory(&snapshot), None);
+
+        std::fs::remove_dir(&dir).unwrap();
+        assert_eq!(changed_directory(&snapshot), Some(dir.as_path()));
+
+        // A directory that didn't exist at startup appearing counts too.
+        let snapshot = snapshot_identities([&missing]);
+        std::fs::create_dir(&missing).unwrap();
+        assert_eq!(changed_directory(&snapshot), Some(missing.as_path()));
+    }
+
+    #[test]
+    fn stamp_changes_reports_modified_created_and_removed_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let kept = temp.path().join("kept.jsonl");
+        let grown = temp.path().join("grown.jsonl");
+        let removed = temp.path().join("removed.jsonl");
+        for path in [&kept, &grown, &removed] {
+            std::fs::write(path, "{}\n").unwrap();
+        }
+        let roots = vec![temp.path().to_path_buf()];
+        let before = stamp_files(&roots);
+
+        // Same mtime granularity as a coarse network mount: only the size moves.
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&grown)
+            .unwrap()
+            .set_len(10)
+            .unwrap();
+        std::fs::remove_file(&removed).unwrap();
+        let created = temp.path().join("created.jsonl");
+        std::fs::write(&created, "{}\n").unwrap();
+
+        let mut changes: Vec<(bool, PathBuf)> = stamp_changes(&before, &stamp_files(&roots))
+            .into_iter()
+            .map(|event| (event.kind.is_remove(), event.paths[0].clone()))
+            .collect();
+        changes.sort();
+        assert_eq!(
+            changes,
+            vec![(false, created), (false, grown), (true, removed)]
+        );
+    }
+
+    #[test]
+    fn polling_watcher_reports_new_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("sessions");
+        std::fs::create_dir(&dir).unwrap();
+        let mut mapping = HashMap::new();
+        mapping.insert(dir.clone(), "analyzer".to_string());
+
+        let watcher =
+            FileWatcher::with_mapping(mapping, None, Some(Duration::from_millis(20))).unwrap();
+        let file_path = dir.join("session.jsonl");
+        std::fs::write(&file_path, "{}\n").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let event = loop {
+            if let Some(event) = watcher.try_recv() {
+                break event;
+            }
+            assert!(Instant::now() < deadline, "no event from the poller");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        match event {
+            WatcherEvent::FileChanged(name, path) => {
+                assert_eq!(name, "analyzer");
+                assert_eq!(path, file_path);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
 }